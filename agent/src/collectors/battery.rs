@@ -0,0 +1,223 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::sysfs::{read_trimmed, read_u64};
+
+/// Battery or UPS status.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BatteryInfo {
+    pub name: String,
+    /// Status as reported by the device, e.g. "Charging", "Discharging", "Full".
+    pub status: String,
+    pub level_pct: Option<u8>,
+    pub charging: bool,
+    /// Estimated runtime left; only known while discharging.
+    pub time_remaining_secs: Option<u64>,
+}
+
+const POWER_SUPPLY_CLASS_DIR: &str = "/sys/class/power_supply";
+const UPS_NAME: &str = "ups@localhost";
+
+/// Divide a remaining charge (µAh or µWh) by the present draw (µA or µW).
+fn remaining_secs(now: Option<u64>, rate: Option<u64>) -> Option<u64> {
+    match (now, rate) {
+        (Some(now), Some(rate)) if rate > 0 => Some(now * 3600 / rate),
+        _ => None,
+    }
+}
+
+/// Read a single `BAT*` power supply directory.
+fn read_battery(name: String, path: &Path) -> BatteryInfo {
+    let status = read_trimmed(&path.join("status")).unwrap_or_else(|| "Unknown".to_string());
+
+    let level_pct = read_u64(&path.join("capacity"))
+        .or_else(|| {
+            let now = read_u64(&path.join("charge_now"))?;
+            let full = read_u64(&path.join("charge_full"))?;
+            (full > 0).then(|| now * 100 / full)
+        })
+        .map(|pct| pct.min(100) as u8);
+
+    let time_remaining_secs = if status == "Discharging" {
+        remaining_secs(
+            read_u64(&path.join("charge_now")),
+            read_u64(&path.join("current_now")),
+        )
+        .or_else(|| {
+            remaining_secs(
+                read_u64(&path.join("energy_now")),
+                read_u64(&path.join("power_now")),
+            )
+        })
+    } else {
+        None
+    };
+
+    BatteryInfo {
+        name,
+        charging: status == "Charging",
+        status,
+        level_pct,
+        time_remaining_secs,
+    }
+}
+
+/// Read every `BAT*` entry under a power_supply class directory.
+pub fn read_power_supplies(power_supply_dir: &Path) -> Vec<BatteryInfo> {
+    let Ok(entries) = fs::read_dir(power_supply_dir) else {
+        return Vec::new();
+    };
+    let mut batteries: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            name.starts_with("BAT").then(|| (name, entry.path()))
+        })
+        .collect();
+    batteries.sort();
+    batteries
+        .into_iter()
+        .map(|(name, path)| read_battery(name, &path))
+        .collect()
+}
+
+/// Parse `upsc <ups>` output (`key: value` lines) into a battery entry.
+///
+/// `ups.status` holds NUT flags such as `OL` (online), `OB` (on battery)
+/// and `CHRG` (charging).
+pub fn parse_upsc_output(name: &str, output: &str) -> Option<BatteryInfo> {
+    let value = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.split_once(':')?;
+            (k.trim() == key).then(|| v.trim())
+        })
+    };
+
+    let flags: Vec<&str> = value("ups.status")?.split_whitespace().collect();
+    let on_battery = flags.contains(&"OB");
+    let charging = flags.contains(&"CHRG");
+    let status = if on_battery {
+        "Discharging"
+    } else if charging {
+        "Charging"
+    } else if flags.contains(&"OL") {
+        "Online"
+    } else {
+        "Unknown"
+    };
+
+    let level_pct = value("battery.charge")
+        .and_then(|v| v.parse::<f64>().ok())
+        .map(|pct| pct.clamp(0.0, 100.0) as u8);
+    let time_remaining_secs = if on_battery {
+        value("battery.runtime")
+            .and_then(|v| v.parse::<f64>().ok())
+            .map(|secs| secs.max(0.0) as u64)
+    } else {
+        None
+    };
+
+    Some(BatteryInfo {
+        name: name.to_string(),
+        status: status.to_string(),
+        level_pct,
+        charging,
+        time_remaining_secs,
+    })
+}
+
+/// Query a local NUT UPS via `upsc`, if the tool is installed.
+fn collect_ups() -> Option<BatteryInfo> {
+    let output = Command::new("upsc").arg(UPS_NAME).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_upsc_output(UPS_NAME, &String::from_utf8_lossy(&output.stdout))
+}
+
+/// Collect laptop batteries from sysfs and a local UPS via NUT.
+///
+/// Hosts with neither report an empty list.
+pub fn collect() -> Vec<BatteryInfo> {
+    let mut batteries = read_power_supplies(Path::new(POWER_SUPPLY_CLASS_DIR));
+    batteries.extend(collect_ups());
+    batteries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(tag: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("panoptikon-battery-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn test_read_power_supplies() {
+        let root = temp_root("sysfs");
+        fs::create_dir_all(root.join("BAT0")).unwrap();
+        fs::create_dir_all(root.join("BAT1")).unwrap();
+        fs::create_dir_all(root.join("AC")).unwrap();
+        fs::write(root.join("BAT0/status"), "Discharging\n").unwrap();
+        fs::write(root.join("BAT0/capacity"), "75\n").unwrap();
+        fs::write(root.join("BAT0/charge_now"), "3000000\n").unwrap();
+        fs::write(root.join("BAT0/current_now"), "1500000\n").unwrap();
+        fs::write(root.join("BAT1/status"), "Charging\n").unwrap();
+        fs::write(root.join("BAT1/charge_now"), "1000000\n").unwrap();
+        fs::write(root.join("BAT1/charge_full"), "4000000\n").unwrap();
+        fs::write(root.join("AC/online"), "1\n").unwrap();
+
+        let batteries = read_power_supplies(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            batteries,
+            vec![
+                BatteryInfo {
+                    name: "BAT0".to_string(),
+                    status: "Discharging".to_string(),
+                    level_pct: Some(75),
+                    charging: false,
+                    time_remaining_secs: Some(7200),
+                },
+                BatteryInfo {
+                    name: "BAT1".to_string(),
+                    status: "Charging".to_string(),
+                    level_pct: Some(25),
+                    charging: true,
+                    time_remaining_secs: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_power_supplies_missing_dir() {
+        let root = temp_root("missing");
+        assert!(read_power_supplies(&root).is_empty());
+    }
+
+    #[test]
+    fn test_parse_upsc_output() {
+        let output = "battery.charge: 87\nbattery.runtime: 1260\nups.status: OB DISCHRG\n";
+        let ups = parse_upsc_output("ups@localhost", output).unwrap();
+        assert_eq!(ups.status, "Discharging");
+        assert_eq!(ups.level_pct, Some(87));
+        assert!(!ups.charging);
+        assert_eq!(ups.time_remaining_secs, Some(1260));
+
+        let online =
+            parse_upsc_output("ups@localhost", "battery.charge: 99\nups.status: OL CHRG\n")
+                .unwrap();
+        assert_eq!(online.status, "Charging");
+        assert!(online.charging);
+        assert_eq!(online.time_remaining_secs, None);
+
+        assert!(parse_upsc_output("ups@localhost", "Error: Data stale\n").is_none());
+    }
+}
@@ -1,21 +1,129 @@
 use serde::Serialize;
+use std::path::Path;
 use sysinfo::System;
 
+use super::sysfs::read_trimmed;
+
+/// cpufreq attributes of the first core; absent in most VMs and on non-Linux.
+const CPUFREQ_DIR: &str = "/sys/devices/system/cpu/cpu0/cpufreq";
+
 /// CPU usage information.
 #[derive(Debug, Serialize)]
 pub struct CpuInfo {
     pub count: usize,
     pub usage_percent: f32,
     pub load_avg: [f64; 3],
+    /// Per-core usage and frequency.
+    pub cores: Vec<CpuCoreInfo>,
+    /// cpufreq scaling governor of cpu0 (e.g. `performance`, `powersave`), lowercase.
+    pub scaling_governor: Option<String>,
+    /// cpufreq current frequency of cpu0.
+    pub current_freq_mhz: Option<u64>,
+    /// cpufreq maximum (turbo) frequency of cpu0.
+    pub max_freq_mhz: Option<u64>,
+}
+
+/// Frequency scaling state read from a cpufreq directory.
+#[derive(Debug, Default, PartialEq)]
+pub struct CpuFreq {
+    pub scaling_governor: Option<String>,
+    pub current_freq_mhz: Option<u64>,
+    pub max_freq_mhz: Option<u64>,
+}
+
+/// Read a kHz attribute and convert it to MHz.
+fn read_khz_as_mhz(path: &Path) -> Option<u64> {
+    let khz: u64 = read_trimmed(path)?.parse().ok()?;
+    Some(khz / 1000)
+}
+
+/// Read the governor and frequencies from a cpufreq directory.
+pub fn read_cpufreq(dir: &Path) -> CpuFreq {
+    CpuFreq {
+        scaling_governor: read_trimmed(&dir.join("scaling_governor")).map(|g| g.to_lowercase()),
+        current_freq_mhz: read_khz_as_mhz(&dir.join("scaling_cur_freq")),
+        max_freq_mhz: read_khz_as_mhz(&dir.join("cpuinfo_max_freq")),
+    }
+}
+
+/// Usage and current frequency of a single logical core.
+#[derive(Debug, Serialize)]
+pub struct CpuCoreInfo {
+    pub core_id: usize,
+    pub usage_pct: f64,
+    pub frequency_mhz: u64,
 }
 
 /// Collect CPU metrics.
 pub fn collect(sys: &System) -> CpuInfo {
     let load = sysinfo::System::load_average();
 
+    let cores = sys
+        .cpus()
+        .iter()
+        .enumerate()
+        .map(|(core_id, cpu)| CpuCoreInfo {
+            core_id,
+            usage_pct: f64::from(cpu.cpu_usage()).clamp(0.0, 100.0),
+            frequency_mhz: cpu.frequency(),
+        })
+        .collect();
+    let freq = read_cpufreq(Path::new(CPUFREQ_DIR));
+
     CpuInfo {
         count: sys.cpus().len(),
         usage_percent: sys.global_cpu_info().cpu_usage(),
         load_avg: [load.one, load.five, load.fifteen],
+        cores,
+        scaling_governor: freq.scaling_governor,
+        current_freq_mhz: freq.current_freq_mhz,
+        max_freq_mhz: freq.max_freq_mhz,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_collect_per_core() {
+        let mut sys = System::new();
+        sys.refresh_cpu();
+
+        let info = collect(&sys);
+
+        assert!(!info.cores.is_empty());
+        assert_eq!(info.cores.len(), info.count);
+        for (i, core) in info.cores.iter().enumerate() {
+            assert_eq!(core.core_id, i);
+            assert!((0.0..=100.0).contains(&core.usage_pct));
+        }
+    }
+
+    #[test]
+    fn test_read_cpufreq() {
+        let dir = std::env::temp_dir().join(format!("panoptikon-cpufreq-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("scaling_governor"), "Performance\n").unwrap();
+        fs::write(dir.join("scaling_cur_freq"), "3400123\n").unwrap();
+        fs::write(dir.join("cpuinfo_max_freq"), "4700000\n").unwrap();
+
+        let freq = read_cpufreq(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            freq,
+            CpuFreq {
+                scaling_governor: Some("performance".to_string()),
+                current_freq_mhz: Some(3400),
+                max_freq_mhz: Some(4700),
+            }
+        );
+        assert_eq!(
+            read_cpufreq(Path::new("/nonexistent/cpufreq")),
+            CpuFreq::default()
+        );
     }
 }
@@ -0,0 +1,344 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// Default Docker Engine API socket.
+const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+
+/// Upper bound for the whole collection; `stats?stream=false` samples CPU
+/// twice (~1 s) per container, so requests are issued concurrently.
+const COLLECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Resource usage of a single running container.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub cpu_pct: f64,
+    pub memory_usage_bytes: u64,
+    pub memory_limit_bytes: u64,
+    /// Bytes received across all container networks.
+    pub network_rx_bytes: u64,
+    /// Bytes transmitted across all container networks.
+    pub network_tx_bytes: u64,
+}
+
+/// Entry of `GET /containers/json`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerSummary {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuUsage {
+    #[serde(default)]
+    total_usage: u64,
+    #[serde(default)]
+    percpu_usage: Option<Vec<u64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CpuStats {
+    #[serde(default)]
+    cpu_usage: CpuUsage,
+    #[serde(default)]
+    system_cpu_usage: Option<u64>,
+    #[serde(default)]
+    online_cpus: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MemoryStats {
+    #[serde(default)]
+    usage: u64,
+    #[serde(default)]
+    limit: u64,
+    #[serde(default)]
+    stats: std::collections::HashMap<String, u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct NetworkStats {
+    #[serde(default)]
+    rx_bytes: u64,
+    #[serde(default)]
+    tx_bytes: u64,
+}
+
+/// Body of `GET /containers/:id/stats?stream=false`.
+#[derive(Debug, Default, Deserialize)]
+struct ContainerStats {
+    #[serde(default)]
+    cpu_stats: CpuStats,
+    #[serde(default)]
+    precpu_stats: CpuStats,
+    #[serde(default)]
+    memory_stats: MemoryStats,
+    #[serde(default)]
+    networks: std::collections::HashMap<String, NetworkStats>,
+}
+
+/// Compute CPU % the same way `docker stats` does: the container's share of
+/// host CPU time between the two samples, scaled by the number of CPUs.
+fn cpu_percent(stats: &ContainerStats) -> f64 {
+    let cpu_delta = stats
+        .cpu_stats
+        .cpu_usage
+        .total_usage
+        .saturating_sub(stats.precpu_stats.cpu_usage.total_usage);
+    let system_delta = stats
+        .cpu_stats
+        .system_cpu_usage
+        .unwrap_or(0)
+        .saturating_sub(stats.precpu_stats.system_cpu_usage.unwrap_or(0));
+    if cpu_delta == 0 || system_delta == 0 {
+        return 0.0;
+    }
+    let cpus = stats
+        .cpu_stats
+        .online_cpus
+        .map(u64::from)
+        .unwrap_or_else(|| {
+            stats
+                .cpu_stats
+                .cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map_or(1, |v| v.len().max(1) as u64)
+        });
+    cpu_delta as f64 / system_delta as f64 * cpus as f64 * 100.0
+}
+
+/// Memory in use excluding page cache (`inactive_file` on cgroup v2,
+/// `cache` on cgroup v1), matching `docker stats`.
+fn memory_usage(stats: &MemoryStats) -> u64 {
+    let cache = stats
+        .stats
+        .get("inactive_file")
+        .or_else(|| stats.stats.get("cache"))
+        .copied()
+        .unwrap_or(0);
+    stats.usage.saturating_sub(cache)
+}
+
+/// Build a [`ContainerInfo`] from a container summary and its stats body.
+fn to_container_info(summary: &ContainerSummary, stats: &ContainerStats) -> ContainerInfo {
+    let name = summary
+        .names
+        .first()
+        .map(|n| n.trim_start_matches('/').to_string())
+        .unwrap_or_else(|| summary.id.chars().take(12).collect());
+    ContainerInfo {
+        id: summary.id.clone(),
+        name,
+        status: summary.state.clone(),
+        cpu_pct: cpu_percent(stats),
+        memory_usage_bytes: memory_usage(&stats.memory_stats),
+        memory_limit_bytes: stats.memory_stats.limit,
+        network_rx_bytes: stats.networks.values().map(|n| n.rx_bytes).sum(),
+        network_tx_bytes: stats.networks.values().map(|n| n.tx_bytes).sum(),
+    }
+}
+
+/// Decode a `Transfer-Encoding: chunked` body.
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_str = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size_str = size_str.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_str, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Split a raw HTTP/1.1 response into its body, returning `None` for
+/// non-2xx statuses or malformed responses.
+fn parse_http_response(raw: &[u8]) -> Option<Vec<u8>> {
+    let header_end = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..header_end]).ok()?;
+    let body = &raw[header_end + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status: u16 = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    if !(200..300).contains(&status) {
+        return None;
+    }
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    if chunked {
+        dechunk(body)
+    } else {
+        Some(body.to_vec())
+    }
+}
+
+/// Issue a GET against the Docker socket and return the response body.
+async fn get(socket: &Path, path: &str) -> Option<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket).await.ok()?;
+    let request = format!("GET {path} HTTP/1.1\r\nHost: docker\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.ok()?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await.ok()?;
+    parse_http_response(&raw)
+}
+
+/// Collect container metrics from a Docker-compatible API socket.
+async fn collect_from(socket: &Path) -> Vec<ContainerInfo> {
+    let Some(body) = get(socket, "/containers/json").await else {
+        return Vec::new();
+    };
+    let summaries: Vec<ContainerSummary> = serde_json::from_slice(&body).unwrap_or_default();
+
+    let requests = summaries.iter().map(|summary| async move {
+        let path = format!("/containers/{}/stats?stream=false", summary.id);
+        let stats = get(socket, &path)
+            .await
+            .and_then(|body| serde_json::from_slice::<ContainerStats>(&body).ok())
+            .unwrap_or_default();
+        to_container_info(summary, &stats)
+    });
+    futures_util::future::join_all(requests).await
+}
+
+/// Collect metrics for running containers.
+///
+/// Hosts without Docker, or agents lacking permission to open the socket,
+/// report an empty list; this never fails.
+pub async fn collect() -> Vec<ContainerInfo> {
+    let socket = Path::new(DOCKER_SOCKET);
+    if !socket.exists() {
+        return Vec::new();
+    }
+    tokio::time::timeout(COLLECT_TIMEOUT, collect_from(socket))
+        .await
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    const STATS_FIXTURE: &str = r#"{
+        "cpu_stats": {
+            "cpu_usage": {"total_usage": 400000000},
+            "system_cpu_usage": 20000000000,
+            "online_cpus": 4
+        },
+        "precpu_stats": {
+            "cpu_usage": {"total_usage": 200000000},
+            "system_cpu_usage": 18000000000
+        },
+        "memory_stats": {
+            "usage": 104857600,
+            "limit": 2147483648,
+            "stats": {"inactive_file": 4857600}
+        },
+        "networks": {
+            "eth0": {"rx_bytes": 1000, "tx_bytes": 200},
+            "eth1": {"rx_bytes": 24, "tx_bytes": 6}
+        }
+    }"#;
+
+    fn summary() -> ContainerSummary {
+        ContainerSummary {
+            id: "0123456789abcdef0123".to_string(),
+            names: vec!["/nginx".to_string()],
+            state: "running".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_container_info() {
+        let stats: ContainerStats = serde_json::from_str(STATS_FIXTURE).unwrap();
+        let info = to_container_info(&summary(), &stats);
+
+        assert_eq!(info.name, "nginx");
+        assert_eq!(info.status, "running");
+        assert!((info.cpu_pct - 40.0).abs() < 1e-9);
+        assert_eq!(info.memory_usage_bytes, 100_000_000);
+        assert_eq!(info.memory_limit_bytes, 2_147_483_648);
+        assert_eq!(info.network_rx_bytes, 1024);
+        assert_eq!(info.network_tx_bytes, 206);
+    }
+
+    #[test]
+    fn test_cpu_percent_without_previous_sample() {
+        let stats: ContainerStats =
+            serde_json::from_str(r#"{"cpu_stats": {"cpu_usage": {"total_usage": 5}}}"#).unwrap();
+        assert_eq!(cpu_percent(&stats), 0.0);
+    }
+
+    #[test]
+    fn test_parse_http_response_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                    4\r\n[{}]\r\n1\r\n \r\n0\r\n\r\n";
+        assert_eq!(parse_http_response(raw).unwrap(), b"[{}] ");
+    }
+
+    #[test]
+    fn test_parse_http_response_error_status() {
+        let raw = b"HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}";
+        assert!(parse_http_response(raw).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_collect_from_missing_socket() {
+        assert!(collect_from(Path::new("/nonexistent/docker.sock"))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_collect_from_fake_daemon() {
+        let socket =
+            std::env::temp_dir().join(format!("panoptikon-docker-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).into_owned();
+                let body = if request.starts_with("GET /containers/json ") {
+                    r#"[{"Id":"abc","Names":["/db"],"State":"running"}]"#
+                } else {
+                    STATS_FIXTURE
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let containers = collect_from(&socket).await;
+        std::fs::remove_file(&socket).unwrap();
+
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].id, "abc");
+        assert_eq!(containers[0].name, "db");
+        assert_eq!(containers[0].memory_limit_bytes, 2_147_483_648);
+    }
+}
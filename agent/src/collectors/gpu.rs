@@ -0,0 +1,187 @@
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::sysfs::{read_trimmed, read_u64};
+
+/// Utilisation and VRAM usage of a single GPU.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GpuInfo {
+    pub name: String,
+    pub utilization_pct: f64,
+    pub vram_used_mb: u64,
+    pub vram_total_mb: u64,
+}
+
+/// Root of the DRM class directory exposed by amdgpu (and other DRM drivers).
+const DRM_CLASS_DIR: &str = "/sys/class/drm";
+
+/// Upper bound for an `nvidia-smi` query; it can hang while a driver
+/// initialises.
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Read GPUs exposing `device/gpu_busy_percent` under a DRM class directory.
+///
+/// Only `cardN` entries are considered; connector entries such as
+/// `card0-DP-1` share the device directory and would duplicate results.
+/// VRAM sizes come from `mem_info_vram_used` / `mem_info_vram_total` (bytes).
+pub fn read_drm_gpus(drm_dir: &Path) -> Vec<GpuInfo> {
+    let Ok(entries) = fs::read_dir(drm_dir) else {
+        return Vec::new();
+    };
+
+    let mut cards: Vec<(String, std::path::PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let index = name.strip_prefix("card")?;
+            (!index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| (name, entry.path().join("device")))
+        })
+        .collect();
+    cards.sort();
+
+    cards
+        .into_iter()
+        .filter_map(|(card, device)| {
+            let busy = read_u64(&device.join("gpu_busy_percent"))?;
+            let name = read_trimmed(&device.join("product_name")).unwrap_or(card);
+            let to_mb = |file: &str| read_u64(&device.join(file)).unwrap_or(0) / (1024 * 1024);
+            Some(GpuInfo {
+                name,
+                utilization_pct: busy as f64,
+                vram_used_mb: to_mb("mem_info_vram_used"),
+                vram_total_mb: to_mb("mem_info_vram_total"),
+            })
+        })
+        .collect()
+}
+
+/// Parse `nvidia-smi --query-gpu=name,utilization.gpu,memory.used,memory.total
+/// --format=csv,noheader,nounits` output (one GPU per line, MiB units).
+///
+/// Lines with unparsable values (e.g. `[N/A]`) are skipped.
+pub fn parse_nvidia_smi_output(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            // The name may itself contain commas, so split from the right.
+            let mut fields = line.rsplitn(4, ',').map(str::trim);
+            let vram_total_mb = fields.next()?.parse().ok()?;
+            let vram_used_mb = fields.next()?.parse().ok()?;
+            let utilization_pct = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            if name.is_empty() {
+                return None;
+            }
+            Some(GpuInfo {
+                name: name.to_string(),
+                utilization_pct,
+                vram_used_mb,
+                vram_total_mb,
+            })
+        })
+        .collect()
+}
+
+/// Query NVIDIA GPUs through `nvidia-smi`, if installed.
+async fn query_nvidia_smi() -> Vec<GpuInfo> {
+    let output = tokio::time::timeout(
+        NVIDIA_SMI_TIMEOUT,
+        Command::new("nvidia-smi")
+            .args([
+                "--query-gpu=name,utilization.gpu,memory.used,memory.total",
+                "--format=csv,noheader,nounits",
+            ])
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    match output {
+        Ok(Ok(out)) if out.status.success() => {
+            parse_nvidia_smi_output(&String::from_utf8_lossy(&out.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Collect GPU metrics.
+///
+/// Tries the DRM sysfs interface (AMD) first and falls back to `nvidia-smi`.
+/// Hosts without a supported GPU report an empty list; this never fails.
+pub async fn collect() -> Vec<GpuInfo> {
+    let gpus = read_drm_gpus(Path::new(DRM_CLASS_DIR));
+    if !gpus.is_empty() {
+        return gpus;
+    }
+    query_nvidia_smi().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi_output() {
+        let output = "NVIDIA GeForce RTX 3080, 42, 2048, 10240\n\
+                      Tesla T4, 0, 0, 15360\n";
+        let gpus = parse_nvidia_smi_output(output);
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].name, "NVIDIA GeForce RTX 3080");
+        assert_eq!(gpus[0].utilization_pct, 42.0);
+        assert_eq!(gpus[0].vram_used_mb, 2048);
+        assert_eq!(gpus[0].vram_total_mb, 10240);
+        assert_eq!(gpus[1].name, "Tesla T4");
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_skips_unavailable_and_garbage() {
+        let output = "GRID, vGPU, [N/A], 100, 200\nnot csv at all\n\nA100, 5, 1, 2\n";
+        let gpus = parse_nvidia_smi_output(output);
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "A100");
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_name_with_comma() {
+        let gpus = parse_nvidia_smi_output("Quadro RTX 4000, Mobile, 7, 100, 8192");
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name, "Quadro RTX 4000, Mobile");
+    }
+
+    #[test]
+    fn test_read_drm_gpus() {
+        let root = std::env::temp_dir().join(format!("panoptikon-gpu-{}", std::process::id()));
+        let card0 = root.join("card0/device");
+        let card1 = root.join("card1/device");
+        fs::create_dir_all(&card0).unwrap();
+        fs::create_dir_all(&card1).unwrap();
+        fs::create_dir_all(root.join("card0-DP-1/device")).unwrap();
+        fs::write(card0.join("gpu_busy_percent"), "37\n").unwrap();
+        fs::write(card0.join("mem_info_vram_used"), "1073741824\n").unwrap();
+        fs::write(card0.join("mem_info_vram_total"), "8589934592\n").unwrap();
+        fs::write(card0.join("product_name"), "Radeon RX 6600\n").unwrap();
+        fs::write(root.join("card0-DP-1/device/gpu_busy_percent"), "37\n").unwrap();
+        // card1 has no busy counter (e.g. an Intel iGPU) and is skipped.
+
+        let gpus = read_drm_gpus(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            gpus,
+            vec![GpuInfo {
+                name: "Radeon RX 6600".to_string(),
+                utilization_pct: 37.0,
+                vram_used_mb: 1024,
+                vram_total_mb: 8192,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_drm_gpus_missing_dir() {
+        assert!(read_drm_gpus(Path::new("/nonexistent/drm")).is_empty());
+    }
+}
@@ -1,10 +1,18 @@
+pub mod battery;
 pub mod cpu;
 pub mod disk;
+pub mod docker;
+pub mod gpu;
 pub mod memory;
 pub mod network;
 pub mod os;
+pub mod packages;
+pub mod process;
+pub mod sysfs;
+pub mod thermal;
 
 use std::collections::HashMap;
+use std::time::Instant;
 
 use serde::Serialize;
 use sysinfo::{Disks, Networks, System};
@@ -20,24 +28,56 @@ pub struct AgentReport {
     pub hostname: String,
     pub os: os::OsInfo,
     pub uptime_seconds: u64,
+    /// Unix load averages; always 0.0 on Windows.
+    pub load_avg_1m: f64,
+    pub load_avg_5m: f64,
+    pub load_avg_15m: f64,
+    /// Number of processes as of the last process table refresh.
+    pub process_count: u32,
     pub cpu: cpu::CpuInfo,
     pub memory: memory::MemoryInfo,
     pub disks: Vec<disk::DiskInfo>,
     pub network_interfaces: Vec<network::NetworkInterface>,
+    /// GPU utilisation; empty when no supported GPU is present.
+    pub gpus: Vec<gpu::GpuInfo>,
+    /// Top processes by CPU usage; refreshed every 5th cycle.
+    pub top_cpu_processes: Vec<process::ProcessInfo>,
+    /// Top processes by resident memory; refreshed every 5th cycle.
+    pub top_mem_processes: Vec<process::ProcessInfo>,
+    /// Running Docker containers; empty when the socket is unavailable.
+    pub containers: Vec<docker::ContainerInfo>,
+    /// Temperature sensors; refreshed every 5th cycle.
+    pub thermal_sensors: Vec<thermal::ThermalSensor>,
+    /// Batteries and UPS devices; refreshed every 10th cycle.
+    pub batteries: Vec<battery::BatteryInfo>,
+    /// Installed packages; only sent on package refresh cycles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packages: Option<Vec<packages::PackageInfo>>,
 }
 
+/// Battery and UPS status is refreshed every this many reports (~5 minutes at 30 s).
+const BATTERY_REFRESH_CYCLES: u64 = 10;
+
+/// Installed packages are collected every this many reports (~1 hour at 30 s).
+const PACKAGE_REFRESH_CYCLES: u64 = 120;
+
 /// Long-lived system metrics collector.
 ///
 /// Holds `sysinfo` structs across report cycles to avoid re-enumerating
 /// processes, disks, and interfaces on every 30-second report.
-/// CPU and memory are refreshed every cycle; disks and network only
-/// every 5th cycle (~2.5 minutes at default 30 s interval).
+/// CPU, memory and network counters are refreshed every cycle; disks, the
+/// interface list, the process table and thermal sensors only every 5th
+/// cycle (~2.5 minutes at default 30 s interval).
 pub struct SystemCollector {
     sys: System,
     disks: Disks,
     networks: Networks,
     report_count: u64,
     prev_net_counters: HashMap<String, (u64, u64)>,
+    /// When the previous report was collected; used to turn byte deltas into rates.
+    last_collect_at: Option<Instant>,
+    thermal_sensors: Vec<thermal::ThermalSensor>,
+    batteries: Vec<battery::BatteryInfo>,
 }
 
 impl SystemCollector {
@@ -60,29 +100,62 @@ impl SystemCollector {
             networks,
             report_count: 0,
             prev_net_counters: HashMap::new(),
+            last_collect_at: None,
+            thermal_sensors: Vec::new(),
+            batteries: Vec::new(),
         }
     }
 
     /// Collect a full system report using incremental refresh.
     ///
-    /// CPU and memory are refreshed on every call (lightweight).
-    /// Disks and network interfaces are refreshed only every 5th call
-    /// to avoid the heavier enumeration cost.
-    pub fn collect(&mut self, config: &AgentConfig) -> AgentReport {
+    /// CPU, memory and network counters are refreshed on every call (lightweight).
+    /// Disks, the interface list, processes and thermal sensors are
+    /// refreshed only every 5th call to avoid the heavier enumeration cost.
+    pub async fn collect(&mut self, config: &AgentConfig) -> AgentReport {
         // Always refresh CPU and memory (lightweight).
         self.sys.refresh_cpu_usage();
+        self.sys.refresh_cpu_frequency();
         self.sys.refresh_memory();
 
-        // Heavy refresh (disks, networks) only every 5th cycle.
+        // Heavy refresh (disks, networks, processes, thermal) only every 5th cycle.
         if self.report_count.is_multiple_of(5) {
             self.disks.refresh_list();
             self.networks.refresh_list();
+            self.sys.refresh_processes();
+            self.thermal_sensors = thermal::collect();
+        } else {
+            // Per-interface rates need fresh counters every report.
+            self.networks.refresh();
         }
 
-        let network_interfaces = network::collect_from(&self.networks, &mut self.prev_net_counters);
+        // Battery status changes slowly; UPS queries spawn `upsc`.
+        if self.report_count.is_multiple_of(BATTERY_REFRESH_CYCLES) {
+            self.batteries = battery::collect();
+        }
+
+        let top_processes = process::collect(&self.sys);
+
+        let now = Instant::now();
+        let elapsed = self.last_collect_at.map(|prev| now.duration_since(prev));
+        self.last_collect_at = Some(now);
+        let network_interfaces =
+            network::collect_from(&self.networks, &mut self.prev_net_counters, elapsed);
+
+        // Package lists change rarely and spawn external tools, so send them
+        // on the first report and then about hourly.
+        let packages = if self.report_count.is_multiple_of(PACKAGE_REFRESH_CYCLES) {
+            Some(packages::collect().await)
+        } else {
+            None
+        };
+
+        let containers = docker::collect().await;
+        let gpus = gpu::collect().await;
 
         self.report_count += 1;
 
+        let load = System::load_average();
+
         AgentReport {
             agent_id: config.agent_id.clone(),
             timestamp: chrono::Utc::now().to_rfc3339(),
@@ -90,10 +163,21 @@ impl SystemCollector {
             hostname: System::host_name().unwrap_or_else(|| "unknown".to_string()),
             os: os::collect(),
             uptime_seconds: System::uptime(),
+            load_avg_1m: load.one,
+            load_avg_5m: load.five,
+            load_avg_15m: load.fifteen,
+            process_count: self.sys.processes().len() as u32,
             cpu: cpu::collect(&self.sys),
             memory: memory::collect(&self.sys),
             disks: disk::collect_from(&self.disks),
             network_interfaces,
+            gpus,
+            top_cpu_processes: top_processes.by_cpu,
+            top_mem_processes: top_processes.by_mem,
+            containers,
+            thermal_sensors: self.thermal_sensors.clone(),
+            batteries: self.batteries.clone(),
+            packages,
         }
     }
 
@@ -114,17 +198,22 @@ mod tests {
         assert_eq!(collector.report_count(), 0);
     }
 
-    #[test]
-    fn test_collector_increments_count() {
+    #[tokio::test]
+    async fn test_collector_increments_count() {
         let mut collector = SystemCollector::new();
         let config = AgentConfig {
             server_url: "ws://localhost:8080".to_string(),
             api_key: "test-key".to_string(),
             agent_id: "test-agent".to_string(),
             report_interval_secs: 30,
+            send_delta: false,
         };
-        let report = collector.collect(&config);
+        let report = collector.collect(&config).await;
         assert_eq!(collector.report_count(), 1);
         assert_eq!(report.agent_id, "test-agent");
+        assert!(report.load_avg_1m >= 0.0);
+        assert!(report.load_avg_5m >= 0.0);
+        assert!(report.load_avg_15m >= 0.0);
+        assert!(report.process_count > 0);
     }
 }
@@ -1,5 +1,6 @@
 use serde::Serialize;
 use std::collections::HashMap;
+use std::time::Duration;
 use sysinfo::Networks;
 
 /// Network interface information.
@@ -11,18 +12,49 @@ pub struct NetworkInterface {
     pub rx_bytes: u64,
     pub tx_bytes_delta: u64,
     pub rx_bytes_delta: u64,
+    /// Transmit rate in bytes per second since the previous report.
+    pub tx_rate_bps: u64,
+    /// Receive rate in bytes per second since the previous report.
+    pub rx_rate_bps: u64,
     pub state: String,
 }
 
+/// Compute `(tx, rx)` deltas from the previous cumulative counters.
+///
+/// Returns 0 for a newly-seen interface, and on counter reset/overflow
+/// (current < previous).
+fn counter_delta(prev: Option<(u64, u64)>, current: (u64, u64)) -> (u64, u64) {
+    match prev {
+        Some((prev_tx, prev_rx)) => (
+            current.0.saturating_sub(prev_tx),
+            current.1.saturating_sub(prev_rx),
+        ),
+        None => (0, 0),
+    }
+}
+
+/// Convert a byte delta into bytes per second over `elapsed`.
+///
+/// Returns 0 when there is no previous sample or no measurable time passed.
+fn rate_per_sec(delta: u64, elapsed: Option<Duration>) -> u64 {
+    match elapsed {
+        Some(elapsed) if !elapsed.is_zero() => (delta as f64 / elapsed.as_secs_f64()) as u64,
+        _ => 0,
+    }
+}
+
 /// Collect network interface statistics with in-memory delta tracking.
 ///
 /// `prev_counters` maps interface name → `(tx_cumulative, rx_cumulative)`.
 /// On each call, deltas are computed from the previous values and the
 /// map is updated with current counters.  On the first call (or for
-/// newly-appeared interfaces) deltas are 0.
+/// newly-appeared interfaces) deltas are 0.  Rates divide the deltas by
+/// `elapsed`, the wall-clock time since the previous call (`None` on the
+/// first call, which yields rates of 0).
 pub fn collect_from(
     networks: &Networks,
     prev_counters: &mut HashMap<String, (u64, u64)>,
+    elapsed: Option<Duration>,
 ) -> Vec<NetworkInterface> {
     networks
         .iter()
@@ -34,16 +66,10 @@ pub fn collect_from(
             let current_tx = data.total_transmitted();
             let current_rx = data.total_received();
 
-            // Compute deltas from previous counters.
-            let (tx_delta, rx_delta) = match prev_counters.get(name.as_str()) {
-                Some(&(prev_tx, prev_rx)) => {
-                    // Handle counter reset/overflow: if current < previous, delta is 0.
-                    let tx_d = current_tx.saturating_sub(prev_tx);
-                    let rx_d = current_rx.saturating_sub(prev_rx);
-                    (tx_d, rx_d)
-                }
-                None => (0, 0),
-            };
+            let (tx_delta, rx_delta) = counter_delta(
+                prev_counters.get(name.as_str()).copied(),
+                (current_tx, current_rx),
+            );
 
             // Update counters for next report.
             prev_counters.insert(name.clone(), (current_tx, current_rx));
@@ -55,6 +81,8 @@ pub fn collect_from(
                 rx_bytes: current_rx,
                 tx_bytes_delta: tx_delta,
                 rx_bytes_delta: rx_delta,
+                tx_rate_bps: rate_per_sec(tx_delta, elapsed),
+                rx_rate_bps: rate_per_sec(rx_delta, elapsed),
                 state: "up".to_string(),
             }
         })
@@ -69,13 +97,7 @@ mod tests {
     fn test_delta_no_previous() {
         let mut prev: HashMap<String, (u64, u64)> = HashMap::new();
 
-        let (tx_delta, rx_delta) = match prev.get("eth0") {
-            Some(&(prev_tx, prev_rx)) => (
-                100u64.saturating_sub(prev_tx),
-                200u64.saturating_sub(prev_rx),
-            ),
-            None => (0, 0),
-        };
+        let (tx_delta, rx_delta) = counter_delta(prev.get("eth0").copied(), (100, 200));
 
         assert_eq!(tx_delta, 0);
         assert_eq!(rx_delta, 0);
@@ -90,16 +112,7 @@ mod tests {
         let mut prev: HashMap<String, (u64, u64)> = HashMap::new();
         prev.insert("eth0".to_string(), (10_000, 20_000));
 
-        let current_tx = 15_000u64;
-        let current_rx = 25_000u64;
-
-        let (tx_delta, rx_delta) = match prev.get("eth0") {
-            Some(&(prev_tx, prev_rx)) => (
-                current_tx.saturating_sub(prev_tx),
-                current_rx.saturating_sub(prev_rx),
-            ),
-            None => (0, 0),
-        };
+        let (tx_delta, rx_delta) = counter_delta(prev.get("eth0").copied(), (15_000, 25_000));
 
         assert_eq!(tx_delta, 5_000);
         assert_eq!(rx_delta, 5_000);
@@ -110,18 +123,24 @@ mod tests {
         let mut prev: HashMap<String, (u64, u64)> = HashMap::new();
         prev.insert("eth0".to_string(), (99_999, 88_888));
 
-        let current_tx = 100u64;
-        let current_rx = 200u64;
-
-        let (tx_delta, rx_delta) = match prev.get("eth0") {
-            Some(&(prev_tx, prev_rx)) => (
-                current_tx.saturating_sub(prev_tx),
-                current_rx.saturating_sub(prev_rx),
-            ),
-            None => (0, 0),
-        };
+        let (tx_delta, rx_delta) = counter_delta(prev.get("eth0").copied(), (100, 200));
 
         assert_eq!(tx_delta, 0);
         assert_eq!(rx_delta, 0);
     }
+
+    #[test]
+    fn test_rate_per_sec() {
+        assert_eq!(rate_per_sec(30_000, Some(Duration::from_secs(30))), 1_000);
+        assert_eq!(
+            rate_per_sec(5_000, Some(Duration::from_millis(2_500))),
+            2_000
+        );
+    }
+
+    #[test]
+    fn test_rate_first_report_is_zero() {
+        assert_eq!(rate_per_sec(5_000, None), 0);
+        assert_eq!(rate_per_sec(5_000, Some(Duration::ZERO)), 0);
+    }
 }
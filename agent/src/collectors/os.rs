@@ -1,4 +1,6 @@
 use serde::Serialize;
+use std::fs;
+use std::path::Path;
 use sysinfo::System;
 
 /// Operating system information.
@@ -8,14 +10,185 @@ pub struct OsInfo {
     pub version: String,
     pub kernel: String,
     pub arch: String,
+    /// Whether the agent runs inside a container, where `hostname` is
+    /// usually the container ID rather than the host's name.
+    pub is_container: bool,
+    /// `docker`, `podman`, `kubernetes`, `containerd` or `lxc`.
+    pub container_runtime: Option<String>,
+    /// Kubernetes pod name (`POD_NAME`, else the pod hostname).
+    pub pod_name: Option<String>,
+    /// Kubernetes namespace (`POD_NAMESPACE`, else the service account's).
+    pub namespace: Option<String>,
+    /// Kubernetes node the pod runs on (`NODE_NAME` via the downward API).
+    pub node_name: Option<String>,
+}
+
+/// Container context detected from marker files, cgroups and environment.
+#[derive(Debug, Default, PartialEq)]
+pub struct ContainerInfo {
+    pub is_container: bool,
+    pub runtime: Option<String>,
+    pub pod_name: Option<String>,
+    pub namespace: Option<String>,
+    pub node_name: Option<String>,
+}
+
+/// Read a file as a trimmed, non-empty string.
+fn read_trimmed(path: &Path) -> Option<String> {
+    let value = fs::read_to_string(path).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Infer the runtime from the cgroup paths in `/proc/1/cgroup` (cgroup v1
+/// puts the container ID under e.g. `/docker/<id>` or `/kubepods/...`).
+fn runtime_from_cgroup(cgroup: &str) -> Option<&'static str> {
+    const MARKERS: [(&str, &str); 5] = [
+        ("kubepods", "kubernetes"),
+        ("docker", "docker"),
+        ("libpod", "podman"),
+        ("containerd", "containerd"),
+        ("lxc", "lxc"),
+    ];
+    cgroup.lines().find_map(|line| {
+        let path = line.rsplit(':').next()?;
+        MARKERS
+            .iter()
+            .find(|(marker, _)| path.contains(marker))
+            .map(|(_, runtime)| *runtime)
+    })
+}
+
+/// Detect whether we run in a container, reading files under `root` and
+/// variables through `env`.
+pub fn detect_container(
+    root: &Path,
+    env: impl Fn(&str) -> Option<String>,
+    hostname: Option<String>,
+) -> ContainerInfo {
+    let env = |name: &str| env(name).filter(|v| !v.is_empty());
+    let in_kubernetes = env("KUBERNETES_SERVICE_HOST").is_some();
+
+    let runtime = if in_kubernetes {
+        Some("kubernetes")
+    } else if root.join(".dockerenv").exists() {
+        Some("docker")
+    } else if root.join("run/.containerenv").exists() {
+        Some("podman")
+    } else {
+        read_trimmed(&root.join("proc/1/cgroup")).and_then(|c| runtime_from_cgroup(&c))
+    };
+    let Some(runtime) = runtime else {
+        return ContainerInfo::default();
+    };
+
+    let (pod_name, namespace) = if in_kubernetes {
+        (
+            env("POD_NAME").or(hostname),
+            env("POD_NAMESPACE").or_else(|| {
+                read_trimmed(&root.join("var/run/secrets/kubernetes.io/serviceaccount/namespace"))
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    ContainerInfo {
+        is_container: true,
+        runtime: Some(runtime.to_string()),
+        pod_name,
+        namespace,
+        node_name: env("NODE_NAME"),
+    }
 }
 
 /// Collect OS information.
 pub fn collect() -> OsInfo {
+    let container = detect_container(
+        Path::new("/"),
+        |name| std::env::var(name).ok(),
+        System::host_name(),
+    );
     OsInfo {
         name: System::name().unwrap_or_else(|| "unknown".to_string()),
         version: System::os_version().unwrap_or_else(|| "unknown".to_string()),
         kernel: System::kernel_version().unwrap_or_else(|| "unknown".to_string()),
         arch: std::env::consts::ARCH.to_string(),
+        is_container: container.is_container,
+        container_runtime: container.runtime,
+        pod_name: container.pod_name,
+        namespace: container.namespace,
+        node_name: container.node_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_root(tag: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("panoptikon-os-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn test_detect_container() {
+        let no_env = |_: &str| None;
+
+        let host = temp_root("host");
+        fs::create_dir_all(host.join("proc/1")).unwrap();
+        fs::write(host.join("proc/1/cgroup"), "0::/init.scope\n").unwrap();
+        assert_eq!(
+            detect_container(&host, no_env, Some("nas".into())),
+            ContainerInfo::default()
+        );
+
+        let docker = temp_root("docker");
+        fs::write(docker.join(".dockerenv"), "").unwrap();
+        let info = detect_container(&docker, no_env, Some("3f2a9c1b".into()));
+        assert!(info.is_container);
+        assert_eq!(info.runtime.as_deref(), Some("docker"));
+        assert_eq!(info.pod_name, None);
+
+        let cgroup = temp_root("cgroup");
+        fs::create_dir_all(cgroup.join("proc/1")).unwrap();
+        fs::write(
+            cgroup.join("proc/1/cgroup"),
+            "12:memory:/lxc/web01\n11:cpu:/lxc/web01\n",
+        )
+        .unwrap();
+        let info = detect_container(&cgroup, no_env, None);
+        assert_eq!(info.runtime.as_deref(), Some("lxc"));
+
+        let pod = temp_root("pod");
+        let sa = pod.join("var/run/secrets/kubernetes.io/serviceaccount");
+        fs::create_dir_all(&sa).unwrap();
+        fs::write(sa.join("namespace"), "monitoring\n").unwrap();
+        let env: HashMap<&str, &str> = [
+            ("KUBERNETES_SERVICE_HOST", "10.96.0.1"),
+            ("NODE_NAME", "worker-2"),
+        ]
+        .into();
+        let info = detect_container(
+            &pod,
+            |name| env.get(name).map(|v| v.to_string()),
+            Some("agent-7d9f".into()),
+        );
+
+        for root in [host, docker, cgroup, pod] {
+            fs::remove_dir_all(root).unwrap();
+        }
+        assert_eq!(
+            info,
+            ContainerInfo {
+                is_container: true,
+                runtime: Some("kubernetes".to_string()),
+                pod_name: Some("agent-7d9f".to_string()),
+                namespace: Some("monitoring".to_string()),
+                node_name: Some("worker-2".to_string()),
+            }
+        );
     }
 }
@@ -0,0 +1,144 @@
+use serde::Serialize;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Upper bound for one package manager query; `rpm -qa` on a large host
+/// takes a few seconds.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An installed software package.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+    /// Package manager that reported it: "dpkg", "rpm" or "brew".
+    pub manager: String,
+}
+
+/// Parse tab-separated `name\tversion` lines (dpkg-query and rpm output).
+fn parse_tab_separated(output: &str, manager: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, version) = line.split_once('\t')?;
+            let (name, version) = (name.trim(), version.trim());
+            if name.is_empty() || version.is_empty() {
+                return None;
+            }
+            Some(PackageInfo {
+                name: name.to_string(),
+                version: version.to_string(),
+                manager: manager.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse `dpkg-query -W -f='${Package}\t${Version}\n'` output.
+pub fn parse_dpkg_output(output: &str) -> Vec<PackageInfo> {
+    parse_tab_separated(output, "dpkg")
+}
+
+/// Parse `rpm -qa --qf '%{NAME}\t%{VERSION}\n'` output.
+pub fn parse_rpm_output(output: &str) -> Vec<PackageInfo> {
+    parse_tab_separated(output, "rpm")
+}
+
+/// Parse `brew list --versions` output (`name v1 [v2 ...]`; the last version wins).
+pub fn parse_brew_output(output: &str) -> Vec<PackageInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let version = parts.last()?;
+            Some(PackageInfo {
+                name: name.to_string(),
+                version: version.to_string(),
+                manager: "brew".to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Run a command and return its stdout if it exited successfully within
+/// [`QUERY_TIMEOUT`].
+async fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = tokio::time::timeout(
+        QUERY_TIMEOUT,
+        Command::new(program).args(args).kill_on_drop(true).output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Collect installed packages from the first available package manager.
+///
+/// Returns every package, sorted by name, so compliance checks see the
+/// whole host. Hosts without a supported package manager report an empty
+/// list.
+pub async fn collect() -> Vec<PackageInfo> {
+    let mut packages = if cfg!(target_os = "macos") {
+        run("brew", &["list", "--versions"])
+            .await
+            .map(|out| parse_brew_output(&out))
+            .unwrap_or_default()
+    } else if let Some(out) = run("dpkg-query", &["-W", "-f=${Package}\t${Version}\n"]).await {
+        parse_dpkg_output(&out)
+    } else {
+        run("rpm", &["-qa", "--qf", "%{NAME}\t%{VERSION}\n"])
+            .await
+            .map(|out| parse_rpm_output(&out))
+            .unwrap_or_default()
+    };
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DPKG_FIXTURE: &str = "adduser\t3.118ubuntu5\n\
+                                openssl\t3.0.2-0ubuntu1.15\n\
+                                libssl3\t3.0.2-0ubuntu1.15\n\
+                                \n\
+                                broken-line-without-tab\n";
+
+    #[test]
+    fn test_parse_dpkg_output() {
+        let packages = parse_dpkg_output(DPKG_FIXTURE);
+        assert_eq!(packages.len(), 3);
+        assert_eq!(
+            packages[1],
+            PackageInfo {
+                name: "openssl".to_string(),
+                version: "3.0.2-0ubuntu1.15".to_string(),
+                manager: "dpkg".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rpm_output() {
+        let packages = parse_rpm_output("bash\t5.2.15\nopenssl-libs\t3.0.7\n");
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].manager, "rpm");
+        assert_eq!(packages[1].name, "openssl-libs");
+    }
+
+    #[test]
+    fn test_parse_brew_output() {
+        let packages = parse_brew_output("openssl@3 3.1.4 3.2.1\nwget 1.21.4\n");
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "openssl@3");
+        assert_eq!(packages[0].version, "3.2.1");
+        assert_eq!(packages[1].manager, "brew");
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Serialize;
+use sysinfo::System;
+
+/// Number of processes reported in each top-N list.
+pub const TOP_N: usize = 10;
+
+/// A single running process.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub cpu_pct: f64,
+    pub rss_kb: u64,
+    pub cmdline: String,
+}
+
+/// Top CPU and memory consumers.
+#[derive(Debug, Default)]
+pub struct TopProcesses {
+    pub by_cpu: Vec<ProcessInfo>,
+    pub by_mem: Vec<ProcessInfo>,
+}
+
+/// Rank processes by CPU and by RSS, keeping the top [`TOP_N`] of each.
+///
+/// Processes with an empty name (e.g. exited between refreshes) are dropped.
+pub fn rank(processes: Vec<ProcessInfo>) -> TopProcesses {
+    let mut by_cpu: Vec<ProcessInfo> = processes
+        .into_iter()
+        .filter(|p| !p.name.is_empty())
+        .collect();
+    let mut by_mem = by_cpu.clone();
+
+    by_cpu.sort_by(|a, b| b.cpu_pct.total_cmp(&a.cpu_pct).then(a.pid.cmp(&b.pid)));
+    by_cpu.truncate(TOP_N);
+    by_mem.sort_by(|a, b| b.rss_kb.cmp(&a.rss_kb).then(a.pid.cmp(&b.pid)));
+    by_mem.truncate(TOP_N);
+
+    TopProcesses { by_cpu, by_mem }
+}
+
+/// Collect the top processes from an already refreshed `System`.
+pub fn collect(sys: &System) -> TopProcesses {
+    let processes = sys
+        .processes()
+        .values()
+        .map(|p| ProcessInfo {
+            pid: p.pid().as_u32(),
+            name: p.name().to_string(),
+            cpu_pct: p.cpu_usage() as f64,
+            rss_kb: p.memory() / 1024,
+            cmdline: p.cmd().join(" "),
+        })
+        .collect();
+    rank(processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: u32, name: &str, cpu_pct: f64, rss_kb: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            cpu_pct,
+            rss_kb,
+            cmdline: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_rank_sorts_and_truncates() {
+        let processes = (1..=15)
+            .map(|i| proc(i, &format!("p{i}"), i as f64, 1000 - i as u64))
+            .collect();
+        let top = rank(processes);
+
+        assert_eq!(top.by_cpu.len(), TOP_N);
+        assert_eq!(top.by_cpu[0].pid, 15);
+        assert_eq!(top.by_cpu[9].pid, 6);
+        assert_eq!(top.by_mem.len(), TOP_N);
+        assert_eq!(top.by_mem[0].pid, 1);
+        assert_eq!(top.by_mem[9].pid, 10);
+    }
+
+    #[test]
+    fn test_rank_filters_empty_names() {
+        let top = rank(vec![proc(1, "", 99.0, 99_999), proc(2, "sshd", 0.1, 4096)]);
+        assert_eq!(top.by_cpu, vec![proc(2, "sshd", 0.1, 4096)]);
+        assert_eq!(top.by_mem, vec![proc(2, "sshd", 0.1, 4096)]);
+    }
+}
@@ -0,0 +1,15 @@
+//! Helpers for reading Linux sysfs attributes.
+
+use std::fs;
+use std::path::Path;
+
+/// Read a sysfs attribute as a trimmed, non-empty string.
+pub fn read_trimmed(path: &Path) -> Option<String> {
+    let value = fs::read_to_string(path).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Read a numeric sysfs attribute.
+pub fn read_u64(path: &Path) -> Option<u64> {
+    read_trimmed(path)?.parse().ok()
+}
@@ -0,0 +1,148 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::sysfs::read_trimmed;
+
+/// A single temperature reading.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ThermalSensor {
+    pub name: String,
+    pub temp_celsius: f64,
+}
+
+const THERMAL_CLASS_DIR: &str = "/sys/class/thermal";
+const HWMON_CLASS_DIR: &str = "/sys/class/hwmon";
+
+/// Read a millidegree Celsius attribute and convert it to degrees.
+fn read_millidegrees(path: &Path) -> Option<f64> {
+    let milli: i64 = read_trimmed(path)?.parse().ok()?;
+    Some(milli as f64 / 1000.0)
+}
+
+/// List entries of `dir` whose file name starts with `prefix`, sorted by name.
+fn entries_with_prefix(dir: &Path, prefix: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            name.starts_with(prefix).then(|| (name, entry.path()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Read `thermal_zone*/temp`, labelled by the zone's `type` file.
+pub fn read_thermal_zones(thermal_dir: &Path) -> Vec<ThermalSensor> {
+    entries_with_prefix(thermal_dir, "thermal_zone")
+        .into_iter()
+        .filter_map(|(zone, path)| {
+            let temp_celsius = read_millidegrees(&path.join("temp"))?;
+            let name = read_trimmed(&path.join("type")).unwrap_or(zone);
+            Some(ThermalSensor { name, temp_celsius })
+        })
+        .collect()
+}
+
+/// Read `hwmon*/temp*_input`, labelled `<chip>/<label>` using the chip's
+/// `name` file and the matching `temp*_label` when present.
+pub fn read_hwmon(hwmon_dir: &Path) -> Vec<ThermalSensor> {
+    entries_with_prefix(hwmon_dir, "hwmon")
+        .into_iter()
+        .flat_map(|(hwmon, path)| {
+            let chip = read_trimmed(&path.join("name")).unwrap_or(hwmon);
+            entries_with_prefix(&path, "temp")
+                .into_iter()
+                .filter_map(|(file, input)| {
+                    let sensor = file.strip_suffix("_input")?;
+                    let temp_celsius = read_millidegrees(&input)?;
+                    let label = read_trimmed(&path.join(format!("{sensor}_label")))
+                        .unwrap_or_else(|| sensor.to_string());
+                    Some(ThermalSensor {
+                        name: format!("{chip}/{label}"),
+                        temp_celsius,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Collect temperature readings from thermal zones and hwmon chips.
+///
+/// Hosts without these sysfs trees (macOS, Windows) report an empty list.
+pub fn collect() -> Vec<ThermalSensor> {
+    let mut sensors = read_thermal_zones(Path::new(THERMAL_CLASS_DIR));
+    sensors.extend(read_hwmon(Path::new(HWMON_CLASS_DIR)));
+    sensors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(tag: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("panoptikon-thermal-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn test_read_thermal_zones() {
+        let root = temp_root("zones");
+        fs::create_dir_all(root.join("thermal_zone0")).unwrap();
+        fs::create_dir_all(root.join("thermal_zone1")).unwrap();
+        fs::create_dir_all(root.join("cooling_device0")).unwrap();
+        fs::write(root.join("thermal_zone0/temp"), "48312\n").unwrap();
+        fs::write(root.join("thermal_zone0/type"), "cpu-thermal\n").unwrap();
+        fs::write(root.join("thermal_zone1/temp"), "-2500\n").unwrap();
+
+        let sensors = read_thermal_zones(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            sensors,
+            vec![
+                ThermalSensor {
+                    name: "cpu-thermal".to_string(),
+                    temp_celsius: 48.312,
+                },
+                ThermalSensor {
+                    name: "thermal_zone1".to_string(),
+                    temp_celsius: -2.5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_hwmon() {
+        let root = temp_root("hwmon");
+        let chip = root.join("hwmon0");
+        fs::create_dir_all(&chip).unwrap();
+        fs::write(chip.join("name"), "coretemp\n").unwrap();
+        fs::write(chip.join("temp1_input"), "55000\n").unwrap();
+        fs::write(chip.join("temp1_label"), "Package id 0\n").unwrap();
+        fs::write(chip.join("temp2_input"), "51000\n").unwrap();
+        fs::write(chip.join("temp2_crit"), "100000\n").unwrap();
+
+        let sensors = read_hwmon(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(sensors.len(), 2);
+        assert_eq!(sensors[0].name, "coretemp/Package id 0");
+        assert_eq!(sensors[0].temp_celsius, 55.0);
+        assert_eq!(sensors[1].name, "coretemp/temp2");
+    }
+
+    #[test]
+    fn test_missing_dirs_return_empty() {
+        assert!(read_thermal_zones(Path::new("/nonexistent/thermal")).is_empty());
+        assert!(read_hwmon(Path::new("/nonexistent/hwmon")).is_empty());
+    }
+}
@@ -9,6 +9,7 @@ use serde::Deserialize;
 /// api_key = "pnk_a1b2c3d4e5f6..."
 /// agent_id = "550e8400-e29b-41d4-a716-446655440000"
 /// report_interval_seconds = 30
+/// send_delta = false
 /// ```
 #[derive(Debug, Clone, Deserialize)]
 pub struct AgentConfig {
@@ -24,6 +25,11 @@ pub struct AgentConfig {
     /// How often to send reports, in seconds.
     #[serde(default = "default_interval")]
     pub report_interval_secs: u64,
+
+    /// Send only the report sections that changed (for slow links). CPU and
+    /// memory are always sent; everything is resent every 10th report.
+    #[serde(default)]
+    pub send_delta: bool,
 }
 
 fn default_interval() -> u64 {
@@ -0,0 +1,188 @@
+//! Delta reports for agents on slow links (`send_delta = true`).
+//!
+//! CPU, memory, load and uptime change every cycle and are always sent. The
+//! remaining sections are hashed and only sent when their hash differs from
+//! the one last sent; the server keeps the previous values for omitted
+//! sections.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use serde::Serialize;
+
+use crate::collectors::{
+    battery, cpu, disk, docker, gpu, memory, network, os, packages, process, thermal, AgentReport,
+};
+
+/// A full report is sent every this many reports, so the server recovers
+/// from a dropped or rejected delta.
+const FULL_REPORT_CYCLES: u64 = 10;
+
+/// An [`AgentReport`] with unchanged sections left out; `None` means the
+/// section is the same as in the last report sent.
+#[derive(Debug, Serialize)]
+pub struct AgentReportDelta<'a> {
+    pub agent_id: &'a str,
+    pub timestamp: &'a str,
+    /// Tells the server to carry omitted sections forward.
+    pub delta: bool,
+    pub uptime_seconds: u64,
+    pub load_avg_1m: f64,
+    pub load_avg_5m: f64,
+    pub load_avg_15m: f64,
+    pub process_count: u32,
+    pub cpu: &'a cpu::CpuInfo,
+    pub memory: &'a memory::MemoryInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<&'a os::OsInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disks: Option<&'a Vec<disk::DiskInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_interfaces: Option<&'a Vec<network::NetworkInterface>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<&'a Vec<gpu::GpuInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_cpu_processes: Option<&'a Vec<process::ProcessInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_mem_processes: Option<&'a Vec<process::ProcessInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub containers: Option<&'a Vec<docker::ContainerInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal_sensors: Option<&'a Vec<thermal::ThermalSensor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batteries: Option<&'a Vec<battery::BatteryInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packages: Option<&'a [packages::PackageInfo]>,
+}
+
+/// Hash of a section's JSON serialization.
+fn section_hash<T: Serialize + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_vec(value)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Turns successive reports of one session into full or delta messages.
+pub struct DeltaEncoder {
+    report_count: u64,
+    /// Hash of each section as last sent.
+    sent: HashMap<&'static str, u64>,
+}
+
+impl DeltaEncoder {
+    pub fn new() -> Self {
+        Self {
+            report_count: 0,
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Serialize `report`: in full on the first and every 10th call, otherwise
+    /// as an [`AgentReportDelta`].
+    pub fn encode(&mut self, report: &AgentReport) -> serde_json::Result<String> {
+        let full = self.report_count.is_multiple_of(FULL_REPORT_CYCLES);
+        self.report_count += 1;
+
+        let sent = &mut self.sent;
+        let mut send = |name: &'static str, hash: u64| {
+            let previous = sent.insert(name, hash);
+            full || previous != Some(hash)
+        };
+
+        let delta = AgentReportDelta {
+            agent_id: &report.agent_id,
+            timestamp: &report.timestamp,
+            delta: true,
+            uptime_seconds: report.uptime_seconds,
+            load_avg_1m: report.load_avg_1m,
+            load_avg_5m: report.load_avg_5m,
+            load_avg_15m: report.load_avg_15m,
+            process_count: report.process_count,
+            cpu: &report.cpu,
+            memory: &report.memory,
+            version: send("version", section_hash(&report.version))
+                .then_some(report.version.as_str()),
+            hostname: send("hostname", section_hash(&report.hostname))
+                .then_some(report.hostname.as_str()),
+            os: send("os", section_hash(&report.os)).then_some(&report.os),
+            disks: send("disks", section_hash(&report.disks)).then_some(&report.disks),
+            network_interfaces: send(
+                "network_interfaces",
+                section_hash(&report.network_interfaces),
+            )
+            .then_some(&report.network_interfaces),
+            gpus: send("gpus", section_hash(&report.gpus)).then_some(&report.gpus),
+            top_cpu_processes: send("top_cpu_processes", section_hash(&report.top_cpu_processes))
+                .then_some(&report.top_cpu_processes),
+            top_mem_processes: send("top_mem_processes", section_hash(&report.top_mem_processes))
+                .then_some(&report.top_mem_processes),
+            containers: send("containers", section_hash(&report.containers))
+                .then_some(&report.containers),
+            thermal_sensors: send("thermal_sensors", section_hash(&report.thermal_sensors))
+                .then_some(&report.thermal_sensors),
+            batteries: send("batteries", section_hash(&report.batteries))
+                .then_some(&report.batteries),
+            // Packages are only collected on refresh cycles; skip an
+            // unchanged list.
+            packages: report
+                .packages
+                .as_deref()
+                .filter(|list| send("packages", section_hash(list))),
+        };
+
+        if full {
+            serde_json::to_string(report)
+        } else {
+            serde_json::to_string(&delta)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::SystemCollector;
+    use crate::config::AgentConfig;
+
+    #[tokio::test]
+    async fn test_encoder_sends_full_then_deltas() {
+        let mut collector = SystemCollector::new();
+        let config = AgentConfig {
+            server_url: "ws://localhost:8080".to_string(),
+            api_key: "test-key".to_string(),
+            agent_id: "test-agent".to_string(),
+            report_interval_secs: 30,
+            send_delta: true,
+        };
+        let report = collector.collect(&config).await;
+        let mut encoder = DeltaEncoder::new();
+
+        let first: serde_json::Value =
+            serde_json::from_str(&encoder.encode(&report).unwrap()).unwrap();
+        assert!(first.get("delta").is_none());
+        assert!(first.get("os").is_some());
+
+        for _ in 1..FULL_REPORT_CYCLES {
+            let delta: serde_json::Value =
+                serde_json::from_str(&encoder.encode(&report).unwrap()).unwrap();
+            assert_eq!(delta["delta"], true);
+            assert!(delta.get("cpu").is_some());
+            assert!(delta.get("memory").is_some());
+            assert!(delta.get("os").is_none());
+            assert!(delta.get("hostname").is_none());
+            assert!(delta.get("network_interfaces").is_none());
+        }
+
+        let full: serde_json::Value =
+            serde_json::from_str(&encoder.encode(&report).unwrap()).unwrap();
+        assert!(full.get("delta").is_none());
+        assert!(full.get("os").is_some());
+    }
+}
@@ -4,6 +4,8 @@ use tracing::{error, info};
 
 mod collectors;
 mod config;
+mod delta;
+mod systemd;
 mod ws;
 
 /// Panoptikon Agent — lightweight system metrics collector.
@@ -15,6 +17,10 @@ struct Cli {
     ///           /etc/panoptikon-agent/config.toml (system).
     #[arg(short, long)]
     config: Option<String>,
+
+    /// Print a systemd unit for this binary and config, then exit.
+    #[arg(long)]
+    print_systemd_unit: bool,
 }
 
 /// Resolve config path: explicit flag → user dir → system dir.
@@ -45,6 +51,11 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     let config_path = resolve_config(cli.config);
 
+    if cli.print_systemd_unit {
+        print!("{}", systemd::unit_for_current_exe(&config_path));
+        return Ok(());
+    }
+
     info!(
         version = env!("CARGO_PKG_VERSION"),
         "Starting Panoptikon agent"
@@ -0,0 +1,72 @@
+//! systemd unit generation for `--print-systemd-unit`.
+//!
+//! Mirrors the unit served by `GET /api/v1/agent/install/:platform/systemd-unit`
+//! so a manually installed agent can be registered as a service.
+
+/// Binary path used when the running executable can't be determined.
+pub const DEFAULT_BINARY: &str = "/usr/local/bin/panoptikon-agent";
+
+/// systemd unit running the agent binary at `exec` with `config`.
+pub fn unit_file(exec: &str, config: &str, server_url: Option<&str>) -> String {
+    let server_comment = server_url
+        .map(|url| format!("# Server: {url}\n"))
+        .unwrap_or_default();
+    format!(
+        r#"# Panoptikon Agent
+{server_comment}[Unit]
+Description=Panoptikon Agent
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=simple
+ExecStart={exec} --config {config}
+Restart=always
+RestartSec=5
+
+[Install]
+WantedBy=multi-user.target
+"#
+    )
+}
+
+/// Unit for this binary and `config_path`, taking the server URL from the
+/// config file when it can be read.
+pub fn unit_for_current_exe(config_path: &str) -> String {
+    let exec = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.canonicalize().ok())
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|| DEFAULT_BINARY.to_string());
+    let config = std::fs::canonicalize(config_path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| config_path.to_string());
+    let server_url = crate::config::AgentConfig::from_file(config_path)
+        .ok()
+        .map(|cfg| cfg.server_url);
+    unit_file(&exec, &config, server_url.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_file() {
+        let unit = unit_file(
+            DEFAULT_BINARY,
+            "/etc/panoptikon-agent/config.toml",
+            Some("http://10.0.0.1:8080"),
+        );
+        assert!(unit.contains("# Server: http://10.0.0.1:8080\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/local/bin/panoptikon-agent --config /etc/panoptikon-agent/config.toml\n"
+        ));
+        assert!(unit.contains("Restart=always\nRestartSec=5\n"));
+        assert!(unit.contains("After=network-online.target\n"));
+
+        let unit = unit_file(DEFAULT_BINARY, "config.toml", None);
+        assert!(!unit.contains("# Server:"));
+        assert!(unit.starts_with("# Panoptikon Agent\n[Unit]\n"));
+    }
+}
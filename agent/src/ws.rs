@@ -5,6 +5,7 @@ use tracing::{debug, info, warn};
 
 use crate::collectors::SystemCollector;
 use crate::config::AgentConfig;
+use crate::delta::DeltaEncoder;
 
 /// Run a single WebSocket session: connect, authenticate, then loop sending reports.
 ///
@@ -36,11 +37,17 @@ pub async fn run_session(config: &AgentConfig, collector: &mut SystemCollector)
     let (mut write, mut read) = ws_stream.split();
 
     let interval = std::time::Duration::from_secs(config.report_interval_secs);
+    // A new session starts from a full report, since the server may have lost
+    // whatever the previous connection sent.
+    let mut encoder = config.send_delta.then(DeltaEncoder::new);
 
     loop {
         // Collect system metrics (incremental refresh).
-        let report = collector.collect(config);
-        let json = serde_json::to_string(&report)?;
+        let report = collector.collect(config).await;
+        let json = match encoder {
+            Some(ref mut encoder) => encoder.encode(&report)?,
+            None => serde_json::to_string(&report)?,
+        };
         debug!(bytes = json.len(), "Sending report");
 
         write.send(Message::Text(json)).await?;
@@ -43,7 +43,7 @@ pub async fn list_reports(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Query(params): Query<ReportsQuery>,
-) -> Result<Json<Vec<AgentReportRow>>, StatusCode> {
+) -> Result<Json<Vec<AgentReportRow>>, AppError> {
     let limit = params.limit.clamp(1, 500);
 
     let rows = sqlx::query_as::<_, (i64, Option<f64>, Option<i64>, Option<i64>, String)>(
@@ -56,11 +56,7 @@ pub async fn list_reports(
     .bind(&id)
     .bind(limit)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        error!("Failed to list reports for agent {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let reports: Vec<AgentReportRow> = rows
         .into_iter()
@@ -78,6 +74,443 @@ pub async fn list_reports(
     Ok(Json(reports))
 }
 
+/// Report interval assumed when an agent has too few reports to infer its
+/// own (the agent's default `report_interval_secs`).
+const DEFAULT_REPORT_INTERVAL_SECS: i64 = 30;
+
+/// Maximum look-back window for the uptime history endpoint.
+const UPTIME_HISTORY_MAX_DAYS: i64 = 30;
+
+/// A period during which an agent sent no reports.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DowntimeEvent {
+    pub from: String,
+    pub to: String,
+    pub duration_secs: i64,
+}
+
+/// Agent availability reconstructed from report gaps.
+#[derive(Debug, Serialize)]
+pub struct AgentUptimeHistory {
+    pub uptime_percent: f64,
+    pub downtime_events: Vec<DowntimeEvent>,
+    pub total_downtime_secs: i64,
+}
+
+/// Query parameters for the uptime history endpoint.
+#[derive(Debug, Deserialize)]
+pub struct UptimeHistoryQuery {
+    pub days: Option<i64>,
+}
+
+/// Parse a stored timestamp (RFC 3339 or SQLite `datetime('now')` format).
+fn parse_db_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .map(|ndt| ndt.and_utc())
+        })
+        .ok()
+}
+
+/// An agent's report interval, taken as the median gap between its
+/// consecutive reports so that downtime gaps do not skew it. `None` with
+/// fewer than two reports.
+fn median_report_interval(reports: &[chrono::DateTime<chrono::Utc>]) -> Option<i64> {
+    let mut gaps: Vec<i64> = reports
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds())
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_unstable();
+    Some(gaps[gaps.len() / 2].max(1))
+}
+
+/// Find gaps longer than `2 × interval_secs` between consecutive report times.
+///
+/// The window edges count as boundaries too, so an agent that stopped
+/// reporting before `end` is reported as down until `end`.
+fn detect_downtime(
+    reports: &[chrono::DateTime<chrono::Utc>],
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    interval_secs: i64,
+) -> Vec<DowntimeEvent> {
+    let threshold = interval_secs * 2;
+    let mut events = Vec::new();
+    let mut prev = start;
+
+    for &at in reports.iter().chain(std::iter::once(&end)) {
+        let gap = (at - prev).num_seconds();
+        if gap > threshold {
+            events.push(DowntimeEvent {
+                from: prev.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                to: at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                duration_secs: gap,
+            });
+        }
+        prev = prev.max(at);
+    }
+
+    events
+}
+
+/// Build the uptime history for an agent over the last `days` days.
+async fn agent_uptime_history(
+    db: &sqlx::SqlitePool,
+    id: &str,
+    days: i64,
+) -> Result<AgentUptimeHistory, AppError> {
+    let created_at: String = sqlx::query_scalar("SELECT created_at FROM agents WHERE id = ?")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("agent", "Agent not found".to_string()))?;
+
+    let now = chrono::Utc::now();
+    let cutoff = now - chrono::Duration::days(days);
+    // Time before the agent was registered is not downtime.
+    let start = parse_db_timestamp(&created_at)
+        .map(|created| created.max(cutoff))
+        .unwrap_or(cutoff)
+        .min(now);
+
+    let rows: Vec<String> = sqlx::query_scalar(
+        r#"SELECT reported_at FROM agent_reports
+           WHERE agent_id = ? AND reported_at >= ?
+           ORDER BY reported_at ASC"#,
+    )
+    .bind(id)
+    .bind(cutoff.to_rfc3339())
+    .fetch_all(db)
+    .await?;
+
+    let reports: Vec<_> = rows
+        .iter()
+        .filter_map(|r| parse_db_timestamp(r))
+        .filter(|at| *at >= start && *at <= now)
+        .collect();
+
+    let interval_secs = median_report_interval(&reports).unwrap_or(DEFAULT_REPORT_INTERVAL_SECS);
+    let downtime_events = detect_downtime(&reports, start, now, interval_secs);
+    let total_downtime_secs: i64 = downtime_events.iter().map(|e| e.duration_secs).sum();
+    let total_secs = (now - start).num_seconds();
+    let uptime_percent = if total_secs > 0 {
+        ((total_secs - total_downtime_secs).max(0) as f64 / total_secs as f64) * 100.0
+    } else {
+        100.0
+    };
+
+    Ok(AgentUptimeHistory {
+        uptime_percent,
+        downtime_events,
+        total_downtime_secs,
+    })
+}
+
+/// GET /api/v1/agents/:id/uptime-history?days=30 — agent availability from report gaps.
+pub async fn uptime_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<UptimeHistoryQuery>,
+) -> Result<Json<AgentUptimeHistory>, AppError> {
+    let days = params
+        .days
+        .unwrap_or(UPTIME_HISTORY_MAX_DAYS)
+        .clamp(1, UPTIME_HISTORY_MAX_DAYS);
+    Ok(Json(agent_uptime_history(&state.db, &id, days).await?))
+}
+
+/// Query parameters for the installed packages endpoint.
+#[derive(Debug, Deserialize)]
+pub struct InstalledPackagesQuery {
+    /// Case-insensitive package name prefix.
+    pub search: Option<String>,
+}
+
+/// Response for `GET /api/v1/agents/:id/installed-packages`.
+#[derive(Debug, Serialize)]
+pub struct InstalledPackagesResponse {
+    pub agent_id: String,
+    /// When the package list was reported; `None` if the agent never sent one.
+    pub reported_at: Option<String>,
+    pub total: usize,
+    pub packages: Vec<AgentPackage>,
+}
+
+/// Latest reported package list for an agent, filtered by name prefix.
+async fn installed_packages(
+    db: &sqlx::SqlitePool,
+    id: &str,
+    search: Option<&str>,
+) -> Result<InstalledPackagesResponse, AppError> {
+    let exists: bool = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM agents WHERE id = ?")
+        .bind(id)
+        .fetch_one(db)
+        .await?
+        > 0;
+    if !exists {
+        return Err(AppError::ResourceNotFound(
+            "agent",
+            "Agent not found".to_string(),
+        ));
+    }
+
+    let latest: Option<(String, String)> = sqlx::query_as(
+        r#"SELECT reported_at, packages_json FROM agent_reports
+           WHERE agent_id = ? AND packages_json IS NOT NULL
+           ORDER BY reported_at DESC, id DESC
+           LIMIT 1"#,
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    let (reported_at, mut packages) = match latest {
+        Some((at, json)) => (
+            Some(at),
+            serde_json::from_str::<Vec<AgentPackage>>(&json).unwrap_or_default(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    if let Some(prefix) = search.map(str::to_lowercase).filter(|s| !s.is_empty()) {
+        packages.retain(|p| p.name.to_lowercase().starts_with(&prefix));
+    }
+
+    Ok(InstalledPackagesResponse {
+        agent_id: id.to_string(),
+        reported_at,
+        total: packages.len(),
+        packages,
+    })
+}
+
+/// GET /api/v1/agents/:id/installed-packages?search= — installed software on a host.
+pub async fn list_installed_packages(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<InstalledPackagesQuery>,
+) -> Result<Json<InstalledPackagesResponse>, AppError> {
+    Ok(Json(
+        installed_packages(&state.db, &id, params.search.as_deref()).await?,
+    ))
+}
+
+/// How long the OS distribution is served from cache.
+const OS_DISTRIBUTION_CACHE_SECS: u64 = 120;
+
+/// Number of agents running a given OS name and version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OsVersionCount {
+    pub os: String,
+    pub version: Option<String>,
+    pub count: i64,
+}
+
+/// Agent counts per platform family.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlatformBreakdown {
+    pub linux: i64,
+    pub windows: i64,
+    pub macos: i64,
+    pub other: i64,
+}
+
+/// Response for `GET /api/v1/agents/os-distribution`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OsDistributionResponse {
+    pub distribution: Vec<OsVersionCount>,
+    pub platforms: PlatformBreakdown,
+    pub total_agents: i64,
+    /// Agents whose latest report carries no OS name.
+    pub unknown: i64,
+}
+
+/// Classify an agent into a platform family.
+///
+/// Prefers the registered platform (e.g. `linux-amd64`, `darwin-arm64`) and
+/// falls back to the reported OS name.
+fn platform_family(platform: Option<&str>, os_name: Option<&str>) -> Option<&'static str> {
+    let hint = platform
+        .filter(|p| !p.is_empty())
+        .or(os_name.filter(|o| !o.is_empty()))?
+        .to_ascii_lowercase();
+    if hint.contains("windows") {
+        Some("windows")
+    } else if hint.contains("darwin") || hint.contains("mac") {
+        Some("macos")
+    } else if hint.contains("bsd") {
+        Some("other")
+    } else {
+        // sysinfo reports the distribution name (Ubuntu, Debian, ...) on Linux.
+        Some("linux")
+    }
+}
+
+/// Group agents by the OS name/version of their latest report.
+async fn compute_os_distribution(
+    db: &sqlx::SqlitePool,
+) -> Result<OsDistributionResponse, AppError> {
+    let rows: Vec<(Option<String>, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT a.platform, r.os_name, r.os_version \
+         FROM agents a \
+         LEFT JOIN agent_reports r ON r.agent_id = a.id \
+           AND r.id = ( \
+               SELECT ar.id FROM agent_reports ar \
+               WHERE ar.agent_id = a.id \
+               ORDER BY ar.reported_at DESC, ar.id DESC \
+               LIMIT 1 \
+           )",
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut counts: HashMap<(String, Option<String>), i64> = HashMap::new();
+    let mut platforms = PlatformBreakdown::default();
+    let mut unknown = 0;
+
+    for (platform, os_name, os_version) in &rows {
+        match platform_family(platform.as_deref(), os_name.as_deref()) {
+            Some("linux") => platforms.linux += 1,
+            Some("windows") => platforms.windows += 1,
+            Some("macos") => platforms.macos += 1,
+            Some(_) => platforms.other += 1,
+            None => {}
+        }
+        match os_name.as_deref().filter(|o| !o.is_empty()) {
+            Some(os) => {
+                let version = os_version.clone().filter(|v| !v.is_empty());
+                *counts.entry((os.to_string(), version)).or_insert(0) += 1;
+            }
+            None => unknown += 1,
+        }
+    }
+
+    let mut distribution: Vec<OsVersionCount> = counts
+        .into_iter()
+        .map(|((os, version), count)| OsVersionCount { os, version, count })
+        .collect();
+    distribution.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.os.cmp(&b.os))
+            .then_with(|| a.version.cmp(&b.version))
+    });
+
+    Ok(OsDistributionResponse {
+        distribution,
+        platforms,
+        total_agents: rows.len() as i64,
+        unknown,
+    })
+}
+
+/// GET /api/v1/agents/os-distribution — fleet OS name/version breakdown.
+pub async fn os_distribution(
+    State(state): State<AppState>,
+) -> Result<Json<OsDistributionResponse>, AppError> {
+    let mut cache = state.os_distribution_cache.lock().await;
+    if let Some((computed_at, ref cached)) = *cache {
+        if computed_at.elapsed().as_secs() < OS_DISTRIBUTION_CACHE_SECS {
+            return Ok(Json(cached.clone()));
+        }
+    }
+
+    let response = compute_os_distribution(&state.db).await?;
+    *cache = Some((std::time::Instant::now(), response.clone()));
+    Ok(Json(response))
+}
+
+/// Agent version shipped with this server build.
+pub const LATEST_AGENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// An agent running an older version than [`LATEST_AGENT_VERSION`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutdatedAgent {
+    pub agent_id: String,
+    pub current_version: String,
+    pub latest_version: String,
+}
+
+/// Parse `major.minor.patch` (optionally `v`-prefixed, pre-release and build
+/// suffixes ignored).
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Whether `current` is older than `latest`. Unparseable versions count as
+/// outdated unless they match exactly.
+fn is_outdated(current: &str, latest: &str) -> bool {
+    match (parse_version(current), parse_version(latest)) {
+        (Some(c), Some(l)) => c < l,
+        _ => current.trim() != latest.trim(),
+    }
+}
+
+/// Whether two versions differ in their major or minor component.
+fn minor_version_mismatch(a: &str, b: &str) -> bool {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => (a.0, a.1) != (b.0, b.1),
+        _ => false,
+    }
+}
+
+/// GET /api/v1/agents/outdated — agents reporting a version older than the server's.
+pub async fn outdated(State(state): State<AppState>) -> Result<Json<Vec<OutdatedAgent>>, AppError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, version FROM agents \
+         WHERE version IS NOT NULL AND version != '' \
+         ORDER BY name, id",
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(
+        rows.into_iter()
+            .filter(|(_, version)| is_outdated(version, LATEST_AGENT_VERSION))
+            .map(|(agent_id, current_version)| OutdatedAgent {
+                agent_id,
+                current_version,
+                latest_version: LATEST_AGENT_VERSION.to_string(),
+            })
+            .collect(),
+    ))
+}
+
+/// Tell the UI when a connecting agent is a minor version or more away from
+/// the server.
+fn notify_version_mismatch(state: &AppState, agent_id: &str, agent_version: &str) {
+    if minor_version_mismatch(agent_version, LATEST_AGENT_VERSION) {
+        warn!(
+            agent_id,
+            agent_version,
+            server_version = LATEST_AGENT_VERSION,
+            "Agent version mismatch"
+        );
+        state.ws_hub.broadcast(
+            "agent_version_mismatch",
+            json!({
+                "agent_id": agent_id,
+                "agent_version": agent_version,
+                "server_version": LATEST_AGENT_VERSION,
+            }),
+        );
+    }
+}
+
 /// An agent as returned by the API.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Agent {
@@ -96,6 +529,17 @@ pub struct Agent {
     pub cpu_percent: Option<f64>,
     pub mem_total: Option<i64>,
     pub mem_used: Option<i64>,
+    /// Whether the agent runs in a container (its hostname is then usually
+    /// the container ID).
+    #[serde(default)]
+    pub is_container: bool,
+    pub container_runtime: Option<String>,
+    pub pod_name: Option<String>,
+    pub namespace: Option<String>,
+    pub node_name: Option<String>,
+    /// Name to show for the host: the pod name for containerized agents that
+    /// report one, otherwise the hostname.
+    pub display_name: Option<String>,
 }
 
 /// Request body for registering a new agent.
@@ -130,6 +574,21 @@ pub struct AgentReport {
     pub version: Option<String>,
     #[serde(default)]
     pub network_interfaces: Option<Vec<AgentNetworkInterface>>,
+    /// Installed packages; agents only send these periodically.
+    #[serde(default)]
+    pub packages: Option<Vec<AgentPackage>>,
+    /// Set by agents with `send_delta` enabled: omitted fields are unchanged
+    /// since the previous report.
+    #[serde(default)]
+    pub delta: bool,
+}
+
+/// An installed package as reported by an agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentPackage {
+    pub name: String,
+    pub version: String,
+    pub manager: String,
 }
 
 /// Network interface info from agent report (used for MAC-based device linking and traffic tracking).
@@ -155,6 +614,16 @@ pub struct AgentOsInfo {
     pub version: Option<String>,
     pub kernel: Option<String>,
     pub arch: Option<String>,
+    #[serde(default)]
+    pub is_container: bool,
+    #[serde(default)]
+    pub container_runtime: Option<String>,
+    #[serde(default)]
+    pub pod_name: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub node_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -183,10 +652,24 @@ pub struct AgentAuthMessage {
     #[allow(dead_code)]
     pub api_key: Option<String>,
     pub agent_id: String,
+    /// Agent version; the first message is a full report, which carries it.
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 impl Agent {
     fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let hostname: Option<String> = row.try_get("hostname").ok().flatten();
+        let pod_name: Option<String> = row.try_get("pod_name").ok().flatten();
+        let is_container = row
+            .try_get::<Option<i32>, _>("is_container")
+            .ok()
+            .flatten()
+            .unwrap_or(0)
+            != 0;
+        let display_name = pod_name
+            .filter(|_| is_container)
+            .or_else(|| hostname.clone());
         Ok(Self {
             id: row.try_get("id")?,
             device_id: row.try_get("device_id")?,
@@ -196,22 +679,29 @@ impl Agent {
             is_online: row.try_get::<i32, _>("is_online").unwrap_or(0) != 0,
             last_report_at: row.try_get("last_report_at")?,
             created_at: row.try_get("created_at")?,
-            hostname: row.try_get("hostname").ok(),
+            hostname,
             os_name: row.try_get("os_name").ok(),
             os_version: row.try_get("os_version").ok(),
             cpu_percent: row.try_get("cpu_percent").ok(),
             mem_total: row.try_get("mem_total").ok(),
             mem_used: row.try_get("mem_used").ok(),
+            is_container,
+            container_runtime: row.try_get("container_runtime").ok().flatten(),
+            pod_name: row.try_get("pod_name").ok().flatten(),
+            namespace: row.try_get("k8s_namespace").ok().flatten(),
+            node_name: row.try_get("node_name").ok().flatten(),
+            display_name,
         })
     }
 }
 
 /// GET /api/v1/agents — list all agents.
-pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Agent>>, StatusCode> {
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Agent>>, AppError> {
     let rows = sqlx::query(
         "SELECT a.id, a.device_id, a.name, a.platform, a.version, a.is_online, \
                 a.last_report_at, a.created_at, \
-                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used \
+                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used, \
+                r.is_container, r.container_runtime, r.pod_name, r.k8s_namespace, r.node_name \
          FROM agents a \
          LEFT JOIN agent_reports r ON r.agent_id = a.id \
            AND r.id = ( \
@@ -223,11 +713,7 @@ pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Agent>>, Sta
          ORDER BY a.created_at DESC",
     )
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        error!("Failed to list agents: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let agents: Vec<Agent> = rows
         .into_iter()
@@ -249,7 +735,8 @@ pub async fn get_one(
     let row = sqlx::query(
         "SELECT a.id, a.device_id, a.name, a.platform, a.version, a.is_online, \
                 a.last_report_at, a.created_at, \
-                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used \
+                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used, \
+                r.is_container, r.container_runtime, r.pod_name, r.k8s_namespace, r.node_name \
          FROM agents a \
          LEFT JOIN agent_reports r ON r.agent_id = a.id \
            AND r.id = ( \
@@ -275,24 +762,18 @@ pub async fn get_one(
 pub async fn register(
     State(state): State<AppState>,
     Json(body): Json<RegisterAgent>,
-) -> Result<(StatusCode, Json<RegisterAgentResponse>), StatusCode> {
+) -> Result<(StatusCode, Json<RegisterAgentResponse>), AppError> {
     let id = uuid::Uuid::new_v4().to_string();
     let api_key = format!("pnk_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
-    let api_key_hash = bcrypt::hash(&api_key, bcrypt::DEFAULT_COST).map_err(|e| {
-        error!("Failed to hash API key: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let api_key_hash = bcrypt::hash(&api_key, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::Internal(format!("Failed to hash API key: {e}")))?;
 
     sqlx::query("INSERT INTO agents (id, api_key_hash, name) VALUES (?, ?, ?)")
         .bind(&id)
         .bind(&api_key_hash)
         .bind(&body.name)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to register agent: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     info!(agent_id = %id, "New agent registered");
 
@@ -313,22 +794,19 @@ pub async fn update(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(body): Json<UpdateAgent>,
-) -> Result<Json<Agent>, StatusCode> {
+) -> Result<Json<Agent>, AppError> {
     sqlx::query("UPDATE agents SET name = ? WHERE id = ?")
         .bind(&body.name)
         .bind(&id)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to update agent {id}: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     // Return updated agent
     let row = sqlx::query(
         "SELECT a.id, a.device_id, a.name, a.platform, a.version, a.is_online, \
                 a.last_report_at, a.created_at, \
-                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used \
+                r.hostname, r.os_name, r.os_version, r.cpu_percent, r.mem_total, r.mem_used, \
+                r.is_container, r.container_runtime, r.pod_name, r.k8s_namespace, r.node_name \
          FROM agents a \
          LEFT JOIN agent_reports r ON r.agent_id = a.id \
            AND r.id = ( \
@@ -341,14 +819,12 @@ pub async fn update(
     )
     .bind(&id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .ok_or(StatusCode::NOT_FOUND)?;
+    .await?
+    .ok_or_else(|| AppError::ResourceNotFound("agent", "Agent not found".to_string()))?;
 
-    Agent::from_row(row).map(Json).map_err(|e| {
-        error!("Failed to parse agent row after update: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })
+    Agent::from_row(row)
+        .map(Json)
+        .map_err(|e| AppError::Internal(format!("Failed to parse agent row: {e}")))
 }
 
 /// DELETE /api/v1/agents/:id — remove an agent.
@@ -388,9 +864,11 @@ pub struct BulkDeleteResponse {
 pub async fn bulk_delete(
     State(state): State<AppState>,
     Json(body): Json<BulkDeleteRequest>,
-) -> Result<Json<BulkDeleteResponse>, StatusCode> {
+) -> Result<Json<BulkDeleteResponse>, AppError> {
     if body.ids.is_empty() && body.name_pattern.is_none() {
-        return Err(StatusCode::BAD_REQUEST);
+        return Err(AppError::Validation(
+            "Provide ids or name_pattern".to_string(),
+        ));
     }
 
     let mut total_deleted: u64 = 0;
@@ -407,20 +885,14 @@ pub async fn bulk_delete(
         for id in &ids {
             q = q.bind(id.as_str());
         }
-        let _ = q.execute(&state.db).await.map_err(|e| {
-            error!("Failed to delete agent_reports in bulk: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        let _ = q.execute(&state.db).await?;
 
         let agents_query = format!("DELETE FROM agents WHERE id IN ({placeholders})");
         let mut q = sqlx::query(&agents_query);
         for id in &ids {
             q = q.bind(id.as_str());
         }
-        let result = q.execute(&state.db).await.map_err(|e| {
-            error!("Failed to bulk-delete agents by ID: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        let result = q.execute(&state.db).await?;
         total_deleted += result.rows_affected();
     }
 
@@ -432,20 +904,12 @@ pub async fn bulk_delete(
         )
         .bind(pattern)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to delete agent_reports by pattern: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
         let result = sqlx::query("DELETE FROM agents WHERE name LIKE ?")
             .bind(pattern)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                error!("Failed to bulk-delete agents by pattern: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
+            .await?;
         total_deleted += result.rows_affected();
     }
 
@@ -528,8 +992,8 @@ async fn handle_agent_ws(mut socket: WebSocket, state: AppState, api_key: Option
     info!("Agent WebSocket connection opened");
 
     // Step 1: Verify agent via API key from Authorization header + agent_id from first message.
-    let agent_id = match wait_for_auth(&mut socket, &state, api_key).await {
-        Some(id) => id,
+    let (agent_id, version) = match wait_for_auth(&mut socket, &state, api_key).await {
+        Some(auth) => (auth.agent_id, auth.version),
         None => {
             warn!("Agent WebSocket: auth failed or timed out");
             let _ = socket
@@ -543,11 +1007,19 @@ async fn handle_agent_ws(mut socket: WebSocket, state: AppState, api_key: Option
 
     // Mark agent online in DB.
     let now = chrono::Utc::now().to_rfc3339();
-    let _ = sqlx::query("UPDATE agents SET is_online = 1, last_report_at = ? WHERE id = ?")
-        .bind(&now)
-        .bind(&agent_id)
-        .execute(&state.db)
-        .await;
+    let _ = sqlx::query(
+        "UPDATE agents SET is_online = 1, last_report_at = ?, version = COALESCE(?, version) \
+         WHERE id = ?",
+    )
+    .bind(&now)
+    .bind(&version)
+    .bind(&agent_id)
+    .execute(&state.db)
+    .await;
+
+    if let Some(ref version) = version {
+        notify_version_mismatch(&state, &agent_id, version);
+    }
 
     // Register in hub.
     let mut cmd_rx = state.ws_hub.register_agent(&agent_id).await;
@@ -632,17 +1104,22 @@ async fn handle_agent_ws(mut socket: WebSocket, state: AppState, api_key: Option
 
     if !device_muted {
         let alert_id = uuid::Uuid::new_v4().to_string();
-        let severity = alerts::severity_for_alert_type("agent_offline");
-        let _ = sqlx::query(
+        let severity =
+            alerts::severity_for_alert_type("agent_offline", &state.db, &state.severity_overrides)
+                .await;
+        let inserted = sqlx::query(
             r#"INSERT INTO alerts (id, type, agent_id, message, severity, created_at) VALUES (?, 'agent_offline', ?, ?, ?, ?)"#,
         )
         .bind(&alert_id)
         .bind(&agent_id)
         .bind(format!("Agent {} disconnected", &agent_id))
-        .bind(severity)
+        .bind(&severity)
         .bind(&now)
         .execute(&state.db)
         .await;
+        if inserted.is_ok() {
+            super::metrics::record_alert_created("agent_offline");
+        }
     }
 
     state.ws_hub.unregister_agent(&agent_id).await;
@@ -653,13 +1130,13 @@ async fn handle_agent_ws(mut socket: WebSocket, state: AppState, api_key: Option
     webhook::dispatch_webhook(&state.db, "agent_offline", json!({"agent_id": &agent_id}));
 }
 
-/// Wait for the agent's first message (containing its agent_id) and verify the API key
+/// Wait for the agent's first message (containing its agent_id and version) and verify the API key
 /// that was supplied via the `Authorization: Bearer` header during the WS upgrade.
 async fn wait_for_auth(
     socket: &mut WebSocket,
     state: &AppState,
     api_key: Option<String>,
-) -> Option<String> {
+) -> Option<AgentAuthMessage> {
     // Reject immediately if no API key was provided in the upgrade headers.
     let api_key = match api_key {
         Some(k) if !k.is_empty() => k,
@@ -696,7 +1173,7 @@ async fn wait_for_auth(
     let stored_hash: String = row.try_get("api_key_hash").ok()?;
 
     if bcrypt::verify(&api_key, &stored_hash).unwrap_or(false) {
-        Some(auth.agent_id)
+        Some(auth)
     } else {
         warn!(agent_id = %auth.agent_id, "Agent API key verification failed");
         None
@@ -712,7 +1189,7 @@ async fn wait_for_auth(
 /// - No separator:    `AABBCCDDEEFF`
 ///
 /// Returns `None` if the input is not a valid 6-byte MAC address.
-fn normalize_mac(mac: &str) -> Option<String> {
+pub(crate) fn normalize_mac(mac: &str) -> Option<String> {
     // Only strip known MAC separators (colon, dash, dot) — reject anything else.
     // This prevents accidentally accepting hex digits embedded in arbitrary strings.
     let stripped: String = mac
@@ -735,9 +1212,46 @@ fn normalize_mac(mac: &str) -> Option<String> {
     Some(normalized)
 }
 
+/// Fill the fields a delta report omitted from the agent's latest stored report.
+async fn fill_from_last_report(report: &mut AgentReport, agent_id: &str, db: &sqlx::SqlitePool) {
+    let last = sqlx::query(
+        "SELECT hostname, os_name, os_version, kernel, arch, is_container, \
+                container_runtime, pod_name, k8s_namespace, node_name \
+         FROM agent_reports WHERE agent_id = ? ORDER BY reported_at DESC, id DESC LIMIT 1",
+    )
+    .bind(agent_id)
+    .fetch_optional(db)
+    .await
+    .unwrap_or(None);
+    let Some(last) = last else {
+        return;
+    };
+    let text = |column: &str| last.try_get::<Option<String>, _>(column).ok().flatten();
+
+    if report.hostname.is_none() {
+        report.hostname = text("hostname");
+    }
+    if report.os.is_none() {
+        report.os = Some(AgentOsInfo {
+            name: text("os_name"),
+            version: text("os_version"),
+            kernel: text("kernel"),
+            arch: text("arch"),
+            is_container: last.try_get::<i32, _>("is_container").unwrap_or(0) != 0,
+            container_runtime: text("container_runtime"),
+            pod_name: text("pod_name"),
+            namespace: text("k8s_namespace"),
+            node_name: text("node_name"),
+        });
+    }
+}
+
 /// Process an agent report message and store it in the database.
 async fn handle_agent_report(text: &str, agent_id: &str, state: &AppState) -> anyhow::Result<()> {
-    let report: AgentReport = serde_json::from_str(text)?;
+    let mut report: AgentReport = serde_json::from_str(text)?;
+    if report.delta {
+        fill_from_last_report(&mut report, agent_id, &state.db).await;
+    }
     let now = chrono::Utc::now().to_rfc3339();
 
     // Update agent metadata.
@@ -760,8 +1274,9 @@ async fn handle_agent_report(text: &str, agent_id: &str, state: &AppState) -> an
         "INSERT INTO agent_reports \
          (agent_id, reported_at, hostname, os_name, os_version, kernel, arch, \
           uptime_secs, cpu_count, cpu_percent, load_1m, load_5m, load_15m, \
-          mem_total, mem_used, swap_total, swap_used) \
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+          mem_total, mem_used, swap_total, swap_used, packages_json, \
+          is_container, container_runtime, pod_name, k8s_namespace, node_name) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
     .bind(agent_id)
     .bind(&now)
@@ -780,6 +1295,17 @@ async fn handle_agent_report(text: &str, agent_id: &str, state: &AppState) -> an
     .bind(mem.and_then(|m| m.used_bytes))
     .bind(mem.and_then(|m| m.swap_total_bytes))
     .bind(mem.and_then(|m| m.swap_used_bytes))
+    .bind(
+        report
+            .packages
+            .as_ref()
+            .and_then(|p| serde_json::to_string(p).ok()),
+    )
+    .bind(os.is_some_and(|o| o.is_container))
+    .bind(os.and_then(|o| o.container_runtime.as_deref()))
+    .bind(os.and_then(|o| o.pod_name.as_deref()))
+    .bind(os.and_then(|o| o.namespace.as_deref()))
+    .bind(os.and_then(|o| o.node_name.as_deref()))
     .execute(&state.db)
     .await?;
 
@@ -968,6 +1494,74 @@ async fn handle_agent_report(text: &str, agent_id: &str, state: &AppState) -> an
     Ok(())
 }
 
+/// Installed agent binary for system-wide (root) installs on Linux.
+const LINUX_AGENT_BINARY: &str = "/usr/local/bin/panoptikon-agent";
+/// Agent config file for system-wide installs on Linux.
+const LINUX_AGENT_CONFIG: &str = "/etc/panoptikon-agent/config.toml";
+
+/// Server URL agents should use: the Host header of the incoming request,
+/// falling back to the configured listen address (without hardcoded IPs).
+fn install_server_url(headers: &HeaderMap, state: &AppState) -> String {
+    if let Some(host) = headers.get("host").and_then(|v| v.to_str().ok()) {
+        format!("http://{}", host)
+    } else {
+        let config = state.config();
+        let listen = config.listen.as_deref().unwrap_or("0.0.0.0:8080");
+        format!("http://{}", listen)
+    }
+}
+
+/// systemd unit running the agent binary at `exec` with `config`.
+fn systemd_unit_file(exec: &str, config: &str, server_url: &str) -> String {
+    format!(
+        r#"# Panoptikon Agent
+# Server: {server_url}
+[Unit]
+Description=Panoptikon Agent
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=simple
+ExecStart={exec} --config {config}
+Restart=always
+RestartSec=5
+
+[Install]
+WantedBy=multi-user.target
+"#
+    )
+}
+
+/// GET /api/v1/agent/install/:platform/systemd-unit
+/// Returns the `.service` file for a system-wide agent install
+/// (`/usr/local/bin`, `/etc/panoptikon-agent`). Linux platforms only.
+pub async fn systemd_unit(
+    Path(platform): Path<String>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Response {
+    if !matches!(platform.as_str(), "linux-amd64" | "linux-arm64") {
+        return (
+            StatusCode::BAD_REQUEST,
+            "systemd units are only available for linux-amd64 and linux-arm64",
+        )
+            .into_response();
+    }
+
+    let unit = systemd_unit_file(
+        LINUX_AGENT_BINARY,
+        LINUX_AGENT_CONFIG,
+        &install_server_url(&headers, &state),
+    );
+    (
+        StatusCode::OK,
+        [("content-type", "text/plain; charset=utf-8")],
+        unit,
+    )
+        .into_response()
+}
+
 /// GET /api/v1/agent/install/:platform?key=<api_key>
 /// Returns a shell script that installs the panoptikon-agent on the target platform.
 pub async fn install_script(
@@ -990,14 +1584,7 @@ pub async fn install_script(
             .await
             .unwrap_or(false);
 
-    // Determine server URL: prefer the Host header from the incoming request,
-    // fall back to config listen address (without hardcoded IPs).
-    let server_url = if let Some(host) = headers.get("host").and_then(|v| v.to_str().ok()) {
-        format!("http://{}", host)
-    } else {
-        let listen = state.config.listen.as_deref().unwrap_or("0.0.0.0:8080");
-        format!("http://{}", listen)
-    };
+    let server_url = install_server_url(&headers, &state);
 
     let (_target_triple, _binary_name) = match platform.as_str() {
         "linux-amd64" => ("x86_64-unknown-linux-musl", "panoptikon-agent-linux-amd64"),
@@ -1077,19 +1664,8 @@ TOMLEOF
 if command -v systemctl >/dev/null 2>&1; then
     if [ "$SYSTEMD_SYSTEM" = "1" ]; then
         SERVICE_FILE="/etc/systemd/system/panoptikon-agent.service"
-        cat > "$SERVICE_FILE" <<SVCEOF
-[Unit]
-Description=Panoptikon Agent
-After=network.target
-
-[Service]
-ExecStart=$INSTALL_DIR/panoptikon-agent --config $CONFIG_DIR/config.toml
-Restart=on-failure
-RestartSec=10
-
-[Install]
-WantedBy=multi-user.target
-SVCEOF
+        cat > "$SERVICE_FILE" <<'SVCEOF'
+{system_unit}SVCEOF
         systemctl daemon-reload
         systemctl enable --now panoptikon-agent
         echo "==> Agent installed and started (systemd system)"
@@ -1152,6 +1728,7 @@ echo "==> Done! Agent is reporting to $SERVER_URL"
         platform = platform,
         server_url = server_url,
         api_key = api_key,
+        system_unit = systemd_unit_file(LINUX_AGENT_BINARY, LINUX_AGENT_CONFIG, &server_url),
     );
 
     (
@@ -1331,6 +1908,83 @@ mod tests {
         id
     }
 
+    #[tokio::test]
+    async fn test_delta_report_keeps_unchanged_fields() {
+        let pool = test_db().await;
+        let agent_id = insert_test_agent(&pool).await;
+        let state = crate::api::AppState::new(pool.clone(), crate::config::AppConfig::default());
+
+        let full = serde_json::json!({
+            "agent_id": &agent_id,
+            "hostname": "nas",
+            "os": {"name": "Debian", "version": "12", "kernel": "6.1", "arch": "x86_64"},
+            "cpu": {"count": 4, "usage_percent": 10.0},
+        });
+        super::handle_agent_report(&full.to_string(), &agent_id, &state)
+            .await
+            .unwrap();
+        let delta = serde_json::json!({
+            "agent_id": &agent_id,
+            "delta": true,
+            "cpu": {"count": 4, "usage_percent": 55.0},
+        });
+        super::handle_agent_report(&delta.to_string(), &agent_id, &state)
+            .await
+            .unwrap();
+
+        let (hostname, os_name, kernel, cpu_percent): (String, String, String, f64) =
+            sqlx::query_as(
+                "SELECT hostname, os_name, kernel, cpu_percent FROM agent_reports \
+                 WHERE agent_id = ? ORDER BY id DESC LIMIT 1",
+            )
+            .bind(&agent_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(hostname, "nas");
+        assert_eq!(os_name, "Debian");
+        assert_eq!(kernel, "6.1");
+        assert_eq!(cpu_percent, 55.0);
+    }
+
+    #[tokio::test]
+    async fn test_container_agent_displays_pod_name() {
+        let pool = test_db().await;
+        let agent_id = insert_test_agent(&pool).await;
+        let state = crate::api::AppState::new(pool.clone(), crate::config::AppConfig::default());
+
+        let report = serde_json::json!({
+            "agent_id": &agent_id,
+            "hostname": "agent-7d9f",
+            "os": {
+                "name": "Alpine", "version": "3.20", "kernel": "6.1", "arch": "x86_64",
+                "is_container": true, "container_runtime": "kubernetes",
+                "pod_name": "panoptikon-agent-7d9f", "namespace": "monitoring",
+                "node_name": "worker-2",
+            },
+        });
+        super::handle_agent_report(&report.to_string(), &agent_id, &state)
+            .await
+            .unwrap();
+        let delta = serde_json::json!({"agent_id": &agent_id, "delta": true});
+        super::handle_agent_report(&delta.to_string(), &agent_id, &state)
+            .await
+            .unwrap();
+
+        let axum::Json(agent) = super::get_one(
+            axum::extract::State(state),
+            axum::extract::Path(agent_id.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(agent.is_container);
+        assert_eq!(agent.hostname.as_deref(), Some("agent-7d9f"));
+        assert_eq!(agent.display_name.as_deref(), Some("panoptikon-agent-7d9f"));
+        assert_eq!(agent.container_runtime.as_deref(), Some("kubernetes"));
+        assert_eq!(agent.namespace.as_deref(), Some("monitoring"));
+        assert_eq!(agent.node_name.as_deref(), Some("worker-2"));
+    }
+
     #[tokio::test]
     async fn test_traffic_insert_skipped_no_device() {
         // Agent without device_id → no traffic_samples row inserted.
@@ -1444,4 +2098,297 @@ mod tests {
             "Interval between reports 60s apart should be 60.0, got {interval}"
         );
     }
+
+    #[test]
+    fn test_detect_downtime_finds_gap() {
+        let start = chrono::Utc::now() - chrono::Duration::hours(1);
+        let reports: Vec<_> = (0..5)
+            .map(|i| start + chrono::Duration::seconds(i * 30))
+            .chain((0..5).map(|i| start + chrono::Duration::seconds(720 + i * 30)))
+            .collect();
+        let end = *reports.last().unwrap();
+
+        let events = super::detect_downtime(&reports, start, end, 30);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].duration_secs, 600);
+    }
+
+    #[tokio::test]
+    async fn test_agent_uptime_history_detects_gap() {
+        let pool = test_db().await;
+        let agent_id = insert_test_agent(&pool).await;
+
+        // 10 reports 30s apart, with a 10-minute gap after the fifth, ending now.
+        let now = chrono::Utc::now();
+        let offsets: Vec<i64> = (0..5)
+            .map(|i| 720 + 120 - i * 30)
+            .chain((0..5).map(|i| 120 - i * 30))
+            .collect();
+        let first = now - chrono::Duration::seconds(offsets[0]);
+        for secs in &offsets {
+            let at = (now - chrono::Duration::seconds(*secs)).to_rfc3339();
+            insert_report(&pool, &agent_id, &at, 1.0, 1, 2).await;
+        }
+        sqlx::query("UPDATE agents SET created_at = ? WHERE id = ?")
+            .bind(first.format("%Y-%m-%d %H:%M:%S").to_string())
+            .bind(&agent_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let history = super::agent_uptime_history(&pool, &agent_id, 30)
+            .await
+            .unwrap();
+        assert_eq!(history.downtime_events.len(), 1);
+        assert_eq!(history.downtime_events[0].duration_secs, 600);
+        assert_eq!(history.total_downtime_secs, 600);
+        assert!(history.uptime_percent < 100.0 && history.uptime_percent > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_agent_uptime_history_uses_agent_interval() {
+        let pool = test_db().await;
+        let agent_id = insert_test_agent(&pool).await;
+
+        // An agent configured for 300s reports, with no gaps, ending now.
+        let now = chrono::Utc::now();
+        let first = now - chrono::Duration::seconds(3000);
+        for i in 0..=10 {
+            let at = (first + chrono::Duration::seconds(i * 300)).to_rfc3339();
+            insert_report(&pool, &agent_id, &at, 1.0, 1, 2).await;
+        }
+        sqlx::query("UPDATE agents SET created_at = ? WHERE id = ?")
+            .bind(first.format("%Y-%m-%d %H:%M:%S").to_string())
+            .bind(&agent_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let history = super::agent_uptime_history(&pool, &agent_id, 30)
+            .await
+            .unwrap();
+        assert!(history.downtime_events.is_empty());
+        assert_eq!(history.uptime_percent, 100.0);
+    }
+
+    #[tokio::test]
+    async fn test_agent_uptime_history_unknown_agent() {
+        let pool = test_db().await;
+        let result = super::agent_uptime_history(&pool, "missing", 30).await;
+        assert!(matches!(
+            result,
+            Err(crate::api::AppError::ResourceNotFound("agent", _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_os_distribution_groups_by_latest_report() {
+        let pool = test_db().await;
+
+        let reports = [
+            ("Ubuntu", "22.04", "2026-01-01T10:00:00Z"),
+            ("Ubuntu", "22.04", "2026-01-01T10:00:00Z"),
+            ("Debian", "12", "2026-01-01T10:00:00Z"),
+        ];
+        for (os, version, at) in reports {
+            let agent_id = insert_test_agent(&pool).await;
+            // An older report with a different OS must be ignored.
+            sqlx::query(
+                "INSERT INTO agent_reports (agent_id, reported_at, os_name, os_version) \
+                 VALUES (?, '2025-01-01T00:00:00Z', 'Fedora', '39')",
+            )
+            .bind(&agent_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO agent_reports (agent_id, reported_at, os_name, os_version) \
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(&agent_id)
+            .bind(at)
+            .bind(os)
+            .bind(version)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        // An agent that never reported.
+        insert_test_agent(&pool).await;
+
+        let result = super::compute_os_distribution(&pool).await.unwrap();
+        assert_eq!(result.total_agents, 4);
+        assert_eq!(result.unknown, 1);
+        assert_eq!(
+            result.distribution,
+            vec![
+                super::OsVersionCount {
+                    os: "Ubuntu".to_string(),
+                    version: Some("22.04".to_string()),
+                    count: 2,
+                },
+                super::OsVersionCount {
+                    os: "Debian".to_string(),
+                    version: Some("12".to_string()),
+                    count: 1,
+                },
+            ]
+        );
+        assert_eq!(result.platforms.linux, 3);
+        assert_eq!(result.platforms.macos, 0);
+    }
+
+    #[test]
+    fn test_platform_family() {
+        assert_eq!(
+            super::platform_family(Some("darwin-arm64"), Some("Ubuntu")),
+            Some("macos")
+        );
+        assert_eq!(
+            super::platform_family(None, Some("Windows 11")),
+            Some("windows")
+        );
+        assert_eq!(
+            super::platform_family(Some(""), Some("Debian")),
+            Some("linux")
+        );
+        assert_eq!(super::platform_family(None, None), None);
+    }
+
+    #[tokio::test]
+    async fn test_installed_packages_latest_report_and_prefix_search() {
+        let pool = test_db().await;
+        let agent_id = insert_test_agent(&pool).await;
+
+        for (at, json) in [
+            (
+                "2026-01-01T10:00:00Z",
+                r#"[{"name":"openssl","version":"1.1.1","manager":"dpkg"}]"#,
+            ),
+            (
+                "2026-01-02T10:00:00Z",
+                r#"[{"name":"openssl","version":"3.0.2","manager":"dpkg"},
+                    {"name":"libssl3","version":"3.0.2","manager":"dpkg"},
+                    {"name":"OpenSSH-server","version":"8.9","manager":"dpkg"}]"#,
+            ),
+        ] {
+            sqlx::query(
+                "INSERT INTO agent_reports (agent_id, reported_at, packages_json) VALUES (?, ?, ?)",
+            )
+            .bind(&agent_id)
+            .bind(at)
+            .bind(json)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        // A newer report without packages must not hide the list.
+        insert_report(&pool, &agent_id, "2026-01-03T10:00:00Z", 1.0, 1, 2).await;
+
+        let all = super::installed_packages(&pool, &agent_id, None)
+            .await
+            .unwrap();
+        assert_eq!(all.total, 3);
+        assert_eq!(all.reported_at.as_deref(), Some("2026-01-02T10:00:00Z"));
+
+        let open = super::installed_packages(&pool, &agent_id, Some("open"))
+            .await
+            .unwrap();
+        let names: Vec<_> = open.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["openssl", "OpenSSH-server"]);
+        assert_eq!(open.packages[0].version, "3.0.2");
+    }
+
+    #[tokio::test]
+    async fn test_systemd_unit() {
+        use axum::body::to_bytes;
+        use axum::extract::{Path, State};
+        use axum::http::{HeaderMap, HeaderValue, StatusCode};
+
+        let state = crate::api::AppState::new(test_db().await, Default::default());
+        let mut headers = HeaderMap::new();
+        headers.insert("host", HeaderValue::from_static("10.0.0.1:8080"));
+
+        let resp = super::systemd_unit(
+            Path("linux-amd64".to_string()),
+            headers.clone(),
+            State(state.clone()),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let unit = String::from_utf8(body.to_vec()).unwrap();
+        assert!(unit.contains("# Server: http://10.0.0.1:8080\n"));
+        assert!(unit.contains(
+            "ExecStart=/usr/local/bin/panoptikon-agent --config /etc/panoptikon-agent/config.toml\n"
+        ));
+        assert!(unit.contains("Restart=always\nRestartSec=5\n"));
+        assert!(unit.contains("After=network-online.target\n"));
+
+        let resp =
+            super::systemd_unit(Path("darwin-arm64".to_string()), headers, State(state)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_version_comparison() {
+        use super::{is_outdated, minor_version_mismatch, parse_version};
+
+        assert_eq!(parse_version("v1.2.3-beta+7"), Some((1, 2, 3)));
+        assert_eq!(parse_version("0.4"), Some((0, 4, 0)));
+        assert_eq!(parse_version("dev"), None);
+
+        assert!(is_outdated("0.1.9", "0.2.0"));
+        assert!(!is_outdated("0.2.0", "0.2.0"));
+        assert!(!is_outdated("0.3.0", "0.2.0"));
+        assert!(is_outdated("dev", "0.2.0"));
+
+        assert!(minor_version_mismatch("0.1.9", "0.2.0"));
+        assert!(minor_version_mismatch("1.2.0", "0.2.0"));
+        assert!(!minor_version_mismatch("0.2.5", "0.2.0"));
+        assert!(!minor_version_mismatch("dev", "0.2.0"));
+    }
+
+    #[tokio::test]
+    async fn test_outdated_agents_and_mismatch_event() {
+        use super::LATEST_AGENT_VERSION;
+        use axum::extract::State;
+
+        let pool = test_db().await;
+        for (id, version) in [
+            ("a-old", Some("0.0.1")),
+            ("a-current", Some(LATEST_AGENT_VERSION)),
+            ("a-unknown", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO agents (id, api_key_hash, name, version) VALUES (?, 'x', ?, ?)",
+            )
+            .bind(id)
+            .bind(id)
+            .bind(version)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let state = crate::api::AppState::new(pool, Default::default());
+
+        let outdated = super::outdated(State(state.clone())).await.unwrap().0;
+        assert_eq!(
+            outdated,
+            vec![super::OutdatedAgent {
+                agent_id: "a-old".to_string(),
+                current_version: "0.0.1".to_string(),
+                latest_version: LATEST_AGENT_VERSION.to_string(),
+            }]
+        );
+
+        let mut rx = state.ws_hub.subscribe_ui();
+        super::notify_version_mismatch(&state, "a-current", LATEST_AGENT_VERSION);
+        assert!(rx.try_recv().is_err());
+        super::notify_version_mismatch(&state, "a-old", "0.0.1");
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.event, "agent_version_mismatch");
+        assert_eq!(msg.payload["agent_id"], "a-old");
+        assert_eq!(msg.payload["agent_version"], "0.0.1");
+    }
 }
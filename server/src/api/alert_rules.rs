@@ -0,0 +1,623 @@
+//! Operator-defined alert trigger rules.
+//!
+//! A rule targets one scanner event type and carries a simple condition
+//! (all present fields must match). `suppress` rules drop matching alerts and
+//! always win. If any `create` rule exists for an event type, alerts of that
+//! type are only raised when one of them matches, using its severity if set.
+//! Event types without rules keep the default behaviour.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use tracing::{info, warn};
+
+use super::alerts::normalize_severity;
+use super::{AppError, AppState};
+
+/// Scanner events that alert rules can target.
+pub const RULE_EVENT_TYPES: &[&str] = &[
+    "new_device",
+    "device_online",
+    "device_offline",
+    "mac_conflict",
+    "arp_spoofing",
+    "ip_conflict",
+    "ip_change",
+];
+
+/// What a matching rule does to the alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Create,
+    Suppress,
+}
+
+impl RuleAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            RuleAction::Create => "create",
+            RuleAction::Suppress => "suppress",
+        }
+    }
+}
+
+/// Filter stored in `condition_json`. Every field that is set must match.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertCondition {
+    /// Case-insensitive substring of the OUI vendor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_contains: Option<String>,
+    /// `true` matches devices without a known vendor, `false` those with one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_unknown: Option<bool>,
+    /// Case-insensitive MAC prefix, e.g. `"b8:27:eb"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac_prefix: Option<String>,
+    /// IP address prefix, e.g. `"192.168.10."`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_prefix: Option<String>,
+    /// Case-insensitive substring of the hostname.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname_contains: Option<String>,
+    /// Minimum time the device has been (or was) offline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_offline_secs: Option<u64>,
+}
+
+/// Device facts a condition is evaluated against.
+#[derive(Debug, Default)]
+pub struct AlertContext<'a> {
+    pub mac: &'a str,
+    pub ip: Option<&'a str>,
+    pub vendor: Option<&'a str>,
+    pub hostname: Option<&'a str>,
+    /// Offline duration: current outage for `device_offline`, the outage
+    /// that just ended for `device_online`.
+    pub offline_secs: Option<u64>,
+}
+
+fn contains_ignore_case(haystack: Option<&str>, needle: &str) -> bool {
+    haystack.is_some_and(|h| h.to_lowercase().contains(&needle.to_lowercase()))
+}
+
+impl AlertCondition {
+    /// Whether every set field matches `ctx`.
+    pub fn matches(&self, ctx: &AlertContext) -> bool {
+        if let Some(ref needle) = self.vendor_contains {
+            if !contains_ignore_case(ctx.vendor, needle) {
+                return false;
+            }
+        }
+        if let Some(unknown) = self.vendor_unknown {
+            if ctx.vendor.is_none_or(str::is_empty) != unknown {
+                return false;
+            }
+        }
+        if let Some(ref prefix) = self.mac_prefix {
+            if !ctx.mac.to_lowercase().starts_with(&prefix.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(ref prefix) = self.ip_prefix {
+            if !ctx.ip.is_some_and(|ip| ip.starts_with(prefix.as_str())) {
+                return false;
+            }
+        }
+        if let Some(ref needle) = self.hostname_contains {
+            if !contains_ignore_case(ctx.hostname, needle) {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_offline_secs {
+            if ctx.offline_secs.is_none_or(|secs| secs < min) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// An alert rule as returned by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    pub event_type: String,
+    pub condition: AlertCondition,
+    pub action: RuleAction,
+    pub severity: Option<String>,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+impl AlertRule {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let condition_json: String = row.try_get("condition_json")?;
+        let action: String = row.try_get("action")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            event_type: row.try_get("event_type")?,
+            condition: serde_json::from_str(&condition_json)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            action: if action == "suppress" {
+                RuleAction::Suppress
+            } else {
+                RuleAction::Create
+            },
+            severity: row.try_get("severity")?,
+            enabled: row.try_get::<i32, _>("enabled")? != 0,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Outcome of evaluating the rules for one event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertDecision {
+    /// Drop the alert.
+    Suppress,
+    /// Raise the alert, with a rule-provided severity if any.
+    Create { severity: Option<String> },
+}
+
+impl AlertDecision {
+    /// Severity to store: the rule's, else `default`.
+    pub fn severity_or<'a>(&'a self, default: &'a str) -> Option<&'a str> {
+        match self {
+            AlertDecision::Suppress => None,
+            AlertDecision::Create { severity } => Some(severity.as_deref().unwrap_or(default)),
+        }
+    }
+}
+
+/// Enabled rules, loaded once per scan.
+#[derive(Debug, Clone, Default)]
+pub struct AlertRuleSet {
+    rules: Vec<AlertRule>,
+}
+
+impl AlertRuleSet {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules: rules.into_iter().filter(|r| r.enabled).collect(),
+        }
+    }
+
+    /// Decide whether an alert of `event_type` should be raised for `ctx`.
+    pub fn decide(&self, event_type: &str, ctx: &AlertContext) -> AlertDecision {
+        let applicable = || self.rules.iter().filter(|r| r.event_type == event_type);
+
+        if applicable().any(|r| r.action == RuleAction::Suppress && r.condition.matches(ctx)) {
+            return AlertDecision::Suppress;
+        }
+
+        let mut creates = applicable()
+            .filter(|r| r.action == RuleAction::Create)
+            .peekable();
+        if creates.peek().is_none() {
+            return AlertDecision::Create { severity: None };
+        }
+        match creates.find(|r| r.condition.matches(ctx)) {
+            Some(rule) => AlertDecision::Create {
+                severity: rule.severity.clone(),
+            },
+            None => AlertDecision::Suppress,
+        }
+    }
+
+    /// Whether some `create` rule for `event_type` waits on an offline duration,
+    /// so offline devices must be re-evaluated on later scans.
+    pub fn has_delayed_rules(&self, event_type: &str) -> bool {
+        self.rules.iter().any(|r| {
+            r.event_type == event_type
+                && r.action == RuleAction::Create
+                && r.condition.min_offline_secs.is_some()
+        })
+    }
+}
+
+const SELECT_RULES: &str = "SELECT id, name, event_type, condition_json, action, severity, \
+     enabled, created_at FROM alert_rules";
+
+/// Load the enabled rules. On error, logs and falls back to no rules.
+pub async fn load_rule_set(db: &SqlitePool) -> AlertRuleSet {
+    let rows = match sqlx::query(&format!("{SELECT_RULES} WHERE enabled = 1"))
+        .fetch_all(db)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(error = %e, "Failed to load alert rules");
+            return AlertRuleSet::default();
+        }
+    };
+    let rules = rows
+        .into_iter()
+        .filter_map(|row| match AlertRule::from_row(row) {
+            Ok(rule) => Some(rule),
+            Err(e) => {
+                warn!(error = %e, "Skipping unreadable alert rule");
+                None
+            }
+        })
+        .collect();
+    AlertRuleSet::new(rules)
+}
+
+/// Request body for creating an alert rule.
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertRule {
+    pub name: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub condition: AlertCondition,
+    pub action: RuleAction,
+    pub severity: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// Request body for updating an alert rule. Omitted fields are unchanged;
+/// an empty `severity` clears it.
+#[derive(Debug, Deserialize)]
+pub struct UpdateAlertRule {
+    pub name: Option<String>,
+    pub event_type: Option<String>,
+    pub condition: Option<AlertCondition>,
+    pub action: Option<RuleAction>,
+    pub severity: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("Rule name is required".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_event_type(event_type: &str) -> Result<String, AppError> {
+    if !RULE_EVENT_TYPES.contains(&event_type) {
+        return Err(AppError::Validation(format!(
+            "Unsupported event_type '{event_type}'; expected one of {}",
+            RULE_EVENT_TYPES.join(", ")
+        )));
+    }
+    Ok(event_type.to_string())
+}
+
+/// Normalize a severity; empty means "use the alert type's default".
+fn validate_severity(severity: Option<&str>) -> Result<Option<String>, AppError> {
+    match severity.map(str::trim).filter(|s| !s.is_empty()) {
+        None => Ok(None),
+        Some(s) => normalize_severity(s)
+            .map(|s| Some(s.to_string()))
+            .ok_or_else(|| AppError::Validation(format!("Invalid severity '{s}'"))),
+    }
+}
+
+async fn fetch_rule(db: &SqlitePool, id: &str) -> Result<AlertRule, AppError> {
+    let row = sqlx::query(&format!("{SELECT_RULES} WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::ResourceNotFound("alert_rule", "Alert rule not found".to_string())
+        })?;
+    Ok(AlertRule::from_row(row)?)
+}
+
+/// GET /api/v1/alert-rules — list all rules.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<AlertRule>>, AppError> {
+    let rows = sqlx::query(&format!("{SELECT_RULES} ORDER BY created_at, name"))
+        .fetch_all(&state.db)
+        .await?;
+    let rules = rows
+        .into_iter()
+        .map(AlertRule::from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(rules))
+}
+
+/// POST /api/v1/alert-rules — create a rule.
+pub async fn create(
+    State(state): State<AppState>,
+    Json(body): Json<CreateAlertRule>,
+) -> Result<(StatusCode, Json<AlertRule>), AppError> {
+    let name = validate_name(&body.name)?;
+    let event_type = validate_event_type(&body.event_type)?;
+    let severity = validate_severity(body.severity.as_deref())?;
+    let condition_json = serde_json::to_string(&body.condition)
+        .map_err(|e| AppError::Internal(format!("Failed to encode condition: {e}")))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO alert_rules (id, name, event_type, condition_json, action, severity, enabled) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&name)
+    .bind(&event_type)
+    .bind(&condition_json)
+    .bind(body.action.as_str())
+    .bind(&severity)
+    .bind(body.enabled.unwrap_or(true))
+    .execute(&state.db)
+    .await?;
+
+    info!(rule_id = %id, name = %name, event_type = %event_type, "Alert rule created");
+    Ok((StatusCode::CREATED, Json(fetch_rule(&state.db, &id).await?)))
+}
+
+/// PATCH /api/v1/alert-rules/:id — update a rule.
+pub async fn update(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateAlertRule>,
+) -> Result<Json<AlertRule>, AppError> {
+    let mut rule = fetch_rule(&state.db, &id).await?;
+
+    if let Some(ref name) = body.name {
+        rule.name = validate_name(name)?;
+    }
+    if let Some(ref event_type) = body.event_type {
+        rule.event_type = validate_event_type(event_type)?;
+    }
+    if let Some(condition) = body.condition {
+        rule.condition = condition;
+    }
+    if let Some(action) = body.action {
+        rule.action = action;
+    }
+    if body.severity.is_some() {
+        rule.severity = validate_severity(body.severity.as_deref())?;
+    }
+    if let Some(enabled) = body.enabled {
+        rule.enabled = enabled;
+    }
+
+    let condition_json = serde_json::to_string(&rule.condition)
+        .map_err(|e| AppError::Internal(format!("Failed to encode condition: {e}")))?;
+    sqlx::query(
+        "UPDATE alert_rules SET name = ?, event_type = ?, condition_json = ?, action = ?, \
+         severity = ?, enabled = ? WHERE id = ?",
+    )
+    .bind(&rule.name)
+    .bind(&rule.event_type)
+    .bind(&condition_json)
+    .bind(rule.action.as_str())
+    .bind(&rule.severity)
+    .bind(rule.enabled)
+    .bind(&id)
+    .execute(&state.db)
+    .await?;
+
+    info!(rule_id = %id, "Alert rule updated");
+    Ok(Json(rule))
+}
+
+/// DELETE /api/v1/alert-rules/:id — delete a rule.
+pub async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM alert_rules WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "alert_rule",
+            "Alert rule not found".to_string(),
+        ));
+    }
+    info!(rule_id = %id, "Alert rule deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(event_type: &str, action: RuleAction, condition: AlertCondition) -> AlertRule {
+        AlertRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "test".to_string(),
+            event_type: event_type.to_string(),
+            condition,
+            action,
+            severity: None,
+            enabled: true,
+            created_at: String::new(),
+        }
+    }
+
+    fn ctx<'a>(vendor: Option<&'a str>, offline_secs: Option<u64>) -> AlertContext<'a> {
+        AlertContext {
+            mac: "b8:27:eb:00:00:01",
+            ip: Some("192.168.1.20"),
+            vendor,
+            hostname: Some("pi.lan"),
+            offline_secs,
+        }
+    }
+
+    #[test]
+    fn test_condition_matches() {
+        let cond: AlertCondition = serde_json::from_str(
+            r#"{"vendor_contains": "raspberry", "mac_prefix": "B8:27:EB", "ip_prefix": "192.168.1."}"#,
+        )
+        .unwrap();
+        assert!(cond.matches(&ctx(Some("Raspberry Pi Foundation"), None)));
+        assert!(!cond.matches(&ctx(Some("Apple, Inc."), None)));
+        assert!(!cond.matches(&ctx(None, None)));
+
+        let unknown = AlertCondition {
+            vendor_unknown: Some(true),
+            ..Default::default()
+        };
+        assert!(unknown.matches(&ctx(None, None)));
+        assert!(!unknown.matches(&ctx(Some("Apple, Inc."), None)));
+
+        let long_outage = AlertCondition {
+            min_offline_secs: Some(7200),
+            ..Default::default()
+        };
+        assert!(long_outage.matches(&ctx(None, Some(7200))));
+        assert!(!long_outage.matches(&ctx(None, Some(600))));
+        assert!(!long_outage.matches(&ctx(None, None)));
+    }
+
+    #[test]
+    fn test_condition_rejects_unknown_fields() {
+        assert!(serde_json::from_str::<AlertCondition>(r#"{"vendor": "Apple"}"#).is_err());
+    }
+
+    #[test]
+    fn test_decide_without_rules_creates() {
+        let rules = AlertRuleSet::default();
+        assert_eq!(
+            rules.decide("new_device", &ctx(None, None)),
+            AlertDecision::Create { severity: None }
+        );
+    }
+
+    #[test]
+    fn test_decide_create_rules_act_as_allow_list() {
+        let mut only_unknown = rule(
+            "new_device",
+            RuleAction::Create,
+            AlertCondition {
+                vendor_unknown: Some(true),
+                ..Default::default()
+            },
+        );
+        only_unknown.severity = Some("CRITICAL".to_string());
+        let rules = AlertRuleSet::new(vec![only_unknown]);
+
+        assert_eq!(
+            rules.decide("new_device", &ctx(None, None)),
+            AlertDecision::Create {
+                severity: Some("CRITICAL".to_string())
+            }
+        );
+        assert_eq!(
+            rules.decide("new_device", &ctx(Some("Apple, Inc."), None)),
+            AlertDecision::Suppress
+        );
+        // Other event types are unaffected.
+        assert_eq!(
+            rules.decide("device_online", &ctx(Some("Apple, Inc."), None)),
+            AlertDecision::Create { severity: None }
+        );
+    }
+
+    #[test]
+    fn test_decide_suppress_takes_precedence() {
+        let rules = AlertRuleSet::new(vec![
+            rule("new_device", RuleAction::Create, AlertCondition::default()),
+            rule(
+                "new_device",
+                RuleAction::Suppress,
+                AlertCondition {
+                    vendor_contains: Some("apple".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        assert_eq!(
+            rules.decide("new_device", &ctx(Some("Apple, Inc."), None)),
+            AlertDecision::Suppress
+        );
+        assert_eq!(
+            rules.decide("new_device", &ctx(Some("Raspberry Pi"), None)),
+            AlertDecision::Create { severity: None }
+        );
+    }
+
+    #[test]
+    fn test_disabled_rules_are_ignored() {
+        let mut suppress_all = rule(
+            "new_device",
+            RuleAction::Suppress,
+            AlertCondition::default(),
+        );
+        suppress_all.enabled = false;
+        let rules = AlertRuleSet::new(vec![suppress_all]);
+        assert_eq!(
+            rules.decide("new_device", &ctx(None, None)),
+            AlertDecision::Create { severity: None }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_crud_round_trip() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        let body: CreateAlertRule = serde_json::from_value(serde_json::json!({
+            "name": "Apple devices are fine",
+            "event_type": "new_device",
+            "condition": {"vendor_contains": "Apple"},
+            "action": "suppress",
+        }))
+        .unwrap();
+        let (status, Json(created)) = create(State(state.clone()), Json(body)).await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(created.enabled);
+
+        let update_body: UpdateAlertRule = serde_json::from_value(serde_json::json!({
+            "enabled": false,
+            "severity": "critical",
+        }))
+        .unwrap();
+        let Json(updated) = update(
+            State(state.clone()),
+            Path(created.id.clone()),
+            Json(update_body),
+        )
+        .await
+        .unwrap();
+        assert!(!updated.enabled);
+        assert_eq!(updated.severity.as_deref(), Some("CRITICAL"));
+        assert!(load_rule_set(&state.db).await.rules.is_empty());
+
+        let Json(all) = list(State(state.clone())).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].condition.vendor_contains.as_deref(), Some("Apple"));
+
+        assert_eq!(
+            delete(State(state.clone()), Path(created.id.clone()))
+                .await
+                .unwrap(),
+            StatusCode::NO_CONTENT
+        );
+        assert!(matches!(
+            delete(State(state), Path(created.id)).await,
+            Err(AppError::ResourceNotFound("alert_rule", _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_invalid_event_type() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+        let body: CreateAlertRule = serde_json::from_value(serde_json::json!({
+            "name": "bogus",
+            "event_type": "agent_offline",
+            "action": "create",
+        }))
+        .unwrap();
+        assert!(matches!(
+            create(State(state), Json(body)).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+}
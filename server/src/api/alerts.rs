@@ -4,9 +4,13 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
 
-use super::AppState;
+use super::{AppError, AppState};
 
 /// An alert as returned by the API.
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +27,10 @@ pub struct Alert {
     pub acknowledged_at: Option<String>,
     pub acknowledged_by: Option<String>,
     pub created_at: String,
+    /// Number of occurrences folded into this alert by deduplication.
+    pub count: i64,
+    /// When the alert last recurred within the dedup window.
+    pub updated_at: Option<String>,
 }
 
 /// Query parameters for listing alerts.
@@ -60,6 +68,8 @@ impl Alert {
             acknowledged_at: row.try_get("acknowledged_at").unwrap_or(None),
             acknowledged_by: row.try_get("acknowledged_by").unwrap_or(None),
             created_at: row.try_get("created_at")?,
+            count: row.try_get("count").unwrap_or(1),
+            updated_at: row.try_get("updated_at").unwrap_or(None),
         })
     }
 }
@@ -68,7 +78,7 @@ impl Alert {
 pub async fn list(
     State(state): State<AppState>,
     Query(params): Query<ListAlertsQuery>,
-) -> Result<Json<Vec<Alert>>, StatusCode> {
+) -> Result<Json<Vec<Alert>>, AppError> {
     let limit = params.limit.unwrap_or(50);
     let unread_only = params.unread_only.unwrap_or(false);
 
@@ -101,18 +111,14 @@ pub async fn list(
     };
 
     let query_str = format!(
-        r#"SELECT id, type, device_id, agent_id, message, details, is_read, severity, acknowledged_at, acknowledged_by, created_at
+        r#"SELECT id, type, device_id, agent_id, message, details, is_read, severity, acknowledged_at, acknowledged_by, created_at, count, updated_at
          FROM alerts {where_clause} ORDER BY created_at DESC LIMIT ?"#
     );
 
     let rows = sqlx::query(&query_str)
         .bind(limit)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to list alerts: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     let alerts: Vec<Alert> = rows
         .into_iter()
@@ -126,32 +132,27 @@ pub async fn list(
 pub async fn mark_read(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let result = sqlx::query("UPDATE alerts SET is_read = 1 WHERE id = ?")
         .bind(&id)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to mark alert {id} as read: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "alert",
+            "Alert not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// POST /api/v1/alerts/mark-all-read — mark all unread alerts as read.
-pub async fn mark_all_read(State(state): State<AppState>) -> Result<StatusCode, StatusCode> {
+pub async fn mark_all_read(State(state): State<AppState>) -> Result<StatusCode, AppError> {
     sqlx::query("UPDATE alerts SET is_read = 1 WHERE is_read = 0")
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to mark all alerts as read: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -161,21 +162,20 @@ pub async fn acknowledge(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(body): Json<AcknowledgeBody>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let result = sqlx::query(
         r#"UPDATE alerts SET acknowledged_at = datetime('now'), acknowledged_by = ?, is_read = 1 WHERE id = ?"#,
     )
     .bind(&body.note)
     .bind(&id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to acknowledge alert {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "alert",
+            "Alert not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -185,32 +185,25 @@ pub async fn acknowledge(
 pub async fn delete_one(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let result = sqlx::query("DELETE FROM alerts WHERE id = ?")
         .bind(&id)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to delete alert {id}: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "alert",
+            "Alert not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// DELETE /api/v1/alerts — delete all alerts.
-pub async fn delete_all(State(state): State<AppState>) -> Result<StatusCode, StatusCode> {
-    sqlx::query("DELETE FROM alerts")
-        .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to delete all alerts: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+pub async fn delete_all(State(state): State<AppState>) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM alerts").execute(&state.db).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -227,7 +220,7 @@ pub async fn mute_device(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Query(params): Query<MuteQuery>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let hours = params.hours.unwrap_or(1);
 
     let result = if hours <= 0 {
@@ -244,14 +237,13 @@ pub async fn mute_device(
         .bind(&id)
         .execute(&state.db)
         .await
-    }
-    .map_err(|e| {
-        tracing::error!("Failed to mute device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    }?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "device",
+            "Device not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -278,16 +270,138 @@ where
     result.is_some()
 }
 
-/// Determine the severity level for an alert type.
-pub fn severity_for_alert_type(alert_type: &str) -> &'static str {
+/// Alert types that support severity overrides.
+pub const ALERT_TYPES: &[&str] = &[
+    "new_device",
+    "device_online",
+    "device_offline",
+    "agent_offline",
+    "high_bandwidth",
+    "dhcp_lease_expiring",
+    "mac_conflict",
+    "arp_spoofing",
+    "ip_conflict",
+    "ip_change",
+    "traffic_spike",
+    "new_port_open",
+    "port_closed",
+    "cert_expiring",
+    "cert_expired",
+];
+
+/// Settings key holding the JSON map of per-alert-type severity overrides.
+pub const SEVERITY_OVERRIDES_KEY: &str = "alert_severity_overrides";
+
+/// How long severity overrides are cached before being re-read from the DB.
+const SEVERITY_CACHE_SECS: u64 = 60;
+
+/// Compiled-in default severity level for an alert type.
+pub fn default_severity_for_alert_type(alert_type: &str) -> &'static str {
     match alert_type {
         "new_device" => "INFO",
-        "device_online" => "INFO",
-        "device_offline" | "agent_offline" | "high_bandwidth" => "WARNING",
+        "device_online" | "ip_change" | "port_closed" => "INFO",
+        "device_offline"
+        | "agent_offline"
+        | "high_bandwidth"
+        | "dhcp_lease_expiring"
+        | "mac_conflict"
+        | "ip_conflict"
+        | "traffic_spike"
+        | "new_port_open"
+        | "cert_expiring" => "WARNING",
+        "arp_spoofing" | "cert_expired" => "CRITICAL",
         _ => "WARNING",
     }
 }
 
+/// Normalize a severity string to its stored form, or `None` if it is not recognised.
+pub fn normalize_severity(severity: &str) -> Option<&'static str> {
+    match severity.to_uppercase().as_str() {
+        "INFO" => Some("INFO"),
+        "WARNING" => Some("WARNING"),
+        "CRITICAL" => Some("CRITICAL"),
+        _ => None,
+    }
+}
+
+/// Severity overrides keyed by alert type, e.g. `"new_device" → "CRITICAL"`.
+type SeverityOverrides = HashMap<String, String>;
+
+/// Cached per-alert-type severity overrides read from the settings table.
+///
+/// Cloning is cheap; all clones share the same cache.
+#[derive(Clone, Default)]
+pub struct SeverityOverrideCache {
+    inner: Arc<Mutex<Option<(Instant, SeverityOverrides)>>>,
+}
+
+impl SeverityOverrideCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the current overrides, re-reading the DB if the cache is stale.
+    pub async fn get(&self, db: &SqlitePool) -> SeverityOverrides {
+        let mut cached = self.inner.lock().await;
+        if let Some((loaded_at, ref overrides)) = *cached {
+            if loaded_at.elapsed().as_secs() < SEVERITY_CACHE_SECS {
+                return overrides.clone();
+            }
+        }
+        let overrides = load_severity_overrides(db).await;
+        *cached = Some((Instant::now(), overrides.clone()));
+        overrides
+    }
+
+    /// Drop the cached overrides so the next lookup reads the DB.
+    pub async fn invalidate(&self) {
+        *self.inner.lock().await = None;
+    }
+}
+
+/// Read severity overrides from the settings table.
+///
+/// Unknown severities are ignored; malformed JSON yields no overrides.
+pub async fn load_severity_overrides(db: &SqlitePool) -> HashMap<String, String> {
+    let raw: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+        .bind(SEVERITY_OVERRIDES_KEY)
+        .fetch_optional(db)
+        .await
+        .unwrap_or(None);
+
+    let Some(raw) = raw.filter(|v| !v.is_empty()) else {
+        return HashMap::new();
+    };
+
+    match serde_json::from_str::<HashMap<String, String>>(&raw) {
+        Ok(map) => map
+            .into_iter()
+            .filter_map(|(k, v)| normalize_severity(&v).map(|sev| (k, sev.to_string())))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Ignoring malformed {SEVERITY_OVERRIDES_KEY} setting: {e}");
+            HashMap::new()
+        }
+    }
+}
+
+/// Determine the severity level for an alert type.
+///
+/// Operator overrides from the settings table take priority over the
+/// compiled-in defaults.
+pub async fn severity_for_alert_type(
+    alert_type: &str,
+    db: &SqlitePool,
+    cache: &SeverityOverrideCache,
+) -> String {
+    cache
+        .get(db)
+        .await
+        .get(alert_type)
+        .cloned()
+        .unwrap_or_else(|| default_severity_for_alert_type(alert_type).to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -553,4 +667,73 @@ mod tests {
             "The acknowledged alert should not appear in active filter"
         );
     }
+
+    #[tokio::test]
+    async fn test_severity_default_without_override() {
+        let pool = test_db().await;
+        let cache = SeverityOverrideCache::new();
+
+        assert_eq!(
+            severity_for_alert_type("new_device", &pool, &cache).await,
+            "INFO"
+        );
+        assert_eq!(
+            severity_for_alert_type("device_offline", &pool, &cache).await,
+            "WARNING"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_severity_db_override_takes_priority() {
+        let pool = test_db().await;
+        sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?)")
+            .bind(SEVERITY_OVERRIDES_KEY)
+            .bind(r#"{"new_device": "critical", "device_offline": "info"}"#)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let cache = SeverityOverrideCache::new();
+
+        assert_eq!(
+            severity_for_alert_type("new_device", &pool, &cache).await,
+            "CRITICAL"
+        );
+        assert_eq!(
+            severity_for_alert_type("device_offline", &pool, &cache).await,
+            "INFO"
+        );
+        // Types without an override keep the compiled-in default.
+        assert_eq!(
+            severity_for_alert_type("agent_offline", &pool, &cache).await,
+            "WARNING"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_severity_cache_invalidate() {
+        let pool = test_db().await;
+        let cache = SeverityOverrideCache::new();
+        assert_eq!(
+            severity_for_alert_type("new_device", &pool, &cache).await,
+            "INFO"
+        );
+
+        sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?)")
+            .bind(SEVERITY_OVERRIDES_KEY)
+            .bind(r#"{"new_device": "critical"}"#)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Still cached until invalidated.
+        assert_eq!(
+            severity_for_alert_type("new_device", &pool, &cache).await,
+            "INFO"
+        );
+        cache.invalidate().await;
+        assert_eq!(
+            severity_for_alert_type("new_device", &pool, &cache).await,
+            "CRITICAL"
+        );
+    }
 }
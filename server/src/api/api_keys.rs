@@ -0,0 +1,419 @@
+use axum::{
+    extract::{Path, State},
+    http::{Method, StatusCode},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, SqlitePool};
+
+use super::audit::Actor;
+use super::{audit, AppError, AppState};
+
+/// Scopes an API key may be granted.
+///
+/// `read` allows safe (GET/HEAD) requests, `write` allows everything else,
+/// and `agent` allows agent registration and management under `/agents`.
+pub const API_KEY_SCOPES: &[&str] = &["read", "write", "agent"];
+
+/// Maximum length of an API key name.
+const MAX_NAME_LEN: usize = 64;
+
+/// API key metadata (the plaintext key is never stored).
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(FromRow)]
+struct ApiKeyRow {
+    id: String,
+    name: String,
+    scopes: String,
+    created_at: String,
+    expires_at: Option<String>,
+    last_used_at: Option<String>,
+}
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            scopes: split_scopes(&row.scopes),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+        }
+    }
+}
+
+/// Request body for creating an API key.
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Days until the key expires; never expires when omitted.
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+/// Response for a newly created key — the only time the plaintext is returned.
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+/// An authenticated API key, stored in request extensions by `auth_middleware`.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    pub id: String,
+    pub scopes: Vec<String>,
+}
+
+fn split_scopes(scopes: &str) -> Vec<String> {
+    scopes
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Hex-encoded SHA-256 of a plaintext key, as stored in `api_keys.key_hash`.
+pub fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+/// Fail with 403 unless the key was granted `scope`.
+pub fn require_scope(auth: &ApiKeyAuth, scope: &str) -> Result<(), AppError> {
+    if auth.scopes.iter().any(|s| s == scope) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(format!(
+            "API key lacks the '{scope}' scope"
+        )))
+    }
+}
+
+/// Check an API key against the scope a request needs.
+///
+/// Key management, user accounts and two-factor settings always require a
+/// browser session, so a leaked key cannot mint new keys or admin accounts,
+/// or lock out the admin.
+pub fn authorize_request(auth: &ApiKeyAuth, method: &Method, path: &str) -> Result<(), AppError> {
+    if path.starts_with("/api-keys") || path.starts_with("/users") || path.starts_with("/auth/") {
+        return Err(AppError::Forbidden(
+            "This endpoint requires a session login".to_string(),
+        ));
+    }
+    if path.starts_with("/agents") && require_scope(auth, "agent").is_ok() {
+        return Ok(());
+    }
+    if method == Method::GET || method == Method::HEAD {
+        require_scope(auth, "read")
+    } else {
+        require_scope(auth, "write")
+    }
+}
+
+/// Look up an unexpired key by its plaintext and record its use.
+pub async fn authenticate(db: &SqlitePool, key: &str) -> Result<Option<ApiKeyAuth>, sqlx::Error> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        "SELECT id, scopes FROM api_keys \
+         WHERE key_hash = ? AND (expires_at IS NULL OR expires_at > datetime('now'))",
+    )
+    .bind(hash_key(key))
+    .fetch_optional(db)
+    .await?;
+
+    let Some((id, scopes)) = row else {
+        return Ok(None);
+    };
+
+    sqlx::query("UPDATE api_keys SET last_used_at = datetime('now') WHERE id = ?")
+        .bind(&id)
+        .execute(db)
+        .await?;
+
+    Ok(Some(ApiKeyAuth {
+        id,
+        scopes: split_scopes(&scopes),
+    }))
+}
+
+async fn load_api_key(db: &SqlitePool, id: &str) -> Result<Option<ApiKey>, sqlx::Error> {
+    let row: Option<ApiKeyRow> = sqlx::query_as(
+        "SELECT id, name, scopes, created_at, expires_at, last_used_at \
+         FROM api_keys WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.map(ApiKey::from))
+}
+
+/// GET /api/v1/api-keys — list key metadata.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<ApiKey>>, AppError> {
+    let rows: Vec<ApiKeyRow> = sqlx::query_as(
+        "SELECT id, name, scopes, created_at, expires_at, last_used_at \
+         FROM api_keys ORDER BY created_at DESC, name",
+    )
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(ApiKey::from).collect()))
+}
+
+/// POST /api/v1/api-keys — create a key and return its plaintext once.
+pub async fn create(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<CreateApiKey>,
+) -> Result<(StatusCode, Json<CreateApiKeyResponse>), AppError> {
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "name must be 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    if body.scopes.is_empty() {
+        return Err(AppError::Validation(
+            "at least one scope is required".to_string(),
+        ));
+    }
+    if let Some(scope) = body
+        .scopes
+        .iter()
+        .find(|s| !API_KEY_SCOPES.contains(&s.as_str()))
+    {
+        return Err(AppError::Validation(format!(
+            "unknown scope '{scope}' (expected one of: {})",
+            API_KEY_SCOPES.join(", ")
+        )));
+    }
+    if body.expires_in_days == Some(0) {
+        return Err(AppError::Validation(
+            "expires_in_days must be at least 1".to_string(),
+        ));
+    }
+
+    let mut scopes = body.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let key = format!("pnk_{}", uuid::Uuid::new_v4().to_string().replace('-', ""));
+    let expires_modifier = body.expires_in_days.map(|days| format!("+{days} days"));
+
+    sqlx::query(
+        "INSERT INTO api_keys (id, name, key_hash, scopes, expires_at) \
+         VALUES (?, ?, ?, ?, CASE WHEN ?5 IS NULL THEN NULL ELSE datetime('now', ?5) END)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(hash_key(&key))
+    .bind(scopes.join(","))
+    .bind(&expires_modifier)
+    .execute(&state.db)
+    .await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "api_key_create",
+        &format!("Created API key '{name}' ({})", scopes.join(", ")),
+        &[],
+    )
+    .await;
+
+    let api_key = load_api_key(&state.db, &id)
+        .await?
+        .ok_or_else(|| AppError::Internal("Created API key not found".to_string()))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateApiKeyResponse { api_key, key }),
+    ))
+}
+
+/// DELETE /api/v1/api-keys/:id — revoke a key.
+pub async fn delete(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let api_key = load_api_key(&state.db, &id)
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("api-key", "API key not found".to_string()))?;
+
+    sqlx::query("DELETE FROM api_keys WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "api_key_delete",
+        &format!("Revoked API key '{}'", api_key.name),
+        &[],
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    fn create_body(scopes: &[&str], expires_in_days: Option<u32>) -> Json<CreateApiKey> {
+        Json(CreateApiKey {
+            name: "ansible".to_string(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_in_days,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_authenticate_and_revoke() {
+        let state = test_state().await;
+
+        let (status, Json(created)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(&["write", "read"], None),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert!(created.key.starts_with("pnk_"));
+        assert_eq!(created.api_key.scopes, vec!["read", "write"]);
+        assert!(created.api_key.expires_at.is_none());
+
+        // Only the hash is stored.
+        let stored: String = sqlx::query_scalar("SELECT key_hash FROM api_keys")
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(stored, hash_key(&created.key));
+
+        let auth = authenticate(&state.db, &created.key)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(auth.id, created.api_key.id);
+        assert!(authenticate(&state.db, "pnk_wrong")
+            .await
+            .unwrap()
+            .is_none());
+
+        let Json(keys) = list(State(state.clone())).await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].last_used_at.is_some());
+
+        let status = delete(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(created.api_key.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(authenticate(&state.db, &created.key)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(delete(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(created.api_key.id)
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_rejected() {
+        let state = test_state().await;
+        let (_, Json(created)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(&["read"], Some(1)),
+        )
+        .await
+        .unwrap();
+        assert!(created.api_key.expires_at.is_some());
+
+        sqlx::query("UPDATE api_keys SET expires_at = datetime('now', '-1 second')")
+            .execute(&state.db)
+            .await
+            .unwrap();
+        assert!(authenticate(&state.db, &created.key)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_validation() {
+        let state = test_state().await;
+        assert!(create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(&[], None)
+        )
+        .await
+        .is_err());
+        assert!(create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(&["admin"], None)
+        )
+        .await
+        .is_err());
+        assert!(create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(&["read"], Some(0))
+        )
+        .await
+        .is_err());
+    }
+
+    #[test]
+    fn test_authorize_request() {
+        let read = ApiKeyAuth {
+            id: "k".to_string(),
+            scopes: vec!["read".to_string()],
+        };
+        let agent = ApiKeyAuth {
+            id: "k".to_string(),
+            scopes: vec!["agent".to_string()],
+        };
+        let write = ApiKeyAuth {
+            id: "k".to_string(),
+            scopes: vec!["read".to_string(), "write".to_string()],
+        };
+
+        assert!(authorize_request(&read, &Method::GET, "/devices").is_ok());
+        assert!(authorize_request(&read, &Method::POST, "/devices/bulk").is_err());
+        assert!(authorize_request(&read, &Method::GET, "/api-keys").is_err());
+        assert!(authorize_request(&agent, &Method::POST, "/agents").is_ok());
+        assert!(authorize_request(&agent, &Method::GET, "/devices").is_err());
+        assert!(authorize_request(&write, &Method::POST, "/devices/bulk").is_ok());
+        assert!(authorize_request(&write, &Method::POST, "/users").is_err());
+        assert!(authorize_request(&write, &Method::GET, "/users").is_err());
+    }
+}
@@ -1,12 +1,21 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    async_trait,
+    body::Body,
+    extract::{ConnectInfo, FromRequestParts, Query, Request, State},
+    http::{header, request::Parts, Extensions, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
+use std::convert::Infallible;
+use std::net::SocketAddr;
 
-use super::AppState;
+use super::api_keys::ApiKeyAuth;
+use super::auth::{self, SessionUser};
+use super::{rate_limit, AppError, AppState};
+use crate::notification::syslog::{self, SyslogEvent, SyslogStatus};
 
 /// A single audit log entry.
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +27,15 @@ pub struct AuditLogEntry {
     pub vyos_commands: String,
     pub success: bool,
     pub error_msg: Option<String>,
+    /// Who made the change (username, `api-key:<id>` or `system`).
+    pub actor: Option<String>,
+    /// HTTP method, path and response status for request-level entries.
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<i64>,
+    /// Request body with secrets redacted, truncated.
+    pub request_body_summary: Option<String>,
+    pub ip_address: Option<String>,
 }
 
 /// Paginated response for the audit log list endpoint.
@@ -41,7 +59,7 @@ pub struct AuditLogQuery {
 pub async fn list(
     State(state): State<AppState>,
     Query(params): Query<AuditLogQuery>,
-) -> Result<Json<AuditLogListResponse>, StatusCode> {
+) -> Result<Json<AuditLogListResponse>, AppError> {
     let page = params.page.unwrap_or(1).max(1);
     let per_page = params.per_page.unwrap_or(25).clamp(1, 100);
     let offset = (page - 1) * per_page;
@@ -50,48 +68,34 @@ pub async fn list(
         let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE action = ?")
             .bind(action_filter)
             .fetch_one(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!("audit_log count query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
+            .await?;
 
         let rows = sqlx::query_as::<_, AuditLogRow>(
-            "SELECT id, created_at, action, description, vyos_commands, success, error_msg \
+            "SELECT id, created_at, action, description, vyos_commands, success, error_msg, \
+                    actor, method, path, status_code, request_body_summary, ip_address \
              FROM audit_log WHERE action = ? ORDER BY id DESC LIMIT ? OFFSET ?",
         )
         .bind(action_filter)
         .bind(per_page)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("audit_log list query failed: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
         (rows, total)
     } else {
         let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log")
             .fetch_one(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!("audit_log count query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
+            .await?;
 
         let rows = sqlx::query_as::<_, AuditLogRow>(
-            "SELECT id, created_at, action, description, vyos_commands, success, error_msg \
+            "SELECT id, created_at, action, description, vyos_commands, success, error_msg, \
+                    actor, method, path, status_code, request_body_summary, ip_address \
              FROM audit_log ORDER BY id DESC LIMIT ? OFFSET ?",
         )
         .bind(per_page)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("audit_log list query failed: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
         (rows, total)
     };
@@ -106,6 +110,12 @@ pub async fn list(
             vyos_commands: row.vyos_commands,
             success: row.success != 0,
             error_msg: row.error_msg,
+            actor: row.actor,
+            method: row.method,
+            path: row.path,
+            status_code: row.status_code,
+            request_body_summary: row.request_body_summary,
+            ip_address: row.ip_address,
         })
         .collect();
 
@@ -118,19 +128,21 @@ pub async fn list(
 }
 
 /// GET /api/v1/audit-log/actions — list distinct action types for filter dropdown.
-pub async fn actions(State(state): State<AppState>) -> Result<Json<Vec<String>>, StatusCode> {
+pub async fn actions(State(state): State<AppState>) -> Result<Json<Vec<String>>, AppError> {
     let rows: Vec<(String,)> =
         sqlx::query_as("SELECT DISTINCT action FROM audit_log ORDER BY action")
             .fetch_all(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!("audit_log actions query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
+            .await?;
 
     Ok(Json(rows.into_iter().map(|(a,)| a).collect()))
 }
 
+/// GET /api/v1/audit-log/syslog-status — syslog forwarding state and recent
+/// delivery failures.
+pub async fn syslog_status() -> Json<SyslogStatus> {
+    Json(syslog::status())
+}
+
 /// Internal row type for sqlx deserialization.
 #[derive(sqlx::FromRow)]
 struct AuditLogRow {
@@ -141,6 +153,43 @@ struct AuditLogRow {
     vyos_commands: String,
     success: i32,
     error_msg: Option<String>,
+    actor: Option<String>,
+    method: Option<String>,
+    path: Option<String>,
+    status_code: Option<i64>,
+    request_body_summary: Option<String>,
+    ip_address: Option<String>,
+}
+
+// ── Actor ────────────────────────────────────────────────────────────────────
+
+/// Actor recorded for changes made by background tasks.
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// Who is making the request: the session's username, `api-key:<id>` for
+/// API-key requests, or `system` when neither is present.
+#[derive(Debug, Clone)]
+pub struct Actor(pub String);
+
+impl Actor {
+    fn from_extensions(extensions: &Extensions) -> Self {
+        if let Some(user) = extensions.get::<SessionUser>() {
+            Actor(user.username.clone())
+        } else if let Some(key) = extensions.get::<ApiKeyAuth>() {
+            Actor(format!("api-key:{}", key.id))
+        } else {
+            Actor(SYSTEM_ACTOR.to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Actor::from_extensions(&parts.extensions))
+    }
 }
 
 // ── Audit log helper ─────────────────────────────────────────────────────────
@@ -149,6 +198,7 @@ struct AuditLogRow {
 /// do not affect the caller.
 pub async fn log_success(
     db: &SqlitePool,
+    actor: &str,
     action: &str,
     description: &str,
     vyos_commands: &[String],
@@ -156,8 +206,10 @@ pub async fn log_success(
     let commands_json = serde_json::to_string(vyos_commands).unwrap_or_else(|_| "[]".to_string());
 
     if let Err(e) = sqlx::query(
-        "INSERT INTO audit_log (action, description, vyos_commands, success) VALUES (?, ?, ?, 1)",
+        "INSERT INTO audit_log (actor, action, description, vyos_commands, success) \
+         VALUES (?, ?, ?, ?, 1)",
     )
+    .bind(actor)
     .bind(action)
     .bind(description)
     .bind(&commands_json)
@@ -166,11 +218,20 @@ pub async fn log_success(
     {
         tracing::error!("Failed to write audit log: {e}");
     }
+
+    syslog::forward(SyslogEvent {
+        success: true,
+        action: action.to_string(),
+        actor: actor.to_string(),
+        device_id: None,
+        description: description.to_string(),
+    });
 }
 
 /// Record a failed audit log entry.
 pub async fn log_failure(
     db: &SqlitePool,
+    actor: &str,
     action: &str,
     description: &str,
     vyos_commands: &[String],
@@ -179,9 +240,10 @@ pub async fn log_failure(
     let commands_json = serde_json::to_string(vyos_commands).unwrap_or_else(|_| "[]".to_string());
 
     if let Err(e) = sqlx::query(
-        "INSERT INTO audit_log (action, description, vyos_commands, success, error_msg) \
-         VALUES (?, ?, ?, 0, ?)",
+        "INSERT INTO audit_log (actor, action, description, vyos_commands, success, error_msg) \
+         VALUES (?, ?, ?, ?, 0, ?)",
     )
+    .bind(actor)
     .bind(action)
     .bind(description)
     .bind(&commands_json)
@@ -191,4 +253,186 @@ pub async fn log_failure(
     {
         tracing::error!("Failed to write audit log: {e}");
     }
+
+    syslog::forward(SyslogEvent {
+        success: false,
+        action: action.to_string(),
+        actor: actor.to_string(),
+        device_id: None,
+        description: format!("{description}: {error_msg}"),
+    });
+}
+
+// ── Request audit middleware ─────────────────────────────────────────────────
+
+/// Action recorded for request-level entries written by [`audit_middleware`].
+pub const API_REQUEST_ACTION: &str = "api_request";
+
+/// Largest request body the middleware buffers (matches the device import limit).
+const MAX_AUDIT_BODY_BYTES: usize = super::devices::MAX_IMPORT_BODY_BYTES;
+
+/// Maximum length of a stored request body summary, in characters.
+const MAX_BODY_SUMMARY_CHARS: usize = 500;
+
+/// JSON keys whose values are replaced with `[redacted]` (case-insensitive substring match).
+const REDACTED_KEYS: &[&str] = &["password", "secret", "token", "key", "code"];
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                let lower = k.to_lowercase();
+                if REDACTED_KEYS.iter().any(|r| lower.contains(r)) {
+                    *v = serde_json::Value::String("[redacted]".to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Summarize a request body for the audit log: JSON with secrets redacted,
+/// or just the size and content type for anything else.
+pub fn summarize_body(content_type: Option<&str>, body: &[u8]) -> Option<String> {
+    if body.is_empty() {
+        return None;
+    }
+    let summary = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) if content_type.is_some_and(|ct| ct.starts_with("application/json")) => {
+            redact(&mut json);
+            json.to_string()
+        }
+        _ => format!(
+            "<{} bytes{}>",
+            body.len(),
+            content_type.map(|ct| format!(", {ct}")).unwrap_or_default()
+        ),
+    };
+    Some(match summary.char_indices().nth(MAX_BODY_SUMMARY_CHARS) {
+        Some((cut, _)) => format!("{}…", &summary[..cut]),
+        None => summary,
+    })
+}
+
+/// Middleware recording every non-GET request (actor, method, path, status,
+/// body summary and client IP) in `audit_log`.
+///
+/// Must run inside `auth_middleware` so the actor is known.
+pub async fn audit_middleware(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if req.method() == Method::GET || req.method() == Method::HEAD {
+        return next.run(req).await;
+    }
+
+    let actor = Actor::from_extensions(req.extensions()).0;
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let ip_address = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| {
+            auth::extract_client_ip(req.headers(), *addr, &state.config().auth.trusted_proxies)
+                .to_string()
+        });
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    // Buffer the body so it can be summarized and still reach the handler.
+    let (parts, body) = req.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_AUDIT_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return AppError::PayloadTooLarge(format!(
+                "Request body exceeds {MAX_AUDIT_BODY_BYTES} bytes"
+            ))
+            .into_response()
+        }
+    };
+    let body_summary = summarize_body(content_type.as_deref(), &bytes);
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    let status = response.status();
+    let error_msg = (!status.is_success() && !status.is_redirection())
+        .then(|| status.canonical_reason().unwrap_or("error").to_string());
+    let description = format!("{method} {path} → {}", status.as_u16());
+    if let Err(e) = sqlx::query(
+        "INSERT INTO audit_log (actor, action, description, vyos_commands, success, error_msg, \
+                                method, path, status_code, request_body_summary, ip_address) \
+         VALUES (?, ?, ?, '[]', ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&actor)
+    .bind(API_REQUEST_ACTION)
+    .bind(&description)
+    .bind(error_msg.is_none())
+    .bind(&error_msg)
+    .bind(&method)
+    .bind(&path)
+    .bind(i64::from(status.as_u16()))
+    .bind(&body_summary)
+    .bind(&ip_address)
+    .execute(&state.db)
+    .await
+    {
+        tracing::error!("Failed to write request audit log: {e}");
+    }
+
+    syslog::forward(SyslogEvent {
+        success: error_msg.is_none(),
+        action: API_REQUEST_ACTION.to_string(),
+        actor,
+        device_id: rate_limit::device_id_from_path(&path).map(str::to_string),
+        description,
+    });
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_body_redacts_secrets() {
+        let body = br#"{"name":"noc","password":"hunter22","nested":{"api_key":"k","ok":1}}"#;
+        let summary = summarize_body(Some("application/json"), body).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&summary).unwrap();
+        assert_eq!(json["name"], "noc");
+        assert_eq!(json["password"], "[redacted]");
+        assert_eq!(json["nested"]["api_key"], "[redacted]");
+        assert_eq!(json["nested"]["ok"], 1);
+    }
+
+    #[test]
+    fn test_summarize_body_non_json_and_truncation() {
+        assert_eq!(summarize_body(Some("text/csv"), b""), None);
+        assert_eq!(
+            summarize_body(Some("text/csv"), b"mac,alias\n").unwrap(),
+            "<10 bytes, text/csv>"
+        );
+
+        let long = serde_json::json!({ "notes": "x".repeat(2_000) }).to_string();
+        let summary = summarize_body(Some("application/json"), long.as_bytes()).unwrap();
+        assert_eq!(summary.chars().count(), MAX_BODY_SUMMARY_CHARS + 1);
+        assert!(summary.ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn test_log_success_records_actor() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        log_success(&pool, "noc", "device_note_create", "Added note", &[]).await;
+
+        let actor: Option<String> = sqlx::query_scalar("SELECT actor FROM audit_log")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(actor.as_deref(), Some("noc"));
+    }
 }
@@ -1,19 +1,23 @@
 use axum::{
     extract::{ConnectInfo, Request, State},
-    http::{header, HeaderMap, StatusCode},
+    http::{header, HeaderMap, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use sqlx::{Row, SqliteConnection, SqlitePool};
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Instant;
+use totp_rs::{Algorithm, Secret, TOTP};
 use tracing::{debug, warn};
 
-use super::AppState;
+use super::audit::Actor;
+use super::users::{ADMIN_USERNAME, ROLE_ADMIN, ROLE_VIEWER};
+use super::{api_keys, audit, AppError, AppState};
+use crate::crypto;
 
 // ---------- Rate limiting ----------
 
@@ -92,7 +96,11 @@ impl Default for LoginRateLimiter {
 /// Only trusts `X-Forwarded-For` when the TCP peer address matches a configured
 /// trusted proxy, preventing spoofing by external clients. Falls back to the
 /// direct connection IP otherwise.
-fn extract_client_ip(headers: &HeaderMap, addr: SocketAddr, trusted_proxies: &[String]) -> IpAddr {
+pub(super) fn extract_client_ip(
+    headers: &HeaderMap,
+    addr: SocketAddr,
+    trusted_proxies: &[String],
+) -> IpAddr {
     let peer_ip = addr.ip();
 
     let peer_is_trusted = trusted_proxies.iter().any(|proxy| {
@@ -121,7 +129,13 @@ fn extract_client_ip(headers: &HeaderMap, addr: SocketAddr, trusted_proxies: &[S
 /// Login request body.
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
+    /// Account to log in as; the built-in admin when omitted.
+    #[serde(default)]
+    pub username: Option<String>,
     pub password: String,
+    /// Current 6-digit code; required once TOTP is enabled.
+    #[serde(default)]
+    pub totp_code: Option<String>,
 }
 
 /// Login response.
@@ -135,19 +149,23 @@ pub struct LoginResponse {
 pub struct AuthStatusResponse {
     pub authenticated: bool,
     pub needs_setup: bool,
+    /// Whether logging in as the built-in admin requires a TOTP code.
+    pub totp_enabled: bool,
 }
 
 /// POST /api/v1/auth/login — authenticate and set session cookie.
 ///
 /// Rate-limited: after 5 failed password attempts within 60 seconds the
 /// endpoint returns `429 Too Many Requests` with a `Retry-After` header.
+/// When TOTP is enabled a missing or wrong `totp_code` fails with 401 and
+/// counts as a failed attempt.
 pub async fn login(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     Json(body): Json<LoginRequest>,
 ) -> Result<Response, Response> {
-    let client_ip = extract_client_ip(&headers, addr, &state.config.auth.trusted_proxies);
+    let client_ip = extract_client_ip(&headers, addr, &state.config().auth.trusted_proxies);
 
     // Atomically check rate limit and reserve a slot. This prevents TOCTOU races
     // where concurrent requests could all pass a separate check() before any
@@ -163,6 +181,100 @@ pub async fn login(
         return Err(resp);
     }
 
+    // Named accounts live in the users table; the built-in admin in settings.
+    let username = body
+        .username
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty() && *u != ADMIN_USERNAME);
+    let auth_config = state.config().auth.clone();
+    let ldap = crate::ldap::load_settings(&state.db, &auth_config).await;
+    let local_allowed = ldap.is_none() || !auth_config.ldap_required;
+    let user_id = match username {
+        Some(username) => {
+            let ldap_user = match ldap {
+                Some(ref settings) => {
+                    ldap_login(&state, settings, username, &body.password, &client_ip).await?
+                }
+                None => None,
+            };
+            match ldap_user {
+                Some(id) => Some(id),
+                None if local_allowed => {
+                    Some(verify_user(&state, username, &body.password, &client_ip).await?)
+                }
+                None => {
+                    warn!(%client_ip, username, "Failed login attempt (LDAP)");
+                    return Err(StatusCode::UNAUTHORIZED.into_response());
+                }
+            }
+        }
+        None if local_allowed => {
+            verify_admin(&state, &body, &client_ip).await?;
+            None
+        }
+        None => {
+            warn!(%client_ip, "Admin login refused: ldap_required is set");
+            return Err(StatusCode::UNAUTHORIZED.into_response());
+        }
+    };
+
+    // Every account, local or LDAP, must pass its own TOTP check if enabled.
+    check_totp(
+        &state,
+        user_id.as_deref(),
+        body.totp_code.as_deref(),
+        &client_ip,
+    )
+    .await?;
+
+    // Generate session token and store it in the database.
+    let token = uuid::Uuid::new_v4().to_string();
+    // Ensure at least 1 second; a zero expiry would create an immediately-invalid session.
+    let expiry_secs = state.config().auth.session_expiry_seconds.max(1);
+    let expiry_modifier = format!("+{expiry_secs} seconds");
+
+    sqlx::query(
+        "INSERT INTO sessions (token, expires_at, user_id) VALUES (?, datetime('now', ?), ?)",
+    )
+    .bind(&token)
+    .bind(&expiry_modifier)
+    .bind(&user_id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to store session: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    })?;
+
+    // Clear rate-limiter only after session is successfully persisted.
+    state.rate_limiter.clear(&client_ip);
+
+    tracing::info!(%client_ip, username = username.unwrap_or(ADMIN_USERNAME), "User logged in, session created");
+
+    // Build Set-Cookie header.
+    let cookie = format!(
+        "panoptikon_session={token}; HttpOnly; SameSite=Lax; Path=/; Max-Age={expiry_secs}"
+    );
+
+    let mut response = Json(LoginResponse {
+        message: "Login successful".to_string(),
+    })
+    .into_response();
+    response.headers_mut().insert(
+        header::SET_COOKIE,
+        header::HeaderValue::from_str(&cookie).expect("cookie value is always valid ASCII"),
+    );
+
+    Ok(response)
+}
+
+/// Verify the built-in admin's password (from settings).
+async fn verify_admin(
+    state: &AppState,
+    body: &LoginRequest,
+    client_ip: &IpAddr,
+) -> Result<(), Response> {
     // Retrieve the stored admin password hash from settings.
     let row: Option<String> =
         sqlx::query("SELECT value FROM settings WHERE key = 'admin_password_hash'")
@@ -179,7 +291,7 @@ pub async fn login(
         None => {
             // No password set — setup hasn't been completed yet.
             // The user must go through POST /api/v1/setup first.
-            state.rate_limiter.clear(&client_ip);
+            state.rate_limiter.clear(client_ip);
             return Err(StatusCode::PRECONDITION_REQUIRED.into_response());
         }
     };
@@ -196,42 +308,90 @@ pub async fn login(
         return Err(StatusCode::UNAUTHORIZED.into_response());
     }
 
-    // Generate session token and store it in the database.
-    let token = uuid::Uuid::new_v4().to_string();
-    // Ensure at least 1 second; a zero expiry would create an immediately-invalid session.
-    let expiry_secs = state.config.auth.session_expiry_seconds.max(1);
-    let expiry_modifier = format!("+{expiry_secs} seconds");
+    Ok(())
+}
 
-    sqlx::query("INSERT INTO sessions (token, expires_at) VALUES (?, datetime('now', ?))")
-        .bind(&token)
-        .bind(&expiry_modifier)
-        .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to store session: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?;
+/// Verify a named account's password, returning its user id.
+async fn verify_user(
+    state: &AppState,
+    username: &str,
+    password: &str,
+    client_ip: &IpAddr,
+) -> Result<String, Response> {
+    let row: Option<(String, String)> =
+        sqlx::query_as("SELECT id, password_hash FROM users WHERE username = ?")
+            .bind(username)
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to query users: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            })?;
 
-    // Clear rate-limiter only after session is successfully persisted.
-    state.rate_limiter.clear(&client_ip);
+    let valid = match &row {
+        Some((_, hash)) => bcrypt::verify(password, hash).map_err(|e| {
+            tracing::error!("Password verification error: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        })?,
+        None => false,
+    };
 
-    tracing::info!(%client_ip, "Admin logged in, session created");
+    match row {
+        Some((id, _)) if valid => Ok(id),
+        _ => {
+            warn!(%client_ip, username, "Failed login attempt");
+            Err(StatusCode::UNAUTHORIZED.into_response())
+        }
+    }
+}
 
-    // Build Set-Cookie header.
-    let cookie = format!(
-        "panoptikon_session={token}; HttpOnly; SameSite=Lax; Path=/; Max-Age={expiry_secs}"
-    );
+/// Authenticate a named account against LDAP, returning its user id.
+///
+/// `None` means the directory rejected the credentials or could not be
+/// reached. Directory users without a local account are provisioned as
+/// viewers with an unusable local password.
+async fn ldap_login(
+    state: &AppState,
+    settings: &crate::ldap::LdapSettings,
+    username: &str,
+    password: &str,
+    client_ip: &IpAddr,
+) -> Result<Option<String>, Response> {
+    match crate::ldap::authenticate(settings, username, password).await {
+        Ok(true) => {}
+        Ok(false) => return Ok(None),
+        Err(e) => {
+            warn!(%client_ip, username, "LDAP authentication unavailable: {e}");
+            return Ok(None);
+        }
+    }
 
-    let mut response = Json(LoginResponse {
-        message: "Login successful".to_string(),
-    })
-    .into_response();
-    response.headers_mut().insert(
-        header::SET_COOKIE,
-        header::HeaderValue::from_str(&cookie).expect("cookie value is always valid ASCII"),
-    );
+    let internal = |e: String| {
+        tracing::error!("{e}");
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    };
+    let existing: Option<String> = sqlx::query_scalar("SELECT id FROM users WHERE username = ?")
+        .bind(username)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| internal(format!("Failed to query users: {e}")))?;
+    if let Some(id) = existing {
+        return Ok(Some(id));
+    }
 
-    Ok(response)
+    let hash = bcrypt::hash(crate::crypto::random_hex(32), bcrypt::DEFAULT_COST)
+        .map_err(|e| internal(format!("Failed to hash password: {e}")))?;
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO users (id, username, password_hash, role) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(username)
+        .bind(&hash)
+        .bind(ROLE_VIEWER)
+        .execute(&state.db)
+        .await
+        .map_err(|e| internal(format!("Failed to provision LDAP user: {e}")))?;
+    tracing::info!(username, "Provisioned viewer account for LDAP user");
+    Ok(Some(id))
 }
 
 /// Change-password request body.
@@ -245,57 +405,45 @@ pub struct ChangePasswordRequest {
 pub async fn change_password(
     State(state): State<AppState>,
     Json(body): Json<ChangePasswordRequest>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     if body.new_password.len() < 8 {
-        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        return Err(AppError::UnprocessableEntity(
+            "New password must be at least 8 characters".to_string(),
+        ));
     }
 
     // Fetch current hash.
     let row: Option<String> =
         sqlx::query("SELECT value FROM settings WHERE key = 'admin_password_hash'")
             .fetch_optional(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to query settings: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
+            .await?
             .and_then(|r| r.try_get("value").ok());
 
-    let current_hash = row.ok_or(StatusCode::NOT_FOUND)?;
+    let current_hash = row.ok_or_else(|| {
+        AppError::ResourceNotFound("admin-password", "Admin password is not set".to_string())
+    })?;
 
     // Verify current password.
-    let valid = bcrypt::verify(&body.current_password, &current_hash).map_err(|e| {
-        tracing::error!("Password verification error: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let valid = bcrypt::verify(&body.current_password, &current_hash)
+        .map_err(|e| AppError::Internal(format!("Password verification error: {e}")))?;
     if !valid {
         warn!("Change-password: wrong current password");
-        return Err(StatusCode::UNAUTHORIZED);
+        return Err(AppError::Unauthorized);
     }
 
     // Hash new password and update.
-    let new_hash = bcrypt::hash(&body.new_password, bcrypt::DEFAULT_COST).map_err(|e| {
-        tracing::error!("Failed to hash new password: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let new_hash = bcrypt::hash(&body.new_password, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::Internal(format!("Failed to hash new password: {e}")))?;
 
     sqlx::query("UPDATE settings SET value = ? WHERE key = 'admin_password_hash'")
         .bind(&new_hash)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to update password: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     // Invalidate all existing sessions so the new password takes effect immediately.
     sqlx::query("DELETE FROM sessions")
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to clear sessions: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     tracing::info!("Admin password changed, all sessions invalidated");
     Ok(StatusCode::NO_CONTENT)
@@ -329,64 +477,413 @@ pub async fn logout(State(state): State<AppState>, req: Request) -> impl IntoRes
 pub async fn status(
     State(state): State<AppState>,
     req: Request,
-) -> Result<Json<AuthStatusResponse>, StatusCode> {
+) -> Result<Json<AuthStatusResponse>, AppError> {
     let needs_setup = sqlx::query("SELECT 1 FROM settings WHERE key = 'admin_password_hash'")
         .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to query settings: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
+        .await?
         .is_none();
 
     let authenticated = if let Some(token) = extract_session_token(&req) {
         sqlx::query("SELECT 1 FROM sessions WHERE token = ? AND expires_at > datetime('now')")
             .bind(&token)
             .fetch_optional(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to check session: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
+            .await?
             .is_some()
     } else {
         false
     };
 
+    let totp_enabled = get_totp_secret(&state.db, None, TotpSlot::Active)
+        .await?
+        .is_some();
+
     Ok(Json(AuthStatusResponse {
         authenticated,
         needs_setup,
+        totp_enabled,
     }))
 }
 
 /// Auth middleware: protects routes by checking the session cookie.
-pub async fn auth_middleware(State(state): State<AppState>, req: Request, next: Next) -> Response {
+pub async fn auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    // Headless clients authenticate with `Authorization: Bearer <api key>`.
+    if let Some(key) = extract_bearer_token(req.headers()) {
+        let auth = match api_keys::authenticate(&state.db, &key).await {
+            Ok(Some(auth)) => auth,
+            Ok(None) => {
+                debug!("Auth middleware rejected request (unknown or expired API key)");
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "DB error in auth middleware");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        if let Err(e) = api_keys::authorize_request(&auth, req.method(), req.uri().path()) {
+            return e.into_response();
+        }
+        req.extensions_mut().insert(auth);
+        return next.run(req).await;
+    }
+
     let token = extract_session_token(&req);
 
-    let session_row = if let Some(ref token) = token {
-        match sqlx::query("SELECT 1 FROM sessions WHERE token = ? AND expires_at > datetime('now')")
+    let session_row: Option<(Option<String>, Option<String>, Option<String>)> =
+        if let Some(ref token) = token {
+            // Sessions without a user_id belong to the built-in admin.
+            match sqlx::query_as(
+                "SELECT s.user_id, u.username, u.role FROM sessions s \
+             LEFT JOIN users u ON u.id = s.user_id \
+             WHERE s.token = ? AND s.expires_at > datetime('now')",
+            )
             .bind(token)
             .fetch_optional(&state.db)
             .await
-        {
-            Ok(row) => row,
-            Err(e) => {
-                tracing::error!(error = %e, "DB error in auth middleware");
-                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            {
+                Ok(row) => row,
+                Err(e) => {
+                    tracing::error!(error = %e, "DB error in auth middleware");
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
             }
-        }
-    } else {
-        None
-    };
+        } else {
+            None
+        };
 
-    if session_row.is_none() {
+    let Some((user_id, username, role)) = session_row else {
         debug!("Auth middleware rejected request (no valid session)");
         return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    req.extensions_mut().insert(SessionUser {
+        user_id,
+        username: username.unwrap_or_else(|| ADMIN_USERNAME.to_string()),
+        role: role.unwrap_or_else(|| ROLE_ADMIN.to_string()),
+    });
+    next.run(req).await
+}
+
+/// The logged-in account, stored in request extensions by [`auth_middleware`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionUser {
+    /// `users.id`; `None` for the built-in admin.
+    #[serde(skip)]
+    pub user_id: Option<String>,
+    pub username: String,
+    pub role: String,
+}
+
+/// Paths viewers may call with any method. Everything else under `auth/`
+/// (notably TOTP setup) acts on the admin account and is admin-only.
+const VIEWER_AUTH_PATHS: [&str; 2] = ["/auth/logout", "/auth/me"];
+
+/// Middleware rejecting writes (anything but GET/HEAD) from viewer sessions
+/// with 403, except for [`VIEWER_AUTH_PATHS`].
+///
+/// Must run inside [`auth_middleware`]; API-key requests are governed by
+/// their scopes instead.
+pub async fn require_admin(req: Request, next: Next) -> Response {
+    let is_read = req.method() == Method::GET || req.method() == Method::HEAD;
+    let is_viewer = req
+        .extensions()
+        .get::<SessionUser>()
+        .is_some_and(|user| user.role == ROLE_VIEWER);
+
+    if is_viewer && !is_read && !VIEWER_AUTH_PATHS.contains(&req.uri().path()) {
+        debug!(path = %req.uri().path(), "Viewer attempted a write request");
+        return AppError::Forbidden("Viewer accounts are read-only".to_string()).into_response();
     }
 
     next.run(req).await
 }
 
+/// GET /api/v1/auth/me — the logged-in account's username and role.
+pub async fn me(user: Option<Extension<SessionUser>>) -> Result<Json<SessionUser>, AppError> {
+    user.map(|Extension(user)| Json(user))
+        .ok_or(AppError::Unauthorized)
+}
+
+// ---------- TOTP two-factor authentication ----------
+
+/// Settings key holding the built-in admin's active TOTP secret. Named
+/// accounts keep theirs in `users.totp_secret`.
+pub const ADMIN_TOTP_SECRET_KEY: &str = "admin_totp_secret";
+/// Settings key holding the built-in admin's secret generated by setup but
+/// not yet verified (`users.totp_pending_secret` for named accounts).
+pub const ADMIN_TOTP_PENDING_SECRET_KEY: &str = "admin_totp_pending_secret";
+const TOTP_ISSUER: &str = "Panoptikon";
+
+/// Which of an account's TOTP secrets to read or write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TotpSlot {
+    /// Enforced at login.
+    Active,
+    /// Generated by setup; becomes active once a code from it is verified.
+    Pending,
+}
+
+impl TotpSlot {
+    fn settings_key(self) -> &'static str {
+        match self {
+            TotpSlot::Active => ADMIN_TOTP_SECRET_KEY,
+            TotpSlot::Pending => ADMIN_TOTP_PENDING_SECRET_KEY,
+        }
+    }
+}
+
+/// Build an RFC 6238 TOTP (SHA-1, 6 digits, 30-second step, ±1 step skew),
+/// labelled `account` in otpauth URIs.
+fn build_totp(secret_b32: &str, account: &str) -> Option<TOTP> {
+    let secret = Secret::Encoded(secret_b32.to_string()).to_bytes().ok()?;
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        30,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account.replace(':', "_"),
+    )
+    .ok()
+}
+
+/// Check a 6-digit code against the secret for the current time step.
+fn verify_totp(secret_b32: &str, code: &str) -> bool {
+    build_totp(secret_b32, "")
+        .and_then(|totp| totp.check_current(code.trim()).ok())
+        .unwrap_or(false)
+}
+
+/// Read and decrypt an account's TOTP secret. `user_id` is `None` for the
+/// built-in admin, as in `sessions`.
+///
+/// A secret that no longer decrypts is an error rather than `None`, so a
+/// changed server key cannot silently turn two-factor login off.
+async fn get_totp_secret(
+    db: &SqlitePool,
+    user_id: Option<&str>,
+    slot: TotpSlot,
+) -> Result<Option<String>, AppError> {
+    let sealed: Option<String> = match (user_id, slot) {
+        (None, _) => {
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+                .bind(slot.settings_key())
+                .fetch_optional(db)
+                .await?
+        }
+        (Some(id), TotpSlot::Active) => {
+            sqlx::query_scalar("SELECT totp_secret FROM users WHERE id = ?")
+                .bind(id)
+                .fetch_optional(db)
+                .await?
+                .flatten()
+        }
+        (Some(id), TotpSlot::Pending) => {
+            sqlx::query_scalar("SELECT totp_pending_secret FROM users WHERE id = ?")
+                .bind(id)
+                .fetch_optional(db)
+                .await?
+                .flatten()
+        }
+    };
+    match sealed.filter(|s| !s.is_empty()) {
+        Some(sealed) => crypto::decrypt(&sealed).map(Some).ok_or_else(|| {
+            AppError::Internal(
+                "Cannot decrypt TOTP secret; was the secret key changed?".to_string(),
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Store an account's TOTP secret encrypted, or clear it with `None`.
+async fn set_totp_secret(
+    conn: &mut SqliteConnection,
+    user_id: Option<&str>,
+    slot: TotpSlot,
+    secret: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let sealed = secret.map(crypto::encrypt);
+    match (user_id, sealed) {
+        (None, Some(sealed)) => {
+            sqlx::query(
+                "INSERT INTO settings (key, value) VALUES (?, ?) \
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            )
+            .bind(slot.settings_key())
+            .bind(sealed)
+            .execute(conn)
+            .await?;
+        }
+        (None, None) => {
+            sqlx::query("DELETE FROM settings WHERE key = ?")
+                .bind(slot.settings_key())
+                .execute(conn)
+                .await?;
+        }
+        (Some(id), sealed) => {
+            let query = match slot {
+                TotpSlot::Active => "UPDATE users SET totp_secret = ? WHERE id = ?",
+                TotpSlot::Pending => "UPDATE users SET totp_pending_secret = ? WHERE id = ?",
+            };
+            sqlx::query(query)
+                .bind(sealed)
+                .bind(id)
+                .execute(conn)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Require a valid code from `totp_code` if the account that just passed
+/// its password or LDAP check has TOTP enabled.
+async fn check_totp(
+    state: &AppState,
+    user_id: Option<&str>,
+    totp_code: Option<&str>,
+    client_ip: &IpAddr,
+) -> Result<(), Response> {
+    let secret = get_totp_secret(&state.db, user_id, TotpSlot::Active)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    if let Some(secret) = secret {
+        if !totp_code.is_some_and(|code| verify_totp(&secret, code)) {
+            warn!(%client_ip, "Failed login attempt (missing or invalid TOTP code)");
+            return Err(StatusCode::UNAUTHORIZED.into_response());
+        }
+    }
+    Ok(())
+}
+
+/// TOTP setup response: the otpauth URI (for QR codes) and the raw secret.
+#[derive(Debug, Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub uri: String,
+}
+
+/// Request body carrying a TOTP code (verify / disable).
+#[derive(Debug, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+/// POST /api/v1/auth/totp/setup — generate a new secret for the logged-in
+/// account, pending verification.
+///
+/// TOTP is not enforced until the secret is confirmed via `/auth/totp/verify`.
+pub async fn totp_setup(
+    State(state): State<AppState>,
+    user: Option<Extension<SessionUser>>,
+) -> Result<Json<TotpSetupResponse>, AppError> {
+    let Extension(user) = user.ok_or(AppError::Unauthorized)?;
+    let user_id = user.user_id.as_deref();
+    if get_totp_secret(&state.db, user_id, TotpSlot::Active)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Validation(
+            "TOTP is already enabled; disable it first".to_string(),
+        ));
+    }
+
+    let secret = Secret::generate_secret().to_encoded().to_string();
+    let totp = build_totp(&secret, &user.username)
+        .ok_or_else(|| AppError::Internal("Failed to build TOTP".to_string()))?;
+
+    let mut conn = state.db.acquire().await?;
+    set_totp_secret(&mut conn, user_id, TotpSlot::Pending, Some(&secret)).await?;
+
+    Ok(Json(TotpSetupResponse {
+        uri: totp.get_url(),
+        secret,
+    }))
+}
+
+/// POST /api/v1/auth/totp/verify — confirm the pending secret and enable TOTP
+/// for the logged-in account.
+pub async fn totp_verify(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    user: Option<Extension<SessionUser>>,
+    Json(body): Json<TotpCodeRequest>,
+) -> Result<StatusCode, AppError> {
+    let Extension(user) = user.ok_or(AppError::Unauthorized)?;
+    let user_id = user.user_id.as_deref();
+    let secret = get_totp_secret(&state.db, user_id, TotpSlot::Pending)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation("No pending TOTP setup; call /auth/totp/setup first".to_string())
+        })?;
+
+    if !verify_totp(&secret, &body.code) {
+        return Err(AppError::Validation("Invalid TOTP code".to_string()));
+    }
+
+    let mut tx = state.db.begin().await?;
+    set_totp_secret(&mut tx, user_id, TotpSlot::Active, Some(&secret)).await?;
+    set_totp_secret(&mut tx, user_id, TotpSlot::Pending, None).await?;
+    tx.commit().await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "totp_enable",
+        "Enabled TOTP two-factor login",
+        &[],
+    )
+    .await;
+    tracing::info!(username = %user.username, "TOTP two-factor authentication enabled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/auth/totp/disable — remove the logged-in account's secret
+/// (requires a current code).
+pub async fn totp_disable(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    user: Option<Extension<SessionUser>>,
+    Json(body): Json<TotpCodeRequest>,
+) -> Result<StatusCode, AppError> {
+    let Extension(user) = user.ok_or(AppError::Unauthorized)?;
+    let user_id = user.user_id.as_deref();
+    let secret = get_totp_secret(&state.db, user_id, TotpSlot::Active)
+        .await?
+        .ok_or_else(|| AppError::Validation("TOTP is not enabled".to_string()))?;
+
+    if !verify_totp(&secret, &body.code) {
+        return Err(AppError::Validation("Invalid TOTP code".to_string()));
+    }
+
+    let mut tx = state.db.begin().await?;
+    set_totp_secret(&mut tx, user_id, TotpSlot::Active, None).await?;
+    set_totp_secret(&mut tx, user_id, TotpSlot::Pending, None).await?;
+    tx.commit().await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "totp_disable",
+        "Disabled TOTP two-factor login",
+        &[],
+    )
+    .await;
+    tracing::info!(username = %user.username, "TOTP two-factor authentication disabled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Extract the token from an `Authorization: Bearer <token>` header.
+fn extract_bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
 /// Extract the session token from the Cookie header.
 fn extract_session_token(req: &Request) -> Option<String> {
     let cookie_header = req.headers().get(header::COOKIE)?.to_str().ok()?;
@@ -644,4 +1141,235 @@ mod tests {
             "A session rejected by auth must also be cleaned up by purge — no leaks"
         );
     }
+
+    // ── TOTP ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_verify_totp() {
+        let secret = super::Secret::generate_secret().to_encoded().to_string();
+        let code = super::build_totp(&secret, "admin")
+            .unwrap()
+            .generate_current()
+            .unwrap();
+
+        assert!(super::verify_totp(&secret, &code));
+        assert!(!super::verify_totp(&secret, "not-a-code"));
+        assert!(!super::verify_totp("not base32!", &code));
+    }
+
+    /// Enable TOTP for `user`, check that logging in as `username` needs a
+    /// code, then disable it again.
+    async fn check_totp_lifecycle(
+        state: &super::AppState,
+        user: super::SessionUser,
+        username: Option<&str>,
+    ) {
+        use super::*;
+        use axum::extract::{ConnectInfo, State};
+
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let login_with = |totp_code: Option<String>| {
+            login(
+                State(state.clone()),
+                ConnectInfo(addr),
+                HeaderMap::new(),
+                Json(LoginRequest {
+                    username: username.map(str::to_string),
+                    password: "password123".to_string(),
+                    totp_code,
+                }),
+            )
+        };
+        let actor = || Actor(user.username.clone());
+        let session = || Some(Extension(user.clone()));
+
+        let Json(setup) = totp_setup(State(state.clone()), session()).await.unwrap();
+        assert!(setup.uri.starts_with("otpauth://totp/"));
+        assert!(setup.uri.contains(&user.username));
+        let code = build_totp(&setup.secret, "")
+            .unwrap()
+            .generate_current()
+            .unwrap();
+
+        // Not enforced until verified.
+        assert!(login_with(None).await.is_ok());
+
+        let err = totp_verify(
+            State(state.clone()),
+            actor(),
+            session(),
+            Json(TotpCodeRequest {
+                code: "000000x".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        let status = totp_verify(
+            State(state.clone()),
+            actor(),
+            session(),
+            Json(TotpCodeRequest { code: code.clone() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let resp = login_with(None).await.unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = login_with(Some("123".to_string())).await.unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(login_with(Some(code.clone())).await.is_ok());
+
+        // Setup is refused while enabled.
+        assert!(totp_setup(State(state.clone()), session()).await.is_err());
+
+        let status = totp_disable(
+            State(state.clone()),
+            actor(),
+            session(),
+            Json(TotpCodeRequest { code }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(login_with(None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_totp_enable_login_disable() {
+        use super::*;
+
+        let pool = test_db().await;
+        let hash = bcrypt::hash("password123", 4).unwrap();
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('admin_password_hash', ?)")
+            .bind(&hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        let admin = SessionUser {
+            user_id: None,
+            username: ADMIN_USERNAME.to_string(),
+            role: ROLE_ADMIN.to_string(),
+        };
+        check_totp_lifecycle(&state, admin.clone(), None).await;
+
+        // Secrets are stored encrypted.
+        let Json(setup) = totp_setup(axum::extract::State(state.clone()), Some(Extension(admin)))
+            .await
+            .unwrap();
+        let stored: String = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+            .bind(ADMIN_TOTP_PENDING_SECRET_KEY)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(stored.starts_with(crypto::SEALED_PREFIX));
+        assert!(!stored.contains(&setup.secret));
+    }
+
+    #[tokio::test]
+    async fn test_totp_for_named_admin() {
+        use super::*;
+
+        let pool = test_db().await;
+        let hash = bcrypt::hash("password123", 4).unwrap();
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, role) VALUES ('u1', 'alice', ?, 'admin')",
+        )
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        let alice = SessionUser {
+            user_id: Some("u1".to_string()),
+            username: "alice".to_string(),
+            role: ROLE_ADMIN.to_string(),
+        };
+        check_totp_lifecycle(&state, alice, Some("alice")).await;
+
+        // The built-in admin's settings were never touched.
+        let admin_secrets: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM settings WHERE key LIKE 'admin\\_totp%' ESCAPE '\\'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(admin_secrets, 0);
+    }
+
+    #[tokio::test]
+    async fn test_login_with_ldap_configured() {
+        use super::*;
+        use axum::extract::{ConnectInfo, State};
+
+        let pool = test_db().await;
+        let hash = bcrypt::hash("password123", 4).unwrap();
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('admin_password_hash', ?)")
+            .bind(&hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, role) VALUES ('u1', 'alice', ?, 'viewer')",
+        )
+        .bind(&hash)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut config = crate::config::AppConfig::default();
+        config.auth.ldap_url = Some("ldap://127.0.0.1:1".to_string());
+        config.auth.ldap_user_base_dn = Some("dc=example,dc=com".to_string());
+        let state = AppState::new(pool.clone(), config);
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let login_as = |username: Option<&str>| {
+            login(
+                State(state.clone()),
+                ConnectInfo(addr),
+                HeaderMap::new(),
+                Json(LoginRequest {
+                    username: username.map(str::to_string),
+                    password: "password123".to_string(),
+                    totp_code: None,
+                }),
+            )
+        };
+
+        // The directory is unreachable: local accounts still work.
+        assert!(login_as(Some("alice")).await.is_ok());
+        assert!(login_as(None).await.is_ok());
+
+        state.config.write().unwrap().auth.ldap_required = true;
+        let resp = login_as(Some("alice")).await.unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let resp = login_as(None).await.unwrap_err();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_viewer_cannot_set_up_totp() {
+        use super::*;
+        use axum::{body::Body, middleware, routing::post, Router};
+        use tower::Service;
+
+        let mut app = Router::new()
+            .route("/auth/totp/setup", post(|| async { StatusCode::OK }))
+            .route("/auth/logout", post(|| async { StatusCode::OK }))
+            .route_layer(middleware::from_fn(require_admin))
+            .layer(middleware::from_fn(|mut req: Request, next: Next| async {
+                req.extensions_mut().insert(SessionUser {
+                    user_id: None,
+                    username: "alice".to_string(),
+                    role: ROLE_VIEWER.to_string(),
+                });
+                next.run(req).await
+            }));
+        let post_to = |uri: &str| Request::post(uri).body(Body::empty()).unwrap();
+
+        let resp = app.call(post_to("/auth/totp/setup")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let resp = app.call(post_to("/auth/logout")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
 }
@@ -5,7 +5,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::AppState;
+use super::{AppError, AppState};
 
 // ── Types ────────────────────────────────────────────────────────────────────
 
@@ -85,18 +85,14 @@ struct BackupRow {
 pub async fn list(
     State(state): State<AppState>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<ConfigBackupListResponse>, StatusCode> {
+) -> Result<Json<ConfigBackupListResponse>, AppError> {
     let page = params.page.unwrap_or(1).max(1);
     let per_page = params.per_page.unwrap_or(25).clamp(1, 100);
     let offset = (page - 1) * per_page;
 
     let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM vyos_config_backups")
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("config_backups count failed: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     let rows = sqlx::query_as::<_, BackupSummaryRow>(
         "SELECT id, created_at, label, size_bytes, created_by \
@@ -105,11 +101,7 @@ pub async fn list(
     .bind(per_page)
     .bind(offset)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("config_backups list failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let items = rows
         .into_iter()
@@ -129,19 +121,17 @@ pub async fn list(
 pub async fn get_one(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<Json<ConfigBackup>, StatusCode> {
+) -> Result<Json<ConfigBackup>, AppError> {
     let row = sqlx::query_as::<_, BackupRow>(
         "SELECT id, created_at, label, config_text, size_bytes, created_by \
          FROM vyos_config_backups WHERE id = ?",
     )
     .bind(id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("config_backups get_one failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?
-    .ok_or(StatusCode::NOT_FOUND)?;
+    .await?
+    .ok_or_else(|| {
+        AppError::ResourceNotFound("config-backup", "Config backup not found".to_string())
+    })?;
 
     Ok(Json(ConfigBackup {
         id: row.id,
@@ -157,12 +147,12 @@ pub async fn get_one(
 pub async fn create(
     State(state): State<AppState>,
     Json(body): Json<CreateBackupRequest>,
-) -> Result<(StatusCode, Json<ConfigBackup>), StatusCode> {
-    let client = super::vyos::get_vyos_client_or_503(&state).await?;
+) -> Result<(StatusCode, Json<ConfigBackup>), AppError> {
+    let client = super::vyos::get_vyos_client(&state).await?;
 
     let config_text = fetch_running_config(&client).await.map_err(|e| {
         tracing::error!("Failed to fetch running config for backup: {e}");
-        StatusCode::BAD_GATEWAY
+        AppError::BadGateway(format!("Failed to fetch running config: {e}"))
     })?;
 
     let size_bytes = config_text.len() as i64;
@@ -175,11 +165,7 @@ pub async fn create(
     .bind(&config_text)
     .bind(size_bytes)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("config_backups insert failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let row = sqlx::query_as::<_, BackupRow>(
         "SELECT id, created_at, label, config_text, size_bytes, created_by \
@@ -187,11 +173,7 @@ pub async fn create(
     )
     .bind(id)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("config_backups fetch after insert failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     Ok((
         StatusCode::CREATED,
@@ -210,18 +192,17 @@ pub async fn create(
 pub async fn delete(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let result = sqlx::query("DELETE FROM vyos_config_backups WHERE id = ?")
         .bind(id)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("config_backups delete failed: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "config-backup",
+            "Config backup not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -230,12 +211,12 @@ pub async fn delete(
 /// GET /api/v1/config-backups/current — fetch current running config from VyOS.
 pub async fn show_current(
     State(state): State<AppState>,
-) -> Result<Json<ShowConfigResponse>, StatusCode> {
-    let client = super::vyos::get_vyos_client_or_503(&state).await?;
+) -> Result<Json<ShowConfigResponse>, AppError> {
+    let client = super::vyos::get_vyos_client(&state).await?;
 
     let config_text = fetch_running_config(&client).await.map_err(|e| {
         tracing::error!("Failed to fetch running config: {e}");
-        StatusCode::BAD_GATEWAY
+        AppError::BadGateway(format!("Failed to fetch running config: {e}"))
     })?;
 
     Ok(Json(ShowConfigResponse { config_text }))
@@ -245,8 +226,8 @@ pub async fn show_current(
 pub async fn diff(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-) -> Result<Json<ConfigDiffResponse>, StatusCode> {
-    let client = super::vyos::get_vyos_client_or_503(&state).await?;
+) -> Result<Json<ConfigDiffResponse>, AppError> {
+    let client = super::vyos::get_vyos_client(&state).await?;
 
     // Fetch the backup from DB
     let row = sqlx::query_as::<_, BackupRow>(
@@ -255,17 +236,15 @@ pub async fn diff(
     )
     .bind(id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("config_backups diff query failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?
-    .ok_or(StatusCode::NOT_FOUND)?;
+    .await?
+    .ok_or_else(|| {
+        AppError::ResourceNotFound("config-backup", "Config backup not found".to_string())
+    })?;
 
     // Fetch current running config
     let current = fetch_running_config(&client).await.map_err(|e| {
         tracing::error!("Failed to fetch running config for diff: {e}");
-        StatusCode::BAD_GATEWAY
+        AppError::BadGateway(format!("Failed to fetch running config: {e}"))
     })?;
 
     Ok(Json(ConfigDiffResponse {
@@ -13,6 +13,9 @@ pub struct DashboardStats {
     pub alerts_unread: i64,
     pub wan_rx_bps: i64,
     pub wan_tx_bps: i64,
+    /// Agent version shipped with this server; older agents are listed by
+    /// `GET /agents/outdated`.
+    pub latest_agent_version: String,
 }
 
 #[derive(Serialize)]
@@ -34,54 +37,34 @@ pub struct LimitQuery {
 /// GET /api/v1/dashboard/stats
 pub async fn stats(State(state): State<AppState>) -> Json<DashboardStats> {
     let devices_online: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM devices WHERE is_online = 1")
+        sqlx::query_scalar("SELECT COUNT(*) FROM devices WHERE is_online = 1 AND is_deleted = 0")
             .fetch_one(&state.db)
             .await
             .unwrap_or(0);
 
-    let devices_total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM devices")
-        .fetch_one(&state.db)
-        .await
-        .unwrap_or(0);
+    let devices_total: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM devices WHERE is_deleted = 0")
+            .fetch_one(&state.db)
+            .await
+            .unwrap_or(0);
 
     let alerts_unread: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alerts WHERE is_read = 0")
         .fetch_one(&state.db)
         .await
         .unwrap_or(0);
 
-    // Check VyOS connectivity — read settings from DB first, fall back to config.
-    let router_status = {
-        let db_url: Option<String> =
-            sqlx::query_scalar(r#"SELECT value FROM settings WHERE key = 'vyos_url'"#)
-                .fetch_optional(&state.db)
-                .await
-                .ok()
-                .flatten();
-        let db_key: Option<String> =
-            sqlx::query_scalar(r#"SELECT value FROM settings WHERE key = 'vyos_api_key'"#)
-                .fetch_optional(&state.db)
-                .await
-                .ok()
-                .flatten();
-
-        let url = db_url
-            .filter(|s| !s.is_empty())
-            .or_else(|| state.config.vyos.url.clone());
-        let key = db_key
-            .filter(|s| !s.is_empty())
-            .or_else(|| state.config.vyos.api_key.clone());
-
-        match (url, key) {
-            (Some(u), Some(k)) if !u.is_empty() && !k.is_empty() => {
-                let client = crate::vyos::client::VyosClient::new(&u, &k);
-                match client.show(&["system", "uptime"]).await {
-                    Ok(_) => "connected".to_string(),
-                    Err(_) => "disconnected".to_string(),
-                }
-            }
-            _ => "unconfigured".to_string(),
-        }
-    };
+    // Check VyOS connectivity for the active router profile (or router settings).
+    let config = state.config().clone();
+    let router_status =
+        match super::vyos::get_vyos_client_from_db(&state.db, &config, &state.active_profile_id)
+            .await
+        {
+            Some(client) => match client.show(&["system", "uptime"]).await {
+                Ok(_) => "connected".to_string(),
+                Err(_) => "disconnected".to_string(),
+            },
+            None => "unconfigured".to_string(),
+        };
 
     // Latest WAN traffic from traffic_samples (source = 'vyos'), most recent entry
     let (wan_rx_bps, wan_tx_bps): (i64, i64) = sqlx::query_as(
@@ -102,6 +85,7 @@ pub async fn stats(State(state): State<AppState>) -> Json<DashboardStats> {
         alerts_unread,
         wan_rx_bps,
         wan_tx_bps,
+        latest_agent_version: super::agents::LATEST_AGENT_VERSION.to_string(),
     })
 }
 
@@ -133,7 +117,7 @@ pub async fn top_devices(
                         ROW_NUMBER() OVER (PARTITION BY device_id ORDER BY sampled_at DESC) as rn
                  FROM traffic_samples
              ) ts ON ts.device_id = d.id AND ts.rn = 1
-             WHERE d.is_online = 1
+             WHERE d.is_online = 1 AND d.is_deleted = 0
              ORDER BY (COALESCE(ts.rx_bps, 0) + COALESCE(ts.tx_bps, 0)) DESC
              LIMIT ?",
     )
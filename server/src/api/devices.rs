@@ -4,11 +4,20 @@ use axum::{
     response::IntoResponse,
     Json,
 };
+use ipnetwork::Ipv4Network;
 use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
 use sqlx::Row;
-use std::net::UdpSocket;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::sync::Arc;
 
+use super::alerts::{is_device_muted, severity_for_alert_type, SeverityOverrideCache};
+use super::audit::{self, Actor};
 use super::{AppError, AppState};
+use crate::scanner::tls_cert;
+use crate::ssdp::UpnpInfo;
+use crate::webhook;
+use crate::ws::hub::WsHub;
 
 /// Agent summary attached to a device response.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,6 +46,8 @@ pub struct Device {
     pub is_online: bool,
     /// Current IP address(es) from device_ips table
     pub ips: Vec<String>,
+    /// Operator-assigned tags, sorted alphabetically
+    pub tags: Vec<String>,
     /// mDNS/Bonjour discovered service types (comma-separated)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mdns_services: Option<String>,
@@ -61,12 +72,21 @@ pub struct Device {
     /// Device brand (e.g. "Apple", "Samsung")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_brand: Option<String>,
+    /// Best OS guess from mDNS service types or nmap OS detection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_hint: Option<String>,
     /// Which enrichment source provided the identification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enrichment_source: Option<String>,
     /// Whether user has manually corrected the enrichment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enrichment_corrected: Option<bool>,
+    /// UPnP device description learned from SSDP announcements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upnp_info: Option<UpnpInfo>,
+    /// First 100 characters of the most recent note (single-device responses only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_note: Option<String>,
 }
 
 /// Request body for creating a device.
@@ -115,7 +135,8 @@ impl Device {
             first_seen_at: row.try_get("first_seen_at")?,
             last_seen_at: row.try_get("last_seen_at")?,
             is_online: row.try_get::<i32, _>("is_online").unwrap_or(0) != 0,
-            ips: vec![], // populated after query
+            ips: vec![],  // populated after query
+            tags: vec![], // populated after query
             mdns_services: row.try_get("mdns_services").unwrap_or(None),
             agent,
             muted_until: row.try_get("muted_until").unwrap_or(None),
@@ -124,24 +145,42 @@ impl Device {
             device_type: row.try_get("device_type").unwrap_or(None),
             device_model: row.try_get("device_model").unwrap_or(None),
             device_brand: row.try_get("device_brand").unwrap_or(None),
+            os_hint: row.try_get("os_hint").unwrap_or(None),
             enrichment_source: row.try_get("enrichment_source").unwrap_or(None),
             enrichment_corrected: row
                 .try_get::<i32, _>("enrichment_corrected")
                 .ok()
                 .map(|v| v != 0),
+            upnp_info: row
+                .try_get::<Option<String>, _>("upnp_info")
+                .ok()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            latest_note: row.try_get("latest_note").unwrap_or(None),
         })
     }
 }
 
-/// GET /api/v1/devices — list all devices.
-pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Device>>, StatusCode> {
+/// Query parameters for `GET /api/v1/devices`.
+#[derive(Debug, Deserialize)]
+pub struct ListDevicesQuery {
+    /// Only return devices carrying this tag.
+    pub tag: Option<String>,
+}
+
+/// GET /api/v1/devices — list all devices, optionally filtered by `?tag=`.
+pub async fn list(
+    State(state): State<AppState>,
+    Query(params): Query<ListDevicesQuery>,
+) -> Result<Json<Vec<Device>>, AppError> {
     let rows = sqlx::query(
         r#"
         SELECT d.id, d.mac, d.name, d.hostname, d.vendor, d.icon, d.notes,
                d.is_known, d.is_favorite, d.first_seen_at, d.last_seen_at, d.is_online,
                d.mdns_services, d.muted_until,
                d.os_family, d.os_version, d.device_type, d.device_model,
-               d.device_brand, d.enrichment_source, d.enrichment_corrected,
+               d.device_brand, d.os_hint, d.enrichment_source, d.enrichment_corrected,
+               d.upnp_info,
                a.id AS agent_id,
                a.name AS agent_name,
                r.cpu_percent AS agent_cpu_percent,
@@ -157,15 +196,14 @@ pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Device>>, St
             AND r.reported_at = (
                 SELECT MAX(ar.reported_at) FROM agent_reports ar WHERE ar.agent_id = a.id
             )
+        LEFT JOIN device_tags t ON t.device_id = d.id AND t.tag = ?1
+        WHERE d.is_deleted = 0 AND (?1 IS NULL OR t.tag IS NOT NULL)
         ORDER BY d.last_seen_at DESC
     "#,
     )
+    .bind(&params.tag)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to list devices: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let mut devices: Vec<Device> = rows
         .into_iter()
@@ -175,7 +213,7 @@ pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Device>>, St
     // Fetch current IPs for all devices in one query
     if !devices.is_empty() {
         let ip_rows = sqlx::query(
-            "SELECT device_id, ip FROM device_ips WHERE is_current = 1 ORDER BY device_id",
+            "SELECT device_id, ip FROM device_ips WHERE is_current = 1 ORDER BY device_id, ip_version",
         )
         .fetch_all(&state.db)
         .await
@@ -188,6 +226,18 @@ pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Device>>, St
                 dev.ips.push(ip);
             }
         }
+
+        let tag_rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT device_id, tag FROM device_tags ORDER BY device_id, tag")
+                .fetch_all(&state.db)
+                .await
+                .unwrap_or_default();
+
+        for (device_id, tag) in tag_rows {
+            if let Some(dev) = devices.iter_mut().find(|d| d.id == device_id) {
+                dev.tags.push(tag);
+            }
+        }
     }
 
     Ok(Json(devices))
@@ -204,7 +254,8 @@ pub async fn get_one(
                d.is_known, d.is_favorite, d.first_seen_at, d.last_seen_at, d.is_online,
                d.mdns_services, d.muted_until,
                d.os_family, d.os_version, d.device_type, d.device_model,
-               d.device_brand, d.enrichment_source, d.enrichment_corrected,
+               d.device_brand, d.os_hint, d.enrichment_source, d.enrichment_corrected,
+               d.upnp_info,
                a.id AS agent_id,
                a.name AS agent_name,
                r.cpu_percent AS agent_cpu_percent,
@@ -213,14 +264,17 @@ pub async fn get_one(
                     ELSE NULL END AS agent_memory_percent,
                CASE WHEN a.last_report_at IS NOT NULL
                          AND a.last_report_at > datetime('now', '-120 seconds')
-                    THEN 1 ELSE 0 END AS agent_is_online
+                    THEN 1 ELSE 0 END AS agent_is_online,
+               (SELECT substr(n.body, 1, 100) FROM device_notes n
+                WHERE n.device_id = d.id
+                ORDER BY n.created_at DESC, n.rowid DESC LIMIT 1) AS latest_note
         FROM devices d
         LEFT JOIN agents a ON a.device_id = d.id
         LEFT JOIN agent_reports r ON r.agent_id = a.id
             AND r.reported_at = (
                 SELECT MAX(ar.reported_at) FROM agent_reports ar WHERE ar.agent_id = a.id
             )
-        WHERE d.id = ?
+        WHERE d.id = ? AND d.is_deleted = 0
     "#,
     )
     .bind(&id)
@@ -232,17 +286,21 @@ pub async fn get_one(
         .map_err(|e| AppError::Internal(format!("Failed to parse device row: {e}")))?;
 
     // Fetch current IPs for the device.
-    let ip_rows = sqlx::query("SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1")
-        .bind(&id)
-        .fetch_all(&state.db)
-        .await
-        .unwrap_or_default();
+    let ip_rows = sqlx::query(
+        "SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 ORDER BY ip_version",
+    )
+    .bind(&id)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default();
 
     for ip_row in ip_rows {
         let ip: String = ip_row.try_get("ip").unwrap_or_default();
         device.ips.push(ip);
     }
 
+    device.tags = load_tags(&state.db, &id).await?;
+
     Ok(Json(device))
 }
 
@@ -250,7 +308,7 @@ pub async fn get_one(
 pub async fn create(
     State(state): State<AppState>,
     Json(body): Json<CreateDevice>,
-) -> Result<(StatusCode, Json<Device>), StatusCode> {
+) -> Result<(StatusCode, Json<Device>), AppError> {
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -265,11 +323,7 @@ pub async fn create(
     .bind(&now)
     .bind(&now)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to create device: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let device = Device {
         id,
@@ -285,6 +339,7 @@ pub async fn create(
         last_seen_at: now,
         is_online: false,
         ips: vec![],
+        tags: vec![],
         mdns_services: None,
         agent: None,
         muted_until: None,
@@ -293,19 +348,414 @@ pub async fn create(
         device_type: None,
         device_model: None,
         device_brand: None,
+        os_hint: None,
         enrichment_source: None,
         enrichment_corrected: None,
+        upnp_info: None,
+        latest_note: None,
     };
 
     Ok((StatusCode::CREATED, Json(device)))
 }
 
+// ── Bulk Import ─────────────────────────────────────────────────────────────
+
+/// Maximum number of rows accepted by a single import.
+pub const MAX_IMPORT_ROWS: usize = 5000;
+
+/// Maximum request body size for the import endpoint.
+pub const MAX_IMPORT_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// A row that could not be imported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportRowError {
+    /// 1-based data row number (the CSV header is row 0).
+    pub row: usize,
+    /// Older history records stored this as `message`.
+    #[serde(alias = "message")]
+    pub reason: String,
+}
+
+/// Outcome of a bulk device import.
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub total_rows: usize,
+    pub imported: usize,
+    pub skipped: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A past import as returned by the import history endpoint.
+#[derive(Debug, Serialize)]
+pub struct DeviceImportRecord {
+    pub id: i64,
+    pub imported_at: String,
+    pub total_rows: i64,
+    pub imported_count: i64,
+    pub skipped_count: i64,
+    pub error_count: i64,
+    pub errors: Vec<ImportRowError>,
+    pub filename: Option<String>,
+}
+
+/// Query parameters for the import history endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ImportHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// Split one CSV line into fields, honouring double-quoted values.
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Extract the filename from a `Content-Disposition` header value.
+fn content_disposition_filename(value: &str) -> Option<String> {
+    value.split(';').find_map(|part| {
+        let (key, val) = part.trim().split_once('=')?;
+        (key.trim().eq_ignore_ascii_case("filename"))
+            .then(|| val.trim().trim_matches('"').to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+/// One device row from a CSV or JSON import.
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportRow {
+    #[serde(alias = "mac_address")]
+    pub mac: String,
+    #[serde(default, alias = "name")]
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub hostname: Option<String>,
+    #[serde(default, alias = "ip_address")]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Parse CSV text into import rows.
+///
+/// The header row must contain `mac` (or `mac_address`); `alias` (or
+/// `name`), `hostname`, `ip` (or `ip_address`), `notes` and `tags` are
+/// optional, so the devices export can be re-imported as-is. Tags within a
+/// field are separated by semicolons.
+fn parse_import_csv(csv: &str) -> Result<Vec<ImportRow>, AppError> {
+    let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .map(parse_csv_line)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let col_mac = column(&["mac_address", "mac"])
+        .ok_or_else(|| AppError::Validation("CSV header must include mac_address".to_string()))?;
+    let col_alias = column(&["alias", "name"]);
+    let col_ip = column(&["ip_address", "ip"]);
+    let col_hostname = column(&["hostname"]);
+    let col_notes = column(&["notes"]);
+    let col_tags = column(&["tags"]);
+
+    Ok(lines
+        .map(|line| {
+            let fields = parse_csv_line(line);
+            let field = |col: Option<usize>| {
+                col.and_then(|c| fields.get(c))
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+            ImportRow {
+                mac: field(Some(col_mac)).unwrap_or_default(),
+                alias: field(col_alias),
+                hostname: field(col_hostname),
+                ip: field(col_ip),
+                notes: field(col_notes),
+                tags: field(col_tags)
+                    .map(|t| t.split(';').map(str::to_string).collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+/// A validated import row, ready to be written.
+struct ValidImportRow {
+    mac: String,
+    alias: Option<String>,
+    hostname: Option<String>,
+    ip: Option<String>,
+    notes: Option<String>,
+    tags: Vec<String>,
+}
+
+/// Validate one import row, returning the reason it cannot be imported.
+fn validate_import_row(row: ImportRow) -> Result<ValidImportRow, String> {
+    let non_empty = |v: Option<String>| v.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    let mac = super::agents::normalize_mac(row.mac.trim())
+        .ok_or_else(|| format!("Invalid MAC address '{}'", row.mac.trim()))?;
+    let ip = non_empty(row.ip);
+    if let Some(ref ip) = ip {
+        if ip.parse::<std::net::IpAddr>().is_err() {
+            return Err(format!("Invalid IP address '{ip}'"));
+        }
+    }
+    let notes = non_empty(row.notes);
+    if let Some(ref notes) = notes {
+        validate_note_body(notes).map_err(|e| e.to_string())?;
+    }
+    let mut tags: Vec<String> = row
+        .tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    for tag in &tags {
+        validate_tag(tag).map_err(|e| e.to_string())?;
+    }
+    tags.sort_unstable();
+    tags.dedup();
+
+    Ok(ValidImportRow {
+        mac,
+        alias: non_empty(row.alias),
+        hostname: non_empty(row.hostname),
+        ip,
+        notes,
+        tags,
+    })
+}
+
+/// Import devices and record the outcome in `device_imports`.
+///
+/// Rows are upserted on the normalized MAC in a single transaction: new
+/// devices are created, existing ones get the alias, note and tags from the
+/// row. Existing devices with nothing to apply are counted as skipped.
+/// Invalid rows are reported in `errors` without affecting the others.
+async fn import_devices(
+    db: &sqlx::SqlitePool,
+    rows: Vec<ImportRow>,
+    filename: Option<&str>,
+) -> Result<ImportResult, AppError> {
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(AppError::Validation(format!(
+            "At most {MAX_IMPORT_ROWS} rows per import"
+        )));
+    }
+
+    let mut result = ImportResult {
+        total_rows: rows.len(),
+        imported: 0,
+        skipped: 0,
+        errors: Vec::new(),
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut tx = db.begin().await?;
+    for (idx, row) in rows.into_iter().enumerate() {
+        let row_number = idx + 1;
+        let row = match validate_import_row(row) {
+            Ok(row) => row,
+            Err(reason) => {
+                result.errors.push(ImportRowError {
+                    row: row_number,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let existing: Option<String> = sqlx::query_scalar("SELECT id FROM devices WHERE mac = ?")
+            .bind(&row.mac)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let id = match existing {
+            Some(id) => {
+                if row.alias.is_none() && row.notes.is_none() && row.tags.is_empty() {
+                    result.skipped += 1;
+                    continue;
+                }
+                sqlx::query(
+                    "UPDATE devices SET name = COALESCE(?, name), is_deleted = 0, updated_at = ? \
+                     WHERE id = ?",
+                )
+                .bind(&row.alias)
+                .bind(&now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+                id
+            }
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                sqlx::query(
+                    "INSERT INTO devices (id, mac, name, hostname, first_seen_at, last_seen_at) \
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&id)
+                .bind(&row.mac)
+                .bind(&row.alias)
+                .bind(&row.hostname)
+                .bind(&now)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await?;
+                if let Some(ref ip) = row.ip {
+                    sqlx::query(
+                        "INSERT INTO device_ips (device_id, ip, seen_at, is_current) VALUES (?, ?, ?, 1)",
+                    )
+                    .bind(&id)
+                    .bind(ip)
+                    .bind(&now)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                id
+            }
+        };
+
+        if let Some(ref notes) = row.notes {
+            sqlx::query(
+                "INSERT INTO device_notes (id, device_id, body, created_at, updated_at) \
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(&id)
+            .bind(notes)
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        for tag in &row.tags {
+            sqlx::query("INSERT OR IGNORE INTO device_tags (device_id, tag) VALUES (?, ?)")
+                .bind(&id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+        result.imported += 1;
+    }
+
+    let errors_json = serde_json::to_string(&result.errors).unwrap_or_else(|_| "[]".to_string());
+    sqlx::query(
+        "INSERT INTO device_imports \
+         (total_rows, imported_count, skipped_count, error_count, errors_json, filename) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(result.total_rows as i64)
+    .bind(result.imported as i64)
+    .bind(result.skipped as i64)
+    .bind(result.errors.len() as i64)
+    .bind(&errors_json)
+    .bind(filename)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+/// POST /api/v1/devices/import — bulk-import devices from a CSV or JSON body.
+///
+/// `Content-Type: application/json` selects a JSON array of device objects;
+/// anything else is parsed as CSV. The original filename is taken from the
+/// request's `Content-Disposition: attachment; filename="..."` header, if
+/// present.
+pub async fn import(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> Result<Json<ImportResult>, AppError> {
+    let filename = headers
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(content_disposition_filename);
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+
+    let rows = if is_json {
+        serde_json::from_str(&body)
+            .map_err(|e| AppError::Validation(format!("Invalid JSON import: {e}")))?
+    } else {
+        parse_import_csv(&body)?
+    };
+
+    let result = import_devices(&state.db, rows, filename.as_deref()).await?;
+    tracing::info!(
+        imported = result.imported,
+        skipped = result.skipped,
+        errors = result.errors.len(),
+        "Device import completed"
+    );
+    Ok(Json(result))
+}
+
+/// GET /api/v1/devices/import-history?limit=10 — most recent device imports.
+pub async fn import_history(
+    State(state): State<AppState>,
+    Query(params): Query<ImportHistoryQuery>,
+) -> Result<Json<Vec<DeviceImportRecord>>, AppError> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+
+    let rows = sqlx::query(
+        "SELECT id, imported_at, total_rows, imported_count, skipped_count, error_count, \
+                errors_json, filename \
+         FROM device_imports ORDER BY id DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await?;
+
+    let records = rows
+        .into_iter()
+        .map(|row| {
+            let errors_json: String = row.try_get("errors_json").unwrap_or_default();
+            DeviceImportRecord {
+                id: row.try_get("id").unwrap_or_default(),
+                imported_at: row.try_get("imported_at").unwrap_or_default(),
+                total_rows: row.try_get("total_rows").unwrap_or_default(),
+                imported_count: row.try_get("imported_count").unwrap_or_default(),
+                skipped_count: row.try_get("skipped_count").unwrap_or_default(),
+                error_count: row.try_get("error_count").unwrap_or_default(),
+                errors: serde_json::from_str(&errors_json).unwrap_or_default(),
+                filename: row.try_get("filename").ok().flatten(),
+            }
+        })
+        .collect();
+
+    Ok(Json(records))
+}
+
 /// PATCH /api/v1/devices/:id — update device fields.
 pub async fn update(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(body): Json<UpdateDevice>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let now = chrono::Utc::now().to_rfc3339();
 
     let result = sqlx::query(
@@ -326,14 +776,13 @@ pub async fn update(
     .bind(&now)
     .bind(&id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to update device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "device",
+            "Device not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -358,7 +807,7 @@ pub async fn events(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Query(params): Query<EventsQuery>,
-) -> Result<Json<Vec<DeviceEvent>>, StatusCode> {
+) -> Result<Json<Vec<DeviceEvent>>, AppError> {
     let limit = params.limit.unwrap_or(50).min(500);
 
     let rows = sqlx::query(
@@ -367,11 +816,7 @@ pub async fn events(
     .bind(&id)
     .bind(limit)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch device events for {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let events: Vec<DeviceEvent> = rows
         .into_iter()
@@ -404,7 +849,7 @@ pub async fn uptime(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Query(params): Query<UptimeQuery>,
-) -> Result<Json<UptimeStats>, StatusCode> {
+) -> Result<Json<UptimeStats>, AppError> {
     let days = params.days.unwrap_or(7).clamp(1, 365);
     let total_seconds = days * 86400;
 
@@ -417,11 +862,7 @@ pub async fn uptime(
     .bind(&id)
     .bind(&cutoff_str)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch uptime data for {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let now = chrono::Utc::now();
 
@@ -432,11 +873,7 @@ pub async fn uptime(
     .bind(&id)
     .bind(&cutoff_str)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch prior event for {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?
+    .await?
     .map(|row| row.try_get("event_type").unwrap_or_default());
 
     // Determine initial state at start of window
@@ -487,6 +924,332 @@ pub async fn uptime(
     }))
 }
 
+/// Default availability lookback in days.
+const DEFAULT_AVAILABILITY_DAYS: u32 = 30;
+/// Longest availability lookback in days.
+const MAX_AVAILABILITY_DAYS: u32 = 365;
+
+/// Query parameters for the availability endpoint.
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityQuery {
+    /// Lookback window such as `30d` (a bare number is also read as days).
+    pub window: Option<String>,
+}
+
+/// Online/offline totals for a device over a lookback window.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Availability {
+    pub device_id: String,
+    pub window_days: u32,
+    /// `null` when the state log has no entries in the window.
+    pub uptime_pct: Option<f64>,
+    pub total_online_secs: u64,
+    pub total_offline_secs: u64,
+    pub transition_count: u32,
+}
+
+/// Parse an availability window (`30d` or `30`) into days.
+pub fn parse_window_days(window: &str) -> Result<u32, AppError> {
+    let days = window.trim();
+    let days = days.strip_suffix('d').unwrap_or(days);
+    days.parse::<u32>()
+        .ok()
+        .filter(|d| (1..=MAX_AVAILABILITY_DAYS).contains(d))
+        .ok_or_else(|| {
+            AppError::Validation(format!(
+                "window must be between 1d and {MAX_AVAILABILITY_DAYS}d, got '{window}'"
+            ))
+        })
+}
+
+/// Time spent online and offline between `start` and `end`, from the device
+/// state log. The last state logged before `start` seeds the window so the
+/// time before the first in-window change is attributed correctly; each
+/// state lasts until the next change (LEAD) and transitions are counted
+/// where the state differs from the previous entry (LAG).
+///
+/// Returns `(in_window_entries, online_secs, offline_secs, transitions)`.
+async fn state_log_totals(
+    db: &sqlx::SqlitePool,
+    device_id: &str,
+    start: &str,
+    end: &str,
+) -> Result<(i64, f64, f64, i64), sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        WITH changes AS (
+            SELECT state, julianday(?2) AS at, 0 AS in_window
+            FROM (
+                SELECT state FROM device_state_log
+                WHERE device_id = ?1 AND julianday(changed_at) < julianday(?2)
+                ORDER BY julianday(changed_at) DESC, id DESC
+                LIMIT 1
+            )
+            UNION ALL
+            SELECT state, julianday(changed_at), 1
+            FROM device_state_log
+            WHERE device_id = ?1
+              AND julianday(changed_at) >= julianday(?2)
+              AND julianday(changed_at) <= julianday(?3)
+        ),
+        intervals AS (
+            SELECT state, in_window,
+                   LAG(state) OVER (ORDER BY at) AS prev_state,
+                   (COALESCE(LEAD(at) OVER (ORDER BY at), julianday(?3)) - at) * 86400 AS secs
+            FROM changes
+        )
+        SELECT COALESCE(SUM(in_window), 0),
+               COALESCE(SUM(CASE WHEN state = 'online' THEN secs END), 0.0),
+               COALESCE(SUM(CASE WHEN state = 'offline' THEN secs END), 0.0),
+               COALESCE(SUM(prev_state IS NOT NULL AND prev_state != state), 0)
+        FROM intervals
+        "#,
+    )
+    .bind(device_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(db)
+    .await
+}
+
+/// GET /api/v1/devices/:id/availability?window=30d — SLA-style availability
+/// from the device state log. The window starts no earlier than the device's
+/// `first_seen_at`.
+pub async fn availability(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<AvailabilityQuery>,
+) -> Result<Json<Availability>, AppError> {
+    let window_days = match params.window.as_deref() {
+        Some(window) => parse_window_days(window)?,
+        None => DEFAULT_AVAILABILITY_DAYS,
+    };
+
+    let first_seen_at: String =
+        sqlx::query_scalar("SELECT first_seen_at FROM devices WHERE id = ?")
+            .bind(&id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+
+    let now = chrono::Utc::now();
+    let mut start = now - chrono::Duration::days(window_days.into());
+    if let Ok(first_seen) = chrono::DateTime::parse_from_rfc3339(&first_seen_at) {
+        start = start.max(first_seen.with_timezone(&chrono::Utc));
+    }
+
+    let (entries, online_secs, offline_secs, transitions) =
+        state_log_totals(&state.db, &id, &start.to_rfc3339(), &now.to_rfc3339()).await?;
+
+    if entries == 0 {
+        return Ok(Json(Availability {
+            device_id: id,
+            window_days,
+            uptime_pct: None,
+            total_online_secs: 0,
+            total_offline_secs: 0,
+            transition_count: 0,
+        }));
+    }
+
+    let total_online_secs = online_secs.max(0.0).round() as u64;
+    let total_offline_secs = offline_secs.max(0.0).round() as u64;
+    let total = total_online_secs + total_offline_secs;
+    let uptime_pct = (total > 0).then(|| total_online_secs as f64 / total as f64 * 100.0);
+
+    Ok(Json(Availability {
+        device_id: id,
+        window_days,
+        uptime_pct,
+        total_online_secs,
+        total_offline_secs,
+        transition_count: transitions.try_into().unwrap_or(u32::MAX),
+    }))
+}
+
+/// Default number of periods in an SLA report.
+const DEFAULT_SLA_PERIODS: u32 = 4;
+/// Most periods an SLA report may cover.
+const MAX_SLA_PERIODS: u32 = 52;
+
+/// Calendar period an SLA report is broken into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlaPeriod {
+    /// Monday to Sunday.
+    Weekly,
+    /// First to last day of the month.
+    Monthly,
+}
+
+/// Query parameters for the SLA report endpoint.
+#[derive(Debug, Deserialize)]
+pub struct SlaReportQuery {
+    pub period: Option<SlaPeriod>,
+    pub count: Option<u32>,
+}
+
+/// Availability of a device over one calendar period.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SlaReportPeriod {
+    /// Start of the period (UTC midnight).
+    pub period_start: String,
+    /// Exclusive end of the period: the start of the next one.
+    pub period_end: String,
+    /// `null` when nothing is known about the device's state in the period.
+    pub uptime_pct: Option<f64>,
+    pub uptime_secs: u64,
+    pub downtime_secs: u64,
+    pub outage_count: u32,
+    pub longest_outage_secs: u64,
+}
+
+/// The last `count` calendar periods up to and including the one containing
+/// `today`, oldest first, as `[start, end)` date pairs.
+pub fn sla_period_bounds(
+    period: SlaPeriod,
+    count: u32,
+    today: chrono::NaiveDate,
+) -> Vec<(chrono::NaiveDate, chrono::NaiveDate)> {
+    use chrono::{Datelike, Days, Months};
+
+    let current = match period {
+        SlaPeriod::Weekly => today - Days::new(today.weekday().num_days_from_monday().into()),
+        SlaPeriod::Monthly => today.with_day(1).expect("day 1 exists in every month"),
+    };
+    let advance = |start: chrono::NaiveDate, periods: u32| match period {
+        SlaPeriod::Weekly => start + Days::new(u64::from(periods) * 7),
+        SlaPeriod::Monthly => start + Months::new(periods),
+    };
+    let back = |start: chrono::NaiveDate, periods: u32| match period {
+        SlaPeriod::Weekly => start - Days::new(u64::from(periods) * 7),
+        SlaPeriod::Monthly => start - Months::new(periods),
+    };
+    (0..count)
+        .rev()
+        .map(|periods| {
+            let start = back(current, periods);
+            (start, advance(start, 1))
+        })
+        .collect()
+}
+
+/// Online and offline time plus outage statistics between `start` and `end`,
+/// from the device state log. Seeds the window like [`state_log_totals`];
+/// consecutive offline entries (LAG) are grouped into one outage.
+///
+/// Returns `(entries, online_secs, offline_secs, outages, longest_outage_secs)`,
+/// where `entries` includes the seeding state.
+async fn state_log_outages(
+    db: &sqlx::SqlitePool,
+    device_id: &str,
+    start: &str,
+    end: &str,
+) -> Result<(i64, f64, f64, i64, f64), sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        WITH changes AS (
+            SELECT state, julianday(?2) AS at
+            FROM (
+                SELECT state FROM device_state_log
+                WHERE device_id = ?1 AND julianday(changed_at) < julianday(?2)
+                ORDER BY julianday(changed_at) DESC, id DESC
+                LIMIT 1
+            )
+            UNION ALL
+            SELECT state, julianday(changed_at)
+            FROM device_state_log
+            WHERE device_id = ?1
+              AND julianday(changed_at) >= julianday(?2)
+              AND julianday(changed_at) < julianday(?3)
+        ),
+        intervals AS (
+            SELECT state, at,
+                   LAG(state) OVER (ORDER BY at) AS prev_state,
+                   (COALESCE(LEAD(at) OVER (ORDER BY at), julianday(?3)) - at) * 86400 AS secs
+            FROM changes
+        ),
+        runs AS (
+            SELECT state, secs,
+                   SUM(prev_state IS NULL OR prev_state != state)
+                       OVER (ORDER BY at ROWS UNBOUNDED PRECEDING) AS run
+            FROM intervals
+        ),
+        outages AS (
+            SELECT SUM(secs) AS secs FROM runs WHERE state = 'offline' GROUP BY run
+        )
+        SELECT (SELECT COUNT(*) FROM runs),
+               (SELECT COALESCE(SUM(CASE WHEN state = 'online' THEN secs END), 0.0) FROM runs),
+               (SELECT COALESCE(SUM(CASE WHEN state = 'offline' THEN secs END), 0.0) FROM runs),
+               (SELECT COUNT(*) FROM outages),
+               (SELECT COALESCE(MAX(secs), 0.0) FROM outages)
+        "#,
+    )
+    .bind(device_id)
+    .bind(start)
+    .bind(end)
+    .fetch_one(db)
+    .await
+}
+
+/// GET /api/v1/devices/:id/sla-report?period=weekly|monthly&count=4 — uptime
+/// per calendar period (UTC), oldest first. The current period runs up to
+/// now, and periods are pro-rated to the device's `first_seen_at`.
+pub async fn sla_report(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<SlaReportQuery>,
+) -> Result<Json<Vec<SlaReportPeriod>>, AppError> {
+    let count = params.count.unwrap_or(DEFAULT_SLA_PERIODS);
+    if !(1..=MAX_SLA_PERIODS).contains(&count) {
+        return Err(AppError::Validation(format!(
+            "count must be between 1 and {MAX_SLA_PERIODS}"
+        )));
+    }
+    let period = params.period.unwrap_or(SlaPeriod::Weekly);
+
+    let first_seen_at: String =
+        sqlx::query_scalar("SELECT first_seen_at FROM devices WHERE id = ?")
+            .bind(&id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+    let first_seen = chrono::DateTime::parse_from_rfc3339(&first_seen_at)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .ok();
+
+    let now = chrono::Utc::now();
+    let mut report = Vec::new();
+    for (start_date, end_date) in sla_period_bounds(period, count, now.date_naive()) {
+        let period_start = start_date.and_time(chrono::NaiveTime::MIN).and_utc();
+        let period_end = end_date.and_time(chrono::NaiveTime::MIN).and_utc();
+        let start = first_seen.map_or(period_start, |seen| seen.max(period_start));
+        let end = period_end.min(now);
+
+        let (entries, online_secs, offline_secs, outages, longest) = if start < end {
+            state_log_outages(&state.db, &id, &start.to_rfc3339(), &end.to_rfc3339()).await?
+        } else {
+            (0, 0.0, 0.0, 0, 0.0)
+        };
+
+        let uptime_secs = online_secs.max(0.0).round() as u64;
+        let downtime_secs = offline_secs.max(0.0).round() as u64;
+        let total = uptime_secs + downtime_secs;
+        report.push(SlaReportPeriod {
+            period_start: period_start.to_rfc3339(),
+            period_end: period_end.to_rfc3339(),
+            uptime_pct: (entries > 0 && total > 0)
+                .then(|| uptime_secs as f64 / total as f64 * 100.0),
+            uptime_secs,
+            downtime_secs,
+            outage_count: outages.try_into().unwrap_or(u32::MAX),
+            longest_outage_secs: longest.max(0.0).round() as u64,
+        });
+    }
+
+    Ok(Json(report))
+}
+
 /// Build a Wake-on-LAN magic packet from a MAC address string.
 ///
 /// The magic packet is 102 bytes: 6 × 0xFF followed by 16 repetitions of the
@@ -515,53 +1278,139 @@ pub fn build_magic_packet(mac: &str) -> Result<[u8; 102], String> {
     Ok(packet)
 }
 
+/// Optional body for `POST /api/v1/devices/:id/wake`.
+#[derive(Debug, Default, Deserialize)]
+pub struct WakeRequest {
+    /// Network interface to send from (e.g. `eth1`); routing decides if unset.
+    pub interface: Option<String>,
+    /// IPv4 broadcast address; derived from the device IP and the configured
+    /// scanner subnets if unset.
+    pub broadcast: Option<String>,
+}
+
+/// UDP port magic packets are sent to (discard).
+const WOL_PORT: u16 = 9;
+
+/// Linux interface names are at most 15 bytes (IFNAMSIZ - 1).
+const MAX_INTERFACE_LEN: usize = 15;
+
+/// Broadcast address of the first configured IPv4 subnet containing `ip`.
+pub fn subnet_broadcast(subnets: &[String], ip: Ipv4Addr) -> Option<Ipv4Addr> {
+    subnets
+        .iter()
+        .filter_map(|s| s.trim().parse::<Ipv4Network>().ok())
+        .find(|net| net.contains(ip))
+        .map(|net| net.broadcast())
+}
+
+/// Resolve the broadcast address for a wake request: the explicit one, or
+/// the broadcast of the configured subnet holding the device's current IPv4.
+async fn wake_broadcast(
+    state: &AppState,
+    id: &str,
+    requested: Option<&str>,
+) -> Result<Ipv4Addr, AppError> {
+    if let Some(broadcast) = requested {
+        return broadcast.trim().parse().map_err(|_| {
+            AppError::Validation(format!(
+                "broadcast must be an IPv4 address, got '{broadcast}'"
+            ))
+        });
+    }
+
+    let ips: Vec<String> = sqlx::query_scalar(
+        "SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 AND ip_version = 4 \
+         ORDER BY seen_at DESC",
+    )
+    .bind(id)
+    .fetch_all(&state.db)
+    .await?;
+    let ip: Ipv4Addr = ips.iter().find_map(|ip| ip.parse().ok()).ok_or_else(|| {
+        AppError::Validation(
+            "device has no current IPv4 address; specify a broadcast address".to_string(),
+        )
+    })?;
+
+    subnet_broadcast(&state.config().scanner.subnets, ip).ok_or_else(|| {
+        AppError::Validation(format!(
+            "device IP {ip} is not in any configured scanner subnet; specify a broadcast address"
+        ))
+    })
+}
+
+/// Send a magic packet to `broadcast:9`, optionally bound to `interface`
+/// (`SO_BINDTODEVICE`).
+fn send_magic_packet(
+    packet: &[u8],
+    broadcast: Ipv4Addr,
+    interface: Option<&str>,
+) -> std::io::Result<()> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_broadcast(true)?;
+    if let Some(interface) = interface {
+        socket.bind_device(Some(interface.as_bytes()))?;
+    }
+    socket.send_to(packet, &SocketAddrV4::new(broadcast, WOL_PORT).into())?;
+    Ok(())
+}
+
 /// POST /api/v1/devices/:id/wake — send a Wake-on-LAN magic packet.
+///
+/// Accepts an optional `{ "interface": "eth1", "broadcast": "192.168.2.255" }`
+/// body; without one, the packet goes to the broadcast address of the
+/// configured subnet containing the device's IP.
 pub async fn wake(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(id): Path<String>,
-) -> Result<StatusCode, StatusCode> {
-    // Fetch the device to get its MAC address
-    let row = sqlx::query(r#"SELECT mac FROM devices WHERE id = ?"#)
+    body: Option<Json<WakeRequest>>,
+) -> Result<StatusCode, AppError> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+
+    let mac: String = sqlx::query_scalar("SELECT mac FROM devices WHERE id = ?")
         .bind(&id)
         .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to fetch device {id} for WoL: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    let mac: String = row.try_get("mac").map_err(|e| {
-        tracing::error!("Failed to read MAC for device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    if mac.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+
+    let packet = build_magic_packet(&mac).map_err(AppError::Validation)?;
+
+    let interface = body
+        .interface
+        .as_deref()
+        .map(str::trim)
+        .filter(|i| !i.is_empty());
+    if interface.is_some_and(|i| i.len() > MAX_INTERFACE_LEN || i.contains(['/', ' '])) {
+        return Err(AppError::Validation("invalid interface name".to_string()));
     }
 
-    let packet = build_magic_packet(&mac).map_err(|e| {
-        tracing::warn!("Invalid MAC for WoL on device {id}: {e}");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    // Send magic packet via UDP broadcast
-    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| {
-        tracing::error!("Failed to bind UDP socket for WoL: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let broadcast = wake_broadcast(&state, &id, body.broadcast.as_deref()).await?;
 
-    socket.set_broadcast(true).map_err(|e| {
-        tracing::error!("Failed to set SO_BROADCAST for WoL: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let description = match interface {
+        Some(interface) => format!(
+            "Sent Wake-on-LAN to device {id} (MAC: {mac}) via {broadcast}:{WOL_PORT} on {interface}"
+        ),
+        None => format!("Sent Wake-on-LAN to device {id} (MAC: {mac}) via {broadcast}:{WOL_PORT}"),
+    };
 
-    socket.send_to(&packet, "255.255.255.255:9").map_err(|e| {
+    if let Err(e) = send_magic_packet(&packet, broadcast, interface) {
         tracing::error!("Failed to send WoL magic packet for device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+        audit::log_failure(
+            &state.db,
+            &actor,
+            "device_wake",
+            &description,
+            &[],
+            &e.to_string(),
+        )
+        .await;
+        return Err(AppError::Internal(format!(
+            "Failed to send magic packet to {broadcast}: {e}"
+        )));
+    }
 
-    tracing::info!("Sent WoL magic packet for device {id} (MAC: {mac})");
+    tracing::info!("{description}");
+    audit::log_success(&state.db, &actor, "device_wake", &description, &[]).await;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -578,12 +1427,23 @@ pub struct PortEntry {
     pub version: String,
 }
 
-/// Response for the port scan endpoints.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PortScanResult {
+/// A service banner captured from an open TCP port.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PortBanner {
+    pub port: u16,
+    pub banner: String,
+}
+
+/// Response for the port scan endpoints.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortScanResult {
     pub device_id: String,
     pub scanned_at: String,
     pub ports: Vec<PortEntry>,
+    /// Banners grabbed after the scan; filled in the background, so empty
+    /// in the `POST` response.
+    #[serde(default)]
+    pub banners: Vec<PortBanner>,
 }
 
 /// Parse nmap text output into a list of open port entries.
@@ -646,173 +1506,686 @@ pub fn parse_nmap_output(output: &str) -> Vec<PortEntry> {
     results
 }
 
-/// POST /api/v1/devices/:id/scan — trigger a local nmap port scan.
-pub async fn trigger_scan(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    // Check device exists and get its IP
-    let ip: String = match sqlx::query_scalar(
-        r#"SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 LIMIT 1"#,
+/// An OS guess from an nmap `<osmatch>` element.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OsMatch {
+    pub name: String,
+    /// nmap's confidence in the match, 0–100.
+    pub accuracy: u8,
+}
+
+/// Minimum nmap accuracy for an OS match to be written to `devices.os_hint`.
+const OS_MATCH_MIN_ACCURACY: u8 = 80;
+
+/// Extract the value of `attr` from the attributes of a single XML start tag.
+fn xml_attr(tag: &str, attr: &str) -> Option<String> {
+    let needle = format!(" {attr}=\"");
+    let start = tag.find(&needle)? + needle.len();
+    let len = tag[start..].find('"')?;
+    Some(
+        tag[start..start + len]
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
     )
-    .bind(&id)
-    .fetch_optional(&state.db)
+}
+
+/// Parse `<osmatch name="..." accuracy="..."/>` elements from nmap XML output,
+/// highest accuracy first.
+pub fn parse_nmap_os_matches(xml: &str) -> Vec<OsMatch> {
+    let mut matches: Vec<OsMatch> = xml
+        .match_indices("<osmatch ")
+        .filter_map(|(start, _)| {
+            let tag = &xml[start..start + xml[start..].find('>')?];
+            Some(OsMatch {
+                name: xml_attr(tag, "name")?,
+                accuracy: xml_attr(tag, "accuracy")?.parse().ok()?,
+            })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.accuracy));
+    matches
+}
+
+/// The most accurate OS match, if nmap is more than 80% confident in it.
+pub fn best_os_match(matches: &[OsMatch]) -> Option<&OsMatch> {
+    matches
+        .iter()
+        .max_by_key(|m| m.accuracy)
+        .filter(|m| m.accuracy > OS_MATCH_MIN_ACCURACY)
+}
+
+/// Whether the server runs with an effective UID of 0 (needed for nmap -O).
+fn running_as_root() -> bool {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            let uids = status.lines().find_map(|l| l.strip_prefix("Uid:"))?;
+            uids.split_whitespace().nth(1).map(|euid| euid == "0")
+        })
+        .unwrap_or(false)
+}
+
+/// Current IP to scan for a device: 404 if the device doesn't exist, 400 if
+/// it has no current address.
+pub async fn scan_target_ip(db: &sqlx::SqlitePool, id: &str) -> Result<String, AppError> {
+    match sqlx::query_scalar(
+        r#"SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 ORDER BY ip_version LIMIT 1"#,
+    )
+    .bind(id)
+    .fetch_optional(db)
     .await
     {
-        Ok(Some(ip)) => ip,
+        Ok(Some(ip)) => Ok(ip),
         Ok(None) => {
             let exists: bool =
                 sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*) FROM devices WHERE id = ?"#)
-                    .bind(&id)
-                    .fetch_one(&state.db)
+                    .bind(id)
+                    .fetch_one(db)
                     .await
                     .unwrap_or(0)
                     > 0;
             if !exists {
-                return Err((
-                    StatusCode::NOT_FOUND,
-                    Json(serde_json::json!({"error": "Device not found"})),
+                return Err(AppError::ResourceNotFound(
+                    "device",
+                    "Device not found".to_string(),
                 ));
             }
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({"error": "Device has no current IP address"})),
-            ));
+            Err(AppError::Validation(
+                "Device has no current IP address".to_string(),
+            ))
         }
         Err(e) => {
             tracing::error!("Failed to fetch device IP for scan: {e}");
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Internal server error"})),
-            ));
+            Err(AppError::Internal("Internal server error".to_string()))
         }
-    };
+    }
+}
 
-    // Rate limit: check last scan time
-    let last_scan_at: Option<String> = sqlx::query_scalar(
-        r#"SELECT scanned_at FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC LIMIT 1"#,
-    )
-    .bind(&id)
-    .fetch_optional(&state.db)
-    .await
-    .unwrap_or(None);
+/// Ports that changed between two scans of the same device.
+#[derive(Debug, Default, PartialEq)]
+pub struct PortScanDiff {
+    pub opened: Vec<PortEntry>,
+    pub closed: Vec<PortEntry>,
+}
 
-    if let Some(ref last_at) = last_scan_at {
-        if let Ok(last_time) = chrono::NaiveDateTime::parse_from_str(last_at, "%Y-%m-%d %H:%M:%S") {
-            let now = chrono::Utc::now().naive_utc();
-            let elapsed = (now - last_time).num_seconds();
-            if elapsed < 60 {
-                let retry_after = 60 - elapsed;
-                return Err((
-                    StatusCode::TOO_MANY_REQUESTS,
-                    Json(serde_json::json!({
-                        "error": "Rate limited. Try again later.",
-                        "retry_after": retry_after,
-                    })),
-                ));
+/// Compare two scans by `(port, protocol)`: ports only in `new` were opened,
+/// ports only in `old` were closed.
+pub fn compare_port_scans(old: &PortScanResult, new: &PortScanResult) -> PortScanDiff {
+    let key = |p: &PortEntry| (p.port, p.protocol.clone());
+    let old_keys: std::collections::HashSet<_> = old.ports.iter().map(key).collect();
+    let new_keys: std::collections::HashSet<_> = new.ports.iter().map(key).collect();
+    PortScanDiff {
+        opened: new
+            .ports
+            .iter()
+            .filter(|p| !old_keys.contains(&key(p)))
+            .cloned()
+            .collect(),
+        closed: old
+            .ports
+            .iter()
+            .filter(|p| !new_keys.contains(&key(p)))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// `22/tcp (ssh), 443/tcp (https)`.
+fn describe_ports(ports: &[PortEntry]) -> String {
+    ports
+        .iter()
+        .map(|p| {
+            if p.service.is_empty() {
+                format!("{}/{}", p.port, p.protocol)
+            } else {
+                format!("{}/{} ({})", p.port, p.protocol, p.service)
             }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The most recent stored scan for a device, if any.
+async fn latest_port_scan(
+    db: &sqlx::SqlitePool,
+    id: &str,
+) -> Result<Option<PortScanResult>, sqlx::Error> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        r#"SELECT scanned_at, result_json FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC, id DESC LIMIT 1"#,
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+    Ok(row.map(|(scanned_at, result_json)| PortScanResult {
+        device_id: id.to_string(),
+        scanned_at,
+        ports: serde_json::from_str(&result_json).unwrap_or_default(),
+        banners: Vec::new(),
+    }))
+}
+
+/// Raise `new_port_open` / `port_closed` alerts for a scan diff, unless the
+/// device is muted. Returns the number of alerts created.
+pub async fn create_port_change_alerts(
+    db: &sqlx::SqlitePool,
+    ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+    device_id: &str,
+    diff: &PortScanDiff,
+) -> Result<usize, sqlx::Error> {
+    if (diff.opened.is_empty() && diff.closed.is_empty()) || is_device_muted(db, device_id).await {
+        return Ok(0);
+    }
+
+    let label: String =
+        sqlx::query_scalar(r#"SELECT COALESCE(name, hostname, mac) FROM devices WHERE id = ?"#)
+            .bind(device_id)
+            .fetch_optional(db)
+            .await?
+            .unwrap_or_else(|| device_id.to_string());
+
+    let mut created = 0;
+    for (alert_type, ports, verb) in [
+        ("new_port_open", &diff.opened, "New open ports"),
+        ("port_closed", &diff.closed, "Ports closed"),
+    ] {
+        if ports.is_empty() {
+            continue;
         }
+        let severity = severity_for_alert_type(alert_type, db, severities).await;
+        let message = format!("{verb} on {label}: {}", describe_ports(ports));
+        let details = serde_json::json!({ "ports": ports });
+        sqlx::query(
+            r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+               VALUES (?, ?, ?, ?, ?, ?, datetime('now'))"#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(alert_type)
+        .bind(device_id)
+        .bind(&message)
+        .bind(details.to_string())
+        .bind(&severity)
+        .execute(db)
+        .await?;
+        created += 1;
+        super::metrics::record_alert_created(alert_type);
+
+        let payload = serde_json::json!({
+            "device_id": device_id,
+            "message": &message,
+            "ports": ports,
+        });
+        ws_hub.broadcast(alert_type, payload.clone());
+        webhook::dispatch_webhook(db, alert_type, payload);
     }
+    Ok(created)
+}
 
+/// Run nmap against `ip`, store the result in `port_scans` (updating the
+/// device's OS hint), alert on ports opened or closed since the previous
+/// scan and start banner grabbing and TLS certificate inspection in the
+/// background.
+pub async fn run_port_scan(
+    db: &sqlx::SqlitePool,
+    ws_hub: &Arc<WsHub>,
+    severities: &SeverityOverrideCache,
+    id: &str,
+    ip: &str,
+) -> Result<PortScanResult, AppError> {
     // Validate IP to prevent command injection
     if ip.parse::<std::net::IpAddr>().is_err() {
         tracing::error!("Invalid IP address for scan: {ip}");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({"error": "Invalid IP address"})),
-        ));
+        return Err(AppError::Validation("Invalid IP address".to_string()));
     }
 
-    // Run nmap locally
-    let nmap_output = match tokio::process::Command::new("nmap")
-        .args(["-sV", "--open", "-T4", "--host-timeout", "30s"])
-        .arg(&ip)
-        .output()
+    // Run nmap locally. Normal output goes to stdout for port parsing; XML is
+    // written alongside so OS matches can be read from `<osmatch>` elements.
+    // OS detection (-O) needs raw sockets, so it is only requested as root.
+    let xml_path =
+        std::env::temp_dir().join(format!("panoptikon-nmap-{}.xml", uuid::Uuid::new_v4()));
+    let mut nmap = tokio::process::Command::new("nmap");
+    nmap.args(["-sV", "--open", "-T4", "--host-timeout", "30s"]);
+    if running_as_root() {
+        nmap.args(["-O", "--osscan-limit"]);
+    }
+    nmap.arg("-oX").arg(&xml_path);
+    let nmap_result = nmap.arg(ip).output().await;
+    let nmap_xml = tokio::fs::read_to_string(&xml_path)
         .await
-    {
+        .unwrap_or_default();
+    let _ = tokio::fs::remove_file(&xml_path).await;
+
+    let nmap_output = match nmap_result {
         Ok(output) => {
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
                 tracing::error!("nmap exited with {}: {stderr}", output.status);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": format!("nmap failed: {stderr}")})),
-                ));
+                return Err(AppError::Internal(format!("nmap failed: {stderr}")));
             }
             String::from_utf8_lossy(&output.stdout).to_string()
         }
         Err(e) => {
             tracing::error!("Failed to execute nmap for device {id} (IP: {ip}): {e}");
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Failed to execute nmap: {e}")})),
-            ));
+            return Err(AppError::Internal(format!("Failed to execute nmap: {e}")));
         }
     };
 
     // Parse output
     let ports = parse_nmap_output(&nmap_output);
     let result_json = serde_json::to_string(&ports).unwrap_or_else(|_| "[]".to_string());
+    let os_matches = parse_nmap_os_matches(&nmap_xml);
+    let os_matches_json = serde_json::to_string(&os_matches).unwrap_or_else(|_| "[]".to_string());
+
+    let previous = latest_port_scan(db, id).await?;
 
     // Store in DB
-    let scanned_at: String = sqlx::query_scalar(
-        r#"INSERT INTO port_scans (device_id, result_json) VALUES (?, ?) RETURNING scanned_at"#,
+    let (scan_id, scanned_at): (i64, String) = sqlx::query_as(
+        r#"INSERT INTO port_scans (device_id, result_json, os_matches_json) VALUES (?, ?, ?) RETURNING id, scanned_at"#,
     )
-    .bind(&id)
+    .bind(id)
     .bind(&result_json)
-    .fetch_one(&state.db)
+    .bind(&os_matches_json)
+    .fetch_one(db)
     .await
     .map_err(|e| {
         tracing::error!("Failed to store port scan result: {e}");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Failed to store scan result"})),
-        )
+        AppError::Internal("Failed to store scan result".to_string())
     })?;
 
+    // Grab service banners from the open TCP ports in the background.
+    let tcp_ports: Vec<u16> = ports
+        .iter()
+        .filter(|p| p.protocol == "tcp")
+        .map(|p| p.port)
+        .collect();
+    if let (false, Ok(addr)) = (tcp_ports.is_empty(), ip.parse::<std::net::IpAddr>()) {
+        let db = db.clone();
+        tokio::spawn(async move {
+            crate::scanner::banner::grab_and_store(&db, scan_id, addr, tcp_ports).await;
+        });
+    }
+
+    // Inspect certificates on TLS ports, alerting on ones about to expire.
+    let tls_ports: Vec<u16> = ports
+        .iter()
+        .filter(|p| p.protocol == "tcp" && tls_cert::is_tls_port(p.port, &p.service))
+        .map(|p| p.port)
+        .collect();
+    if let (false, Ok(addr)) = (tls_ports.is_empty(), ip.parse::<std::net::IpAddr>()) {
+        let db = db.clone();
+        let ws_hub = ws_hub.clone();
+        let severities = severities.clone();
+        let id = id.to_string();
+        tokio::spawn(async move {
+            tls_cert::inspect_and_store(&db, &ws_hub, &severities, &id, addr, tls_ports).await;
+        });
+    }
+
+    if let Some(best) = best_os_match(&os_matches) {
+        sqlx::query(r#"UPDATE devices SET os_hint = ?, updated_at = datetime('now') WHERE id = ?"#)
+            .bind(&best.name)
+            .bind(id)
+            .execute(db)
+            .await?;
+    }
+
+    let result = PortScanResult {
+        device_id: id.to_string(),
+        scanned_at,
+        ports,
+        banners: Vec::new(),
+    };
+
+    if let Some(previous) = previous {
+        let diff = compare_port_scans(&previous, &result);
+        if let Err(e) = create_port_change_alerts(db, ws_hub, severities, id, &diff).await {
+            tracing::error!("Failed to create port change alerts for device {id}: {e}");
+        }
+    }
+
+    Ok(result)
+}
+
+/// POST /api/v1/devices/:id/scan — trigger a local nmap port scan.
+pub async fn trigger_scan(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let ip = scan_target_ip(&state.db, &id).await?;
+
+    // Rate limit: check last scan time
+    let last_scan_at: Option<String> = sqlx::query_scalar(
+        r#"SELECT scanned_at FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC LIMIT 1"#,
+    )
+    .bind(&id)
+    .fetch_optional(&state.db)
+    .await
+    .unwrap_or(None);
+
+    if let Some(ref last_at) = last_scan_at {
+        if let Ok(last_time) = chrono::NaiveDateTime::parse_from_str(last_at, "%Y-%m-%d %H:%M:%S") {
+            let now = chrono::Utc::now().naive_utc();
+            let elapsed = (now - last_time).num_seconds();
+            if elapsed < 60 {
+                let retry_after = 60 - elapsed;
+                return Err(AppError::RateLimited(
+                    "Rate limited. Try again later.".to_string(),
+                    retry_after as u64,
+                ));
+            }
+        }
+    }
+
+    let result = run_port_scan(
+        &state.db,
+        &state.ws_hub,
+        &state.severity_overrides,
+        &id,
+        &ip,
+    )
+    .await?;
+
     Ok((
         StatusCode::OK,
         [(header::CACHE_CONTROL, "no-cache")],
-        Json(PortScanResult {
-            device_id: id,
-            scanned_at,
-            ports,
-        }),
+        Json(result),
     ))
 }
 
+/// Banners stored for a port scan, ordered by port.
+async fn load_port_banners(
+    db: &sqlx::SqlitePool,
+    scan_id: i64,
+) -> Result<Vec<PortBanner>, sqlx::Error> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        r#"SELECT port, banner FROM port_scan_banners WHERE scan_id = ? ORDER BY port"#,
+    )
+    .bind(scan_id)
+    .fetch_all(db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(port, banner)| PortBanner {
+            port: port as u16,
+            banner,
+        })
+        .collect())
+}
+
 /// GET /api/v1/devices/:id/scan — get the latest cached port scan result.
 pub async fn get_scan(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<PortScanResult>, StatusCode> {
+) -> Result<Json<PortScanResult>, AppError> {
     let row = sqlx::query(
-        r#"SELECT scanned_at, result_json FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC LIMIT 1"#,
+        r#"SELECT id, scanned_at, result_json FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC, id DESC LIMIT 1"#,
     )
     .bind(&id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch port scan for device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     match row {
         Some(row) => {
             let scanned_at: String = row.try_get("scanned_at").unwrap_or_default();
             let result_json: String = row.try_get("result_json").unwrap_or_default();
             let ports: Vec<PortEntry> = serde_json::from_str(&result_json).unwrap_or_default();
+            let scan_id: i64 = row.try_get("id").unwrap_or_default();
+            let banners = load_port_banners(&state.db, scan_id).await?;
 
             Ok(Json(PortScanResult {
                 device_id: id,
                 scanned_at,
                 ports,
+                banners,
             }))
         }
-        None => Err(StatusCode::NOT_FOUND),
+        None => Err(AppError::ResourceNotFound(
+            "port-scan",
+            "Device has not been scanned".to_string(),
+        )),
     }
 }
 
+/// Response for `GET /api/v1/devices/:id/os`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeviceOsGuesses {
+    pub device_id: String,
+    pub os_hint: Option<String>,
+    /// When the port scan that produced `matches` ran; `None` if never scanned.
+    pub scanned_at: Option<String>,
+    pub matches: Vec<OsMatch>,
+}
+
+/// GET /api/v1/devices/:id/certificates — TLS certificates seen on the
+/// device's HTTPS ports.
+pub async fn certificates(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<tls_cert::TlsCertificate>>, AppError> {
+    sqlx::query_scalar::<_, i64>("SELECT 1 FROM devices WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+
+    Ok(Json(tls_cert::load_certificates(&state.db, &id).await?))
+}
+
+/// GET /api/v1/devices/:id/os — raw nmap OS guesses from the latest port scan.
+pub async fn os_guesses(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DeviceOsGuesses>, AppError> {
+    load_os_guesses(&state.db, id).await.map(Json)
+}
+
+async fn load_os_guesses(db: &sqlx::SqlitePool, id: String) -> Result<DeviceOsGuesses, AppError> {
+    let os_hint: Option<String> = sqlx::query_scalar("SELECT os_hint FROM devices WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+
+    let latest: Option<(String, String)> = sqlx::query_as(
+        r#"SELECT scanned_at, os_matches_json FROM port_scans
+           WHERE device_id = ? AND os_matches_json IS NOT NULL
+           ORDER BY scanned_at DESC, id DESC LIMIT 1"#,
+    )
+    .bind(&id)
+    .fetch_optional(db)
+    .await?;
+
+    let (scanned_at, matches) = match latest {
+        Some((scanned_at, json)) => (
+            Some(scanned_at),
+            serde_json::from_str(&json).unwrap_or_default(),
+        ),
+        None => (None, Vec::new()),
+    };
+
+    Ok(DeviceOsGuesses {
+        device_id: id,
+        os_hint,
+        scanned_at,
+        matches,
+    })
+}
+
+// ─── Security Score ─────────────────────────────────────
+
+/// GET /api/v1/devices/:id/security-score — 0–100 risk score with contributing factors.
+pub async fn security_score(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<crate::security::score::SecurityScore>, AppError> {
+    let inputs = crate::security::score::load_inputs(&state.db, &id)
+        .await?
+        .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))?;
+    Ok(Json(crate::security::score::compute_score(
+        &inputs,
+        chrono::Utc::now(),
+    )))
+}
+
+// ─── SNMP ───────────────────────────────────────────────
+
+/// Request body for `POST /api/v1/devices/:id/snmp`.
+#[derive(Debug, Deserialize)]
+pub struct SnmpConfigRequest {
+    pub community: String,
+    /// SNMP version; only `"2c"` is supported (default).
+    pub version: Option<String>,
+    /// UDP port (default 161).
+    pub port: Option<u16>,
+}
+
+/// Stored SNMP settings, without the community string.
+#[derive(Debug, Serialize)]
+pub struct SnmpConfigResponse {
+    pub device_id: String,
+    pub version: String,
+    pub port: u16,
+}
+
+/// Validate and store SNMP settings for a device.
+async fn save_snmp_config(
+    db: &sqlx::SqlitePool,
+    device_id: &str,
+    body: SnmpConfigRequest,
+) -> Result<SnmpConfigResponse, AppError> {
+    let community = body.community.trim();
+    if community.is_empty() || community.len() > 255 {
+        return Err(AppError::Validation(
+            "community must be 1-255 characters".to_string(),
+        ));
+    }
+    let version = body.version.unwrap_or_else(|| "2c".to_string());
+    if version != "2c" {
+        return Err(AppError::Validation(format!(
+            "Unsupported SNMP version '{version}'; only 2c is supported"
+        )));
+    }
+    let port = body.port.unwrap_or(161);
+    if port == 0 {
+        return Err(AppError::Validation("port must be 1-65535".to_string()));
+    }
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM devices WHERE id = ?")
+        .bind(device_id)
+        .fetch_optional(db)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::ResourceNotFound(
+            "device",
+            "Device not found".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO device_snmp (device_id, community, version, port, updated_at)
+           VALUES (?, ?, ?, ?, datetime('now'))
+           ON CONFLICT(device_id) DO UPDATE SET
+             community = excluded.community,
+             version = excluded.version,
+             port = excluded.port,
+             updated_at = excluded.updated_at"#,
+    )
+    .bind(device_id)
+    .bind(community)
+    .bind(&version)
+    .bind(port)
+    .execute(db)
+    .await?;
+
+    Ok(SnmpConfigResponse {
+        device_id: device_id.to_string(),
+        version,
+        port,
+    })
+}
+
+/// POST /api/v1/devices/:id/snmp — configure SNMP polling for a device.
+pub async fn configure_snmp(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<SnmpConfigRequest>,
+) -> Result<Json<SnmpConfigResponse>, AppError> {
+    let saved = save_snmp_config(&state.db, &id, body).await?;
+    // A new community or port makes any cached poll stale.
+    state.snmp_poll_cache.lock().await.remove(&id);
+    Ok(Json(saved))
+}
+
+/// Load a device's SNMP settings and the address to poll.
+async fn snmp_target(
+    db: &sqlx::SqlitePool,
+    device_id: &str,
+) -> Result<(std::net::SocketAddr, crate::snmp::SnmpConfig), AppError> {
+    let config: Option<(String, String, i64)> =
+        sqlx::query_as("SELECT community, version, port FROM device_snmp WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_optional(db)
+            .await?;
+    let (community, version, port) = config.ok_or_else(|| {
+        AppError::ResourceNotFound(
+            "snmp_config",
+            "SNMP is not configured for this device".to_string(),
+        )
+    })?;
+    let port = u16::try_from(port).unwrap_or(161);
+
+    let ip: Option<String> = sqlx::query_scalar(
+        "SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 ORDER BY ip_version LIMIT 1",
+    )
+    .bind(device_id)
+    .fetch_optional(db)
+    .await?;
+    let ip: std::net::IpAddr = ip
+        .and_then(|ip| ip.parse().ok())
+        .ok_or_else(|| AppError::Validation("Device has no current IP address".to_string()))?;
+
+    Ok((
+        std::net::SocketAddr::new(ip, port),
+        crate::snmp::SnmpConfig {
+            community,
+            version,
+            port,
+        },
+    ))
+}
+
+/// GET /api/v1/devices/:id/snmp/poll — poll the device and return its MIB data.
+///
+/// Results are cached per device for 60 seconds.
+pub async fn poll_snmp(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<crate::snmp::SnmpPollResult>, AppError> {
+    if let Some((at, cached)) = state.snmp_poll_cache.lock().await.get(&id) {
+        if at.elapsed() < crate::snmp::POLL_CACHE_TTL {
+            return Ok(Json(cached.clone()));
+        }
+    }
+
+    let (target, config) = snmp_target(&state.db, &id).await?;
+    let result = crate::snmp::poll(&id, target, &config.community)
+        .await
+        .map_err(|e| {
+            tracing::warn!(device_id = %id, %target, "SNMP poll failed: {e:#}");
+            AppError::BadGateway(format!("SNMP poll failed: {e}"))
+        })?;
+
+    state
+        .snmp_poll_cache
+        .lock()
+        .await
+        .insert(id, (std::time::Instant::now(), result.clone()));
+    Ok(Json(result))
+}
+
 // ─── Enrichment Feedback ────────────────────────────────
 
 /// Request body for correcting device enrichment.
@@ -833,7 +2206,7 @@ pub async fn update_enrichment(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(body): Json<EnrichmentCorrection>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let now = chrono::Utc::now().to_rfc3339();
 
     let result = sqlx::query(
@@ -856,14 +2229,13 @@ pub async fn update_enrichment(
     .bind(&now)
     .bind(&id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to update enrichment for device {id}: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     if result.rows_affected() == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(AppError::ResourceNotFound(
+            "device",
+            "Device not found".to_string(),
+        ));
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -893,6 +2265,511 @@ pub async fn check_port_scan_rate_limit(
     Ok(None)
 }
 
+// ─── Scan schedules ─────────────────────────────────────
+
+/// Longest allowed interval between scheduled scans (30 days).
+const MAX_SCAN_INTERVAL_HOURS: i64 = 720;
+
+/// A device's recurring port scan schedule.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ScanSchedule {
+    pub device_id: String,
+    pub interval_hours: i64,
+    pub last_scan_at: Option<String>,
+    pub enabled: bool,
+}
+
+/// Request body for `POST /api/v1/devices/:id/scan/schedule`.
+#[derive(Debug, Deserialize)]
+pub struct ScanScheduleRequest {
+    pub interval_hours: i64,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Load a device's scan schedule, if it has one.
+async fn load_scan_schedule(
+    db: &sqlx::SqlitePool,
+    id: &str,
+) -> Result<Option<ScanSchedule>, AppError> {
+    Ok(sqlx::query_as(
+        "SELECT device_id, interval_hours, last_scan_at, enabled \
+         FROM device_scan_schedules WHERE device_id = ?",
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?)
+}
+
+/// GET /api/v1/devices/:id/scan/schedule — the device's scan schedule.
+pub async fn get_scan_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ScanSchedule>, AppError> {
+    load_scan_schedule(&state.db, &id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::ResourceNotFound("scan_schedule", "Device has no scan schedule".to_string())
+        })
+}
+
+/// POST /api/v1/devices/:id/scan/schedule — create or replace the device's
+/// recurring port scan schedule. The last scan time is kept on update.
+pub async fn set_scan_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<ScanScheduleRequest>,
+) -> Result<Json<ScanSchedule>, AppError> {
+    if !(1..=MAX_SCAN_INTERVAL_HOURS).contains(&body.interval_hours) {
+        return Err(AppError::Validation(format!(
+            "interval_hours must be between 1 and {MAX_SCAN_INTERVAL_HOURS}"
+        )));
+    }
+    ensure_device_exists(&state.db, &id).await?;
+
+    sqlx::query(
+        "INSERT INTO device_scan_schedules (device_id, interval_hours, enabled) VALUES (?, ?, ?) \
+         ON CONFLICT(device_id) DO UPDATE SET \
+             interval_hours = excluded.interval_hours, enabled = excluded.enabled",
+    )
+    .bind(&id)
+    .bind(body.interval_hours)
+    .bind(body.enabled)
+    .execute(&state.db)
+    .await?;
+
+    load_scan_schedule(&state.db, &id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::Internal("Scan schedule missing after save".to_string()))
+}
+
+/// DELETE /api/v1/devices/:id/scan/schedule — stop scheduled scans.
+pub async fn delete_scan_schedule(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM device_scan_schedules WHERE device_id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "scan_schedule",
+            "Device has no scan schedule".to_string(),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ─── Tags ───────────────────────────────────────────────
+
+/// Maximum length of a device tag.
+const MAX_TAG_LEN: usize = 32;
+
+/// Request body for `POST /api/v1/devices/:id/tags`.
+#[derive(Debug, Deserialize)]
+pub struct AddTagRequest {
+    pub tag: String,
+}
+
+/// Check that a tag is 1–32 characters of lowercase ASCII letters, digits
+/// and hyphens.
+pub fn validate_tag(tag: &str) -> Result<(), AppError> {
+    if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+        return Err(AppError::Validation(format!(
+            "Tag must be 1-{MAX_TAG_LEN} characters"
+        )));
+    }
+    if !tag
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(AppError::Validation(format!(
+            "Invalid tag '{tag}': only lowercase letters, digits and hyphens are allowed"
+        )));
+    }
+    Ok(())
+}
+
+/// Load a device's tags in alphabetical order.
+async fn load_tags(db: &sqlx::SqlitePool, device_id: &str) -> Result<Vec<String>, AppError> {
+    Ok(
+        sqlx::query_scalar("SELECT tag FROM device_tags WHERE device_id = ? ORDER BY tag")
+            .bind(device_id)
+            .fetch_all(db)
+            .await?,
+    )
+}
+
+/// Fail with 404 unless the device exists.
+async fn ensure_device_exists(db: &sqlx::SqlitePool, id: &str) -> Result<(), AppError> {
+    sqlx::query_scalar::<_, i64>("SELECT 1 FROM devices WHERE id = ? AND is_deleted = 0")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| AppError::ResourceNotFound("device", "Device not found".to_string()))
+}
+
+/// Add a tag to a device (no-op if already present) and return its tags.
+async fn add_tag(db: &sqlx::SqlitePool, id: &str, tag: &str) -> Result<Vec<String>, AppError> {
+    validate_tag(tag)?;
+    ensure_device_exists(db, id).await?;
+    sqlx::query("INSERT OR IGNORE INTO device_tags (device_id, tag) VALUES (?, ?)")
+        .bind(id)
+        .bind(tag)
+        .execute(db)
+        .await?;
+    load_tags(db, id).await
+}
+
+/// GET /api/v1/devices/:id/tags — list a device's tags.
+pub async fn list_tags(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<String>>, AppError> {
+    ensure_device_exists(&state.db, &id).await?;
+    load_tags(&state.db, &id).await.map(Json)
+}
+
+/// POST /api/v1/devices/:id/tags — tag a device.
+pub async fn create_tag(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<AddTagRequest>,
+) -> Result<(StatusCode, Json<Vec<String>>), AppError> {
+    let tags = add_tag(&state.db, &id, &body.tag).await?;
+    Ok((StatusCode::CREATED, Json(tags)))
+}
+
+/// DELETE /api/v1/devices/:id/tags/:tag — remove a tag from a device.
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM device_tags WHERE device_id = ? AND tag = ?")
+        .bind(&id)
+        .bind(&tag)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "tag",
+            format!("Device has no tag '{tag}'"),
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ─── Notes ──────────────────────────────────────────────
+
+/// Maximum length of a device note body, in characters.
+const MAX_NOTE_LEN: usize = 10_000;
+
+/// A free-text note attached to a device.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeviceNote {
+    pub id: String,
+    pub device_id: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Request body for creating or editing a device note.
+#[derive(Debug, Deserialize)]
+pub struct NoteRequest {
+    pub body: String,
+}
+
+/// Check that a note body is non-blank and at most 10,000 characters.
+fn validate_note_body(body: &str) -> Result<(), AppError> {
+    if body.trim().is_empty() {
+        return Err(AppError::Validation(
+            "Note body must not be empty".to_string(),
+        ));
+    }
+    if body.chars().count() > MAX_NOTE_LEN {
+        return Err(AppError::Validation(format!(
+            "Note body must be at most {MAX_NOTE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+async fn load_note(
+    db: &sqlx::SqlitePool,
+    device_id: &str,
+    note_id: &str,
+) -> Result<DeviceNote, AppError> {
+    sqlx::query_as(
+        "SELECT id, device_id, author, body, created_at, updated_at \
+         FROM device_notes WHERE id = ? AND device_id = ?",
+    )
+    .bind(note_id)
+    .bind(device_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::ResourceNotFound("note", "Note not found".to_string()))
+}
+
+/// GET /api/v1/devices/:id/notes — list a device's notes, newest first.
+pub async fn list_notes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<DeviceNote>>, AppError> {
+    ensure_device_exists(&state.db, &id).await?;
+    let notes = sqlx::query_as(
+        "SELECT id, device_id, author, body, created_at, updated_at \
+         FROM device_notes WHERE device_id = ? ORDER BY created_at DESC, rowid DESC",
+    )
+    .bind(&id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(notes))
+}
+
+/// POST /api/v1/devices/:id/notes — add a note to a device.
+pub async fn create_note(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+    Json(body): Json<NoteRequest>,
+) -> Result<(StatusCode, Json<DeviceNote>), AppError> {
+    validate_note_body(&body.body)?;
+    ensure_device_exists(&state.db, &id).await?;
+
+    let note_id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO device_notes (id, device_id, body, created_at, updated_at) \
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&note_id)
+    .bind(&id)
+    .bind(&body.body)
+    .bind(&now)
+    .bind(&now)
+    .execute(&state.db)
+    .await?;
+
+    crate::api::audit::log_success(
+        &state.db,
+        &actor,
+        "device_note_create",
+        &format!("Added note {note_id} to device {id}"),
+        &[],
+    )
+    .await;
+
+    let note = load_note(&state.db, &id, &note_id).await?;
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+/// PATCH /api/v1/devices/:id/notes/:note_id — edit a note's body.
+pub async fn update_note(
+    State(state): State<AppState>,
+    Path((id, note_id)): Path<(String, String)>,
+    Json(body): Json<NoteRequest>,
+) -> Result<Json<DeviceNote>, AppError> {
+    validate_note_body(&body.body)?;
+
+    let result = sqlx::query(
+        "UPDATE device_notes SET body = ?, updated_at = ? WHERE id = ? AND device_id = ?",
+    )
+    .bind(&body.body)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .bind(&note_id)
+    .bind(&id)
+    .execute(&state.db)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "note",
+            "Note not found".to_string(),
+        ));
+    }
+
+    load_note(&state.db, &id, &note_id).await.map(Json)
+}
+
+/// DELETE /api/v1/devices/:id/notes/:note_id — delete a note.
+pub async fn delete_note(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path((id, note_id)): Path<(String, String)>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM device_notes WHERE id = ? AND device_id = ?")
+        .bind(&note_id)
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "note",
+            "Note not found".to_string(),
+        ));
+    }
+
+    crate::api::audit::log_success(
+        &state.db,
+        &actor,
+        "device_note_delete",
+        &format!("Deleted note {note_id} from device {id}"),
+        &[],
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ─── Bulk Operations ────────────────────────────────────
+
+/// Maximum number of devices a single bulk request may touch.
+pub const MAX_BULK_DEVICES: usize = 500;
+
+/// Operation applied by `POST /api/v1/devices/bulk`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkOperation {
+    /// Mute for `value` hours (default 1).
+    Mute,
+    Unmute,
+    /// Soft-delete the devices.
+    Delete,
+    /// Add the tag given in `value`.
+    Tag,
+    /// Remove the tag given in `value`.
+    Untag,
+}
+
+/// Request body for `POST /api/v1/devices/bulk`.
+#[derive(Debug, Deserialize)]
+pub struct BulkRequest {
+    pub device_ids: Vec<String>,
+    pub operation: BulkOperation,
+    pub value: Option<String>,
+}
+
+/// Outcome of a bulk operation. Either every device succeeded or nothing
+/// was changed and `failed` lists the unknown device IDs.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct BulkResult {
+    pub succeeded: usize,
+    pub failed: Vec<String>,
+}
+
+/// Run a bulk operation in a single transaction, rolling back if any device
+/// does not exist.
+pub async fn apply_bulk(db: &sqlx::SqlitePool, req: &BulkRequest) -> Result<BulkResult, AppError> {
+    if req.device_ids.is_empty() {
+        return Err(AppError::Validation(
+            "device_ids must not be empty".to_string(),
+        ));
+    }
+    if req.device_ids.len() > MAX_BULK_DEVICES {
+        return Err(AppError::Validation(format!(
+            "At most {MAX_BULK_DEVICES} devices per bulk request"
+        )));
+    }
+
+    let value = req.value.as_deref().map(str::trim);
+    let mute_hours = match req.operation {
+        BulkOperation::Mute => match value {
+            None => 1,
+            Some(v) => v.parse::<i64>().ok().filter(|h| *h > 0).ok_or_else(|| {
+                AppError::Validation(format!("Invalid mute duration '{v}' (hours)"))
+            })?,
+        },
+        _ => 0,
+    };
+    let tag = match req.operation {
+        BulkOperation::Tag | BulkOperation::Untag => {
+            let tag =
+                value.ok_or_else(|| AppError::Validation("value (tag) is required".to_string()))?;
+            validate_tag(tag)?;
+            Some(tag)
+        }
+        _ => None,
+    };
+
+    let mut ids: Vec<&str> = req.device_ids.iter().map(String::as_str).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut tx = db.begin().await?;
+    let mut failed = Vec::new();
+    for id in &ids {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM devices WHERE id = ? AND is_deleted = 0")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if exists.is_none() {
+            failed.push(id.to_string());
+            continue;
+        }
+
+        let query = match req.operation {
+            BulkOperation::Mute => sqlx::query(
+                "UPDATE devices SET muted_until = datetime('now', '+' || ? || ' hours') WHERE id = ?",
+            )
+            .bind(mute_hours),
+            BulkOperation::Unmute => {
+                sqlx::query("UPDATE devices SET muted_until = NULL WHERE id = ?")
+            }
+            BulkOperation::Delete => {
+                sqlx::query("UPDATE devices SET is_deleted = 1, updated_at = ? WHERE id = ?")
+                    .bind(chrono::Utc::now().to_rfc3339())
+            }
+            BulkOperation::Tag => {
+                sqlx::query("INSERT OR IGNORE INTO device_tags (tag, device_id) VALUES (?, ?)")
+                    .bind(tag)
+            }
+            BulkOperation::Untag => {
+                sqlx::query("DELETE FROM device_tags WHERE tag = ? AND device_id = ?").bind(tag)
+            }
+        };
+        query.bind(id).execute(&mut *tx).await?;
+    }
+
+    if !failed.is_empty() {
+        tx.rollback().await?;
+        return Ok(BulkResult {
+            succeeded: 0,
+            failed,
+        });
+    }
+    tx.commit().await?;
+
+    Ok(BulkResult {
+        succeeded: ids.len(),
+        failed,
+    })
+}
+
+/// POST /api/v1/devices/bulk — mute, unmute, delete, tag or untag many
+/// devices at once. Responds 422 with the unknown IDs if any device fails.
+pub async fn bulk(
+    State(state): State<AppState>,
+    Json(body): Json<BulkRequest>,
+) -> Result<(StatusCode, Json<BulkResult>), AppError> {
+    let result = apply_bulk(&state.db, &body).await?;
+    let status = if result.failed.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((status, Json(result)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -973,7 +2850,8 @@ mod tests {
                    d.is_known, d.is_favorite, d.first_seen_at, d.last_seen_at, d.is_online,
                    d.mdns_services, d.muted_until,
                    d.os_family, d.os_version, d.device_type, d.device_model,
-                   d.device_brand, d.enrichment_source, d.enrichment_corrected,
+                   d.device_brand, d.os_hint, d.enrichment_source, d.enrichment_corrected,
+                   d.upnp_info,
                    a.id AS agent_id,
                    a.name AS agent_name,
                    r.cpu_percent AS agent_cpu_percent,
@@ -1241,6 +3119,179 @@ mod tests {
         assert_eq!(rows[2].0, "online", "Oldest event should be last");
     }
 
+    #[test]
+    fn test_parse_window_days() {
+        assert_eq!(parse_window_days("30d").unwrap(), 30);
+        assert_eq!(parse_window_days("7").unwrap(), 7);
+        for bad in ["0d", "366d", "12h", "d", "-1d"] {
+            assert!(parse_window_days(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_availability() {
+        let pool = test_db().await;
+        let now = chrono::Utc::now();
+        let ago = |days: i64| (now - chrono::Duration::days(days)).to_rfc3339();
+        let log = |id: String, state: &'static str, changed_at: String| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO device_state_log (device_id, state, changed_at) VALUES (?, ?, ?)",
+                )
+                .bind(id)
+                .bind(state)
+                .bind(changed_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        };
+        let availability_of = |id: &str, window: Option<&str>| {
+            let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+            let query = AvailabilityQuery {
+                window: window.map(str::to_string),
+            };
+            let id = id.to_string();
+            async move {
+                availability(State(state), Path(id), Query(query))
+                    .await
+                    .unwrap()
+                    .0
+            }
+        };
+        let approx = |secs: u64, days: u64| secs.abs_diff(days * 86400) < 60;
+
+        // Offline since before the window, then online 20d ago, offline 10d
+        // ago and back online 5d ago: 15 days each way.
+        let old = insert_test_device(&pool, "AA:BB:CC:00:00:0A").await;
+        sqlx::query("UPDATE devices SET first_seen_at = ? WHERE id = ?")
+            .bind(ago(60))
+            .bind(&old)
+            .execute(&pool)
+            .await
+            .unwrap();
+        log(old.clone(), "offline", ago(40)).await;
+        log(old.clone(), "online", ago(20)).await;
+        log(old.clone(), "offline", ago(10)).await;
+        log(old.clone(), "online", ago(5)).await;
+
+        let result = availability_of(&old, Some("30d")).await;
+        assert_eq!(result.window_days, 30);
+        assert!(approx(result.total_online_secs, 15), "{result:?}");
+        assert!(approx(result.total_offline_secs, 15), "{result:?}");
+        assert!((result.uptime_pct.unwrap() - 50.0).abs() < 0.1);
+        assert_eq!(result.transition_count, 3);
+
+        // A device first seen 2 days ago: the window is clamped to 2 days.
+        let new = insert_test_device(&pool, "AA:BB:CC:00:00:0B").await;
+        sqlx::query("UPDATE devices SET first_seen_at = ? WHERE id = ?")
+            .bind(ago(2))
+            .bind(&new)
+            .execute(&pool)
+            .await
+            .unwrap();
+        log(new.clone(), "online", ago(2)).await;
+        log(new.clone(), "offline", ago(1)).await;
+        let result = availability_of(&new, None).await;
+        assert!(approx(result.total_online_secs, 1), "{result:?}");
+        assert!(approx(result.total_offline_secs, 1), "{result:?}");
+        assert_eq!(result.transition_count, 1);
+
+        // No state log entries in the window.
+        let quiet = insert_test_device(&pool, "AA:BB:CC:00:00:0C").await;
+        let result = availability_of(&quiet, Some("7d")).await;
+        assert_eq!(result.uptime_pct, None);
+        assert_eq!(result.transition_count, 0);
+    }
+
+    #[test]
+    fn test_sla_period_bounds() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // 2026-03-04 is a Wednesday.
+        assert_eq!(
+            sla_period_bounds(SlaPeriod::Weekly, 2, date(2026, 3, 4)),
+            vec![
+                (date(2026, 2, 23), date(2026, 3, 2)),
+                (date(2026, 3, 2), date(2026, 3, 9)),
+            ]
+        );
+        assert_eq!(
+            sla_period_bounds(SlaPeriod::Monthly, 3, date(2026, 3, 31)),
+            vec![
+                (date(2026, 1, 1), date(2026, 2, 1)),
+                (date(2026, 2, 1), date(2026, 3, 1)),
+                (date(2026, 3, 1), date(2026, 4, 1)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sla_report() {
+        let pool = test_db().await;
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        let now = chrono::Utc::now();
+        let id = insert_test_device(&pool, "AA:BB:CC:00:00:0D").await;
+        sqlx::query("UPDATE devices SET first_seen_at = ? WHERE id = ?")
+            .bind((now - chrono::Duration::days(60)).to_rfc3339())
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Last week: offline from before it began until Tuesday, online
+        // Tuesday, offline Wednesday to Friday (logged twice), then online.
+        let (last_week, _) = sla_period_bounds(SlaPeriod::Weekly, 2, now.date_naive())[0];
+        let monday = last_week.and_time(chrono::NaiveTime::MIN).and_utc();
+        for (state_name, at) in [
+            ("offline", monday - chrono::Duration::days(1)),
+            ("online", monday + chrono::Duration::days(1)),
+            ("offline", monday + chrono::Duration::days(2)),
+            (
+                "offline",
+                monday + chrono::Duration::days(2) + chrono::Duration::hours(1),
+            ),
+            ("online", monday + chrono::Duration::days(4)),
+        ] {
+            sqlx::query(
+                "INSERT INTO device_state_log (device_id, state, changed_at) VALUES (?, ?, ?)",
+            )
+            .bind(&id)
+            .bind(state_name)
+            .bind(at.to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let report = |period, count| {
+            sla_report(
+                State(state.clone()),
+                Path(id.clone()),
+                Query(SlaReportQuery {
+                    period: Some(period),
+                    count: Some(count),
+                }),
+            )
+        };
+        let Json(weeks) = report(SlaPeriod::Weekly, 2).await.unwrap();
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[0].period_start, monday.to_rfc3339());
+        assert_eq!(weeks[0].downtime_secs, 3 * 86400);
+        assert_eq!(weeks[0].uptime_secs, 4 * 86400);
+        assert_eq!(weeks[0].outage_count, 2);
+        assert_eq!(weeks[0].longest_outage_secs, 2 * 86400);
+        assert!((weeks[0].uptime_pct.unwrap() - 57.14).abs() < 0.01);
+        // This week so far: online throughout.
+        assert_eq!(weeks[1].outage_count, 0);
+        assert_eq!(weeks[1].downtime_secs, 0);
+
+        assert!(matches!(
+            report(SlaPeriod::Monthly, 0).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_uptime_calculation() {
         let pool = test_db().await;
@@ -1430,6 +3481,80 @@ mod tests {
         assert_eq!(&packet[96..102], &expected_mac);
     }
 
+    #[test]
+    fn test_subnet_broadcast() {
+        let subnets = vec![
+            "10.0.0.0/8".to_string(),
+            "192.168.2.0/24".to_string(),
+            "fd00::/64".to_string(),
+        ];
+        let ip = |s: &str| s.parse::<Ipv4Addr>().unwrap();
+        assert_eq!(
+            subnet_broadcast(&subnets, ip("192.168.2.40")),
+            Some(ip("192.168.2.255"))
+        );
+        assert_eq!(
+            subnet_broadcast(&subnets, ip("10.1.2.3")),
+            Some(ip("10.255.255.255"))
+        );
+        assert_eq!(subnet_broadcast(&subnets, ip("192.168.3.1")), None);
+    }
+
+    #[tokio::test]
+    async fn test_wake_broadcast_selection() {
+        let pool = test_db().await;
+        let id = insert_test_device(&pool, "AA:BB:CC:00:00:09").await;
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
+             VALUES (?, '192.168.3.7', datetime('now'), 1)",
+        )
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let mut config = crate::config::AppConfig::default();
+        config.scanner.subnets = vec!["192.168.2.0/24".to_string()];
+        let state = AppState::new(pool.clone(), config);
+        let wake_with = |interface: Option<&str>, broadcast: Option<&str>| {
+            wake(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                Path(id.clone()),
+                Some(Json(WakeRequest {
+                    interface: interface.map(str::to_string),
+                    broadcast: broadcast.map(str::to_string),
+                })),
+            )
+        };
+
+        // The device IP is outside every configured subnet.
+        match wake_with(None, None).await {
+            Err(AppError::Validation(msg)) => assert!(msg.contains("192.168.3.7"), "{msg}"),
+            other => panic!("expected validation error, got {other:?}"),
+        }
+        assert!(matches!(
+            wake_with(None, Some("not-an-ip")).await,
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            wake_with(Some("eth0/../x"), Some("127.0.0.1")).await,
+            Err(AppError::Validation(_))
+        ));
+
+        // An explicit address is used as-is and recorded in the audit log.
+        assert_eq!(
+            wake_with(None, Some("127.0.0.1")).await.unwrap(),
+            StatusCode::NO_CONTENT
+        );
+        let description: String = sqlx::query_scalar(
+            "SELECT description FROM audit_log WHERE action = 'device_wake' AND success = 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(description.contains("via 127.0.0.1:9"), "{description}");
+    }
+
     // ─── Port Scan Tests ────────────────────────────────────
 
     #[test]
@@ -1564,4 +3689,575 @@ Nmap done: 1 IP address (1 host up) scanned in 0.42 seconds
             "scanned_at should be auto-populated"
         );
     }
+
+    fn port(port: u16, protocol: &str, service: &str) -> PortEntry {
+        PortEntry {
+            port,
+            protocol: protocol.to_string(),
+            state: "open".to_string(),
+            service: service.to_string(),
+            version: String::new(),
+        }
+    }
+
+    fn scan(ports: Vec<PortEntry>) -> PortScanResult {
+        PortScanResult {
+            device_id: "dev".to_string(),
+            scanned_at: String::new(),
+            ports,
+            banners: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compare_port_scans() {
+        let old = scan(vec![port(22, "tcp", "ssh"), port(53, "udp", "domain")]);
+        let new = scan(vec![
+            port(22, "tcp", "ssh"),
+            port(53, "tcp", "domain"),
+            port(443, "tcp", "https"),
+        ]);
+
+        let diff = compare_port_scans(&old, &new);
+        assert_eq!(
+            diff.opened,
+            vec![port(53, "tcp", "domain"), port(443, "tcp", "https")]
+        );
+        assert_eq!(diff.closed, vec![port(53, "udp", "domain")]);
+
+        assert_eq!(compare_port_scans(&new, &new), PortScanDiff::default());
+        assert_eq!(
+            describe_ports(&diff.opened),
+            "53/tcp (domain), 443/tcp (https)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_port_change_alerts() {
+        let pool = test_db().await;
+        let id = insert_test_device(&pool, "AA:BB:CC:DD:EE:22").await;
+        let ws_hub = WsHub::new();
+        let severities = SeverityOverrideCache::new();
+        let diff = PortScanDiff {
+            opened: vec![port(8080, "tcp", "http-proxy")],
+            closed: vec![port(22, "tcp", "ssh")],
+        };
+
+        let created = create_port_change_alerts(&pool, &ws_hub, &severities, &id, &diff)
+            .await
+            .unwrap();
+        assert_eq!(created, 2);
+
+        let alerts: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT type, message, details, severity FROM alerts WHERE device_id = ? ORDER BY type",
+        )
+        .bind(&id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(alerts[0].0, "new_port_open");
+        assert!(alerts[0].1.ends_with("8080/tcp (http-proxy)"));
+        assert!(alerts[0].2.contains("\"port\":8080"));
+        assert_eq!(alerts[0].3, "WARNING");
+        assert_eq!(alerts[1].0, "port_closed");
+        assert!(alerts[1].1.ends_with("22/tcp (ssh)"));
+        assert_eq!(alerts[1].3, "INFO");
+
+        // Muted devices don't alert.
+        sqlx::query("UPDATE devices SET muted_until = datetime('now', '+1 hours') WHERE id = ?")
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let created = create_port_change_alerts(&pool, &ws_hub, &severities, &id, &diff)
+            .await
+            .unwrap();
+        assert_eq!(created, 0);
+    }
+
+    #[test]
+    fn test_parse_csv_line_quotes() {
+        assert_eq!(parse_csv_line("a,b,c"), vec!["a", "b", "c"]);
+        assert_eq!(
+            parse_csv_line(r#"x,"hello, world","say ""hi""""#),
+            vec!["x", "hello, world", r#"say "hi""#]
+        );
+        assert_eq!(parse_csv_line("a,,"), vec!["a", "", ""]);
+    }
+
+    #[test]
+    fn test_content_disposition_filename() {
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="devices.csv""#).as_deref(),
+            Some("devices.csv")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename=lan.csv").as_deref(),
+            Some("lan.csv")
+        );
+        assert_eq!(content_disposition_filename("attachment"), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_with_failed_rows_records_history() {
+        let pool = test_db().await;
+        insert_test_device(&pool, "aa:bb:cc:00:00:01").await;
+
+        let csv = "mac_address,ip_address,hostname\n\
+                   aa:bb:cc:00:00:01,10.0.0.1,existing\n\
+                   AA-BB-CC-00-00-02,10.0.0.2,printer\n\
+                   not-a-mac,10.0.0.3,broken\n\
+                   aa:bb:cc:00:00:04,999.1.1.1,bad-ip\n";
+        let rows = parse_import_csv(csv).unwrap();
+        let result = import_devices(&pool, rows, Some("lan.csv")).await.unwrap();
+        assert_eq!(result.total_rows, 4);
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].row, 3);
+
+        let (error_count, filename, errors_json): (i64, Option<String>, String) = sqlx::query_as(
+            "SELECT error_count, filename, errors_json FROM device_imports ORDER BY id DESC LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(error_count, 2);
+        assert_eq!(filename.as_deref(), Some("lan.csv"));
+        let errors: Vec<ImportRowError> = serde_json::from_str(&errors_json).unwrap();
+        assert_eq!(errors.len(), 2);
+
+        let ip: String = sqlx::query_scalar(
+            "SELECT di.ip FROM device_ips di JOIN devices d ON d.id = di.device_id \
+             WHERE d.mac = 'aa:bb:cc:00:00:02'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(ip, "10.0.0.2");
+    }
+
+    #[test]
+    fn test_import_requires_mac_column() {
+        let result = parse_import_csv("hostname\nfoo\n");
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_import_upserts_alias_notes_and_tags() {
+        let pool = test_db().await;
+        let existing = insert_test_device(&pool, "aa:bb:cc:00:00:01").await;
+
+        let csv = "mac,alias,notes,tags\n\
+                   AA:BB:CC:00:00:01,core-nas,Backup NAS,server;storage\n\
+                   aa:bb:cc:00:00:02,office-printer,,printer\n\
+                   aa:bb:cc:00:00:03,,,Bad_Tag\n";
+        let result = import_devices(&pool, parse_import_csv(csv).unwrap(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.skipped, 0);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].row, 3);
+
+        let name: Option<String> = sqlx::query_scalar("SELECT name FROM devices WHERE id = ?")
+            .bind(&existing)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("core-nas"));
+        assert_eq!(
+            load_tags(&pool, &existing).await.unwrap(),
+            vec!["server", "storage"]
+        );
+        let note: String = sqlx::query_scalar("SELECT body FROM device_notes WHERE device_id = ?")
+            .bind(&existing)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(note, "Backup NAS");
+
+        // JSON rows use the same fields.
+        let rows: Vec<ImportRow> = serde_json::from_str(
+            r#"[{"mac": "aa:bb:cc:00:00:04", "alias": "cam", "tags": ["iot", "camera"]},
+                {"mac": "zz"}]"#,
+        )
+        .unwrap();
+        let result = import_devices(&pool, rows, None).await.unwrap();
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.errors[0].reason, "Invalid MAC address 'zz'");
+
+        let too_many = (0..=MAX_IMPORT_ROWS)
+            .map(|_| ImportRow::default())
+            .collect();
+        assert!(matches!(
+            import_devices(&pool, too_many, None).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_snmp_config_validation_and_upsert() {
+        let pool = db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) \
+             VALUES ('sw1', 'aa:bb:cc:00:00:51', datetime('now'), datetime('now'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let request = |community: &str, version: Option<&str>| SnmpConfigRequest {
+            community: community.to_string(),
+            version: version.map(str::to_string),
+            port: None,
+        };
+
+        let err = save_snmp_config(&pool, "sw1", request("public", Some("3")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        let err = save_snmp_config(&pool, "sw1", request("  ", None))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        let err = save_snmp_config(&pool, "missing", request("public", None))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::ResourceNotFound("device", _)));
+
+        // Polling before configuration reports the missing config.
+        let err = snmp_target(&pool, "sw1").await.unwrap_err();
+        assert!(matches!(err, AppError::ResourceNotFound("snmp_config", _)));
+
+        save_snmp_config(&pool, "sw1", request("public", None))
+            .await
+            .unwrap();
+        let saved = save_snmp_config(&pool, "sw1", request("lab", Some("2c")))
+            .await
+            .unwrap();
+        assert_eq!((saved.version.as_str(), saved.port), ("2c", 161));
+
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current, ip_version) VALUES \
+             ('sw1', '2001:db8::51', datetime('now'), 1, 6), \
+             ('sw1', '10.0.0.51', datetime('now'), 1, 4)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (target, config) = snmp_target(&pool, "sw1").await.unwrap();
+        assert_eq!(target.to_string(), "10.0.0.51:161");
+        assert_eq!(config.community, "lab");
+    }
+
+    const NMAP_OS_XML: &str = r#"<?xml version="1.0"?>
+<nmaprun><host><os>
+<portused state="open" proto="tcp" portid="22"/>
+<osmatch name="Linux 4.15 - 5.8" accuracy="96" line="68012">
+<osclass type="general purpose" vendor="Linux" osfamily="Linux" osgen="4.X" accuracy="96"/>
+</osmatch>
+<osmatch name="Linux 5.0 &amp; later" accuracy="92" line="68100"/>
+<osmatch name="Crestron XPanel" accuracy="85" line="1"/>
+</os></host></nmaprun>"#;
+
+    #[test]
+    fn test_parse_nmap_os_matches() {
+        let matches = parse_nmap_os_matches(NMAP_OS_XML);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].name, "Linux 4.15 - 5.8");
+        assert_eq!(matches[0].accuracy, 96);
+        assert_eq!(matches[1].name, "Linux 5.0 & later");
+        assert!(parse_nmap_os_matches("<nmaprun></nmaprun>").is_empty());
+    }
+
+    #[test]
+    fn test_best_os_match_requires_confidence_above_80() {
+        let matches = parse_nmap_os_matches(NMAP_OS_XML);
+        assert_eq!(best_os_match(&matches).unwrap().name, "Linux 4.15 - 5.8");
+
+        let weak = vec![OsMatch {
+            name: "Windows 10".to_string(),
+            accuracy: 80,
+        }];
+        assert!(best_os_match(&weak).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_load_os_guesses() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool, "AA:BB:CC:DD:EE:31").await;
+
+        let empty = load_os_guesses(&pool, device_id.clone()).await.unwrap();
+        assert!(empty.matches.is_empty());
+        assert!(empty.scanned_at.is_none());
+
+        let matches = parse_nmap_os_matches(NMAP_OS_XML);
+        sqlx::query(
+            "INSERT INTO port_scans (device_id, result_json, os_matches_json) VALUES (?, '[]', ?)",
+        )
+        .bind(&device_id)
+        .bind(serde_json::to_string(&matches).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE devices SET os_hint = 'Linux 4.15 - 5.8' WHERE id = ?")
+            .bind(&device_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let guesses = load_os_guesses(&pool, device_id).await.unwrap();
+        assert_eq!(guesses.os_hint.as_deref(), Some("Linux 4.15 - 5.8"));
+        assert_eq!(guesses.matches, matches);
+        assert!(guesses.scanned_at.is_some());
+
+        let missing = load_os_guesses(&pool, "nope".to_string()).await;
+        assert!(matches!(missing, Err(AppError::ResourceNotFound(..))));
+    }
+
+    #[tokio::test]
+    async fn test_load_port_banners() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool, "AA:BB:CC:DD:EE:32").await;
+        let scan_id: i64 = sqlx::query_scalar(
+            "INSERT INTO port_scans (device_id, result_json) VALUES (?, '[]') RETURNING id",
+        )
+        .bind(&device_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        for (port, banner) in [
+            (80, "HTTP/1.0 200 OK\r\nServer: nginx"),
+            (22, "SSH-2.0-OpenSSH_9.2"),
+        ] {
+            sqlx::query("INSERT INTO port_scan_banners (scan_id, port, banner) VALUES (?, ?, ?)")
+                .bind(scan_id)
+                .bind(port)
+                .bind(banner)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let banners = load_port_banners(&pool, scan_id).await.unwrap();
+        assert_eq!(banners.len(), 2);
+        assert_eq!(banners[0].port, 22);
+        assert_eq!(banners[0].banner, "SSH-2.0-OpenSSH_9.2");
+        assert!(banners[1].banner.contains("Server: nginx"));
+        assert!(load_port_banners(&pool, scan_id + 1)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_tag() {
+        assert!(validate_tag("printer").is_ok());
+        assert!(validate_tag("iot-2").is_ok());
+        assert!(validate_tag(&"a".repeat(32)).is_ok());
+        assert!(validate_tag("").is_err());
+        assert!(validate_tag(&"a".repeat(33)).is_err());
+        assert!(validate_tag("Printer").is_err());
+        assert!(validate_tag("guest wifi").is_err());
+        assert!(validate_tag("iot_2").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_schedule_set_update_delete() {
+        let pool = test_db().await;
+        let id = insert_test_device(&pool, "AA:BB:CC:00:00:03").await;
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        let body = |interval_hours, enabled| {
+            Json(ScanScheduleRequest {
+                interval_hours,
+                enabled,
+            })
+        };
+
+        let Json(schedule) =
+            set_scan_schedule(State(state.clone()), Path(id.clone()), body(6, true))
+                .await
+                .unwrap();
+        assert_eq!(schedule.interval_hours, 6);
+        assert!(schedule.enabled);
+        assert!(schedule.last_scan_at.is_none());
+
+        // Updating keeps the last scan time.
+        sqlx::query("UPDATE device_scan_schedules SET last_scan_at = '2026-01-01 00:00:00'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let Json(schedule) =
+            set_scan_schedule(State(state.clone()), Path(id.clone()), body(12, false))
+                .await
+                .unwrap();
+        assert_eq!(schedule.interval_hours, 12);
+        assert!(!schedule.enabled);
+        assert_eq!(
+            schedule.last_scan_at.as_deref(),
+            Some("2026-01-01 00:00:00")
+        );
+
+        for hours in [0, MAX_SCAN_INTERVAL_HOURS + 1] {
+            assert!(matches!(
+                set_scan_schedule(State(state.clone()), Path(id.clone()), body(hours, true)).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert!(matches!(
+            set_scan_schedule(
+                State(state.clone()),
+                Path("missing".to_string()),
+                body(6, true)
+            )
+            .await,
+            Err(AppError::ResourceNotFound("device", _))
+        ));
+
+        let status = delete_scan_schedule(State(state.clone()), Path(id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(get_scan_schedule(State(state.clone()), Path(id.clone()))
+            .await
+            .is_err());
+        assert!(delete_scan_schedule(State(state.clone()), Path(id))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_device_tags_add_list_delete() {
+        let pool = test_db().await;
+        let printer = insert_test_device(&pool, "AA:BB:CC:00:00:01").await;
+        let laptop = insert_test_device(&pool, "AA:BB:CC:00:00:02").await;
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+
+        add_tag(&pool, &printer, "printer").await.unwrap();
+        add_tag(&pool, &printer, "office").await.unwrap();
+        // Re-adding an existing tag is a no-op.
+        let tags = add_tag(&pool, &printer, "printer").await.unwrap();
+        assert_eq!(tags, vec!["office", "printer"]);
+
+        assert!(matches!(
+            add_tag(&pool, &printer, "Bad Tag").await,
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            add_tag(&pool, "missing", "printer").await,
+            Err(AppError::ResourceNotFound("device", _))
+        ));
+
+        let Json(devices) = list(
+            State(state.clone()),
+            Query(ListDevicesQuery {
+                tag: Some("printer".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, printer);
+        assert_eq!(devices[0].tags, vec!["office", "printer"]);
+
+        let Json(devices) = list(State(state.clone()), Query(ListDevicesQuery { tag: None }))
+            .await
+            .unwrap();
+        assert_eq!(devices.len(), 2);
+        let untagged = devices.iter().find(|d| d.id == laptop).unwrap();
+        assert!(untagged.tags.is_empty());
+
+        let status = delete_tag(
+            State(state.clone()),
+            Path((printer.clone(), "printer".to_string())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(matches!(
+            delete_tag(State(state), Path((printer.clone(), "printer".to_string()))).await,
+            Err(AppError::ResourceNotFound("tag", _))
+        ));
+        assert_eq!(load_tags(&pool, &printer).await.unwrap(), vec!["office"]);
+    }
+
+    #[tokio::test]
+    async fn test_device_notes_crud() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool, "AA:BB:CC:00:00:10").await;
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        let note = |body: &str| {
+            Json(NoteRequest {
+                body: body.to_string(),
+            })
+        };
+
+        let long_body = "x".repeat(150);
+        let (status, Json(first)) = create_note(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(device_id.clone()),
+            note(&long_body),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(first.author, "admin");
+
+        assert!(matches!(
+            create_note(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                Path(device_id.clone()),
+                note(&"x".repeat(MAX_NOTE_LEN + 1))
+            )
+            .await,
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            create_note(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                Path("missing".to_string()),
+                note("hi")
+            )
+            .await,
+            Err(AppError::ResourceNotFound("device", _))
+        ));
+
+        // The device response carries a 100-character preview of the note.
+        let Json(device) = get_one(State(state.clone()), Path(device_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(device.latest_note.as_deref(), Some(&long_body[..100]));
+
+        let Json(updated) = update_note(
+            State(state.clone()),
+            Path((device_id.clone(), first.id.clone())),
+            note("Backup NAS"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.body, "Backup NAS");
+
+        let Json(notes) = list_notes(State(state.clone()), Path(device_id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(notes.len(), 1);
+
+        let status = delete_note(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path((device_id.clone(), first.id)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let actions: Vec<String> = sqlx::query_scalar("SELECT action FROM audit_log ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(actions, vec!["device_note_create", "device_note_delete"]);
+    }
 }
@@ -1,31 +1,80 @@
 use axum::{
-    http::StatusCode,
+    body::Body,
+    extract::{OriginalUri, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
     response::{IntoResponse, Response},
-    Json,
 };
 use serde::Serialize;
 
-/// Structured JSON error body returned by all API error responses.
-#[derive(Serialize)]
-pub struct ApiErrorBody {
+/// Media type for RFC 7807 problem detail responses.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Base URI for problem `type` identifiers.
+const PROBLEM_TYPE_BASE: &str = "https://panoptikon.dev/errors/";
+
+/// RFC 7807 Problem Details body returned by all API error responses.
+///
+/// `code` and `message` are extension members kept for clients that predate
+/// the problem format; `message` always equals `detail`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProblemDetail {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
     pub code: &'static str,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+}
+
+impl ProblemDetail {
+    /// Serialize into a response with the `application/problem+json` content type.
+    ///
+    /// The problem is also stored in the response extensions so
+    /// [`problem_instance`] can fill in `instance` from the request URI.
+    fn into_response_with_status(self, status: StatusCode) -> Response {
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+        let mut response = (status, [(header::CONTENT_TYPE, PROBLEM_JSON)], body).into_response();
+        if let Some(secs) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response.extensions_mut().insert(self);
+        response
+    }
 }
 
 /// Unified application error type.
 ///
 /// Implements [`IntoResponse`] so handlers can return `Result<T, AppError>`
-/// and axum will convert errors into structured JSON responses with the
+/// and axum will convert errors into RFC 7807 problem responses with the
 /// appropriate HTTP status code.
+#[derive(Debug)]
 pub enum AppError {
     /// Database query failed.
     Database(sqlx::Error),
     /// Resource not found (404).
     NotFound,
+    /// A specific kind of resource was not found (404), e.g. `("device", "Device not found")`.
+    ResourceNotFound(&'static str, String),
     /// Authentication required (401).
     Unauthorized,
+    /// Authenticated but not permitted (403), e.g. an API key without the needed scope.
+    Forbidden(String),
     /// Input validation failed (400).
     Validation(String),
+    /// Well-formed input that the server refuses to act on (422), e.g. a too-short password.
+    UnprocessableEntity(String),
+    /// Request body exceeds the accepted size (413).
+    PayloadTooLarge(String),
+    /// The request conflicts with the current state of a resource (409).
+    Conflict(String),
     /// Internal server error (500).
     Internal(String),
     /// Upstream service returned an error (502).
@@ -34,37 +83,168 @@ pub enum AppError {
     ServiceUnavailable(String),
     /// Rate limit exceeded (429).
     TooManyRequests(String),
+    /// Rate limit exceeded (429) with a known retry delay, sent as `Retry-After`.
+    RateLimited(String, u64),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, code, message) = match self {
+impl AppError {
+    /// Build the problem detail for this error (without `instance`).
+    pub fn problem(self) -> (StatusCode, ProblemDetail) {
+        let (status, slug, title, code, detail, retry_after) = match self {
             AppError::NotFound => (
                 StatusCode::NOT_FOUND,
+                "not-found".to_string(),
+                "Not Found",
                 "not_found",
                 "Resource not found".to_string(),
+                None,
+            ),
+            AppError::ResourceNotFound(resource, msg) => (
+                StatusCode::NOT_FOUND,
+                format!("{resource}-not-found"),
+                "Not Found",
+                "not_found",
+                msg,
+                None,
             ),
             AppError::Unauthorized => (
                 StatusCode::UNAUTHORIZED,
+                "unauthorized".to_string(),
+                "Unauthorized",
                 "unauthorized",
                 "Authentication required".to_string(),
+                None,
+            ),
+            AppError::Forbidden(msg) => (
+                StatusCode::FORBIDDEN,
+                "forbidden".to_string(),
+                "Forbidden",
+                "forbidden",
+                msg,
+                None,
+            ),
+            AppError::Validation(msg) => (
+                StatusCode::BAD_REQUEST,
+                "validation-error".to_string(),
+                "Validation Error",
+                "validation_error",
+                msg,
+                None,
+            ),
+            AppError::UnprocessableEntity(msg) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unprocessable-entity".to_string(),
+                "Unprocessable Entity",
+                "unprocessable_entity",
+                msg,
+                None,
+            ),
+            AppError::PayloadTooLarge(msg) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload-too-large".to_string(),
+                "Payload Too Large",
+                "payload_too_large",
+                msg,
+                None,
+            ),
+            AppError::Conflict(msg) => (
+                StatusCode::CONFLICT,
+                "conflict".to_string(),
+                "Conflict",
+                "conflict",
+                msg,
+                None,
             ),
-            AppError::Validation(msg) => (StatusCode::BAD_REQUEST, "validation_error", msg),
             AppError::Database(e) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
+                "database-error".to_string(),
+                "Database Error",
                 "database_error",
                 e.to_string(),
+                None,
+            ),
+            AppError::Internal(msg) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal-error".to_string(),
+                "Internal Server Error",
+                "internal_error",
+                msg,
+                None,
+            ),
+            AppError::BadGateway(msg) => (
+                StatusCode::BAD_GATEWAY,
+                "bad-gateway".to_string(),
+                "Bad Gateway",
+                "bad_gateway",
+                msg,
+                None,
+            ),
+            AppError::ServiceUnavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "service-unavailable".to_string(),
+                "Service Unavailable",
+                "service_unavailable",
+                msg,
+                None,
+            ),
+            AppError::TooManyRequests(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate-limited".to_string(),
+                "Too Many Requests",
+                "too_many_requests",
+                msg,
+                None,
+            ),
+            AppError::RateLimited(msg, secs) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate-limited".to_string(),
+                "Too Many Requests",
+                "too_many_requests",
+                msg,
+                Some(secs),
             ),
-            AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg),
-            AppError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, "bad_gateway", msg),
-            AppError::ServiceUnavailable(msg) => {
-                (StatusCode::SERVICE_UNAVAILABLE, "service_unavailable", msg)
-            }
-            AppError::TooManyRequests(msg) => {
-                (StatusCode::TOO_MANY_REQUESTS, "too_many_requests", msg)
-            }
         };
-        (status, Json(ApiErrorBody { code, message })).into_response()
+        (
+            status,
+            ProblemDetail {
+                problem_type: format!("{PROBLEM_TYPE_BASE}{slug}"),
+                title,
+                status: status.as_u16(),
+                message: detail.clone(),
+                detail,
+                instance: None,
+                code,
+                retry_after,
+            },
+        )
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Database(e) => write!(f, "database error: {e}"),
+            AppError::NotFound => write!(f, "resource not found"),
+            AppError::ResourceNotFound(_, msg)
+            | AppError::Forbidden(msg)
+            | AppError::Validation(msg)
+            | AppError::UnprocessableEntity(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::Conflict(msg)
+            | AppError::Internal(msg)
+            | AppError::BadGateway(msg)
+            | AppError::ServiceUnavailable(msg)
+            | AppError::TooManyRequests(msg)
+            | AppError::RateLimited(msg, _) => write!(f, "{msg}"),
+            AppError::Unauthorized => write!(f, "authentication required"),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, problem) = self.problem();
+        problem.into_response_with_status(status)
     }
 }
 
@@ -77,6 +257,29 @@ impl From<sqlx::Error> for AppError {
     }
 }
 
+/// Middleware that sets the problem `instance` to the request path.
+///
+/// Handlers don't see the full URI of nested routes, so the instance is
+/// filled in here from the [`ProblemDetail`] left in the response extensions.
+pub async fn problem_instance(req: Request, next: Next) -> Response {
+    let path = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let mut response = next.run(req).await;
+    let Some(mut problem) = response.extensions_mut().remove::<ProblemDetail>() else {
+        return response;
+    };
+    if problem.instance.is_none() {
+        problem.instance = Some(path);
+    }
+    *response.body_mut() = Body::from(serde_json::to_vec(&problem).unwrap_or_default());
+    response.headers_mut().remove(header::CONTENT_LENGTH);
+    response
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +310,19 @@ mod tests {
         assert_eq!(json["code"], "unauthorized");
     }
 
+    #[tokio::test]
+    async fn test_app_error_forbidden_response() {
+        let response = AppError::Forbidden("missing scope".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "forbidden");
+        assert_eq!(json["message"], "missing scope");
+    }
+
     #[tokio::test]
     async fn test_app_error_validation_response() {
         let response = AppError::Validation("email is required".to_string()).into_response();
@@ -120,6 +336,50 @@ mod tests {
         assert_eq!(json["message"], "email is required");
     }
 
+    #[tokio::test]
+    async fn test_app_error_unprocessable_entity_response() {
+        let response =
+            AppError::UnprocessableEntity("password too short".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "unprocessable_entity");
+        assert_eq!(json["message"], "password too short");
+    }
+
+    #[tokio::test]
+    async fn test_app_error_payload_too_large_response() {
+        let response = AppError::PayloadTooLarge("body too large".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "payload_too_large");
+        assert_eq!(
+            json["type"],
+            "https://panoptikon.dev/errors/payload-too-large"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_app_error_conflict_response() {
+        let response = AppError::Conflict("rule 20 already exists".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "conflict");
+        assert_eq!(json["type"], "https://panoptikon.dev/errors/conflict");
+    }
+
     #[tokio::test]
     async fn test_app_error_internal_response() {
         let response = AppError::Internal("something broke".to_string()).into_response();
@@ -150,4 +410,80 @@ mod tests {
         let response = AppError::TooManyRequests("try again later".to_string()).into_response();
         assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
     }
+
+    #[tokio::test]
+    async fn test_problem_json_content_type() {
+        let response = AppError::NotFound.into_response();
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+    }
+
+    #[tokio::test]
+    async fn test_problem_detail_fields() {
+        let response =
+            AppError::ResourceNotFound("device", "Device not found".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            json["type"],
+            "https://panoptikon.dev/errors/device-not-found"
+        );
+        assert_eq!(json["title"], "Not Found");
+        assert_eq!(json["status"], 404);
+        assert_eq!(json["detail"], "Device not found");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_sets_retry_after() {
+        let response = AppError::RateLimited("slow down".to_string(), 42).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "42");
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["type"], "https://panoptikon.dev/errors/rate-limited");
+        assert_eq!(json["retry_after"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_problem_instance_middleware_sets_request_path() {
+        use axum::{middleware, routing::get, Router};
+        use tower::Service;
+
+        let mut app = Router::new()
+            .route(
+                "/devices/:id",
+                get(|| async { AppError::ResourceNotFound("device", "Device not found".into()) }),
+            )
+            .layer(middleware::from_fn(problem_instance));
+
+        let response = app
+            .call(
+                Request::builder()
+                    .uri("/devices/abc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), 1_000_000)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["instance"], "/devices/abc");
+    }
 }
@@ -2,11 +2,18 @@ use axum::{
     body::Body,
     extract::{Query, State},
     http::{header, Response, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
 };
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::Row;
+use sqlx::{Row, SqlitePool};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
 
-use super::AppState;
+use super::auth::SessionUser;
+use super::users::ROLE_ADMIN;
+use super::{AppError, AppState};
 
 #[derive(Debug, Deserialize)]
 pub struct DevicesExportQuery {
@@ -107,7 +114,7 @@ fn download_response(
     content_type: &str,
     filename: &str,
     body: String,
-) -> Result<Response<Body>, StatusCode> {
+) -> Result<Response<Body>, AppError> {
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, content_type)
@@ -116,13 +123,13 @@ fn download_response(
             format!("attachment; filename=\"{filename}\""),
         )
         .body(Body::from(body))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .map_err(|e| AppError::Internal(format!("Failed to build download response: {e}")))
 }
 
 pub async fn devices_export(
     State(state): State<AppState>,
     Query(query): Query<DevicesExportQuery>,
-) -> Result<Response<Body>, StatusCode> {
+) -> Result<Response<Body>, AppError> {
     let format = query
         .format
         .unwrap_or_else(|| "csv".to_string())
@@ -147,15 +154,12 @@ pub async fn devices_export(
             WHERE is_current = 1
             GROUP BY device_id
         ) di ON di.device_id = d.id
+        WHERE d.is_deleted = 0
         ORDER BY d.last_seen_at DESC
         "#,
     )
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("devices_export query failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let devices: Vec<ExportDevice> = rows
         .into_iter()
@@ -194,7 +198,7 @@ pub async fn devices_export(
 pub async fn traffic_export(
     State(state): State<AppState>,
     Query(query): Query<TrafficExportQuery>,
-) -> Result<Response<Body>, StatusCode> {
+) -> Result<Response<Body>, AppError> {
     let format = query
         .format
         .unwrap_or_else(|| "csv".to_string())
@@ -225,11 +229,7 @@ pub async fn traffic_export(
     )
     .bind(minutes)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("traffic_export query failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let samples: Vec<ExportTrafficSample> = rows
         .into_iter()
@@ -263,6 +263,425 @@ pub async fn traffic_export(
     }
 }
 
+// ─── InfluxDB line protocol ─────────────────────────────
+
+/// Rows fetched per query (and lines per push request).
+const INFLUX_BATCH_SIZE: i64 = 1000;
+/// Export range when `start` is omitted.
+const INFLUX_DEFAULT_DAYS: i64 = 30;
+/// Buffer between the batch producer and the response stream.
+const INFLUX_STREAM_BUFFER: usize = 64 * 1024;
+/// Bucket written to when pushing without `bucket=`.
+const INFLUX_DEFAULT_BUCKET: &str = "panoptikon";
+
+/// Data set exported as InfluxDB line protocol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Measurement {
+    /// Device online/offline events.
+    DeviceEvents,
+    /// NetFlow traffic averaged per device per minute.
+    Traffic,
+    /// WAN speed test history.
+    Speedtest,
+}
+
+impl Measurement {
+    fn parse(value: &str) -> Result<Self, AppError> {
+        match value {
+            "device_events" => Ok(Self::DeviceEvents),
+            "traffic" => Ok(Self::Traffic),
+            "speedtest" => Ok(Self::Speedtest),
+            other => Err(AppError::Validation(format!(
+                "measurement must be one of device_events, traffic, speedtest (got '{other}')"
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::DeviceEvents => "device_events",
+            Self::Traffic => "traffic",
+            Self::Speedtest => "speedtest",
+        }
+    }
+}
+
+/// Query parameters for `GET /api/v1/export/influxdb`.
+#[derive(Debug, Deserialize)]
+pub struct InfluxExportQuery {
+    pub measurement: String,
+    /// Start of the range (`2024-01-01` or RFC 3339); defaults to 30 days ago.
+    pub start: Option<String>,
+    /// End of the range, exclusive; defaults to now.
+    pub end: Option<String>,
+    /// InfluxDB base URL. When set, lines are written there instead of
+    /// being returned (admin sessions only).
+    pub url: Option<String>,
+    pub token: Option<String>,
+    pub bucket: Option<String>,
+    pub org: Option<String>,
+}
+
+/// Result of pushing an export to InfluxDB.
+#[derive(Debug, Serialize)]
+pub struct InfluxPushResult {
+    pub measurement: &'static str,
+    pub lines_written: u64,
+    pub batches: u64,
+}
+
+/// Parse a range bound given as a date (midnight UTC) or RFC 3339 timestamp.
+fn parse_export_time(value: &str, field: &str) -> Result<DateTime<Utc>, AppError> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| {
+            AppError::Validation(format!(
+                "{field} must be a date (YYYY-MM-DD) or RFC 3339 timestamp"
+            ))
+        })
+}
+
+/// Escape a tag value (commas, equals signs and spaces are significant).
+fn escape_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ',' | '=' | ' ' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append `,key=value` for non-empty tag values (empty tags are invalid).
+fn push_tag(line: &mut String, key: &str, value: &str) {
+    if !value.is_empty() {
+        line.push(',');
+        line.push_str(key);
+        line.push('=');
+        line.push_str(&escape_tag(value));
+    }
+}
+
+/// Seconds to the nanosecond timestamps line protocol expects.
+fn to_ns(secs: i64) -> i128 {
+    i128::from(secs) * 1_000_000_000
+}
+
+/// Reads one measurement in keyset-paginated batches of line protocol.
+struct LineBatches {
+    db: SqlitePool,
+    measurement: Measurement,
+    start: i64,
+    end: i64,
+    /// Last row returned: `(id, "")`, or `(minute, device_id)` for traffic.
+    cursor: (i64, String),
+    done: bool,
+}
+
+impl LineBatches {
+    fn new(db: SqlitePool, measurement: Measurement, start: i64, end: i64) -> Self {
+        Self {
+            db,
+            measurement,
+            start,
+            end,
+            cursor: (i64::MIN, String::new()),
+            done: false,
+        }
+    }
+
+    /// The next batch of lines, or `None` once the range is exhausted.
+    async fn next_batch(&mut self) -> Result<Option<String>, sqlx::Error> {
+        if self.done {
+            return Ok(None);
+        }
+        let (rows, out) = match self.measurement {
+            Measurement::DeviceEvents => self.device_events().await?,
+            Measurement::Traffic => self.traffic().await?,
+            Measurement::Speedtest => self.speedtest().await?,
+        };
+        if rows < INFLUX_BATCH_SIZE as usize {
+            self.done = true;
+        }
+        Ok((rows > 0).then_some(out))
+    }
+
+    async fn device_events(&mut self) -> Result<(usize, String), sqlx::Error> {
+        let rows: Vec<(i64, String, String, String, Option<i64>)> = sqlx::query_as(
+            r#"SELECT e.id, e.device_id, COALESCE(d.mac, ''), e.event_type,
+                      CAST(strftime('%s', e.occurred_at) AS INTEGER)
+               FROM device_events e
+               LEFT JOIN devices d ON d.id = e.device_id
+               WHERE e.id > ?
+                 AND CAST(strftime('%s', e.occurred_at) AS INTEGER) >= ?
+                 AND CAST(strftime('%s', e.occurred_at) AS INTEGER) < ?
+               ORDER BY e.id
+               LIMIT ?"#,
+        )
+        .bind(self.cursor.0)
+        .bind(self.start)
+        .bind(self.end)
+        .bind(INFLUX_BATCH_SIZE)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut out = String::new();
+        for (id, device_id, mac, event_type, ts) in &rows {
+            self.cursor.0 = *id;
+            let Some(ts) = ts else { continue };
+            out.push_str("device_events");
+            push_tag(&mut out, "device_id", device_id);
+            push_tag(&mut out, "mac", mac);
+            push_tag(&mut out, "event_type", event_type);
+            out.push_str(&format!(" value=1 {}\n", to_ns(*ts)));
+        }
+        Ok((rows.len(), out))
+    }
+
+    async fn traffic(&mut self) -> Result<(usize, String), sqlx::Error> {
+        let (after_minute, after_device) = self.cursor.clone();
+        // Samples in minutes before the cursor can't contribute to this batch.
+        let from = self.start.max(after_minute);
+        let rows: Vec<(i64, String, i64, i64)> = sqlx::query_as(
+            r#"SELECT CAST(strftime('%s', sampled_at) AS INTEGER) / 60 * 60 AS minute,
+                      device_id,
+                      CAST(AVG(COALESCE(rx_bps, 0)) AS INTEGER),
+                      CAST(AVG(COALESCE(tx_bps, 0)) AS INTEGER)
+               FROM traffic_samples
+               WHERE source = 'netflow'
+                 AND CAST(strftime('%s', sampled_at) AS INTEGER) >= ?
+                 AND CAST(strftime('%s', sampled_at) AS INTEGER) < ?
+               GROUP BY minute, device_id
+               HAVING minute > ? OR (minute = ? AND device_id > ?)
+               ORDER BY minute, device_id
+               LIMIT ?"#,
+        )
+        .bind(from)
+        .bind(self.end)
+        .bind(after_minute)
+        .bind(after_minute)
+        .bind(&after_device)
+        .bind(INFLUX_BATCH_SIZE)
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut out = String::new();
+        for (minute, device_id, rx_bps, tx_bps) in &rows {
+            out.push_str("traffic");
+            push_tag(&mut out, "device_id", device_id);
+            out.push_str(&format!(
+                " rx_bps={rx_bps}i,tx_bps={tx_bps}i {}\n",
+                to_ns(*minute)
+            ));
+        }
+        if let Some((minute, device_id, _, _)) = rows.last() {
+            self.cursor = (*minute, device_id.clone());
+        }
+        Ok((rows.len(), out))
+    }
+
+    async fn speedtest(&mut self) -> Result<(usize, String), sqlx::Error> {
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(i64, f64, f64, f64, f64, f64, String, String, Option<i64>)> =
+            sqlx::query_as(
+                r#"SELECT id, download_mbps, upload_mbps, ping_ms, jitter_ms, packet_loss,
+                          isp, server, CAST(strftime('%s', tested_at) AS INTEGER)
+                   FROM speedtest_results
+                   WHERE id > ?
+                     AND CAST(strftime('%s', tested_at) AS INTEGER) >= ?
+                     AND CAST(strftime('%s', tested_at) AS INTEGER) < ?
+                   ORDER BY id
+                   LIMIT ?"#,
+            )
+            .bind(self.cursor.0)
+            .bind(self.start)
+            .bind(self.end)
+            .bind(INFLUX_BATCH_SIZE)
+            .fetch_all(&self.db)
+            .await?;
+
+        let mut out = String::new();
+        for (id, download, upload, ping, jitter, loss, isp, server, ts) in &rows {
+            self.cursor.0 = *id;
+            let Some(ts) = ts else { continue };
+            out.push_str("speedtest");
+            push_tag(&mut out, "isp", isp);
+            push_tag(&mut out, "server", server);
+            out.push_str(&format!(
+                " download_mbps={download},upload_mbps={upload},ping_ms={ping},\
+                 jitter_ms={jitter},packet_loss={loss} {}\n",
+                to_ns(*ts)
+            ));
+        }
+        Ok((rows.len(), out))
+    }
+}
+
+/// Write every batch to InfluxDB's v2 write API.
+async fn push_to_influx(
+    mut batches: LineBatches,
+    url: &str,
+    token: Option<&str>,
+    bucket: &str,
+    org: Option<&str>,
+) -> Result<InfluxPushResult, AppError> {
+    let mut write_url = reqwest::Url::parse(url)
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .ok_or_else(|| AppError::Validation("url must be an http(s) URL".to_string()))?
+        .join("api/v2/write")
+        .map_err(|e| AppError::Validation(format!("Invalid InfluxDB URL: {e}")))?;
+    {
+        let mut query = write_url.query_pairs_mut();
+        query.append_pair("bucket", bucket);
+        query.append_pair("precision", "ns");
+        if let Some(org) = org {
+            query.append_pair("org", org);
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::Internal(format!("Failed to build HTTP client: {e}")))?;
+
+    let mut result = InfluxPushResult {
+        measurement: batches.measurement.name(),
+        lines_written: 0,
+        batches: 0,
+    };
+    while let Some(body) = batches.next_batch().await? {
+        let lines = body.lines().count() as u64;
+        let mut request = client
+            .post(write_url.clone())
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(body);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Token {token}"));
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| AppError::BadGateway(format!("InfluxDB write failed: {e}")))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let detail = resp.text().await.unwrap_or_default();
+            return Err(AppError::BadGateway(format!(
+                "InfluxDB write returned {status}: {}",
+                detail.chars().take(200).collect::<String>()
+            )));
+        }
+        result.lines_written += lines;
+        result.batches += 1;
+    }
+    Ok(result)
+}
+
+/// GET /api/v1/export/influxdb?measurement=device_events&start=2024-01-01&end=2024-02-01
+///
+/// Streams the measurement as InfluxDB line protocol, reading 1000 rows at a
+/// time. With `url=` (and optionally `token`, `bucket`, `org`) the lines are
+/// written to that InfluxDB instead and a summary is returned.
+pub async fn influxdb_export(
+    State(state): State<AppState>,
+    user: Option<Extension<SessionUser>>,
+    Query(query): Query<InfluxExportQuery>,
+) -> Result<axum::response::Response, AppError> {
+    let measurement = Measurement::parse(query.measurement.trim())?;
+    let end = match query.end.as_deref() {
+        Some(end) => parse_export_time(end, "end")?,
+        None => Utc::now(),
+    };
+    let start = match query.start.as_deref() {
+        Some(start) => parse_export_time(start, "start")?,
+        None => end - chrono::Duration::days(INFLUX_DEFAULT_DAYS),
+    };
+    if start >= end {
+        return Err(AppError::Validation("start must be before end".to_string()));
+    }
+    let batches = LineBatches::new(
+        state.db.clone(),
+        measurement,
+        start.timestamp(),
+        end.timestamp(),
+    );
+
+    if let Some(url) = query
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+    {
+        if user.is_none_or(|Extension(user)| user.role != ROLE_ADMIN) {
+            return Err(AppError::Forbidden(
+                "Pushing to InfluxDB requires an admin session".to_string(),
+            ));
+        }
+        let bucket = query.bucket.as_deref().unwrap_or(INFLUX_DEFAULT_BUCKET);
+        let result = push_to_influx(
+            batches,
+            url,
+            query.token.as_deref(),
+            bucket,
+            query.org.as_deref(),
+        )
+        .await?;
+        tracing::info!(
+            measurement = result.measurement,
+            lines = result.lines_written,
+            "Pushed export to InfluxDB"
+        );
+        return Ok(Json(result).into_response());
+    }
+
+    // Batches are written into one end of an in-memory pipe and streamed
+    // from the other, so only one batch is held in memory at a time.
+    let (reader, mut writer) = tokio::io::duplex(INFLUX_STREAM_BUFFER);
+    tokio::spawn(async move {
+        let mut batches = batches;
+        loop {
+            match batches.next_batch().await {
+                Ok(Some(lines)) => {
+                    if writer.write_all(lines.as_bytes()).await.is_err() {
+                        // Client went away.
+                        return;
+                    }
+                }
+                Ok(None) => return,
+                Err(e) => {
+                    tracing::error!("InfluxDB export query failed: {e}");
+                    return;
+                }
+            }
+        }
+    });
+
+    let filename = format!(
+        "panoptikon-{}-{}.lp",
+        measurement.name(),
+        Utc::now().format("%Y-%m-%d")
+    );
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .map(IntoResponse::into_response)
+        .map_err(|e| AppError::Internal(format!("Failed to build export response: {e}")))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,4 +811,210 @@ mod tests {
         assert!(row.contains("\"host, \"\"quoted\"\"\""));
         assert!(row.contains("\"Acme, Inc\""));
     }
+
+    fn influx_query(measurement: &str, start: &str, end: &str) -> InfluxExportQuery {
+        InfluxExportQuery {
+            measurement: measurement.to_string(),
+            start: Some(start.to_string()),
+            end: Some(end.to_string()),
+            url: None,
+            token: None,
+            bucket: None,
+            org: None,
+        }
+    }
+
+    async fn influx_body(state: &AppState, query: InfluxExportQuery) -> String {
+        let resp = influxdb_export(State(state.clone()), None, Query(query))
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_influx_tag_escaping() {
+        assert_eq!(escape_tag("living room,tv=1"), "living\\ room\\,tv\\=1");
+        let mut line = String::from("m");
+        push_tag(&mut line, "empty", "");
+        push_tag(&mut line, "isp", "ACME Net");
+        assert_eq!(line, "m,isp=ACME\\ Net");
+    }
+
+    #[tokio::test]
+    async fn test_influxdb_export_device_events_in_batches() {
+        let pool = db::init(":memory:").await.expect("db init failed");
+        sqlx::query(
+            r#"INSERT INTO devices (id, mac, first_seen_at, last_seen_at)
+               VALUES ('dev-1', 'AA:BB:CC:DD:EE:01', datetime('now'), datetime('now'))"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        // 1500 events one minute apart from 2024-01-01, spanning two batches,
+        // plus one outside the range.
+        sqlx::query(
+            r#"WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < 1499)
+               INSERT INTO device_events (device_id, event_type, occurred_at)
+               SELECT 'dev-1', CASE i % 2 WHEN 0 THEN 'online' ELSE 'offline' END,
+                      datetime('2024-01-01 00:00:00', '+' || i || ' minutes')
+               FROM n"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO device_events (device_id, event_type, occurred_at) \
+             VALUES ('dev-1', 'online', '2024-03-01 00:00:00')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        let body = influx_body(
+            &state,
+            influx_query("device_events", "2024-01-01", "2024-02-01"),
+        )
+        .await;
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 1500);
+        assert_eq!(
+            lines[0],
+            "device_events,device_id=dev-1,mac=AA:BB:CC:DD:EE:01,event_type=online value=1 1704067200000000000"
+        );
+        assert!(lines[1499].ends_with(&format!(
+            " {}",
+            (1_704_067_200i128 + 1499 * 60) * 1_000_000_000
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_influxdb_export_traffic_per_minute() {
+        let pool = db::init(":memory:").await.expect("db init failed");
+        sqlx::query(
+            r#"INSERT INTO devices (id, mac, first_seen_at, last_seen_at)
+               VALUES ('dev-1', 'AA:BB:CC:DD:EE:01', datetime('now'), datetime('now'))"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (sampled_at, rx, source) in [
+            ("2024-01-01T10:00:10", 100, "netflow"),
+            ("2024-01-01T10:00:40", 300, "netflow"),
+            ("2024-01-01T10:01:05", 50, "netflow"),
+            ("2024-01-01T10:00:20", 9999, "vyos"),
+        ] {
+            sqlx::query(
+                r#"INSERT INTO traffic_samples (device_id, sampled_at, rx_bps, tx_bps, source)
+                   VALUES ('dev-1', ?, ?, 10, ?)"#,
+            )
+            .bind(sampled_at)
+            .bind(rx)
+            .bind(source)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        let body = influx_body(&state, influx_query("traffic", "2024-01-01", "2024-01-02")).await;
+        assert_eq!(
+            body,
+            "traffic,device_id=dev-1 rx_bps=200i,tx_bps=10i 1704103200000000000\n\
+             traffic,device_id=dev-1 rx_bps=50i,tx_bps=10i 1704103260000000000\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_influxdb_export_validation_and_push() {
+        let pool = db::init(":memory:").await.expect("db init failed");
+        sqlx::query(
+            r#"INSERT INTO speedtest_results
+                 (download_mbps, upload_mbps, ping_ms, jitter_ms, packet_loss, isp, server, tested_at)
+               VALUES (940.5, 41.2, 8.0, 1.5, 0.0, 'ACME Net', 'Vienna', '2024-01-05T12:00:00+00:00')"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        for query in [
+            influx_query("cpu", "2024-01-01", "2024-02-01"),
+            influx_query("speedtest", "January", "2024-02-01"),
+            influx_query("speedtest", "2024-02-01", "2024-01-01"),
+        ] {
+            assert!(matches!(
+                influxdb_export(State(state.clone()), None, Query(query)).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+
+        // A stand-in InfluxDB that records write requests.
+        let received = std::sync::Arc::new(tokio::sync::Mutex::new(Vec::<(String, String)>::new()));
+        let app = axum::Router::new().route(
+            "/api/v2/write",
+            axum::routing::post({
+                let received = received.clone();
+                move |uri: axum::http::Uri, headers: axum::http::HeaderMap, body: String| async move {
+                    let auth = headers[header::AUTHORIZATION].to_str().unwrap().to_string();
+                    received
+                        .lock()
+                        .await
+                        .push((format!("{} {auth}", uri.query().unwrap_or("")), body));
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut query = influx_query("speedtest", "2024-01-01", "2024-02-01");
+        query.url = Some(format!("http://{addr}"));
+        query.token = Some("secret".to_string());
+
+        // Viewers may download but not push.
+        let viewer = SessionUser {
+            user_id: None,
+            username: "noc".to_string(),
+            role: "viewer".to_string(),
+        };
+        let mut viewer_query = influx_query("speedtest", "2024-01-01", "2024-02-01");
+        viewer_query.url = query.url.clone();
+        assert!(matches!(
+            influxdb_export(
+                State(state.clone()),
+                Some(Extension(viewer)),
+                Query(viewer_query)
+            )
+            .await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        let admin = SessionUser {
+            user_id: None,
+            username: "admin".to_string(),
+            role: ROLE_ADMIN.to_string(),
+        };
+        let resp = influxdb_export(State(state.clone()), Some(Extension(admin)), Query(query))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let received = received.lock().await;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].0, "bucket=panoptikon&precision=ns Token secret");
+        assert_eq!(
+            received[0].1,
+            "speedtest,isp=ACME\\ Net,server=Vienna download_mbps=940.5,upload_mbps=41.2,\
+             ping_ms=8,jitter_ms=1.5,packet_loss=0 1704456000000000000\n"
+        );
+    }
 }
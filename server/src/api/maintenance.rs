@@ -0,0 +1,396 @@
+//! Maintenance windows: planned downtime during which the scanner raises no
+//! alerts for the covered devices (or for any device when `device_ids` is
+//! null). Device state and WebSocket events are still updated as usual.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use tracing::{info, warn};
+
+use super::{AppError, AppState};
+
+/// A maintenance window as returned by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: String,
+    pub name: String,
+    /// RFC 3339, UTC.
+    pub start_at: String,
+    /// RFC 3339, UTC.
+    pub end_at: String,
+    /// Covered devices; `None` covers every device.
+    pub device_ids: Option<Vec<String>>,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+impl MaintenanceWindow {
+    fn from_row(row: sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        let device_ids: Option<String> = row.try_get("device_ids")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            start_at: row.try_get("start_at")?,
+            end_at: row.try_get("end_at")?,
+            device_ids: device_ids
+                .map(|json| serde_json::from_str(&json))
+                .transpose()
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            enabled: row.try_get::<i32, _>("enabled")? != 0,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// Windows in effect at one instant, loaded once per scan.
+#[derive(Debug, Clone, Default)]
+pub struct ActiveWindows {
+    global: bool,
+    device_ids: HashSet<String>,
+}
+
+impl ActiveWindows {
+    /// Whether alerts for `device_id` are suppressed. Alerts without a device
+    /// are only covered by global windows.
+    pub fn covers(&self, device_id: Option<&str>) -> bool {
+        self.global || device_id.is_some_and(|id| self.device_ids.contains(id))
+    }
+}
+
+/// Load the enabled windows containing `now` (RFC 3339, UTC). On error, logs
+/// and treats no window as active.
+pub async fn load_active(db: &SqlitePool, now: &str) -> ActiveWindows {
+    let rows: Vec<Option<String>> = match sqlx::query_scalar(
+        "SELECT device_ids FROM maintenance_windows \
+         WHERE enabled = 1 AND start_at <= ? AND end_at > ?",
+    )
+    .bind(now)
+    .bind(now)
+    .fetch_all(db)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(error = %e, "Failed to load maintenance windows");
+            return ActiveWindows::default();
+        }
+    };
+
+    let mut active = ActiveWindows::default();
+    for device_ids in rows {
+        let Some(json) = device_ids else {
+            active.global = true;
+            continue;
+        };
+        match serde_json::from_str::<Vec<String>>(&json) {
+            Ok(ids) => active.device_ids.extend(ids),
+            Err(e) => warn!(error = %e, "Skipping unreadable maintenance window"),
+        }
+    }
+    active
+}
+
+/// Request body for creating a maintenance window.
+#[derive(Debug, Deserialize)]
+pub struct CreateMaintenanceWindow {
+    pub name: String,
+    pub start_at: String,
+    pub end_at: String,
+    /// Omitted, null or empty covers every device.
+    pub device_ids: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+/// Request body for updating a maintenance window. Omitted fields are
+/// unchanged; an empty `device_ids` makes the window cover every device.
+#[derive(Debug, Deserialize)]
+pub struct UpdateMaintenanceWindow {
+    pub name: Option<String>,
+    pub start_at: Option<String>,
+    pub end_at: Option<String>,
+    pub device_ids: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "Maintenance window name is required".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Parse an RFC 3339 timestamp and normalize it to UTC, so stored values
+/// compare correctly as strings.
+fn validate_time(field: &str, value: &str) -> Result<String, AppError> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|t| t.with_timezone(&Utc).to_rfc3339())
+        .map_err(|_| AppError::Validation(format!("{field} must be an RFC 3339 timestamp")))
+}
+
+fn validate_period(start_at: &str, end_at: &str) -> Result<(), AppError> {
+    if end_at <= start_at {
+        return Err(AppError::Validation(
+            "end_at must be after start_at".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Empty lists mean "all devices".
+fn normalize_device_ids(device_ids: Option<Vec<String>>) -> Option<Vec<String>> {
+    device_ids.filter(|ids| !ids.is_empty())
+}
+
+fn encode_device_ids(device_ids: &Option<Vec<String>>) -> Result<Option<String>, AppError> {
+    device_ids
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| AppError::Internal(format!("Failed to encode device_ids: {e}")))
+}
+
+const SELECT_WINDOWS: &str = "SELECT id, name, start_at, end_at, device_ids, enabled, created_at \
+     FROM maintenance_windows";
+
+async fn fetch_window(db: &SqlitePool, id: &str) -> Result<MaintenanceWindow, AppError> {
+    let row = sqlx::query(&format!("{SELECT_WINDOWS} WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::ResourceNotFound(
+                "maintenance_window",
+                "Maintenance window not found".to_string(),
+            )
+        })?;
+    Ok(MaintenanceWindow::from_row(row)?)
+}
+
+/// GET /api/v1/maintenance — list all windows, most recent first.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<MaintenanceWindow>>, AppError> {
+    let rows = sqlx::query(&format!("{SELECT_WINDOWS} ORDER BY start_at DESC"))
+        .fetch_all(&state.db)
+        .await?;
+    let windows = rows
+        .into_iter()
+        .map(MaintenanceWindow::from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(windows))
+}
+
+/// POST /api/v1/maintenance — create a window.
+pub async fn create(
+    State(state): State<AppState>,
+    Json(body): Json<CreateMaintenanceWindow>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), AppError> {
+    let name = validate_name(&body.name)?;
+    let start_at = validate_time("start_at", &body.start_at)?;
+    let end_at = validate_time("end_at", &body.end_at)?;
+    validate_period(&start_at, &end_at)?;
+    let device_ids = encode_device_ids(&normalize_device_ids(body.device_ids))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO maintenance_windows (id, name, start_at, end_at, device_ids, enabled) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(&name)
+    .bind(&start_at)
+    .bind(&end_at)
+    .bind(&device_ids)
+    .bind(body.enabled.unwrap_or(true))
+    .execute(&state.db)
+    .await?;
+
+    info!(window_id = %id, name = %name, %start_at, %end_at, "Maintenance window created");
+    Ok((
+        StatusCode::CREATED,
+        Json(fetch_window(&state.db, &id).await?),
+    ))
+}
+
+/// PATCH /api/v1/maintenance/:id — update a window.
+pub async fn update(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateMaintenanceWindow>,
+) -> Result<Json<MaintenanceWindow>, AppError> {
+    let mut window = fetch_window(&state.db, &id).await?;
+
+    if let Some(ref name) = body.name {
+        window.name = validate_name(name)?;
+    }
+    if let Some(ref start_at) = body.start_at {
+        window.start_at = validate_time("start_at", start_at)?;
+    }
+    if let Some(ref end_at) = body.end_at {
+        window.end_at = validate_time("end_at", end_at)?;
+    }
+    validate_period(&window.start_at, &window.end_at)?;
+    if body.device_ids.is_some() {
+        window.device_ids = normalize_device_ids(body.device_ids);
+    }
+    if let Some(enabled) = body.enabled {
+        window.enabled = enabled;
+    }
+
+    sqlx::query(
+        "UPDATE maintenance_windows SET name = ?, start_at = ?, end_at = ?, device_ids = ?, \
+         enabled = ? WHERE id = ?",
+    )
+    .bind(&window.name)
+    .bind(&window.start_at)
+    .bind(&window.end_at)
+    .bind(encode_device_ids(&window.device_ids)?)
+    .bind(window.enabled)
+    .bind(&id)
+    .execute(&state.db)
+    .await?;
+
+    info!(window_id = %id, "Maintenance window updated");
+    Ok(Json(window))
+}
+
+/// DELETE /api/v1/maintenance/:id — delete a window.
+pub async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let result = sqlx::query("DELETE FROM maintenance_windows WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::ResourceNotFound(
+            "maintenance_window",
+            "Maintenance window not found".to_string(),
+        ));
+    }
+    info!(window_id = %id, "Maintenance window deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    fn window(
+        start_at: &str,
+        end_at: &str,
+        device_ids: Option<Vec<&str>>,
+    ) -> CreateMaintenanceWindow {
+        CreateMaintenanceWindow {
+            name: "Firmware upgrade".to_string(),
+            start_at: start_at.to_string(),
+            end_at: end_at.to_string(),
+            device_ids: device_ids.map(|ids| ids.into_iter().map(String::from).collect()),
+            enabled: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crud_validates_period() {
+        let state = test_state().await;
+
+        let err = create(
+            State(state.clone()),
+            Json(window("2026-01-01T02:00:00Z", "2026-01-01T01:00:00Z", None)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        let err = create(
+            State(state.clone()),
+            Json(window("tomorrow", "2026-01-01T01:00:00Z", None)),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+
+        let (status, Json(created)) = create(
+            State(state.clone()),
+            Json(window(
+                "2026-01-01T01:00:00+02:00",
+                "2026-01-01T03:00:00+02:00",
+                Some(vec![]),
+            )),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.start_at, "2025-12-31T23:00:00+00:00");
+        assert_eq!(created.device_ids, None);
+        assert!(created.enabled);
+
+        let Json(updated) = update(
+            State(state.clone()),
+            Path(created.id.clone()),
+            Json(UpdateMaintenanceWindow {
+                name: None,
+                start_at: None,
+                end_at: None,
+                device_ids: Some(vec!["dev-1".to_string()]),
+                enabled: Some(false),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.device_ids, Some(vec!["dev-1".to_string()]));
+        assert!(!updated.enabled);
+
+        let Json(all) = list(State(state.clone())).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(
+            delete(State(state.clone()), Path(created.id.clone()))
+                .await
+                .unwrap(),
+            StatusCode::NO_CONTENT
+        );
+        assert!(delete(State(state), Path(created.id)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_active() {
+        let state = test_state().await;
+        let now = Utc::now();
+        let at = |hours: i64| (now + chrono::Duration::hours(hours)).to_rfc3339();
+
+        for (start, end, ids) in [
+            (at(-1), at(1), Some(vec!["dev-1"])),
+            (at(1), at(2), None),
+            (at(-2), at(-1), Some(vec!["dev-2"])),
+        ] {
+            let _ = create(State(state.clone()), Json(window(&start, &end, ids)))
+                .await
+                .unwrap();
+        }
+
+        let active = load_active(&state.db, &now.to_rfc3339()).await;
+        assert!(active.covers(Some("dev-1")));
+        assert!(!active.covers(Some("dev-2")));
+        assert!(!active.covers(None));
+
+        let _ = create(State(state.clone()), Json(window(&at(-1), &at(1), None)))
+            .await
+            .unwrap();
+        let active = load_active(&state.db, &now.to_rfc3339()).await;
+        assert!(active.covers(Some("dev-2")));
+        assert!(active.covers(None));
+    }
+}
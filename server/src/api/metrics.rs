@@ -2,34 +2,143 @@
 //!
 //! Returns metrics in Prometheus text exposition format (text/plain; version=0.0.4).
 //! No external crate dependency — formats the text manually.
+//!
+//! Most values are read from the database at scrape time. Values with no
+//! database record (scan durations) live in [`MetricsState`] on `AppState`,
+//! updated by the scanner task.
+//!
+//! Metrics:
+//!
+//! | Name | Type | Labels |
+//! |------|------|--------|
+//! | `panoptikon_devices_online_total` | gauge | |
+//! | `panoptikon_devices_offline_total` | gauge | |
+//! | `panoptikon_devices_total` | gauge | `status` |
+//! | `panoptikon_device_is_online` | gauge | `mac`, `alias`, `vendor` (most recently seen `prometheus_max_device_labels` devices) |
+//! | `panoptikon_agents_online_total` | gauge | |
+//! | `panoptikon_agents_total` | gauge | `status` |
+//! | `panoptikon_alerts_total` | gauge | `type`, `severity`, `status` |
+//! | `panoptikon_alerts_created_total` | counter | `type` (since process start) |
+//! | `panoptikon_traffic_rx_bps` / `_tx_bps` | gauge | `device_id`, `ip` |
+//! | `panoptikon_netflow_flows_received_total` | counter | |
+//! | `panoptikon_scan_duration_seconds` | histogram | |
+//! | `panoptikon_scan_devices_found` | gauge | (devices in the last ARP scan) |
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use axum::extract::State;
-use axum::http::{header, StatusCode};
+use axum::http::header;
 use axum::response::{IntoResponse, Response};
+use tokio::sync::Mutex;
+
+use super::{AppError, AppState};
+
+/// Upper bounds (seconds) of the `panoptikon_scan_duration_seconds` buckets.
+pub const SCAN_DURATION_BUCKETS: &[f64] = &[1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// A Prometheus histogram with fixed bucket bounds.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Non-cumulative count per bucket.
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    /// Record one observation.
+    pub fn observe(&mut self, value: f64) {
+        if let Some(i) = self.bounds.iter().position(|b| value <= *b) {
+            self.counts[i] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Write HELP, TYPE, cumulative `_bucket` lines, `_sum` and `_count`.
+    fn write(&self, out: &mut String, name: &str, help: &str) {
+        out.push_str(&format!("# HELP {name} {help}\n"));
+        out.push_str(&format!("# TYPE {name} histogram\n"));
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            out.push_str(&format!("{name}_bucket{{le=\"{bound}\"}} {cumulative}\n"));
+        }
+        out.push_str(&format!("{name}_bucket{{le=\"+Inf\"}} {}\n", self.count));
+        out.push_str(&format!("{name}_sum {}\n", self.sum));
+        out.push_str(&format!("{name}_count {}\n", self.count));
+    }
+}
+
+/// In-process metrics that are not stored in the database.
+#[derive(Debug, Clone)]
+pub struct MetricsState {
+    /// Duration of each completed ARP scan cycle (scan + processing).
+    pub scan_duration: Histogram,
+    /// Devices found by the last ARP scan; `None` before the first scan.
+    pub scan_devices_found: Option<u64>,
+}
+
+impl Default for MetricsState {
+    fn default() -> Self {
+        Self {
+            scan_duration: Histogram::new(SCAN_DURATION_BUCKETS),
+            scan_devices_found: None,
+        }
+    }
+}
 
-use super::AppState;
+/// Alerts stored since process start, by type. Global like the NetFlow flow
+/// counter, since alerts are raised from many background tasks.
+static ALERTS_CREATED: std::sync::Mutex<BTreeMap<String, u64>> =
+    std::sync::Mutex::new(BTreeMap::new());
+
+/// Count a newly stored alert for `panoptikon_alerts_created_total`.
+pub fn record_alert_created(alert_type: &str) {
+    let mut counts = ALERTS_CREATED.lock().unwrap_or_else(|e| e.into_inner());
+    *counts.entry(alert_type.to_string()).or_default() += 1;
+}
+
+/// Escape a label value for the text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Shared handle to [`MetricsState`].
+pub type SharedMetrics = Arc<Mutex<MetricsState>>;
 
 /// GET /metrics — Prometheus scrape endpoint (no auth).
-pub async fn handler(State(state): State<AppState>) -> Result<Response, StatusCode> {
+pub async fn handler(State(state): State<AppState>) -> Result<Response, AppError> {
     let mut out = String::with_capacity(4096);
 
     // ── Devices ────────────────────────────────────────────────────────
-    let devices_online: i64 =
-        sqlx::query_scalar(r#"SELECT COUNT(*) FROM devices WHERE is_online = 1"#)
-            .fetch_one(&state.db)
-            .await
-            .unwrap_or(0);
-
-    let devices_offline: i64 =
-        sqlx::query_scalar(r#"SELECT COUNT(*) FROM devices WHERE is_online = 0"#)
-            .fetch_one(&state.db)
-            .await
-            .unwrap_or(0);
+    let devices_online: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM devices WHERE is_online = 1 AND is_deleted = 0"#,
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap_or(0);
 
-    let devices_total: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM devices"#)
-        .fetch_one(&state.db)
-        .await
-        .unwrap_or(0);
+    let devices_offline: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM devices WHERE is_online = 0 AND is_deleted = 0"#,
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap_or(0);
 
     write_gauge(
         &mut out,
@@ -43,12 +152,42 @@ pub async fn handler(State(state): State<AppState>) -> Result<Response, StatusCo
         "Number of devices currently offline",
         devices_offline,
     );
-    write_gauge(
-        &mut out,
-        "panoptikon_devices_total",
-        "Total number of discovered devices",
-        devices_total,
-    );
+    out.push_str("# HELP panoptikon_devices_total Number of discovered devices by status\n");
+    out.push_str("# TYPE panoptikon_devices_total gauge\n");
+    out.push_str(&format!(
+        "panoptikon_devices_total{{status=\"online\"}} {devices_online}\n"
+    ));
+    out.push_str(&format!(
+        "panoptikon_devices_total{{status=\"offline\"}} {devices_offline}\n"
+    ));
+
+    // ── Per-device state (bounded label cardinality) ───────────────────
+    let max_device_labels = state.config().prometheus_max_device_labels;
+    let device_rows: Vec<(String, String, String, bool)> = sqlx::query_as(
+        r#"SELECT mac, COALESCE(name, hostname, ''), COALESCE(vendor, ''), is_online
+           FROM devices WHERE is_deleted = 0
+           ORDER BY last_seen_at DESC LIMIT ?"#,
+    )
+    .bind(max_device_labels as i64)
+    .fetch_all(&state.db)
+    .await
+    .unwrap_or_default();
+
+    if !device_rows.is_empty() {
+        out.push_str(
+            "# HELP panoptikon_device_is_online Whether the device is online (1) or offline (0)\n",
+        );
+        out.push_str("# TYPE panoptikon_device_is_online gauge\n");
+        for (mac, alias, vendor, is_online) in &device_rows {
+            out.push_str(&format!(
+                "panoptikon_device_is_online{{mac=\"{}\",alias=\"{}\",vendor=\"{}\"}} {}\n",
+                escape_label(mac),
+                escape_label(alias),
+                escape_label(vendor),
+                u8::from(*is_online)
+            ));
+        }
+    }
 
     // ── Agents ─────────────────────────────────────────────────────────
     let agents_online: i64 = sqlx::query_scalar(
@@ -65,34 +204,62 @@ pub async fn handler(State(state): State<AppState>) -> Result<Response, StatusCo
         agents_online,
     );
 
-    // ── Alerts by severity × status ────────────────────────────────────
-    let alert_rows: Vec<(String, String, i64)> = sqlx::query_as(
+    let agents_total: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM agents"#)
+        .fetch_one(&state.db)
+        .await
+        .unwrap_or(0);
+    let agents_connected = state.ws_hub.agent_count().await as i64;
+    let agents_disconnected = (agents_total - agents_connected).max(0);
+
+    out.push_str(
+        "# HELP panoptikon_agents_total Number of registered agents by WebSocket connection status\n",
+    );
+    out.push_str("# TYPE panoptikon_agents_total gauge\n");
+    out.push_str(&format!(
+        "panoptikon_agents_total{{status=\"connected\"}} {agents_connected}\n"
+    ));
+    out.push_str(&format!(
+        "panoptikon_agents_total{{status=\"disconnected\"}} {agents_disconnected}\n"
+    ));
+
+    // ── Alerts by type × severity × status ─────────────────────────────
+    let alert_rows: Vec<(String, String, String, i64)> = sqlx::query_as(
         r#"SELECT
+             type,
              severity,
              CASE WHEN acknowledged_at IS NOT NULL THEN 'acknowledged' ELSE 'active' END AS status,
              COUNT(*) AS cnt
            FROM alerts
-           GROUP BY severity, status"#,
+           GROUP BY type, severity, status
+           ORDER BY type, severity, status"#,
     )
     .fetch_all(&state.db)
     .await
     .unwrap_or_default();
 
-    out.push_str("# HELP panoptikon_alerts_total Number of alerts by severity and status\n");
+    out.push_str("# HELP panoptikon_alerts_total Number of alerts by type, severity and status\n");
     out.push_str("# TYPE panoptikon_alerts_total gauge\n");
 
-    // Ensure we emit all expected label combos (even if zero).
-    for severity in &["INFO", "WARNING", "CRITICAL"] {
-        for status in &["active", "acknowledged"] {
-            let count = alert_rows
-                .iter()
-                .find(|(s, st, _)| s == severity && st == status)
-                .map(|(_, _, c)| *c)
-                .unwrap_or(0);
-            out.push_str(&format!(
-                "panoptikon_alerts_total{{severity=\"{severity}\",status=\"{status}\"}} {count}\n"
-            ));
-        }
+    for (alert_type, severity, status, count) in &alert_rows {
+        out.push_str(&format!(
+            "panoptikon_alerts_total{{type=\"{alert_type}\",severity=\"{severity}\",status=\"{status}\"}} {count}\n"
+        ));
+    }
+
+    // ── Alerts created since start (counter) ───────────────────────────
+    let alerts_created = ALERTS_CREATED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    out.push_str(
+        "# HELP panoptikon_alerts_created_total Alerts created since the server started, by type\n",
+    );
+    out.push_str("# TYPE panoptikon_alerts_created_total counter\n");
+    for (alert_type, count) in &alerts_created {
+        out.push_str(&format!(
+            "panoptikon_alerts_created_total{{type=\"{}\"}} {count}\n",
+            escape_label(alert_type)
+        ));
     }
 
     // ── Traffic per device (latest sample) ─────────────────────────────
@@ -138,13 +305,29 @@ pub async fn handler(State(state): State<AppState>) -> Result<Response, StatusCo
     let flows = crate::netflow::flows_received();
 
     out.push_str(
-        "# HELP panoptikon_netflow_flows_received_total Total NetFlow v5 flow records received\n",
+        "# HELP panoptikon_netflow_flows_received_total Total NetFlow flow records received\n",
     );
     out.push_str("# TYPE panoptikon_netflow_flows_received_total counter\n");
     out.push_str(&format!(
         "panoptikon_netflow_flows_received_total {flows}\n"
     ));
 
+    // ── Scan duration (histogram) and size ─────────────────────────────
+    let metrics = state.metrics.lock().await.clone();
+    metrics.scan_duration.write(
+        &mut out,
+        "panoptikon_scan_duration_seconds",
+        "Duration of ARP scan cycles in seconds",
+    );
+    if let Some(found) = metrics.scan_devices_found {
+        write_gauge(
+            &mut out,
+            "panoptikon_scan_devices_found",
+            "Number of devices found by the last ARP scan",
+            found as i64,
+        );
+    }
+
     Ok((
         [(
             header::CONTENT_TYPE,
@@ -211,6 +394,127 @@ mod tests {
         assert!(body.contains("# TYPE panoptikon_agents_online_total gauge"));
         assert!(body.contains("# TYPE panoptikon_alerts_total gauge"));
         assert!(body.contains("# TYPE panoptikon_netflow_flows_received_total counter"));
+        assert!(body.contains("# TYPE panoptikon_scan_duration_seconds histogram"));
+        assert!(body.contains("panoptikon_agents_total{status=\"connected\"} 0"));
+    }
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::new(&[1.0, 5.0]);
+        for value in [0.5, 3.0, 4.0, 9.0] {
+            histogram.observe(value);
+        }
+        let mut out = String::new();
+        histogram.write(&mut out, "scan_seconds", "Scan time");
+
+        assert!(out.contains("# TYPE scan_seconds histogram"));
+        assert!(out.contains("scan_seconds_bucket{le=\"1\"} 1\n"));
+        assert!(out.contains("scan_seconds_bucket{le=\"5\"} 3\n"));
+        assert!(out.contains("scan_seconds_bucket{le=\"+Inf\"} 4\n"));
+        assert!(out.contains("scan_seconds_sum 16.5\n"));
+        assert!(out.contains("scan_seconds_count 4\n"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_alerts_by_type_and_agents() {
+        let state = test_state().await;
+        for (id, alert_type, acknowledged) in [
+            ("a1", "new_device", false),
+            ("a2", "new_device", true),
+            ("a3", "device_offline", false),
+        ] {
+            sqlx::query(
+                r#"INSERT INTO alerts (id, type, message, severity, created_at, acknowledged_at)
+                   VALUES (?, ?, 'msg', 'INFO', datetime('now'),
+                           CASE WHEN ? THEN datetime('now') END)"#,
+            )
+            .bind(id)
+            .bind(alert_type)
+            .bind(acknowledged)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+        for agent_id in ["agent-1", "agent-2"] {
+            sqlx::query("INSERT INTO agents (id, api_key_hash, name) VALUES (?, 'x', ?)")
+                .bind(agent_id)
+                .bind(agent_id)
+                .execute(&state.db)
+                .await
+                .unwrap();
+        }
+        let _rx = state.ws_hub.register_agent("agent-1").await;
+        state.metrics.lock().await.scan_duration.observe(2.0);
+
+        let body = get_metrics_body(&state).await;
+
+        assert!(body.contains(
+            "panoptikon_alerts_total{type=\"new_device\",severity=\"INFO\",status=\"active\"} 1"
+        ));
+        assert!(body.contains(
+            "panoptikon_alerts_total{type=\"new_device\",severity=\"INFO\",status=\"acknowledged\"} 1"
+        ));
+        assert!(body.contains(
+            "panoptikon_alerts_total{type=\"device_offline\",severity=\"INFO\",status=\"active\"} 1"
+        ));
+        assert!(body.contains("panoptikon_agents_total{status=\"connected\"} 1"));
+        assert!(body.contains("panoptikon_agents_total{status=\"disconnected\"} 1"));
+        assert!(body.contains("panoptikon_scan_duration_seconds_bucket{le=\"2.5\"} 1"));
+        assert!(body.contains("panoptikon_scan_duration_seconds_count 1"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_per_device_labels_are_bounded() {
+        let state = test_state().await;
+        state.config.write().unwrap().prometheus_max_device_labels = 2;
+        for (mac, name, vendor, online, seen) in [
+            ("AA:BB:CC:DD:EE:01", "old", "Acme", 1, "2026-01-01 00:00:00"),
+            (
+                "AA:BB:CC:DD:EE:02",
+                "nas \"main\"",
+                "Acme",
+                0,
+                "2026-01-02 00:00:00",
+            ),
+            (
+                "AA:BB:CC:DD:EE:03",
+                "tv",
+                "Vendor\\Co",
+                1,
+                "2026-01-03 00:00:00",
+            ),
+        ] {
+            sqlx::query(
+                r#"INSERT INTO devices (id, mac, name, vendor, first_seen_at, last_seen_at, is_online)
+                   VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(mac)
+            .bind(name)
+            .bind(vendor)
+            .bind(seen)
+            .bind(seen)
+            .bind(online)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+        state.metrics.lock().await.scan_devices_found = Some(3);
+        record_alert_created("metrics_test_alert");
+        record_alert_created("metrics_test_alert");
+
+        let body = get_metrics_body(&state).await;
+
+        assert!(body.contains(
+            "panoptikon_device_is_online{mac=\"AA:BB:CC:DD:EE:03\",alias=\"tv\",vendor=\"Vendor\\\\Co\"} 1"
+        ));
+        assert!(body.contains(
+            "panoptikon_device_is_online{mac=\"AA:BB:CC:DD:EE:02\",alias=\"nas \\\"main\\\"\",vendor=\"Acme\"} 0"
+        ));
+        assert!(!body.contains("mac=\"AA:BB:CC:DD:EE:01\""));
+        assert!(body.contains("panoptikon_scan_devices_found 3"));
+        assert!(body.contains("# TYPE panoptikon_alerts_created_total counter"));
+        assert!(body.contains("panoptikon_alerts_created_total{type=\"metrics_test_alert\"} 2"));
     }
 
     #[tokio::test]
@@ -233,10 +537,10 @@ mod tests {
 
         let body = get_metrics_body(&state).await;
 
-        // panoptikon_devices_total should be 2.
+        // Both devices are counted as online.
         assert!(
-            body.contains("panoptikon_devices_total 2"),
-            "Expected devices_total 2, got:\n{body}"
+            body.contains("panoptikon_devices_total{status=\"online\"} 2"),
+            "Expected 2 online devices, got:\n{body}"
         );
     }
 
@@ -277,8 +581,12 @@ mod tests {
             "Expected offline=1, got:\n{body}"
         );
         assert!(
-            body.contains("panoptikon_devices_total 2"),
-            "Expected total=2, got:\n{body}"
+            body.contains("panoptikon_devices_total{status=\"online\"} 1"),
+            "Expected labeled online=1, got:\n{body}"
+        );
+        assert!(
+            body.contains("panoptikon_devices_total{status=\"offline\"} 1"),
+            "Expected labeled offline=1, got:\n{body}"
         );
     }
 }
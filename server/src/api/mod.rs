@@ -1,8 +1,9 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, SharedConfig};
 use crate::static_files::serve_static_asset;
 use crate::ws::hub::WsHub;
-use axum::http::{header, Method};
+use axum::http::{header, Method, StatusCode};
 use axum::{
+    extract::DefaultBodyLimit,
     middleware::{self},
     routing::{delete, get, patch, post, put},
     Router,
@@ -10,10 +11,14 @@ use axum::{
 use sqlx::SqlitePool;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 
 pub mod agents;
+pub mod alert_rules;
 pub mod alerts;
+pub mod api_keys;
 pub mod audit;
 pub mod auth;
 pub mod config_backups;
@@ -21,14 +26,19 @@ pub mod dashboard;
 pub mod devices;
 pub mod error;
 pub mod export;
+pub mod maintenance;
 pub mod metrics;
+pub mod rate_limit;
+pub mod router_profiles;
 pub mod scanner;
 pub mod search;
 pub mod settings;
 pub mod setup;
 pub mod topology;
 pub mod traffic;
+pub mod users;
 pub mod vyos;
+pub mod webhooks;
 
 pub use error::AppError;
 
@@ -36,10 +46,40 @@ pub use error::AppError;
 #[derive(Clone)]
 pub struct AppState {
     pub db: SqlitePool,
-    pub config: AppConfig,
+    /// Current configuration; use [`AppState::config`] to read it.
+    pub config: SharedConfig,
+    /// Config file the server was started with, re-read on SIGHUP.
+    pub config_path: Option<String>,
     pub ws_hub: Arc<WsHub>,
     pub rate_limiter: auth::LoginRateLimiter,
+    /// Per-endpoint limits for expensive operations (speed tests, scans).
+    pub endpoint_limiter: rate_limit::EndpointRateLimiter,
     pub last_speedtest: Arc<Mutex<Option<vyos::SpeedTestResult>>>,
+    /// Cached storage breakdown with the instant it was computed.
+    pub storage_cache: Arc<Mutex<Option<(std::time::Instant, settings::StorageResponse)>>>,
+    pub severity_overrides: alerts::SeverityOverrideCache,
+    pub firewall_hit_top_cache: vyos::FirewallHitTopCache,
+    pub dhcp_client_status_cache: vyos::DhcpClientStatusCache,
+    pub traceroute_limiter: vyos::TracerouteRateLimiter,
+    /// Cached firewall chain list with the instant it was fetched.
+    pub firewall_chains_cache:
+        Arc<Mutex<Option<(std::time::Instant, vyos::FirewallChainsResponse)>>>,
+    pub dhcp_pools_cache: vyos::DhcpPoolsCache,
+    /// Cached responses of the slow VyOS read endpoints.
+    pub vyos_cache: vyos::VyosCache,
+    /// Cached agent OS distribution with the instant it was computed.
+    pub os_distribution_cache:
+        Arc<Mutex<Option<(std::time::Instant, agents::OsDistributionResponse)>>>,
+    pub snmp_poll_cache: crate::snmp::SnmpPollCache,
+    /// Recent NetFlow bps samples per device for traffic spike detection.
+    pub traffic_stats: crate::netflow::spike::SharedTrafficStats,
+    /// ASN lookups for external IPs (24 h TTL).
+    pub asn_cache: crate::whois::AsnCache,
+    pub telegram_limiter: crate::notification::telegram::TelegramRateLimiter,
+    /// In-process Prometheus metrics (scan durations).
+    pub metrics: metrics::SharedMetrics,
+    /// Active VyOS router profile; `None` uses the router settings.
+    pub active_profile_id: router_profiles::ActiveRouterProfile,
 }
 
 impl AppState {
@@ -47,14 +87,38 @@ impl AppState {
     pub fn new(db: SqlitePool, config: AppConfig) -> Self {
         Self {
             db,
-            config,
+            config: Arc::new(std::sync::RwLock::new(config)),
+            config_path: None,
             ws_hub: WsHub::new(),
             rate_limiter: auth::LoginRateLimiter::new(),
+            endpoint_limiter: rate_limit::EndpointRateLimiter::new(),
             last_speedtest: Arc::new(Mutex::new(None)),
+            storage_cache: Arc::new(Mutex::new(None)),
+            severity_overrides: alerts::SeverityOverrideCache::new(),
+            firewall_hit_top_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            dhcp_client_status_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            traceroute_limiter: Arc::new(dashmap::DashMap::new()),
+            firewall_chains_cache: Arc::new(Mutex::new(None)),
+            dhcp_pools_cache: Arc::new(Mutex::new(None)),
+            vyos_cache: vyos::VyosCache::default(),
+            os_distribution_cache: Arc::new(Mutex::new(None)),
+            snmp_poll_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            traffic_stats: Arc::new(Mutex::new(Default::default())),
+            asn_cache: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            telegram_limiter: crate::notification::telegram::TelegramRateLimiter::new(),
+            metrics: Arc::new(Mutex::new(metrics::MetricsState::default())),
+            active_profile_id: Arc::new(tokio::sync::RwLock::new(None)),
         }
     }
 }
 
+impl AppState {
+    /// Read the current configuration. Don't hold the guard across `.await`.
+    pub fn config(&self) -> std::sync::RwLockReadGuard<'_, AppConfig> {
+        crate::config::read(&self.config)
+    }
+}
+
 /// Build the main application router with all API routes.
 pub fn router(state: AppState) -> Router {
     let cors = CorsLayer::new()
@@ -81,7 +145,11 @@ pub fn router(state: AppState) -> Router {
     // Agent WebSocket + install script — authenticated via API key, not session cookie.
     let agent_ws = Router::new()
         .route("/agent/ws", get(agents::ws_handler))
-        .route("/agent/install/:platform", get(agents::install_script));
+        .route("/agent/install/:platform", get(agents::install_script))
+        .route(
+            "/agent/install/:platform/systemd-unit",
+            get(agents::systemd_unit),
+        );
 
     // Protected routes — each method registered in its own .route() call to avoid
     // Axum 0.7 MethodRouter chaining issue where DELETE/PATCH can be dropped
@@ -90,14 +158,52 @@ pub fn router(state: AppState) -> Router {
         // Devices
         .route("/devices", get(devices::list))
         .route("/devices", post(devices::create))
+        .route("/devices/bulk", post(devices::bulk))
+        .route(
+            "/devices/import",
+            post(devices::import).layer(DefaultBodyLimit::max(devices::MAX_IMPORT_BODY_BYTES)),
+        )
+        .route("/devices/import-history", get(devices::import_history))
         .route("/devices/:id", get(devices::get_one))
         .route("/devices/:id", patch(devices::update))
         .route("/devices/:id/events", get(devices::events))
         .route("/devices/:id/uptime", get(devices::uptime))
+        .route("/devices/:id/availability", get(devices::availability))
+        .route("/devices/:id/sla-report", get(devices::sla_report))
         .route("/devices/:id/wake", post(devices::wake))
         .route("/devices/:id/scan", get(devices::get_scan))
-        .route("/devices/:id/scan", post(devices::trigger_scan))
+        .route(
+            "/devices/:id/scan",
+            post(devices::trigger_scan).route_layer(middleware::from_fn_with_state(
+                (state.clone(), rate_limit::DEVICE_SCAN_LIMIT),
+                rate_limit::rate_limit,
+            )),
+        )
+        .route(
+            "/devices/:id/scan/schedule",
+            get(devices::get_scan_schedule),
+        )
+        .route(
+            "/devices/:id/scan/schedule",
+            post(devices::set_scan_schedule),
+        )
+        .route(
+            "/devices/:id/scan/schedule",
+            delete(devices::delete_scan_schedule),
+        )
+        .route("/devices/:id/certificates", get(devices::certificates))
         .route("/devices/:id/enrichment", patch(devices::update_enrichment))
+        .route("/devices/:id/security-score", get(devices::security_score))
+        .route("/devices/:id/os", get(devices::os_guesses))
+        .route("/devices/:id/snmp", post(devices::configure_snmp))
+        .route("/devices/:id/snmp/poll", get(devices::poll_snmp))
+        .route("/devices/:id/tags", get(devices::list_tags))
+        .route("/devices/:id/tags", post(devices::create_tag))
+        .route("/devices/:id/tags/:tag", delete(devices::delete_tag))
+        .route("/devices/:id/notes", get(devices::list_notes))
+        .route("/devices/:id/notes", post(devices::create_note))
+        .route("/devices/:id/notes/:note_id", patch(devices::update_note))
+        .route("/devices/:id/notes/:note_id", delete(devices::delete_note))
         // Agents
         .route("/agents", get(agents::list))
         .route("/agents", post(agents::register))
@@ -105,7 +211,14 @@ pub fn router(state: AppState) -> Router {
         .route("/agents/:id", patch(agents::update))
         .route("/agents/:id", delete(agents::delete))
         .route("/agents/:id/reports", get(agents::list_reports))
+        .route("/agents/:id/uptime-history", get(agents::uptime_history))
+        .route(
+            "/agents/:id/installed-packages",
+            get(agents::list_installed_packages),
+        )
         .route("/agents/bulk-delete", post(agents::bulk_delete))
+        .route("/agents/os-distribution", get(agents::os_distribution))
+        .route("/agents/outdated", get(agents::outdated))
         // Dashboard
         .route("/dashboard/stats", get(dashboard::stats))
         .route("/dashboard/top-devices", get(dashboard::top_devices))
@@ -117,15 +230,46 @@ pub fn router(state: AppState) -> Router {
         .route("/alerts/:id", delete(alerts::delete_one))
         .route("/alerts/:id/read", post(alerts::mark_read))
         .route("/alerts/:id/acknowledge", post(alerts::acknowledge))
+        .route("/alert-rules", get(alert_rules::list))
+        .route("/alert-rules", post(alert_rules::create))
+        .route("/alert-rules/:id", patch(alert_rules::update))
+        .route("/alert-rules/:id", delete(alert_rules::delete))
+        .route("/maintenance", get(maintenance::list))
+        .route("/maintenance", post(maintenance::create))
+        .route("/maintenance/:id", patch(maintenance::update))
+        .route("/maintenance/:id", delete(maintenance::delete))
         // Device mute
         .route("/devices/:id/mute", post(alerts::mute_device))
         // Settings
         .route("/settings", get(settings::get_settings))
         .route("/settings", patch(settings::update_settings))
         .route("/settings/test-webhook", post(settings::test_webhook))
+        // Webhooks
+        .route("/webhooks", get(webhooks::list))
+        .route("/webhooks", post(webhooks::create))
+        .route("/webhooks/:id", patch(webhooks::update))
+        .route("/webhooks/:id", delete(webhooks::delete))
+        .route("/webhooks/:id/test", post(webhooks::test))
+        .route("/webhooks/:id/deliveries", get(webhooks::deliveries))
+        .route("/settings/test-email", post(settings::test_email))
+        .route("/settings/test-telegram", post(settings::test_telegram))
+        .route("/settings/test-ldap", post(settings::test_ldap))
         .route("/settings/netflow-status", get(settings::netflow_status))
         .route("/settings/db-size", get(settings::db_size))
+        .route("/settings/storage", get(settings::storage))
+        .route(
+            "/settings/alert-severities",
+            get(settings::get_alert_severities),
+        )
+        .route(
+            "/settings/alert-severities",
+            patch(settings::update_alert_severities),
+        )
         .route("/settings/vacuum", post(settings::vacuum))
+        .route("/settings/backup", post(settings::backup))
+        .route("/settings/backup/info", get(settings::backup_info))
+        .route("/settings/oui-db-info", get(settings::oui_db_info))
+        .route("/settings/update-oui-db", post(settings::update_oui_db))
         // VyOS router proxy
         .route("/vyos/status", get(vyos::status))
         .route("/vyos/interfaces", get(vyos::interfaces))
@@ -136,13 +280,67 @@ pub fn router(state: AppState) -> Router {
             "/vyos/routes/static/:destination",
             delete(vyos::delete_static_route),
         )
+        .route("/vyos/bgp/summary", get(vyos::bgp_summary))
+        .route("/vyos/bgp/rib/summary", get(vyos::rib_summary))
+        .route("/vyos/ospf/neighbors", get(vyos::ospf_neighbors))
+        .route("/vyos/ospf/routes", get(vyos::ospf_routes))
+        .route("/vyos/ipsec/tunnels", get(vyos::ipsec_tunnels))
+        .route("/vyos/ipsec/config", get(vyos::ipsec_config))
+        .route("/vyos/nat/:kind", get(vyos::nat_rules))
+        .route("/vyos/nat/:kind", post(vyos::create_nat_rule))
+        .route("/vyos/nat/:kind/:number", delete(vyos::delete_nat_rule))
+        .route("/vyos/dns/forwarding", get(vyos::dns_forwarding))
+        .route(
+            "/vyos/dns/forwarding/nameservers",
+            post(vyos::add_dns_nameserver),
+        )
+        .route(
+            "/vyos/dns/forwarding/nameservers/:ip",
+            delete(vyos::delete_dns_nameserver),
+        )
+        .route(
+            "/vyos/dns/forwarding/domain-override",
+            post(vyos::add_dns_domain_override),
+        )
         .route("/vyos/dhcp-leases", get(vyos::dhcp_leases))
+        .route(
+            "/vyos/dhcp-leases/:ip/make-static",
+            post(vyos::make_lease_static),
+        )
+        .route(
+            "/vyos/dhcp/leases/expiring",
+            get(vyos::dhcp_leases_expiring),
+        )
         .route("/vyos/firewall", get(vyos::firewall))
+        .route("/vyos/firewall/chains", get(vyos::firewall_chains))
+        .route("/vyos/firewall/hit-topN", get(vyos::firewall_hit_top))
+        .route("/vyos/cache/invalidate", post(vyos::invalidate_cache))
         // VyOS write operations
         .route(
             "/vyos/interfaces/:name/toggle",
             post(vyos::interface_toggle),
         )
+        .route(
+            "/vyos/interfaces/:name/addresses",
+            get(vyos::interface_addresses),
+        )
+        .route(
+            "/vyos/interfaces/:name/dhcp-client-status",
+            get(vyos::interface_dhcp_client_status),
+        )
+        .route(
+            "/vyos/interfaces/:name/trace-route",
+            get(vyos::interface_trace_route),
+        )
+        .route(
+            "/vyos/interfaces/:name/ip-alias",
+            post(vyos::add_interface_ip_alias),
+        )
+        .route(
+            "/vyos/interfaces/:name/ip-alias/:address",
+            delete(vyos::remove_interface_ip_alias),
+        )
+        .route("/vyos/dhcp/pools", get(vyos::dhcp_pools))
         .route(
             "/vyos/dhcp/static-mappings",
             get(vyos::dhcp_static_mappings),
@@ -172,8 +370,36 @@ pub fn router(state: AppState) -> Router {
             "/vyos/firewall/:chain/rules/:number/enabled",
             patch(vyos::toggle_firewall_rule),
         )
+        .route(
+            "/vyos/firewall/:chain/rules/:number/move",
+            post(vyos::move_firewall_rule),
+        )
+        .route(
+            "/vyos/firewall/:chain/rules/:number/clone",
+            post(vyos::clone_firewall_rule),
+        )
+        .route(
+            "/vyos/firewall/:chain/rules/:number/stats",
+            get(vyos::firewall_rule_stats),
+        )
+        .route(
+            "/vyos/firewall/:chain/stats",
+            get(vyos::firewall_chain_stats),
+        )
         // Firewall groups
         .route("/vyos/firewall/groups", get(vyos::firewall_groups))
+        .route(
+            "/vyos/firewall/groups/address-group/:name/devices",
+            get(vyos::address_group_devices),
+        )
+        .route(
+            "/vyos/firewall/groups/network-group/:name/devices",
+            get(vyos::network_group_devices),
+        )
+        .route(
+            "/vyos/firewall/groups/port-group/:name/devices",
+            get(vyos::port_group_devices),
+        )
         .route(
             "/vyos/firewall/groups/address-group",
             post(vyos::create_address_group),
@@ -226,12 +452,39 @@ pub fn router(state: AppState) -> Router {
         .route("/topology/positions", get(topology::get_positions))
         .route("/topology/positions", put(topology::save_positions))
         .route("/topology/positions", delete(topology::delete_positions))
+        .route("/topology/auto-layout", post(topology::auto_layout))
+        .route("/topology/export.svg", get(topology::export))
         // Scanner
-        .route("/scanner/trigger", post(scanner::trigger))
+        .route(
+            "/scanner/trigger",
+            post(scanner::trigger).route_layer(middleware::from_fn_with_state(
+                (state.clone(), rate_limit::SCANNER_TRIGGER_LIMIT),
+                rate_limit::rate_limit,
+            )),
+        )
+        .route("/scanner/local-subnets", get(scanner::local_subnets))
+        // Config archive to git
+        .route("/vyos/system/config-archive", post(vyos::config_archive))
+        .route(
+            "/vyos/system/config-archive/schedule",
+            post(vyos::config_archive_schedule),
+        )
         // Speed test
-        .route("/router/speedtest", post(vyos::speedtest))
+        .route(
+            "/router/speedtest",
+            post(vyos::speedtest).route_layer(middleware::from_fn_with_state(
+                (state.clone(), rate_limit::SPEEDTEST_LIMIT),
+                rate_limit::rate_limit,
+            )),
+        )
+        .route("/router/speedtest/history", get(vyos::speedtest_history))
+        .route("/router/speedtest/latest", get(vyos::speedtest_latest))
         // Traffic
         .route("/traffic/history", get(traffic::history))
+        .route("/traffic/device/:id", get(traffic::device_totals))
+        .route("/traffic/top-talkers", get(traffic::top_talkers))
+        .route("/traffic/enrich", get(traffic::enrich))
+        .route("/traffic/conversations", get(traffic::conversations))
         // Config backups
         .route("/config-backups", get(config_backups::list))
         .route("/config-backups", post(config_backups::create))
@@ -242,13 +495,43 @@ pub fn router(state: AppState) -> Router {
         // Audit log
         .route("/audit-log", get(audit::list))
         .route("/audit-log/actions", get(audit::actions))
+        .route("/audit-log/syslog-status", get(audit::syslog_status))
         // Search
         .route("/search", get(search::search))
         // Export
         .route("/devices/export", get(export::devices_export))
         .route("/traffic/export", get(export::traffic_export))
+        .route("/export/influxdb", get(export::influxdb_export))
+        // Accounts and roles
+        .route("/auth/me", get(auth::me))
+        .route("/users", get(users::list))
+        .route("/users", post(users::create))
+        // Router profiles
+        .route("/router-profiles", get(router_profiles::list))
+        .route("/router-profiles", post(router_profiles::create))
+        .route("/router-profiles/:id", patch(router_profiles::update))
+        .route("/router-profiles/:id", delete(router_profiles::delete))
+        .route(
+            "/router-profiles/:id/activate",
+            post(router_profiles::activate),
+        )
+        // API keys for headless integrations
+        .route("/api-keys", get(api_keys::list))
+        .route("/api-keys", post(api_keys::create))
+        .route("/api-keys/:id", delete(api_keys::delete))
+        // Two-factor authentication
+        .route("/auth/totp/setup", post(auth::totp_setup))
+        .route("/auth/totp/verify", post(auth::totp_verify))
+        .route("/auth/totp/disable", post(auth::totp_disable))
         // WebSocket for UI live updates
         .route("/ws", get(agents::ui_ws_handler))
+        // Viewer sessions are read-only; runs inside auth_middleware.
+        .route_layer(middleware::from_fn(auth::require_admin))
+        // Record every write request (including rejected ones) with its actor.
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            audit::audit_middleware,
+        ))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth::auth_middleware,
@@ -264,10 +547,24 @@ pub fn router(state: AppState) -> Router {
             public_routes.merge(agent_ws).merge(protected_routes),
         )
         .fallback(serve_static_asset)
+        .layer(middleware::from_fn(error::problem_instance))
+        .layer(compression_layer())
         .layer(cors)
         .with_state(state)
 }
 
+/// Compress responses (gzip, deflate or brotli, per `Accept-Encoding`),
+/// except WebSocket handshakes: after `101 Switching Protocols` the
+/// connection belongs to the socket, not an HTTP body.
+fn compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(
+        |status: StatusCode,
+         _: axum::http::Version,
+         _: &axum::http::HeaderMap,
+         _: &axum::http::Extensions| { status != StatusCode::SWITCHING_PROTOCOLS },
+    ))
+}
+
 /// Simple health check endpoint.
 async fn health() -> &'static str {
     "ok"
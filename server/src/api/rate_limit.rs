@@ -0,0 +1,227 @@
+//! Per-endpoint rate limiting for expensive operations (speed tests, scans).
+//!
+//! Each limited route gets a [`RateLimitLayer`] describing its budget and how
+//! to key requests (client IP, device id, ...). Counters live in
+//! [`EndpointRateLimiter`] on `AppState` and are pruned by the hourly
+//! maintenance task.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use dashmap::DashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{auth, AppError, AppState};
+
+/// Fixed-window request counters keyed by `"<scope>:<key>"`.
+///
+/// Each entry holds the request count and the instant its window resets.
+#[derive(Clone)]
+pub struct EndpointRateLimiter {
+    counters: Arc<DashMap<String, (u32, Instant)>>,
+}
+
+impl EndpointRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            counters: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Count a request against `key`, starting a new window if the previous
+    /// one has ended.
+    ///
+    /// Returns `Some(retry_after_secs)` if the budget is already spent.
+    pub fn check(&self, key: String, max_requests: u32, window: Duration) -> Option<u64> {
+        let now = Instant::now();
+        let mut entry = self.counters.entry(key).or_insert((0, now + window));
+        if entry.1 <= now {
+            *entry = (0, now + window);
+        }
+
+        if entry.0 >= max_requests {
+            return Some(entry.1.saturating_duration_since(now).as_secs().max(1));
+        }
+
+        entry.0 += 1;
+        None
+    }
+
+    /// Remove all entries whose window has ended.
+    pub fn cleanup_stale(&self) {
+        let now = Instant::now();
+        self.counters.retain(|_, (_, resets_at)| *resets_at > now);
+    }
+}
+
+impl Default for EndpointRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Client IP resolved by [`rate_limit`] (honouring trusted proxies) and
+/// stored in request extensions for key functions.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+/// Rate limit applied to a single route.
+#[derive(Clone, Copy)]
+pub struct RateLimitLayer {
+    /// Namespace for counter keys so routes don't share budgets.
+    pub scope: &'static str,
+    pub max_requests: u32,
+    pub window_secs: u64,
+    pub key_fn: fn(&Request) -> String,
+}
+
+impl RateLimitLayer {
+    pub const fn new(
+        scope: &'static str,
+        max_requests: u32,
+        window_secs: u64,
+        key_fn: fn(&Request) -> String,
+    ) -> Self {
+        Self {
+            scope,
+            max_requests,
+            window_secs,
+            key_fn,
+        }
+    }
+}
+
+/// `POST /router/speedtest`: 3 runs per 10 minutes per client IP.
+pub const SPEEDTEST_LIMIT: RateLimitLayer =
+    RateLimitLayer::new("speedtest", 3, 600, key_by_client_ip);
+
+/// `POST /scanner/trigger`: 6 scans per minute per client IP.
+pub const SCANNER_TRIGGER_LIMIT: RateLimitLayer =
+    RateLimitLayer::new("scanner_trigger", 6, 60, key_by_client_ip);
+
+/// `POST /devices/:id/scan`: one nmap scan per minute per device.
+pub const DEVICE_SCAN_LIMIT: RateLimitLayer =
+    RateLimitLayer::new("device_scan", 1, 60, key_by_device_id);
+
+/// Key on the client IP resolved by the middleware.
+pub fn key_by_client_ip(req: &Request) -> String {
+    req.extensions()
+        .get::<ClientIp>()
+        .map(|ip| ip.0.to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Key on the `:id` segment following `devices` in the request path.
+pub fn key_by_device_id(req: &Request) -> String {
+    device_id_from_path(req.uri().path())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The non-empty segment following `devices` in a path, if any.
+pub fn device_id_from_path(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments
+        .find(|s| *s == "devices")
+        .and_then(|_| segments.next())
+        .filter(|s| !s.is_empty())
+}
+
+/// Middleware enforcing a [`RateLimitLayer`]; responds 429 with
+/// `Retry-After` once the budget for the request's key is spent.
+pub async fn rate_limit(
+    State((state, limit)): State<(AppState, RateLimitLayer)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>().copied() {
+        let ip = auth::extract_client_ip(req.headers(), addr, &state.config().auth.trusted_proxies);
+        req.extensions_mut().insert(ClientIp(ip));
+    }
+
+    let key = format!("{}:{}", limit.scope, (limit.key_fn)(&req));
+    let window = Duration::from_secs(limit.window_secs);
+    if let Some(retry_after) = state
+        .endpoint_limiter
+        .check(key, limit.max_requests, window)
+    {
+        tracing::warn!(
+            scope = limit.scope,
+            retry_after,
+            "Endpoint rate limit exceeded"
+        );
+        return AppError::RateLimited(
+            format!("Too many requests. Try again in {retry_after} seconds."),
+            retry_after,
+        )
+        .into_response();
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    #[test]
+    fn test_check_limits_within_window() {
+        let limiter = EndpointRateLimiter::new();
+        let window = Duration::from_secs(60);
+
+        assert!(limiter.check("a".to_string(), 2, window).is_none());
+        assert!(limiter.check("a".to_string(), 2, window).is_none());
+        let retry_after = limiter.check("a".to_string(), 2, window).unwrap();
+        assert!((1..=60).contains(&retry_after));
+
+        // Other keys have their own budget.
+        assert!(limiter.check("b".to_string(), 2, window).is_none());
+    }
+
+    #[test]
+    fn test_check_resets_after_window() {
+        let limiter = EndpointRateLimiter::new();
+
+        assert!(limiter.check("a".to_string(), 1, Duration::ZERO).is_none());
+        // A zero-length window has already ended, so the next call starts fresh.
+        assert!(limiter.check("a".to_string(), 1, Duration::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_cleanup_stale() {
+        let limiter = EndpointRateLimiter::new();
+        limiter.check("expired".to_string(), 1, Duration::ZERO);
+        limiter.check("active".to_string(), 1, Duration::from_secs(60));
+
+        limiter.cleanup_stale();
+
+        assert!(!limiter.counters.contains_key("expired"));
+        assert!(limiter.counters.contains_key("active"));
+    }
+
+    #[test]
+    fn test_key_by_device_id() {
+        let req = Request::builder()
+            .uri("/devices/abc-123/scan")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(key_by_device_id(&req), "abc-123");
+        assert_eq!(device_id_from_path("/devices"), None);
+        assert_eq!(device_id_from_path("/devices/abc/notes"), Some("abc"));
+    }
+
+    #[test]
+    fn test_key_by_client_ip() {
+        let mut req = Request::builder().uri("/").body(Body::empty()).unwrap();
+        assert_eq!(key_by_client_ip(&req), "unknown");
+
+        req.extensions_mut()
+            .insert(ClientIp("192.168.1.5".parse().unwrap()));
+        assert_eq!(key_by_client_ip(&req), "192.168.1.5");
+    }
+}
@@ -0,0 +1,476 @@
+//! VyOS router profiles.
+//!
+//! Each profile holds the URL and API key of one router; the API key is
+//! encrypted at rest with [`crate::crypto`]. Activating a profile points
+//! every VyOS endpoint and background task at that router without a
+//! restart. While no profile is active, the `vyos_url` / `vyos_api_key`
+//! settings (or the config file) are used as before.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{FromRow, SqlitePool};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::audit::Actor;
+use super::{audit, AppError, AppState};
+use crate::crypto;
+
+/// Id of the active router profile, shared by handlers and background tasks.
+pub type ActiveRouterProfile = Arc<RwLock<Option<String>>>;
+
+/// Maximum length of a profile name.
+const MAX_NAME_LEN: usize = 64;
+
+/// A router profile as returned by the API (the API key is never returned).
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct RouterProfile {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+/// Request body for `POST /api/v1/router-profiles`.
+#[derive(Debug, Deserialize)]
+pub struct CreateRouterProfile {
+    pub name: String,
+    pub url: String,
+    pub api_key: String,
+}
+
+/// Request body for `PATCH /api/v1/router-profiles/:id`.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateRouterProfile {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+fn validate_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "name must be 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    Ok(name)
+}
+
+fn validate_url(url: &str) -> Result<&str, AppError> {
+    let url = url.trim().trim_end_matches('/');
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or("");
+    if host.is_empty() {
+        return Err(AppError::Validation(
+            "url must start with http:// or https:// and include a host".to_string(),
+        ));
+    }
+    Ok(url)
+}
+
+fn validate_api_key(api_key: &str) -> Result<&str, AppError> {
+    let api_key = api_key.trim();
+    if api_key.is_empty() {
+        return Err(AppError::Validation(
+            "api_key must not be empty".to_string(),
+        ));
+    }
+    Ok(api_key)
+}
+
+/// Map a unique-constraint violation on `name` to 409.
+fn name_conflict(e: sqlx::Error, name: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("A router profile named '{name}' already exists"))
+        }
+        e => e.into(),
+    }
+}
+
+async fn load_profile(db: &SqlitePool, id: &str) -> Result<RouterProfile, AppError> {
+    sqlx::query_as("SELECT id, name, url, is_active, created_at FROM router_profiles WHERE id = ?")
+        .bind(id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::ResourceNotFound("router-profile", "Router profile not found".to_string())
+        })
+}
+
+/// Id of the profile marked active in the database. Call at startup to
+/// seed [`ActiveRouterProfile`].
+pub async fn load_active_profile_id(db: &SqlitePool) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM router_profiles WHERE is_active = 1")
+        .fetch_optional(db)
+        .await
+}
+
+/// URL and decrypted API key of a profile, if it exists and its key can be
+/// decrypted with the current server key.
+pub async fn profile_credentials(db: &SqlitePool, id: &str) -> Option<(String, String)> {
+    let (url, sealed): (String, String) =
+        sqlx::query_as("SELECT url, api_key_encrypted FROM router_profiles WHERE id = ?")
+            .bind(id)
+            .fetch_optional(db)
+            .await
+            .ok()??;
+    match crypto::decrypt(&sealed) {
+        Some(key) => Some((url, key)),
+        None => {
+            warn!(profile_id = id, "Cannot decrypt router profile API key");
+            None
+        }
+    }
+}
+
+/// Drop cached router data so the next request queries the newly active router.
+async fn clear_router_caches(state: &AppState) {
+    state.firewall_hit_top_cache.lock().await.clear();
+    state.dhcp_client_status_cache.lock().await.clear();
+    *state.firewall_chains_cache.lock().await = None;
+    *state.dhcp_pools_cache.lock().await = None;
+    state.vyos_cache.clear();
+}
+
+/// GET /api/v1/router-profiles — list profiles.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<RouterProfile>>, AppError> {
+    let profiles: Vec<RouterProfile> = sqlx::query_as(
+        "SELECT id, name, url, is_active, created_at FROM router_profiles ORDER BY name",
+    )
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(profiles))
+}
+
+/// POST /api/v1/router-profiles — create an (inactive) profile.
+pub async fn create(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<CreateRouterProfile>,
+) -> Result<(StatusCode, Json<RouterProfile>), AppError> {
+    let name = validate_name(&body.name)?;
+    let url = validate_url(&body.url)?;
+    let api_key = validate_api_key(&body.api_key)?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO router_profiles (id, name, url, api_key_encrypted) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(url)
+    .bind(crypto::encrypt(api_key))
+    .execute(&state.db)
+    .await
+    .map_err(|e| name_conflict(e, name))?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "router_profile_create",
+        &format!("Created router profile '{name}' ({url})"),
+        &[],
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(load_profile(&state.db, &id).await?),
+    ))
+}
+
+/// PATCH /api/v1/router-profiles/:id — rename a profile or change its
+/// URL / API key.
+pub async fn update(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateRouterProfile>,
+) -> Result<Json<RouterProfile>, AppError> {
+    let existing = load_profile(&state.db, &id).await?;
+    let name = body.name.as_deref().map(validate_name).transpose()?;
+    let url = body.url.as_deref().map(validate_url).transpose()?;
+    let api_key = body.api_key.as_deref().map(validate_api_key).transpose()?;
+
+    sqlx::query(
+        "UPDATE router_profiles SET name = COALESCE(?, name), url = COALESCE(?, url), \
+         api_key_encrypted = COALESCE(?, api_key_encrypted) WHERE id = ?",
+    )
+    .bind(name)
+    .bind(url)
+    .bind(api_key.map(crypto::encrypt))
+    .bind(&id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| name_conflict(e, name.unwrap_or(&existing.name)))?;
+
+    if existing.is_active && (url.is_some() || api_key.is_some()) {
+        clear_router_caches(&state).await;
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "router_profile_update",
+        &format!(
+            "Updated router profile '{}'",
+            name.unwrap_or(&existing.name)
+        ),
+        &[],
+    )
+    .await;
+
+    Ok(Json(load_profile(&state.db, &id).await?))
+}
+
+/// DELETE /api/v1/router-profiles/:id — delete a profile. Deleting the
+/// active profile falls back to the router settings.
+pub async fn delete(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let existing = load_profile(&state.db, &id).await?;
+
+    sqlx::query("DELETE FROM router_profiles WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+
+    if existing.is_active {
+        *state.active_profile_id.write().await = None;
+        clear_router_caches(&state).await;
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "router_profile_delete",
+        &format!("Deleted router profile '{}'", existing.name),
+        &[],
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/router-profiles/:id/activate — switch all VyOS requests to
+/// this profile's router.
+pub async fn activate(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+) -> Result<Json<RouterProfile>, AppError> {
+    let existing = load_profile(&state.db, &id).await?;
+
+    let mut tx = state.db.begin().await?;
+    sqlx::query("UPDATE router_profiles SET is_active = 0 WHERE is_active = 1")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE router_profiles SET is_active = 1 WHERE id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    *state.active_profile_id.write().await = Some(id.clone());
+    clear_router_caches(&state).await;
+    info!(profile = %existing.name, url = %existing.url, "Router profile activated");
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "router_profile_activate",
+        &format!(
+            "Activated router profile '{}' ({})",
+            existing.name, existing.url
+        ),
+        &[],
+    )
+    .await;
+    state.ws_hub.broadcast(
+        "router_profile_activated",
+        json!({"id": &id, "name": &existing.name}),
+    );
+
+    Ok(Json(load_profile(&state.db, &id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::vyos::get_vyos_client_from_db;
+
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    fn create_body(name: &str, url: &str) -> Json<CreateRouterProfile> {
+        Json(CreateRouterProfile {
+            name: name.to_string(),
+            url: url.to_string(),
+            api_key: format!("{name}-key"),
+        })
+    }
+
+    async fn create_profile(state: &AppState, name: &str, url: &str) -> RouterProfile {
+        let (status, Json(profile)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body(name, url),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        profile
+    }
+
+    async fn active_url(state: &AppState) -> Option<String> {
+        let config = state.config().clone();
+        get_vyos_client_from_db(&state.db, &config, &state.active_profile_id)
+            .await
+            .map(|client| client.base_url().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_profile_crud_and_activation() {
+        let state = test_state().await;
+        assert_eq!(active_url(&state).await, None);
+
+        let home = create_profile(&state, "home", "https://192.168.1.1/").await;
+        let office = create_profile(&state, "office", "https://10.0.0.1").await;
+        assert_eq!(home.url, "https://192.168.1.1");
+        assert!(!home.is_active);
+
+        // The key is stored encrypted.
+        let sealed: String =
+            sqlx::query_scalar("SELECT api_key_encrypted FROM router_profiles WHERE id = ?")
+                .bind(&home.id)
+                .fetch_one(&state.db)
+                .await
+                .unwrap();
+        assert!(!sealed.contains("home-key"));
+        assert_eq!(
+            profile_credentials(&state.db, &home.id).await,
+            Some(("https://192.168.1.1".to_string(), "home-key".to_string()))
+        );
+
+        // Inactive profiles don't affect the client.
+        assert_eq!(active_url(&state).await, None);
+
+        let Json(activated) = activate(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(home.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(activated.is_active);
+        assert_eq!(
+            active_url(&state).await.as_deref(),
+            Some("https://192.168.1.1")
+        );
+
+        let Json(activated) = activate(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(office.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(activated.name, "office");
+        assert_eq!(
+            active_url(&state).await.as_deref(),
+            Some("https://10.0.0.1")
+        );
+        assert_eq!(
+            load_active_profile_id(&state.db).await.unwrap(),
+            Some(office.id.clone())
+        );
+        let Json(profiles) = list(State(state.clone())).await.unwrap();
+        assert_eq!(
+            profiles
+                .iter()
+                .map(|p| (p.name.as_str(), p.is_active))
+                .collect::<Vec<_>>(),
+            vec![("home", false), ("office", true)]
+        );
+
+        let Json(updated) = update(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(office.id.clone()),
+            Json(UpdateRouterProfile {
+                url: Some("https://10.0.0.2".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.name, "office");
+        assert_eq!(
+            active_url(&state).await.as_deref(),
+            Some("https://10.0.0.2")
+        );
+
+        // Deleting the active profile falls back to the settings (none here).
+        let status = delete(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(office.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(active_url(&state).await, None);
+        assert!(activate(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(office.id)
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_validation_and_conflict() {
+        let state = test_state().await;
+        create_profile(&state, "home", "https://192.168.1.1").await;
+
+        let err = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body("home", "https://10.0.0.1"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        for (name, url) in [
+            ("", "https://10.0.0.1"),
+            ("lab", "10.0.0.1"),
+            ("lab", "https://"),
+        ] {
+            let err = create(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                create_body(name, url),
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{name} {url}");
+        }
+    }
+}
@@ -1,36 +1,43 @@
 use axum::{extract::State, http::StatusCode, Json};
 
-use super::AppState;
+use super::{AppError, AppState};
+use crate::scanner::local_subnets::{self, LocalSubnet};
 
 /// POST /api/v1/scanner/trigger — trigger an immediate ARP scan.
-pub async fn trigger(
-    State(state): State<AppState>,
-) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
-    let subnets = &state.config.scanner.subnets;
-    let arp_settle = state.config.scanner.arp_settle_millis;
-    let grace = state.config.scanner.offline_grace_seconds;
+pub async fn trigger(State(state): State<AppState>) -> Result<StatusCode, AppError> {
+    let mut scanner_config = state.config().scanner.clone();
+    local_subnets::fill_if_empty(&mut scanner_config);
+    let grace = scanner_config.offline_grace_seconds;
 
-    let discovered = crate::scanner::scan_subnets(subnets, arp_settle)
+    let discovered = crate::scanner::scan_subnets(&scanner_config)
         .await
         .map_err(|e| {
             tracing::error!("Manual scan failed: {e}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Scan failed: {e}")})),
-            )
+            AppError::Internal(format!("Scan failed: {e}"))
         })?;
 
     tracing::info!(count = discovered.len(), "Manual ARP scan completed");
 
-    crate::scanner::process_scan_results(&state.db, &discovered, grace, &state.ws_hub)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to process manual scan results: {e}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": format!("Failed to process results: {e}")})),
-            )
-        })?;
+    crate::scanner::process_scan_results(
+        &state.db,
+        &discovered,
+        grace,
+        scanner_config.gateway_ip.as_deref(),
+        &state.ws_hub,
+        &state.severity_overrides,
+        &state.telegram_limiter,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to process manual scan results: {e}");
+        AppError::Internal(format!("Failed to process results: {e}"))
+    })?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// GET /api/v1/scanner/local-subnets — subnets the scanner would auto-detect
+/// when `[scanner] subnets` is empty.
+pub async fn local_subnets() -> Json<Vec<LocalSubnet>> {
+    Json(local_subnets::detect())
+}
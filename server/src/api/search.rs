@@ -1,12 +1,12 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
+use std::collections::HashSet;
 
-use super::AppState;
+use super::{AppError, AppState};
 
 /// Query parameters for the global search endpoint.
 #[derive(Debug, Deserialize)]
@@ -24,8 +24,24 @@ pub struct SearchDevice {
     pub mac_address: String,
     pub vendor: Option<String>,
     pub is_online: bool,
+    /// `exact` for substring matches, `fuzzy` for trigram-similar ones.
+    pub match_type: MatchType,
 }
 
+/// How a device search result matched the query.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchType {
+    Exact,
+    Fuzzy,
+}
+
+/// Maximum results per entity type.
+const SEARCH_LIMIT: usize = 5;
+
+/// Fuzzy candidates fetched from the trigram index before scoring.
+const FUZZY_CANDIDATES: i64 = 100;
+
 /// An agent in search results.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SearchAgent {
@@ -62,11 +78,24 @@ impl SearchResponse {
     }
 }
 
+/// Build an FTS5 query matching every word of `term` as a prefix.
+///
+/// Words are reduced to alphanumerics and quoted so user input can never be
+/// parsed as FTS5 syntax. Returns `None` when nothing searchable is left.
+fn notes_fts_query(term: &str) -> Option<String> {
+    let words: Vec<String> = term
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{w}\"*"))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
 /// GET /api/v1/search?q=<term> — search across devices, agents, and alerts.
 pub async fn search(
     State(state): State<AppState>,
     Query(params): Query<SearchQuery>,
-) -> Result<Json<SearchResponse>, StatusCode> {
+) -> Result<Json<SearchResponse>, AppError> {
     let q = params.q.unwrap_or_default();
 
     // Return empty results for short queries
@@ -76,37 +105,14 @@ pub async fn search(
 
     let like_term = format!("%{q}%");
 
-    // Search devices by IP (via device_ips), hostname, MAC, or vendor
-    let device_rows = sqlx::query(
-        r#"SELECT DISTINCT d.id, d.hostname, d.mac, d.vendor, d.is_online,
-                  (SELECT di.ip FROM device_ips di WHERE di.device_id = d.id AND di.is_current = 1 LIMIT 1) AS ip_address
-           FROM devices d
-           LEFT JOIN device_ips di ON di.device_id = d.id AND di.is_current = 1
-           WHERE di.ip LIKE ?1
-              OR d.hostname LIKE ?1
-              OR d.mac LIKE ?1
-              OR d.vendor LIKE ?1
-           LIMIT 5"#,
-    )
-    .bind(&like_term)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Search devices failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let devices: Vec<SearchDevice> = device_rows
-        .into_iter()
-        .map(|row| SearchDevice {
-            id: row.try_get("id").unwrap_or_default(),
-            ip_address: row.try_get("ip_address").unwrap_or(None),
-            hostname: row.try_get("hostname").unwrap_or(None),
-            mac_address: row.try_get("mac").unwrap_or_default(),
-            vendor: row.try_get("vendor").unwrap_or(None),
-            is_online: row.try_get::<i32, _>("is_online").unwrap_or(0) != 0,
-        })
-        .collect();
+    // Exact matches first, then fill up with trigram-similar name/hostname/vendor matches.
+    let mut devices = search_devices(&state.db, &q).await?;
+    if devices.len() < SEARCH_LIMIT {
+        let exclude: HashSet<String> = devices.iter().map(|d| d.id.clone()).collect();
+        let threshold = state.config().fuzzy_search_threshold;
+        let fuzzy = search_devices_fuzzy(&state.db, &q, threshold, &exclude).await?;
+        devices.extend(fuzzy.into_iter().take(SEARCH_LIMIT - devices.len()));
+    }
 
     // Search agents by name or hostname (from latest report)
     let agent_rows = sqlx::query(
@@ -124,11 +130,7 @@ pub async fn search(
     )
     .bind(&like_term)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Search agents failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let agents: Vec<SearchAgent> = agent_rows
         .into_iter()
@@ -150,11 +152,7 @@ pub async fn search(
     )
     .bind(&like_term)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Search alerts failed: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let alerts: Vec<SearchAlert> = alert_rows
         .into_iter()
@@ -175,7 +173,7 @@ pub async fn search(
     }))
 }
 
-/// Search devices directly from pool (for unit tests).
+/// Exact substring search over devices (IP, name, hostname, MAC, vendor, tags, notes).
 pub async fn search_devices(
     pool: &sqlx::SqlitePool,
     term: &str,
@@ -184,16 +182,22 @@ pub async fn search_devices(
 
     let rows = sqlx::query(
         r#"SELECT DISTINCT d.id, d.hostname, d.mac, d.vendor, d.is_online,
-                  (SELECT di.ip FROM device_ips di WHERE di.device_id = d.id AND di.is_current = 1 LIMIT 1) AS ip_address
+                  (SELECT di.ip FROM device_ips di WHERE di.device_id = d.id AND di.is_current = 1 ORDER BY di.ip_version LIMIT 1) AS ip_address
            FROM devices d
            LEFT JOIN device_ips di ON di.device_id = d.id AND di.is_current = 1
-           WHERE di.ip LIKE ?1
+           WHERE d.is_deleted = 0
+             AND (di.ip LIKE ?1
+              OR d.name LIKE ?1
               OR d.hostname LIKE ?1
               OR d.mac LIKE ?1
               OR d.vendor LIKE ?1
+              OR EXISTS (SELECT 1 FROM device_tags t WHERE t.device_id = d.id AND t.tag LIKE ?1)
+              OR (?2 IS NOT NULL AND d.id IN (SELECT n.device_id FROM device_notes n WHERE n.rowid IN
+                          (SELECT rowid FROM device_notes_fts WHERE device_notes_fts MATCH ?2))))
            LIMIT 5"#,
     )
     .bind(&like_term)
+    .bind(notes_fts_query(term))
     .fetch_all(pool)
     .await?;
 
@@ -206,10 +210,130 @@ pub async fn search_devices(
             mac_address: row.try_get("mac").unwrap_or_default(),
             vendor: row.try_get("vendor").unwrap_or(None),
             is_online: row.try_get::<i32, _>("is_online").unwrap_or(0) != 0,
+            match_type: MatchType::Exact,
         })
         .collect())
 }
 
+/// Lowercased trigrams of each alphanumeric word in `s`, padded like
+/// pg_trgm (two leading spaces, one trailing) so short words still count.
+fn trigrams(s: &str) -> HashSet<String> {
+    let mut out = HashSet::new();
+    for word in s
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let padded: Vec<char> = format!("  {word} ").chars().collect();
+        for window in padded.windows(3) {
+            out.insert(window.iter().collect());
+        }
+    }
+    out
+}
+
+/// Jaccard similarity of the trigram sets of `a` and `b` (0.0–1.0).
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Best similarity between `term` and `field`, comparing against the whole
+/// field and each of its words ("raspbery" vs "Raspberry Pi Foundation").
+fn field_similarity(term: &str, field: &str) -> f64 {
+    field
+        .split_whitespace()
+        .map(|word| trigram_similarity(term, word))
+        .fold(trigram_similarity(term, field), f64::max)
+}
+
+/// Build an FTS5 query matching any 3-character window of `term` against
+/// the trigram index. Returns `None` when no word is 3+ characters long.
+fn devices_fts_query(term: &str) -> Option<String> {
+    let mut seen = HashSet::new();
+    let mut grams = Vec::new();
+    for word in term
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let chars: Vec<char> = word.chars().collect();
+        for window in chars.windows(3) {
+            let gram: String = window.iter().collect();
+            if seen.insert(gram.clone()) {
+                grams.push(format!("\"{gram}\""));
+            }
+        }
+    }
+    (!grams.is_empty()).then(|| grams.join(" OR "))
+}
+
+/// Devices whose name, hostname or vendor is trigram-similar to `term` above
+/// `threshold`, best match first, skipping ids in `exclude`.
+pub async fn search_devices_fuzzy(
+    pool: &sqlx::SqlitePool,
+    term: &str,
+    threshold: f64,
+    exclude: &HashSet<String>,
+) -> Result<Vec<SearchDevice>, sqlx::Error> {
+    let Some(fts_query) = devices_fts_query(term) else {
+        return Ok(Vec::new());
+    };
+
+    let rows = sqlx::query(
+        r#"SELECT d.id, d.name, d.hostname, d.mac, d.vendor, d.is_online,
+                  (SELECT di.ip FROM device_ips di WHERE di.device_id = d.id AND di.is_current = 1 ORDER BY di.ip_version LIMIT 1) AS ip_address
+           FROM devices_fts f
+           JOIN devices d ON d.rowid = f.rowid
+           WHERE devices_fts MATCH ?1 AND d.is_deleted = 0
+           ORDER BY f.rank
+           LIMIT ?2"#,
+    )
+    .bind(&fts_query)
+    .bind(FUZZY_CANDIDATES)
+    .fetch_all(pool)
+    .await?;
+
+    let mut scored: Vec<(f64, SearchDevice)> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let id: String = row.try_get("id").unwrap_or_default();
+            if exclude.contains(&id) {
+                return None;
+            }
+            let name: Option<String> = row.try_get("name").unwrap_or(None);
+            let hostname: Option<String> = row.try_get("hostname").unwrap_or(None);
+            let vendor: Option<String> = row.try_get("vendor").unwrap_or(None);
+            let score = [&name, &hostname, &vendor]
+                .into_iter()
+                .flatten()
+                .map(|field| field_similarity(term, field))
+                .fold(0.0, f64::max);
+            (score > threshold).then(|| {
+                (
+                    score,
+                    SearchDevice {
+                        id,
+                        ip_address: row.try_get("ip_address").unwrap_or(None),
+                        hostname,
+                        mac_address: row.try_get("mac").unwrap_or_default(),
+                        vendor,
+                        is_online: row.try_get::<i32, _>("is_online").unwrap_or(0) != 0,
+                        match_type: MatchType::Fuzzy,
+                    },
+                )
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    Ok(scored.into_iter().map(|(_, device)| device).collect())
+}
+
 /// Search agents directly from pool (for unit tests).
 pub async fn search_agents(
     pool: &sqlx::SqlitePool,
@@ -494,4 +618,137 @@ mod tests {
             results.len()
         );
     }
+
+    #[tokio::test]
+    async fn test_search_devices_by_tag() {
+        let pool = test_db().await;
+        let id = insert_device(&pool, "AA:BB:CC:DD:EE:10", Some("lobby"), None, None).await;
+        insert_device(&pool, "AA:BB:CC:DD:EE:11", Some("desk"), None, None).await;
+        sqlx::query("INSERT INTO device_tags (device_id, tag) VALUES (?, 'printer')")
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let results = search_devices(&pool, "print").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, id);
+    }
+
+    #[test]
+    fn test_notes_fts_query() {
+        assert_eq!(
+            notes_fts_query("backup NAS").as_deref(),
+            Some("\"backup\"* \"NAS\"*")
+        );
+        assert_eq!(
+            notes_fts_query("nic-swap\"").as_deref(),
+            Some("\"nic\"* \"swap\"*")
+        );
+        assert_eq!(notes_fts_query("--"), None);
+    }
+
+    #[test]
+    fn test_trigram_similarity() {
+        assert!((trigram_similarity("raspberry", "Raspberry") - 1.0).abs() < f64::EPSILON);
+        assert!(field_similarity("Raspbery", "Raspberry Pi Foundation") > 0.4);
+        assert!(field_similarity("synology", "Raspberry Pi Foundation") < 0.1);
+        assert_eq!(trigram_similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_devices_fts_query() {
+        assert_eq!(
+            devices_fts_query("Pi-hole").as_deref(),
+            Some("\"hol\" OR \"ole\"")
+        );
+        assert_eq!(devices_fts_query("ab"), None);
+    }
+
+    #[tokio::test]
+    async fn test_search_devices_fuzzy() {
+        let pool = test_db().await;
+        let pi = insert_device(
+            &pool,
+            "B8:27:EB:00:00:01",
+            Some("octopi"),
+            Some("Raspberry Pi Foundation"),
+            None,
+        )
+        .await;
+        insert_device(
+            &pool,
+            "AA:BB:CC:DD:EE:31",
+            Some("nas"),
+            Some("Synology"),
+            None,
+        )
+        .await;
+
+        assert!(search_devices(&pool, "Raspbery").await.unwrap().is_empty());
+
+        let results = search_devices_fuzzy(&pool, "Raspbery", 0.4, &HashSet::new())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, pi);
+        assert_eq!(results[0].match_type, MatchType::Fuzzy);
+
+        // Already-returned exact matches are skipped.
+        let exclude = HashSet::from([pi.clone()]);
+        assert!(search_devices_fuzzy(&pool, "Raspbery", 0.4, &exclude)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A stricter threshold drops the typo.
+        assert!(
+            search_devices_fuzzy(&pool, "Raspbery", 0.9, &HashSet::new())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Renames are picked up by the index.
+        sqlx::query("UPDATE devices SET name = 'Kitchen Display' WHERE id = ?")
+            .bind(&pi)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let results = search_devices_fuzzy(&pool, "kitchn", 0.4, &HashSet::new())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        let exact = search_devices(&pool, "kitchen").await.unwrap();
+        assert_eq!(exact[0].match_type, MatchType::Exact);
+    }
+
+    #[tokio::test]
+    async fn test_search_devices_by_note() {
+        let pool = test_db().await;
+        let id = insert_device(&pool, "AA:BB:CC:DD:EE:20", Some("nas"), None, None).await;
+        insert_device(&pool, "AA:BB:CC:DD:EE:21", Some("desk"), None, None).await;
+        sqlx::query(
+            "INSERT INTO device_notes (id, device_id, body) VALUES ('n1', ?, 'Backup NAS, do not scan aggressively')",
+        )
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let results = search_devices(&pool, "aggress").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, id);
+
+        // Punctuation-only terms cannot match notes and must not error.
+        assert!(search_devices(&pool, "!!").await.unwrap().is_empty());
+
+        // Deleting the note removes it from the index.
+        sqlx::query("DELETE FROM device_notes WHERE id = 'n1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(search_devices(&pool, "aggress").await.unwrap().is_empty());
+    }
 }
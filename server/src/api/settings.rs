@@ -1,13 +1,25 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, StatusCode},
+    response::Response,
+    Extension, Json,
+};
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tokio_util::io::ReaderStream;
+use tracing::{error, info, warn};
 
-use super::AppState;
-use crate::{netflow, webhook};
+use super::auth::SessionUser;
+use super::users::ROLE_ADMIN;
+use super::{alerts, AppError, AppState};
+use crate::notification::{email, telegram};
+use crate::{mdns, netflow, oui, webhook};
+use std::collections::HashMap;
 
 /// Settings object returned by the API.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SettingsResponse {
+    /// Legacy single webhook (receives every event); see `/webhooks`.
     pub webhook_url: Option<String>,
     pub vyos_url: Option<String>,
     /// Masked API key — never return the full key to the frontend.
@@ -16,10 +28,44 @@ pub struct SettingsResponse {
     pub scan_interval_seconds: Option<u64>,
     pub scan_subnets: Option<String>,
     pub ping_sweep_enabled: Option<bool>,
+    /// Repeats of the same alert for a device within this window are folded
+    /// into the existing alert (0 disables dedup).
+    pub alert_dedup_window_secs: Option<u64>,
+    /// mDNS service types that are never recorded.
+    pub mdns_ignore_service_types: Vec<String>,
+    /// If non-empty, the only mDNS service types recorded.
+    pub mdns_include_service_types: Vec<String>,
     // --- Data Retention ---
     pub retention_traffic_hours: Option<u64>,
     pub retention_alerts_days: Option<u64>,
     pub retention_agent_reports_days: Option<u64>,
+    // --- Config Archive ---
+    pub git_archive_repo_url: Option<String>,
+    pub git_archive_branch: Option<String>,
+    /// Masked auth token — never return the token itself.
+    pub git_archive_auth_token_set: bool,
+    pub git_archive_schedule_enabled: bool,
+    // --- Email Notifications ---
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    /// Masked SMTP password — never return the password itself.
+    pub smtp_password_set: bool,
+    pub smtp_from: Option<String>,
+    pub smtp_to: Option<String>,
+    // --- Telegram Notifications ---
+    /// Masked bot token — never return the token itself.
+    pub telegram_bot_token_set: bool,
+    pub telegram_chat_id: Option<String>,
+    // --- LDAP Authentication ---
+    pub ldap_url: Option<String>,
+    pub ldap_bind_dn: Option<String>,
+    /// Masked bind password — never return the password itself.
+    pub ldap_bind_password_set: bool,
+    pub ldap_user_base_dn: Option<String>,
+    pub ldap_user_filter: Option<String>,
+    /// From `[auth] ldap_required`; not editable through the API.
+    pub ldap_required: bool,
 }
 
 /// Request body for updating settings.
@@ -32,10 +78,33 @@ pub struct UpdateSettingsRequest {
     pub scan_interval_seconds: Option<u64>,
     pub scan_subnets: Option<String>,
     pub ping_sweep_enabled: Option<bool>,
+    pub alert_dedup_window_secs: Option<u64>,
+    pub mdns_ignore_service_types: Option<Vec<String>>,
+    pub mdns_include_service_types: Option<Vec<String>>,
     // --- Data Retention ---
     pub retention_traffic_hours: Option<u64>,
     pub retention_alerts_days: Option<u64>,
     pub retention_agent_reports_days: Option<u64>,
+    // --- Config Archive ---
+    pub git_archive_repo_url: Option<String>,
+    pub git_archive_branch: Option<String>,
+    pub git_archive_auth_token: Option<String>,
+    // --- Email Notifications ---
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_from: Option<String>,
+    pub smtp_to: Option<String>,
+    // --- Telegram Notifications ---
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    // --- LDAP Authentication ---
+    pub ldap_url: Option<String>,
+    pub ldap_bind_dn: Option<String>,
+    pub ldap_bind_password: Option<String>,
+    pub ldap_user_base_dn: Option<String>,
+    pub ldap_user_filter: Option<String>,
 }
 
 /// Helper: read a string setting from the settings table.
@@ -52,7 +121,7 @@ async fn get_setting(state: &AppState, key: &str) -> Option<String> {
 /// GET /api/v1/settings — return current settings.
 pub async fn get_settings(
     State(state): State<AppState>,
-) -> Result<Json<SettingsResponse>, StatusCode> {
+) -> Result<Json<SettingsResponse>, AppError> {
     let webhook_url = webhook::get_webhook_url(&state.db).await;
 
     let vyos_url = get_setting(&state, "vyos_url").await;
@@ -63,32 +132,80 @@ pub async fn get_settings(
     let scan_interval_seconds = get_setting(&state, "scan_interval_seconds")
         .await
         .and_then(|v| v.parse().ok())
-        .or(Some(state.config.scanner.interval_seconds));
+        .or(Some(state.config().scanner.interval_seconds));
 
     let scan_subnets = get_setting(&state, "scan_subnets")
         .await
-        .or_else(|| Some(state.config.scanner.subnets.join(",")));
+        .or_else(|| Some(state.config().scanner.subnets.join(",")));
 
     let ping_sweep_enabled = get_setting(&state, "ping_sweep_enabled")
         .await
         .map(|v| v == "true")
         .or(Some(true));
 
+    let alert_dedup_window_secs = get_setting(&state, "alert_dedup_window_secs")
+        .await
+        .and_then(|v| v.parse().ok())
+        .or(Some(crate::scanner::DEFAULT_ALERT_DEDUP_WINDOW_SECS));
+
+    let config = state.config().clone();
+    let mdns_filter = mdns::load_service_filter(&state.db, &config).await;
+
     // Data Retention settings (fall back to config defaults).
     let retention_traffic_hours = get_setting(&state, "retention_traffic_hours")
         .await
         .and_then(|v| v.parse().ok())
-        .or(Some(state.config.retention.traffic_samples_hours));
+        .or(Some(config.retention.traffic_samples_hours));
 
     let retention_alerts_days = get_setting(&state, "retention_alerts_days")
         .await
         .and_then(|v| v.parse().ok())
-        .or(Some(state.config.retention.alerts_days));
+        .or(Some(config.retention.alerts_days));
 
     let retention_agent_reports_days = get_setting(&state, "retention_agent_reports_days")
         .await
         .and_then(|v| v.parse().ok())
-        .or(Some(state.config.retention.agent_reports_days));
+        .or(Some(config.retention.agent_reports_days));
+
+    // Config Archive settings.
+    let git_archive_repo_url = get_setting(&state, "git_archive_repo_url").await;
+    let git_archive_branch = get_setting(&state, "git_archive_branch").await;
+    let git_archive_auth_token_set = get_setting(&state, "git_archive_auth_token")
+        .await
+        .is_some();
+    let git_archive_schedule_enabled =
+        crate::vyos::config_archive::schedule_enabled(&state.db).await;
+
+    // Email notification settings.
+    let smtp_host = get_setting(&state, "smtp_host").await;
+    let smtp_port = get_setting(&state, "smtp_port")
+        .await
+        .and_then(|v| v.parse().ok());
+    let smtp_username = get_setting(&state, "smtp_username").await;
+    let smtp_password_set = get_setting(&state, "smtp_password").await.is_some();
+    let smtp_from = get_setting(&state, "smtp_from").await;
+    let smtp_to = get_setting(&state, "smtp_to").await;
+
+    // Telegram notification settings.
+    let telegram_bot_token_set = get_setting(&state, "telegram_bot_token").await.is_some();
+    let telegram_chat_id = get_setting(&state, "telegram_chat_id").await;
+
+    // LDAP settings (fall back to the [auth] config section).
+    let auth = &config.auth;
+    let ldap_url = get_setting(&state, "ldap_url")
+        .await
+        .or_else(|| auth.ldap_url.clone());
+    let ldap_bind_dn = get_setting(&state, "ldap_bind_dn")
+        .await
+        .or_else(|| auth.ldap_bind_dn.clone());
+    let ldap_bind_password_set = get_setting(&state, "ldap_bind_password").await.is_some()
+        || auth.ldap_bind_password.is_some();
+    let ldap_user_base_dn = get_setting(&state, "ldap_user_base_dn")
+        .await
+        .or_else(|| auth.ldap_user_base_dn.clone());
+    let ldap_user_filter = get_setting(&state, "ldap_user_filter")
+        .await
+        .or_else(|| auth.ldap_user_filter.clone());
 
     Ok(Json(SettingsResponse {
         webhook_url,
@@ -97,9 +214,30 @@ pub async fn get_settings(
         scan_interval_seconds,
         scan_subnets,
         ping_sweep_enabled,
+        alert_dedup_window_secs,
+        mdns_ignore_service_types: mdns_filter.ignore,
+        mdns_include_service_types: mdns_filter.include,
         retention_traffic_hours,
         retention_alerts_days,
         retention_agent_reports_days,
+        git_archive_repo_url,
+        git_archive_branch,
+        git_archive_auth_token_set,
+        git_archive_schedule_enabled,
+        smtp_host,
+        smtp_port,
+        smtp_username,
+        smtp_password_set,
+        smtp_from,
+        smtp_to,
+        telegram_bot_token_set,
+        telegram_chat_id,
+        ldap_url,
+        ldap_bind_dn,
+        ldap_bind_password_set,
+        ldap_user_base_dn,
+        ldap_user_filter,
+        ldap_required: auth.ldap_required,
     }))
 }
 
@@ -107,7 +245,7 @@ pub async fn get_settings(
 pub async fn update_settings(
     State(state): State<AppState>,
     Json(body): Json<UpdateSettingsRequest>,
-) -> Result<Json<SettingsResponse>, StatusCode> {
+) -> Result<Json<SettingsResponse>, AppError> {
     if let Some(ref url) = body.webhook_url {
         upsert_setting(&state, "webhook_url", url).await?;
         info!(webhook_url = %url, "Webhook URL updated");
@@ -119,17 +257,14 @@ pub async fn update_settings(
     }
 
     if let Some(ref key) = body.vyos_api_key {
-        // NOTE: The VyOS API key is stored unencrypted in SQLite.
-        // This is intentional for a single-user self-hosted deployment where
-        // the database file is protected by OS filesystem permissions and the
-        // server requires authentication to read or modify settings.
-        // TODO: Add at-rest encryption (e.g. AES-GCM with a server-generated
-        // key stored outside the database) if multi-user or remote DB support
-        // is added in the future.
-        upsert_setting(&state, "vyos_api_key", key).await?;
+        upsert_sealed_setting(&state, "vyos_api_key", key).await?;
         info!("VyOS API key updated");
     }
 
+    if body.vyos_url.is_some() || body.vyos_api_key.is_some() {
+        state.vyos_cache.clear();
+    }
+
     // --- Network Scanner settings ---
     if let Some(interval) = body.scan_interval_seconds {
         upsert_setting(&state, "scan_interval_seconds", &interval.to_string()).await?;
@@ -146,6 +281,31 @@ pub async fn update_settings(
         info!(ping_sweep_enabled = enabled, "Ping sweep toggle updated");
     }
 
+    if let Some(window) = body.alert_dedup_window_secs {
+        upsert_setting(&state, "alert_dedup_window_secs", &window.to_string()).await?;
+        info!(
+            alert_dedup_window_secs = window,
+            "Alert dedup window updated"
+        );
+    }
+
+    for (key, types) in [
+        (
+            mdns::IGNORE_SERVICE_TYPES_KEY,
+            &body.mdns_ignore_service_types,
+        ),
+        (
+            mdns::INCLUDE_SERVICE_TYPES_KEY,
+            &body.mdns_include_service_types,
+        ),
+    ] {
+        if let Some(types) = types {
+            let value = mdns::parse_service_types(&types.join(",")).join(",");
+            upsert_setting(&state, key, &value).await?;
+            info!(key, service_types = %value, "mDNS service type filter updated");
+        }
+    }
+
     // --- Data Retention settings ---
     if let Some(hours) = body.retention_traffic_hours {
         upsert_setting(&state, "retention_traffic_hours", &hours.to_string()).await?;
@@ -165,33 +325,169 @@ pub async fn update_settings(
         );
     }
 
+    // --- Config Archive settings ---
+    if let Some(ref url) = body.git_archive_repo_url {
+        upsert_setting(&state, "git_archive_repo_url", url).await?;
+        info!(git_archive_repo_url = %url, "Git archive repository updated");
+    }
+
+    if let Some(ref branch) = body.git_archive_branch {
+        upsert_setting(&state, "git_archive_branch", branch).await?;
+        info!(git_archive_branch = %branch, "Git archive branch updated");
+    }
+
+    if let Some(ref token) = body.git_archive_auth_token {
+        upsert_sealed_setting(&state, "git_archive_auth_token", token).await?;
+        info!("Git archive auth token updated");
+    }
+
+    // --- Email Notification settings ---
+    if let Some(ref host) = body.smtp_host {
+        upsert_setting(&state, "smtp_host", host).await?;
+        info!(smtp_host = %host, "SMTP host updated");
+    }
+
+    if let Some(port) = body.smtp_port {
+        upsert_setting(&state, "smtp_port", &port.to_string()).await?;
+        info!(smtp_port = port, "SMTP port updated");
+    }
+
+    if let Some(ref username) = body.smtp_username {
+        upsert_setting(&state, "smtp_username", username).await?;
+        info!("SMTP username updated");
+    }
+
+    if let Some(ref password) = body.smtp_password {
+        upsert_sealed_setting(&state, "smtp_password", password).await?;
+        info!("SMTP password updated");
+    }
+
+    if let Some(ref from) = body.smtp_from {
+        upsert_setting(&state, "smtp_from", from).await?;
+        info!(smtp_from = %from, "SMTP sender updated");
+    }
+
+    if let Some(ref to) = body.smtp_to {
+        upsert_setting(&state, "smtp_to", to).await?;
+        info!(smtp_to = %to, "SMTP recipients updated");
+    }
+
+    // --- Telegram Notification settings ---
+    if let Some(ref token) = body.telegram_bot_token {
+        upsert_sealed_setting(&state, "telegram_bot_token", token).await?;
+        info!("Telegram bot token updated");
+    }
+
+    if let Some(ref chat_id) = body.telegram_chat_id {
+        upsert_setting(&state, "telegram_chat_id", chat_id).await?;
+        info!(telegram_chat_id = %chat_id, "Telegram chat ID updated");
+    }
+
+    // --- LDAP Authentication settings ---
+    for (key, value) in [
+        ("ldap_url", &body.ldap_url),
+        ("ldap_bind_dn", &body.ldap_bind_dn),
+        ("ldap_user_base_dn", &body.ldap_user_base_dn),
+        ("ldap_user_filter", &body.ldap_user_filter),
+    ] {
+        if let Some(value) = value {
+            upsert_setting(&state, key, value).await?;
+            info!(key, value = %value, "LDAP setting updated");
+        }
+    }
+
+    if let Some(ref password) = body.ldap_bind_password {
+        upsert_sealed_setting(&state, "ldap_bind_password", password).await?;
+        info!("LDAP bind password updated");
+    }
+
     // Return current state.
     get_settings(State(state)).await
 }
 
-/// POST /api/v1/settings/test-webhook — send a test webhook.
-pub async fn test_webhook(
-    State(state): State<AppState>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let url = webhook::get_webhook_url(&state.db).await.ok_or((
-        StatusCode::BAD_REQUEST,
-        "No webhook URL configured".to_string(),
-    ))?;
-
-    let payload = serde_json::json!({
-        "type": "test",
-        "data": {
-            "message": "Panoptikon webhook test",
-        },
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    });
+/// POST /api/v1/settings/test-webhook — send a test webhook to the legacy URL.
+///
+/// Deprecated: use `POST /api/v1/webhooks/:id/test`.
+pub async fn test_webhook(State(state): State<AppState>) -> Result<StatusCode, AppError> {
+    warn!("POST /settings/test-webhook is deprecated; use /webhooks/:id/test");
+    let url = webhook::get_webhook_url(&state.db)
+        .await
+        .ok_or_else(|| AppError::Validation("No webhook URL configured".to_string()))?;
+
+    let payload = webhook::webhook_payload(
+        "test",
+        serde_json::json!({ "message": "Panoptikon webhook test" }),
+    );
 
     // For test, we actually await the result so we can report success/failure.
-    webhook::send_webhook(&url, payload).await;
+    webhook::send_webhook(&url, None, &payload)
+        .await
+        .map_err(|e| AppError::BadGateway(e.to_string()))?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// POST /api/v1/settings/test-email — send a test email.
+pub async fn test_email(State(state): State<AppState>) -> Result<StatusCode, AppError> {
+    let settings = email::load_settings(&state.db).await.ok_or_else(|| {
+        AppError::Validation("SMTP host, sender and recipients must be configured".to_string())
+    })?;
+
+    // Unlike alert emails, report delivery failures to the caller.
+    email::send_email(
+        &settings,
+        "[Panoptikon] Test email",
+        "This is a test message from Panoptikon.\n",
+    )
+    .await
+    .map_err(|e| AppError::BadGateway(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/settings/test-telegram — send a test Telegram message.
+pub async fn test_telegram(State(state): State<AppState>) -> Result<StatusCode, AppError> {
+    let (token, chat_id) = telegram::get_telegram_settings(&state.db)
+        .await
+        .ok_or_else(|| {
+            AppError::Validation("Telegram bot token and chat ID must be configured".to_string())
+        })?;
+
+    state.telegram_limiter.wait().await;
+    telegram::send_telegram_alert(
+        &token,
+        &chat_id,
+        &telegram::escape_markdown("Panoptikon Telegram test"),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Response for the test-ldap endpoint.
+#[derive(Debug, Serialize)]
+pub struct TestLdapResponse {
+    /// User entries under the base DN matched by the user filter.
+    pub users_found: usize,
+}
+
+/// POST /api/v1/settings/test-ldap — bind with the configured DN and count
+/// the user entries under the base DN.
+pub async fn test_ldap(State(state): State<AppState>) -> Result<Json<TestLdapResponse>, AppError> {
+    let auth = state.config().auth.clone();
+    let settings = crate::ldap::load_settings(&state.db, &auth)
+        .await
+        .ok_or_else(|| {
+            AppError::Validation("LDAP URL and user base DN must be configured".to_string())
+        })?;
+
+    let users_found = crate::ldap::count_users(&settings)
+        .await
+        .map_err(AppError::BadGateway)?;
+
+    Ok(Json(TestLdapResponse { users_found }))
+}
+
 /// Response for the netflow-status endpoint.
 #[derive(Debug, Serialize)]
 pub struct NetflowStatusResponse {
@@ -202,9 +498,10 @@ pub struct NetflowStatusResponse {
 
 /// GET /api/v1/settings/netflow-status — return NetFlow collector status.
 pub async fn netflow_status(State(state): State<AppState>) -> Json<NetflowStatusResponse> {
+    let config = state.config();
     Json(NetflowStatusResponse {
-        enabled: state.config.scanner.netflow_enabled,
-        port: state.config.scanner.netflow_port,
+        enabled: config.scanner.netflow_enabled,
+        port: config.scanner.netflow_port,
         flows_received: netflow::flows_received(),
     })
 }
@@ -216,29 +513,196 @@ pub struct DbSizeResponse {
 }
 
 /// GET /api/v1/settings/db-size — return the current database file size.
-pub async fn db_size(State(state): State<AppState>) -> Result<Json<DbSizeResponse>, StatusCode> {
+pub async fn db_size(State(state): State<AppState>) -> Result<Json<DbSizeResponse>, AppError> {
     // Use SQLite's page_count * page_size to get the logical size.
     let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to get page_count: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
     let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to get page_size: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     let size_bytes = (page_count * page_size) as u64;
     Ok(Json(DbSizeResponse { size_bytes }))
 }
 
+/// How long a computed storage breakdown is served from cache.
+const STORAGE_CACHE_SECS: u64 = 30;
+
+/// Disk usage of a single table (including its indexes).
+#[derive(Debug, Clone, Serialize)]
+pub struct TableStorage {
+    pub name: String,
+    pub bytes: u64,
+    pub rows: u64,
+    pub percent: f64,
+}
+
+/// Response for the storage endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageResponse {
+    pub total_bytes: u64,
+    pub total_human: String,
+    pub tables: Vec<TableStorage>,
+}
+
+/// GET /api/v1/settings/storage — per-table disk usage breakdown.
+///
+/// Uses SQLite's `dbstat` virtual table; index pages are attributed to the
+/// table they belong to. Results are cached for 30 seconds since `dbstat`
+/// walks every page of the database.
+pub async fn storage(State(state): State<AppState>) -> Result<Json<StorageResponse>, AppError> {
+    let mut cache = state.storage_cache.lock().await;
+    if let Some((computed_at, ref cached)) = *cache {
+        if computed_at.elapsed().as_secs() < STORAGE_CACHE_SECS {
+            return Ok(Json(cached.clone()));
+        }
+    }
+
+    let response = compute_storage(&state.db).await?;
+    *cache = Some((std::time::Instant::now(), response.clone()));
+    Ok(Json(response))
+}
+
+/// Query `dbstat` and row counts to build a [`StorageResponse`].
+async fn compute_storage(db: &sqlx::SqlitePool) -> Result<StorageResponse, sqlx::Error> {
+    let sizes: Vec<(String, i64)> = sqlx::query_as(
+        r#"SELECT m.tbl_name AS name, SUM(d.payload) AS payload_bytes
+           FROM dbstat d
+           JOIN sqlite_master m ON m.name = d.name
+           WHERE m.tbl_name NOT LIKE 'sqlite_%'
+           GROUP BY m.tbl_name
+           ORDER BY payload_bytes DESC"#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let total_bytes: u64 = sizes.iter().map(|(_, b)| (*b).max(0) as u64).sum();
+
+    let mut tables = Vec::with_capacity(sizes.len());
+    for (name, bytes) in sizes {
+        // Table names come from sqlite_master, not user input; quote them anyway.
+        let rows: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM \"{}\"",
+            name.replace('"', "\"\"")
+        ))
+        .fetch_one(db)
+        .await?;
+
+        let bytes = bytes.max(0) as u64;
+        let percent = if total_bytes > 0 {
+            (bytes as f64 / total_bytes as f64 * 1000.0).round() / 10.0
+        } else {
+            0.0
+        };
+        tables.push(TableStorage {
+            name,
+            bytes,
+            rows: rows.max(0) as u64,
+            percent,
+        });
+    }
+
+    Ok(StorageResponse {
+        total_bytes,
+        total_human: format_bytes(total_bytes),
+        tables,
+    })
+}
+
+/// Format a byte count as a human-readable string (e.g. "50 MB").
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || value >= 10.0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Response for the alert-severities endpoints.
+#[derive(Debug, Serialize)]
+pub struct AlertSeveritiesResponse {
+    /// Compiled-in severity per alert type.
+    pub defaults: HashMap<String, String>,
+    /// Operator overrides stored in settings.
+    pub overrides: HashMap<String, String>,
+    /// Severity actually applied per alert type (override or default).
+    pub effective: HashMap<String, String>,
+}
+
+/// GET /api/v1/settings/alert-severities — current severity overrides and defaults.
+pub async fn get_alert_severities(State(state): State<AppState>) -> Json<AlertSeveritiesResponse> {
+    let overrides = alerts::load_severity_overrides(&state.db).await;
+
+    let mut defaults = HashMap::new();
+    let mut effective = HashMap::new();
+    for alert_type in alerts::ALERT_TYPES {
+        let default = alerts::default_severity_for_alert_type(alert_type).to_string();
+        let applied = overrides
+            .get(*alert_type)
+            .cloned()
+            .unwrap_or_else(|| default.clone());
+        defaults.insert(alert_type.to_string(), default);
+        effective.insert(alert_type.to_string(), applied);
+    }
+
+    Json(AlertSeveritiesResponse {
+        defaults,
+        overrides,
+        effective,
+    })
+}
+
+/// PATCH /api/v1/settings/alert-severities — set or clear severity overrides.
+///
+/// Body maps alert type to severity (`info`, `warning`, `critical`);
+/// a `null` value removes the override for that type.
+pub async fn update_alert_severities(
+    State(state): State<AppState>,
+    Json(body): Json<HashMap<String, Option<String>>>,
+) -> Result<Json<AlertSeveritiesResponse>, AppError> {
+    let mut overrides = alerts::load_severity_overrides(&state.db).await;
+
+    for (alert_type, severity) in body {
+        if !alerts::ALERT_TYPES.contains(&alert_type.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Unknown alert type '{alert_type}'"
+            )));
+        }
+        match severity {
+            Some(sev) => {
+                let normalized = alerts::normalize_severity(&sev).ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "Invalid severity '{sev}' (expected info, warning or critical)"
+                    ))
+                })?;
+                overrides.insert(alert_type, normalized.to_string());
+            }
+            None => {
+                overrides.remove(&alert_type);
+            }
+        }
+    }
+
+    let json = serde_json::to_string(&overrides).map_err(|e| AppError::Internal(e.to_string()))?;
+    upsert_setting(&state, alerts::SEVERITY_OVERRIDES_KEY, &json)
+        .await
+        .map_err(|_| AppError::Internal("Failed to save severity overrides".to_string()))?;
+    state.severity_overrides.invalidate().await;
+    info!(overrides = %json, "Alert severity overrides updated");
+
+    Ok(get_alert_severities(State(state)).await)
+}
+
 /// POST /api/v1/settings/vacuum — manually trigger a database VACUUM.
-pub async fn vacuum(State(state): State<AppState>) -> Result<StatusCode, (StatusCode, String)> {
+pub async fn vacuum(State(state): State<AppState>) -> Result<StatusCode, AppError> {
     info!("Manual VACUUM requested");
 
     // Checkpoint WAL first.
@@ -247,19 +711,13 @@ pub async fn vacuum(State(state): State<AppState>) -> Result<StatusCode, (Status
         .await
     {
         error!("WAL checkpoint failed: {e}");
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("WAL checkpoint failed: {e}"),
-        ));
+        return Err(AppError::Internal(format!("WAL checkpoint failed: {e}")));
     }
 
     // Run VACUUM.
     if let Err(e) = sqlx::query("VACUUM").execute(&state.db).await {
         error!("VACUUM failed: {e}");
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("VACUUM failed: {e}"),
-        ));
+        return Err(AppError::Internal(format!("VACUUM failed: {e}")));
     }
 
     // Update last_vacuum_at.
@@ -274,8 +732,164 @@ pub async fn vacuum(State(state): State<AppState>) -> Result<StatusCode, (Status
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Response for the backup info endpoint.
+#[derive(Debug, Serialize)]
+pub struct BackupInfoResponse {
+    /// When `POST /settings/backup` last produced a backup (UTC).
+    pub last_backup_at: Option<String>,
+    /// Approximate size of a backup (logical database size).
+    pub size_bytes: u64,
+    /// Highest applied schema migration; a backup restores to this version.
+    pub migration_version: i64,
+}
+
+/// GET /api/v1/settings/backup/info — last backup time, database size and
+/// schema version.
+pub async fn backup_info(
+    State(state): State<AppState>,
+) -> Result<Json<BackupInfoResponse>, AppError> {
+    let last_backup_at: Option<String> =
+        sqlx::query_scalar("SELECT value FROM settings WHERE key = 'last_backup_at'")
+            .fetch_optional(&state.db)
+            .await?;
+    let size_bytes: i64 = sqlx::query_scalar(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+    )
+    .fetch_one(&state.db)
+    .await?;
+    let migration_version: i64 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _migrations")
+            .fetch_one(&state.db)
+            .await?;
+
+    Ok(Json(BackupInfoResponse {
+        last_backup_at,
+        size_bytes: size_bytes.max(0) as u64,
+        migration_version,
+    }))
+}
+
+/// POST /api/v1/settings/backup — download a consistent copy of the database.
+///
+/// `VACUUM INTO` writes a point-in-time snapshot (safe under WAL) to a
+/// temporary file, which is streamed back as an attachment. Admin sessions
+/// only; viewers and API keys get 403.
+pub async fn backup(
+    State(state): State<AppState>,
+    user: Option<Extension<SessionUser>>,
+) -> Result<Response, AppError> {
+    if user.is_none_or(|Extension(user)| user.role != ROLE_ADMIN) {
+        return Err(AppError::Forbidden(
+            "Database backups require an admin session".to_string(),
+        ));
+    }
+
+    let path = std::env::temp_dir().join(format!("panoptikon-backup-{}.db", uuid::Uuid::new_v4()));
+    let path_str = path.to_string_lossy().to_string();
+    if let Err(e) = sqlx::query("VACUUM INTO ?")
+        .bind(&path_str)
+        .execute(&state.db)
+        .await
+    {
+        let _ = tokio::fs::remove_file(&path).await;
+        error!("Database backup failed: {e}");
+        return Err(AppError::Internal(format!("Database backup failed: {e}")));
+    }
+
+    let file = tokio::fs::File::open(&path).await;
+    // The open handle keeps the data readable while streaming; unlink now so
+    // the file can't be left behind if the client disconnects.
+    let _ = tokio::fs::remove_file(&path).await;
+    let file = file.map_err(|e| AppError::Internal(format!("Failed to open backup: {e}")))?;
+    let size = file
+        .metadata()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read backup size: {e}")))?
+        .len();
+
+    let _ = sqlx::query(
+        r#"INSERT INTO settings (key, value) VALUES ('last_backup_at', datetime('now'))
+           ON CONFLICT(key) DO UPDATE SET value = datetime('now')"#,
+    )
+    .execute(&state.db)
+    .await;
+    info!(size_bytes = size, "Database backup created");
+
+    let filename = format!(
+        "panoptikon-backup-{}.db",
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, size)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| AppError::Internal(format!("Failed to build backup response: {e}")))
+}
+
+/// Response for the OUI database endpoints.
+#[derive(Debug, Serialize)]
+pub struct OuiDbInfo {
+    /// Number of vendor prefixes in the active database.
+    pub entries: usize,
+    /// `embedded` (compiled in) or `file` (downloaded copy).
+    pub source: oui::OuiSource,
+}
+
+/// GET /api/v1/settings/oui-db-info — size and source of the vendor database.
+pub async fn oui_db_info() -> Json<OuiDbInfo> {
+    let (entries, source) = oui::info();
+    Json(OuiDbInfo { entries, source })
+}
+
+/// POST /api/v1/settings/update-oui-db — download the IEEE registry, save it
+/// to `oui_db_path` and start using it without a restart. Admin sessions only.
+pub async fn update_oui_db(
+    State(state): State<AppState>,
+    user: Option<Extension<SessionUser>>,
+) -> Result<Json<OuiDbInfo>, AppError> {
+    if user.is_none_or(|Extension(user)| user.role != ROLE_ADMIN) {
+        return Err(AppError::Forbidden(
+            "Updating the OUI database requires an admin session".to_string(),
+        ));
+    }
+
+    let path = oui::db_path(&state.config());
+    let entries = oui::update_from(oui::IEEE_OUI_URL, &path)
+        .await
+        .map_err(|e| {
+            error!("OUI database update failed: {e}");
+            AppError::BadGateway(format!("OUI database update failed: {e}"))
+        })?;
+    info!(entries, path = %path.display(), "OUI database updated");
+
+    state
+        .ws_hub
+        .broadcast("oui_db_updated", serde_json::json!({ "entries": entries }));
+
+    Ok(Json(OuiDbInfo {
+        entries,
+        source: oui::OuiSource::File,
+    }))
+}
+
+/// Store a [`crate::crypto::SEALED_SETTINGS`] value encrypted, like router
+/// profile keys. An empty value clears the setting.
+async fn upsert_sealed_setting(state: &AppState, key: &str, value: &str) -> Result<(), AppError> {
+    let sealed = if value.is_empty() {
+        String::new()
+    } else {
+        crate::crypto::encrypt(value)
+    };
+    upsert_setting(state, key, &sealed).await
+}
+
 /// Helper to upsert a key-value pair into the settings table.
-async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(), StatusCode> {
+async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(), AppError> {
     sqlx::query(
         r#"INSERT INTO settings (key, value) VALUES (?, ?)
            ON CONFLICT(key) DO UPDATE SET value = excluded.value"#,
@@ -283,10 +897,309 @@ async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(),
     .bind(key)
     .bind(value)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        error!("Failed to save setting '{key}': {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper: build an AppState with an in-memory database.
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:")
+            .await
+            .expect("in-memory DB init failed");
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(52_428_800), "50 MB");
+    }
+
+    #[tokio::test]
+    async fn test_storage_includes_traffic_samples() {
+        let state = test_state().await;
+
+        let device_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            r#"INSERT INTO devices (id, mac, name, first_seen_at, last_seen_at, is_online)
+               VALUES (?, 'AA:BB:CC:DD:EE:01', 'test', datetime('now'), datetime('now'), 1)"#,
+        )
+        .bind(&device_id)
+        .execute(&state.db)
+        .await
+        .unwrap();
+        for i in 0..50 {
+            sqlx::query(
+                r#"INSERT INTO traffic_samples (device_id, sampled_at, rx_bps, tx_bps, source)
+                   VALUES (?, datetime('now', ?), 1000, 2000, 'test')"#,
+            )
+            .bind(&device_id)
+            .bind(format!("-{i} minutes"))
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+
+        let Json(resp) = storage(State(state.clone())).await.expect("handler failed");
+
+        let traffic = resp
+            .tables
+            .iter()
+            .find(|t| t.name == "traffic_samples")
+            .expect("traffic_samples should be listed");
+        assert_eq!(traffic.rows, 50);
+        assert!(traffic.bytes > 0);
+        assert!(resp.total_bytes >= traffic.bytes);
+        assert!(!resp.tables.iter().any(|t| t.name.starts_with("sqlite_")));
+    }
+
+    #[tokio::test]
+    async fn test_alert_severities_patch_and_get() {
+        let state = test_state().await;
+
+        let body = HashMap::from([
+            ("new_device".to_string(), Some("critical".to_string())),
+            ("device_offline".to_string(), Some("info".to_string())),
+        ]);
+        let Json(resp) = update_alert_severities(State(state.clone()), Json(body))
+            .await
+            .expect("patch failed");
+        assert_eq!(resp.overrides.get("new_device").unwrap(), "CRITICAL");
+        assert_eq!(resp.effective.get("device_offline").unwrap(), "INFO");
+        assert_eq!(resp.defaults.get("new_device").unwrap(), "INFO");
+
+        let severity =
+            alerts::severity_for_alert_type("new_device", &state.db, &state.severity_overrides)
+                .await;
+        assert_eq!(severity, "CRITICAL");
+
+        // A null value clears the override.
+        let body = HashMap::from([("new_device".to_string(), None)]);
+        let Json(resp) = update_alert_severities(State(state.clone()), Json(body))
+            .await
+            .expect("patch failed");
+        assert!(!resp.overrides.contains_key("new_device"));
+        assert_eq!(resp.effective.get("new_device").unwrap(), "INFO");
+    }
+
+    #[tokio::test]
+    async fn test_alert_severities_rejects_invalid() {
+        let state = test_state().await;
+
+        let body = HashMap::from([("new_device".to_string(), Some("urgent".to_string()))]);
+        let err = update_alert_severities(State(state.clone()), Json(body))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+
+        let body = HashMap::from([("bogus".to_string(), Some("info".to_string()))]);
+        let err = update_alert_severities(State(state), Json(body))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_smtp_settings_patch_masks_password() {
+        let state = test_state().await;
+
+        let result = test_email(State(state.clone())).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let body: UpdateSettingsRequest = serde_json::from_value(serde_json::json!({
+            "smtp_host": "mail.example.com",
+            "smtp_port": 465,
+            "smtp_password": "hunter2",
+            "smtp_from": "panoptikon@example.com",
+            "smtp_to": "ops@example.com",
+        }))
+        .unwrap();
+        let Json(resp) = update_settings(State(state.clone()), Json(body))
+            .await
+            .expect("handler failed");
+
+        assert_eq!(resp.smtp_host.as_deref(), Some("mail.example.com"));
+        assert_eq!(resp.smtp_port, Some(465));
+        assert!(resp.smtp_password_set);
+        assert!(!serde_json::to_string(&resp).unwrap().contains("hunter2"));
+        let stored = get_setting(&state, "smtp_password").await.unwrap();
+        assert!(stored.starts_with(crate::crypto::SEALED_PREFIX));
+        assert_eq!(
+            crate::notification::email::load_settings(&state.db)
+                .await
+                .unwrap()
+                .password
+                .as_deref(),
+            Some("hunter2")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_telegram_bot_token_stored_encrypted() {
+        let state = test_state().await;
+
+        let body: UpdateSettingsRequest = serde_json::from_value(serde_json::json!({
+            "telegram_bot_token": "123:abc",
+            "telegram_chat_id": "-1001",
+        }))
+        .unwrap();
+        let Json(resp) = update_settings(State(state.clone()), Json(body))
+            .await
+            .expect("handler failed");
+
+        assert!(resp.telegram_bot_token_set);
+        let stored = get_setting(&state, "telegram_bot_token").await.unwrap();
+        assert!(stored.starts_with(crate::crypto::SEALED_PREFIX));
+        assert_eq!(
+            telegram::get_telegram_settings(&state.db).await,
+            Some(("123:abc".to_string(), "-1001".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ldap_settings_patch_masks_password() {
+        let state = test_state().await;
+
+        let result = test_ldap(State(state.clone())).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let body: UpdateSettingsRequest = serde_json::from_value(serde_json::json!({
+            "ldap_url": "ldap://127.0.0.1:1",
+            "ldap_bind_password": "hunter2",
+            "ldap_user_base_dn": "dc=example,dc=com",
+        }))
+        .unwrap();
+        let Json(resp) = update_settings(State(state.clone()), Json(body))
+            .await
+            .expect("handler failed");
+
+        assert_eq!(resp.ldap_url.as_deref(), Some("ldap://127.0.0.1:1"));
+        assert!(resp.ldap_bind_password_set);
+        assert!(!resp.ldap_required);
+        assert!(!serde_json::to_string(&resp).unwrap().contains("hunter2"));
+        let stored = get_setting(&state, "ldap_bind_password").await.unwrap();
+        assert!(stored.starts_with(crate::crypto::SEALED_PREFIX));
+        let auth = state.config().auth.clone();
+        let ldap = crate::ldap::load_settings(&state.db, &auth).await.unwrap();
+        assert_eq!(ldap.bind_password.as_deref(), Some("hunter2"));
+
+        // Configured but unreachable.
+        let result = test_ldap(State(state.clone())).await;
+        assert!(matches!(result, Err(AppError::BadGateway(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mdns_service_type_settings() {
+        let mut config = crate::config::AppConfig::default();
+        config.scanner.mdns_ignore_service_types = vec!["_sleep-proxy._udp".to_string()];
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+
+        let Json(resp) = get_settings(State(state.clone())).await.unwrap();
+        assert_eq!(resp.mdns_ignore_service_types, vec!["_sleep-proxy._udp"]);
+        assert!(resp.mdns_include_service_types.is_empty());
+
+        let body: UpdateSettingsRequest = serde_json::from_value(serde_json::json!({
+            "mdns_ignore_service_types": ["_Companion-Link._tcp.local.", " "],
+            "mdns_include_service_types": ["_ipp._tcp"],
+        }))
+        .unwrap();
+        let Json(resp) = update_settings(State(state.clone()), Json(body))
+            .await
+            .unwrap();
+        assert_eq!(resp.mdns_ignore_service_types, vec!["_companion-link._tcp"]);
+        assert_eq!(resp.mdns_include_service_types, vec!["_ipp._tcp"]);
+
+        // An empty list clears the config file default.
+        let body: UpdateSettingsRequest =
+            serde_json::from_value(serde_json::json!({ "mdns_ignore_service_types": [] })).unwrap();
+        let Json(resp) = update_settings(State(state), Json(body)).await.unwrap();
+        assert!(resp.mdns_ignore_service_types.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backup_downloads_sqlite_snapshot() {
+        // VACUUM INTO from a shared in-memory database writes to memory too,
+        // so this test needs a real file.
+        let db_path =
+            std::env::temp_dir().join(format!("panoptikon-test-{}.db", uuid::Uuid::new_v4()));
+        let pool = crate::db::init(db_path.to_str().unwrap()).await.unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        let Json(info) = backup_info(State(state.clone())).await.unwrap();
+        assert!(info.last_backup_at.is_none());
+        assert!(info.size_bytes > 0);
+        assert!(info.migration_version >= 29);
+
+        let admin = SessionUser {
+            user_id: None,
+            username: "admin".to_string(),
+            role: ROLE_ADMIN.to_string(),
+        };
+        let resp = backup(State(state.clone()), Some(Extension(admin)))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let disposition = resp.headers()[header::CONTENT_DISPOSITION]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(disposition.starts_with("attachment; filename=\"panoptikon-backup-"));
+        assert!(disposition.ends_with(".db\""));
+
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(bytes.starts_with(b"SQLite format 3\0"));
+
+        let Json(info) = backup_info(State(state.clone())).await.unwrap();
+        assert!(info.last_backup_at.is_some());
+
+        state.db.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{suffix}", db_path.display()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backup_requires_admin_session() {
+        let state = test_state().await;
+
+        let viewer = SessionUser {
+            user_id: None,
+            username: "noc".to_string(),
+            role: "viewer".to_string(),
+        };
+        for user in [None, Some(Extension(viewer))] {
+            let err = backup(State(state.clone()), user).await.unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oui_db_endpoints() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+
+        let Json(info) = oui_db_info().await;
+        assert!(info.entries > 30_000);
+
+        let viewer = SessionUser {
+            user_id: None,
+            username: "viewer".to_string(),
+            role: "viewer".to_string(),
+        };
+        let err = update_oui_db(State(state.clone()), Some(Extension(viewer)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+        let err = update_oui_db(State(state), None).await.unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)));
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
     extract::{ConnectInfo, State},
-    http::{header, StatusCode},
+    http::header,
     response::{IntoResponse, Response},
     Json,
 };
@@ -8,12 +8,17 @@ use serde::Deserialize;
 use std::net::SocketAddr;
 use tracing::info;
 
-use super::AppState;
+use super::users::ROLE_ADMIN;
+use super::{AppError, AppState};
 
 /// Request body for initial setup.
 #[derive(Debug, Deserialize)]
 pub struct SetupRequest {
     pub password: String,
+    /// Role of the initial account; it manages everything else, so only
+    /// `admin` is accepted. Viewers are added later via `POST /users`.
+    #[serde(default)]
+    pub role: Option<String>,
     pub vyos_url: Option<String>,
     pub vyos_api_key: Option<String>,
 }
@@ -26,44 +31,40 @@ pub async fn setup(
     State(state): State<AppState>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     Json(body): Json<SetupRequest>,
-) -> Result<Response, Response> {
+) -> Result<Response, AppError> {
     // Check if setup has already been completed (password already exists).
     let already_set: bool = sqlx::query("SELECT 1 FROM settings WHERE key = 'admin_password_hash'")
         .fetch_optional(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to query settings: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?
+        .await?
         .is_some();
 
     if already_set {
-        return Err((StatusCode::CONFLICT, "Setup already completed").into_response());
+        return Err(AppError::Conflict("Setup already completed".to_string()));
     }
 
     // Validate password.
     if body.password.len() < 8 {
-        return Err((
-            StatusCode::UNPROCESSABLE_ENTITY,
-            "Password must be at least 8 characters",
-        )
-            .into_response());
+        return Err(AppError::UnprocessableEntity(
+            "Password must be at least 8 characters".to_string(),
+        ));
+    }
+
+    if let Some(role) = body.role.as_deref() {
+        if role != ROLE_ADMIN {
+            return Err(AppError::UnprocessableEntity(
+                "The initial account must have the admin role".to_string(),
+            ));
+        }
     }
 
     // Hash and store the admin password.
-    let hash = bcrypt::hash(&body.password, bcrypt::DEFAULT_COST).map_err(|e| {
-        tracing::error!("Failed to hash password: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR.into_response()
-    })?;
+    let hash = bcrypt::hash(&body.password, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {e}")))?;
 
     sqlx::query("INSERT INTO settings (key, value) VALUES ('admin_password_hash', ?)")
         .bind(&hash)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to store password: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?;
+        .await?;
 
     // Store optional VyOS settings.
     if let Some(ref url) = body.vyos_url {
@@ -73,7 +74,7 @@ pub async fn setup(
     }
     if let Some(ref key) = body.vyos_api_key {
         if !key.is_empty() {
-            upsert_setting(&state, "vyos_api_key", key).await?;
+            upsert_setting(&state, "vyos_api_key", &crate::crypto::encrypt(key)).await?;
         }
     }
 
@@ -84,18 +85,14 @@ pub async fn setup(
 
     // Auto-login: create a session so the user doesn't have to log in immediately.
     let token = uuid::Uuid::new_v4().to_string();
-    let expiry_secs = state.config.auth.session_expiry_seconds.max(1);
+    let expiry_secs = state.config().auth.session_expiry_seconds.max(1);
     let expiry_modifier = format!("+{expiry_secs} seconds");
 
     sqlx::query("INSERT INTO sessions (token, expires_at) VALUES (?, datetime('now', ?))")
         .bind(&token)
         .bind(&expiry_modifier)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to create session: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
-        })?;
+        .await?;
 
     let cookie = format!(
         "panoptikon_session={token}; HttpOnly; SameSite=Lax; Path=/; Max-Age={expiry_secs}"
@@ -114,7 +111,7 @@ pub async fn setup(
 }
 
 /// Helper to upsert a setting.
-async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(), Response> {
+async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(), AppError> {
     sqlx::query(
         "INSERT INTO settings (key, value) VALUES (?, ?) \
          ON CONFLICT(key) DO UPDATE SET value = excluded.value",
@@ -122,10 +119,6 @@ async fn upsert_setting(state: &AppState, key: &str, value: &str) -> Result<(),
     .bind(key)
     .bind(value)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to save setting '{key}': {e}");
-        StatusCode::INTERNAL_SERVER_ERROR.into_response()
-    })?;
+    .await?;
     Ok(())
 }
@@ -1,8 +1,14 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use std::collections::HashMap;
+use std::fmt::Write;
 
-use super::AppState;
+use super::{AppError, AppState};
 
 /// A single node's persisted position.
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,16 +22,12 @@ pub struct NodePosition {
 /// GET /api/v1/topology/positions — return all saved node positions.
 pub async fn get_positions(
     State(state): State<AppState>,
-) -> Result<Json<Vec<NodePosition>>, StatusCode> {
+) -> Result<Json<Vec<NodePosition>>, AppError> {
     let rows = sqlx::query_as::<_, (String, f64, f64, i32)>(
         "SELECT node_id, x, y, pinned FROM topology_positions",
     )
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        error!("Failed to fetch topology positions: {e}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    .await?;
 
     let positions = rows
         .into_iter()
@@ -50,7 +52,7 @@ pub struct SavePositionsRequest {
 pub async fn save_positions(
     State(state): State<AppState>,
     Json(body): Json<SavePositionsRequest>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     for pos in &body.positions {
         sqlx::query(
             "INSERT INTO topology_positions (node_id, x, y, pinned) VALUES (?, ?, ?, ?)
@@ -61,25 +63,677 @@ pub async fn save_positions(
         .bind(pos.y)
         .bind(pos.pinned as i32)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to save topology position for '{}': {e}", pos.node_id);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
     }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 /// DELETE /api/v1/topology/positions — clear all saved positions (reset layout).
-pub async fn delete_positions(State(state): State<AppState>) -> Result<StatusCode, StatusCode> {
+pub async fn delete_positions(State(state): State<AppState>) -> Result<StatusCode, AppError> {
     sqlx::query("DELETE FROM topology_positions")
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            error!("Failed to clear topology positions: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+// ── Auto Layout ─────────────────────────────────────────────────────────────
+
+/// Node ID of the router hub the frontend draws every device attached to.
+pub const ROUTER_NODE_ID: &str = "router";
+
+/// Iterations of the force simulation.
+const LAYOUT_ITERATIONS: usize = 200;
+
+/// Only flows from this window count as adjacency.
+const LAYOUT_FLOW_WINDOW: &str = "-1 day";
+
+/// Lay out a graph with the Fruchterman-Reingold force-directed algorithm.
+///
+/// `edges` index into `node_ids`. Nodes start evenly spaced on a circle so
+/// the result is deterministic; the layout is centred on the origin within a
+/// square canvas that grows with the node count. All positions are unpinned.
+pub fn force_directed_layout(node_ids: &[String], edges: &[(usize, usize)]) -> Vec<NodePosition> {
+    let n = node_ids.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let side = (150.0 * (n as f64).sqrt()).max(800.0);
+    let half = side / 2.0;
+    let k = (side * side / n as f64).sqrt();
+
+    let mut pos: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+            (half / 2.0 * angle.cos(), half / 2.0 * angle.sin())
+        })
+        .collect();
+
+    let initial_temp = side / 10.0;
+    for iter in 0..LAYOUT_ITERATIONS {
+        let mut disp = vec![(0.0_f64, 0.0_f64); n];
+
+        // Every pair of nodes repels.
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let (dx, dy, dist) = delta(pos[i], pos[j]);
+                let force = k * k / dist;
+                let (fx, fy) = (dx / dist * force, dy / dist * force);
+                disp[i].0 += fx;
+                disp[i].1 += fy;
+                disp[j].0 -= fx;
+                disp[j].1 -= fy;
+            }
+        }
+
+        // Connected nodes attract.
+        for &(a, b) in edges {
+            if a == b || a >= n || b >= n {
+                continue;
+            }
+            let (dx, dy, dist) = delta(pos[a], pos[b]);
+            let force = dist * dist / k;
+            let (fx, fy) = (dx / dist * force, dy / dist * force);
+            disp[a].0 -= fx;
+            disp[a].1 -= fy;
+            disp[b].0 += fx;
+            disp[b].1 += fy;
+        }
+
+        // Move each node at most `temp`, cooling linearly, and keep it on the canvas.
+        let temp = initial_temp * (1.0 - iter as f64 / LAYOUT_ITERATIONS as f64);
+        for (p, (dx, dy)) in pos.iter_mut().zip(disp) {
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0.0 {
+                let step = len.min(temp);
+                p.0 = (p.0 + dx / len * step).clamp(-half, half);
+                p.1 = (p.1 + dy / len * step).clamp(-half, half);
+            }
+        }
+    }
+
+    node_ids
+        .iter()
+        .zip(pos)
+        .map(|(node_id, (x, y))| NodePosition {
+            node_id: node_id.clone(),
+            x,
+            y,
+            pinned: false,
+        })
+        .collect()
+}
+
+/// Vector from `b` to `a` and its length, nudged off zero so coincident
+/// nodes still push apart.
+fn delta(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (mut dx, dy) = (a.0 - b.0, a.1 - b.1);
+    if dx == 0.0 && dy == 0.0 {
+        dx = 0.01;
+    }
+    (dx, dy, (dx * dx + dy * dy).sqrt())
+}
+
+/// Load the topology graph: the router plus every device, with a router
+/// edge per device and an edge per device pair seen talking in NetFlow.
+async fn load_layout_graph(
+    db: &sqlx::SqlitePool,
+) -> Result<(Vec<String>, Vec<(usize, usize)>), sqlx::Error> {
+    let device_ids: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM devices WHERE is_deleted = 0 ORDER BY id")
+            .fetch_all(db)
+            .await?;
+    let pairs: Vec<(String, String)> = sqlx::query_as(
+        "SELECT DISTINCT s.device_id, d.device_id FROM netflow_flows f \
+         JOIN device_ips s ON s.ip = f.src_ip AND s.is_current = 1 \
+         JOIN device_ips d ON d.ip = f.dst_ip AND d.is_current = 1 \
+         WHERE s.device_id != d.device_id AND f.recorded_at >= datetime('now', ?)",
+    )
+    .bind(LAYOUT_FLOW_WINDOW)
+    .fetch_all(db)
+    .await?;
+
+    let mut node_ids = Vec::with_capacity(device_ids.len() + 1);
+    node_ids.push(ROUTER_NODE_ID.to_string());
+    node_ids.extend(device_ids);
+    let index: std::collections::HashMap<&str, usize> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    let mut edges: Vec<(usize, usize)> = (1..node_ids.len()).map(|i| (0, i)).collect();
+    let mut seen = std::collections::HashSet::new();
+    for (src, dst) in &pairs {
+        if let (Some(&a), Some(&b)) = (index.get(src.as_str()), index.get(dst.as_str())) {
+            if seen.insert((a.min(b), a.max(b))) {
+                edges.push((a, b));
+            }
+        }
+    }
+
+    Ok((node_ids, edges))
+}
+
+/// POST /api/v1/topology/auto-layout — compute (but do not save) a
+/// force-directed layout. Save it with `PUT /api/v1/topology/positions`.
+pub async fn auto_layout(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<NodePosition>>, AppError> {
+    let (node_ids, edges) = load_layout_graph(&state.db).await?;
+
+    let positions = tokio::task::spawn_blocking(move || force_directed_layout(&node_ids, &edges))
+        .await
+        .map_err(|e| AppError::Internal(format!("Topology layout task failed: {e}")))?;
+
+    Ok(Json(positions))
+}
+
+// ── Export ──────────────────────────────────────────────────────────────────
+
+/// Radius of a device circle in the SVG export.
+const EXPORT_NODE_RADIUS: f64 = 14.0;
+
+/// Margin around the outermost nodes in the SVG export.
+const EXPORT_PADDING: f64 = 80.0;
+
+/// Edge stroke width range; widths scale with log(bytes).
+const EXPORT_MIN_EDGE_WIDTH: f64 = 1.0;
+const EXPORT_MAX_EDGE_WIDTH: f64 = 8.0;
+
+/// A device in the exported graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportNode {
+    pub id: String,
+    pub label: String,
+    /// Colour group: `apple`, `windows`, `android`, `linux`, `network` or `other`.
+    pub group: &'static str,
+    pub ip: Option<String>,
+    pub vendor: Option<String>,
+    pub os_family: Option<String>,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Device pair that exchanged NetFlow traffic in the last 24 hours.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportLink {
+    pub source: String,
+    pub target: String,
+    pub bytes: i64,
+}
+
+/// Node-link graph (D3 compatible) returned by `?format=json`.
+#[derive(Debug, Serialize)]
+pub struct TopologyExport {
+    pub nodes: Vec<ExportNode>,
+    pub links: Vec<ExportLink>,
+}
+
+/// Query parameters for `GET /api/v1/topology/export.svg`.
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `svg` (default) or `json`.
+    pub format: Option<String>,
+}
+
+/// Colour group for a device, from its OS family and then its vendor.
+fn node_group(os_family: Option<&str>, vendor: Option<&str>) -> &'static str {
+    let os = os_family.unwrap_or("").to_ascii_lowercase();
+    let vendor = vendor.unwrap_or("").to_ascii_lowercase();
+    if os.contains("windows") {
+        "windows"
+    } else if os.contains("macos") || os.contains("ios") {
+        "apple"
+    } else if os.contains("android") {
+        "android"
+    } else if os.contains("linux") {
+        "linux"
+    } else if vendor.contains("apple") {
+        "apple"
+    } else if vendor.contains("microsoft") {
+        "windows"
+    } else if vendor.contains("raspberry") {
+        "linux"
+    } else if [
+        "cisco", "ubiquiti", "tp-link", "netgear", "mikrotik", "juniper", "aruba",
+    ]
+    .iter()
+    .any(|v| vendor.contains(v))
+    {
+        "network"
+    } else {
+        "other"
+    }
+}
+
+fn group_color(group: &str) -> &'static str {
+    match group {
+        "apple" => "#a855f7",
+        "windows" => "#3b82f6",
+        "android" => "#22c55e",
+        "linux" => "#f59e0b",
+        "network" => "#ef4444",
+        _ => "#64748b",
+    }
+}
+
+/// Stroke width for an edge carrying `bytes`, on a log scale up to `max_bytes`.
+fn edge_width(bytes: i64, max_bytes: i64) -> f64 {
+    let max = (max_bytes.max(1) as f64).ln_1p();
+    let ratio = (bytes.max(0) as f64).ln_1p() / max;
+    EXPORT_MIN_EDGE_WIDTH + (EXPORT_MAX_EDGE_WIDTH - EXPORT_MIN_EDGE_WIDTH) * ratio.min(1.0)
+}
+
+/// Escape text for SVG element content and attribute values.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Load online devices, their NetFlow links from the last 24 hours and
+/// positions: saved ones where available, a force-directed layout otherwise.
+async fn load_export_graph(db: &sqlx::SqlitePool) -> Result<TopologyExport, sqlx::Error> {
+    type DeviceRow = (
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    );
+    let devices: Vec<DeviceRow> = sqlx::query_as(
+        "SELECT d.id, d.name, d.hostname, d.vendor, d.os_family, \
+                (SELECT ip FROM device_ips WHERE device_id = d.id AND is_current = 1 \
+                 ORDER BY seen_at DESC LIMIT 1) \
+         FROM devices d WHERE d.is_online = 1 AND d.is_deleted = 0 ORDER BY d.id",
+    )
+    .fetch_all(db)
+    .await?;
+    let flows: Vec<(String, String, i64)> = sqlx::query_as(
+        "SELECT s.device_id, d.device_id, SUM(f.bytes) FROM netflow_flows f \
+         JOIN device_ips s ON s.ip = f.src_ip AND s.is_current = 1 \
+         JOIN device_ips d ON d.ip = f.dst_ip AND d.is_current = 1 \
+         WHERE s.device_id != d.device_id AND f.recorded_at >= datetime('now', ?) \
+         GROUP BY s.device_id, d.device_id",
+    )
+    .bind(LAYOUT_FLOW_WINDOW)
+    .fetch_all(db)
+    .await?;
+    let saved: HashMap<String, (f64, f64)> =
+        sqlx::query_as::<_, (String, f64, f64)>("SELECT node_id, x, y FROM topology_positions")
+            .fetch_all(db)
+            .await?
+            .into_iter()
+            .map(|(id, x, y)| (id, (x, y)))
+            .collect();
+
+    let index: HashMap<&str, usize> = devices
+        .iter()
+        .enumerate()
+        .map(|(i, d)| (d.0.as_str(), i))
+        .collect();
+
+    // Sum both directions into one undirected link per device pair.
+    let mut pair_bytes: HashMap<(usize, usize), i64> = HashMap::new();
+    for (src, dst, bytes) in &flows {
+        if let (Some(&a), Some(&b)) = (index.get(src.as_str()), index.get(dst.as_str())) {
+            *pair_bytes.entry((a.min(b), a.max(b))).or_insert(0) += bytes;
+        }
+    }
+    let mut pairs: Vec<((usize, usize), i64)> = pair_bytes.into_iter().collect();
+    pairs.sort();
+
+    let ids: Vec<String> = devices.iter().map(|d| d.0.clone()).collect();
+    let edges: Vec<(usize, usize)> = pairs.iter().map(|(pair, _)| *pair).collect();
+    let layout = if ids.iter().all(|id| saved.contains_key(id)) {
+        Vec::new()
+    } else {
+        force_directed_layout(&ids, &edges)
+    };
+
+    let nodes = devices
+        .into_iter()
+        .enumerate()
+        .map(|(i, (id, name, hostname, vendor, os_family, ip))| {
+            let (x, y) = saved
+                .get(&id)
+                .copied()
+                .unwrap_or_else(|| (layout[i].x, layout[i].y));
+            let label = [&name, &hostname, &ip]
+                .into_iter()
+                .flatten()
+                .find(|s| !s.trim().is_empty())
+                .cloned()
+                .unwrap_or_else(|| id.clone());
+            ExportNode {
+                group: node_group(os_family.as_deref(), vendor.as_deref()),
+                id,
+                label,
+                ip,
+                vendor,
+                os_family,
+                x,
+                y,
+            }
+        })
+        .collect::<Vec<_>>();
+    let links = pairs
+        .into_iter()
+        .map(|((a, b), bytes)| ExportLink {
+            source: nodes[a].id.clone(),
+            target: nodes[b].id.clone(),
+            bytes,
+        })
+        .collect();
+
+    Ok(TopologyExport { nodes, links })
+}
+
+/// Render the graph as a standalone SVG document.
+pub fn render_svg(graph: &TopologyExport) -> String {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0.0_f64, 0.0_f64, 0.0_f64, 0.0_f64);
+    for (i, node) in graph.nodes.iter().enumerate() {
+        if i == 0 {
+            (min_x, min_y, max_x, max_y) = (node.x, node.y, node.x, node.y);
+        }
+        min_x = min_x.min(node.x);
+        min_y = min_y.min(node.y);
+        max_x = max_x.max(node.x);
+        max_y = max_y.max(node.y);
+    }
+    let (left, top) = (min_x - EXPORT_PADDING, min_y - EXPORT_PADDING);
+    let width = max_x - min_x + 2.0 * EXPORT_PADDING;
+    let height = max_y - min_y + 2.0 * EXPORT_PADDING;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{left:.1} {top:.1} {width:.1} {height:.1}" width="{width:.0}" height="{height:.0}" font-family="sans-serif" font-size="12">"#
+    );
+    let _ = writeln!(svg, "<title>Panoptikon network topology</title>");
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{left:.1}" y="{top:.1}" width="{width:.1}" height="{height:.1}" fill="#ffffff"/>"##
+    );
+
+    let positions: HashMap<&str, (f64, f64)> = graph
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), (n.x, n.y)))
+        .collect();
+    let max_bytes = graph.links.iter().map(|l| l.bytes).max().unwrap_or(1);
+    let _ = writeln!(svg, r##"<g stroke="#94a3b8" stroke-opacity="0.7">"##);
+    for link in &graph.links {
+        let (Some(&(x1, y1)), Some(&(x2, y2))) = (
+            positions.get(link.source.as_str()),
+            positions.get(link.target.as_str()),
+        ) else {
+            continue;
+        };
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke-width="{:.2}"><title>{} bytes</title></line>"#,
+            edge_width(link.bytes, max_bytes),
+            link.bytes
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(svg, r#"<g text-anchor="middle">"#);
+    for node in &graph.nodes {
+        let label = xml_escape(&node.label);
+        let _ = writeln!(
+            svg,
+            r##"<circle cx="{:.1}" cy="{:.1}" r="{EXPORT_NODE_RADIUS}" fill="{}" stroke="#1e293b"><title>{label}</title></circle>"##,
+            node.x,
+            node.y,
+            group_color(node.group)
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{:.1}" y="{:.1}" fill="#1e293b">{label}</text>"##,
+            node.x,
+            node.y + EXPORT_NODE_RADIUS + 14.0
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// GET /api/v1/topology/export.svg — the topology of online devices as an
+/// SVG document, or as node-link JSON with `?format=json`.
+pub async fn export(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let format = query.format.as_deref().unwrap_or("svg");
+    if format != "svg" && format != "json" {
+        return Err(AppError::Validation(format!(
+            "Unsupported export format '{format}' (expected svg or json)"
+        )));
+    }
+
+    let graph = load_export_graph(&state.db).await?;
+
+    if format == "json" {
+        return Ok(Json(graph).into_response());
+    }
+    Ok((
+        [(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")],
+        render_svg(&graph),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("n{i}")).collect()
+    }
+
+    fn distance(a: &NodePosition, b: &NodePosition) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_force_directed_layout_empty_and_single() {
+        assert!(force_directed_layout(&[], &[]).is_empty());
+        let layout = force_directed_layout(&ids(1), &[]);
+        assert_eq!(layout.len(), 1);
+        assert!(layout[0].x.is_finite() && layout[0].y.is_finite());
+    }
+
+    #[test]
+    fn test_force_directed_layout_pulls_connected_nodes_together() {
+        // Two triangles joined by a single edge.
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)];
+        let layout = force_directed_layout(&ids(6), &edges);
+        assert_eq!(layout.len(), 6);
+        assert!(layout
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && !p.pinned));
+
+        let within = distance(&layout[0], &layout[1]);
+        let across = distance(&layout[0], &layout[4]);
+        assert!(
+            within < across,
+            "clustered nodes should sit closer ({within} vs {across})"
+        );
+        // Deterministic for the same input.
+        let again = force_directed_layout(&ids(6), &edges);
+        assert_eq!(layout[4].x, again[4].x);
+    }
+
+    #[tokio::test]
+    async fn test_load_layout_graph_uses_netflow_adjacency() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        for (id, mac, ip) in [
+            ("a", "aa:bb:cc:00:00:01", "10.0.0.1"),
+            ("b", "aa:bb:cc:00:00:02", "10.0.0.2"),
+            ("c", "aa:bb:cc:00:00:03", "10.0.0.3"),
+        ] {
+            sqlx::query(
+                "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) \
+                 VALUES (?, ?, datetime('now'), datetime('now'))",
+            )
+            .bind(id)
+            .bind(mac)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
+                 VALUES (?, ?, datetime('now'), 1)",
+            )
+            .bind(id)
+            .bind(ip)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        for (src, dst) in [("10.0.0.1", "10.0.0.2"), ("10.0.0.2", "10.0.0.1")] {
+            sqlx::query(
+                "INSERT INTO netflow_flows (src_ip, dst_ip, bytes, packets, recorded_at) \
+                 VALUES (?, ?, 100, 1, datetime('now'))",
+            )
+            .bind(src)
+            .bind(dst)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let (nodes, edges) = load_layout_graph(&pool).await.unwrap();
+        assert_eq!(nodes, vec!["router", "a", "b", "c"]);
+        // Three router edges plus one deduplicated a<->b edge.
+        assert_eq!(edges.len(), 4);
+        assert!(edges.contains(&(1, 2)));
+    }
+
+    #[test]
+    fn test_node_group_and_edge_width() {
+        assert_eq!(node_group(Some("iOS"), None), "apple");
+        assert_eq!(node_group(Some("Windows"), Some("Apple, Inc.")), "windows");
+        assert_eq!(node_group(None, Some("Ubiquiti Inc")), "network");
+        assert_eq!(node_group(None, None), "other");
+
+        assert_eq!(edge_width(1_000_000, 1_000_000), EXPORT_MAX_EDGE_WIDTH);
+        let small = edge_width(1_000, 1_000_000);
+        assert!(small > EXPORT_MIN_EDGE_WIDTH && small < EXPORT_MAX_EDGE_WIDTH);
+        // Log scale: 1000x less traffic is still half the width range.
+        assert!((small - 4.5).abs() < 0.1, "{small}");
+    }
+
+    #[tokio::test]
+    async fn test_export_svg_and_json() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        for (id, name, ip, online) in [
+            ("a", Some("NAS <main>"), "10.0.0.1", 1),
+            ("b", None, "10.0.0.2", 1),
+            ("c", None, "10.0.0.3", 0),
+        ] {
+            sqlx::query(
+                "INSERT INTO devices (id, mac, name, hostname, os_family, first_seen_at, \
+                 last_seen_at, is_online) \
+                 VALUES (?, ?, ?, ?, 'Linux', datetime('now'), datetime('now'), ?)",
+            )
+            .bind(id)
+            .bind(format!("aa:bb:cc:00:00:0{id}"))
+            .bind(name)
+            .bind(format!("host-{id}"))
+            .bind(online)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
+                 VALUES (?, ?, datetime('now'), 1)",
+            )
+            .bind(id)
+            .bind(ip)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        for (src, dst, bytes, at) in [
+            ("10.0.0.1", "10.0.0.2", 600, "-1 hours"),
+            ("10.0.0.2", "10.0.0.1", 400, "-2 hours"),
+            ("10.0.0.1", "10.0.0.2", 9999, "-2 days"),
+            ("10.0.0.1", "10.0.0.3", 100, "-1 hours"),
+        ] {
+            sqlx::query(
+                "INSERT INTO netflow_flows (src_ip, dst_ip, bytes, packets, recorded_at) \
+                 VALUES (?, ?, ?, 1, datetime('now', ?))",
+            )
+            .bind(src)
+            .bind(dst)
+            .bind(bytes)
+            .bind(at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query("INSERT INTO topology_positions (node_id, x, y) VALUES ('a', 10, 20)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let graph = load_export_graph(&pool).await.unwrap();
+        let labels: Vec<&str> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, vec!["NAS <main>", "host-b"]);
+        assert_eq!((graph.nodes[0].x, graph.nodes[0].y), (10.0, 20.0));
+        assert_eq!(graph.nodes[0].group, "linux");
+        // Offline device c and the stale flow are excluded; both directions summed.
+        assert_eq!(
+            graph.links,
+            vec![ExportLink {
+                source: "a".to_string(),
+                target: "b".to_string(),
+                bytes: 1000,
+            }]
+        );
+
+        let svg = render_svg(&graph);
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("NAS &lt;main&gt;"));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+
+        let state = AppState::new(pool, crate::config::AppConfig::default());
+        let resp = export(State(state.clone()), Query(ExportQuery { format: None }))
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.headers()[header::CONTENT_TYPE],
+            "image/svg+xml; charset=utf-8"
+        );
+        let resp = export(
+            State(state.clone()),
+            Query(ExportQuery {
+                format: Some("json".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+        let err = export(
+            State(state),
+            Query(ExportQuery {
+                format: Some("png".to_string()),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+}
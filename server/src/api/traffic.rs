@@ -1,9 +1,14 @@
-use crate::api::AppState;
+use crate::api::{AppError, AppState};
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     Json,
 };
+use ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::net::IpAddr;
+
+use crate::whois::{self, AsnInfo};
 
 #[derive(Serialize)]
 pub struct TrafficHistoryPoint {
@@ -53,9 +58,367 @@ pub async fn history(
     )
 }
 
+/// Query parameters for the per-device traffic totals endpoint.
+#[derive(Deserialize)]
+pub struct DeviceTrafficQuery {
+    /// One of `1h`, `24h`, `7d` (default `1h`).
+    pub window: Option<String>,
+}
+
+/// Response for `GET /api/v1/traffic/device/:id`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DeviceTrafficTotals {
+    pub device_id: String,
+    pub ingress_bytes: i64,
+    pub egress_bytes: i64,
+    pub ingress_packets: i64,
+    pub egress_packets: i64,
+    pub window: String,
+}
+
+/// Map a window name to a SQLite datetime modifier.
+fn window_modifier(window: &str) -> Option<&'static str> {
+    match window {
+        "1h" => Some("-1 hours"),
+        "24h" => Some("-24 hours"),
+        "7d" => Some("-7 days"),
+        _ => None,
+    }
+}
+
+/// Sum NetFlow bytes/packets to and from every IP the device has ever used.
+async fn device_traffic_totals(
+    db: &SqlitePool,
+    device_id: &str,
+    window: &str,
+) -> Result<DeviceTrafficTotals, AppError> {
+    let modifier = window_modifier(window).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid window '{window}'; expected one of 1h, 24h, 7d"
+        ))
+    })?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM devices WHERE id = ?")
+        .bind(device_id)
+        .fetch_optional(db)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::ResourceNotFound(
+            "device",
+            "Device not found".to_string(),
+        ));
+    }
+
+    // Historical IPs count too, so traffic from before a DHCP change is kept.
+    // Each direction is filtered on its own column so the (ip, recorded_at)
+    // indexes are used.
+    let (ingress_bytes, ingress_packets): (i64, i64) = sqlx::query_as(
+        r#"SELECT COALESCE(SUM(bytes), 0), COALESCE(SUM(packets), 0)
+           FROM netflow_flows
+           WHERE dst_ip IN (SELECT ip FROM device_ips WHERE device_id = ?)
+             AND recorded_at >= datetime('now', ?)"#,
+    )
+    .bind(device_id)
+    .bind(modifier)
+    .fetch_one(db)
+    .await?;
+
+    let (egress_bytes, egress_packets): (i64, i64) = sqlx::query_as(
+        r#"SELECT COALESCE(SUM(bytes), 0), COALESCE(SUM(packets), 0)
+           FROM netflow_flows
+           WHERE src_ip IN (SELECT ip FROM device_ips WHERE device_id = ?)
+             AND recorded_at >= datetime('now', ?)"#,
+    )
+    .bind(device_id)
+    .bind(modifier)
+    .fetch_one(db)
+    .await?;
+
+    Ok(DeviceTrafficTotals {
+        device_id: device_id.to_string(),
+        ingress_bytes,
+        egress_bytes,
+        ingress_packets,
+        egress_packets,
+        window: window.to_string(),
+    })
+}
+
+/// GET /api/v1/traffic/device/:id?window=1h|24h|7d
+///
+/// Returns NetFlow byte and packet totals received (ingress) and sent
+/// (egress) by a device over the window.
+pub async fn device_totals(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(q): Query<DeviceTrafficQuery>,
+) -> Result<Json<DeviceTrafficTotals>, AppError> {
+    let window = q.window.as_deref().unwrap_or("1h");
+    Ok(Json(device_traffic_totals(&state.db, &id, window).await?))
+}
+
+/// Maximum number of top talkers returned.
+const MAX_TOP_TALKERS: i64 = 100;
+
+/// Query parameters for the top talkers endpoint.
+#[derive(Deserialize)]
+pub struct TopTalkersQuery {
+    /// One of `1h`, `24h`, `7d` (default `1h`).
+    pub window: Option<String>,
+    /// Number of results (default 20, max 100).
+    pub limit: Option<i64>,
+    /// Rank by `src` (default) or `dst` IP.
+    pub by: Option<String>,
+    /// Add ASN data for external IPs (default false).
+    pub enrich: Option<bool>,
+}
+
+/// One IP in the top talkers list, with its device if the IP is current.
+#[derive(Debug, Serialize, PartialEq, sqlx::FromRow)]
+pub struct TopTalker {
+    pub ip: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    pub bytes: i64,
+    pub packets: i64,
+    pub flow_count: i64,
+    /// Origin AS of an external IP; only with `enrich=true`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asn: Option<AsnInfo>,
+}
+
+/// Rank source or destination IPs by NetFlow bytes over the window.
+async fn load_top_talkers(
+    db: &SqlitePool,
+    window: &str,
+    limit: i64,
+    by: &str,
+) -> Result<Vec<TopTalker>, AppError> {
+    let modifier = window_modifier(window).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid window '{window}'; expected one of 1h, 24h, 7d"
+        ))
+    })?;
+    let column = match by {
+        "src" => "src_ip",
+        "dst" => "dst_ip",
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Invalid by '{by}'; expected src or dst"
+            )))
+        }
+    };
+
+    let sql = format!(
+        r#"SELECT t.ip, di.device_id, d.name AS alias, d.hostname,
+                  t.bytes, t.packets, t.flow_count
+           FROM (SELECT {column} AS ip, SUM(bytes) AS bytes, SUM(packets) AS packets,
+                        COUNT(*) AS flow_count
+                 FROM netflow_flows
+                 WHERE recorded_at >= datetime('now', ?)
+                 GROUP BY {column}
+                 ORDER BY bytes DESC
+                 LIMIT ?) t
+           LEFT JOIN device_ips di ON di.rowid = (
+               SELECT rowid FROM device_ips WHERE ip = t.ip AND is_current = 1 LIMIT 1)
+           LEFT JOIN devices d ON d.id = di.device_id
+           ORDER BY t.bytes DESC"#
+    );
+    Ok(sqlx::query_as(&sql)
+        .bind(modifier)
+        .bind(limit.clamp(1, MAX_TOP_TALKERS))
+        .fetch_all(db)
+        .await?)
+}
+
+/// GET /api/v1/traffic/top-talkers?window=1h&limit=20&by=src|dst
+///
+/// Returns the IPs that sent (`by=src`) or received (`by=dst`) the most
+/// NetFlow bytes over the window, with the owning device when known.
+pub async fn top_talkers(
+    State(state): State<AppState>,
+    Query(q): Query<TopTalkersQuery>,
+) -> Result<Json<Vec<TopTalker>>, AppError> {
+    let window = q.window.as_deref().unwrap_or("1h");
+    let by = q.by.as_deref().unwrap_or("src");
+    let limit = q.limit.unwrap_or(20);
+    let mut talkers = load_top_talkers(&state.db, window, limit, by).await?;
+    if q.enrich.unwrap_or(false) {
+        for talker in talkers.iter_mut().filter(|t| t.device_id.is_none()) {
+            if let Ok(ip) = talker.ip.parse::<IpAddr>() {
+                talker.asn = whois::cached_lookup(&state.asn_cache, ip).await;
+            }
+        }
+    }
+    Ok(Json(talkers))
+}
+
+/// Maximum number of conversation pairs returned.
+const MAX_CONVERSATIONS: i64 = 100;
+
+/// Query parameters for the conversations endpoint.
+#[derive(Deserialize)]
+pub struct ConversationsQuery {
+    /// One of `1h`, `24h`, `7d` (default `1h`).
+    pub window: Option<String>,
+    /// Number of pairs (default 20, max 100).
+    pub limit: Option<i64>,
+}
+
+/// Where a conversation runs relative to the configured scanner subnets.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Both ends are in configured subnets.
+    Intra,
+    /// Only the destination is in a configured subnet.
+    Inbound,
+    /// Only the source is in a configured subnet.
+    Outbound,
+    /// Neither end is in a configured subnet (routed through).
+    #[default]
+    Transit,
+}
+
+/// Classify a pair by which ends fall in `subnets`.
+fn classify_direction(subnets: &[IpNetwork], src_ip: &str, dst_ip: &str) -> Direction {
+    let internal = |ip: &str| {
+        ip.parse::<IpAddr>()
+            .is_ok_and(|ip| subnets.iter().any(|net| net.contains(ip)))
+    };
+    match (internal(src_ip), internal(dst_ip)) {
+        (true, true) => Direction::Intra,
+        (false, true) => Direction::Inbound,
+        (true, false) => Direction::Outbound,
+        (false, false) => Direction::Transit,
+    }
+}
+
+/// One (source, destination) pair with its NetFlow totals over the window.
+#[derive(Debug, Serialize, PartialEq, sqlx::FromRow)]
+pub struct Conversation {
+    pub src_ip: String,
+    pub dst_ip: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src_device_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst_device_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub src_alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dst_alias: Option<String>,
+    pub bytes: i64,
+    pub packets: i64,
+    pub flow_count: i64,
+    #[sqlx(skip)]
+    pub direction: Direction,
+}
+
+/// Rank (src_ip, dst_ip) pairs by NetFlow bytes over the window.
+async fn load_conversations(
+    db: &SqlitePool,
+    window: &str,
+    limit: i64,
+    subnets: &[IpNetwork],
+) -> Result<Vec<Conversation>, AppError> {
+    let modifier = window_modifier(window).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid window '{window}'; expected one of 1h, 24h, 7d"
+        ))
+    })?;
+
+    let mut conversations: Vec<Conversation> = sqlx::query_as(
+        r#"SELECT t.src_ip, t.dst_ip,
+                  si.device_id AS src_device_id, di.device_id AS dst_device_id,
+                  sd.name AS src_alias, dd.name AS dst_alias,
+                  t.bytes, t.packets, t.flow_count
+           FROM (SELECT src_ip, dst_ip, SUM(bytes) AS bytes, SUM(packets) AS packets,
+                        COUNT(*) AS flow_count
+                 FROM netflow_flows
+                 WHERE recorded_at >= datetime('now', ?)
+                 GROUP BY src_ip, dst_ip
+                 ORDER BY bytes DESC
+                 LIMIT ?) t
+           LEFT JOIN device_ips si ON si.rowid = (
+               SELECT rowid FROM device_ips WHERE ip = t.src_ip AND is_current = 1 LIMIT 1)
+           LEFT JOIN devices sd ON sd.id = si.device_id
+           LEFT JOIN device_ips di ON di.rowid = (
+               SELECT rowid FROM device_ips WHERE ip = t.dst_ip AND is_current = 1 LIMIT 1)
+           LEFT JOIN devices dd ON dd.id = di.device_id
+           ORDER BY t.bytes DESC"#,
+    )
+    .bind(modifier)
+    .bind(limit.clamp(1, MAX_CONVERSATIONS))
+    .fetch_all(db)
+    .await?;
+
+    for conversation in &mut conversations {
+        conversation.direction =
+            classify_direction(subnets, &conversation.src_ip, &conversation.dst_ip);
+    }
+    Ok(conversations)
+}
+
+/// GET /api/v1/traffic/conversations?window=1h&limit=20
+///
+/// Returns the (source, destination) pairs that exchanged the most NetFlow
+/// bytes over the window, with their devices and direction relative to the
+/// configured scanner subnets.
+pub async fn conversations(
+    State(state): State<AppState>,
+    Query(q): Query<ConversationsQuery>,
+) -> Result<Json<Vec<Conversation>>, AppError> {
+    let window = q.window.as_deref().unwrap_or("1h");
+    let limit = q.limit.unwrap_or(20);
+    let subnets: Vec<IpNetwork> = state
+        .config()
+        .scanner
+        .subnets
+        .iter()
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+    Ok(Json(
+        load_conversations(&state.db, window, limit, &subnets).await?,
+    ))
+}
+
+/// Query parameters for the IP enrichment endpoint.
+#[derive(Deserialize)]
+pub struct EnrichQuery {
+    pub ip: String,
+}
+
+/// GET /api/v1/traffic/enrich?ip=1.2.3.4
+///
+/// Returns the origin AS of a public IP (Team Cymru), cached for 24 hours.
+pub async fn enrich(
+    State(state): State<AppState>,
+    Query(q): Query<EnrichQuery>,
+) -> Result<Json<AsnInfo>, AppError> {
+    let ip: IpAddr =
+        q.ip.trim()
+            .parse()
+            .map_err(|_| AppError::Validation(format!("Invalid IP address '{}'", q.ip)))?;
+    if !whois::is_external(ip) {
+        return Err(AppError::Validation(format!(
+            "{ip} is not a public address"
+        )));
+    }
+    whois::cached_lookup(&state.asn_cache, ip)
+        .await
+        .map(Json)
+        .ok_or_else(|| AppError::ResourceNotFound("asn", format!("No ASN data for {ip}")))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::db;
+    use ipnetwork::IpNetwork;
     use sqlx::SqlitePool;
 
     /// Helper: create a fresh in-memory database with all migrations applied.
@@ -191,4 +554,178 @@ mod tests {
             rows[1].0
         );
     }
+
+    /// Helper: insert a flow total recorded `age` ago (SQLite modifier).
+    async fn insert_flow(pool: &SqlitePool, src: &str, dst: &str, bytes: i64, age: &str) {
+        sqlx::query(
+            r#"INSERT INTO netflow_flows (src_ip, dst_ip, bytes, packets, recorded_at)
+               VALUES (?, ?, ?, 1, datetime('now', ?))"#,
+        )
+        .bind(src)
+        .bind(dst)
+        .bind(bytes)
+        .bind(age)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_device_traffic_totals_counts_historical_ips() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool).await;
+        for (ip, current) in [("10.0.0.5", 1), ("10.0.0.9", 0)] {
+            sqlx::query(
+                "INSERT INTO device_ips (device_id, ip, seen_at, is_current) VALUES (?, ?, datetime('now'), ?)",
+            )
+            .bind(&device_id)
+            .bind(ip)
+            .bind(current)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        insert_flow(&pool, "10.0.0.5", "1.1.1.1", 100, "-5 minutes").await;
+        insert_flow(&pool, "1.1.1.1", "10.0.0.5", 1000, "-5 minutes").await;
+        // Old address from before a DHCP change.
+        insert_flow(&pool, "1.1.1.1", "10.0.0.9", 500, "-10 minutes").await;
+        // Outside the 1h window, inside 24h.
+        insert_flow(&pool, "10.0.0.5", "8.8.8.8", 40, "-3 hours").await;
+        // Unrelated device.
+        insert_flow(&pool, "10.0.0.77", "1.1.1.1", 9999, "-1 minutes").await;
+
+        let hour = super::device_traffic_totals(&pool, &device_id, "1h")
+            .await
+            .unwrap();
+        assert_eq!(hour.ingress_bytes, 1500);
+        assert_eq!(hour.egress_bytes, 100);
+        assert_eq!(hour.ingress_packets, 2);
+        assert_eq!(hour.egress_packets, 1);
+
+        let day = super::device_traffic_totals(&pool, &device_id, "24h")
+            .await
+            .unwrap();
+        assert_eq!(day.egress_bytes, 140);
+        assert_eq!(day.window, "24h");
+    }
+
+    #[tokio::test]
+    async fn test_device_traffic_totals_rejects_bad_window_and_unknown_device() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool).await;
+
+        let err = super::device_traffic_totals(&pool, &device_id, "2w")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::api::AppError::Validation(_)));
+
+        let err = super::device_traffic_totals(&pool, "missing", "1h")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::api::AppError::ResourceNotFound(..)));
+    }
+
+    #[tokio::test]
+    async fn test_top_talkers_by_src_and_dst() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool).await;
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current) VALUES (?, '10.0.0.5', datetime('now'), 1)",
+        )
+        .bind(&device_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        insert_flow(&pool, "10.0.0.5", "1.1.1.1", 700, "-5 minutes").await;
+        insert_flow(&pool, "10.0.0.5", "8.8.8.8", 300, "-10 minutes").await;
+        insert_flow(&pool, "10.0.0.7", "1.1.1.1", 200, "-1 minutes").await;
+        // Outside the 1h window.
+        insert_flow(&pool, "10.0.0.7", "1.1.1.1", 5000, "-2 hours").await;
+
+        let top = super::load_top_talkers(&pool, "1h", 20, "src")
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].ip, "10.0.0.5");
+        assert_eq!(top[0].bytes, 1000);
+        assert_eq!(top[0].flow_count, 2);
+        assert_eq!(top[0].device_id.as_deref(), Some(device_id.as_str()));
+        assert_eq!(top[0].alias.as_deref(), Some("test-device"));
+        assert_eq!(top[1].device_id, None);
+
+        let top = super::load_top_talkers(&pool, "24h", 1, "dst")
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].ip, "1.1.1.1");
+        assert_eq!(top[0].bytes, 5900);
+
+        let err = super::load_top_talkers(&pool, "1h", 20, "both")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::api::AppError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_enrich_rejects_private_and_invalid_ips() {
+        let state = crate::api::AppState::new(test_db().await, crate::config::AppConfig::default());
+        for ip in ["10.0.0.5", "not-an-ip"] {
+            let err = super::enrich(
+                axum::extract::State(state.clone()),
+                axum::extract::Query(super::EnrichQuery { ip: ip.to_string() }),
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(err, crate::api::AppError::Validation(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conversations_pairs_and_direction() {
+        let pool = test_db().await;
+        let device_id = insert_test_device(&pool).await;
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current) VALUES (?, '10.0.0.5', datetime('now'), 1)",
+        )
+        .bind(&device_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        insert_flow(&pool, "10.0.0.5", "1.1.1.1", 700, "-5 minutes").await;
+        insert_flow(&pool, "10.0.0.5", "1.1.1.1", 100, "-3 minutes").await;
+        insert_flow(&pool, "10.0.0.7", "10.0.0.5", 500, "-1 minutes").await;
+        insert_flow(&pool, "8.8.8.8", "10.0.0.7", 300, "-1 minutes").await;
+        insert_flow(&pool, "8.8.8.8", "9.9.9.9", 200, "-1 minutes").await;
+
+        let subnets: Vec<IpNetwork> = vec!["10.0.0.0/24".parse().unwrap()];
+        let pairs = super::load_conversations(&pool, "1h", 20, &subnets)
+            .await
+            .unwrap();
+        let summary: Vec<_> = pairs
+            .iter()
+            .map(|c| (c.src_ip.as_str(), c.dst_ip.as_str(), c.bytes, c.direction))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("10.0.0.5", "1.1.1.1", 800, super::Direction::Outbound),
+                ("10.0.0.7", "10.0.0.5", 500, super::Direction::Intra),
+                ("8.8.8.8", "10.0.0.7", 300, super::Direction::Inbound),
+                ("8.8.8.8", "9.9.9.9", 200, super::Direction::Transit),
+            ]
+        );
+        assert_eq!(pairs[0].flow_count, 2);
+        assert_eq!(pairs[0].src_device_id.as_deref(), Some(device_id.as_str()));
+        assert_eq!(pairs[0].src_alias.as_deref(), Some("test-device"));
+        assert_eq!(pairs[0].dst_device_id, None);
+        assert_eq!(pairs[1].dst_device_id.as_deref(), Some(device_id.as_str()));
+
+        let top = super::load_conversations(&pool, "1h", 1, &subnets)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+    }
 }
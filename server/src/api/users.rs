@@ -0,0 +1,219 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::audit::Actor;
+use super::{audit, AppError, AppState};
+
+/// Username of the built-in admin account (stored in settings, not `users`).
+pub const ADMIN_USERNAME: &str = "admin";
+/// Full access.
+pub const ROLE_ADMIN: &str = "admin";
+/// Read-only access: GET endpoints plus `auth/*`.
+pub const ROLE_VIEWER: &str = "viewer";
+
+/// Maximum username length.
+const MAX_USERNAME_LEN: usize = 32;
+/// Minimum password length (matches setup and change-password).
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// A named login account (the password hash is never returned).
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct User {
+    pub id: String,
+    pub username: String,
+    pub role: String,
+    pub created_at: String,
+}
+
+/// Request body for creating a user.
+#[derive(Debug, Deserialize)]
+pub struct CreateUser {
+    pub username: String,
+    pub password: String,
+    /// Defaults to `viewer`.
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// Validate a role name.
+pub fn validate_role(role: &str) -> Result<(), AppError> {
+    if role == ROLE_ADMIN || role == ROLE_VIEWER {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "role must be '{ROLE_ADMIN}' or '{ROLE_VIEWER}'"
+        )))
+    }
+}
+
+/// Usernames are 1-32 characters of `[a-z0-9._-]`; `admin` is reserved.
+fn validate_username(username: &str) -> Result<(), AppError> {
+    let valid_chars = username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'));
+    if username.is_empty() || username.len() > MAX_USERNAME_LEN || !valid_chars {
+        return Err(AppError::Validation(format!(
+            "username must be 1-{MAX_USERNAME_LEN} characters of a-z, 0-9, '.', '_' or '-'"
+        )));
+    }
+    if username == ADMIN_USERNAME {
+        return Err(AppError::Validation(format!(
+            "username '{ADMIN_USERNAME}' is reserved"
+        )));
+    }
+    Ok(())
+}
+
+/// GET /api/v1/users — list named accounts.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<User>>, AppError> {
+    let users: Vec<User> =
+        sqlx::query_as("SELECT id, username, role, created_at FROM users ORDER BY username")
+            .fetch_all(&state.db)
+            .await?;
+    Ok(Json(users))
+}
+
+/// POST /api/v1/users — create an account (admin only; viewer by default).
+pub async fn create(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<CreateUser>,
+) -> Result<(StatusCode, Json<User>), AppError> {
+    let username = body.username.trim();
+    validate_username(username)?;
+    let role = body.role.as_deref().unwrap_or(ROLE_VIEWER);
+    validate_role(role)?;
+    if body.password.len() < MIN_PASSWORD_LEN {
+        return Err(AppError::Validation(format!(
+            "password must be at least {MIN_PASSWORD_LEN} characters"
+        )));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM users WHERE username = ?")
+        .bind(username)
+        .fetch_one(&state.db)
+        .await?;
+    if exists {
+        return Err(AppError::Validation(format!(
+            "username '{username}' is already taken"
+        )));
+    }
+
+    let hash = bcrypt::hash(&body.password, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::Internal(format!("Failed to hash password: {e}")))?;
+    let id = uuid::Uuid::new_v4().to_string();
+
+    sqlx::query("INSERT INTO users (id, username, password_hash, role) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(username)
+        .bind(&hash)
+        .bind(role)
+        .execute(&state.db)
+        .await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "user_create",
+        &format!("Created {role} account '{username}'"),
+        &[],
+    )
+    .await;
+
+    let user: User =
+        sqlx::query_as("SELECT id, username, role, created_at FROM users WHERE id = ?")
+            .bind(&id)
+            .fetch_one(&state.db)
+            .await?;
+
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    fn body(username: &str, role: Option<&str>) -> Json<CreateUser> {
+        Json(CreateUser {
+            username: username.to_string(),
+            password: "viewerpass".to_string(),
+            role: role.map(str::to_string),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_users() {
+        let state = test_state().await;
+
+        let (status, Json(user)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            body("noc", None),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(user.role, ROLE_VIEWER);
+
+        let (_, Json(ops)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            body("ops", Some("admin")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(ops.role, ROLE_ADMIN);
+
+        let Json(users) = list(State(state.clone())).await.unwrap();
+        let names: Vec<&str> = users.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(names, vec!["noc", "ops"]);
+
+        // Duplicates are rejected.
+        assert!(create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            body("noc", None)
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_user_validation() {
+        let state = test_state().await;
+        for (username, role) in [
+            ("admin", None),
+            ("Bad Name", None),
+            ("", None),
+            ("noc", Some("root")),
+        ] {
+            assert!(
+                create(
+                    State(state.clone()),
+                    Actor("admin".to_string()),
+                    body(username, role)
+                )
+                .await
+                .is_err(),
+                "{username:?} / {role:?} should be rejected"
+            );
+        }
+
+        let short = Json(CreateUser {
+            username: "noc".to_string(),
+            password: "short".to_string(),
+            role: None,
+        });
+        assert!(
+            create(State(state.clone()), Actor("admin".to_string()), short)
+                .await
+                .is_err()
+        );
+    }
+}
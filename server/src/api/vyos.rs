@@ -1,15 +1,21 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
     Json,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
 
-use super::audit;
-use super::AppState;
+use super::audit::{self, Actor};
+use super::{router_profiles, AppError, AppState};
+use crate::notification::telegram;
 
 // ── Parsed VyOS route ───────────────────────────────────
 
@@ -305,18 +311,116 @@ pub struct RouterStatus {
     pub hostname: Option<String>,
 }
 
+// ── Read Cache ──────────────────────────────────────────
+
+/// Cache keys for the slow read endpoints, named after the endpoint.
+pub const CACHE_STATUS: &str = "status";
+pub const CACHE_INTERFACES: &str = "interfaces";
+pub const CACHE_ROUTES: &str = "routes";
+pub const CACHE_DHCP: &str = "dhcp";
+pub const CACHE_FIREWALL: &str = "firewall";
+
+const X_CACHE: &str = "x-cache";
+
+/// Serialized responses of the slow read endpoints keyed by endpoint name.
+/// Each lookup passes its TTL so a config reload applies immediately.
+#[derive(Clone, Default)]
+pub struct VyosCache(Arc<dashmap::DashMap<&'static str, (Instant, Value)>>);
+
+impl VyosCache {
+    /// Return the cached value if it is younger than `ttl`.
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<Value> {
+        let entry = self.0.get(key)?;
+        let (cached_at, value) = entry.value();
+        (cached_at.elapsed() < ttl).then(|| value.clone())
+    }
+
+    pub fn insert(&self, key: &'static str, value: Value) {
+        self.0.insert(key, (Instant::now(), value));
+    }
+
+    /// Drop the given keys after a write changed the data behind them.
+    pub fn invalidate(&self, keys: &[&str]) {
+        for key in keys {
+            self.0.remove(*key);
+        }
+    }
+
+    /// Drop every entry, returning how many there were.
+    pub fn clear(&self) -> usize {
+        let count = self.0.len();
+        self.0.clear();
+        count
+    }
+}
+
+fn cache_ttl(state: &AppState, key: &str) -> Duration {
+    let config = state.config();
+    let ttl = &config.vyos.cache_ttl;
+    Duration::from_secs(match key {
+        CACHE_STATUS => ttl.status,
+        CACHE_INTERFACES => ttl.interfaces,
+        CACHE_ROUTES => ttl.routes,
+        CACHE_DHCP => ttl.dhcp,
+        CACHE_FIREWALL => ttl.firewall,
+        _ => 0,
+    })
+}
+
+/// Serve `key` from the cache if fresh, otherwise run `fetch` and cache its
+/// result. The `X-Cache` header says which happened.
+async fn cached_read<T, E, F>(state: &AppState, key: &'static str, fetch: F) -> Result<Response, E>
+where
+    T: Serialize,
+    F: std::future::Future<Output = Result<(T, bool), E>>,
+{
+    let ttl = cache_ttl(state, key);
+    if let Some(value) = state.vyos_cache.get(key, ttl) {
+        return Ok(([(X_CACHE, "HIT")], Json(value)).into_response());
+    }
+    let (value, cacheable) = fetch.await?;
+    if cacheable && !ttl.is_zero() {
+        if let Ok(json) = serde_json::to_value(&value) {
+            state.vyos_cache.insert(key, json);
+        }
+    }
+    Ok(([(X_CACHE, "MISS")], Json(value)).into_response())
+}
+
+/// POST /api/v1/vyos/cache/invalidate — flush all cached router reads.
+pub async fn invalidate_cache(State(state): State<AppState>) -> Json<Value> {
+    let invalidated = state.vyos_cache.clear();
+    Json(serde_json::json!({ "invalidated": invalidated }))
+}
+
 /// GET /api/v1/vyos/status — check if VyOS is configured and reachable.
-pub async fn status(State(state): State<AppState>) -> Json<RouterStatus> {
-    let client = match get_vyos_client_from_db(&state.db, &state.config).await {
+///
+/// Only reachable results are cached, so a router coming back online shows
+/// up on the next poll.
+pub async fn status(State(state): State<AppState>) -> Response {
+    let fetch = async {
+        let status = fetch_status(&state).await;
+        let reachable = status.reachable;
+        Ok::<_, Infallible>((status, reachable))
+    };
+    match cached_read(&state, CACHE_STATUS, fetch).await {
+        Ok(response) => response,
+        Err(never) => match never {},
+    }
+}
+
+async fn fetch_status(state: &AppState) -> RouterStatus {
+    let config = state.config().clone();
+    let client = match get_vyos_client_from_db(&state.db, &config, &state.active_profile_id).await {
         Some(c) => c,
         None => {
-            return Json(RouterStatus {
+            return RouterStatus {
                 configured: false,
                 reachable: false,
                 version: None,
                 uptime: None,
                 hostname: None,
-            });
+            };
         }
     };
 
@@ -348,47 +452,643 @@ pub async fn status(State(state): State<AppState>) -> Json<RouterStatus> {
 
     let reachable = version.is_some() || uptime.is_some();
 
-    Json(RouterStatus {
+    RouterStatus {
         configured: true,
         reachable,
         version,
         uptime,
         hostname,
-    })
+    }
 }
 
 /// GET /api/v1/vyos/interfaces — fetch VyOS interface information (parsed).
 ///
 /// Calls `show interfaces` on VyOS, parses the tabular text output, and returns
 /// a JSON array of [`VyosInterface`] objects.
-pub async fn interfaces(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<VyosInterface>>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
-    let raw_value = client.show(&["interfaces"]).await.map_err(|e| {
-        tracing::error!("VyOS interfaces query failed: {e}");
-        StatusCode::BAD_GATEWAY
-    })?;
+pub async fn interfaces(State(state): State<AppState>) -> Result<Response, AppError> {
+    cached_read(&state, CACHE_INTERFACES, async {
+        let client = get_vyos_client(&state).await?;
+        let raw_value = client.show(&["interfaces"]).await.map_err(|e| {
+            tracing::error!("VyOS interfaces query failed: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
 
-    let text = raw_value.as_str().unwrap_or("");
-    let parsed = parse_interfaces_text(text);
-    Ok(Json(parsed))
+        let text = raw_value.as_str().unwrap_or("");
+        Ok((parse_interfaces_text(text), true))
+    })
+    .await
 }
 
 /// GET /api/v1/vyos/routes — fetch VyOS routing table (parsed).
 ///
 /// Calls `show ip route` on VyOS, parses the text output, and returns
 /// a JSON array of [`VyosRoute`] objects.
-pub async fn routes(State(state): State<AppState>) -> Result<Json<Vec<VyosRoute>>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
-    let raw_value = client.show(&["ip", "route"]).await.map_err(|e| {
-        tracing::error!("VyOS routes query failed: {e}");
-        StatusCode::BAD_GATEWAY
+pub async fn routes(State(state): State<AppState>) -> Result<Response, AppError> {
+    cached_read(&state, CACHE_ROUTES, async {
+        let client = get_vyos_client(&state).await?;
+        let raw_value = client.show(&["ip", "route"]).await.map_err(|e| {
+            tracing::error!("VyOS routes query failed: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+
+        let text = raw_value.as_str().unwrap_or("");
+        Ok((parse_routes_text(text), true))
+    })
+    .await
+}
+
+// ── RIB Summary ─────────────────────────────────────────
+
+/// Route count for a single protocol in the RIB.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RibEntry {
+    pub protocol: String,
+    pub routes: u64,
+}
+
+/// RIB prefix counts by protocol for one address family.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RibSummary {
+    pub address_family: String,
+    pub rib_entries: Vec<RibEntry>,
+    pub total: u64,
+}
+
+/// Query parameters for the RIB summary endpoint.
+#[derive(Debug, Deserialize)]
+pub struct RibSummaryQuery {
+    /// `ipv4` (default) or `ipv6`.
+    pub family: Option<String>,
+}
+
+/// Protocols always reported, even when they contribute no routes.
+const RIB_CORE_PROTOCOLS: &[&str] = &["bgp", "connected", "static", "ospf"];
+
+/// Parse the text output of `show ip route summary` into a [`RibSummary`].
+///
+/// Expected format:
+/// ```text
+/// Route Source         Routes               FIB  (vrf default)
+/// kernel               1                    1
+/// connected            4                    4
+/// static               3                    3
+/// ebgp                 12340                12340
+/// ibgp                 5                    5
+/// ------
+/// Totals               12353                12353
+/// ```
+///
+/// `ebgp` and `ibgp` are folded into a single `bgp` entry.
+pub fn parse_rib_summary_text(text: &str, address_family: &str) -> RibSummary {
+    let mut counts: Vec<(String, u64)> = Vec::new();
+    let mut totals: Option<u64> = None;
+
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(source), Some(routes)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Ok(routes) = routes.parse::<u64>() else {
+            continue; // header or separator line
+        };
+
+        let source = source.to_lowercase();
+        if source == "totals" {
+            totals = Some(routes);
+            continue;
+        }
+        let protocol = match source.as_str() {
+            "ebgp" | "ibgp" => "bgp".to_string(),
+            other => other.to_string(),
+        };
+        match counts.iter_mut().find(|(p, _)| *p == protocol) {
+            Some((_, n)) => *n += routes,
+            None => counts.push((protocol, routes)),
+        }
+    }
+
+    for proto in RIB_CORE_PROTOCOLS {
+        if !counts.iter().any(|(p, _)| p == proto) {
+            counts.push((proto.to_string(), 0));
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let total = totals.unwrap_or_else(|| counts.iter().map(|(_, n)| n).sum());
+
+    RibSummary {
+        address_family: address_family.to_string(),
+        rib_entries: counts
+            .into_iter()
+            .map(|(protocol, routes)| RibEntry { protocol, routes })
+            .collect(),
+        total,
+    }
+}
+
+/// GET /api/v1/vyos/bgp/rib/summary — RIB route counts by protocol.
+///
+/// Calls `show ip route summary` (or `show ipv6 route summary` with
+/// `?family=ipv6`). A zero `bgp` count on a BGP router usually means the
+/// session is down.
+pub async fn rib_summary(
+    State(state): State<AppState>,
+    Query(params): Query<RibSummaryQuery>,
+) -> Result<Json<RibSummary>, AppError> {
+    let family = params.family.as_deref().unwrap_or("ipv4");
+    let path: &[&str] = match family {
+        "ipv4" => &["ip", "route", "summary"],
+        "ipv6" => &["ipv6", "route", "summary"],
+        other => {
+            return Err(AppError::Validation(format!(
+                "Unknown address family '{other}' (expected ipv4 or ipv6)"
+            )))
+        }
+    };
+
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client.show(path).await.map_err(|e| {
+        tracing::error!("VyOS route summary query failed: {e}");
+        AppError::BadGateway(format!("VyOS error: {e}"))
+    })?;
+
+    let text = raw_value.as_str().unwrap_or("");
+    Ok(Json(parse_rib_summary_text(text, family)))
+}
+
+// ── BGP peer summary ────────────────────────────────────
+
+/// A BGP neighbor from `show ip bgp summary`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BgpPeer {
+    pub neighbor: String,
+    pub remote_as: u32,
+    /// "Established" when prefixes are being exchanged, otherwise the FSM
+    /// state FRR reports (e.g. "Active", "Idle (Admin)").
+    pub state: String,
+    /// Session up/down time (e.g. "1d02h03m"); `None` if never established.
+    pub uptime: Option<String>,
+    pub prefixes_received: u32,
+}
+
+/// Parse the text output of `show ip bgp summary` into a vec of [`BgpPeer`].
+///
+/// Expected format (FRR):
+/// ```text
+/// BGP router identifier 10.0.0.1, local AS number 65001 vrf-id 0
+/// Neighbor        V         AS   MsgRcvd   MsgSent   TblVer  InQ OutQ  Up/Down State/PfxRcd   PfxSnt Desc
+/// 10.0.0.2        4      65002      1234      1230        0    0    0 1d02h03m           12       15 N/A
+/// 10.0.0.3        4      65003         0         0        0    0    0    never       Active        0 N/A
+/// ```
+///
+/// The State/PfxRcd column holds a prefix count once the session is
+/// established and the FSM state otherwise. Long IPv6 neighbor addresses
+/// wrap onto their own line and are joined with the following one.
+/// Output containing "not configured" yields no peers.
+pub fn parse_bgp_summary_text(text: &str) -> Vec<BgpPeer> {
+    if text.contains("not configured") {
+        return Vec::new();
+    }
+
+    let mut peers = Vec::new();
+    let mut in_table = false;
+    let mut wrapped_neighbor: Option<&str> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("Neighbor") {
+            in_table = true;
+            continue;
+        }
+        if !in_table || trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.starts_with("Total number") {
+            in_table = false;
+            continue;
+        }
+
+        let mut fields: Vec<&str> = trimmed.split_whitespace().collect();
+        if fields.len() == 1 {
+            wrapped_neighbor = Some(fields[0]);
+            continue;
+        }
+        if let Some(neighbor) = wrapped_neighbor.take() {
+            fields.insert(0, neighbor);
+        }
+
+        // Neighbor V AS MsgRcvd MsgSent TblVer InQ OutQ Up/Down State/PfxRcd ...
+        if fields.len() < 10 {
+            continue;
+        }
+        let Ok(remote_as) = fields[2].parse::<u32>() else {
+            continue;
+        };
+        let uptime = (fields[8] != "never").then(|| fields[8].to_string());
+        let (state, prefixes_received) = match fields[9].parse::<u32>() {
+            Ok(count) => ("Established".to_string(), count),
+            Err(_) => {
+                let state = match fields.get(10) {
+                    Some(qualifier) if qualifier.starts_with('(') => {
+                        format!("{} {}", fields[9], qualifier)
+                    }
+                    _ => fields[9].to_string(),
+                };
+                (state, 0)
+            }
+        };
+
+        peers.push(BgpPeer {
+            neighbor: fields[0].to_string(),
+            remote_as,
+            state,
+            uptime,
+            prefixes_received,
+        });
+    }
+
+    peers
+}
+
+/// GET /api/v1/vyos/bgp/summary — BGP neighbor state and received prefixes.
+///
+/// Returns an empty array when BGP is not configured on the router.
+pub async fn bgp_summary(State(state): State<AppState>) -> Result<Json<Vec<BgpPeer>>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client.show(&["ip", "bgp", "summary"]).await.map_err(|e| {
+        tracing::error!("VyOS BGP summary query failed: {e}");
+        AppError::BadGateway(format!("VyOS error: {e}"))
+    })?;
+
+    let text = raw_value.as_str().unwrap_or("");
+    Ok(Json(parse_bgp_summary_text(text)))
+}
+
+// ── OSPF neighbors & routes ─────────────────────────────
+
+/// An OSPF adjacency from `show ip ospf neighbor`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OspfNeighbor {
+    /// Router ID of the neighbor.
+    pub neighbor_id: String,
+    pub priority: u32,
+    /// Adjacency state and role (e.g. "Full/DR", "2-Way/DROther").
+    pub state: String,
+    /// Time until the neighbor is declared dead (e.g. "35.123s").
+    pub dead_time: String,
+    /// Neighbor interface address.
+    pub address: String,
+    /// Local interface name (e.g. "eth0").
+    pub interface: String,
+}
+
+/// True when FRR reports that OSPF is not running.
+fn ospf_not_configured(text: &str) -> bool {
+    text.contains("not configured")
+        || text.contains("OSPF instance not found")
+        || text.contains("OSPF not enabled")
+}
+
+/// Parse the text output of `show ip ospf neighbor` into a vec of [`OspfNeighbor`].
+///
+/// Expected format (FRR; older releases omit the "Up Time" column):
+/// ```text
+/// Neighbor ID     Pri State           Up Time         Dead Time Address         Interface                        RXmtL RqstL DBsmL
+/// 10.0.0.2          1 Full/DR         1h02m03s          35.123s 10.0.0.2        eth0:10.0.0.1                        0     0     0
+/// ```
+///
+/// The local address suffix of the interface column is dropped.
+pub fn parse_ospf_neighbor_text(text: &str) -> Vec<OspfNeighbor> {
+    if ospf_not_configured(text) {
+        return Vec::new();
+    }
+
+    let mut neighbors = Vec::new();
+    // Index of the "Dead Time" field; `None` until the header is seen.
+    let mut dead_idx: Option<usize> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("Neighbor ID") {
+            dead_idx = Some(if trimmed.contains("Up Time") { 4 } else { 3 });
+            continue;
+        }
+        let Some(dead_idx) = dead_idx else {
+            continue;
+        };
+
+        let fields: Vec<&str> = trimmed.split_whitespace().collect();
+        if fields.len() < dead_idx + 3 {
+            continue;
+        }
+        let Ok(priority) = fields[1].parse::<u32>() else {
+            continue;
+        };
+        let interface = fields[dead_idx + 2];
+        let interface = interface
+            .split_once(':')
+            .map_or(interface, |(name, _)| name);
+
+        neighbors.push(OspfNeighbor {
+            neighbor_id: fields[0].to_string(),
+            priority,
+            state: fields[2].to_string(),
+            dead_time: fields[dead_idx].to_string(),
+            address: fields[dead_idx + 1].to_string(),
+            interface: interface.to_string(),
+        });
+    }
+
+    neighbors
+}
+
+/// GET /api/v1/vyos/ospf/neighbors — OSPF adjacency state.
+///
+/// Returns an empty array when OSPF is not configured on the router.
+pub async fn ospf_neighbors(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<OspfNeighbor>>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client
+        .show(&["ip", "ospf", "neighbor"])
+        .await
+        .map_err(|e| {
+            tracing::error!("VyOS OSPF neighbor query failed: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+
+    let text = raw_value.as_str().unwrap_or("");
+    Ok(Json(parse_ospf_neighbor_text(text)))
+}
+
+/// GET /api/v1/vyos/ospf/routes — OSPF-learned routes from the RIB.
+///
+/// Calls `show ip route ospf` and reuses [`parse_routes_text`]. Returns an
+/// empty array when OSPF is not configured.
+pub async fn ospf_routes(State(state): State<AppState>) -> Result<Json<Vec<VyosRoute>>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client.show(&["ip", "route", "ospf"]).await.map_err(|e| {
+        tracing::error!("VyOS OSPF routes query failed: {e}");
+        AppError::BadGateway(format!("VyOS error: {e}"))
     })?;
 
     let text = raw_value.as_str().unwrap_or("");
-    let parsed = parse_routes_text(text);
-    Ok(Json(parsed))
+    if ospf_not_configured(text) {
+        return Ok(Json(Vec::new()));
+    }
+    let routes = parse_routes_text(text)
+        .into_iter()
+        .filter(|r| r.protocol == "O")
+        .collect();
+    Ok(Json(routes))
+}
+
+// ── IPsec ───────────────────────────────────────────────
+
+/// An IPsec security association from `show vpn ipsec sa`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IpsecTunnel {
+    /// Remote peer address (the connection name if no address is shown).
+    pub peer: String,
+    /// SA state, e.g. "up" or "down".
+    pub state: String,
+    /// Local traffic selector(s) from the site-to-site config, comma-separated.
+    pub local_subnet: String,
+    /// Remote traffic selector(s) from the site-to-site config, comma-separated.
+    pub remote_subnet: String,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// SA uptime as reported by VyOS (e.g. "1h2m3s"); `None` when down.
+    pub uptime: Option<String>,
+    /// Connection name (e.g. "branch-tunnel-0"); used to match the config.
+    pub connection: String,
+}
+
+/// Parse a human-readable byte count ("1.2K", "3.40 MB", "512B", "1024")
+/// using binary multiples. Returns 0 for anything unparseable.
+fn parse_human_bytes(value: &str) -> u64 {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let Ok(number) = number.parse::<f64>() else {
+        return 0;
+    };
+    let exponent = match unit.trim().chars().next().map(|c| c.to_ascii_uppercase()) {
+        None | Some('B') => 0,
+        Some('K') => 1,
+        Some('M') => 2,
+        Some('G') => 3,
+        Some('T') => 4,
+        Some('P') => 5,
+        Some(_) => return 0,
+    };
+    (number * 1024f64.powi(exponent)).round() as u64
+}
+
+/// Parse a human-readable count ("1.2M", "35K", "812") using decimal
+/// multiples, as VyOS abbreviates packet counters. Returns 0 for anything
+/// unparseable.
+fn parse_human_count(value: &str) -> u64 {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let Ok(number) = number.parse::<f64>() else {
+        return 0;
+    };
+    let exponent = match unit.trim().chars().next().map(|c| c.to_ascii_uppercase()) {
+        None => 0,
+        Some('K') => 1,
+        Some('M') => 2,
+        Some('G') => 3,
+        Some('T') => 4,
+        Some(_) => return 0,
+    };
+    (number * 1000f64.powi(exponent)).round() as u64
+}
+
+/// Parse the text output of `show vpn ipsec sa` into a vec of [`IpsecTunnel`].
+///
+/// Expected format (VyOS 1.3+):
+/// ```text
+/// Connection          State    Uptime    Bytes In/Out    Packets In/Out    Remote address    Remote ID    Proposal
+/// ------------------  -------  --------  --------------  ----------------  ----------------  -----------  ------------------------
+/// branch-tunnel-0     up       1h2m3s    1.2K/3.4K       12/34             192.0.2.1         192.0.2.1    AES_CBC_256/HMAC_SHA2_256
+/// ```
+///
+/// Values may contain spaces (e.g. "1.21 KB/3.4 KB"), so columns are cut at
+/// the positions of the dash separator line rather than split on whitespace.
+/// Subnets are not part of this output; see [`apply_ipsec_prefixes`].
+pub fn parse_ipsec_sa_text(text: &str) -> Vec<IpsecTunnel> {
+    let lines: Vec<&str> = text.lines().collect();
+    let Some(sep_idx) = lines.iter().position(|l| {
+        let t = l.trim();
+        !t.is_empty() && t.chars().all(|c| c == '-' || c == ' ')
+    }) else {
+        return Vec::new();
+    };
+    let Some(header) = sep_idx.checked_sub(1).map(|i| lines[i]) else {
+        return Vec::new();
+    };
+
+    // Column spans from the runs of dashes.
+    let sep = lines[sep_idx];
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in sep.char_indices() {
+        match (c, start) {
+            ('-', None) => start = Some(i),
+            (' ', Some(s)) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, sep.len()));
+    }
+    // The last column runs to the end of the line.
+    if let Some(last) = spans.last_mut() {
+        last.1 = usize::MAX;
+    }
+
+    let cut = |line: &str, (start, end): (usize, usize)| -> String {
+        line.get(start.min(line.len())..end.min(line.len()))
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    let columns: Vec<String> = spans
+        .iter()
+        .map(|&span| cut(header, span).to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| columns.iter().position(|c| c == name);
+    let (Some(conn_idx), Some(state_idx)) = (column("connection"), column("state")) else {
+        return Vec::new();
+    };
+    let uptime_idx = column("uptime");
+    let bytes_idx = column("bytes in/out");
+    let remote_idx = column("remote address");
+
+    let mut tunnels = Vec::new();
+    for line in &lines[sep_idx + 1..] {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<String> = spans.iter().map(|&span| cut(line, span)).collect();
+        let connection = fields[conn_idx].clone();
+        if connection.is_empty() {
+            continue;
+        }
+        let field = |idx: Option<usize>| idx.map(|i| fields[i].as_str()).unwrap_or("");
+
+        let (bytes_in, bytes_out) = field(bytes_idx)
+            .split_once('/')
+            .map(|(i, o)| (parse_human_bytes(i), parse_human_bytes(o)))
+            .unwrap_or((0, 0));
+        let uptime = Some(field(uptime_idx))
+            .filter(|u| !u.is_empty() && !matches!(*u, "-" | "N/A"))
+            .map(str::to_string);
+        let peer = Some(field(remote_idx))
+            .filter(|p| !p.is_empty() && !matches!(*p, "-" | "N/A"))
+            .unwrap_or(&connection)
+            .to_string();
+
+        tunnels.push(IpsecTunnel {
+            peer,
+            state: fields[state_idx].clone(),
+            local_subnet: String::new(),
+            remote_subnet: String::new(),
+            bytes_in,
+            bytes_out,
+            uptime,
+            connection,
+        });
+    }
+
+    tunnels
+}
+
+/// Fill `local_subnet` / `remote_subnet` from the `vpn ipsec` config.
+///
+/// Connections are named `<peer>-tunnel-<n>`, matching
+/// `site-to-site peer <peer> tunnel <n> {local,remote} prefix`.
+pub fn apply_ipsec_prefixes(tunnels: &mut [IpsecTunnel], config: &Value) {
+    fn prefixes(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => String::new(),
+        }
+    }
+
+    let peers = &config["site-to-site"]["peer"];
+    for tunnel in tunnels {
+        let Some((peer, number)) = tunnel.connection.rsplit_once("-tunnel-") else {
+            continue;
+        };
+        let tunnel_config = &peers[peer]["tunnel"][number];
+        tunnel.local_subnet = prefixes(&tunnel_config["local"]["prefix"]);
+        tunnel.remote_subnet = prefixes(&tunnel_config["remote"]["prefix"]);
+    }
+}
+
+/// Retrieve the `vpn ipsec` config, or `Null` if IPsec is not configured.
+async fn retrieve_ipsec_config(
+    client: &crate::vyos::client::VyosClient,
+) -> Result<Value, AppError> {
+    match client.retrieve(&["vpn", "ipsec"]).await {
+        Ok(config) => Ok(config),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                return Ok(Value::Null);
+            }
+            tracing::error!("VyOS IPsec config query failed: {e}");
+            Err(AppError::BadGateway(format!("VyOS error: {e}")))
+        }
+    }
+}
+
+/// GET /api/v1/vyos/ipsec/tunnels — IPsec SA state and traffic counters.
+///
+/// Returns an empty array when IPsec is not configured on the router.
+pub async fn ipsec_tunnels(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<IpsecTunnel>>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client.show(&["vpn", "ipsec", "sa"]).await.map_err(|e| {
+        tracing::error!("VyOS IPsec SA query failed: {e}");
+        AppError::BadGateway(format!("VyOS error: {e}"))
+    })?;
+
+    let mut tunnels = parse_ipsec_sa_text(raw_value.as_str().unwrap_or(""));
+    if !tunnels.is_empty() {
+        let config = retrieve_ipsec_config(&client).await?;
+        apply_ipsec_prefixes(&mut tunnels, &config);
+    }
+    Ok(Json(tunnels))
+}
+
+/// GET /api/v1/vyos/ipsec/config — structured `vpn ipsec` configuration.
+///
+/// Returns an empty object when IPsec is not configured.
+pub async fn ipsec_config(State(state): State<AppState>) -> Result<Json<Value>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let config = retrieve_ipsec_config(&client).await?;
+    Ok(Json(if config.is_null() {
+        serde_json::json!({})
+    } else {
+        config
+    }))
 }
 
 // ── Parsed VyOS DHCP lease ──────────────────────────────
@@ -562,21 +1262,117 @@ pub fn parse_dhcp_leases_text(text: &str) -> Vec<VyosDhcpLease> {
 /// Calls `show dhcp server leases` on VyOS, parses the tabular text output,
 /// and returns a JSON array of [`VyosDhcpLease`] objects.
 /// If DHCP is not configured, returns an empty array (not an error).
-pub async fn dhcp_leases(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<VyosDhcpLease>>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
-    let raw_value = client
+pub async fn dhcp_leases(State(state): State<AppState>) -> Result<Response, AppError> {
+    cached_read(&state, CACHE_DHCP, async {
+        let client = get_vyos_client(&state).await?;
+        let raw_value = client
+            .show(&["dhcp", "server", "leases"])
+            .await
+            .map_err(|e| {
+                tracing::error!("VyOS DHCP leases query failed: {e}");
+                AppError::BadGateway(format!("VyOS error: {e}"))
+            })?;
+
+        let text = raw_value.as_str().unwrap_or("");
+        Ok((parse_dhcp_leases_text(text), true))
+    })
+    .await
+}
+
+// ── Expiring DHCP Leases ────────────────────────────────
+
+/// A DHCP lease together with its remaining lifetime in seconds.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExpiringDhcpLease {
+    #[serde(flatten)]
+    pub lease: VyosDhcpLease,
+    pub remaining_secs: i64,
+}
+
+/// Query parameters for the expiring leases endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ExpiringLeasesQuery {
+    pub within_hours: Option<i64>,
+}
+
+/// Parse a `remaining` column value such as `01:45:00` into seconds.
+///
+/// Hours may exceed 24 (e.g. `36:00:00`).
+pub fn parse_lease_remaining(value: &str) -> Option<i64> {
+    let mut parts = value.trim().split(':');
+    let hours: i64 = parts.next()?.trim().parse().ok()?;
+    let minutes: i64 = parts.next()?.trim().parse().ok()?;
+    let seconds: i64 = parts.next().map_or(Some(0), |s| s.trim().parse().ok())?;
+    if parts.next().is_some()
+        || hours < 0
+        || !(0..60).contains(&minutes)
+        || !(0..60).contains(&seconds)
+    {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Seconds until a lease expires.
+///
+/// Uses the `remaining` column when present and falls back to
+/// `lease_expiry` (`2026/02/21 22:00:00`, router time taken as UTC).
+pub fn lease_remaining_secs(lease: &VyosDhcpLease, now: DateTime<Utc>) -> Option<i64> {
+    if let Some(secs) = lease.remaining.as_deref().and_then(parse_lease_remaining) {
+        return Some(secs);
+    }
+    let expiry = chrono::NaiveDateTime::parse_from_str(
+        lease.lease_expiry.as_deref()?.trim(),
+        "%Y/%m/%d %H:%M:%S",
+    )
+    .ok()?
+    .and_utc();
+    Some((expiry - now).num_seconds())
+}
+
+/// Leases that expire within `within_secs` from `now`, soonest first.
+pub fn filter_expiring_leases(
+    leases: Vec<VyosDhcpLease>,
+    within_secs: i64,
+    now: DateTime<Utc>,
+) -> Vec<ExpiringDhcpLease> {
+    let mut expiring: Vec<ExpiringDhcpLease> = leases
+        .into_iter()
+        .filter_map(|lease| {
+            let remaining_secs = lease_remaining_secs(&lease, now)?;
+            (0..=within_secs)
+                .contains(&remaining_secs)
+                .then_some(ExpiringDhcpLease {
+                    lease,
+                    remaining_secs,
+                })
+        })
+        .collect();
+    expiring.sort_by_key(|l| l.remaining_secs);
+    expiring
+}
+
+/// GET /api/v1/vyos/dhcp/leases/expiring?within_hours=24 — leases about to expire.
+pub async fn dhcp_leases_expiring(
+    State(state): State<AppState>,
+    Query(params): Query<ExpiringLeasesQuery>,
+) -> Result<Json<Vec<ExpiringDhcpLease>>, AppError> {
+    let within_hours = params.within_hours.unwrap_or(24).clamp(1, 168);
+    let client = get_vyos_client(&state).await?;
+    let raw_value = client
         .show(&["dhcp", "server", "leases"])
         .await
         .map_err(|e| {
             tracing::error!("VyOS DHCP leases query failed: {e}");
-            StatusCode::BAD_GATEWAY
+            AppError::BadGateway(format!("VyOS error: {e}"))
         })?;
 
-    let text = raw_value.as_str().unwrap_or("");
-    let parsed = parse_dhcp_leases_text(text);
-    Ok(Json(parsed))
+    let leases = parse_dhcp_leases_text(raw_value.as_str().unwrap_or(""));
+    Ok(Json(filter_expiring_leases(
+        leases,
+        within_hours * 3600,
+        Utc::now(),
+    )))
 }
 
 // ── Parsed VyOS Firewall Config ─────────────────────────
@@ -803,25 +1599,408 @@ fn capitalize(s: &str) -> String {
 /// Calls `showConfig` for the `firewall` path on VyOS, parses the JSON config
 /// into structured chains and rules, and returns a [`FirewallConfig`].
 /// If no firewall is configured, returns an empty chains list.
-pub async fn firewall(State(state): State<AppState>) -> Result<Json<FirewallConfig>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
-    // Firewall may not be configured — that's OK, return empty config
-    match client.retrieve(&["firewall"]).await {
-        Ok(data) => {
-            let config = parse_firewall_config(&data);
-            Ok(Json(config))
+pub async fn firewall(State(state): State<AppState>) -> Result<Response, AppError> {
+    cached_read(&state, CACHE_FIREWALL, async {
+        let client = get_vyos_client(&state).await?;
+        // Firewall may not be configured — that's OK, return empty config
+        match client.retrieve(&["firewall"]).await {
+            Ok(data) => Ok((parse_firewall_config(&data), true)),
+            Err(e) => {
+                let msg = e.to_string();
+                // VyOS returns error when path is empty (no firewall configured)
+                if msg.contains("empty") || msg.contains("does not exist") {
+                    Ok((FirewallConfig { chains: Vec::new() }, true))
+                } else {
+                    tracing::error!("VyOS firewall query failed: {e}");
+                    Err(AppError::BadGateway(format!("VyOS error: {e}")))
+                }
+            }
+        }
+    })
+    .await
+}
+
+// ── Firewall Chain List ─────────────────────────────────────────────────────
+
+/// How long the chain list is served from cache.
+const FIREWALL_CHAINS_CACHE_SECS: u64 = 30;
+
+/// A firewall chain without its rules, for chain selectors.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FirewallChainSummary {
+    pub name: String,
+    /// VyOS config path joined with dots, e.g. "ipv4.forward.filter".
+    pub path: String,
+    pub default_action: String,
+    pub rule_count: usize,
+}
+
+/// Response for `GET /api/v1/vyos/firewall/chains`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FirewallChainsResponse {
+    pub chains: Vec<FirewallChainSummary>,
+}
+
+/// Project parsed firewall chains into summaries, dropping the rules.
+fn summarize_firewall_chains(config: FirewallConfig) -> Vec<FirewallChainSummary> {
+    config
+        .chains
+        .into_iter()
+        .map(|chain| FirewallChainSummary {
+            rule_count: chain.rules.len(),
+            path: chain.path.join("."),
+            name: chain.name,
+            default_action: chain.default_action,
+        })
+        .collect()
+}
+
+/// GET /api/v1/vyos/firewall/chains — list firewall chains without their rules.
+pub async fn firewall_chains(
+    State(state): State<AppState>,
+) -> Result<Json<FirewallChainsResponse>, AppError> {
+    let mut cache = state.firewall_chains_cache.lock().await;
+    if let Some((fetched_at, ref cached)) = *cache {
+        if fetched_at.elapsed().as_secs() < FIREWALL_CHAINS_CACHE_SECS {
+            return Ok(Json(cached.clone()));
         }
+    }
+
+    let client = get_vyos_client(&state).await?;
+    let config = match client.retrieve(&["firewall"]).await {
+        Ok(data) => parse_firewall_config(&data),
         Err(e) => {
             let msg = e.to_string();
-            // VyOS returns error when path is empty (no firewall configured)
             if msg.contains("empty") || msg.contains("does not exist") {
-                Ok(Json(FirewallConfig { chains: Vec::new() }))
+                FirewallConfig { chains: Vec::new() }
             } else {
-                tracing::error!("VyOS firewall query failed: {e}");
-                Err(StatusCode::BAD_GATEWAY)
+                tracing::error!("VyOS firewall chains query failed: {e}");
+                return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+            }
+        }
+    };
+
+    let response = FirewallChainsResponse {
+        chains: summarize_firewall_chains(config),
+    };
+    *cache = Some((Instant::now(), response.clone()));
+    Ok(Json(response))
+}
+
+// ── Firewall Hit Top-N ──────────────────────────────────────────────────────
+
+/// How long a hit top-N result is served from cache.
+const FIREWALL_HIT_TOP_CACHE_SECS: u64 = 60;
+
+/// Cached hit top-N responses keyed by `chain|limit|period`.
+pub type FirewallHitTopCache = Arc<Mutex<HashMap<String, (Instant, FirewallHitTopResponse)>>>;
+
+/// Query parameters for the hit top-N endpoint.
+#[derive(Debug, Deserialize)]
+pub struct FirewallHitTopQuery {
+    pub chain: Option<String>,
+    pub limit: Option<i64>,
+    pub period: Option<String>,
+}
+
+/// A firewall rule ranked by packet hits over the requested period.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FirewallRuleHits {
+    pub chain: String,
+    pub rule_number: i64,
+    pub hits: i64,
+    pub action: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Response for `GET /api/v1/vyos/firewall/hit-topN`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FirewallHitTopResponse {
+    pub top_rules: Vec<FirewallRuleHits>,
+}
+
+/// Convert a period like `24h`, `7d` or `30m` into an SQLite datetime modifier.
+///
+/// Periods are capped at 30 days.
+fn period_to_sqlite_modifier(period: &str) -> Option<String> {
+    let period = period.trim();
+    let (num, unit) = period.split_at(period.len().checked_sub(1)?);
+    let num: i64 = num.parse().ok().filter(|n| *n > 0)?;
+    let minutes = match unit {
+        "m" => num,
+        "h" => num.checked_mul(60)?,
+        "d" => num.checked_mul(1440)?,
+        _ => return None,
+    };
+    Some(format!("-{} minutes", minutes.min(30 * 1440)))
+}
+
+/// Sum packet deltas per rule from `firewall_rule_stats` and return the top `limit`.
+async fn query_rule_hits(
+    db: &SqlitePool,
+    chain: Option<&str>,
+    limit: i64,
+    modifier: &str,
+) -> Result<Vec<FirewallRuleHits>, sqlx::Error> {
+    let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"SELECT chain, rule_number, SUM(MAX(packets - prev_packets, 0)) AS delta
+           FROM firewall_rule_stats
+           WHERE sampled_at > datetime('now', ?)
+             AND (? IS NULL OR chain = ?)
+           GROUP BY chain, rule_number
+           ORDER BY delta DESC, chain ASC, rule_number ASC
+           LIMIT ?"#,
+    )
+    .bind(modifier)
+    .bind(chain)
+    .bind(chain)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(chain, rule_number, hits)| FirewallRuleHits {
+            chain,
+            rule_number,
+            hits,
+            action: None,
+            description: None,
+        })
+        .collect())
+}
+
+/// Fill in each rule's action and description from the live firewall config.
+fn annotate_rule_hits(hits: &mut [FirewallRuleHits], config: &FirewallConfig) {
+    for hit in hits.iter_mut() {
+        let rule = config
+            .chains
+            .iter()
+            .find(|c| c.path.join(".") == hit.chain)
+            .and_then(|c| {
+                c.rules
+                    .iter()
+                    .find(|r| i64::from(r.number) == hit.rule_number)
+            });
+        if let Some(rule) = rule {
+            hit.action = Some(rule.action.clone());
+            hit.description = rule.description.clone();
+        }
+    }
+}
+
+/// GET /api/v1/vyos/firewall/hit-topN?chain=&limit=10&period=24h — most-matched rules.
+///
+/// Ranks rules by the sum of packet deltas over `period`, as sampled by
+/// [`crate::vyos::firewall_stats`], and joins the live firewall config for
+/// each rule's action and description. The config lookup is best-effort: if
+/// the router is unreachable, rules are still returned without those fields.
+pub async fn firewall_hit_top(
+    State(state): State<AppState>,
+    Query(params): Query<FirewallHitTopQuery>,
+) -> Result<Json<FirewallHitTopResponse>, AppError> {
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+    let period = params.period.as_deref().unwrap_or("24h");
+    let modifier = period_to_sqlite_modifier(period).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Invalid period '{period}' (expected e.g. 30m, 24h or 7d)"
+        ))
+    })?;
+    let chain = params.chain.as_deref().filter(|c| !c.is_empty());
+
+    let key = format!("{}|{limit}|{period}", chain.unwrap_or(""));
+    {
+        let cache = state.firewall_hit_top_cache.lock().await;
+        if let Some((computed_at, cached)) = cache.get(&key) {
+            if computed_at.elapsed().as_secs() < FIREWALL_HIT_TOP_CACHE_SECS {
+                return Ok(Json(cached.clone()));
             }
         }
     }
+
+    let mut top_rules = query_rule_hits(&state.db, chain, limit, &modifier).await?;
+
+    if !top_rules.is_empty() {
+        let config = state.config().clone();
+        if let Some(client) =
+            get_vyos_client_from_db(&state.db, &config, &state.active_profile_id).await
+        {
+            match client.retrieve(&["firewall"]).await {
+                Ok(data) => annotate_rule_hits(&mut top_rules, &parse_firewall_config(&data)),
+                Err(e) => tracing::warn!("Firewall config lookup for hit top-N failed: {e}"),
+            }
+        }
+    }
+
+    let response = FirewallHitTopResponse { top_rules };
+    let mut cache = state.firewall_hit_top_cache.lock().await;
+    cache.retain(|_, (at, _)| at.elapsed().as_secs() < FIREWALL_HIT_TOP_CACHE_SECS);
+    cache.insert(key, (Instant::now(), response.clone()));
+    Ok(Json(response))
+}
+
+// ── Firewall Rule Counters ──────────────────────────────────────────────────
+
+/// Caveat returned with live counters.
+const FIREWALL_COUNTER_NOTE: &str =
+    "Counters are kept by the router and reset when it reboots or the rule is recreated.";
+
+/// Live packet/byte counters for one rule.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FirewallRuleCounters {
+    pub rule_number: u32,
+    pub packet_count: u64,
+    pub byte_count: u64,
+}
+
+/// Response for `GET /api/v1/vyos/firewall/:chain/rules/:number/stats`.
+#[derive(Debug, Serialize)]
+pub struct FirewallRuleStatsResponse {
+    #[serde(flatten)]
+    pub counters: FirewallRuleCounters,
+    pub note: String,
+}
+
+/// Response for `GET /api/v1/vyos/firewall/:chain/stats`.
+#[derive(Debug, Serialize)]
+pub struct FirewallChainStatsResponse {
+    pub chain: String,
+    pub rules: Vec<FirewallRuleCounters>,
+    pub note: String,
+}
+
+/// Parse per-rule counters from `show firewall <ver> <dir> <filter> [rule <n>]`.
+///
+/// Expected format (VyOS 1.4; columns may be tab- or space-separated):
+/// ```text
+/// Rule     Action    Protocol      Packets    Bytes    Conditions
+/// -------  --------  ----------  ---------  -------  ----------------------------
+/// 10       accept    all               123    45678  ct state established accept
+/// default  drop      all                 0        0
+/// ```
+///
+/// Columns are located by header name. Counts may be abbreviated: packets
+/// in decimal multiples ("1.2M" = 1 200 000), bytes in binary ones. The
+/// `default` action row is skipped.
+pub fn parse_firewall_rule_counters(text: &str) -> Vec<FirewallRuleCounters> {
+    let mut counters = Vec::new();
+    // (rule, packets, bytes) column indices from the most recent header.
+    let mut columns: Option<(usize, usize, usize)> = None;
+
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let lower: Vec<String> = fields.iter().map(|f| f.to_ascii_lowercase()).collect();
+        let index = |name: &str| lower.iter().position(|f| f == name);
+        if let (Some(rule), Some(packets), Some(bytes)) =
+            (index("rule"), index("packets"), index("bytes"))
+        {
+            columns = Some((rule, packets, bytes));
+            continue;
+        }
+        let Some((rule_idx, packets_idx, bytes_idx)) = columns else {
+            continue;
+        };
+        let Some(Ok(rule_number)) = fields.get(rule_idx).map(|f| f.parse::<u32>()) else {
+            continue;
+        };
+        let (Some(packets), Some(bytes)) = (fields.get(packets_idx), fields.get(bytes_idx)) else {
+            continue;
+        };
+        counters.push(FirewallRuleCounters {
+            rule_number,
+            packet_count: parse_human_count(packets),
+            byte_count: parse_human_bytes(bytes),
+        });
+    }
+
+    counters
+}
+
+/// True when VyOS says the requested chain or rule is not there.
+fn firewall_path_missing(msg: &str) -> bool {
+    let msg = msg.to_ascii_lowercase();
+    msg.contains("does not exist") || msg.contains("not found") || msg.contains("is not valid")
+}
+
+/// Run a firewall show command and parse its counters, mapping a missing
+/// chain/rule to 404.
+async fn show_firewall_counters(
+    client: &crate::vyos::client::VyosClient,
+    path: &[&str],
+    not_found: impl FnOnce() -> AppError,
+) -> Result<Vec<FirewallRuleCounters>, AppError> {
+    let text = match client.show(path).await {
+        Ok(value) => value.as_str().unwrap_or("").to_string(),
+        Err(e) if firewall_path_missing(&e.to_string()) => return Err(not_found()),
+        Err(e) => {
+            tracing::error!("VyOS firewall counter query failed: {e}");
+            return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+        }
+    };
+    if firewall_path_missing(&text) {
+        return Err(not_found());
+    }
+    Ok(parse_firewall_rule_counters(&text))
+}
+
+/// GET /api/v1/vyos/firewall/:chain/rules/:number/stats — live counters for one rule.
+pub async fn firewall_rule_stats(
+    State(state): State<AppState>,
+    Path(path): Path<FirewallRulePath>,
+) -> Result<Json<FirewallRuleStatsResponse>, AppError> {
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
+    let client = get_vyos_client(&state).await?;
+
+    let number = path.number.to_string();
+    let not_found = || {
+        AppError::ResourceNotFound(
+            "firewall-rule",
+            format!("Rule {} not found in {}", path.number, path.chain),
+        )
+    };
+    let counters = show_firewall_counters(
+        &client,
+        &[
+            "firewall",
+            chain_parts[0],
+            chain_parts[1],
+            chain_parts[2],
+            "rule",
+            &number,
+        ],
+        not_found,
+    )
+    .await?
+    .into_iter()
+    .find(|c| c.rule_number == path.number)
+    .ok_or_else(not_found)?;
+
+    Ok(Json(FirewallRuleStatsResponse {
+        counters,
+        note: FIREWALL_COUNTER_NOTE.to_string(),
+    }))
+}
+
+/// GET /api/v1/vyos/firewall/:chain/stats — live counters for every rule in a chain.
+pub async fn firewall_chain_stats(
+    State(state): State<AppState>,
+    Path(path): Path<FirewallChainPath>,
+) -> Result<Json<FirewallChainStatsResponse>, AppError> {
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
+    let client = get_vyos_client(&state).await?;
+
+    let rules = show_firewall_counters(
+        &client,
+        &["firewall", chain_parts[0], chain_parts[1], chain_parts[2]],
+        || AppError::ResourceNotFound("firewall-chain", format!("Chain {} not found", path.chain)),
+    )
+    .await?;
+
+    Ok(Json(FirewallChainStatsResponse {
+        chain: path.chain,
+        rules,
+        note: FIREWALL_COUNTER_NOTE.to_string(),
+    }))
 }
 
 // ── Firewall Rule CRUD ──────────────────────────────────────────────────────
@@ -865,6 +2044,12 @@ pub struct FirewallRuleRequest {
     pub disabled: bool,
 }
 
+/// Request body for moving or cloning a firewall rule to a new number.
+#[derive(Debug, Deserialize)]
+pub struct FirewallRuleMoveRequest {
+    pub new_number: u32,
+}
+
 /// Request body for toggling a firewall rule's enabled state.
 #[derive(Debug, Deserialize)]
 pub struct FirewallRuleToggleRequest {
@@ -913,8 +2098,9 @@ fn firewall_rule_base_path(chain_parts: &[&str], rule_number: u32) -> Vec<String
     ]
 }
 
-/// Validate a firewall rule request body.
-fn validate_firewall_rule(body: &FirewallRuleRequest) -> Result<(), String> {
+/// Validate a firewall rule request body for a chain of the given IP version
+/// (`"ipv4"` or `"ipv6"`).
+fn validate_firewall_rule(body: &FirewallRuleRequest, ip_version: &str) -> Result<(), String> {
     if body.number == 0 || body.number > 99999 {
         return Err("Rule number must be between 1 and 99999".to_string());
     }
@@ -935,15 +2121,20 @@ fn validate_firewall_rule(body: &FirewallRuleRequest) -> Result<(), String> {
         }
     }
 
-    // Validate IP addresses (simple check for CIDR or plain IP)
-    if let Some(ref addr) = body.source_address {
-        if !is_valid_ip_or_cidr(addr) {
-            return Err(format!("Invalid source address: '{}'", addr));
-        }
-    }
-    if let Some(ref addr) = body.destination_address {
-        if !is_valid_ip_or_cidr(addr) {
-            return Err(format!("Invalid destination address: '{}'", addr));
+    // Validate IP addresses; VyOS rejects addresses of the other family at commit.
+    for (field, addr) in [
+        ("source", &body.source_address),
+        ("destination", &body.destination_address),
+    ] {
+        let Some(addr) = addr else { continue };
+        match ip_or_cidr_version(addr) {
+            None => return Err(format!("Invalid {field} address: '{addr}'")),
+            Some(version) if version != ip_version => {
+                return Err(format!(
+                    "{field} address '{addr}' is not valid in an {ip_version} chain"
+                ));
+            }
+            Some(_) => {}
         }
     }
 
@@ -971,22 +2162,28 @@ fn validate_firewall_rule(body: &FirewallRuleRequest) -> Result<(), String> {
     Ok(())
 }
 
-/// Check if a string looks like a valid IPv4/IPv6 address or CIDR.
+/// Check if a string is a valid IPv4/IPv6 address or CIDR.
 fn is_valid_ip_or_cidr(addr: &str) -> bool {
-    // Allow "!" prefix for negation
+    ip_or_cidr_version(addr).is_some()
+}
+
+/// `"ipv4"` or `"ipv6"` for a valid address or CIDR (optionally negated
+/// with `!`), `None` if it is neither. Prefix lengths are checked against
+/// the address family (at most /32 and /128).
+fn ip_or_cidr_version(addr: &str) -> Option<&'static str> {
     let addr = addr.strip_prefix('!').unwrap_or(addr);
-    if addr.is_empty() {
-        return false;
-    }
-    // Check for CIDR notation
-    if let Some((ip_part, prefix)) = addr.split_once('/') {
-        if prefix.parse::<u8>().is_err() {
-            return false;
-        }
-        return ip_part.parse::<std::net::IpAddr>().is_ok();
+    let (ip_part, prefix) = match addr.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix.parse::<u8>().ok()?)),
+        None => (addr, None),
+    };
+    let (version, max_prefix) = match ip_part.parse::<std::net::IpAddr>().ok()? {
+        std::net::IpAddr::V4(_) => ("ipv4", 32),
+        std::net::IpAddr::V6(_) => ("ipv6", 128),
+    };
+    match prefix {
+        Some(len) if len > max_prefix => None,
+        _ => Some(version),
     }
-    // Plain IP address
-    addr.parse::<std::net::IpAddr>().is_ok()
 }
 
 /// Check if a port string is valid (single port or range like "80" or "1024-65535").
@@ -1120,51 +2317,36 @@ async fn apply_firewall_rule_config(
 /// POST /api/v1/vyos/firewall/:chain/rules — create a firewall rule.
 pub async fn create_firewall_rule(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(path): Path<FirewallChainPath>,
     Json(body): Json<FirewallRuleRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
-    let chain_parts = parse_chain_path(&path.chain).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        )
-    })?;
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
 
-    if let Err(e) = validate_firewall_rule(&body) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        ));
+    if let Err(e) = validate_firewall_rule(&body, chain_parts[0]) {
+        return Err(AppError::Validation(e));
     }
 
     let base = firewall_rule_base_path(&chain_parts, body.number);
+    let family = if chain_parts[0] == "ipv6" {
+        "IPv6"
+    } else {
+        "IPv4"
+    };
 
     tracing::info!(
-        "VyOS: creating firewall rule {} in chain {} (action={})",
+        "VyOS: creating {} firewall rule {} in chain {} (action={})",
+        family,
         body.number,
         path.chain,
         body.action
     );
 
     let description = format!(
-        "Create firewall rule {} in chain {} (action={})",
-        body.number, path.chain, body.action
+        "Create {} firewall rule {} in chain {} (action={})",
+        family, body.number, path.chain, body.action
     );
     let commands = vec![format!(
         "set firewall {} rule {} ...",
@@ -1173,8 +2355,9 @@ pub async fn create_firewall_rule(
 
     if let Err(e) = apply_firewall_rule_config(&client, &base, &body).await {
         tracing::error!("VyOS firewall rule create failed: {e}");
-        audit::log_failure(
-            &state.db,
+        log_write_failure(
+            &state,
+            &actor,
             "firewall_rule_create",
             &description,
             &commands,
@@ -1184,17 +2367,19 @@ pub async fn create_firewall_rule(
         // Attempt cleanup on failure
         let base_strs: Vec<&str> = base.iter().map(|s| s.as_str()).collect();
         let _ = client.configure_delete(&base_strs).await;
-        return Err((
-            StatusCode::BAD_GATEWAY,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        ));
+        return Err(AppError::BadGateway(e));
     }
 
-    audit::log_success(&state.db, "firewall_rule_create", &description, &commands).await;
+    audit::log_success(
+        &state.db,
+        &actor,
+        "firewall_rule_create",
+        &description,
+        &commands,
+    )
+    .await;
 
+    state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
     Ok(Json(VyosWriteResponse {
         success: true,
         message: format!("Rule {} created in {}", body.number, path.chain),
@@ -1206,37 +2391,16 @@ pub async fn create_firewall_rule(
 /// Deletes the existing rule first, then re-creates it with the new values.
 pub async fn update_firewall_rule(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(path): Path<FirewallRulePath>,
     Json(body): Json<FirewallRuleRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
-    let chain_parts = parse_chain_path(&path.chain).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        )
-    })?;
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
 
-    if let Err(e) = validate_firewall_rule(&body) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        ));
+    if let Err(e) = validate_firewall_rule(&body, chain_parts[0]) {
+        return Err(AppError::Validation(e));
     }
 
     let base = firewall_rule_base_path(&chain_parts, path.number);
@@ -1262,45 +2426,43 @@ pub async fn update_firewall_rule(
     if let Err(e) = client.configure_delete(&base_strs).await {
         tracing::error!("VyOS firewall rule delete (for update) failed: {e}");
         let msg = format!("Failed to delete existing rule for update: {e}");
-        audit::log_failure(
-            &state.db,
+        log_write_failure(
+            &state,
+            &actor,
             "firewall_rule_update",
             &description,
             &commands,
             &msg,
         )
         .await;
-        return Err((
-            StatusCode::BAD_GATEWAY,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::BadGateway(msg));
     }
 
     // Re-create with the updated values
     if let Err(e) = apply_firewall_rule_config(&client, &base, &body).await {
         tracing::error!("VyOS firewall rule re-create (for update) failed: {e}");
-        audit::log_failure(
-            &state.db,
+        log_write_failure(
+            &state,
+            &actor,
             "firewall_rule_update",
             &description,
             &commands,
             &e,
         )
         .await;
-        return Err((
-            StatusCode::BAD_GATEWAY,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        ));
+        return Err(AppError::BadGateway(e));
     }
 
-    audit::log_success(&state.db, "firewall_rule_update", &description, &commands).await;
+    audit::log_success(
+        &state.db,
+        &actor,
+        "firewall_rule_update",
+        &description,
+        &commands,
+    )
+    .await;
 
+    state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
     Ok(Json(VyosWriteResponse {
         success: true,
         message: format!("Rule {} updated in {}", path.number, path.chain),
@@ -1310,27 +2472,12 @@ pub async fn update_firewall_rule(
 /// DELETE /api/v1/vyos/firewall/:chain/rules/:number — delete a firewall rule.
 pub async fn delete_firewall_rule(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(path): Path<FirewallRulePath>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
-    let chain_parts = parse_chain_path(&path.chain).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        )
-    })?;
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
 
     let base = firewall_rule_base_path(&chain_parts, path.number);
     let base_strs: Vec<&str> = base.iter().map(|s| s.as_str()).collect();
@@ -1352,7 +2499,15 @@ pub async fn delete_firewall_rule(
 
     match client.configure_delete(&base_strs).await {
         Ok(_) => {
-            audit::log_success(&state.db, "firewall_rule_delete", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "firewall_rule_delete",
+                &description,
+                &commands,
+            )
+            .await;
+            state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Rule {} deleted from {}", path.number, path.chain),
@@ -1361,21 +2516,16 @@ pub async fn delete_firewall_rule(
         Err(e) => {
             tracing::error!("VyOS firewall rule delete failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "firewall_rule_delete",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -1383,28 +2533,13 @@ pub async fn delete_firewall_rule(
 /// PATCH /api/v1/vyos/firewall/:chain/rules/:number/enabled — toggle a rule.
 pub async fn toggle_firewall_rule(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(path): Path<FirewallRulePath>,
     Json(body): Json<FirewallRuleToggleRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
-    let chain_parts = parse_chain_path(&path.chain).map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: e,
-            }),
-        )
-    })?;
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
 
     let base = firewall_rule_base_path(&chain_parts, path.number);
     let mut disable_path: Vec<&str> = base.iter().map(|s| s.as_str()).collect();
@@ -1441,7 +2576,15 @@ pub async fn toggle_firewall_rule(
 
     match result {
         Ok(_) => {
-            audit::log_success(&state.db, "firewall_rule_toggle", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "firewall_rule_toggle",
+                &description,
+                &commands,
+            )
+            .await;
+            state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Rule {} {}d in {}", path.number, action, path.chain),
@@ -1450,42 +2593,951 @@ pub async fn toggle_firewall_rule(
         Err(e) => {
             tracing::error!("VyOS firewall rule toggle failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "firewall_rule_toggle",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
 
-/// GET /api/v1/vyos/config-interfaces — fetch interface configuration (structured).
-pub async fn config_interfaces(State(state): State<AppState>) -> Result<Json<Value>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
-    client
-        .retrieve(&["interfaces"])
-        .await
-        .map(Json)
-        .map_err(|e| {
-            tracing::error!("VyOS config-interfaces query failed: {e}");
-            StatusCode::BAD_GATEWAY
-        })
-}
+/// Flatten a VyOS config subtree into the `set` paths that recreate it,
+/// relative to the subtree root.
+///
+/// `{"action": "accept", "state": {"new": "enable"}, "disable": {}}` becomes
+/// `[["action", "accept"], ["disable"], ["state", "new", "enable"]]`.
+fn config_set_paths(value: &Value) -> Vec<Vec<String>> {
+    fn walk(value: &Value, prefix: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+        match value {
+            Value::Object(obj) if obj.is_empty() => out.push(prefix.clone()),
+            Value::Object(obj) => {
+                for (key, child) in obj {
+                    prefix.push(key.clone());
+                    walk(child, prefix, out);
+                    prefix.pop();
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, prefix, out);
+                }
+            }
+            Value::String(s) => {
+                let mut path = prefix.clone();
+                path.push(s.clone());
+                out.push(path);
+            }
+            Value::Null => {}
+            other => {
+                let mut path = prefix.clone();
+                path.push(other.to_string());
+                out.push(path);
+            }
+        }
+    }
 
-// ── Interface enable/disable ─────────────────────────────────────────────────
+    let mut out = Vec::new();
+    if value.as_object().is_some_and(|o| !o.is_empty()) {
+        walk(value, &mut Vec::new(), &mut out);
+    }
+    out
+}
 
-/// Request body for the interface toggle endpoint.
-#[derive(Debug, Deserialize)]
+/// Retrieve a rule's config subtree; `None` if the rule does not exist.
+async fn retrieve_firewall_rule(
+    client: &crate::vyos::client::VyosClient,
+    base: &[String],
+) -> Result<Option<Value>, String> {
+    let base_strs: Vec<&str> = base.iter().map(|s| s.as_str()).collect();
+    match client.retrieve(&base_strs).await {
+        Ok(value) if value.as_object().is_some_and(|o| !o.is_empty()) => Ok(Some(value)),
+        Ok(_) => Ok(None),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Ok(None)
+            } else {
+                Err(format!("VyOS error: {e}"))
+            }
+        }
+    }
+}
+
+/// Validate a move/clone target and fetch the source rule's config.
+///
+/// 404 if the source rule does not exist, 409 if `new_number` is taken.
+async fn load_rule_for_copy(
+    client: &crate::vyos::client::VyosClient,
+    path: &FirewallRulePath,
+    new_number: u32,
+) -> Result<Value, AppError> {
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
+    if new_number == 0 || new_number > 99999 {
+        return Err(AppError::Validation(
+            "Rule number must be between 1 and 99999".to_string(),
+        ));
+    }
+    if new_number == path.number {
+        return Err(AppError::Validation(format!(
+            "New rule number must differ from {}",
+            path.number
+        )));
+    }
+
+    let rule = retrieve_firewall_rule(client, &firewall_rule_base_path(&chain_parts, path.number))
+        .await
+        .map_err(AppError::BadGateway)?
+        .ok_or_else(|| {
+            AppError::ResourceNotFound(
+                "firewall-rule",
+                format!("Rule {} not found in {}", path.number, path.chain),
+            )
+        })?;
+    if retrieve_firewall_rule(client, &firewall_rule_base_path(&chain_parts, new_number))
+        .await
+        .map_err(AppError::BadGateway)?
+        .is_some()
+    {
+        return Err(AppError::Conflict(format!(
+            "Rule {new_number} already exists in {}; choose a different number",
+            path.chain
+        )));
+    }
+    Ok(rule)
+}
+
+/// The full `set` paths that copy `rule` under `new_base`, plus any `extra`
+/// paths (relative to the rule).
+fn rule_copy_paths(new_base: &[String], rule: &Value, extra: &[&str]) -> Vec<Vec<String>> {
+    config_set_paths(rule)
+        .into_iter()
+        .chain(extra.iter().map(|item| vec![item.to_string()]))
+        .map(|suffix| new_base.iter().cloned().chain(suffix).collect())
+        .collect()
+}
+
+/// Render `set` paths as the CLI commands recorded in the audit log.
+fn set_commands(paths: &[Vec<String>]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| format!("set {}", path.join(" ")))
+        .collect()
+}
+
+/// Write a copy of `rule` under `new_base` in a single commit, plus any
+/// `extra` paths (relative to the rule).
+async fn write_rule_copy(
+    client: &crate::vyos::client::VyosClient,
+    new_base: &[String],
+    rule: &Value,
+    extra: &[&str],
+) -> Result<(), String> {
+    let owned = rule_copy_paths(new_base, rule, extra);
+    let paths: Vec<Vec<&str>> = owned
+        .iter()
+        .map(|path| path.iter().map(|s| s.as_str()).collect())
+        .collect();
+    client
+        .configure_set_many(&paths)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("VyOS error: {e}"))
+}
+
+/// POST /api/v1/vyos/firewall/:chain/rules/:number/move — renumber a rule.
+///
+/// Copies the rule's current config to `new_number` in one commit and then
+/// deletes the old number, so a failure part-way leaves the original rule
+/// in place. Responds 409 if `new_number` is already taken.
+pub async fn move_firewall_rule(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(path): Path<FirewallRulePath>,
+    Json(body): Json<FirewallRuleMoveRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let rule = load_rule_for_copy(&client, &path, body.new_number).await?;
+
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
+    let old_base = firewall_rule_base_path(&chain_parts, path.number);
+    let new_base = firewall_rule_base_path(&chain_parts, body.new_number);
+
+    tracing::info!(
+        "VyOS: moving firewall rule {} to {} in chain {}",
+        path.number,
+        body.new_number,
+        path.chain
+    );
+
+    let description = format!(
+        "Move firewall rule {} to {} in chain {}",
+        path.number, body.new_number, path.chain
+    );
+    let mut commands = set_commands(&rule_copy_paths(&new_base, &rule, &[]));
+    commands.push(format!("delete {}", old_base.join(" ")));
+
+    if let Err(e) = write_rule_copy(&client, &new_base, &rule, &[]).await {
+        tracing::error!("VyOS firewall rule move failed: {e}");
+        let msg = format!("Failed to create rule {}: {e}", body.new_number);
+        log_write_failure(
+            &state,
+            &actor,
+            "firewall_rule_move",
+            &description,
+            &commands,
+            &msg,
+        )
+        .await;
+        return Err(AppError::BadGateway(msg));
+    }
+
+    let old_base_strs: Vec<&str> = old_base.iter().map(|s| s.as_str()).collect();
+    if let Err(e) = client.configure_delete(&old_base_strs).await {
+        tracing::error!("VyOS firewall rule move failed: {e}");
+        let msg = format!(
+            "Rule copied to {} but deleting {} failed: {e}",
+            body.new_number, path.number
+        );
+        log_write_failure(
+            &state,
+            &actor,
+            "firewall_rule_move",
+            &description,
+            &commands,
+            &msg,
+        )
+        .await;
+        // The copy was committed, so the chain has changed either way.
+        state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
+        return Err(AppError::BadGateway(msg));
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "firewall_rule_move",
+        &description,
+        &commands,
+    )
+    .await;
+
+    state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
+    Ok(Json(VyosWriteResponse {
+        success: true,
+        message: format!(
+            "Rule {} moved to {} in {}",
+            path.number, body.new_number, path.chain
+        ),
+    }))
+}
+
+/// POST /api/v1/vyos/firewall/:chain/rules/:number/clone — duplicate a rule.
+///
+/// The copy is written to `new_number` disabled, in the same commit, so it
+/// never matches traffic until it has been reviewed and enabled. Responds
+/// 409 if `new_number` is already taken.
+pub async fn clone_firewall_rule(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(path): Path<FirewallRulePath>,
+    Json(body): Json<FirewallRuleMoveRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let rule = load_rule_for_copy(&client, &path, body.new_number).await?;
+
+    let chain_parts = parse_chain_path(&path.chain).map_err(AppError::Validation)?;
+    let new_base = firewall_rule_base_path(&chain_parts, body.new_number);
+
+    tracing::info!(
+        "VyOS: cloning firewall rule {} to {} in chain {}",
+        path.number,
+        body.new_number,
+        path.chain
+    );
+
+    let description = format!(
+        "Clone firewall rule {} to {} (disabled) in chain {}",
+        path.number, body.new_number, path.chain
+    );
+    let commands = set_commands(&rule_copy_paths(&new_base, &rule, &["disable"]));
+
+    if let Err(e) = write_rule_copy(&client, &new_base, &rule, &["disable"]).await {
+        tracing::error!("VyOS firewall rule clone failed: {e}");
+        log_write_failure(
+            &state,
+            &actor,
+            "firewall_rule_clone",
+            &description,
+            &commands,
+            &e,
+        )
+        .await;
+        return Err(AppError::BadGateway(e));
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "firewall_rule_clone",
+        &description,
+        &commands,
+    )
+    .await;
+
+    state.vyos_cache.invalidate(&[CACHE_FIREWALL]);
+    Ok(Json(VyosWriteResponse {
+        success: true,
+        message: format!(
+            "Rule {} cloned to {} in {} (disabled)",
+            path.number, body.new_number, path.chain
+        ),
+    }))
+}
+
+/// GET /api/v1/vyos/config-interfaces — fetch interface configuration (structured).
+pub async fn config_interfaces(State(state): State<AppState>) -> Result<Json<Value>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    client
+        .retrieve(&["interfaces"])
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("VyOS config-interfaces query failed: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })
+}
+
+// ── NAT Rules ───────────────────────────────────────────────────────────────
+
+/// A parsed source or destination NAT rule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NatRule {
+    pub number: u32,
+    /// "source" or "destination".
+    pub rule_type: String,
+    /// Matched source as `address`, `address:port` or `:port`.
+    pub source: Option<String>,
+    /// Matched destination as `address`, `address:port` or `:port`.
+    pub destination: Option<String>,
+    /// Matched interface: outbound for source NAT, inbound for destination NAT.
+    pub outbound_interface: Option<String>,
+    /// Translated address (or "masquerade"), with `:port` when set.
+    pub translation: String,
+    pub description: Option<String>,
+    pub disabled: bool,
+}
+
+/// Path parameters for NAT rule endpoints.
+#[derive(Debug, Deserialize)]
+pub struct NatRulePath {
+    /// "source" or "destination".
+    pub kind: String,
+    pub number: u32,
+}
+
+/// Request body for creating a NAT rule.
+#[derive(Debug, Deserialize)]
+pub struct NatRuleRequest {
+    pub number: u32,
+    #[serde(default)]
+    pub protocol: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub destination: Option<String>,
+    #[serde(default)]
+    pub destination_port: Option<String>,
+    /// Outbound interface for source NAT, inbound interface for destination NAT.
+    #[serde(default)]
+    pub outbound_interface: Option<String>,
+    /// Translation address; `masquerade` is accepted for source NAT.
+    pub translation: String,
+    #[serde(default)]
+    pub translation_port: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+/// Validate the `:kind` path segment of NAT endpoints.
+fn nat_kind(kind: &str) -> Option<&'static str> {
+    match kind {
+        "source" => Some("source"),
+        "destination" => Some("destination"),
+        _ => None,
+    }
+}
+
+/// Format an `address`/`port` pair from a NAT config node.
+fn nat_endpoint(node: Option<&Value>) -> Option<String> {
+    let node = node?;
+    let address = node.get("address").and_then(|v| v.as_str());
+    let port = node.get("port").and_then(|v| v.as_str());
+    match (address, port) {
+        (Some(a), Some(p)) => Some(format!("{a}:{p}")),
+        (Some(a), None) => Some(a.to_string()),
+        (None, Some(p)) => Some(format!(":{p}")),
+        (None, None) => None,
+    }
+}
+
+/// Read an interface that is either a plain string (VyOS 1.3) or
+/// `{"name": "eth0"}` (VyOS 1.4+).
+fn nat_interface(node: Option<&Value>) -> Option<String> {
+    let node = node?;
+    node.as_str()
+        .or_else(|| node.get("name").and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// Parse VyOS config at `nat <kind> rule` into a sorted vec of [`NatRule`].
+pub fn parse_nat_rules(config: &Value, rule_type: &str) -> Vec<NatRule> {
+    let Some(rules) = config.as_object() else {
+        return Vec::new();
+    };
+    let interface_key = if rule_type == "source" {
+        "outbound-interface"
+    } else {
+        "inbound-interface"
+    };
+
+    let mut parsed: Vec<NatRule> = rules
+        .iter()
+        .filter_map(|(number, rule)| {
+            Some(NatRule {
+                number: number.parse().ok()?,
+                rule_type: rule_type.to_string(),
+                source: nat_endpoint(rule.get("source")),
+                destination: nat_endpoint(rule.get("destination")),
+                outbound_interface: nat_interface(rule.get(interface_key)),
+                translation: nat_endpoint(rule.get("translation")).unwrap_or_default(),
+                description: rule
+                    .get("description")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                disabled: rule.get("disable").is_some(),
+            })
+        })
+        .collect();
+    parsed.sort_by_key(|r| r.number);
+    parsed
+}
+
+/// GET /api/v1/vyos/nat/:kind — list source or destination NAT rules.
+pub async fn nat_rules(
+    State(state): State<AppState>,
+    Path(kind): Path<String>,
+) -> Result<Json<Vec<NatRule>>, AppError> {
+    let kind = nat_kind(&kind).ok_or_else(|| {
+        AppError::ResourceNotFound("nat_type", format!("Unknown NAT type '{kind}'"))
+    })?;
+    let client = get_vyos_client(&state).await?;
+
+    let config = match client.retrieve(&["nat", kind, "rule"]).await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                return Ok(Json(Vec::new()));
+            }
+            tracing::error!("VyOS NAT config query failed: {e}");
+            return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+        }
+    };
+
+    Ok(Json(parse_nat_rules(&config, kind)))
+}
+
+/// Check that an interface name only uses characters VyOS allows.
+fn is_valid_interface_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 15
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+/// Validate a NAT rule request body.
+fn validate_nat_rule(kind: &str, body: &NatRuleRequest) -> Result<(), String> {
+    if body.number == 0 || body.number > 9999 {
+        return Err("Rule number must be between 1 and 9999".to_string());
+    }
+
+    if let Some(ref proto) = body.protocol {
+        if !matches!(proto.as_str(), "tcp" | "udp" | "tcp_udp" | "icmp" | "all") {
+            return Err(format!("Invalid protocol: '{}'", proto));
+        }
+    }
+    if let Some(ref addr) = body.source {
+        if !is_valid_ip_or_cidr(addr) {
+            return Err(format!("Invalid source address: '{}'", addr));
+        }
+    }
+    if let Some(ref addr) = body.destination {
+        if !is_valid_ip_or_cidr(addr) {
+            return Err(format!("Invalid destination address: '{}'", addr));
+        }
+    }
+    for port in [&body.destination_port, &body.translation_port]
+        .into_iter()
+        .flatten()
+    {
+        if !is_valid_port(port) {
+            return Err(format!("Invalid port: '{}'", port));
+        }
+    }
+    if let Some(ref iface) = body.outbound_interface {
+        if !is_valid_interface_name(iface) {
+            return Err(format!("Invalid interface: '{}'", iface));
+        }
+    }
+
+    let masquerade = body.translation == "masquerade";
+    if masquerade && kind != "source" {
+        return Err("masquerade is only valid for source NAT".to_string());
+    }
+    if !masquerade && !is_valid_ip_or_cidr(&body.translation) {
+        return Err(format!(
+            "Invalid translation address: '{}'",
+            body.translation
+        ));
+    }
+
+    Ok(())
+}
+
+/// Apply NAT rule configuration to VyOS via a sequence of set commands.
+async fn apply_nat_rule_config(
+    client: &crate::vyos::client::VyosClient,
+    kind: &str,
+    body: &NatRuleRequest,
+) -> Result<(), String> {
+    let number = body.number.to_string();
+    let interface_key = if kind == "source" {
+        "outbound-interface"
+    } else {
+        "inbound-interface"
+    };
+
+    let mut settings: Vec<(Vec<&str>, &str)> = Vec::new();
+    if let Some(ref proto) = body.protocol {
+        settings.push((vec!["protocol", proto], "protocol"));
+    }
+    if let Some(ref addr) = body.source {
+        settings.push((vec!["source", "address", addr], "source address"));
+    }
+    if let Some(ref addr) = body.destination {
+        settings.push((vec!["destination", "address", addr], "destination address"));
+    }
+    if let Some(ref port) = body.destination_port {
+        settings.push((vec!["destination", "port", port], "destination port"));
+    }
+    if let Some(ref iface) = body.outbound_interface {
+        settings.push((vec![interface_key, "name", iface], "interface"));
+    }
+    settings.push((
+        vec!["translation", "address", &body.translation],
+        "translation address",
+    ));
+    if let Some(ref port) = body.translation_port {
+        settings.push((vec!["translation", "port", port], "translation port"));
+    }
+    if let Some(ref desc) = body.description {
+        settings.push((vec!["description", desc], "description"));
+    }
+    if body.disabled {
+        settings.push((vec!["disable"], "disable"));
+    }
+
+    for (suffix, what) in settings {
+        let mut path = vec!["nat", kind, "rule", number.as_str()];
+        path.extend(suffix);
+        client
+            .configure_set(&path)
+            .await
+            .map_err(|e| format!("Failed to set {what}: {e}"))?;
+    }
+
+    Ok(())
+}
+
+/// POST /api/v1/vyos/nat/:kind — create a source or destination NAT rule.
+pub async fn create_nat_rule(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(kind): Path<String>,
+    Json(body): Json<NatRuleRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let kind = nat_kind(&kind).ok_or_else(|| {
+        AppError::ResourceNotFound("nat_type", format!("Unknown NAT type '{kind}'"))
+    })?;
+    validate_nat_rule(kind, &body).map_err(AppError::Validation)?;
+
+    let client = get_vyos_client(&state).await?;
+
+    tracing::info!(
+        "VyOS: creating {kind} NAT rule {} (translation={})",
+        body.number,
+        body.translation
+    );
+
+    let description = format!(
+        "Create {kind} NAT rule {} (translation={})",
+        body.number, body.translation
+    );
+    let commands = vec![format!("set nat {kind} rule {} ...", body.number)];
+
+    if let Err(e) = apply_nat_rule_config(&client, kind, &body).await {
+        tracing::error!("VyOS NAT rule create failed: {e}");
+        log_write_failure(
+            &state,
+            &actor,
+            "nat_rule_create",
+            &description,
+            &commands,
+            &e,
+        )
+        .await;
+        // Attempt cleanup on failure
+        let number = body.number.to_string();
+        let _ = client
+            .configure_delete(&["nat", kind, "rule", &number])
+            .await;
+        return Err(AppError::BadGateway(e));
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "nat_rule_create",
+        &description,
+        &commands,
+    )
+    .await;
+
+    Ok(Json(VyosWriteResponse {
+        success: true,
+        message: format!("{kind} NAT rule {} created", body.number),
+    }))
+}
+
+/// DELETE /api/v1/vyos/nat/:kind/:number — delete a NAT rule.
+pub async fn delete_nat_rule(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(path): Path<NatRulePath>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let kind = nat_kind(&path.kind).ok_or_else(|| {
+        AppError::ResourceNotFound("nat_type", format!("Unknown NAT type '{}'", path.kind))
+    })?;
+    if path.number == 0 || path.number > 9999 {
+        return Err(AppError::Validation(
+            "Rule number must be between 1 and 9999".to_string(),
+        ));
+    }
+
+    let client = get_vyos_client(&state).await?;
+    let number = path.number.to_string();
+
+    tracing::info!("VyOS: deleting {kind} NAT rule {}", path.number);
+
+    let description = format!("Delete {kind} NAT rule {}", path.number);
+    let commands = vec![format!("delete nat {kind} rule {}", path.number)];
+
+    match client
+        .configure_delete(&["nat", kind, "rule", &number])
+        .await
+    {
+        Ok(_) => {
+            audit::log_success(
+                &state.db,
+                &actor,
+                "nat_rule_delete",
+                &description,
+                &commands,
+            )
+            .await;
+            Ok(Json(VyosWriteResponse {
+                success: true,
+                message: format!("{kind} NAT rule {} deleted", path.number),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("VyOS NAT rule delete failed: {e}");
+            let msg = format!("VyOS error: {e}");
+            log_write_failure(
+                &state,
+                &actor,
+                "nat_rule_delete",
+                &description,
+                &commands,
+                &msg,
+            )
+            .await;
+            Err(AppError::BadGateway(msg))
+        }
+    }
+}
+
+// ── DNS Forwarding ──────────────────────────────────────────────────────────
+
+/// A domain whose queries are forwarded to a specific server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DnsDomainOverride {
+    pub domain: String,
+    pub server: String,
+}
+
+/// Parsed `service dns forwarding` configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DnsForwardingConfig {
+    pub listen_on: Vec<String>,
+    pub nameservers: Vec<String>,
+    pub cache_size: u32,
+    pub domain_overrides: Vec<DnsDomainOverride>,
+}
+
+/// VyOS default `cache-size` when none is configured.
+const DNS_DEFAULT_CACHE_SIZE: u32 = 10000;
+
+/// Request body for adding an upstream nameserver.
+#[derive(Debug, Deserialize)]
+pub struct DnsNameserverRequest {
+    pub ip: String,
+}
+
+/// Request body for adding a domain-specific forwarder.
+#[derive(Debug, Deserialize)]
+pub struct DnsDomainOverrideRequest {
+    pub domain: String,
+    pub server: String,
+}
+
+/// Values of a config leaf that may be a single string, a list, or (for
+/// tag nodes like VyOS 1.4 `name-server <ip> { port }`) an object keyed by value.
+fn config_values(node: Option<&Value>) -> Vec<String> {
+    match node {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        Some(Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse VyOS config at `service dns forwarding`.
+///
+/// Accepts both 1.3 (`listen-on`, `domain <d> server <ip>`) and 1.4
+/// (`listen-address`, `domain <d> name-server <ip>`) layouts.
+pub fn parse_dns_forwarding(config: &Value) -> DnsForwardingConfig {
+    let mut listen_on = config_values(config.get("listen-address"));
+    listen_on.extend(config_values(config.get("listen-on")));
+
+    let cache_size = config
+        .get("cache-size")
+        .and_then(|v| {
+            v.as_str()
+                .and_then(|s| s.parse().ok())
+                .or(v.as_u64().map(|n| n as u32))
+        })
+        .unwrap_or(DNS_DEFAULT_CACHE_SIZE);
+
+    let mut domain_overrides = Vec::new();
+    if let Some(domains) = config.get("domain").and_then(|v| v.as_object()) {
+        for (domain, entry) in domains {
+            let mut servers = config_values(entry.get("name-server"));
+            servers.extend(config_values(entry.get("server")));
+            domain_overrides.extend(servers.into_iter().map(|server| DnsDomainOverride {
+                domain: domain.clone(),
+                server,
+            }));
+        }
+    }
+
+    DnsForwardingConfig {
+        listen_on,
+        nameservers: config_values(config.get("name-server")),
+        cache_size,
+        domain_overrides,
+    }
+}
+
+/// GET /api/v1/vyos/dns/forwarding — DNS forwarder configuration.
+pub async fn dns_forwarding(
+    State(state): State<AppState>,
+) -> Result<Json<DnsForwardingConfig>, AppError> {
+    let client = get_vyos_client(&state).await?;
+
+    let config = match client.retrieve(&["service", "dns", "forwarding"]).await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Value::Null
+            } else {
+                tracing::error!("VyOS DNS forwarding config query failed: {e}");
+                return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+            }
+        }
+    };
+
+    Ok(Json(parse_dns_forwarding(&config)))
+}
+
+/// Check a DNS domain name (e.g. "corp.example.com").
+fn is_valid_domain(domain: &str) -> bool {
+    !domain.is_empty()
+        && domain.len() <= 253
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Run a single DNS forwarding config command and audit-log the outcome.
+#[allow(clippy::too_many_arguments)]
+async fn apply_dns_forwarding_change(
+    state: &AppState,
+    actor: &str,
+    action: &str,
+    description: String,
+    command: String,
+    path: &[&str],
+    delete: bool,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(state).await?;
+    let commands = vec![command];
+
+    tracing::info!("VyOS: {description}");
+
+    let result = if delete {
+        client.configure_delete(path).await
+    } else {
+        client.configure_set(path).await
+    };
+
+    match result {
+        Ok(_) => {
+            audit::log_success(&state.db, actor, action, &description, &commands).await;
+            Ok(Json(VyosWriteResponse {
+                success: true,
+                message: description,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("VyOS DNS forwarding change failed: {e}");
+            let msg = format!("VyOS error: {e}");
+            log_write_failure(state, actor, action, &description, &commands, &msg).await;
+            Err(AppError::BadGateway(msg))
+        }
+    }
+}
+
+/// POST /api/v1/vyos/dns/forwarding/nameservers — add an upstream nameserver.
+pub async fn add_dns_nameserver(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<DnsNameserverRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let ip = body.ip.trim();
+    if ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::Validation(format!(
+            "Invalid nameserver IP: '{ip}'"
+        )));
+    }
+
+    apply_dns_forwarding_change(
+        &state,
+        &actor,
+        "dns_nameserver_add",
+        format!("Add DNS forwarding nameserver {ip}"),
+        format!("set service dns forwarding name-server {ip}"),
+        &["service", "dns", "forwarding", "name-server", ip],
+        false,
+    )
+    .await
+}
+
+/// DELETE /api/v1/vyos/dns/forwarding/nameservers/:ip — remove a nameserver.
+pub async fn delete_dns_nameserver(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(ip): Path<String>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    if ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::Validation(format!(
+            "Invalid nameserver IP: '{ip}'"
+        )));
+    }
+
+    apply_dns_forwarding_change(
+        &state,
+        &actor,
+        "dns_nameserver_delete",
+        format!("Remove DNS forwarding nameserver {ip}"),
+        format!("delete service dns forwarding name-server {ip}"),
+        &["service", "dns", "forwarding", "name-server", &ip],
+        true,
+    )
+    .await
+}
+
+/// POST /api/v1/vyos/dns/forwarding/domain-override — forward a domain to a specific server.
+pub async fn add_dns_domain_override(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<DnsDomainOverrideRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let domain = body.domain.trim().trim_end_matches('.').to_lowercase();
+    let server = body.server.trim();
+    if !is_valid_domain(&domain) {
+        return Err(AppError::Validation(format!(
+            "Invalid domain: '{}'",
+            body.domain
+        )));
+    }
+    if server.parse::<std::net::IpAddr>().is_err() {
+        return Err(AppError::Validation(format!(
+            "Invalid server IP: '{server}'"
+        )));
+    }
+
+    apply_dns_forwarding_change(
+        &state,
+        &actor,
+        "dns_domain_override_add",
+        format!("Forward DNS domain {domain} to {server}"),
+        format!("set service dns forwarding domain {domain} name-server {server}"),
+        &[
+            "service",
+            "dns",
+            "forwarding",
+            "domain",
+            &domain,
+            "name-server",
+            server,
+        ],
+        false,
+    )
+    .await
+}
+
+// ── Interface enable/disable ─────────────────────────────────────────────────
+
+/// Request body for the interface toggle endpoint.
+#[derive(Debug, Deserialize)]
 pub struct InterfaceToggleRequest {
     /// `true` to disable the interface, `false` to enable it.
     pub disable: bool,
@@ -1498,106 +3550,724 @@ pub struct VyosWriteResponse {
     pub message: String,
 }
 
-/// Derive the VyOS interface type prefix from the interface name.
+/// Derive the VyOS interface type prefix from the interface name.
+///
+/// e.g. "eth0" → "ethernet", "bond0" → "bonding", "br0" → "bridge", "lo" → "loopback"
+fn interface_type(name: &str) -> Option<&'static str> {
+    if name.starts_with("eth") {
+        Some("ethernet")
+    } else if name.starts_with("bond") {
+        Some("bonding")
+    } else if name.starts_with("br") {
+        Some("bridge")
+    } else if name.starts_with("wg") {
+        Some("wireguard")
+    } else if name == "lo" || name.starts_with("lo") {
+        Some("loopback")
+    } else if name.starts_with("vtun") {
+        Some("openvpn")
+    } else if name.starts_with("tun") {
+        Some("tunnel")
+    } else if name.starts_with("vti") {
+        Some("vti")
+    } else if name.starts_with("pppoe") {
+        Some("pppoe")
+    } else {
+        None
+    }
+}
+
+/// POST /api/v1/vyos/interfaces/:name/toggle — enable or disable a VyOS interface.
+///
+/// Sends `set interfaces <type> <name> disable` or
+/// `delete interfaces <type> <name> disable` to VyOS.
+pub async fn interface_toggle(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(name): Path<String>,
+    Json(body): Json<InterfaceToggleRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+
+    let iface_type = interface_type(&name).ok_or_else(|| {
+        AppError::Validation(format!("Cannot determine interface type for '{name}'"))
+    })?;
+
+    let action = if body.disable { "disable" } else { "enable" };
+    tracing::info!("VyOS: {action} interface {iface_type} {name}");
+
+    let description = format!(
+        "{} interface {} ({})",
+        if body.disable { "Disable" } else { "Enable" },
+        name,
+        iface_type
+    );
+    let commands = vec![if body.disable {
+        format!("set interfaces {iface_type} {name} disable")
+    } else {
+        format!("delete interfaces {iface_type} {name} disable")
+    }];
+
+    let result = if body.disable {
+        client
+            .configure_set(&["interfaces", iface_type, &name, "disable"])
+            .await
+    } else {
+        client
+            .configure_delete(&["interfaces", iface_type, &name, "disable"])
+            .await
+    };
+
+    match result {
+        Ok(_) => {
+            audit::log_success(
+                &state.db,
+                &actor,
+                "interface_toggle",
+                &description,
+                &commands,
+            )
+            .await;
+            state
+                .vyos_cache
+                .invalidate(&[CACHE_INTERFACES, CACHE_ROUTES]);
+            Ok(Json(VyosWriteResponse {
+                success: true,
+                message: format!("Interface {name} {action}d successfully"),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("VyOS interface {action} failed for {name}: {e}");
+            let msg = format!("VyOS error: {e}");
+            log_write_failure(
+                &state,
+                &actor,
+                "interface_toggle",
+                &description,
+                &commands,
+                &msg,
+            )
+            .await;
+            Err(AppError::BadGateway(msg))
+        }
+    }
+}
+
+// ── Interface DHCP Client Status ─────────────────────────────────────────────
+
+/// How long a DHCP client status is served from cache.
+const DHCP_CLIENT_STATUS_CACHE_SECS: u64 = 30;
+
+/// Cached DHCP client status keyed by interface name.
+pub type DhcpClientStatusCache = Arc<Mutex<HashMap<String, (Instant, DhcpClientStatus)>>>;
+
+/// Lease acquired by a VyOS interface acting as a DHCP (or PPPoE) client.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct DhcpClientLease {
+    pub ip_address: String,
+    pub subnet_mask: String,
+    pub gateway: String,
+    pub dns_servers: Vec<String>,
+    pub domain_name: Option<String>,
+    pub lease_obtained: String,
+    pub lease_expires: String,
+    pub dhcp_server: String,
+}
+
+/// Response for `GET /api/v1/vyos/interfaces/:name/dhcp-client-status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DhcpClientStatus {
+    pub interface: String,
+    pub has_lease: bool,
+    #[serde(flatten)]
+    pub lease: Option<DhcpClientLease>,
+}
+
+/// Parse the `key : value` output of `show ... dhcp leases` for one interface.
+///
+/// Expected format:
+/// ```text
+/// interface  : eth0
+/// ip address : 203.0.113.10                [Active]
+/// subnet mask: 255.255.255.0
+/// domain name: isp.example
+/// router     : 203.0.113.1
+/// name server: 1.1.1.1 8.8.8.8
+/// dhcp server: 203.0.113.1
+/// last update: Sat Feb 21 10:00:00 UTC 2026
+/// expiry     : Sun Feb 22 10:00:00 UTC 2026
+/// ```
+///
+/// Returns `None` when no IP address is present (no active lease).
+pub fn parse_dhcp_client_lease(text: &str) -> Option<DhcpClientLease> {
+    let mut lease = DhcpClientLease::default();
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "ip address" | "ip" | "address" => {
+                lease.ip_address = value.split_whitespace().next().unwrap_or("").to_string();
+            }
+            "subnet mask" | "netmask" => lease.subnet_mask = value.to_string(),
+            "router" | "gateway" | "routers" => {
+                lease.gateway = value.split_whitespace().next().unwrap_or("").to_string();
+            }
+            "name server" | "name servers" | "dns" | "domain name servers" => {
+                lease.dns_servers = value
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            "domain name" | "domain" => lease.domain_name = Some(value.to_string()),
+            "last update" | "lease obtained" | "obtained" => {
+                lease.lease_obtained = value.to_string();
+            }
+            "expiry" | "expires" | "lease expires" => lease.lease_expires = value.to_string(),
+            "dhcp server" | "server" => lease.dhcp_server = value.to_string(),
+            _ => {}
+        }
+    }
+
+    (!lease.ip_address.is_empty()).then_some(lease)
+}
+
+/// Convert a prefix length into a dotted subnet mask, e.g. 24 → "255.255.255.0".
+fn prefix_to_netmask(prefix: u8) -> Option<String> {
+    if prefix > 32 {
+        return None;
+    }
+    let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+    Some(std::net::Ipv4Addr::from(mask).to_string())
+}
+
+/// Parse `show interfaces pppoe <name>` output into a lease-like view.
+///
+/// Only the local address and peer (gateway) are available; PPPoE has no
+/// lease timestamps or DHCP server.
+pub fn parse_pppoe_session(text: &str) -> Option<DhcpClientLease> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| l.starts_with("inet ") && l.contains(" peer "))?;
+    let mut parts = line.split_whitespace();
+    let ip_address = parts.nth(1)?.to_string();
+    let peer = parts.skip_while(|p| *p != "peer").nth(1)?;
+    let (gateway, prefix) = peer.split_once('/').unwrap_or((peer, "32"));
+
+    Some(DhcpClientLease {
+        ip_address,
+        subnet_mask: prefix_to_netmask(prefix.parse().ok()?)?,
+        gateway: gateway.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Read the client lease of an interface from VyOS.
+///
+/// Interfaces whose config has no `address dhcp` have no lease.
+async fn fetch_dhcp_client_lease(
+    client: &crate::vyos::client::VyosClient,
+    iface_type: &str,
+    name: &str,
+) -> anyhow::Result<Option<DhcpClientLease>> {
+    if iface_type == "pppoe" {
+        let data = client.show(&["interfaces", "pppoe", name]).await?;
+        return Ok(parse_pppoe_session(data.as_str().unwrap_or("")));
+    }
+
+    let addresses = fetch_interface_addresses(client, iface_type, name).await?;
+    if !addresses.iter().any(|a| a == "dhcp") {
+        return Ok(None);
+    }
+
+    match client
+        .show(&["interfaces", iface_type, name, "dhcp", "leases"])
+        .await
+    {
+        Ok(data) => Ok(parse_dhcp_client_lease(data.as_str().unwrap_or(""))),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Ok(None)
+            } else {
+                Err(e)
+            }
+        }
+    }
+}
+
+/// GET /api/v1/vyos/interfaces/:name/dhcp-client-status — WAN lease details.
+///
+/// Returns `{"has_lease": false}` when the interface is not a DHCP client or
+/// has no active lease. PPPoE interfaces report their session address instead.
+pub async fn interface_dhcp_client_status(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<DhcpClientStatus>, AppError> {
+    {
+        let cache = state.dhcp_client_status_cache.lock().await;
+        if let Some((fetched_at, cached)) = cache.get(&name) {
+            if fetched_at.elapsed().as_secs() < DHCP_CLIENT_STATUS_CACHE_SECS {
+                return Ok(Json(cached.clone()));
+            }
+        }
+    }
+
+    let client = get_vyos_client(&state).await?;
+    let iface_type = interface_type(&name).ok_or_else(|| {
+        AppError::Validation(format!("Cannot determine interface type for '{name}'"))
+    })?;
+
+    let lease = fetch_dhcp_client_lease(&client, iface_type, &name)
+        .await
+        .map_err(|e| {
+            tracing::error!("VyOS DHCP client status query failed for {name}: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+
+    let status = DhcpClientStatus {
+        interface: name.clone(),
+        has_lease: lease.is_some(),
+        lease,
+    };
+    let mut cache = state.dhcp_client_status_cache.lock().await;
+    cache.insert(name, (Instant::now(), status.clone()));
+    Ok(Json(status))
+}
+
+// ── Interface Traceroute ────────────────────────────────────────────────────
+
+/// Maximum number of hops returned by the traceroute endpoint.
+const TRACEROUTE_MAX_HOPS: usize = 30;
+
+/// Minimum interval between traceroutes from the same client IP.
+const TRACEROUTE_RATE_LIMIT_SECS: u64 = 10;
+
+/// Last traceroute time per client IP.
+pub type TracerouteRateLimiter = Arc<dashmap::DashMap<std::net::IpAddr, Instant>>;
+
+/// Query parameters for the traceroute endpoint.
+#[derive(Debug, Deserialize)]
+pub struct TraceRouteQuery {
+    pub target: String,
+}
+
+/// A single hop of a traceroute.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TraceHop {
+    pub hop_number: u8,
+    pub ip: Option<String>,
+    pub hostname: Option<String>,
+    pub rtt_ms: Option<f32>,
+    /// True when the hop did not respond (`* * *`).
+    pub asterisk: bool,
+}
+
+/// Response for `GET /api/v1/vyos/interfaces/:name/trace-route`.
+#[derive(Debug, Serialize)]
+pub struct TraceRouteResult {
+    pub target: String,
+    pub hops: Vec<TraceHop>,
+    pub destination_reached: bool,
+    pub total_hops: usize,
+}
+
+/// Accept an IP address or a plain DNS hostname (no shell metacharacters).
+fn is_valid_trace_target(target: &str) -> bool {
+    if target.parse::<std::net::IpAddr>().is_ok() {
+        return true;
+    }
+    !target.is_empty()
+        && target.len() <= 253
+        && target.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Parse traceroute output into hops (capped at [`TRACEROUTE_MAX_HOPS`]).
+///
+/// Expected format:
+/// ```text
+/// traceroute to 8.8.8.8 (8.8.8.8), 30 hops max, 60 byte packets
+///  1  gateway (192.168.1.1)  0.512 ms  0.480 ms  0.455 ms
+///  2  * * *
+///  3  8.8.8.8  9.120 ms  9.011 ms  8.950 ms
+/// ```
+pub fn parse_traceroute_text(text: &str) -> Vec<TraceHop> {
+    let mut hops = Vec::new();
+
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace().peekable();
+        let Some(hop_number) = tokens.next().and_then(|t| t.parse::<u8>().ok()) else {
+            continue;
+        };
+
+        let mut hop = TraceHop {
+            hop_number,
+            ip: None,
+            hostname: None,
+            rtt_ms: None,
+            asterisk: false,
+        };
+
+        while let Some(token) = tokens.next() {
+            if token == "*" {
+                continue;
+            }
+            if let Some(ip) = token.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+                // "hostname (ip)" — the preceding token was the hostname.
+                if hop.ip.is_some() && hop.hostname.is_none() {
+                    hop.hostname = hop.ip.take();
+                }
+                hop.ip = Some(ip.to_string());
+                continue;
+            }
+            if let Ok(rtt) = token.trim_end_matches("ms").parse::<f32>() {
+                if token.ends_with("ms") || tokens.peek() == Some(&"ms") {
+                    if hop.rtt_ms.is_none() {
+                        hop.rtt_ms = Some(rtt);
+                    }
+                    continue;
+                }
+            }
+            if token == "ms" || token.starts_with('!') {
+                continue;
+            }
+            if hop.ip.is_none() && hop.hostname.is_none() {
+                hop.ip = Some(token.to_string());
+            }
+        }
+
+        hop.asterisk = hop.ip.is_none();
+        hops.push(hop);
+        if hops.len() >= TRACEROUTE_MAX_HOPS {
+            break;
+        }
+    }
+
+    hops
+}
+
+/// Address the traceroute resolved the target to, from the header line.
+fn traceroute_target_ip(text: &str) -> Option<String> {
+    let header = text.lines().find(|l| l.starts_with("traceroute to"))?;
+    let start = header.find('(')? + 1;
+    let end = start + header[start..].find(')')?;
+    Some(header[start..end].to_string())
+}
+
+/// GET /api/v1/vyos/interfaces/:name/trace-route?target= — traceroute from the router.
+///
+/// Diagnostic only; limited to one run per client IP every 10 seconds.
+pub async fn interface_trace_route(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    Path(name): Path<String>,
+    Query(params): Query<TraceRouteQuery>,
+) -> Result<Json<TraceRouteResult>, AppError> {
+    let target = params.target.trim().to_string();
+    if !is_valid_trace_target(&target) {
+        return Err(AppError::Validation(format!(
+            "Invalid traceroute target '{target}'"
+        )));
+    }
+    if interface_type(&name).is_none() {
+        return Err(AppError::Validation(format!(
+            "Cannot determine interface type for '{name}'"
+        )));
+    }
+
+    // Drop expired entries, then check and reserve this client's slot.
+    state
+        .traceroute_limiter
+        .retain(|_, at| at.elapsed().as_secs() < TRACEROUTE_RATE_LIMIT_SECS);
+    match state.traceroute_limiter.entry(addr.ip()) {
+        dashmap::mapref::entry::Entry::Occupied(entry) => {
+            let retry_after = TRACEROUTE_RATE_LIMIT_SECS
+                .saturating_sub(entry.get().elapsed().as_secs())
+                .max(1);
+            return Err(AppError::RateLimited(
+                format!(
+                    "Rate limited. Please wait {retry_after}s before running another traceroute."
+                ),
+                retry_after,
+            ));
+        }
+        dashmap::mapref::entry::Entry::Vacant(entry) => {
+            entry.insert(Instant::now());
+        }
+    }
+
+    let client = get_vyos_client(&state).await?;
+    let description = format!("Traceroute to {target} via {name}");
+    let commands = vec![format!("traceroute {target} interface {name}")];
+
+    let output = match client.run_traceroute(&target, &name).await {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::error!("VyOS traceroute to {target} failed: {e}");
+            let msg = format!("VyOS error: {e}");
+            log_write_failure(&state, &actor, "traceroute", &description, &commands, &msg).await;
+            return Err(AppError::BadGateway(msg));
+        }
+    };
+    audit::log_success(&state.db, &actor, "traceroute", &description, &commands).await;
+
+    let hops = parse_traceroute_text(&output);
+    let target_ip = traceroute_target_ip(&output).unwrap_or_else(|| target.clone());
+    let destination_reached = hops
+        .last()
+        .and_then(|h| h.ip.as_deref())
+        .is_some_and(|ip| ip == target_ip || ip == target);
+
+    Ok(Json(TraceRouteResult {
+        target,
+        total_hops: hops.len(),
+        hops,
+        destination_reached,
+    }))
+}
+
+// ── Interface IP Aliases ─────────────────────────────────────────────────────
+
+/// Request body for adding a secondary IP address to an interface.
+#[derive(Debug, Deserialize)]
+pub struct InterfaceAddressRequest {
+    /// Address in CIDR notation, e.g. "10.10.0.100/24".
+    pub address: String,
+}
+
+/// All configured addresses (primary and secondary) on an interface.
+#[derive(Debug, Serialize)]
+pub struct InterfaceAddresses {
+    pub interface: String,
+    pub addresses: Vec<String>,
+}
+
+/// Parse the `address` values from a single interface's VyOS config JSON.
+///
+/// VyOS returns a single string when one address is configured and an array
+/// when there are several. Non-CIDR values such as `dhcp` are kept as-is.
+fn parse_interface_addresses(config: &Value) -> Vec<String> {
+    match config.get("address") {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(arr)) => arr
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fetch the configured addresses of an interface from VyOS.
 ///
-/// e.g. "eth0" → "ethernet", "bond0" → "bonding", "br0" → "bridge", "lo" → "loopback"
-fn interface_type(name: &str) -> Option<&'static str> {
-    if name.starts_with("eth") {
-        Some("ethernet")
-    } else if name.starts_with("bond") {
-        Some("bonding")
-    } else if name.starts_with("br") {
-        Some("bridge")
-    } else if name.starts_with("wg") {
-        Some("wireguard")
-    } else if name == "lo" || name.starts_with("lo") {
-        Some("loopback")
-    } else if name.starts_with("vtun") {
-        Some("openvpn")
-    } else if name.starts_with("tun") {
-        Some("tunnel")
-    } else if name.starts_with("vti") {
-        Some("vti")
-    } else if name.starts_with("pppoe") {
-        Some("pppoe")
-    } else {
-        None
+/// An interface without any address config yields an empty list.
+async fn fetch_interface_addresses(
+    client: &crate::vyos::client::VyosClient,
+    iface_type: &str,
+    name: &str,
+) -> anyhow::Result<Vec<String>> {
+    match client.retrieve(&["interfaces", iface_type, name]).await {
+        Ok(data) => Ok(parse_interface_addresses(&data)),
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Ok(Vec::new())
+            } else {
+                Err(e)
+            }
+        }
     }
 }
 
-/// POST /api/v1/vyos/interfaces/:name/toggle — enable or disable a VyOS interface.
-///
-/// Sends `set interfaces <type> <name> disable` or
-/// `delete interfaces <type> <name> disable` to VyOS.
-pub async fn interface_toggle(
+/// GET /api/v1/vyos/interfaces/:name/addresses — list configured addresses on an interface.
+pub async fn interface_addresses(
     State(state): State<AppState>,
     Path(name): Path<String>,
-    Json(body): Json<InterfaceToggleRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
+) -> Result<Json<InterfaceAddresses>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    let iface_type = interface_type(&name).ok_or_else(|| {
+        AppError::Validation(format!("Cannot determine interface type for '{name}'"))
     })?;
 
+    let addresses = fetch_interface_addresses(&client, iface_type, &name)
+        .await
+        .map_err(|e| {
+            tracing::error!("VyOS interface address query failed for {name}: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+
+    Ok(Json(InterfaceAddresses {
+        interface: name,
+        addresses,
+    }))
+}
+
+/// POST /api/v1/vyos/interfaces/:name/ip-alias — add a secondary IP address.
+///
+/// Sends `set interfaces <type> <name> address <cidr>` to VyOS.
+pub async fn add_interface_ip_alias(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(name): Path<String>,
+    Json(body): Json<InterfaceAddressRequest>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+
     let iface_type = interface_type(&name).ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: format!("Cannot determine interface type for '{name}'"),
-            }),
-        )
+        AppError::Validation(format!("Cannot determine interface type for '{name}'"))
     })?;
 
-    let action = if body.disable { "disable" } else { "enable" };
-    tracing::info!("VyOS: {action} interface {iface_type} {name}");
+    if !is_valid_cidr(&body.address) {
+        return Err(AppError::Validation(format!(
+            "Invalid CIDR address: {}",
+            body.address
+        )));
+    }
 
-    let description = format!(
-        "{} interface {} ({})",
-        if body.disable { "Disable" } else { "Enable" },
-        name,
-        iface_type
+    tracing::info!(
+        "VyOS: adding address {} to {iface_type} {name}",
+        body.address
     );
-    let commands = vec![if body.disable {
-        format!("set interfaces {iface_type} {name} disable")
-    } else {
-        format!("delete interfaces {iface_type} {name} disable")
-    }];
 
-    let result = if body.disable {
-        client
-            .configure_set(&["interfaces", iface_type, &name, "disable"])
-            .await
-    } else {
-        client
-            .configure_delete(&["interfaces", iface_type, &name, "disable"])
-            .await
-    };
+    let description = format!("Add address {} to interface {name}", body.address);
+    let commands = vec![format!(
+        "set interfaces {iface_type} {name} address {}",
+        body.address
+    )];
 
-    match result {
+    match client
+        .configure_set(&["interfaces", iface_type, &name, "address", &body.address])
+        .await
+    {
         Ok(_) => {
-            audit::log_success(&state.db, "interface_toggle", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "interface_address_add",
+                &description,
+                &commands,
+            )
+            .await;
+            state
+                .vyos_cache
+                .invalidate(&[CACHE_INTERFACES, CACHE_ROUTES]);
             Ok(Json(VyosWriteResponse {
                 success: true,
-                message: format!("Interface {name} {action}d successfully"),
+                message: format!("Address {} added to {name}", body.address),
             }))
         }
         Err(e) => {
-            tracing::error!("VyOS interface {action} failed for {name}: {e}");
+            tracing::error!("VyOS interface address add failed for {name}: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(&state.db, "interface_toggle", &description, &commands, &msg).await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            log_write_failure(
+                &state,
+                &actor,
+                "interface_address_add",
+                &description,
+                &commands,
+                &msg,
+            )
+            .await;
+            Err(AppError::BadGateway(msg))
+        }
+    }
+}
+
+/// DELETE /api/v1/vyos/interfaces/:name/ip-alias/:address — remove an IP address.
+///
+/// The address arrives URL-encoded (e.g. "10.10.0.100%2F24"); Axum decodes it.
+/// Refuses to remove the last remaining address on the interface.
+pub async fn remove_interface_ip_alias(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path((name, address)): Path<(String, String)>,
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+
+    let iface_type = interface_type(&name).ok_or_else(|| {
+        AppError::Validation(format!("Cannot determine interface type for '{name}'"))
+    })?;
+
+    if !is_valid_cidr(&address) {
+        return Err(AppError::Validation(format!(
+            "Invalid CIDR address: {address}"
+        )));
+    }
+
+    let existing = fetch_interface_addresses(&client, iface_type, &name)
+        .await
+        .map_err(|e| {
+            tracing::error!("VyOS interface address query failed for {name}: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+
+    if !existing.contains(&address) {
+        return Err(AppError::ResourceNotFound(
+            "interface-address",
+            format!("Address {address} is not configured on {name}"),
+        ));
+    }
+    if existing.len() <= 1 {
+        return Err(AppError::Validation(format!(
+            "Cannot remove {address}: it is the only address configured on {name}"
+        )));
+    }
+
+    tracing::info!("VyOS: removing address {address} from {iface_type} {name}");
+
+    let description = format!("Remove address {address} from interface {name}");
+    let commands = vec![format!(
+        "delete interfaces {iface_type} {name} address {address}"
+    )];
+
+    match client
+        .configure_delete(&["interfaces", iface_type, &name, "address", &address])
+        .await
+    {
+        Ok(_) => {
+            audit::log_success(
+                &state.db,
+                &actor,
+                "interface_address_remove",
+                &description,
+                &commands,
+            )
+            .await;
+            state
+                .vyos_cache
+                .invalidate(&[CACHE_INTERFACES, CACHE_ROUTES]);
+            Ok(Json(VyosWriteResponse {
+                success: true,
+                message: format!("Address {address} removed from {name}"),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("VyOS interface address remove failed for {name}: {e}");
+            let msg = format!("VyOS error: {e}");
+            log_write_failure(
+                &state,
+                &actor,
+                "interface_address_remove",
+                &description,
+                &commands,
+                &msg,
+            )
+            .await;
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -1639,8 +4309,8 @@ pub struct CreateDhcpStaticMappingRequest {
 /// Reads VyOS config at `service dhcp-server` and parses static-mapping entries.
 pub async fn dhcp_static_mappings(
     State(state): State<AppState>,
-) -> Result<Json<Vec<DhcpStaticMapping>>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
+) -> Result<Json<Vec<DhcpStaticMapping>>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     let config = match client.retrieve(&["service", "dhcp-server"]).await {
         Ok(c) => c,
@@ -1650,7 +4320,7 @@ pub async fn dhcp_static_mappings(
                 return Ok(Json(Vec::new()));
             }
             tracing::error!("VyOS DHCP config query failed: {e}");
-            return Err(StatusCode::BAD_GATEWAY);
+            return Err(AppError::BadGateway(format!("VyOS error: {e}")));
         }
     };
 
@@ -1717,55 +4387,11 @@ fn parse_dhcp_static_mappings(config: &Value) -> Vec<DhcpStaticMapping> {
 /// POST /api/v1/vyos/dhcp/static-mappings — create a DHCP static mapping.
 pub async fn create_dhcp_static_mapping(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Json(body): Json<CreateDhcpStaticMappingRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
-
-    // Validate MAC address format
-    if !is_valid_mac(&body.mac) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Invalid MAC address format. Expected XX:XX:XX:XX:XX:XX".to_string(),
-            }),
-        ));
-    }
-
-    // Validate IP address format
-    if body.ip.parse::<std::net::Ipv4Addr>().is_err() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Invalid IP address format".to_string(),
-            }),
-        ));
-    }
-
-    // Validate name (alphanumeric, hyphens, underscores only)
-    if body.name.is_empty()
-        || !body
-            .name
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
-    {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Invalid mapping name. Use alphanumeric characters, hyphens, and underscores only.".to_string(),
-            }),
-        ));
-    }
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
+    validate_dhcp_static_mapping(&body).map_err(AppError::Validation)?;
 
     let base_path = format!(
         "service dhcp-server shared-network-name {} subnet {} static-mapping {}",
@@ -1809,21 +4435,16 @@ pub async fn create_dhcp_static_mapping(
     if let Err(e) = mac_result {
         tracing::error!("VyOS DHCP static-mapping mac set failed: {e}");
         let msg = format!("Failed to set MAC address: {e}");
-        audit::log_failure(
-            &state.db,
+        log_write_failure(
+            &state,
+            &actor,
             "dhcp_static_mapping_create",
             &audit_desc,
             &audit_commands,
             &msg,
         )
         .await;
-        return Err((
-            StatusCode::BAD_GATEWAY,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::BadGateway(msg));
     }
 
     // Set ip-address
@@ -1845,8 +4466,9 @@ pub async fn create_dhcp_static_mapping(
     if let Err(e) = ip_result {
         tracing::error!("VyOS DHCP static-mapping ip set failed: {e}");
         let msg = format!("Failed to set IP address: {e}");
-        audit::log_failure(
-            &state.db,
+        log_write_failure(
+            &state,
+            &actor,
             "dhcp_static_mapping_create",
             &audit_desc,
             &audit_commands,
@@ -1866,35 +4488,361 @@ pub async fn create_dhcp_static_mapping(
                 &body.name,
             ])
             .await;
-        return Err((
-            StatusCode::BAD_GATEWAY,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::BadGateway(msg));
+    }
+
+    tracing::info!(
+        "VyOS: DHCP static mapping '{}' created ({base_path})",
+        body.name
+    );
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "dhcp_static_mapping_create",
+        &audit_desc,
+        &audit_commands,
+    )
+    .await;
+
+    state.vyos_cache.invalidate(&[CACHE_DHCP]);
+    Ok(Json(VyosWriteResponse {
+        success: true,
+        message: format!(
+            "Static mapping '{}' created: {} -> {}",
+            body.name, body.mac, body.ip
+        ),
+    }))
+}
+
+/// Validate the MAC, IP and name of a static mapping request.
+fn validate_dhcp_static_mapping(body: &CreateDhcpStaticMappingRequest) -> Result<(), String> {
+    if !is_valid_mac(&body.mac) {
+        return Err("Invalid MAC address format. Expected XX:XX:XX:XX:XX:XX".to_string());
+    }
+
+    if body.ip.parse::<std::net::Ipv4Addr>().is_err() {
+        return Err("Invalid IP address format".to_string());
+    }
+
+    // Alphanumeric, hyphens, underscores only
+    if body.name.is_empty()
+        || !body
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(
+            "Invalid mapping name. Use alphanumeric characters, hyphens, and underscores only."
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Request body for converting a lease to a static mapping.
+#[derive(Debug, Deserialize)]
+pub struct MakeLeaseStaticRequest {
+    /// The shared-network-name (e.g. "LAN")
+    pub network: String,
+    /// The subnet CIDR (e.g. "10.10.0.0/24")
+    pub subnet: String,
+    /// Mapping name / hostname identifier
+    pub name: String,
+}
+
+/// POST /api/v1/vyos/dhcp-leases/:ip/make-static — pin an active lease.
+///
+/// Looks up the MAC of the active lease for `ip` and creates a static
+/// mapping for it in one commit. 404 if there is no active lease for the
+/// IP; 409 if the IP or mapping name is already used by a static mapping.
+pub async fn make_lease_static(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(ip): Path<String>,
+    Json(body): Json<MakeLeaseStaticRequest>,
+) -> Result<Json<DhcpStaticMapping>, AppError> {
+    let addr: std::net::Ipv4Addr = ip
+        .parse()
+        .map_err(|_| AppError::Validation("Invalid IP address format".to_string()))?;
+    let subnet: ipnetwork::Ipv4Network = body
+        .subnet
+        .parse()
+        .map_err(|_| AppError::Validation(format!("Invalid subnet: '{}'", body.subnet)))?;
+    if !subnet.contains(addr) {
+        return Err(AppError::Validation(format!(
+            "{ip} is not in subnet {}",
+            body.subnet
+        )));
+    }
+    let client = get_vyos_client(&state).await?;
+
+    let leases = client
+        .show(&["dhcp", "server", "leases"])
+        .await
+        .map_err(|e| {
+            tracing::error!("VyOS DHCP leases query failed: {e}");
+            AppError::BadGateway(format!("VyOS error: {e}"))
+        })?;
+    let lease = parse_dhcp_leases_text(leases.as_str().unwrap_or(""))
+        .into_iter()
+        .find(|l| l.ip == ip && l.state.eq_ignore_ascii_case("active"))
+        .ok_or_else(|| {
+            AppError::ResourceNotFound("dhcp-lease", format!("No active lease for {ip}"))
+        })?;
+
+    let mapping = DhcpStaticMapping {
+        network: body.network,
+        subnet: body.subnet,
+        name: body.name,
+        mac: lease.mac.to_lowercase(),
+        ip,
+    };
+    validate_dhcp_static_mapping(&CreateDhcpStaticMappingRequest {
+        network: mapping.network.clone(),
+        subnet: mapping.subnet.clone(),
+        name: mapping.name.clone(),
+        mac: mapping.mac.clone(),
+        ip: mapping.ip.clone(),
+    })
+    .map_err(AppError::Validation)?;
+
+    let config = match client.retrieve(&["service", "dhcp-server"]).await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Value::Null
+            } else {
+                tracing::error!("VyOS DHCP config query failed: {e}");
+                return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+            }
+        }
+    };
+    if let Some(existing) = parse_dhcp_static_mappings(&config).into_iter().find(|m| {
+        m.ip == mapping.ip
+            || (m.network == mapping.network
+                && m.subnet == mapping.subnet
+                && m.name == mapping.name)
+    }) {
+        return Err(AppError::Conflict(format!(
+            "Static mapping '{}' already exists for {} ({})",
+            existing.name, existing.ip, existing.mac
+        )));
+    }
+
+    let description = format!(
+        "Convert DHCP lease {} ({}) to static mapping '{}' (network={}, subnet={})",
+        mapping.ip, mapping.mac, mapping.name, mapping.network, mapping.subnet
+    );
+    let base = [
+        "service",
+        "dhcp-server",
+        "shared-network-name",
+        &mapping.network,
+        "subnet",
+        &mapping.subnet,
+        "static-mapping",
+        &mapping.name,
+    ];
+    let commands: Vec<String> = [("mac-address", &mapping.mac), ("ip-address", &mapping.ip)]
+        .iter()
+        .map(|(key, value)| format!("set {} {key} {value}", base.join(" ")))
+        .collect();
+
+    tracing::info!(
+        "VyOS: converting DHCP lease {} ({}) to static mapping '{}'",
+        mapping.ip,
+        mapping.mac,
+        mapping.name
+    );
+
+    let mut mac_path = base.to_vec();
+    mac_path.extend(["mac-address", mapping.mac.as_str()]);
+    let mut ip_path = base.to_vec();
+    ip_path.extend(["ip-address", mapping.ip.as_str()]);
+    if let Err(e) = client.configure_set_many(&[mac_path, ip_path]).await {
+        tracing::error!("VyOS DHCP lease to static mapping failed: {e}");
+        let msg = format!("VyOS error: {e}");
+        log_write_failure(
+            &state,
+            &actor,
+            "dhcp_lease_to_static",
+            &description,
+            &commands,
+            &msg,
+        )
+        .await;
+        return Err(AppError::BadGateway(msg));
     }
 
-    tracing::info!(
-        "VyOS: DHCP static mapping '{}' created ({base_path})",
-        body.name
-    );
-
     audit::log_success(
         &state.db,
-        "dhcp_static_mapping_create",
-        &audit_desc,
-        &audit_commands,
+        &actor,
+        "dhcp_lease_to_static",
+        &description,
+        &commands,
     )
     .await;
 
-    Ok(Json(VyosWriteResponse {
-        success: true,
-        message: format!(
-            "Static mapping '{}' created: {} -> {}",
-            body.name, body.mac, body.ip
-        ),
-    }))
+    state.vyos_cache.invalidate(&[CACHE_DHCP]);
+    Ok(Json(mapping))
+}
+
+// ── DHCP Pool Utilization ───────────────────────────────────────────────────
+
+/// How long pool statistics are served from cache.
+const DHCP_POOLS_CACHE_SECS: u64 = 30;
+
+/// Cached pool statistics with the instant they were computed.
+pub type DhcpPoolsCache = Arc<Mutex<Option<(Instant, Vec<DhcpPoolStats>)>>>;
+
+/// Utilization above which a `dhcp_pool_warning` event is broadcast.
+const DHCP_POOL_WARNING_PCT: f64 = 90.0;
+
+/// Address usage for one DHCP subnet.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DhcpPoolStats {
+    /// The shared-network-name (e.g. "LAN")
+    pub network: String,
+    /// The subnet CIDR (e.g. "10.10.0.0/24")
+    pub subnet: String,
+    /// Usable host addresses in the subnet.
+    pub total_addresses: u32,
+    /// Active leases for addresses without a static mapping.
+    pub active_leases: u32,
+    pub static_mappings: u32,
+    pub available: u32,
+    pub utilization_pct: f64,
+}
+
+/// Usable host addresses in an IPv4 subnet (network and broadcast excluded
+/// except for /31 and /32).
+fn usable_addresses(subnet: &ipnetwork::Ipv4Network) -> u32 {
+    let size = 1u64 << (32 - u32::from(subnet.prefix()));
+    let usable = if subnet.prefix() >= 31 {
+        size
+    } else {
+        size - 2
+    };
+    usable.min(u64::from(u32::MAX)) as u32
+}
+
+/// Compute per-subnet usage from the `service dhcp-server` config and the
+/// current leases. Leases are assigned to subnets by address.
+pub fn compute_dhcp_pool_stats(config: &Value, leases: &[VyosDhcpLease]) -> Vec<DhcpPoolStats> {
+    let statics = parse_dhcp_static_mappings(config);
+    let static_ips: std::collections::HashSet<&str> =
+        statics.iter().map(|m| m.ip.as_str()).collect();
+    let active: Vec<std::net::Ipv4Addr> = leases
+        .iter()
+        .filter(|l| l.state.eq_ignore_ascii_case("active") && !static_ips.contains(l.ip.as_str()))
+        .filter_map(|l| l.ip.parse().ok())
+        .collect();
+
+    let mut pools = Vec::new();
+    let Some(networks) = config
+        .get("shared-network-name")
+        .and_then(|v| v.as_object())
+    else {
+        return pools;
+    };
+    for (network, network_val) in networks {
+        let Some(subnets) = network_val.get("subnet").and_then(|v| v.as_object()) else {
+            continue;
+        };
+        for subnet_cidr in subnets.keys() {
+            let Ok(subnet) = subnet_cidr.parse::<ipnetwork::Ipv4Network>() else {
+                continue;
+            };
+            let total_addresses = usable_addresses(&subnet);
+            let static_mappings = statics
+                .iter()
+                .filter(|m| &m.network == network && &m.subnet == subnet_cidr)
+                .count() as u32;
+            let active_leases = active.iter().filter(|ip| subnet.contains(**ip)).count() as u32;
+            let used = static_mappings.saturating_add(active_leases);
+            let utilization_pct = if total_addresses == 0 {
+                0.0
+            } else {
+                (f64::from(used.min(total_addresses)) * 1000.0 / f64::from(total_addresses)).round()
+                    / 10.0
+            };
+            pools.push(DhcpPoolStats {
+                network: network.clone(),
+                subnet: subnet_cidr.clone(),
+                total_addresses,
+                active_leases,
+                static_mappings,
+                available: total_addresses.saturating_sub(used),
+                utilization_pct,
+            });
+        }
+    }
+    pools
+}
+
+/// GET /api/v1/vyos/dhcp/pools — address usage per DHCP subnet.
+///
+/// Combines the DHCP server config and current leases; results are cached
+/// for 30 seconds. Subnets above 90% utilization are broadcast as
+/// `dhcp_pool_warning` each time the statistics are recomputed.
+pub async fn dhcp_pools(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DhcpPoolStats>>, AppError> {
+    let mut cache = state.dhcp_pools_cache.lock().await;
+    if let Some((computed_at, ref cached)) = *cache {
+        if computed_at.elapsed().as_secs() < DHCP_POOLS_CACHE_SECS {
+            return Ok(Json(cached.clone()));
+        }
+    }
+
+    let client = get_vyos_client(&state).await?;
+    let config = match client.retrieve(&["service", "dhcp-server"]).await {
+        Ok(c) => c,
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                Value::Null
+            } else {
+                tracing::error!("VyOS DHCP config query failed: {e}");
+                return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+            }
+        }
+    };
+    let leases = if config.is_null() {
+        Vec::new()
+    } else {
+        let raw = client
+            .show(&["dhcp", "server", "leases"])
+            .await
+            .map_err(|e| {
+                tracing::error!("VyOS DHCP leases query failed: {e}");
+                AppError::BadGateway(format!("VyOS error: {e}"))
+            })?;
+        parse_dhcp_leases_text(raw.as_str().unwrap_or(""))
+    };
+
+    let pools = compute_dhcp_pool_stats(&config, &leases);
+    for pool in pools
+        .iter()
+        .filter(|p| p.utilization_pct > DHCP_POOL_WARNING_PCT)
+    {
+        tracing::warn!(
+            network = %pool.network,
+            subnet = %pool.subnet,
+            utilization_pct = pool.utilization_pct,
+            "DHCP pool nearly exhausted"
+        );
+        state.ws_hub.broadcast(
+            "dhcp_pool_warning",
+            serde_json::to_value(pool).unwrap_or_default(),
+        );
+    }
+
+    *cache = Some((Instant::now(), pools.clone()));
+    Ok(Json(pools))
 }
 
 /// Path parameters for deleting a DHCP static mapping.
@@ -1908,17 +4856,10 @@ pub struct DhcpStaticMappingPath {
 /// DELETE /api/v1/vyos/dhcp/static-mappings/:network/:subnet/:name — delete a DHCP static mapping.
 pub async fn delete_dhcp_static_mapping(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(path): Path<DhcpStaticMappingPath>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!(
         "VyOS: deleting DHCP static mapping '{}' (network={}, subnet={})",
@@ -1953,11 +4894,13 @@ pub async fn delete_dhcp_static_mapping(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "dhcp_static_mapping_delete",
                 &description,
                 &commands,
             )
             .await;
+            state.vyos_cache.invalidate(&[CACHE_DHCP]);
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Static mapping '{}' deleted", path.name),
@@ -1966,21 +4909,16 @@ pub async fn delete_dhcp_static_mapping(
         Err(e) => {
             tracing::error!("VyOS DHCP static-mapping delete failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "dhcp_static_mapping_delete",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -2015,25 +4953,13 @@ pub struct CreateStaticRouteRequest {
 pub async fn create_static_route(
     State(state): State<AppState>,
     Json(body): Json<CreateStaticRouteRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     // Validate destination CIDR
     if !is_valid_cidr(&body.destination) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Invalid destination CIDR. Expected format: x.x.x.x/n".to_string(),
-            }),
+        return Err(AppError::Validation(
+            "Invalid destination CIDR. Expected format: x.x.x.x/n".to_string(),
         ));
     }
 
@@ -2041,24 +4967,16 @@ pub async fn create_static_route(
 
     // Require either next_hop or blackhole
     if !is_blackhole && body.next_hop.is_none() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Either next-hop IP or blackhole option is required".to_string(),
-            }),
+        return Err(AppError::Validation(
+            "Either next-hop IP or blackhole option is required".to_string(),
         ));
     }
 
     // Validate next-hop IP if provided
     if let Some(ref nh) = body.next_hop {
         if !is_valid_ip(nh) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: "Invalid next-hop IP address".to_string(),
-                }),
+            return Err(AppError::Validation(
+                "Invalid next-hop IP address".to_string(),
             ));
         }
     }
@@ -2066,12 +4984,8 @@ pub async fn create_static_route(
     // Validate distance if provided
     if let Some(d) = body.distance {
         if d > 255 {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: "Distance must be between 0 and 255".to_string(),
-                }),
+            return Err(AppError::Validation(
+                "Distance must be between 0 and 255".to_string(),
             ));
         }
     }
@@ -2095,13 +5009,9 @@ pub async fn create_static_route(
 
         if let Err(e) = result {
             tracing::error!("VyOS static route blackhole set failed: {e}");
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("Failed to create blackhole route: {e}"),
-                }),
-            ));
+            return Err(AppError::BadGateway(format!(
+                "Failed to create blackhole route: {e}"
+            )));
         }
 
         // Set description if provided
@@ -2136,6 +5046,7 @@ pub async fn create_static_route(
                 .await;
         }
 
+        state.vyos_cache.invalidate(&[CACHE_ROUTES]);
         Ok(Json(VyosWriteResponse {
             success: true,
             message: format!("Blackhole route for {} created", body.destination),
@@ -2163,13 +5074,9 @@ pub async fn create_static_route(
 
         if let Err(e) = result {
             tracing::error!("VyOS static route next-hop set failed: {e}");
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("Failed to create static route: {e}"),
-                }),
-            ));
+            return Err(AppError::BadGateway(format!(
+                "Failed to create static route: {e}"
+            )));
         }
 
         // Set distance if provided
@@ -2205,6 +5112,7 @@ pub async fn create_static_route(
             }
         }
 
+        state.vyos_cache.invalidate(&[CACHE_ROUTES]);
         Ok(Json(VyosWriteResponse {
             success: true,
             message: format!("Static route {} via {} created", body.destination, next_hop),
@@ -2216,16 +5124,8 @@ pub async fn create_static_route(
 pub async fn delete_static_route(
     State(state): State<AppState>,
     Path(destination): Path<String>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     // The destination comes URL-encoded (e.g., "10.0.0.0%2F8" → "10.0.0.0/8")
     // Axum decodes path parameters automatically.
@@ -2236,19 +5136,16 @@ pub async fn delete_static_route(
         .await;
 
     match result {
-        Ok(_) => Ok(Json(VyosWriteResponse {
-            success: true,
-            message: format!("Static route {} deleted", destination),
-        })),
+        Ok(_) => {
+            state.vyos_cache.invalidate(&[CACHE_ROUTES]);
+            Ok(Json(VyosWriteResponse {
+                success: true,
+                message: format!("Static route {} deleted", destination),
+            }))
+        }
         Err(e) => {
             tracing::error!("VyOS static route delete failed: {e}");
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("VyOS error: {e}"),
-                }),
-            ))
+            Err(AppError::BadGateway(format!("VyOS error: {e}")))
         }
     }
 }
@@ -2387,8 +5284,8 @@ fn parse_group_entries(
 /// GET /api/v1/vyos/firewall/groups — fetch firewall groups from VyOS.
 pub async fn firewall_groups(
     State(state): State<AppState>,
-) -> Result<Json<FirewallGroups>, StatusCode> {
-    let client = get_vyos_client_or_503(&state).await?;
+) -> Result<Json<FirewallGroups>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     match client.retrieve(&["firewall", "group"]).await {
         Ok(data) => {
@@ -2405,12 +5302,222 @@ pub async fn firewall_groups(
                 }))
             } else {
                 tracing::error!("VyOS firewall groups query failed: {e}");
-                Err(StatusCode::BAD_GATEWAY)
+                Err(AppError::BadGateway(format!("VyOS error: {e}")))
+            }
+        }
+    }
+}
+
+// ── Firewall Group ↔ Device Correlation ─────────────────────────────────────
+
+/// A Panoptikon device whose current IP falls within a firewall group member.
+#[derive(Debug, Clone, Serialize, PartialEq, sqlx::FromRow)]
+pub struct GroupMemberDevice {
+    pub device_id: String,
+    pub ip: String,
+    pub hostname: Option<String>,
+    pub mac: String,
+}
+
+/// A single firewall group member, enriched with matching device records.
+///
+/// Address-group members are matched by exact IP, network-group members by
+/// CIDR membership; port-group members carry no device correlation.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum FirewallGroupMember {
+    Address {
+        ip: String,
+        is_panoptikon_device: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        device_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hostname: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mac: Option<String>,
+    },
+    Network {
+        network: String,
+        is_panoptikon_device: bool,
+        devices: Vec<GroupMemberDevice>,
+    },
+    Port {
+        port: String,
+    },
+}
+
+/// Response for the firewall group devices endpoints.
+#[derive(Debug, Serialize)]
+pub struct FirewallGroupDevices {
+    pub group_name: String,
+    pub group_type: String,
+    pub members: Vec<FirewallGroupMember>,
+}
+
+/// Check whether an IPv4 address lies within an IPv4 CIDR network.
+fn ip_in_cidr(ip: &str, cidr: &str) -> bool {
+    let Some((net, prefix)) = cidr.split_once('/') else {
+        return false;
+    };
+    let (Ok(ip), Ok(net), Ok(prefix)) = (
+        ip.parse::<std::net::Ipv4Addr>(),
+        net.parse::<std::net::Ipv4Addr>(),
+        prefix.parse::<u32>(),
+    ) else {
+        return false;
+    };
+    if prefix > 32 {
+        return false;
+    }
+    let mask = if prefix == 0 {
+        0
+    } else {
+        u32::MAX << (32 - prefix)
+    };
+    (u32::from(ip) & mask) == (u32::from(net) & mask)
+}
+
+/// Correlate group members with current device IPs.
+fn correlate_group_members(
+    group_type: &str,
+    members: Vec<String>,
+    devices: &[GroupMemberDevice],
+) -> Vec<FirewallGroupMember> {
+    members
+        .into_iter()
+        .map(|member| match group_type {
+            "address-group" => match devices.iter().find(|d| d.ip == member) {
+                Some(d) => FirewallGroupMember::Address {
+                    ip: member,
+                    is_panoptikon_device: true,
+                    device_id: Some(d.device_id.clone()),
+                    hostname: d.hostname.clone(),
+                    mac: Some(d.mac.clone()),
+                },
+                None => FirewallGroupMember::Address {
+                    ip: member,
+                    is_panoptikon_device: false,
+                    device_id: None,
+                    hostname: None,
+                    mac: None,
+                },
+            },
+            "network-group" => {
+                let matched: Vec<GroupMemberDevice> = devices
+                    .iter()
+                    .filter(|d| ip_in_cidr(&d.ip, &member))
+                    .cloned()
+                    .collect();
+                FirewallGroupMember::Network {
+                    network: member,
+                    is_panoptikon_device: !matched.is_empty(),
+                    devices: matched,
+                }
+            }
+            _ => FirewallGroupMember::Port { port: member },
+        })
+        .collect()
+}
+
+/// Load devices with a current IP, optionally restricted to the given IPs.
+async fn fetch_current_device_ips(
+    db: &SqlitePool,
+    ips: Option<&[String]>,
+) -> Result<Vec<GroupMemberDevice>, sqlx::Error> {
+    let base = "SELECT d.id AS device_id, di.ip, d.hostname, d.mac \
+                FROM device_ips di JOIN devices d ON d.id = di.device_id \
+                WHERE di.is_current = 1";
+    match ips {
+        Some([]) => Ok(Vec::new()),
+        Some(ips) => {
+            let placeholders = ips.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            let sql = format!("{base} AND di.ip IN ({placeholders})");
+            let mut query = sqlx::query_as::<_, GroupMemberDevice>(&sql);
+            for ip in ips {
+                query = query.bind(ip);
             }
+            query.fetch_all(db).await
+        }
+        None => {
+            sqlx::query_as::<_, GroupMemberDevice>(base)
+                .fetch_all(db)
+                .await
         }
     }
 }
 
+/// Fetch a firewall group's members and correlate them with Panoptikon devices.
+async fn firewall_group_devices(
+    state: &AppState,
+    group_type: &str,
+    member_key: &str,
+    name: String,
+) -> Result<Json<FirewallGroupDevices>, AppError> {
+    let client = get_vyos_client(state).await?;
+
+    let data = match client
+        .retrieve(&["firewall", "group", group_type, &name])
+        .await
+    {
+        Ok(data) => data,
+        Err(e) => {
+            let msg = e.to_string();
+            if msg.contains("empty") || msg.contains("does not exist") {
+                return Err(AppError::ResourceNotFound(
+                    "firewall-group",
+                    format!("{group_type} '{name}' not found"),
+                ));
+            }
+            tracing::error!("VyOS {group_type} '{name}' query failed: {e}");
+            return Err(AppError::BadGateway(format!("VyOS error: {e}")));
+        }
+    };
+
+    // Reuse the group parser by wrapping the single group under its name.
+    let wrapped = serde_json::json!({ name.clone(): data });
+    let members = parse_group_entries(Some(&wrapped), member_key)
+        .into_iter()
+        .next()
+        .map(|(_, _, members)| members)
+        .unwrap_or_default();
+
+    let devices = match group_type {
+        "address-group" => fetch_current_device_ips(&state.db, Some(&members)).await,
+        "network-group" => fetch_current_device_ips(&state.db, None).await,
+        _ => Ok(Vec::new()),
+    }?;
+
+    Ok(Json(FirewallGroupDevices {
+        members: correlate_group_members(group_type, members, &devices),
+        group_name: name,
+        group_type: group_type.to_string(),
+    }))
+}
+
+/// GET /api/v1/vyos/firewall/groups/address-group/:name/devices — members with device matches.
+pub async fn address_group_devices(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FirewallGroupDevices>, AppError> {
+    firewall_group_devices(&state, "address-group", "address", name).await
+}
+
+/// GET /api/v1/vyos/firewall/groups/network-group/:name/devices — members with devices inside each CIDR.
+pub async fn network_group_devices(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FirewallGroupDevices>, AppError> {
+    firewall_group_devices(&state, "network-group", "network", name).await
+}
+
+/// GET /api/v1/vyos/firewall/groups/port-group/:name/devices — members (no device correlation).
+pub async fn port_group_devices(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<FirewallGroupDevices>, AppError> {
+    firewall_group_devices(&state, "port-group", "port", name).await
+}
+
 /// Request body for creating an address group.
 #[derive(Debug, Deserialize)]
 pub struct CreateAddressGroupRequest {
@@ -2504,38 +5611,19 @@ fn is_valid_port_entry(port: &str) -> bool {
 /// POST /api/v1/vyos/firewall/groups/address-group — create an address group.
 pub async fn create_address_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Json(body): Json<CreateAddressGroupRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if let Err(msg) = validate_group_name(&body.name) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::Validation(msg));
     }
 
     // Validate all addresses
     for addr in &body.addresses {
         if !is_valid_ip(addr) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("Invalid IP address: {addr}"),
-                }),
-            ));
+            return Err(AppError::Validation(format!("Invalid IP address: {addr}")));
         }
     }
 
@@ -2575,21 +5663,16 @@ pub async fn create_address_group(
         {
             tracing::error!("VyOS address-group description set failed: {e}");
             let msg = format!("Failed to set description: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "address_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
@@ -2608,26 +5691,22 @@ pub async fn create_address_group(
         {
             tracing::error!("VyOS address-group address add failed: {e}");
             let msg = format!("Failed to add address {addr}: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "address_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
     audit::log_success(
         &state.db,
+        &actor,
         "address_group_create",
         &audit_desc,
         &audit_commands,
@@ -2643,17 +5722,10 @@ pub async fn create_address_group(
 /// DELETE /api/v1/vyos/firewall/groups/address-group/:name — delete an address group.
 pub async fn delete_address_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: deleting address-group '{name}'");
 
@@ -2665,7 +5737,14 @@ pub async fn delete_address_group(
         .await
     {
         Ok(_) => {
-            audit::log_success(&state.db, "address_group_delete", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "address_group_delete",
+                &description,
+                &commands,
+            )
+            .await;
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Address group '{name}' deleted"),
@@ -2674,21 +5753,16 @@ pub async fn delete_address_group(
         Err(e) => {
             tracing::error!("VyOS address-group delete failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "address_group_delete",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -2696,27 +5770,17 @@ pub async fn delete_address_group(
 /// POST /api/v1/vyos/firewall/groups/address-group/:name/members — add a member.
 pub async fn add_address_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
     Json(body): Json<AddGroupMemberRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if !is_valid_ip(&body.value) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: format!("Invalid IP address: {}", body.value),
-            }),
-        ));
+        return Err(AppError::Validation(format!(
+            "Invalid IP address: {}",
+            body.value
+        )));
     }
 
     tracing::info!(
@@ -2744,6 +5808,7 @@ pub async fn add_address_group_member(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "address_group_member_add",
                 &description,
                 &commands,
@@ -2757,21 +5822,16 @@ pub async fn add_address_group_member(
         Err(e) => {
             tracing::error!("VyOS address-group member add failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "address_group_member_add",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -2779,17 +5839,10 @@ pub async fn add_address_group_member(
 /// DELETE /api/v1/vyos/firewall/groups/address-group/:name/members/:value — remove a member.
 pub async fn remove_address_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path((name, value)): Path<(String, String)>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: removing address '{value}' from address-group '{name}'");
 
@@ -2812,6 +5865,7 @@ pub async fn remove_address_group_member(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "address_group_member_remove",
                 &description,
                 &commands,
@@ -2825,21 +5879,16 @@ pub async fn remove_address_group_member(
         Err(e) => {
             tracing::error!("VyOS address-group member remove failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "address_group_member_remove",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -2847,37 +5896,18 @@ pub async fn remove_address_group_member(
 /// POST /api/v1/vyos/firewall/groups/network-group — create a network group.
 pub async fn create_network_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Json(body): Json<CreateNetworkGroupRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if let Err(msg) = validate_group_name(&body.name) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::Validation(msg));
     }
 
     for net in &body.networks {
         if !is_valid_cidr(net) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("Invalid CIDR network: {net}"),
-                }),
-            ));
+            return Err(AppError::Validation(format!("Invalid CIDR network: {net}")));
         }
     }
 
@@ -2916,21 +5946,16 @@ pub async fn create_network_group(
         {
             tracing::error!("VyOS network-group description set failed: {e}");
             let msg = format!("Failed to set description: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "network_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
@@ -2948,26 +5973,22 @@ pub async fn create_network_group(
         {
             tracing::error!("VyOS network-group network add failed: {e}");
             let msg = format!("Failed to add network {net}: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "network_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
     audit::log_success(
         &state.db,
+        &actor,
         "network_group_create",
         &audit_desc,
         &audit_commands,
@@ -2983,17 +6004,10 @@ pub async fn create_network_group(
 /// DELETE /api/v1/vyos/firewall/groups/network-group/:name — delete a network group.
 pub async fn delete_network_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: deleting network-group '{name}'");
 
@@ -3005,7 +6019,14 @@ pub async fn delete_network_group(
         .await
     {
         Ok(_) => {
-            audit::log_success(&state.db, "network_group_delete", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "network_group_delete",
+                &description,
+                &commands,
+            )
+            .await;
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Network group '{name}' deleted"),
@@ -3014,21 +6035,16 @@ pub async fn delete_network_group(
         Err(e) => {
             tracing::error!("VyOS network-group delete failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "network_group_delete",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3036,27 +6052,17 @@ pub async fn delete_network_group(
 /// POST /api/v1/vyos/firewall/groups/network-group/:name/members — add a member.
 pub async fn add_network_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
     Json(body): Json<AddGroupMemberRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if !is_valid_cidr(&body.value) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: format!("Invalid CIDR network: {}", body.value),
-            }),
-        ));
+        return Err(AppError::Validation(format!(
+            "Invalid CIDR network: {}",
+            body.value
+        )));
     }
 
     tracing::info!(
@@ -3084,6 +6090,7 @@ pub async fn add_network_group_member(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "network_group_member_add",
                 &description,
                 &commands,
@@ -3097,21 +6104,16 @@ pub async fn add_network_group_member(
         Err(e) => {
             tracing::error!("VyOS network-group member add failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "network_group_member_add",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3119,17 +6121,10 @@ pub async fn add_network_group_member(
 /// DELETE /api/v1/vyos/firewall/groups/network-group/:name/members/:value — remove a member.
 pub async fn remove_network_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path((name, value)): Path<(String, String)>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: removing network '{value}' from network-group '{name}'");
 
@@ -3152,6 +6147,7 @@ pub async fn remove_network_group_member(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "network_group_member_remove",
                 &description,
                 &commands,
@@ -3165,21 +6161,16 @@ pub async fn remove_network_group_member(
         Err(e) => {
             tracing::error!("VyOS network-group member remove failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "network_group_member_remove",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3187,37 +6178,20 @@ pub async fn remove_network_group_member(
 /// POST /api/v1/vyos/firewall/groups/port-group — create a port group.
 pub async fn create_port_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Json(body): Json<CreatePortGroupRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if let Err(msg) = validate_group_name(&body.name) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: msg,
-            }),
-        ));
+        return Err(AppError::Validation(msg));
     }
 
     for port in &body.ports {
         if !is_valid_port_entry(port) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: format!("Invalid port or port range: {port}"),
-                }),
-            ));
+            return Err(AppError::Validation(format!(
+                "Invalid port or port range: {port}"
+            )));
         }
     }
 
@@ -3256,21 +6230,16 @@ pub async fn create_port_group(
         {
             tracing::error!("VyOS port-group description set failed: {e}");
             let msg = format!("Failed to set description: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "port_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
@@ -3281,25 +6250,27 @@ pub async fn create_port_group(
         {
             tracing::error!("VyOS port-group port add failed: {e}");
             let msg = format!("Failed to add port {port}: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "port_group_create",
                 &audit_desc,
                 &audit_commands,
                 &msg,
             )
             .await;
-            return Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ));
+            return Err(AppError::BadGateway(msg));
         }
     }
 
-    audit::log_success(&state.db, "port_group_create", &audit_desc, &audit_commands).await;
+    audit::log_success(
+        &state.db,
+        &actor,
+        "port_group_create",
+        &audit_desc,
+        &audit_commands,
+    )
+    .await;
 
     Ok(Json(VyosWriteResponse {
         success: true,
@@ -3310,17 +6281,10 @@ pub async fn create_port_group(
 /// DELETE /api/v1/vyos/firewall/groups/port-group/:name — delete a port group.
 pub async fn delete_port_group(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: deleting port-group '{name}'");
 
@@ -3332,7 +6296,14 @@ pub async fn delete_port_group(
         .await
     {
         Ok(_) => {
-            audit::log_success(&state.db, "port_group_delete", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "port_group_delete",
+                &description,
+                &commands,
+            )
+            .await;
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Port group '{name}' deleted"),
@@ -3341,21 +6312,16 @@ pub async fn delete_port_group(
         Err(e) => {
             tracing::error!("VyOS port-group delete failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "port_group_delete",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3363,27 +6329,17 @@ pub async fn delete_port_group(
 /// POST /api/v1/vyos/firewall/groups/port-group/:name/members — add a member.
 pub async fn add_port_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path(name): Path<String>,
     Json(body): Json<AddGroupMemberRequest>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     if !is_valid_port_entry(&body.value) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(VyosWriteResponse {
-                success: false,
-                message: format!("Invalid port or port range: {}", body.value),
-            }),
-        ));
+        return Err(AppError::Validation(format!(
+            "Invalid port or port range: {}",
+            body.value
+        )));
     }
 
     tracing::info!("VyOS: adding port '{}' to port-group '{name}'", body.value);
@@ -3406,7 +6362,14 @@ pub async fn add_port_group_member(
         .await
     {
         Ok(_) => {
-            audit::log_success(&state.db, "port_group_member_add", &description, &commands).await;
+            audit::log_success(
+                &state.db,
+                &actor,
+                "port_group_member_add",
+                &description,
+                &commands,
+            )
+            .await;
             Ok(Json(VyosWriteResponse {
                 success: true,
                 message: format!("Port '{}' added to group '{name}'", body.value),
@@ -3415,21 +6378,16 @@ pub async fn add_port_group_member(
         Err(e) => {
             tracing::error!("VyOS port-group member add failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "port_group_member_add",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3437,17 +6395,10 @@ pub async fn add_port_group_member(
 /// DELETE /api/v1/vyos/firewall/groups/port-group/:name/members/:value — remove a member.
 pub async fn remove_port_group_member(
     State(state): State<AppState>,
+    Actor(actor): Actor,
     Path((name, value)): Path<(String, String)>,
-) -> Result<Json<VyosWriteResponse>, (StatusCode, Json<VyosWriteResponse>)> {
-    let client = get_vyos_client_or_503(&state).await.map_err(|_| {
-        (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(VyosWriteResponse {
-                success: false,
-                message: "Router not configured".to_string(),
-            }),
-        )
-    })?;
+) -> Result<Json<VyosWriteResponse>, AppError> {
+    let client = get_vyos_client(&state).await?;
 
     tracing::info!("VyOS: removing port '{value}' from port-group '{name}'");
 
@@ -3463,6 +6414,7 @@ pub async fn remove_port_group_member(
         Ok(_) => {
             audit::log_success(
                 &state.db,
+                &actor,
                 "port_group_member_remove",
                 &description,
                 &commands,
@@ -3476,21 +6428,16 @@ pub async fn remove_port_group_member(
         Err(e) => {
             tracing::error!("VyOS port-group member remove failed: {e}");
             let msg = format!("VyOS error: {e}");
-            audit::log_failure(
-                &state.db,
+            log_write_failure(
+                &state,
+                &actor,
                 "port_group_member_remove",
                 &description,
                 &commands,
                 &msg,
             )
             .await;
-            Err((
-                StatusCode::BAD_GATEWAY,
-                Json(VyosWriteResponse {
-                    success: false,
-                    message: msg,
-                }),
-            ))
+            Err(AppError::BadGateway(msg))
         }
     }
 }
@@ -3512,21 +6459,13 @@ pub struct SpeedTestResult {
     pub error: Option<String>,
 }
 
-/// Error response for the speed test endpoint.
-#[derive(Debug, Serialize)]
-pub struct SpeedTestError {
-    pub error: String,
-}
-
 const SPEEDTEST_RATE_LIMIT_SECS: i64 = 60;
 
 /// POST /api/v1/router/speedtest — run a WAN speed test using Ookla Speedtest CLI.
 ///
 /// Does **not** require VyOS to be configured. Runs the Ookla Speedtest CLI
 /// locally on the Panoptikon server to measure internet throughput.
-pub async fn speedtest(
-    State(state): State<AppState>,
-) -> Result<Json<SpeedTestResult>, (StatusCode, Json<SpeedTestError>)> {
+pub async fn speedtest(State(state): State<AppState>) -> Result<Json<SpeedTestResult>, AppError> {
     // Rate limit: check if last test was less than 60 seconds ago
     {
         let last = state.last_speedtest.lock().await;
@@ -3535,39 +6474,47 @@ pub async fn speedtest(
                 .signed_duration_since(result.tested_at)
                 .num_seconds();
             if elapsed < SPEEDTEST_RATE_LIMIT_SECS {
-                return Err((
-                    StatusCode::TOO_MANY_REQUESTS,
-                    Json(SpeedTestError {
-                        error: format!(
-                            "Rate limited. Please wait {}s before running another test.",
-                            SPEEDTEST_RATE_LIMIT_SECS - elapsed
-                        ),
-                    }),
+                let retry_after = SPEEDTEST_RATE_LIMIT_SECS - elapsed;
+                return Err(AppError::RateLimited(
+                    format!(
+                        "Rate limited. Please wait {retry_after}s before running another test."
+                    ),
+                    retry_after as u64,
                 ));
             }
         }
     }
 
+    let result = run_speedtest(&state.db).await?;
+
+    // Cache the result.
+    {
+        let mut last = state.last_speedtest.lock().await;
+        *last = Some(result.clone());
+    }
+
+    Ok(Json(result))
+}
+
+/// Run the Ookla Speedtest CLI and store the result in `speedtest_results`.
+///
+/// Not rate limited: callers that need a limit (the HTTP handler) check it
+/// first.
+pub async fn run_speedtest(db: &sqlx::SqlitePool) -> Result<SpeedTestResult, AppError> {
     // Check that Ookla Speedtest CLI is installed and executable
     match tokio::fs::metadata("/usr/local/bin/speedtest").await {
         Err(_) => {
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(SpeedTestError {
-                    error: "Ookla Speedtest CLI not installed on server. \
-                            Install it or rebuild the Docker image."
-                        .to_string(),
-                }),
+            return Err(AppError::ServiceUnavailable(
+                "Ookla Speedtest CLI not installed on server. \
+                 Install it or rebuild the Docker image."
+                    .to_string(),
             ));
         }
         Ok(meta) => {
             use std::os::unix::fs::PermissionsExt;
             if meta.permissions().mode() & 0o111 == 0 {
-                return Err((
-                    StatusCode::SERVICE_UNAVAILABLE,
-                    Json(SpeedTestError {
-                        error: "Ookla Speedtest CLI exists but is not executable".to_string(),
-                    }),
+                return Err(AppError::ServiceUnavailable(
+                    "Ookla Speedtest CLI exists but is not executable".to_string(),
                 ));
             }
         }
@@ -3579,12 +6526,7 @@ pub async fn speedtest(
         .await
         .map_err(|e| {
             tracing::error!("Ookla Speedtest failed: {e}");
-            (
-                StatusCode::BAD_GATEWAY,
-                Json(SpeedTestError {
-                    error: format!("Speed test failed: {e}"),
-                }),
-            )
+            AppError::BadGateway(format!("Speed test failed: {e}"))
         })?;
 
     let download_mbps = ookla_result.download.bandwidth as f64 * 8.0 / 1_000_000.0;
@@ -3607,10 +6549,8 @@ pub async fn speedtest(
         error: None,
     };
 
-    // Cache the result
-    {
-        let mut last = state.last_speedtest.lock().await;
-        *last = Some(result.clone());
+    if let Err(e) = save_speedtest_result(db, &result).await {
+        tracing::error!("Failed to store speed test result: {e}");
     }
 
     tracing::info!(
@@ -3620,7 +6560,221 @@ pub async fn speedtest(
         result.ping_ms,
     );
 
-    Ok(Json(result))
+    Ok(result)
+}
+
+/// Maximum number of results returned by the speed test history endpoint.
+const SPEEDTEST_HISTORY_MAX: i64 = 500;
+
+/// Persist a completed speed test.
+async fn save_speedtest_result(
+    db: &sqlx::SqlitePool,
+    result: &SpeedTestResult,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO speedtest_results \
+         (download_mbps, upload_mbps, ping_ms, jitter_ms, packet_loss, isp, server, result_url, tested_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(result.download_mbps)
+    .bind(result.upload_mbps)
+    .bind(result.ping_ms)
+    .bind(result.jitter_ms)
+    .bind(result.packet_loss)
+    .bind(&result.isp)
+    .bind(&result.server)
+    .bind(&result.result_url)
+    .bind(result.tested_at.to_rfc3339())
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Load stored speed test results, newest first.
+async fn load_speedtest_history(
+    db: &sqlx::SqlitePool,
+    limit: i64,
+) -> Result<Vec<SpeedTestResult>, sqlx::Error> {
+    type Row = (
+        f64,
+        f64,
+        f64,
+        f64,
+        f64,
+        String,
+        String,
+        Option<String>,
+        String,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT download_mbps, upload_mbps, ping_ms, jitter_ms, packet_loss, isp, server, \
+                result_url, tested_at \
+         FROM speedtest_results ORDER BY tested_at DESC, id DESC LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(
+            |(
+                download_mbps,
+                upload_mbps,
+                ping_ms,
+                jitter_ms,
+                packet_loss,
+                isp,
+                server,
+                result_url,
+                tested_at,
+            )| {
+                Some(SpeedTestResult {
+                    download_mbps,
+                    upload_mbps,
+                    ping_ms,
+                    jitter_ms,
+                    packet_loss,
+                    isp,
+                    server,
+                    result_url,
+                    tested_at: DateTime::parse_from_rfc3339(&tested_at)
+                        .ok()?
+                        .with_timezone(&Utc),
+                    error: None,
+                })
+            },
+        )
+        .collect())
+}
+
+/// Query parameters for the speed test history endpoint.
+#[derive(Debug, Deserialize)]
+pub struct SpeedTestHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/v1/router/speedtest/history?limit=50 — stored speed test results, newest first.
+pub async fn speedtest_history(
+    State(state): State<AppState>,
+    Query(params): Query<SpeedTestHistoryQuery>,
+) -> Result<Json<Vec<SpeedTestResult>>, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, SPEEDTEST_HISTORY_MAX);
+    Ok(Json(load_speedtest_history(&state.db, limit).await?))
+}
+
+/// GET /api/v1/router/speedtest/latest — most recent result without running a new test.
+pub async fn speedtest_latest(
+    State(state): State<AppState>,
+) -> Result<Json<SpeedTestResult>, AppError> {
+    if let Some(result) = state.last_speedtest.lock().await.clone() {
+        return Ok(Json(result));
+    }
+    load_speedtest_history(&state.db, 1)
+        .await?
+        .into_iter()
+        .next()
+        .map(Json)
+        .ok_or_else(|| {
+            AppError::ResourceNotFound("speedtest", "No speed test has been run yet".to_string())
+        })
+}
+
+// ── Config Archive ──────────────────────────────────────────────────────────
+
+/// Request body for the config archive schedule endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ConfigArchiveScheduleRequest {
+    /// `true` to archive the config automatically once a day.
+    pub enabled: bool,
+}
+
+/// Response for the config archive schedule endpoint.
+#[derive(Debug, Serialize)]
+pub struct ConfigArchiveScheduleResponse {
+    pub enabled: bool,
+}
+
+/// POST /api/v1/vyos/system/config-archive — push the running config to git.
+///
+/// Returns the commit SHA of the branch tip. Bad credentials yield 400 so the
+/// UI can point the operator at the token setting rather than the network.
+pub async fn config_archive(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+) -> Result<Json<crate::vyos::config_archive::ArchiveResult>, AppError> {
+    use crate::vyos::config_archive::{run_archive, ArchiveError};
+
+    let config = state.config().clone();
+    run_archive(&state.db, &config, &state.active_profile_id, &actor)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("VyOS config archive failed: {e}");
+            match e {
+                ArchiveError::NotConfigured | ArchiveError::Auth(_) => {
+                    AppError::Validation(e.to_string())
+                }
+                ArchiveError::Vyos(_) | ArchiveError::Git(_) => AppError::BadGateway(e.to_string()),
+            }
+        })
+}
+
+/// POST /api/v1/vyos/system/config-archive/schedule — enable or disable the daily archive.
+pub async fn config_archive_schedule(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<ConfigArchiveScheduleRequest>,
+) -> Result<Json<ConfigArchiveScheduleResponse>, AppError> {
+    if body.enabled
+        && crate::vyos::config_archive::load_settings(&state.db)
+            .await
+            .is_none()
+    {
+        return Err(AppError::Validation(
+            "Git archive repository is not configured".to_string(),
+        ));
+    }
+
+    let value = if body.enabled { "true" } else { "false" };
+    let description = format!(
+        "{} daily config archive",
+        if body.enabled { "Enable" } else { "Disable" }
+    );
+
+    if let Err(e) = sqlx::query(
+        r#"INSERT INTO settings (key, value) VALUES ('git_archive_schedule_enabled', ?)
+           ON CONFLICT(key) DO UPDATE SET value = excluded.value"#,
+    )
+    .bind(value)
+    .execute(&state.db)
+    .await
+    {
+        tracing::error!("Failed to save config archive schedule: {e}");
+        let msg = format!("Database error: {e}");
+        log_write_failure(
+            &state,
+            &actor,
+            "config_archive_schedule",
+            &description,
+            &[],
+            &msg,
+        )
+        .await;
+        return Err(AppError::Internal(msg));
+    }
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "config_archive_schedule",
+        &description,
+        &[],
+    )
+    .await;
+    Ok(Json(ConfigArchiveScheduleResponse {
+        enabled: body.enabled,
+    }))
 }
 
 // ── Helpers ─────────────────────────────────────────────────────────
@@ -3637,12 +6791,7 @@ async fn get_vyos_settings(
             .ok()
             .flatten();
 
-    let db_key: Option<String> =
-        sqlx::query_scalar(r#"SELECT value FROM settings WHERE key = 'vyos_api_key'"#)
-            .fetch_optional(db)
-            .await
-            .ok()
-            .flatten();
+    let db_key = crate::crypto::get_sealed_setting(db, "vyos_api_key").await;
 
     // DB values take priority, fall back to config file
     let url = db_url
@@ -3655,12 +6804,21 @@ async fn get_vyos_settings(
     (url, key)
 }
 
-/// Try to construct a VyOS client from DB settings + config. Returns None if not configured.
-async fn get_vyos_client_from_db(
+/// Try to construct a VyOS client for the active router profile, or from DB
+/// settings + config when no profile is active. Returns None if not configured.
+pub(crate) async fn get_vyos_client_from_db(
     db: &SqlitePool,
     config: &crate::config::AppConfig,
+    active_profile: &router_profiles::ActiveRouterProfile,
 ) -> Option<crate::vyos::client::VyosClient> {
-    let (url, key) = get_vyos_settings(db, config).await;
+    let profile_id = active_profile.read().await.clone();
+    let (url, key) = match profile_id {
+        Some(id) => {
+            let (url, key) = router_profiles::profile_credentials(db, &id).await?;
+            (Some(url), Some(key))
+        }
+        None => get_vyos_settings(db, config).await,
+    };
     match (url, key) {
         (Some(u), Some(k)) if !u.is_empty() && !k.is_empty() => {
             Some(crate::vyos::client::VyosClient::new(&u, &k))
@@ -3669,13 +6827,39 @@ async fn get_vyos_client_from_db(
     }
 }
 
-/// Get VyOS client or return 503 SERVICE_UNAVAILABLE if not configured.
-pub(crate) async fn get_vyos_client_or_503(
+/// Get VyOS client or return a 503 problem response if not configured.
+pub(crate) async fn get_vyos_client(
     state: &AppState,
-) -> Result<crate::vyos::client::VyosClient, StatusCode> {
-    get_vyos_client_from_db(&state.db, &state.config)
+) -> Result<crate::vyos::client::VyosClient, AppError> {
+    let config = state.config().clone();
+    get_vyos_client_from_db(&state.db, &config, &state.active_profile_id)
         .await
-        .ok_or(StatusCode::SERVICE_UNAVAILABLE)
+        .ok_or_else(|| AppError::ServiceUnavailable("Router not configured".to_string()))
+}
+
+/// Audit-log a failed VyOS write and notify Telegram, if configured.
+async fn log_write_failure(
+    state: &AppState,
+    actor: &str,
+    action: &str,
+    description: &str,
+    vyos_commands: &[String],
+    error_msg: &str,
+) {
+    audit::log_failure(
+        &state.db,
+        actor,
+        action,
+        description,
+        vyos_commands,
+        error_msg,
+    )
+    .await;
+    telegram::dispatch(
+        &state.db,
+        &state.telegram_limiter,
+        telegram::format_vyos_failure(action, description, error_msg),
+    );
 }
 
 // ── Tests ───────────────────────────────────────────────────────────
@@ -4191,6 +7375,35 @@ mod tests {
         assert_eq!(interface_type("xyz"), None);
     }
 
+    // ── Interface addresses ─────────────────────────────────
+
+    #[test]
+    fn test_parse_interface_addresses_single() {
+        let config = serde_json::json!({
+            "address": "10.10.0.50/24",
+            "hw-id": "bc:24:11:12:9f:fa"
+        });
+        assert_eq!(parse_interface_addresses(&config), vec!["10.10.0.50/24"]);
+    }
+
+    #[test]
+    fn test_parse_interface_addresses_multiple() {
+        let config = serde_json::json!({
+            "address": ["10.10.0.50/24", "10.10.0.100/24"],
+            "description": "LAN"
+        });
+        assert_eq!(
+            parse_interface_addresses(&config),
+            vec!["10.10.0.50/24", "10.10.0.100/24"]
+        );
+    }
+
+    #[test]
+    fn test_parse_interface_addresses_none() {
+        let config = serde_json::json!({ "description": "unused" });
+        assert!(parse_interface_addresses(&config).is_empty());
+    }
+
     // ── MAC address validation ──────────────────────────────
 
     #[test]
@@ -4402,6 +7615,350 @@ mod tests {
         assert_eq!(groups.port_groups[0].members, vec!["443"]);
     }
 
+    // ── RIB summary parsing ───────────────────────────────────
+
+    #[test]
+    fn test_parse_rib_summary() {
+        let text = "Route Source         Routes               FIB  (vrf default)\n\
+                    kernel               1                    1\n\
+                    connected            4                    4\n\
+                    local                4                    4\n\
+                    static               3                    3\n\
+                    ebgp                 12340                12340\n\
+                    ibgp                 5                    5\n\
+                    ------\n\
+                    Totals               12357                12357\n";
+        let summary = parse_rib_summary_text(text, "ipv4");
+
+        assert_eq!(summary.address_family, "ipv4");
+        assert_eq!(summary.total, 12357);
+        assert_eq!(
+            summary.rib_entries[0],
+            RibEntry {
+                protocol: "bgp".to_string(),
+                routes: 12345
+            }
+        );
+        let ospf = summary
+            .rib_entries
+            .iter()
+            .find(|e| e.protocol == "ospf")
+            .expect("ospf should always be reported");
+        assert_eq!(ospf.routes, 0);
+        assert!(summary.rib_entries.iter().all(|e| e.protocol != "ebgp"));
+    }
+
+    #[test]
+    fn test_parse_rib_summary_empty() {
+        let summary = parse_rib_summary_text("", "ipv6");
+        assert_eq!(summary.total, 0);
+        assert_eq!(summary.rib_entries.len(), RIB_CORE_PROTOCOLS.len());
+        assert!(summary.rib_entries.iter().all(|e| e.routes == 0));
+    }
+
+    // ── BGP summary parsing ───────────────────────────────────
+
+    #[test]
+    fn test_parse_bgp_summary() {
+        let text = "\n\
+IPv4 Unicast Summary (VRF default):\n\
+BGP router identifier 10.0.0.1, local AS number 65001 vrf-id 0\n\
+BGP table version 12\n\
+RIB entries 23, using 4416 bytes of memory\n\
+Peers 4, using 2868 KiB of memory\n\
+\n\
+Neighbor        V         AS   MsgRcvd   MsgSent   TblVer  InQ OutQ  Up/Down State/PfxRcd   PfxSnt Desc\n\
+10.0.0.2        4      65002      1234      1230        0    0    0 1d02h03m           12       15 N/A\n\
+10.0.0.3        4      65003         0         0        0    0    0    never       Active        0 N/A\n\
+10.0.0.4        4      65004         0         0        0    0    0 00:01:10 Idle (Admin)        0 N/A\n\
+2001:db8:ffff:ffff::1\n\
+                4      65005        50        49        0    0    0 00:20:00            3        3 N/A\n\
+\n\
+Total number of neighbors 4\n";
+        let peers = parse_bgp_summary_text(text);
+
+        assert_eq!(peers.len(), 4);
+        assert_eq!(
+            peers[0],
+            BgpPeer {
+                neighbor: "10.0.0.2".to_string(),
+                remote_as: 65002,
+                state: "Established".to_string(),
+                uptime: Some("1d02h03m".to_string()),
+                prefixes_received: 12,
+            }
+        );
+        assert_eq!(peers[1].state, "Active");
+        assert_eq!(peers[1].uptime, None);
+        assert_eq!(peers[1].prefixes_received, 0);
+        assert_eq!(peers[2].state, "Idle (Admin)");
+        assert_eq!(peers[3].neighbor, "2001:db8:ffff:ffff::1");
+        assert_eq!(peers[3].remote_as, 65005);
+        assert_eq!(peers[3].prefixes_received, 3);
+    }
+
+    #[test]
+    fn test_parse_bgp_summary_not_configured() {
+        assert!(
+            parse_bgp_summary_text("% BGP instance not found\nBGP is not configured\n").is_empty()
+        );
+        assert!(parse_bgp_summary_text("").is_empty());
+    }
+
+    // ── OSPF neighbor parsing ─────────────────────────────────
+
+    #[test]
+    fn test_parse_ospf_neighbor() {
+        let text = "\n\
+Neighbor ID     Pri State           Up Time         Dead Time Address         Interface                        RXmtL RqstL DBsmL\n\
+10.0.0.2          1 Full/DR         1h02m03s          35.123s 10.0.0.2        eth0:10.0.0.1                        0     0     0\n\
+10.0.1.9          0 2-Way/DROther   5m10s             31.004s 10.0.1.9        eth1.20:10.0.1.1                     0     0     0\n";
+        let neighbors = parse_ospf_neighbor_text(text);
+
+        assert_eq!(neighbors.len(), 2);
+        assert_eq!(
+            neighbors[0],
+            OspfNeighbor {
+                neighbor_id: "10.0.0.2".to_string(),
+                priority: 1,
+                state: "Full/DR".to_string(),
+                dead_time: "35.123s".to_string(),
+                address: "10.0.0.2".to_string(),
+                interface: "eth0".to_string(),
+            }
+        );
+        assert_eq!(neighbors[1].state, "2-Way/DROther");
+        assert_eq!(neighbors[1].interface, "eth1.20");
+    }
+
+    #[test]
+    fn test_parse_ospf_neighbor_without_uptime_column() {
+        let text = "Neighbor ID     Pri State           Dead Time Address         Interface            RXmtL RqstL DBsmL\n\
+                    192.168.0.2       1 Full/Backup       38.512s 192.168.0.2     eth2:192.168.0.1         0     0     0\n";
+        let neighbors = parse_ospf_neighbor_text(text);
+
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0].dead_time, "38.512s");
+        assert_eq!(neighbors[0].address, "192.168.0.2");
+        assert_eq!(neighbors[0].interface, "eth2");
+    }
+
+    #[test]
+    fn test_parse_ospf_neighbor_not_configured() {
+        assert!(parse_ospf_neighbor_text("% OSPF instance not found\n").is_empty());
+        assert!(parse_ospf_neighbor_text("").is_empty());
+    }
+
+    // ── NAT rules ─────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_nat_rules() {
+        let source = serde_json::json!({
+            "100": {
+                "outbound-interface": {"name": "eth0"},
+                "source": {"address": "10.0.0.0/24"},
+                "translation": {"address": "masquerade"},
+                "description": "LAN masquerade"
+            },
+            "20": {
+                "outbound-interface": "eth1",
+                "translation": {"address": "203.0.113.5"},
+                "disable": {}
+            }
+        });
+        let rules = parse_nat_rules(&source, "source");
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].number, 20);
+        assert!(rules[0].disabled);
+        assert_eq!(rules[0].outbound_interface.as_deref(), Some("eth1"));
+        assert_eq!(
+            rules[1],
+            NatRule {
+                number: 100,
+                rule_type: "source".to_string(),
+                source: Some("10.0.0.0/24".to_string()),
+                destination: None,
+                outbound_interface: Some("eth0".to_string()),
+                translation: "masquerade".to_string(),
+                description: Some("LAN masquerade".to_string()),
+                disabled: false,
+            }
+        );
+
+        let destination = serde_json::json!({
+            "10": {
+                "inbound-interface": {"name": "eth0"},
+                "destination": {"port": "443"},
+                "protocol": "tcp",
+                "translation": {"address": "10.0.0.5", "port": "8443"}
+            }
+        });
+        let rules = parse_nat_rules(&destination, "destination");
+        assert_eq!(rules[0].destination.as_deref(), Some(":443"));
+        assert_eq!(rules[0].translation, "10.0.0.5:8443");
+        assert_eq!(rules[0].outbound_interface.as_deref(), Some("eth0"));
+        assert!(parse_nat_rules(&serde_json::json!(null), "source").is_empty());
+    }
+
+    fn nat_request(number: u32, translation: &str) -> NatRuleRequest {
+        NatRuleRequest {
+            number,
+            protocol: None,
+            source: Some("10.0.0.0/24".to_string()),
+            destination: None,
+            destination_port: None,
+            outbound_interface: Some("eth0".to_string()),
+            translation: translation.to_string(),
+            translation_port: None,
+            description: None,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn test_validate_nat_rule() {
+        assert!(validate_nat_rule("source", &nat_request(100, "masquerade")).is_ok());
+        assert!(validate_nat_rule("destination", &nat_request(10, "10.0.0.5")).is_ok());
+        assert!(validate_nat_rule("source", &nat_request(0, "masquerade")).is_err());
+        assert!(validate_nat_rule("source", &nat_request(10000, "masquerade")).is_err());
+        assert!(validate_nat_rule("destination", &nat_request(10, "masquerade")).is_err());
+        assert!(validate_nat_rule("source", &nat_request(10, "not-an-ip")).is_err());
+
+        let mut bad_iface = nat_request(10, "masquerade");
+        bad_iface.outbound_interface = Some("eth0; rm".to_string());
+        assert!(validate_nat_rule("source", &bad_iface).is_err());
+
+        let mut bad_port = nat_request(10, "10.0.0.5");
+        bad_port.destination_port = Some("http".to_string());
+        assert!(validate_nat_rule("destination", &bad_port).is_err());
+    }
+
+    #[test]
+    fn test_nat_kind() {
+        assert_eq!(nat_kind("source"), Some("source"));
+        assert_eq!(nat_kind("destination"), Some("destination"));
+        assert_eq!(nat_kind("static"), None);
+    }
+
+    // ── DNS forwarding ────────────────────────────────────────
+
+    #[test]
+    fn test_parse_dns_forwarding_vyos_14() {
+        let config = serde_json::json!({
+            "listen-address": ["10.0.0.1", "10.0.1.1"],
+            "name-server": {"1.1.1.1": {}, "9.9.9.9": {"port": "53"}},
+            "cache-size": "2000",
+            "domain": {
+                "corp.example.com": {"name-server": {"10.0.0.53": {}}}
+            },
+            "allow-from": ["10.0.0.0/8"]
+        });
+        let parsed = parse_dns_forwarding(&config);
+
+        assert_eq!(parsed.listen_on, vec!["10.0.0.1", "10.0.1.1"]);
+        assert_eq!(parsed.nameservers, vec!["1.1.1.1", "9.9.9.9"]);
+        assert_eq!(parsed.cache_size, 2000);
+        assert_eq!(
+            parsed.domain_overrides,
+            vec![DnsDomainOverride {
+                domain: "corp.example.com".to_string(),
+                server: "10.0.0.53".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_dns_forwarding_vyos_13_and_empty() {
+        let config = serde_json::json!({
+            "listen-on": "eth1",
+            "name-server": ["8.8.8.8"],
+            "domain": {"lan": {"server": ["10.0.0.2", "10.0.0.3"]}}
+        });
+        let parsed = parse_dns_forwarding(&config);
+        assert_eq!(parsed.listen_on, vec!["eth1"]);
+        assert_eq!(parsed.nameservers, vec!["8.8.8.8"]);
+        assert_eq!(parsed.cache_size, DNS_DEFAULT_CACHE_SIZE);
+        assert_eq!(parsed.domain_overrides.len(), 2);
+
+        let empty = parse_dns_forwarding(&Value::Null);
+        assert!(empty.nameservers.is_empty());
+        assert!(empty.domain_overrides.is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_domain() {
+        assert!(is_valid_domain("corp.example.com"));
+        assert!(is_valid_domain("lan"));
+        assert!(!is_valid_domain(""));
+        assert!(!is_valid_domain("bad..domain"));
+        assert!(!is_valid_domain("-lead.example"));
+        assert!(!is_valid_domain("evil.com; rm -rf"));
+    }
+
+    // ── Firewall group device correlation ─────────────────────
+
+    #[test]
+    fn test_ip_in_cidr() {
+        assert!(ip_in_cidr("10.10.0.50", "10.10.0.0/24"));
+        assert!(ip_in_cidr("10.10.0.50", "0.0.0.0/0"));
+        assert!(ip_in_cidr("10.10.0.50", "10.10.0.50/32"));
+        assert!(!ip_in_cidr("10.10.1.50", "10.10.0.0/24"));
+        assert!(!ip_in_cidr("10.10.0.50", "10.10.0.0"));
+        assert!(!ip_in_cidr("not-an-ip", "10.10.0.0/24"));
+    }
+
+    #[test]
+    fn test_correlate_group_members() {
+        let devices = vec![GroupMemberDevice {
+            device_id: "dev-1".to_string(),
+            ip: "10.10.0.50".to_string(),
+            hostname: Some("nas".to_string()),
+            mac: "aa:bb:cc:dd:ee:ff".to_string(),
+        }];
+
+        let address = correlate_group_members(
+            "address-group",
+            vec!["10.10.0.50".to_string(), "10.10.0.99".to_string()],
+            &devices,
+        );
+        assert_eq!(
+            serde_json::to_value(&address).unwrap(),
+            serde_json::json!([
+                {"ip": "10.10.0.50", "is_panoptikon_device": true, "device_id": "dev-1",
+                 "hostname": "nas", "mac": "aa:bb:cc:dd:ee:ff"},
+                {"ip": "10.10.0.99", "is_panoptikon_device": false}
+            ])
+        );
+
+        let network = correlate_group_members(
+            "network-group",
+            vec!["10.10.0.0/24".to_string(), "192.168.0.0/16".to_string()],
+            &devices,
+        );
+        assert_eq!(
+            network[0],
+            FirewallGroupMember::Network {
+                network: "10.10.0.0/24".to_string(),
+                is_panoptikon_device: true,
+                devices: devices.clone(),
+            }
+        );
+        assert_eq!(
+            network[1],
+            FirewallGroupMember::Network {
+                network: "192.168.0.0/16".to_string(),
+                is_panoptikon_device: false,
+                devices: Vec::new(),
+            }
+        );
+
+        let port = correlate_group_members("port-group", vec!["443".to_string()], &devices);
+        assert_eq!(
+            serde_json::to_value(&port).unwrap(),
+            serde_json::json!([{"port": "443"}])
+        );
+    }
+
     // ── Validation helpers ────────────────────────────────────
 
     #[test]
@@ -4501,6 +8058,45 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_speedtest_history_and_latest() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool.clone(), crate::config::AppConfig::default());
+        assert!(matches!(
+            speedtest_latest(State(state.clone())).await,
+            Err(AppError::ResourceNotFound("speedtest", _))
+        ));
+
+        for (mbps, age) in [(100.0, 120), (250.0, 60), (400.0, 0)] {
+            let result = SpeedTestResult {
+                download_mbps: mbps,
+                upload_mbps: 50.0,
+                ping_ms: 3.1,
+                jitter_ms: 0.3,
+                packet_loss: 0.0,
+                isp: "Test ISP".to_string(),
+                server: "Test Server - Test City, Test Country".to_string(),
+                result_url: None,
+                tested_at: Utc::now() - chrono::Duration::seconds(age),
+                error: None,
+            };
+            save_speedtest_result(&pool, &result).await.unwrap();
+        }
+
+        let Json(history) = speedtest_history(
+            State(state.clone()),
+            Query(SpeedTestHistoryQuery { limit: Some(2) }),
+        )
+        .await
+        .unwrap();
+        let downloads: Vec<f64> = history.iter().map(|r| r.download_mbps).collect();
+        assert_eq!(downloads, vec![400.0, 250.0]);
+
+        // With an empty cache (e.g. after a restart) the stored result is served.
+        let Json(latest) = speedtest_latest(State(state)).await.unwrap();
+        assert_eq!(latest.download_mbps, 400.0);
+    }
+
     // ── Firewall CRUD helpers ─────────────────────────────
 
     #[test]
@@ -4548,7 +8144,7 @@ mod tests {
             state: Some(vec!["new".to_string(), "established".to_string()]),
             disabled: false,
         };
-        assert!(validate_firewall_rule(&rule).is_ok());
+        assert!(validate_firewall_rule(&rule, "ipv4").is_ok());
     }
 
     #[test]
@@ -4565,7 +8161,7 @@ mod tests {
             state: None,
             disabled: false,
         };
-        assert!(validate_firewall_rule(&rule).is_err());
+        assert!(validate_firewall_rule(&rule, "ipv4").is_err());
     }
 
     #[test]
@@ -4582,7 +8178,7 @@ mod tests {
             state: None,
             disabled: false,
         };
-        assert!(validate_firewall_rule(&rule).is_err());
+        assert!(validate_firewall_rule(&rule, "ipv4").is_err());
     }
 
     #[test]
@@ -4599,7 +8195,7 @@ mod tests {
             state: None,
             disabled: false,
         };
-        assert!(validate_firewall_rule(&rule).is_err());
+        assert!(validate_firewall_rule(&rule, "ipv4").is_err());
     }
 
     #[test]
@@ -4616,7 +8212,7 @@ mod tests {
             state: Some(vec!["bogus".to_string()]),
             disabled: false,
         };
-        assert!(validate_firewall_rule(&rule).is_err());
+        assert!(validate_firewall_rule(&rule, "ipv4").is_err());
     }
 
     #[test]
@@ -4629,6 +8225,249 @@ mod tests {
         assert!(!is_valid_ip_or_cidr(""));
         assert!(!is_valid_ip_or_cidr("not-an-ip"));
         assert!(!is_valid_ip_or_cidr("10.0.0.0/999"));
+        assert!(is_valid_ip_or_cidr("2001:db8::/32"));
+        assert!(is_valid_ip_or_cidr("!2001:db8::/128"));
+        assert!(!is_valid_ip_or_cidr("10.0.0.0/33"));
+        assert!(!is_valid_ip_or_cidr("2001:db8::/129"));
+        assert_eq!(ip_or_cidr_version("::1"), Some("ipv6"));
+        assert_eq!(ip_or_cidr_version("10.0.0.0/8"), Some("ipv4"));
+    }
+
+    #[test]
+    fn test_validate_firewall_rule_address_family() {
+        let rule = FirewallRuleRequest {
+            number: 10,
+            action: "accept".to_string(),
+            protocol: Some("tcp".to_string()),
+            source_address: Some("2001:db8::/32".to_string()),
+            source_port: None,
+            destination_address: Some("::1".to_string()),
+            destination_port: Some("22".to_string()),
+            description: None,
+            state: None,
+            disabled: false,
+        };
+        assert!(validate_firewall_rule(&rule, "ipv6").is_ok());
+        let err = validate_firewall_rule(&rule, "ipv4").unwrap_err();
+        assert!(err.contains("not valid in an ipv4 chain"), "{err}");
+    }
+
+    /// Minimal VyOS HTTP API: `/configure` applies set/delete paths to an
+    /// in-memory config tree, `/retrieve` returns the subtree at a path and
+    /// `/show` returns canned output from `_show` in the tree.
+    async fn mock_vyos() -> (String, Arc<std::sync::Mutex<Value>>) {
+        use axum::{body::Bytes, routing::post, Router};
+
+        fn form_data(body: &[u8]) -> Value {
+            let body = String::from_utf8_lossy(body);
+            let start = body.find("name=\"data\"").unwrap();
+            let start = start + body[start..].find("\r\n\r\n").unwrap() + 4;
+            let end = start + body[start..].find("\r\n--").unwrap();
+            serde_json::from_str(&body[start..end]).unwrap()
+        }
+        fn path_of(data: &Value) -> Vec<String> {
+            data["path"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p.as_str().unwrap().to_string())
+                .collect()
+        }
+
+        fn apply_op(tree: &mut Value, op: &Value) {
+            let path = path_of(op);
+            let mut node = tree;
+            if op["op"] == "set" {
+                // `set a b value` stores `{"a": {"b": "value"}}`; a valueless
+                // node such as `rule 10 disable` becomes `{"disable": {}}`.
+                let (value, keys) = path.split_last().unwrap();
+                let (leaf, parents) = keys.split_last().unwrap();
+                for key in parents {
+                    node = node
+                        .as_object_mut()
+                        .unwrap()
+                        .entry(key.clone())
+                        .or_insert_with(|| serde_json::json!({}));
+                }
+                let obj = node.as_object_mut().unwrap();
+                match obj.get_mut(leaf.as_str()) {
+                    Some(Value::Object(children)) => {
+                        children.insert(value.clone(), serde_json::json!({}));
+                    }
+                    _ => {
+                        obj.insert(leaf.clone(), serde_json::json!(value));
+                    }
+                }
+            } else {
+                let (last, parents) = path.split_last().unwrap();
+                for key in parents {
+                    node = node.get_mut(key.as_str()).unwrap();
+                }
+                node.as_object_mut().unwrap().remove(last);
+            }
+        }
+
+        let tree = Arc::new(std::sync::Mutex::new(serde_json::json!({})));
+        let configure_tree = tree.clone();
+        let retrieve_tree = tree.clone();
+        let show_tree = tree.clone();
+        let app = Router::new()
+            .route(
+                "/configure",
+                post(move |body: Bytes| async move {
+                    let data = form_data(&body);
+                    let ops = match data {
+                        Value::Array(ops) => ops,
+                        op => vec![op],
+                    };
+                    let mut tree = configure_tree.lock().unwrap();
+                    for op in ops {
+                        apply_op(&mut tree, &op);
+                    }
+                    axum::Json(serde_json::json!({ "success": true, "data": null, "error": null }))
+                }),
+            )
+            .route(
+                "/show",
+                post(move |body: Bytes| async move {
+                    // Canned op-mode output, keyed by the command under "_show".
+                    let command = path_of(&form_data(&body)).join(" ");
+                    let output = show_tree.lock().unwrap()["_show"][command.as_str()].clone();
+                    axum::Json(
+                        serde_json::json!({ "success": true, "data": output, "error": null }),
+                    )
+                }),
+            )
+            .route(
+                "/retrieve",
+                post(move |body: Bytes| async move {
+                    let path = path_of(&form_data(&body));
+                    let tree = retrieve_tree.lock().unwrap();
+                    let node = path.iter().fold(&*tree, |node, key| &node[key.as_str()]);
+                    axum::Json(serde_json::json!({ "success": true, "data": node, "error": null }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}"), tree)
+    }
+
+    #[tokio::test]
+    async fn test_create_ipv6_firewall_rule_round_trip() {
+        let (url, _tree) = mock_vyos().await;
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url.clone());
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+
+        let body: FirewallRuleRequest = serde_json::from_value(serde_json::json!({
+            "number": 40,
+            "action": "accept",
+            "protocol": "tcp",
+            "source_address": "2001:db8::/32",
+            "destination_address": "::1",
+            "destination_port": "22",
+            "description": "SSH from docs prefix",
+            "state": ["new"],
+        }))
+        .unwrap();
+        let Json(resp) = create_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(FirewallChainPath {
+                chain: "ipv6.input.filter".to_string(),
+            }),
+            Json(body),
+        )
+        .await
+        .unwrap();
+        assert!(resp.success);
+
+        let client = crate::vyos::client::VyosClient::new(&url, "key");
+        let firewall = parse_firewall_config(&client.retrieve(&["firewall"]).await.unwrap());
+        assert_eq!(firewall.chains.len(), 1);
+        let chain = &firewall.chains[0];
+        assert_eq!(chain.path, vec!["ipv6", "input", "filter"]);
+        assert_eq!(
+            chain.rules,
+            vec![FirewallRule {
+                number: 40,
+                action: "accept".to_string(),
+                source: Some("2001:db8::/32".to_string()),
+                destination: Some("::1, port 22".to_string()),
+                protocol: Some("tcp".to_string()),
+                state: Some("new".to_string()),
+                description: Some("SSH from docs prefix".to_string()),
+                disabled: false,
+            }]
+        );
+
+        let description: String = sqlx::query_scalar(
+            "SELECT description FROM audit_log WHERE action = 'firewall_rule_create'",
+        )
+        .fetch_one(&state.db)
+        .await
+        .unwrap();
+        assert!(
+            description.starts_with("Create IPv6 firewall rule 40"),
+            "{description}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_firewall_read_cache_hit_and_invalidation() {
+        let (url, _tree) = mock_vyos().await;
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url);
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+
+        async fn get(state: &AppState) -> (String, FirewallConfig) {
+            let response = firewall(State(state.clone())).await.unwrap();
+            let x_cache = response.headers()[X_CACHE].to_str().unwrap().to_string();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (x_cache, serde_json::from_slice(&body).unwrap())
+        }
+
+        let (x_cache, first) = get(&state).await;
+        assert_eq!(x_cache, "MISS");
+        assert!(first.chains.is_empty());
+        assert_eq!(get(&state).await.0, "HIT");
+
+        // A rule created through the API is visible on the next read.
+        let body: FirewallRuleRequest = serde_json::from_value(serde_json::json!({
+            "number": 10,
+            "action": "drop",
+        }))
+        .unwrap();
+        let Json(resp) = create_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(FirewallChainPath {
+                chain: "ipv4.input.filter".to_string(),
+            }),
+            Json(body),
+        )
+        .await
+        .unwrap();
+        assert!(resp.success);
+        let (x_cache, after) = get(&state).await;
+        assert_eq!(x_cache, "MISS");
+        assert_eq!(after.chains[0].rules[0].number, 10);
+
+        let Json(flushed) = invalidate_cache(State(state.clone())).await;
+        assert_eq!(flushed["invalidated"], 1);
+        assert_eq!(get(&state).await.0, "MISS");
+
+        // A zero TTL disables caching for that endpoint.
+        state.config.write().unwrap().vyos.cache_ttl.firewall = 0;
+        get(&state).await;
+        assert_eq!(get(&state).await.0, "MISS");
     }
 
     #[test]
@@ -4694,4 +8533,751 @@ mod tests {
         assert_eq!(config.chains.len(), 1);
         assert_eq!(config.chains[0].path, vec!["ipv4", "forward", "filter"]);
     }
+
+    #[test]
+    fn test_period_to_sqlite_modifier() {
+        assert_eq!(
+            period_to_sqlite_modifier("24h").as_deref(),
+            Some("-1440 minutes")
+        );
+        assert_eq!(
+            period_to_sqlite_modifier("30m").as_deref(),
+            Some("-30 minutes")
+        );
+        assert_eq!(
+            period_to_sqlite_modifier("90d").as_deref(),
+            Some("-43200 minutes")
+        );
+        assert_eq!(period_to_sqlite_modifier("0h"), None);
+        assert_eq!(period_to_sqlite_modifier("1w"), None);
+        assert_eq!(period_to_sqlite_modifier(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_firewall_hit_top_ranking() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let samples: &[(&str, i64, i64, i64, &str)] = &[
+            ("ipv4.forward.filter", 100, 1500, 1000, "-1 hours"),
+            ("ipv4.forward.filter", 100, 2500, 1500, "-30 minutes"),
+            ("ipv4.forward.filter", 200, 9000, 5000, "-2 hours"),
+            ("ipv4.input.filter", 10, 300, 100, "-10 minutes"),
+            // Outside the 24h window.
+            ("ipv4.input.filter", 10, 900000, 0, "-2 days"),
+        ];
+        for (chain, rule, packets, prev, age) in samples {
+            sqlx::query(
+                "INSERT INTO firewall_rule_stats (chain, rule_number, packets, prev_packets, sampled_at) \
+                 VALUES (?, ?, ?, ?, datetime('now', ?))",
+            )
+            .bind(chain)
+            .bind(rule)
+            .bind(packets)
+            .bind(prev)
+            .bind(age)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let modifier = period_to_sqlite_modifier("24h").unwrap();
+        let hits = query_rule_hits(&pool, None, 10, &modifier).await.unwrap();
+        let ranked: Vec<_> = hits
+            .iter()
+            .map(|h| (h.chain.as_str(), h.rule_number, h.hits))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("ipv4.forward.filter", 200, 4000),
+                ("ipv4.forward.filter", 100, 1500),
+                ("ipv4.input.filter", 10, 200),
+            ]
+        );
+
+        let hits = query_rule_hits(&pool, Some("ipv4.input.filter"), 10, &modifier)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule_number, 10);
+
+        let hits = query_rule_hits(&pool, None, 1, &modifier).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].rule_number, 200);
+    }
+
+    #[test]
+    fn test_annotate_rule_hits_from_config() {
+        let json: Value = serde_json::json!({
+            "ipv4": {"forward": {"filter": {
+                "default-action": "drop",
+                "rule": {"100": {"action": "accept", "description": "Allow established"}}
+            }}}
+        });
+        let config = parse_firewall_config(&json);
+        let mut hits = vec![
+            FirewallRuleHits {
+                chain: "ipv4.forward.filter".to_string(),
+                rule_number: 100,
+                hits: 5,
+                action: None,
+                description: None,
+            },
+            FirewallRuleHits {
+                chain: "ipv4.forward.filter".to_string(),
+                rule_number: 999,
+                hits: 1,
+                action: None,
+                description: None,
+            },
+        ];
+        annotate_rule_hits(&mut hits, &config);
+        assert_eq!(hits[0].action.as_deref(), Some("accept"));
+        assert_eq!(hits[0].description.as_deref(), Some("Allow established"));
+        assert_eq!(hits[1].action, None);
+    }
+
+    #[test]
+    fn test_summarize_firewall_chains_rule_count() {
+        let json: Value = serde_json::json!({
+            "ipv4": {
+                "forward": {"filter": {
+                    "default-action": "drop",
+                    "rule": {
+                        "10": {"action": "accept"},
+                        "20": {"action": "drop"},
+                        "30": {"action": "reject"}
+                    }
+                }},
+                "input": {"filter": {"default-action": "accept"}}
+            }
+        });
+        let chains = summarize_firewall_chains(parse_firewall_config(&json));
+        assert_eq!(chains.len(), 2);
+
+        let forward = chains
+            .iter()
+            .find(|c| c.path == "ipv4.forward.filter")
+            .unwrap();
+        assert_eq!(forward.rule_count, 3);
+        assert_eq!(forward.default_action, "drop");
+
+        let input = chains
+            .iter()
+            .find(|c| c.path == "ipv4.input.filter")
+            .unwrap();
+        assert_eq!(input.rule_count, 0);
+    }
+
+    fn lease(remaining: Option<&str>, expiry: Option<&str>) -> VyosDhcpLease {
+        VyosDhcpLease {
+            ip: "10.10.0.100".to_string(),
+            mac: "aa:bb:cc:dd:ee:ff".to_string(),
+            hostname: None,
+            state: "active".to_string(),
+            lease_start: None,
+            lease_expiry: expiry.map(str::to_string),
+            remaining: remaining.map(str::to_string),
+            pool: Some("LAN".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_lease_remaining() {
+        assert_eq!(parse_lease_remaining("01:45:00"), Some(6300));
+        assert_eq!(parse_lease_remaining("36:00:00"), Some(129600));
+        assert_eq!(parse_lease_remaining("0:05"), Some(300));
+        assert_eq!(parse_lease_remaining("01:75:00"), None);
+        assert_eq!(parse_lease_remaining("soon"), None);
+    }
+
+    #[test]
+    fn test_lease_remaining_falls_back_to_expiry() {
+        let now = chrono::NaiveDateTime::parse_from_str("2026/02/21 20:00:00", "%Y/%m/%d %H:%M:%S")
+            .unwrap()
+            .and_utc();
+        let l = lease(None, Some("2026/02/21 22:00:00"));
+        assert_eq!(lease_remaining_secs(&l, now), Some(7200));
+        // `remaining` wins over the expiry timestamp.
+        let l = lease(Some("00:10:00"), Some("2026/02/21 22:00:00"));
+        assert_eq!(lease_remaining_secs(&l, now), Some(600));
+        assert_eq!(lease_remaining_secs(&lease(None, None), now), None);
+    }
+
+    #[test]
+    fn test_filter_expiring_leases_window() {
+        let now = chrono::NaiveDateTime::parse_from_str("2026/02/21 20:00:00", "%Y/%m/%d %H:%M:%S")
+            .unwrap()
+            .and_utc();
+        let leases = vec![
+            lease(Some("23:00:00"), None),
+            lease(Some("01:45:00"), None),
+            lease(Some("25:00:00"), None),
+            lease(None, Some("2026/02/21 19:00:00")), // already expired
+            lease(None, Some("2026/02/21 21:00:00")),
+        ];
+        let expiring = filter_expiring_leases(leases, 24 * 3600, now);
+        let remaining: Vec<i64> = expiring.iter().map(|l| l.remaining_secs).collect();
+        assert_eq!(remaining, vec![3600, 6300, 82800]);
+    }
+
+    #[test]
+    fn test_parse_dhcp_client_lease() {
+        let text = "interface  : eth0\n\
+                    ip address : 203.0.113.10                [Active]\n\
+                    subnet mask: 255.255.255.0\n\
+                    domain name: isp.example\n\
+                    router     : 203.0.113.1\n\
+                    name server: 1.1.1.1 8.8.8.8\n\
+                    dhcp server: 203.0.113.254\n\
+                    last update: Sat Feb 21 10:00:00 UTC 2026\n\
+                    expiry     : Sun Feb 22 10:00:00 UTC 2026\n";
+        let lease = parse_dhcp_client_lease(text).unwrap();
+        assert_eq!(lease.ip_address, "203.0.113.10");
+        assert_eq!(lease.subnet_mask, "255.255.255.0");
+        assert_eq!(lease.gateway, "203.0.113.1");
+        assert_eq!(lease.dns_servers, vec!["1.1.1.1", "8.8.8.8"]);
+        assert_eq!(lease.domain_name.as_deref(), Some("isp.example"));
+        assert_eq!(lease.dhcp_server, "203.0.113.254");
+        assert_eq!(lease.lease_obtained, "Sat Feb 21 10:00:00 UTC 2026");
+        assert_eq!(lease.lease_expires, "Sun Feb 22 10:00:00 UTC 2026");
+    }
+
+    #[test]
+    fn test_parse_dhcp_client_lease_none() {
+        assert!(parse_dhcp_client_lease("").is_none());
+        assert!(parse_dhcp_client_lease("No DHCP client lease for interface eth0").is_none());
+    }
+
+    #[test]
+    fn test_parse_pppoe_session() {
+        let text = "pppoe0: <POINTOPOINT,MULTICAST,NOARP,UP,LOWER_UP> mtu 1492 qdisc fq_codel\n\
+                    \x20   link/ppp\n\
+                    \x20   inet 198.51.100.7 peer 198.51.100.1/32 scope global pppoe0\n\
+                    \x20      valid_lft forever preferred_lft forever\n";
+        let lease = parse_pppoe_session(text).unwrap();
+        assert_eq!(lease.ip_address, "198.51.100.7");
+        assert_eq!(lease.gateway, "198.51.100.1");
+        assert_eq!(lease.subnet_mask, "255.255.255.255");
+        assert!(parse_pppoe_session("pppoe0: <NO-CARRIER> mtu 1492").is_none());
+    }
+
+    #[test]
+    fn test_prefix_to_netmask() {
+        assert_eq!(prefix_to_netmask(24).as_deref(), Some("255.255.255.0"));
+        assert_eq!(prefix_to_netmask(0).as_deref(), Some("0.0.0.0"));
+        assert_eq!(prefix_to_netmask(33), None);
+    }
+
+    #[test]
+    fn test_parse_traceroute_text() {
+        let text = "traceroute to dns.google (8.8.8.8), 30 hops max, 60 byte packets\n\
+                    \x20 1  gateway (192.168.1.1)  0.512 ms  0.480 ms  0.455 ms\n\
+                    \x20 2  * * *\n\
+                    \x20 3  10.20.0.1  5.102 ms  5.020 ms *\n\
+                    \x20 4  dns.google (8.8.8.8)  9.120 ms  9.011 ms  8.950 ms\n";
+        let hops = parse_traceroute_text(text);
+        assert_eq!(hops.len(), 4);
+
+        assert_eq!(hops[0].hop_number, 1);
+        assert_eq!(hops[0].hostname.as_deref(), Some("gateway"));
+        assert_eq!(hops[0].ip.as_deref(), Some("192.168.1.1"));
+        assert_eq!(hops[0].rtt_ms, Some(0.512));
+
+        assert!(hops[1].asterisk);
+        assert_eq!(hops[1].ip, None);
+        assert_eq!(hops[1].rtt_ms, None);
+
+        assert_eq!(hops[2].ip.as_deref(), Some("10.20.0.1"));
+        assert_eq!(hops[2].hostname, None);
+        assert!(!hops[2].asterisk);
+
+        assert_eq!(hops[3].ip.as_deref(), Some("8.8.8.8"));
+        assert_eq!(traceroute_target_ip(text).as_deref(), Some("8.8.8.8"));
+    }
+
+    #[test]
+    fn test_parse_traceroute_caps_hops() {
+        let text: String = (1..=40).map(|i| format!(" {i}  * * *\n")).collect();
+        assert_eq!(parse_traceroute_text(&text).len(), TRACEROUTE_MAX_HOPS);
+    }
+
+    #[test]
+    fn test_is_valid_trace_target() {
+        assert!(is_valid_trace_target("8.8.8.8"));
+        assert!(is_valid_trace_target("2001:4860:4860::8888"));
+        assert!(is_valid_trace_target("dns.google"));
+        assert!(!is_valid_trace_target("8.8.8.8; rm -rf /"));
+        assert!(!is_valid_trace_target("$(reboot)"));
+        assert!(!is_valid_trace_target("-badhost"));
+        assert!(!is_valid_trace_target(""));
+    }
+
+    const IPSEC_SA: &str = "\
+Connection          State    Uptime    Bytes In/Out     Packets In/Out    Remote address    Remote ID    Proposal
+------------------  -------  --------  ---------------  ----------------  ----------------  -----------  ------------------------
+branch-tunnel-0     up       1h2m3s    1.5K/3.00 MB     12/34             192.0.2.1         192.0.2.1    AES_CBC_256/HMAC_SHA2_256
+branch-tunnel-1     down     N/A       0B/0B            0/0               192.0.2.1         N/A
+dc-tunnel-0         up       5m        512/2048         4/8               2001:db8::1       dc           AES_GCM_16_256
+";
+
+    #[test]
+    fn test_parse_ipsec_sa_text() {
+        let tunnels = parse_ipsec_sa_text(IPSEC_SA);
+        assert_eq!(tunnels.len(), 3);
+
+        assert_eq!(tunnels[0].peer, "192.0.2.1");
+        assert_eq!(tunnels[0].state, "up");
+        assert_eq!(tunnels[0].uptime.as_deref(), Some("1h2m3s"));
+        assert_eq!(tunnels[0].bytes_in, 1536);
+        assert_eq!(tunnels[0].bytes_out, 3 * 1024 * 1024);
+
+        assert_eq!(tunnels[1].state, "down");
+        assert_eq!(tunnels[1].uptime, None);
+        assert_eq!((tunnels[1].bytes_in, tunnels[1].bytes_out), (0, 0));
+
+        assert_eq!(tunnels[2].peer, "2001:db8::1");
+        assert_eq!((tunnels[2].bytes_in, tunnels[2].bytes_out), (512, 2048));
+    }
+
+    #[test]
+    fn test_parse_ipsec_sa_text_not_configured() {
+        assert!(parse_ipsec_sa_text("").is_empty());
+        assert!(parse_ipsec_sa_text("IPsec process not running").is_empty());
+        assert!(parse_ipsec_sa_text("No active SAs found").is_empty());
+    }
+
+    #[test]
+    fn test_apply_ipsec_prefixes() {
+        let mut tunnels = parse_ipsec_sa_text(IPSEC_SA);
+        let config = serde_json::json!({
+            "site-to-site": {
+                "peer": {
+                    "branch": {
+                        "remote-address": "192.0.2.1",
+                        "tunnel": {
+                            "0": {
+                                "local": { "prefix": "10.0.0.0/24" },
+                                "remote": { "prefix": ["10.1.0.0/24", "10.2.0.0/24"] }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        apply_ipsec_prefixes(&mut tunnels, &config);
+
+        assert_eq!(tunnels[0].local_subnet, "10.0.0.0/24");
+        assert_eq!(tunnels[0].remote_subnet, "10.1.0.0/24, 10.2.0.0/24");
+        // No config for this tunnel or peer.
+        assert_eq!(tunnels[1].local_subnet, "");
+        assert_eq!(tunnels[2].remote_subnet, "");
+    }
+
+    #[test]
+    fn test_config_set_paths() {
+        let rule = serde_json::json!({
+            "action": "accept",
+            "disable": {},
+            "source": { "address": "10.0.0.0/8", "port": "22" },
+            "state": { "established": "enable" },
+        });
+        let paths = config_set_paths(&rule);
+        assert_eq!(
+            paths,
+            vec![
+                vec!["action", "accept"],
+                vec!["disable"],
+                vec!["source", "address", "10.0.0.0/8"],
+                vec!["source", "port", "22"],
+                vec!["state", "established", "enable"],
+            ]
+        );
+        assert!(config_set_paths(&serde_json::json!({})).is_empty());
+        assert!(config_set_paths(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_rule_copy_commands() {
+        let rule = serde_json::json!({
+            "action": "drop",
+            "source": { "address": "10.0.0.0/8" },
+        });
+        let base = firewall_rule_base_path(&["ipv4", "forward", "filter"], 30);
+        assert_eq!(
+            set_commands(&rule_copy_paths(&base, &rule, &["disable"])),
+            vec![
+                "set firewall ipv4 forward filter rule 30 action drop",
+                "set firewall ipv4 forward filter rule 30 source address 10.0.0.0/8",
+                "set firewall ipv4 forward filter rule 30 disable",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_firewall_rule() {
+        let (url, tree) = mock_vyos().await;
+        *tree.lock().unwrap() = serde_json::json!({
+            "firewall": { "ipv4": { "forward": { "filter": { "rule": {
+                "10": {
+                    "action": "drop",
+                    "protocol": "tcp",
+                    "destination": { "port": "23" },
+                    "description": "No telnet"
+                },
+                "20": { "action": "accept" }
+            } } } } }
+        });
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url);
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+        let rule_path = |number| {
+            Path(FirewallRulePath {
+                chain: "ipv4.forward.filter".to_string(),
+                number,
+            })
+        };
+
+        // Target number taken.
+        let err = move_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            rule_path(10),
+            Json(FirewallRuleMoveRequest { new_number: 20 }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        // Source rule missing.
+        let err = move_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            rule_path(30),
+            Json(FirewallRuleMoveRequest { new_number: 5 }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::ResourceNotFound(..)));
+
+        let Json(resp) = move_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            rule_path(10),
+            Json(FirewallRuleMoveRequest { new_number: 5 }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.success);
+
+        let rules = tree.lock().unwrap()["firewall"]["ipv4"]["forward"]["filter"]["rule"].clone();
+        assert!(rules.get("10").is_none());
+        assert_eq!(
+            rules["5"],
+            serde_json::json!({
+                "action": "drop",
+                "protocol": "tcp",
+                "destination": { "port": "23" },
+                "description": "No telnet"
+            })
+        );
+        assert_eq!(rules["20"]["action"], "accept");
+
+        let descriptions: Vec<String> = sqlx::query_scalar(
+            "SELECT description FROM audit_log WHERE action = 'firewall_rule_move'",
+        )
+        .fetch_all(&state.db)
+        .await
+        .unwrap();
+        assert_eq!(
+            descriptions,
+            vec!["Move firewall rule 10 to 5 in chain ipv4.forward.filter"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clone_firewall_rule() {
+        let (url, tree) = mock_vyos().await;
+        *tree.lock().unwrap() = serde_json::json!({
+            "firewall": { "ipv4": { "input": { "filter": { "rule": {
+                "10": {
+                    "action": "accept",
+                    "protocol": "tcp",
+                    "source": { "address": "10.0.0.0/8" },
+                    "destination": { "port": "22" }
+                }
+            } } } } }
+        });
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url.clone());
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+        let rule_path = || {
+            Path(FirewallRulePath {
+                chain: "ipv4.input.filter".to_string(),
+                number: 10,
+            })
+        };
+
+        let Json(resp) = clone_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            rule_path(),
+            Json(FirewallRuleMoveRequest { new_number: 11 }),
+        )
+        .await
+        .unwrap();
+        assert!(resp.success);
+
+        let client = crate::vyos::client::VyosClient::new(&url, "key");
+        let firewall = parse_firewall_config(&client.retrieve(&["firewall"]).await.unwrap());
+        let rules = &firewall.chains[0].rules;
+        assert_eq!(rules.len(), 2);
+        let (original, clone) = (&rules[0], &rules[1]);
+        assert_eq!((original.number, clone.number), (10, 11));
+        assert_eq!(clone.action, original.action);
+        assert_eq!(clone.protocol, original.protocol);
+        assert_eq!(clone.source, original.source);
+        assert_eq!(clone.destination, original.destination);
+        assert!(!original.disabled);
+        assert!(clone.disabled);
+
+        // Cloning onto an existing rule is a conflict.
+        let err = clone_firewall_rule(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            rule_path(),
+            Json(FirewallRuleMoveRequest { new_number: 11 }),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        let descriptions: Vec<String> = sqlx::query_scalar(
+            "SELECT description FROM audit_log WHERE action = 'firewall_rule_clone'",
+        )
+        .fetch_all(&state.db)
+        .await
+        .unwrap();
+        assert_eq!(
+            descriptions,
+            vec!["Clone firewall rule 10 to 11 (disabled) in chain ipv4.input.filter"]
+        );
+    }
+
+    #[test]
+    fn test_parse_firewall_rule_counters() {
+        let text = "\
+Rule Information
+
+---------------------------------
+ipv4 Firewall \"forward filter\"
+
+Rule     Action    Protocol      Packets    Bytes    Conditions
+-------  --------  ----------  ---------  -------  ----------------------------
+10       accept    all               123    45678  ct state established accept
+20\tdrop\ttcp\t7\t1.5K\ttcp dport 23 drop
+30       accept    udp              1.2M     1.5G  udp dport 53 accept
+default  drop      all                 0        0
+";
+        assert_eq!(
+            parse_firewall_rule_counters(text),
+            vec![
+                FirewallRuleCounters {
+                    rule_number: 10,
+                    packet_count: 123,
+                    byte_count: 45678,
+                },
+                FirewallRuleCounters {
+                    rule_number: 20,
+                    packet_count: 7,
+                    byte_count: 1536,
+                },
+                FirewallRuleCounters {
+                    rule_number: 30,
+                    packet_count: 1_200_000,
+                    byte_count: 1_610_612_736,
+                },
+            ]
+        );
+        assert!(parse_firewall_rule_counters("").is_empty());
+        assert!(firewall_path_missing(
+            "Firewall rule 99 does not exist in ipv4 forward filter"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_make_lease_static() {
+        let (url, tree) = mock_vyos().await;
+        *tree.lock().unwrap() = serde_json::json!({
+            "_show": {
+                "dhcp server leases": "\
+IP Address    MAC Address        State    Lease start          Lease expiration     Remaining  Pool  Hostname
+------------  -----------------  -------  -------------------  -------------------  ---------  ----  --------
+10.10.0.100   AA:BB:CC:DD:EE:01  active   2026/02/21 10:00:00  2026/02/21 22:00:00  11:30:00   LAN   laptop
+10.10.0.101   aa:bb:cc:dd:ee:02  expired  2026/02/20 10:00:00  2026/02/20 22:00:00  0:00:00    LAN   -
+10.10.0.102   aa:bb:cc:dd:ee:03  active   2026/02/21 09:00:00  2026/02/21 21:00:00  10:00:00   LAN   printer
+"
+            },
+            "service": { "dhcp-server": { "shared-network-name": { "LAN": { "subnet": {
+                "10.10.0.0/24": { "static-mapping": {
+                    "printer": { "mac-address": "aa:bb:cc:dd:ee:03", "ip-address": "10.10.0.102" }
+                } }
+            } } } } }
+        });
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url);
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+        let request = |name: &str| {
+            Json(MakeLeaseStaticRequest {
+                network: "LAN".to_string(),
+                subnet: "10.10.0.0/24".to_string(),
+                name: name.to_string(),
+            })
+        };
+        let make_static = |ip: &str, name: &str| {
+            make_lease_static(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                Path(ip.to_string()),
+                request(name),
+            )
+        };
+
+        let Json(mapping) = make_static("10.10.0.100", "laptop").await.unwrap();
+        assert_eq!(mapping.mac, "aa:bb:cc:dd:ee:01");
+        assert_eq!(mapping.ip, "10.10.0.100");
+        let stored = tree.lock().unwrap()["service"]["dhcp-server"]["shared-network-name"]["LAN"]
+            ["subnet"]["10.10.0.0/24"]["static-mapping"]["laptop"]
+            .clone();
+        assert_eq!(
+            stored,
+            serde_json::json!({ "mac-address": "aa:bb:cc:dd:ee:01", "ip-address": "10.10.0.100" })
+        );
+
+        // Expired and unknown leases.
+        let err = make_static("10.10.0.101", "old").await.unwrap_err();
+        assert!(matches!(err, AppError::ResourceNotFound("dhcp-lease", _)));
+        let err = make_static("10.10.0.200", "none").await.unwrap_err();
+        assert!(matches!(err, AppError::ResourceNotFound("dhcp-lease", _)));
+
+        // Already pinned, by IP or by name.
+        let err = make_static("10.10.0.102", "printer2").await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        let err = make_static("10.10.0.100", "laptop").await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+
+        // Outside the subnet.
+        let err = make_static("192.168.1.5", "x").await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM audit_log WHERE action = 'dhcp_lease_to_static'",
+        )
+        .fetch_one(&state.db)
+        .await
+        .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    fn lease_at(ip: &str, state: &str) -> VyosDhcpLease {
+        VyosDhcpLease {
+            ip: ip.to_string(),
+            mac: "aa:bb:cc:dd:ee:ff".to_string(),
+            hostname: None,
+            state: state.to_string(),
+            lease_start: None,
+            lease_expiry: None,
+            remaining: None,
+            pool: None,
+        }
+    }
+
+    #[test]
+    fn test_compute_dhcp_pool_stats() {
+        let config = serde_json::json!({
+            "shared-network-name": {
+                "LAN": { "subnet": {
+                    "10.10.0.0/24": { "static-mapping": {
+                        "printer": { "mac-address": "aa:bb:cc:dd:ee:03", "ip-address": "10.10.0.5" }
+                    } }
+                } },
+                "LAB": { "subnet": { "10.20.0.0/30": {} } }
+            }
+        });
+        let leases = vec![
+            lease_at("10.10.0.100", "active"),
+            lease_at("10.10.0.101", "active"),
+            lease_at("10.10.0.102", "expired"),
+            // Static mapping holding its lease: counted once.
+            lease_at("10.10.0.5", "active"),
+            lease_at("10.20.0.1", "active"),
+            lease_at("10.20.0.2", "active"),
+        ];
+        let pools = compute_dhcp_pool_stats(&config, &leases);
+        assert_eq!(
+            pools,
+            vec![
+                DhcpPoolStats {
+                    network: "LAB".to_string(),
+                    subnet: "10.20.0.0/30".to_string(),
+                    total_addresses: 2,
+                    active_leases: 2,
+                    static_mappings: 0,
+                    available: 0,
+                    utilization_pct: 100.0,
+                },
+                DhcpPoolStats {
+                    network: "LAN".to_string(),
+                    subnet: "10.10.0.0/24".to_string(),
+                    total_addresses: 254,
+                    active_leases: 2,
+                    static_mappings: 1,
+                    available: 251,
+                    utilization_pct: 1.2,
+                },
+            ]
+        );
+        assert!(compute_dhcp_pool_stats(&Value::Null, &leases).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dhcp_pools_warns_and_caches() {
+        let (url, tree) = mock_vyos().await;
+        *tree.lock().unwrap() = serde_json::json!({
+            "_show": {
+                "dhcp server leases": "\
+IP Address    MAC Address        State    Lease start          Lease expiration     Remaining  Pool  Hostname
+10.20.0.1     aa:bb:cc:dd:ee:01  active   2026/02/21 10:00:00  2026/02/21 22:00:00  11:30:00   LAB   a
+10.20.0.2     aa:bb:cc:dd:ee:02  active   2026/02/21 10:00:00  2026/02/21 22:00:00  11:30:00   LAB   b
+"
+            },
+            "service": { "dhcp-server": { "shared-network-name": {
+                "LAB": { "subnet": { "10.20.0.0/30": {} } }
+            } } }
+        });
+        let mut config = crate::config::AppConfig::default();
+        config.vyos.url = Some(url);
+        config.vyos.api_key = Some("key".to_string());
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let state = AppState::new(pool, config);
+        let mut rx = state.ws_hub.subscribe_ui();
+
+        let Json(pools) = dhcp_pools(State(state.clone())).await.unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].utilization_pct, 100.0);
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.event, "dhcp_pool_warning");
+        assert_eq!(msg.payload["subnet"], "10.20.0.0/30");
+
+        // Served from cache: no second query, no second warning.
+        tree.lock().unwrap()["service"] = serde_json::json!({});
+        let Json(cached) = dhcp_pools(State(state)).await.unwrap();
+        assert_eq!(cached, pools);
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -0,0 +1,700 @@
+//! Named webhook endpoints.
+//!
+//! Each webhook receives the event types listed in its `events` filter
+//! (`*` for all) and, when a secret is set, an HMAC signature of the body
+//! in [`webhook::SIGNATURE_HEADER`]. A random secret is generated on
+//! creation unless one is supplied. Delivery lives in [`crate::webhook`].
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+use super::audit::Actor;
+use super::{audit, AppError, AppState};
+use crate::webhook;
+
+/// Maximum length of a webhook name.
+const MAX_NAME_LEN: usize = 64;
+
+/// Random bytes in a generated webhook secret.
+const SECRET_BYTES: usize = 32;
+
+/// Number of delivery records returned by the deliveries endpoint.
+const DELIVERY_LOG_LIMIT: i64 = 50;
+
+/// A webhook as returned by the API (the secret is never returned).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub has_secret: bool,
+    pub created_at: String,
+}
+
+#[derive(FromRow)]
+struct WebhookRow {
+    id: String,
+    name: String,
+    url: String,
+    events: String,
+    enabled: bool,
+    secret: Option<String>,
+    created_at: String,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            url: row.url,
+            events: row
+                .events
+                .split(',')
+                .filter(|e| !e.is_empty())
+                .map(str::to_string)
+                .collect(),
+            enabled: row.enabled,
+            has_secret: row.secret.is_some_and(|s| !s.is_empty()),
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// How receivers verify a signed delivery.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignatureInfo {
+    pub header: &'static str,
+    pub algorithm: &'static str,
+    pub format: &'static str,
+}
+
+const SIGNATURE_INFO: SignatureInfo = SignatureInfo {
+    header: webhook::SIGNATURE_HEADER,
+    algorithm: "HMAC-SHA256",
+    format: "sha256=<lowercase hex HMAC-SHA256 of the raw request body, keyed with the secret>",
+};
+
+/// Response for a newly created webhook — the only time the secret is returned.
+#[derive(Debug, Serialize)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// `None` when the webhook was created unsigned.
+    pub secret: Option<String>,
+    pub signature: SignatureInfo,
+}
+
+/// A failed webhook delivery and its retry state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub attempt_count: i64,
+    pub last_attempt_at: String,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    pub response_code: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(FromRow)]
+struct WebhookDeliveryRow {
+    id: String,
+    event_type: String,
+    payload_json: String,
+    attempt_count: i64,
+    last_attempt_at: String,
+    status: String,
+    response_code: Option<i64>,
+    error: Option<String>,
+}
+
+impl From<WebhookDeliveryRow> for WebhookDelivery {
+    fn from(row: WebhookDeliveryRow) -> Self {
+        Self {
+            id: row.id,
+            event_type: row.event_type,
+            payload: serde_json::from_str(&row.payload_json).unwrap_or_default(),
+            attempt_count: row.attempt_count,
+            last_attempt_at: row.last_attempt_at,
+            status: row.status,
+            response_code: row.response_code,
+            error: row.error,
+        }
+    }
+}
+
+/// Request body for `POST /api/v1/webhooks`.
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhook {
+    pub name: String,
+    pub url: String,
+    /// Event types to deliver, e.g. `["new_device"]`, or `["*"]` for all.
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Signing secret; omit to generate one, or pass `""` to send unsigned.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request body for `PATCH /api/v1/webhooks/:id`. An empty `secret` removes it.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateWebhook {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+    pub secret: Option<String>,
+}
+
+fn validate_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "name must be 1-{MAX_NAME_LEN} characters"
+        )));
+    }
+    Ok(name)
+}
+
+fn validate_url(url: &str) -> Result<&str, AppError> {
+    let url = url.trim();
+    let host = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or("");
+    if host.is_empty() {
+        return Err(AppError::Validation(
+            "url must start with http:// or https:// and include a host".to_string(),
+        ));
+    }
+    Ok(url)
+}
+
+/// Normalise an event list into the stored comma-separated form.
+fn validate_events(events: &[String]) -> Result<String, AppError> {
+    let mut events: Vec<&str> = events.iter().map(|e| e.trim()).collect();
+    if events.is_empty() {
+        return Err(AppError::Validation(
+            "at least one event type is required (use \"*\" for all)".to_string(),
+        ));
+    }
+    if let Some(bad) = events.iter().find(|e| {
+        **e != webhook::ALL_EVENTS
+            && (e.is_empty()
+                || !e
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'))
+    }) {
+        return Err(AppError::Validation(format!(
+            "invalid event type '{bad}' (expected e.g. new_device, or \"*\")"
+        )));
+    }
+    events.sort_unstable();
+    events.dedup();
+    Ok(events.join(","))
+}
+
+/// Map a unique-constraint violation on `name` to 409.
+fn name_conflict(e: sqlx::Error, name: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("A webhook named '{name}' already exists"))
+        }
+        e => e.into(),
+    }
+}
+
+async fn load_row(db: &SqlitePool, id: &str) -> Result<WebhookRow, AppError> {
+    sqlx::query_as(
+        "SELECT id, name, url, events, enabled, secret, created_at FROM webhooks WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| AppError::ResourceNotFound("webhook", "Webhook not found".to_string()))
+}
+
+/// GET /api/v1/webhooks — list webhooks.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<Webhook>>, AppError> {
+    let rows: Vec<WebhookRow> = sqlx::query_as(
+        "SELECT id, name, url, events, enabled, secret, created_at FROM webhooks ORDER BY name",
+    )
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(Webhook::from).collect()))
+}
+
+/// POST /api/v1/webhooks — create a webhook.
+pub async fn create(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Json(body): Json<CreateWebhook>,
+) -> Result<(StatusCode, Json<CreateWebhookResponse>), AppError> {
+    let name = validate_name(&body.name)?;
+    let url = validate_url(&body.url)?;
+    let events = validate_events(&body.events)?;
+    let secret = match body.secret {
+        Some(secret) if secret.is_empty() => None,
+        Some(secret) => Some(secret),
+        None => Some(crate::crypto::random_hex(SECRET_BYTES)),
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query(
+        "INSERT INTO webhooks (id, name, url, events, enabled, secret) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&id)
+    .bind(name)
+    .bind(url)
+    .bind(&events)
+    .bind(body.enabled)
+    .bind(&secret)
+    .execute(&state.db)
+    .await
+    .map_err(|e| name_conflict(e, name))?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "webhook_create",
+        &format!("Created webhook '{name}' for {events}"),
+        &[],
+    )
+    .await;
+
+    let webhook = load_row(&state.db, &id).await?.into();
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse {
+            webhook,
+            secret,
+            signature: SIGNATURE_INFO,
+        }),
+    ))
+}
+
+/// PATCH /api/v1/webhooks/:id — update a webhook.
+pub async fn update(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateWebhook>,
+) -> Result<Json<Webhook>, AppError> {
+    let existing = load_row(&state.db, &id).await?;
+    let name = body.name.as_deref().map(validate_name).transpose()?;
+    let url = body.url.as_deref().map(validate_url).transpose()?;
+    let events = body.events.as_deref().map(validate_events).transpose()?;
+    let secret = match body.secret.as_deref() {
+        Some("") => None,
+        Some(secret) => Some(secret),
+        None => existing.secret.as_deref(),
+    };
+
+    sqlx::query(
+        "UPDATE webhooks SET name = COALESCE(?, name), url = COALESCE(?, url), \
+         events = COALESCE(?, events), enabled = COALESCE(?, enabled), secret = ? \
+         WHERE id = ?",
+    )
+    .bind(name)
+    .bind(url)
+    .bind(&events)
+    .bind(body.enabled)
+    .bind(secret)
+    .bind(&id)
+    .execute(&state.db)
+    .await
+    .map_err(|e| name_conflict(e, name.unwrap_or(&existing.name)))?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "webhook_update",
+        &format!("Updated webhook '{}'", name.unwrap_or(&existing.name)),
+        &[],
+    )
+    .await;
+
+    Ok(Json(load_row(&state.db, &id).await?.into()))
+}
+
+/// DELETE /api/v1/webhooks/:id — delete a webhook.
+pub async fn delete(
+    State(state): State<AppState>,
+    Actor(actor): Actor,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let existing = load_row(&state.db, &id).await?;
+
+    sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(&id)
+        .execute(&state.db)
+        .await?;
+
+    audit::log_success(
+        &state.db,
+        &actor,
+        "webhook_delete",
+        &format!("Deleted webhook '{}'", existing.name),
+        &[],
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/webhooks/:id/test — send a test event to one webhook (even
+/// if disabled) and report whether it was accepted.
+pub async fn test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let row = load_row(&state.db, &id).await?;
+    let payload = webhook::webhook_payload(
+        "test",
+        serde_json::json!({ "message": "Panoptikon webhook test", "webhook": row.name }),
+    );
+
+    webhook::send_webhook(
+        &row.url,
+        row.secret.as_deref().filter(|s| !s.is_empty()),
+        &payload,
+    )
+    .await
+    .map_err(|e| AppError::BadGateway(e.to_string()))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/webhooks/:id/deliveries — the most recent retried deliveries.
+pub async fn deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    load_row(&state.db, &id).await?;
+    let rows: Vec<WebhookDeliveryRow> = sqlx::query_as(
+        r#"SELECT id, event_type, payload_json, attempt_count, last_attempt_at, status,
+                  response_code, error
+           FROM webhook_deliveries
+           WHERE webhook_id = ?
+           ORDER BY last_attempt_at DESC, rowid DESC
+           LIMIT ?"#,
+    )
+    .bind(&id)
+    .bind(DELIVERY_LOG_LIMIT)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(Json(rows.into_iter().map(WebhookDelivery::from).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, http::HeaderMap, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(Option<String>, serde_json::Value, Bytes)>>>;
+
+    /// Local endpoint recording each request's signature header, JSON and raw body.
+    async fn mock_receiver() -> (String, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                let signature = headers
+                    .get(webhook::SIGNATURE_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let json = serde_json::from_slice(&body).unwrap();
+                sink.lock().unwrap().push((signature, json, body));
+                StatusCode::OK
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/hook"), received)
+    }
+
+    async fn test_state() -> AppState {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        AppState::new(pool, crate::config::AppConfig::default())
+    }
+
+    fn create_body(name: &str, url: &str, events: &[&str]) -> Json<CreateWebhook> {
+        Json(CreateWebhook {
+            name: name.to_string(),
+            url: url.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            enabled: true,
+            secret: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_webhook_crud_and_test_delivery() {
+        let state = test_state().await;
+        let (url, received) = mock_receiver().await;
+
+        let (
+            status,
+            Json(CreateWebhookResponse {
+                webhook: created,
+                secret,
+                ..
+            }),
+        ) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Json(CreateWebhook {
+                secret: Some("s3cret".to_string()),
+                ..create_body(
+                    "security",
+                    &url,
+                    &["new_device", " device_offline", "new_device"],
+                )
+                .0
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.events, vec!["device_offline", "new_device"]);
+        assert!(created.has_secret && created.enabled);
+        assert_eq!(secret.as_deref(), Some("s3cret"));
+
+        let status = test(State(state.clone()), Path(created.id.clone()))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        {
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 1);
+            let signature = received[0].0.as_deref();
+            assert_eq!(
+                signature,
+                Some(webhook::sign("s3cret", &received[0].2).as_str())
+            );
+            assert_eq!(received[0].1["type"], "test");
+        }
+
+        let Json(updated) = update(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(created.id.clone()),
+            Json(UpdateWebhook {
+                events: Some(vec!["*".to_string()]),
+                enabled: Some(false),
+                secret: Some(String::new()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated.events, vec!["*"]);
+        assert!(!updated.enabled && !updated.has_secret);
+        assert_eq!(updated.name, "security");
+
+        let Json(all) = list(State(state.clone())).await.unwrap();
+        assert_eq!(all, vec![updated]);
+
+        let status = delete(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Path(created.id.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(matches!(
+            test(State(state), Path(created.id)).await,
+            Err(AppError::ResourceNotFound(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_filters_by_event() {
+        let state = test_state().await;
+        let (url, received) = mock_receiver().await;
+        for (name, events) in [("sec", "new_device"), ("ops", "device_offline")] {
+            let (status, _) = create(
+                State(state.clone()),
+                Actor("admin".to_string()),
+                create_body(name, &format!("{url}?{name}"), &[events]),
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        webhook::dispatch_webhook(&state.db, "new_device", serde_json::json!({"mac": "aa"}));
+        for _ in 0..50 {
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].1["type"], "new_device");
+        assert_eq!(received[0].1["data"]["mac"], "aa");
+    }
+
+    #[tokio::test]
+    async fn test_create_validation() {
+        let state = test_state().await;
+        for body in [
+            create_body("", "https://hooks.example", &["new_device"]),
+            create_body("x", "hooks.example", &["new_device"]),
+            create_body("x", "https://hooks.example", &[]),
+            create_body("x", "https://hooks.example", &["New Device"]),
+        ] {
+            let err = create(State(state.clone()), Actor("admin".to_string()), body)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)));
+        }
+
+        let (_, Json(CreateWebhookResponse { webhook: first, .. })) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body("x", "https://hooks.example", &["*"]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.events, vec!["*"]);
+        let err = create(
+            State(state),
+            Actor("admin".to_string()),
+            create_body("x", "https://other.example", &["*"]),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_logged() {
+        let state = test_state().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        let (_, Json(CreateWebhookResponse { webhook: hook, .. })) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body("dead", &dead_url, &["*"]),
+        )
+        .await
+        .unwrap();
+
+        webhook::dispatch_webhook(&state.db, "new_device", serde_json::json!({"mac": "aa"}));
+        let mut logged = Vec::new();
+        for _ in 0..100 {
+            let Json(rows) = deliveries(State(state.clone()), Path(hook.id.clone()))
+                .await
+                .unwrap();
+            if !rows.is_empty() {
+                logged = rows;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(logged.len(), 1);
+        let delivery = &logged[0];
+        assert_eq!(delivery.event_type, "new_device");
+        assert_eq!(delivery.status, "pending");
+        assert_eq!(delivery.attempt_count, 1);
+        assert_eq!(delivery.payload["data"]["mac"], "aa");
+        assert!(delivery.error.is_some());
+
+        assert!(matches!(
+            deliveries(State(state), Path("missing".to_string())).await,
+            Err(AppError::ResourceNotFound(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_generated_secret_signs_deliveries() {
+        let state = test_state().await;
+        let (url, received) = mock_receiver().await;
+
+        let (_, Json(created)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            create_body("signed", &url, &["*"]),
+        )
+        .await
+        .unwrap();
+        let secret = created.secret.expect("a secret is generated by default");
+        assert_eq!(secret.len(), SECRET_BYTES * 2);
+        assert!(secret.chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(created.webhook.has_secret);
+        assert_eq!(created.signature.header, "X-Panoptikon-Signature");
+
+        let (_, Json(unsigned)) = create(
+            State(state.clone()),
+            Actor("admin".to_string()),
+            Json(CreateWebhook {
+                secret: Some(String::new()),
+                ..create_body("unsigned", &url, &["*"]).0
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(unsigned.secret.is_none() && !unsigned.webhook.has_secret);
+
+        for id in [&created.webhook.id, &unsigned.webhook.id] {
+            test(State(state.clone()), Path(id.clone())).await.unwrap();
+        }
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(
+            received[0].0.as_deref(),
+            Some(webhook::sign(&secret, &received[0].2).as_str())
+        );
+        assert_eq!(
+            received[1].0, None,
+            "webhooks without a secret are unsigned"
+        );
+    }
+
+    #[test]
+    fn test_create_response_serialization() {
+        let response = CreateWebhookResponse {
+            webhook: Webhook {
+                id: "1".to_string(),
+                name: "ops".to_string(),
+                url: "https://hooks.example".to_string(),
+                events: vec!["*".to_string()],
+                enabled: true,
+                has_secret: true,
+                created_at: "2026-03-01 12:00:00".to_string(),
+            },
+            secret: Some("abc".to_string()),
+            signature: SIGNATURE_INFO,
+        };
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["name"], "ops");
+        assert_eq!(json["secret"], "abc");
+        assert_eq!(json["signature"]["header"], "X-Panoptikon-Signature");
+        assert_eq!(json["signature"]["algorithm"], "HMAC-SHA256");
+    }
+}
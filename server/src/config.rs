@@ -1,5 +1,18 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use serde::Deserialize;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Configuration shared by handlers and background tasks; replaced wholesale
+/// when the config file is reloaded on SIGHUP.
+pub type SharedConfig = Arc<RwLock<AppConfig>>;
+
+/// Read the shared configuration. Writers only swap in a complete value, so a
+/// poisoned lock still holds a consistent config.
+pub fn read(shared: &SharedConfig) -> RwLockReadGuard<'_, AppConfig> {
+    shared.read().unwrap_or_else(PoisonError::into_inner)
+}
 
 /// Top-level configuration loaded from a TOML file or defaults.
 #[derive(Debug, Clone, Deserialize)]
@@ -28,12 +41,67 @@ pub struct AppConfig {
     /// Retention section — data cleanup periods.
     #[serde(default)]
     pub retention: RetentionConfig,
+
+    /// Alerts section — anomaly detection thresholds.
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+
+    /// Run a WAN speed test every this many hours (disabled when unset).
+    #[serde(default)]
+    pub speedtest_interval_hours: Option<u64>,
+
+    /// Log a warning when a scheduled test's download falls below this (Mbps).
+    #[serde(default)]
+    pub speedtest_warn_threshold_mbps: Option<f64>,
+
+    /// Forward audit log entries to this syslog server (disabled when unset).
+    #[serde(default)]
+    pub syslog_host: Option<String>,
+
+    /// UDP port of the syslog server.
+    #[serde(default = "default_syslog_port")]
+    pub syslog_port: u16,
+
+    /// Minimum trigram similarity (0–1) for fuzzy device search results.
+    #[serde(default = "default_fuzzy_search_threshold")]
+    pub fuzzy_search_threshold: f64,
+
+    /// Maximum number of devices (most recently seen first) exported with
+    /// per-device labels on `/metrics`, bounding label cardinality.
+    #[serde(default = "default_prometheus_max_device_labels")]
+    pub prometheus_max_device_labels: usize,
+
+    /// Where the downloaded IEEE OUI database is stored
+    /// (default `~/.local/share/panoptikon/oui_db.csv`).
+    #[serde(default)]
+    pub oui_db_path: Option<String>,
+
+    /// Key used to encrypt stored router credentials (default `<db_path>.key`).
+    #[serde(default)]
+    pub secret_key_path: Option<String>,
+
+    /// Secret the credential encryption key is derived from. Takes precedence
+    /// over `secret_key_path`; overridden by `PANOPTIKON_SECRET_KEY`.
+    #[serde(default)]
+    pub secret_key: Option<String>,
 }
 
 fn default_listen() -> Option<String> {
     Some("0.0.0.0:8080".to_string())
 }
 
+fn default_syslog_port() -> u16 {
+    514
+}
+
+fn default_fuzzy_search_threshold() -> f64 {
+    0.4
+}
+
+fn default_prometheus_max_device_labels() -> usize {
+    50
+}
+
 /// VyOS router connection settings.
 #[derive(Debug, Clone, Default, Deserialize)]
 #[allow(dead_code)]
@@ -47,10 +115,57 @@ pub struct VyosConfig {
     /// Accept self-signed TLS certificates.
     #[serde(default)]
     pub insecure_tls: bool,
+
+    /// How long slow read endpoints are served from cache.
+    #[serde(default)]
+    pub cache_ttl: VyosCacheTtlConfig,
 }
 
-/// ARP scanner settings.
+/// Cache lifetimes in seconds for VyOS read endpoints; 0 disables caching.
 #[derive(Debug, Clone, Deserialize)]
+pub struct VyosCacheTtlConfig {
+    #[serde(default = "default_cache_ttl_status")]
+    pub status: u64,
+    #[serde(default = "default_cache_ttl_interfaces")]
+    pub interfaces: u64,
+    #[serde(default = "default_cache_ttl_routes")]
+    pub routes: u64,
+    #[serde(default = "default_cache_ttl_dhcp")]
+    pub dhcp: u64,
+    #[serde(default = "default_cache_ttl_firewall")]
+    pub firewall: u64,
+}
+
+fn default_cache_ttl_status() -> u64 {
+    30
+}
+fn default_cache_ttl_interfaces() -> u64 {
+    15
+}
+fn default_cache_ttl_routes() -> u64 {
+    15
+}
+fn default_cache_ttl_dhcp() -> u64 {
+    60
+}
+fn default_cache_ttl_firewall() -> u64 {
+    30
+}
+
+impl Default for VyosCacheTtlConfig {
+    fn default() -> Self {
+        Self {
+            status: default_cache_ttl_status(),
+            interfaces: default_cache_ttl_interfaces(),
+            routes: default_cache_ttl_routes(),
+            dhcp: default_cache_ttl_dhcp(),
+            firewall: default_cache_ttl_firewall(),
+        }
+    }
+}
+
+/// ARP scanner settings.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct ScannerConfig {
     /// Subnets to scan (CIDR notation).
     #[serde(default)]
@@ -69,6 +184,18 @@ pub struct ScannerConfig {
     #[serde(default = "default_arp_settle_millis")]
     pub arp_settle_millis: u64,
 
+    /// Maximum number of pings in flight during the sweep.
+    #[serde(default = "default_ping_concurrency")]
+    pub ping_concurrency: usize,
+
+    /// How long (ms) each sweep ping waits for a reply.
+    #[serde(default = "default_ping_timeout_ms")]
+    pub ping_timeout_ms: u64,
+
+    /// Default gateway IP; a new MAC claiming it raises an `arp_spoofing` alert.
+    #[serde(default)]
+    pub gateway_ip: Option<String>,
+
     /// Enable NetFlow v5 UDP collector.
     #[serde(default)]
     pub netflow_enabled: bool,
@@ -80,6 +207,33 @@ pub struct ScannerConfig {
     /// Enable passive mDNS/Bonjour discovery of device hostnames and services.
     #[serde(default = "default_mdns_enabled")]
     pub mdns_enabled: bool,
+
+    /// Read the IPv6 neighbour (NDP) table to discover IPv6 addresses.
+    #[serde(default = "default_ndp_enabled")]
+    pub ndp_enabled: bool,
+
+    /// Interfaces to ping `ff02::1` (all-nodes multicast) on before reading
+    /// the NDP table. Empty disables the sweep.
+    #[serde(default)]
+    pub ndp_sweep_interfaces: Vec<String>,
+
+    /// mDNS service types to ignore (e.g. `_sleep-proxy._udp`). Overridden
+    /// at runtime by the `mdns_ignore_service_types` setting.
+    #[serde(default)]
+    pub mdns_ignore_service_types: Vec<String>,
+
+    /// If non-empty, only these mDNS service types are recorded. Overridden
+    /// at runtime by the `mdns_include_service_types` setting.
+    #[serde(default)]
+    pub mdns_include_service_types: Vec<String>,
+
+    /// Enable passive SSDP/UPnP discovery (listens on 239.255.255.250:1900).
+    #[serde(default)]
+    pub ssdp_enabled: bool,
+}
+
+fn default_ndp_enabled() -> bool {
+    true
 }
 
 fn default_mdns_enabled() -> bool {
@@ -98,6 +252,14 @@ fn default_arp_settle_millis() -> u64 {
     500
 }
 
+fn default_ping_concurrency() -> usize {
+    256
+}
+
+fn default_ping_timeout_ms() -> u64 {
+    500
+}
+
 fn default_netflow_port() -> u16 {
     9995
 }
@@ -109,9 +271,17 @@ impl Default for ScannerConfig {
             interval_seconds: default_scan_interval(),
             offline_grace_seconds: default_offline_grace(),
             arp_settle_millis: default_arp_settle_millis(),
+            ping_concurrency: default_ping_concurrency(),
+            ping_timeout_ms: default_ping_timeout_ms(),
+            gateway_ip: None,
             netflow_enabled: false,
             netflow_port: default_netflow_port(),
             mdns_enabled: default_mdns_enabled(),
+            ndp_enabled: default_ndp_enabled(),
+            ndp_sweep_interfaces: Vec::new(),
+            mdns_ignore_service_types: Vec::new(),
+            mdns_include_service_types: Vec::new(),
+            ssdp_enabled: false,
         }
     }
 }
@@ -127,6 +297,32 @@ pub struct AuthConfig {
     /// Only add addresses you control. Defaults to loopback only.
     #[serde(default = "default_trusted_proxies")]
     pub trusted_proxies: Vec<String>,
+
+    /// LDAP/AD server URL, e.g. `ldaps://dc.example.com`. Named users are
+    /// authenticated against it when this and `ldap_user_base_dn` are set.
+    #[serde(default)]
+    pub ldap_url: Option<String>,
+
+    /// DN to bind as when searching for users (anonymous search if unset).
+    #[serde(default)]
+    pub ldap_bind_dn: Option<String>,
+
+    #[serde(default)]
+    pub ldap_bind_password: Option<String>,
+
+    /// Base DN searched for user entries.
+    #[serde(default)]
+    pub ldap_user_base_dn: Option<String>,
+
+    /// User search filter; `{username}` is replaced by the login name
+    /// (default `(uid={username})`).
+    #[serde(default)]
+    pub ldap_user_filter: Option<String>,
+
+    /// Disable local password logins, including the built-in admin, when
+    /// LDAP is configured.
+    #[serde(default)]
+    pub ldap_required: bool,
 }
 
 fn default_session_expiry() -> u64 {
@@ -142,12 +338,18 @@ impl Default for AuthConfig {
         Self {
             session_expiry_seconds: default_session_expiry(),
             trusted_proxies: default_trusted_proxies(),
+            ldap_url: None,
+            ldap_bind_dn: None,
+            ldap_bind_password: None,
+            ldap_user_base_dn: None,
+            ldap_user_filter: None,
+            ldap_required: false,
         }
     }
 }
 
 /// Data retention / cleanup periods.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct RetentionConfig {
     /// Delete traffic_samples older than this many hours (default 48).
     #[serde(default = "default_traffic_samples_hours")]
@@ -164,6 +366,10 @@ pub struct RetentionConfig {
     /// Delete acknowledged alerts older than this many days (default 90).
     #[serde(default = "default_alerts_days")]
     pub alerts_days: u64,
+
+    /// Delete netflow_flows older than this many days (default 7).
+    #[serde(default = "default_netflow_flows_days")]
+    pub netflow_flows_days: u64,
 }
 
 fn default_traffic_samples_hours() -> u64 {
@@ -178,6 +384,9 @@ fn default_device_events_days() -> u64 {
 fn default_alerts_days() -> u64 {
     90
 }
+fn default_netflow_flows_days() -> u64 {
+    7
+}
 
 impl Default for RetentionConfig {
     fn default() -> Self {
@@ -186,6 +395,36 @@ impl Default for RetentionConfig {
             agent_reports_days: default_agent_reports_days(),
             device_events_days: default_device_events_days(),
             alerts_days: default_alerts_days(),
+            netflow_flows_days: default_netflow_flows_days(),
+        }
+    }
+}
+
+/// Anomaly detection thresholds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertsConfig {
+    /// Raise `traffic_spike` when a device's NetFlow bps exceeds its recent
+    /// average by this factor (default 3).
+    #[serde(default = "default_traffic_spike_factor")]
+    pub traffic_spike_factor: f64,
+
+    /// Samples of history a device needs before spikes are reported (default 5).
+    #[serde(default = "default_traffic_spike_min_samples")]
+    pub traffic_spike_min_samples: usize,
+}
+
+fn default_traffic_spike_factor() -> f64 {
+    3.0
+}
+fn default_traffic_spike_min_samples() -> usize {
+    5
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            traffic_spike_factor: default_traffic_spike_factor(),
+            traffic_spike_min_samples: default_traffic_spike_min_samples(),
         }
     }
 }
@@ -199,15 +438,231 @@ impl Default for AppConfig {
             scanner: ScannerConfig::default(),
             auth: AuthConfig::default(),
             retention: RetentionConfig::default(),
+            alerts: AlertsConfig::default(),
+            speedtest_interval_hours: None,
+            speedtest_warn_threshold_mbps: None,
+            syslog_host: None,
+            syslog_port: default_syslog_port(),
+            fuzzy_search_threshold: default_fuzzy_search_threshold(),
+            prometheus_max_device_labels: default_prometheus_max_device_labels(),
+            oui_db_path: None,
+            secret_key_path: None,
+            secret_key: None,
         }
     }
 }
 
 impl AppConfig {
-    /// Load configuration from a TOML file.
+    /// Load and validate configuration from a TOML file.
     pub fn from_file(path: &str) -> Result<Self> {
         let contents = std::fs::read_to_string(path)?;
         let config: AppConfig = toml::de::from_str(&contents)?;
+        config.validate()?;
         Ok(config)
     }
+
+    /// Reject values that would break the server at runtime.
+    pub fn validate(&self) -> Result<()> {
+        if self.scanner.interval_seconds == 0 {
+            bail!("scanner.interval_seconds must be at least 1");
+        }
+        if self.scanner.ping_concurrency == 0 {
+            bail!("scanner.ping_concurrency must be at least 1");
+        }
+        if self.scanner.ping_timeout_ms == 0 {
+            bail!("scanner.ping_timeout_ms must be at least 1");
+        }
+        if let Some(ref gateway) = self.scanner.gateway_ip {
+            if gateway.parse::<std::net::IpAddr>().is_err() {
+                bail!("scanner.gateway_ip: '{gateway}' is not a valid IP address");
+            }
+        }
+        if let Some(subnet) = self
+            .scanner
+            .subnets
+            .iter()
+            .find(|s| s.parse::<ipnetwork::IpNetwork>().is_err())
+        {
+            bail!("scanner.subnets: '{subnet}' is not a valid CIDR subnet");
+        }
+        if self.alerts.traffic_spike_factor <= 1.0 {
+            bail!("alerts.traffic_spike_factor must be greater than 1");
+        }
+        if !(0.0..=1.0).contains(&self.fuzzy_search_threshold) {
+            bail!("fuzzy_search_threshold must be between 0 and 1");
+        }
+        if let Some(ref listen) = self.listen {
+            if listen.parse::<std::net::SocketAddr>().is_err() {
+                bail!("listen: '{listen}' is not a valid address:port");
+            }
+        }
+        Ok(())
+    }
+
+    /// Settings that are only read at startup and differ from `other`.
+    fn restart_required_changes(&self, other: &AppConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.listen != other.listen {
+            changed.push("listen");
+        }
+        if self.db_path != other.db_path {
+            changed.push("db_path");
+        }
+        if self.secret_key_path != other.secret_key_path {
+            changed.push("secret_key_path");
+        }
+        if self.secret_key != other.secret_key {
+            changed.push("secret_key");
+        }
+        if self.syslog_host != other.syslog_host || self.syslog_port != other.syslog_port {
+            changed.push("syslog");
+        }
+        if self.oui_db_path != other.oui_db_path {
+            changed.push("oui_db_path");
+        }
+        if self.scanner.netflow_enabled != other.scanner.netflow_enabled
+            || self.scanner.netflow_port != other.scanner.netflow_port
+        {
+            changed.push("scanner.netflow");
+        }
+        if self.scanner.mdns_enabled != other.scanner.mdns_enabled {
+            changed.push("scanner.mdns_enabled");
+        }
+        if self.scanner.ssdp_enabled != other.scanner.ssdp_enabled {
+            changed.push("scanner.ssdp_enabled");
+        }
+        if self.retention != other.retention {
+            changed.push("retention");
+        }
+        if self.speedtest_interval_hours != other.speedtest_interval_hours {
+            changed.push("speedtest_interval_hours");
+        }
+        changed
+    }
+}
+
+/// Re-read the config file at `path` and swap it into `shared`.
+///
+/// An unreadable or invalid file is reported as an error and the current
+/// config stays active. When the `[scanner]` section changes, the new value
+/// is sent on `scanner_tx` so the scanner task restarts its schedule.
+pub fn reload(
+    shared: &SharedConfig,
+    path: &str,
+    scanner_tx: &watch::Sender<ScannerConfig>,
+) -> Result<()> {
+    let new_config = AppConfig::from_file(path)?;
+
+    let old_config = {
+        let mut current = shared.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *current, new_config.clone())
+    };
+
+    let restart_required = old_config.restart_required_changes(&new_config);
+    if !restart_required.is_empty() {
+        warn!(
+            settings = ?restart_required,
+            "Reloaded config changes settings that only take effect after a restart"
+        );
+    }
+    if old_config.scanner != new_config.scanner {
+        scanner_tx.send_replace(new_config.scanner);
+        info!("Scanner configuration changed; restarting scan schedule");
+    }
+    info!(path, "Configuration reloaded");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("panoptikon-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(AppConfig::default().validate().is_ok());
+
+        let mut config = AppConfig::default();
+        config.scanner.subnets = vec!["192.168.1.0/24".to_string(), "10.0.0.0/33".to_string()];
+        assert!(config.validate().is_err());
+
+        let mut config = AppConfig::default();
+        config.scanner.interval_seconds = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = AppConfig::default();
+        config.scanner.ping_concurrency = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = AppConfig::default();
+        config.scanner.gateway_ip = Some("10.0.0.256".to_string());
+        assert!(config.validate().is_err());
+
+        let mut config = AppConfig::default();
+        config.alerts.traffic_spike_factor = 0.5;
+        assert!(config.validate().is_err());
+
+        let config = AppConfig {
+            listen: Some("not-an-address".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_reload_swaps_config_and_notifies_scanner() {
+        let shared: SharedConfig = Arc::new(RwLock::new(AppConfig::default()));
+        let (scanner_tx, mut scanner_rx) = watch::channel(ScannerConfig::default());
+
+        let file =
+            write_config("fuzzy_search_threshold = 0.7\n[scanner]\nsubnets = [\"10.1.0.0/24\"]\n");
+        reload(&shared, &file, &scanner_tx).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(read(&shared).fuzzy_search_threshold, 0.7);
+        assert!(scanner_rx.has_changed().unwrap());
+        assert_eq!(scanner_rx.borrow_and_update().subnets, vec!["10.1.0.0/24"]);
+
+        // Unchanged scanner section: no restart.
+        let file =
+            write_config("fuzzy_search_threshold = 0.5\n[scanner]\nsubnets = [\"10.1.0.0/24\"]\n");
+        reload(&shared, &file, &scanner_tx).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(read(&shared).fuzzy_search_threshold, 0.5);
+        assert!(!scanner_rx.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_reload_keeps_old_config_when_invalid() {
+        let shared: SharedConfig = Arc::new(RwLock::new(AppConfig::default()));
+        let (scanner_tx, scanner_rx) = watch::channel(ScannerConfig::default());
+
+        for contents in ["[scanner\n", "[scanner]\nsubnets = [\"nope\"]\n"] {
+            let file = write_config(contents);
+            assert!(reload(&shared, &file, &scanner_tx).is_err());
+            std::fs::remove_file(file).unwrap();
+        }
+        assert!(reload(&shared, "/nonexistent/panoptikon.toml", &scanner_tx).is_err());
+        assert!(read(&shared).scanner.subnets.is_empty());
+        assert!(!scanner_rx.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_restart_required_changes() {
+        let old = AppConfig::default();
+        assert!(old.restart_required_changes(&old.clone()).is_empty());
+
+        let mut new = old.clone();
+        new.fuzzy_search_threshold = 0.9;
+        new.retention.agent_reports_days = 30;
+        new.speedtest_interval_hours = Some(6);
+        assert_eq!(
+            old.restart_required_changes(&new),
+            vec!["retention", "speedtest_interval_hours"]
+        );
+    }
 }
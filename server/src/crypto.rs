@@ -0,0 +1,344 @@
+//! At-rest encryption for secrets stored in SQLite (router profile API keys
+//! and the `vyos_api_key` setting).
+//!
+//! Values are sealed with AES-256-GCM and stored as `enc:v1:` followed by
+//! base64 `nonce || ciphertext`; the marker tells sealed values apart from
+//! plaintext left by older versions.
+//! The key is derived with HKDF-SHA256 from `secret_key` (or the
+//! `PANOPTIKON_SECRET_KEY` environment variable) when one is set; otherwise it
+//! is generated on first start and kept outside the database, in
+//! `secret_key_path` or `<db path>.key` by default, so a copied database file
+//! alone does not reveal router credentials.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use tracing::{error, info, warn};
+
+use crate::config::AppConfig;
+
+/// AES-GCM nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Prefix of every value produced by [`encrypt`].
+pub const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Environment variable that overrides `secret_key` in the config file.
+pub const SECRET_KEY_ENV: &str = "PANOPTIKON_SECRET_KEY";
+
+/// HKDF `info` string binding derived keys to this use.
+const HKDF_INFO: &[u8] = b"panoptikon secret encryption";
+
+/// Settings-table keys whose values are stored encrypted.
+pub const SEALED_SETTINGS: &[&str] = &[
+    "vyos_api_key",
+    "smtp_password",
+    "telegram_bot_token",
+    "ldap_bind_password",
+    "git_archive_auth_token",
+    "admin_totp_secret",
+    "admin_totp_pending_secret",
+];
+
+/// Key loaded at startup. Unset for in-memory databases (tests), where a
+/// random per-process key is used instead.
+static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+/// Where the encryption key is kept; `None` for in-memory databases.
+pub fn key_path(config: &AppConfig, db_path: &str) -> Option<PathBuf> {
+    if let Some(path) = config.secret_key_path.as_deref().filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    if db_path == ":memory:" {
+        return None;
+    }
+    Some(PathBuf::from(format!("{db_path}.key")))
+}
+
+/// The configured key secret: `PANOPTIKON_SECRET_KEY`, else `secret_key`.
+pub fn secret(config: &AppConfig) -> Option<String> {
+    std::env::var(SECRET_KEY_ENV)
+        .ok()
+        .or_else(|| config.secret_key.clone())
+        .filter(|s| !s.is_empty())
+}
+
+/// Derive the AES key from a configured secret.
+fn derive_key(secret: &str) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, secret.as_bytes())
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Derive the key from `secret` if given, else load it from `path`, creating
+/// it (mode 0600) if missing. Call once at startup, before any secret is
+/// encrypted or decrypted.
+pub fn init(path: Option<&Path>, secret: Option<&str>) -> anyhow::Result<()> {
+    if let Some(secret) = secret {
+        let _ = KEY.set(derive_key(secret));
+        info!("Using encryption key derived from secret_key");
+        return Ok(());
+    }
+    let Some(path) = path else {
+        return Ok(());
+    };
+    let key = match std::fs::read_to_string(path) {
+        Ok(contents) => decode_hex(contents.trim())
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .ok_or_else(|| anyhow!("invalid secret key file {}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key: [u8; 32] = Aes256Gcm::generate_key(OsRng).into();
+            write_key_file(path, &encode_hex(&key))?;
+            info!(path = %path.display(), "Generated secret encryption key");
+            key
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let _ = KEY.set(key);
+    Ok(())
+}
+
+fn write_key_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.write_all(b"\n")
+}
+
+fn key() -> &'static [u8; 32] {
+    KEY.get_or_init(|| Aes256Gcm::generate_key(OsRng).into())
+}
+
+/// Encrypt `plaintext` with AES-256-GCM under a random nonce, returning
+/// base64 `nonce || ciphertext`.
+pub fn encrypt_aes256gcm(plaintext: &str, key: &[u8; 32]) -> String {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .encrypt(&nonce, plaintext.as_bytes())
+        .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    BASE64.encode(sealed)
+}
+
+/// Decrypt a value produced by [`encrypt_aes256gcm`]. Fails if it is
+/// malformed or was sealed with a different key.
+pub fn decrypt_aes256gcm(ciphertext_b64: &str, key: &[u8; 32]) -> anyhow::Result<String> {
+    let bytes = BASE64.decode(ciphertext_b64)?;
+    if bytes.len() <= NONCE_LEN {
+        bail!("ciphertext too short");
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("decryption failed; wrong key or corrupted value"))?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+/// Encrypt a secret for storage with the startup key, marked with
+/// [`SEALED_PREFIX`].
+pub fn encrypt(plaintext: &str) -> String {
+    format!("{SEALED_PREFIX}{}", encrypt_aes256gcm(plaintext, key()))
+}
+
+/// Decrypt a value produced by [`encrypt`]. `None` if it is unmarked,
+/// malformed or was sealed with a different key.
+pub fn decrypt(sealed: &str) -> Option<String> {
+    decrypt_aes256gcm(sealed.strip_prefix(SEALED_PREFIX)?, key()).ok()
+}
+
+/// Read a [`SEALED_SETTINGS`] value, decrypted. `None` if it is unset or
+/// cannot be decrypted with the current key.
+pub async fn get_sealed_setting(db: &SqlitePool, key: &str) -> Option<String> {
+    let sealed: String = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(db)
+        .await
+        .ok()
+        .flatten()
+        .filter(|v: &String| !v.is_empty())?;
+    unseal_setting(key, &sealed)
+}
+
+/// Decrypt the stored value of the [`SEALED_SETTINGS`] entry `key`, logging
+/// a warning if it cannot be decrypted with the current key.
+pub fn unseal_setting(key: &str, sealed: &str) -> Option<String> {
+    let plaintext = decrypt(sealed);
+    if plaintext.is_none() {
+        warn!(
+            key,
+            "Cannot decrypt stored setting; was the secret key changed?"
+        );
+    }
+    plaintext
+}
+
+/// Encrypt [`SEALED_SETTINGS`] values still stored in plaintext by older
+/// versions, i.e. those without [`SEALED_PREFIX`]. Sealed values that no
+/// longer decrypt are logged and left untouched, since re-encrypting them
+/// would bury the original ciphertext. Returns the number of values
+/// encrypted.
+pub async fn seal_plaintext_settings(db: &SqlitePool) -> sqlx::Result<usize> {
+    let mut sealed = 0;
+    for &key in SEALED_SETTINGS {
+        let value: Option<String> = sqlx::query_scalar("SELECT value FROM settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(db)
+            .await?;
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        if value.starts_with(SEALED_PREFIX) {
+            if decrypt(&value).is_none() {
+                error!(
+                    key,
+                    "Cannot decrypt stored setting; was the secret key changed?"
+                );
+            }
+            continue;
+        }
+        sqlx::query("UPDATE settings SET value = ? WHERE key = ?")
+            .bind(encrypt(&value))
+            .bind(key)
+            .execute(db)
+            .await?;
+        info!(key, "Encrypted plaintext setting");
+        sealed += 1;
+    }
+    Ok(sealed)
+}
+
+/// `len` bytes from the OS RNG, hex-encoded.
+pub fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    encode_hex(&bytes)
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let sealed = encrypt("vyos-api-key");
+        assert_ne!(sealed, encrypt("vyos-api-key"), "nonce must be random");
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("vyos"));
+        assert_eq!(decrypt(&sealed).as_deref(), Some("vyos-api-key"));
+
+        // Tampered ciphertext fails authentication.
+        let mut tampered = sealed.clone().into_bytes();
+        let last = tampered.len() - 5;
+        tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+        assert!(decrypt(&String::from_utf8(tampered).unwrap()).is_none());
+        assert!(decrypt("enc:v1:zz").is_none());
+        // Unmarked values are never treated as sealed.
+        assert!(decrypt(sealed.strip_prefix(SEALED_PREFIX).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_aes256gcm_with_explicit_key() {
+        let key = derive_key("hunter2");
+        let sealed = encrypt_aes256gcm("secret", &key);
+        assert!(BASE64.decode(&sealed).is_ok());
+        assert_eq!(decrypt_aes256gcm(&sealed, &key).unwrap(), "secret");
+        assert!(decrypt_aes256gcm(&sealed, &derive_key("hunter3")).is_err());
+        assert!(decrypt_aes256gcm("not base64!", &key).is_err());
+    }
+
+    #[test]
+    fn test_derive_key_is_deterministic() {
+        assert_eq!(derive_key("hunter2"), derive_key("hunter2"));
+        assert_ne!(derive_key("hunter2"), derive_key("hunter3"));
+    }
+
+    #[tokio::test]
+    async fn test_seal_plaintext_settings() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('vyos_api_key', 'plain-key')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(seal_plaintext_settings(&pool).await.unwrap(), 1);
+        let stored: String =
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = 'vyos_api_key'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_ne!(stored, "plain-key");
+        assert_eq!(
+            get_sealed_setting(&pool, "vyos_api_key").await.as_deref(),
+            Some("plain-key")
+        );
+
+        // Already encrypted values are left alone.
+        assert_eq!(seal_plaintext_settings(&pool).await.unwrap(), 0);
+
+        // So are sealed values from another key, instead of being wrapped again.
+        let foreign = format!(
+            "{SEALED_PREFIX}{}",
+            encrypt_aes256gcm("other-key", &derive_key("elsewhere"))
+        );
+        sqlx::query("UPDATE settings SET value = ? WHERE key = 'vyos_api_key'")
+            .bind(&foreign)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(seal_plaintext_settings(&pool).await.unwrap(), 0);
+        let stored: String =
+            sqlx::query_scalar("SELECT value FROM settings WHERE key = 'vyos_api_key'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, foreign);
+    }
+
+    #[test]
+    fn test_key_path() {
+        let config = AppConfig::default();
+        assert_eq!(key_path(&config, ":memory:"), None);
+        assert_eq!(
+            key_path(&config, "/var/lib/panoptikon.db"),
+            Some(PathBuf::from("/var/lib/panoptikon.db.key"))
+        );
+        let config = AppConfig {
+            secret_key_path: Some("/etc/panoptikon/secret.key".to_string()),
+            ..AppConfig::default()
+        };
+        assert_eq!(
+            key_path(&config, "/var/lib/panoptikon.db"),
+            Some(PathBuf::from("/etc/panoptikon/secret.key"))
+        );
+    }
+}
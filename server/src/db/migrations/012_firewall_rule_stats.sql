@@ -0,0 +1,16 @@
+-- Firewall rule statistics: periodic packet/byte counter samples per rule.
+CREATE TABLE IF NOT EXISTS firewall_rule_stats (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    chain        TEXT NOT NULL,           -- VyOS path, e.g. "ipv4.forward.filter"
+    rule_number  INTEGER NOT NULL,
+    packets      INTEGER NOT NULL DEFAULT 0,
+    prev_packets INTEGER NOT NULL DEFAULT 0,
+    bytes        INTEGER NOT NULL DEFAULT 0,
+    prev_bytes   INTEGER NOT NULL DEFAULT 0,
+    sampled_at   TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_firewall_rule_stats_sampled_at
+    ON firewall_rule_stats(sampled_at);
+CREATE INDEX IF NOT EXISTS idx_firewall_rule_stats_rule
+    ON firewall_rule_stats(chain, rule_number);
@@ -0,0 +1,13 @@
+-- Device imports: one row per bulk CSV import with its outcome.
+CREATE TABLE IF NOT EXISTS device_imports (
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    imported_at    TEXT NOT NULL DEFAULT (datetime('now')),
+    total_rows     INTEGER NOT NULL DEFAULT 0,
+    imported_count INTEGER NOT NULL DEFAULT 0,
+    skipped_count  INTEGER NOT NULL DEFAULT 0,
+    error_count    INTEGER NOT NULL DEFAULT 0,
+    errors_json    TEXT NOT NULL DEFAULT '[]',  -- JSON array of {row, message}
+    filename       TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_imports_imported_at ON device_imports(imported_at);
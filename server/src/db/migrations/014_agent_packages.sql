@@ -0,0 +1,2 @@
+-- Installed packages reported by agents (JSON array of {name, version, manager}).
+ALTER TABLE agent_reports ADD COLUMN packages_json TEXT;
@@ -0,0 +1,3 @@
+-- IP version of each device address (4 or 6) so IPv6 neighbours discovered
+-- via NDP can live alongside ARP-discovered IPv4 addresses.
+ALTER TABLE device_ips ADD COLUMN ip_version INTEGER NOT NULL DEFAULT 4;
@@ -0,0 +1,14 @@
+-- NetFlow totals per (src_ip, dst_ip) pair, aggregated over each 60-second
+-- collector window. Device attribution happens at query time via device_ips
+-- so traffic follows a device across IP changes.
+CREATE TABLE IF NOT EXISTS netflow_flows (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    src_ip      TEXT NOT NULL,
+    dst_ip      TEXT NOT NULL,
+    bytes       INTEGER NOT NULL,
+    packets     INTEGER NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_netflow_flows_src ON netflow_flows(src_ip, recorded_at);
+CREATE INDEX IF NOT EXISTS idx_netflow_flows_dst ON netflow_flows(dst_ip, recorded_at);
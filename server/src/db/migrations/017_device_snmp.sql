@@ -0,0 +1,8 @@
+-- Per-device SNMP polling settings.
+CREATE TABLE IF NOT EXISTS device_snmp (
+    device_id   TEXT PRIMARY KEY REFERENCES devices(id) ON DELETE CASCADE,
+    community   TEXT NOT NULL,
+    version     TEXT NOT NULL DEFAULT '2c',
+    port        INTEGER NOT NULL DEFAULT 161,
+    updated_at  TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,4 @@
+-- Best-guess operating system for a device, from mDNS service types or nmap
+-- OS detection, plus the raw nmap OS matches recorded with each port scan.
+ALTER TABLE devices ADD COLUMN os_hint TEXT;
+ALTER TABLE port_scans ADD COLUMN os_matches_json TEXT;
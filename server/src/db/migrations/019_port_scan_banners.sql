@@ -0,0 +1,8 @@
+-- Service banners captured from open TCP ports after a port scan.
+CREATE TABLE IF NOT EXISTS port_scan_banners (
+    scan_id INTEGER NOT NULL,
+    port INTEGER NOT NULL,
+    banner TEXT NOT NULL,
+    PRIMARY KEY (scan_id, port),
+    FOREIGN KEY (scan_id) REFERENCES port_scans(id) ON DELETE CASCADE
+);
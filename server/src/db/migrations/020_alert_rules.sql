@@ -0,0 +1,13 @@
+-- Operator-defined rules that create or suppress alerts for scanner events.
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    condition_json TEXT NOT NULL DEFAULT '{}',
+    action TEXT NOT NULL CHECK (action IN ('create', 'suppress')),
+    severity TEXT,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_rules_event_type ON alert_rules(event_type);
@@ -0,0 +1,7 @@
+-- Migration 021: Alert deduplication — repeated (device, type) alerts within
+-- the dedup window bump `count` and `updated_at` on the existing row.
+
+ALTER TABLE alerts ADD COLUMN count INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE alerts ADD COLUMN updated_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_alerts_device_type ON alerts(device_id, type, created_at);
@@ -0,0 +1,9 @@
+-- Operator-assigned tags for classifying devices (e.g. "server", "printer").
+CREATE TABLE IF NOT EXISTS device_tags (
+    device_id TEXT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (device_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_tags_tag ON device_tags(tag);
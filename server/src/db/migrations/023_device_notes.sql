@@ -0,0 +1,33 @@
+-- Free-text, timestamped notes attached to a device.
+CREATE TABLE IF NOT EXISTS device_notes (
+    id TEXT PRIMARY KEY,
+    device_id TEXT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    author TEXT NOT NULL DEFAULT 'admin',
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_notes_device ON device_notes(device_id, created_at);
+
+-- Full-text index over note bodies, kept in sync by the triggers below.
+CREATE VIRTUAL TABLE IF NOT EXISTS device_notes_fts USING fts5(
+    body,
+    content = 'device_notes',
+    content_rowid = 'rowid'
+);
+
+CREATE TRIGGER IF NOT EXISTS device_notes_fts_insert AFTER INSERT ON device_notes BEGIN
+    INSERT INTO device_notes_fts (rowid, body) VALUES (new.rowid, new.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS device_notes_fts_delete AFTER DELETE ON device_notes BEGIN
+    INSERT INTO device_notes_fts (device_notes_fts, rowid, body)
+    VALUES ('delete', old.rowid, old.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS device_notes_fts_update AFTER UPDATE OF body ON device_notes BEGIN
+    INSERT INTO device_notes_fts (device_notes_fts, rowid, body)
+    VALUES ('delete', old.rowid, old.body);
+    INSERT INTO device_notes_fts (rowid, body) VALUES (new.rowid, new.body);
+END;
@@ -0,0 +1,3 @@
+-- Soft-delete flag for devices removed through the API. Deleted devices are
+-- hidden from listings and restored if the scanner sees them again.
+ALTER TABLE devices ADD COLUMN is_deleted INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Window-only scans of netflow_flows (e.g. top talkers) filter on
+-- recorded_at without an IP, so they need their own index.
+CREATE INDEX IF NOT EXISTS idx_netflow_flows_recorded_at ON netflow_flows(recorded_at);
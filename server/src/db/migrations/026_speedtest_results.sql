@@ -0,0 +1,15 @@
+-- History of completed WAN speed tests.
+CREATE TABLE IF NOT EXISTS speedtest_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    download_mbps REAL NOT NULL,
+    upload_mbps REAL NOT NULL,
+    ping_ms REAL NOT NULL,
+    jitter_ms REAL NOT NULL,
+    packet_loss REAL NOT NULL,
+    isp TEXT NOT NULL,
+    server TEXT NOT NULL,
+    result_url TEXT,
+    tested_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_speedtest_results_tested_at ON speedtest_results(tested_at);
@@ -0,0 +1,10 @@
+-- Who made each change, plus request details for entries written by the
+-- request audit middleware. `created_at` is the time the change occurred.
+ALTER TABLE audit_log ADD COLUMN actor TEXT;
+ALTER TABLE audit_log ADD COLUMN method TEXT;
+ALTER TABLE audit_log ADD COLUMN path TEXT;
+ALTER TABLE audit_log ADD COLUMN status_code INTEGER;
+ALTER TABLE audit_log ADD COLUMN request_body_summary TEXT;
+ALTER TABLE audit_log ADD COLUMN ip_address TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log (actor);
@@ -0,0 +1,29 @@
+-- Trigram index over device names, hostnames and vendors for fuzzy search,
+-- kept in sync by the triggers below.
+CREATE VIRTUAL TABLE IF NOT EXISTS devices_fts USING fts5(
+    name,
+    hostname,
+    vendor,
+    content = 'devices',
+    content_rowid = 'rowid',
+    tokenize = 'trigram'
+);
+
+INSERT INTO devices_fts (devices_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS devices_fts_insert AFTER INSERT ON devices BEGIN
+    INSERT INTO devices_fts (rowid, name, hostname, vendor)
+    VALUES (new.rowid, new.name, new.hostname, new.vendor);
+END;
+
+CREATE TRIGGER IF NOT EXISTS devices_fts_delete AFTER DELETE ON devices BEGIN
+    INSERT INTO devices_fts (devices_fts, rowid, name, hostname, vendor)
+    VALUES ('delete', old.rowid, old.name, old.hostname, old.vendor);
+END;
+
+CREATE TRIGGER IF NOT EXISTS devices_fts_update AFTER UPDATE OF name, hostname, vendor ON devices BEGIN
+    INSERT INTO devices_fts (devices_fts, rowid, name, hostname, vendor)
+    VALUES ('delete', old.rowid, old.name, old.hostname, old.vendor);
+    INSERT INTO devices_fts (rowid, name, hostname, vendor)
+    VALUES (new.rowid, new.name, new.hostname, new.vendor);
+END;
@@ -0,0 +1,8 @@
+-- Recurring nmap port scans per device, run by the port scan scheduler.
+CREATE TABLE IF NOT EXISTS device_scan_schedules (
+    device_id      TEXT PRIMARY KEY REFERENCES devices(id) ON DELETE CASCADE,
+    interval_hours INTEGER NOT NULL,
+    last_scan_at   TEXT,
+    enabled        INTEGER NOT NULL DEFAULT 1,
+    created_at     TEXT NOT NULL DEFAULT (datetime('now'))
+);
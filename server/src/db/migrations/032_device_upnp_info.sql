@@ -0,0 +1,3 @@
+-- UPnP device description (friendly name, manufacturer, model) learned from
+-- SSDP announcements, stored as a JSON object.
+ALTER TABLE devices ADD COLUMN upnp_info TEXT;
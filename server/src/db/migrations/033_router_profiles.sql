@@ -0,0 +1,13 @@
+-- VyOS routers the server can switch between. At most one profile is active;
+-- while none is, the vyos_url / vyos_api_key settings are used.
+CREATE TABLE IF NOT EXISTS router_profiles (
+    id                TEXT PRIMARY KEY,
+    name              TEXT NOT NULL UNIQUE,
+    url               TEXT NOT NULL,
+    api_key_encrypted TEXT NOT NULL,
+    is_active         INTEGER NOT NULL DEFAULT 0,
+    created_at        TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_router_profiles_active
+    ON router_profiles(is_active) WHERE is_active = 1;
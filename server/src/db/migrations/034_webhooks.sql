@@ -0,0 +1,12 @@
+-- Named webhook endpoints, each receiving the event types listed in
+-- `events` (comma-separated, or '*' for all). Replaces the single
+-- `webhook_url` setting, which is still honoured for existing installs.
+CREATE TABLE IF NOT EXISTS webhooks (
+    id         TEXT PRIMARY KEY,
+    name       TEXT NOT NULL UNIQUE,
+    url        TEXT NOT NULL,
+    events     TEXT NOT NULL,
+    enabled    INTEGER NOT NULL DEFAULT 1,
+    secret     TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
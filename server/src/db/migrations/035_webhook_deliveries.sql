@@ -0,0 +1,20 @@
+-- Failed webhook deliveries awaiting retry. A row is written when a POST
+-- fails and is retried with exponential backoff until it is delivered or
+-- marked failed.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id              TEXT PRIMARY KEY,
+    webhook_id      TEXT NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event_type      TEXT NOT NULL,
+    payload_json    TEXT NOT NULL,
+    attempt_count   INTEGER NOT NULL DEFAULT 1,
+    last_attempt_at TEXT NOT NULL DEFAULT (datetime('now')),
+    status          TEXT NOT NULL DEFAULT 'pending'
+                    CHECK (status IN ('pending', 'delivered', 'failed')),
+    response_code   INTEGER,
+    error           TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook
+    ON webhook_deliveries (webhook_id, last_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status
+    ON webhook_deliveries (status);
@@ -0,0 +1,14 @@
+-- Planned downtime: alerts for covered devices are not raised between
+-- start_at and end_at (RFC 3339, UTC). A NULL device_ids covers every device.
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    id         TEXT PRIMARY KEY,
+    name       TEXT NOT NULL,
+    start_at   TEXT NOT NULL,
+    end_at     TEXT NOT NULL,
+    device_ids TEXT,
+    enabled    INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_period
+    ON maintenance_windows (start_at, end_at);
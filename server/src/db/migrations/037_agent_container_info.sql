@@ -0,0 +1,6 @@
+-- Container / Kubernetes context reported by agents running in a container.
+ALTER TABLE agent_reports ADD COLUMN is_container INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE agent_reports ADD COLUMN container_runtime TEXT;
+ALTER TABLE agent_reports ADD COLUMN pod_name TEXT;
+ALTER TABLE agent_reports ADD COLUMN k8s_namespace TEXT;
+ALTER TABLE agent_reports ADD COLUMN node_name TEXT;
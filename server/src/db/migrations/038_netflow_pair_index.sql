@@ -0,0 +1,2 @@
+-- Conversation pairs group netflow_flows by (src_ip, dst_ip).
+CREATE INDEX IF NOT EXISTS idx_netflow_flows_pair ON netflow_flows(src_ip, dst_ip);
@@ -0,0 +1,13 @@
+-- Leaf TLS certificate seen on each HTTPS port of a device.
+CREATE TABLE IF NOT EXISTS tls_certificates (
+    device_id TEXT NOT NULL REFERENCES devices(id) ON DELETE CASCADE,
+    port INTEGER NOT NULL,
+    subject_cn TEXT,
+    issuer_cn TEXT,
+    san_dns_names TEXT NOT NULL DEFAULT '[]',  -- JSON array
+    not_before TEXT NOT NULL,
+    not_after TEXT NOT NULL,
+    is_self_signed INTEGER NOT NULL DEFAULT 0,
+    last_checked TEXT NOT NULL,
+    PRIMARY KEY (device_id, port)
+);
@@ -0,0 +1,4 @@
+-- Per-account TOTP secrets, sealed with the server key (see crypto.rs).
+-- The built-in admin keeps its secret in settings (admin_totp_secret).
+ALTER TABLE users ADD COLUMN totp_secret TEXT;
+ALTER TABLE users ADD COLUMN totp_pending_secret TEXT;
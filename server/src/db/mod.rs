@@ -37,6 +37,97 @@ const DEVICE_ENRICHMENT_MIGRATION: &str = include_str!("migrations/010_device_en
 /// Migration 011: VyOS config backups table.
 const CONFIG_BACKUPS_MIGRATION: &str = include_str!("migrations/011_config_backups.sql");
 
+/// Migration 012: firewall rule hit counter samples.
+const FIREWALL_RULE_STATS_MIGRATION: &str = include_str!("migrations/012_firewall_rule_stats.sql");
+
+/// Migration 013: device import history.
+const DEVICE_IMPORTS_MIGRATION: &str = include_str!("migrations/013_device_imports.sql");
+
+/// Migration 014: installed package lists on agent reports.
+const AGENT_PACKAGES_MIGRATION: &str = include_str!("migrations/014_agent_packages.sql");
+
+/// Migration 015: IP version on device addresses.
+const DEVICE_IP_VERSION_MIGRATION: &str = include_str!("migrations/015_device_ip_version.sql");
+
+/// Migration 016: per IP-pair NetFlow totals.
+const NETFLOW_FLOWS_MIGRATION: &str = include_str!("migrations/016_netflow_flows.sql");
+
+/// Migration 017: per-device SNMP settings.
+const DEVICE_SNMP_MIGRATION: &str = include_str!("migrations/017_device_snmp.sql");
+
+/// Migration 018: device OS hint and nmap OS matches.
+const DEVICE_OS_HINT_MIGRATION: &str = include_str!("migrations/018_device_os_hint.sql");
+
+/// Migration 019: service banners per port scan.
+const PORT_SCAN_BANNERS_MIGRATION: &str = include_str!("migrations/019_port_scan_banners.sql");
+
+/// Migration 020: custom alert trigger rules.
+const ALERT_RULES_MIGRATION: &str = include_str!("migrations/020_alert_rules.sql");
+
+/// Migration 021: alert deduplication counters.
+const ALERT_DEDUP_MIGRATION: &str = include_str!("migrations/021_alert_dedup.sql");
+
+/// Migration 022: device tags.
+const DEVICE_TAGS_MIGRATION: &str = include_str!("migrations/022_device_tags.sql");
+
+/// Migration 023: device notes with a full-text index.
+const DEVICE_NOTES_MIGRATION: &str = include_str!("migrations/023_device_notes.sql");
+
+/// Migration 024: device soft-delete flag.
+const DEVICE_SOFT_DELETE_MIGRATION: &str = include_str!("migrations/024_device_soft_delete.sql");
+
+/// Migration 025: index netflow_flows by recorded_at.
+const NETFLOW_RECORDED_AT_INDEX_MIGRATION: &str =
+    include_str!("migrations/025_netflow_recorded_at_index.sql");
+
+/// Migration 026: speed test result history.
+const SPEEDTEST_RESULTS_MIGRATION: &str = include_str!("migrations/026_speedtest_results.sql");
+
+/// Migration 027: API keys for headless integrations.
+const API_KEYS_MIGRATION: &str = include_str!("migrations/027_api_keys.sql");
+
+/// Migration 028: named user accounts with roles.
+const USERS_MIGRATION: &str = include_str!("migrations/028_users.sql");
+
+/// Migration 029: actor and request details on audit log entries.
+const AUDIT_LOG_REQUEST_FIELDS_MIGRATION: &str =
+    include_str!("migrations/029_audit_log_request_fields.sql");
+
+/// Migration 030: trigram full-text index over device names for fuzzy search.
+const DEVICES_FTS_MIGRATION: &str = include_str!("migrations/030_devices_fts.sql");
+
+/// Migration 031: recurring per-device port scan schedules.
+const DEVICE_SCAN_SCHEDULES_MIGRATION: &str =
+    include_str!("migrations/031_device_scan_schedules.sql");
+
+/// Migration 032: UPnP device descriptions from SSDP discovery.
+const DEVICE_UPNP_INFO_MIGRATION: &str = include_str!("migrations/032_device_upnp_info.sql");
+
+/// Migration 033: VyOS router profiles.
+const ROUTER_PROFILES_MIGRATION: &str = include_str!("migrations/033_router_profiles.sql");
+
+/// Migration 034: named webhook endpoints with event filters.
+const WEBHOOKS_MIGRATION: &str = include_str!("migrations/034_webhooks.sql");
+
+/// Migration 035: webhook delivery retry log.
+const WEBHOOK_DELIVERIES_MIGRATION: &str = include_str!("migrations/035_webhook_deliveries.sql");
+
+/// Migration 036: maintenance windows that suppress alerts.
+const MAINTENANCE_WINDOWS_MIGRATION: &str = include_str!("migrations/036_maintenance_windows.sql");
+
+/// Migration 037: container / Kubernetes context on agent reports.
+const AGENT_CONTAINER_INFO_MIGRATION: &str =
+    include_str!("migrations/037_agent_container_info.sql");
+
+/// Migration 038: (src_ip, dst_ip) index for NetFlow conversation pairs.
+const NETFLOW_PAIR_INDEX_MIGRATION: &str = include_str!("migrations/038_netflow_pair_index.sql");
+
+/// Migration 039: TLS certificates of scanned HTTPS services.
+const TLS_CERTIFICATES_MIGRATION: &str = include_str!("migrations/039_tls_certificates.sql");
+
+/// Migration 040: per-account TOTP secrets.
+const USER_TOTP_MIGRATION: &str = include_str!("migrations/040_user_totp.sql");
+
 /// Initialize the SQLite database pool and run migrations.
 pub async fn init(database_url: &str) -> Result<SqlitePool> {
     let options = SqliteConnectOptions::from_str(database_url)?
@@ -268,6 +359,487 @@ pub(crate) async fn run_migrations(pool: &SqlitePool) -> Result<()> {
         info!("Applied migration 011_config_backups.sql");
     }
 
+    // Migration 012: firewall rule statistics.
+    let applied_12: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 12")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_12 {
+        sqlx::raw_sql(FIREWALL_RULE_STATS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (12)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 012_firewall_rule_stats.sql");
+    }
+
+    // Migration 013: device import history.
+    let applied_13: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 13")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_13 {
+        sqlx::raw_sql(DEVICE_IMPORTS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (13)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 013_device_imports.sql");
+    }
+
+    // Migration 014: agent installed packages.
+    let applied_14: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 14")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_14 {
+        sqlx::raw_sql(AGENT_PACKAGES_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (14)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 014_agent_packages.sql");
+    }
+
+    // Migration 015: device_ips.ip_version.
+    let applied_15: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 15")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_15 {
+        sqlx::raw_sql(DEVICE_IP_VERSION_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (15)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 015_device_ip_version.sql");
+    }
+
+    // Migration 016: netflow_flows.
+    let applied_16: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 16")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_16 {
+        sqlx::raw_sql(NETFLOW_FLOWS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (16)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 016_netflow_flows.sql");
+    }
+
+    // Migration 017: device_snmp.
+    let applied_17: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 17")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_17 {
+        sqlx::raw_sql(DEVICE_SNMP_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (17)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 017_device_snmp.sql");
+    }
+
+    // Migration 018: devices.os_hint, port_scans.os_matches_json.
+    let applied_18: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 18")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_18 {
+        sqlx::raw_sql(DEVICE_OS_HINT_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (18)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 018_device_os_hint.sql");
+    }
+
+    // Migration 019: port_scan_banners.
+    let applied_19: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 19")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_19 {
+        sqlx::raw_sql(PORT_SCAN_BANNERS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (19)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 019_port_scan_banners.sql");
+    }
+
+    // Migration 020: alert_rules.
+    let applied_20: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 20")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_20 {
+        sqlx::raw_sql(ALERT_RULES_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (20)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 020_alert_rules.sql");
+    }
+
+    // Migration 021: alerts.count / alerts.updated_at.
+    let applied_21: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 21")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_21 {
+        sqlx::raw_sql(ALERT_DEDUP_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (21)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 021_alert_dedup.sql");
+    }
+
+    let applied_22: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 22")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_22 {
+        sqlx::raw_sql(DEVICE_TAGS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (22)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 022_device_tags.sql");
+    }
+
+    let applied_23: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 23")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_23 {
+        sqlx::raw_sql(DEVICE_NOTES_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (23)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 023_device_notes.sql");
+    }
+
+    let applied_24: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 24")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_24 {
+        sqlx::raw_sql(DEVICE_SOFT_DELETE_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (24)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 024_device_soft_delete.sql");
+    }
+
+    let applied_25: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 25")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_25 {
+        sqlx::raw_sql(NETFLOW_RECORDED_AT_INDEX_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (25)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 025_netflow_recorded_at_index.sql");
+    }
+
+    let applied_26: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 26")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_26 {
+        sqlx::raw_sql(SPEEDTEST_RESULTS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (26)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 026_speedtest_results.sql");
+    }
+
+    let applied_27: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 27")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_27 {
+        sqlx::raw_sql(API_KEYS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (27)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 027_api_keys.sql");
+    }
+
+    let applied_28: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 28")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_28 {
+        sqlx::raw_sql(USERS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (28)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 028_users.sql");
+    }
+
+    let applied_29: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 29")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_29 {
+        sqlx::raw_sql(AUDIT_LOG_REQUEST_FIELDS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (29)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 029_audit_log_request_fields.sql");
+    }
+
+    let applied_30: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 30")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_30 {
+        sqlx::raw_sql(DEVICES_FTS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (30)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 030_devices_fts.sql");
+    }
+
+    let applied_31: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 31")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_31 {
+        sqlx::raw_sql(DEVICE_SCAN_SCHEDULES_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (31)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 031_device_scan_schedules.sql");
+    }
+
+    let applied_32: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 32")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_32 {
+        sqlx::raw_sql(DEVICE_UPNP_INFO_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (32)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 032_device_upnp_info.sql");
+    }
+
+    let applied_33: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 33")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_33 {
+        sqlx::raw_sql(ROUTER_PROFILES_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (33)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 033_router_profiles.sql");
+    }
+
+    let applied_34: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 34")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_34 {
+        sqlx::raw_sql(WEBHOOKS_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (34)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 034_webhooks.sql");
+    }
+
+    let applied_35: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 35")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_35 {
+        sqlx::raw_sql(WEBHOOK_DELIVERIES_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (35)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 035_webhook_deliveries.sql");
+    }
+
+    let applied_36: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 36")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_36 {
+        sqlx::raw_sql(MAINTENANCE_WINDOWS_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (36)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 036_maintenance_windows.sql");
+    }
+
+    let applied_37: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 37")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_37 {
+        sqlx::raw_sql(AGENT_CONTAINER_INFO_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (37)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 037_agent_container_info.sql");
+    }
+
+    let applied_38: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 38")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_38 {
+        sqlx::raw_sql(NETFLOW_PAIR_INDEX_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (38)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 038_netflow_pair_index.sql");
+    }
+
+    let applied_39: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 39")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_39 {
+        sqlx::raw_sql(TLS_CERTIFICATES_MIGRATION)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (39)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 039_tls_certificates.sql");
+    }
+
+    let applied_40: bool = sqlx::query("SELECT 1 FROM _migrations WHERE version = 40")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+
+    if !applied_40 {
+        sqlx::raw_sql(USER_TOTP_MIGRATION).execute(pool).await?;
+
+        sqlx::query("INSERT INTO _migrations (version) VALUES (40)")
+            .execute(pool)
+            .await?;
+
+        info!("Applied migration 040_user_totp.sql");
+    }
+
     // Purge expired sessions on startup.
     let deleted = sqlx::query("DELETE FROM sessions WHERE expires_at <= datetime('now')")
         .execute(pool)
@@ -313,6 +885,22 @@ mod tests {
             "topology_positions",
             "audit_log",
             "vyos_config_backups",
+            "firewall_rule_stats",
+            "device_imports",
+            "netflow_flows",
+            "device_snmp",
+            "port_scan_banners",
+            "alert_rules",
+            "device_tags",
+            "device_notes",
+            "speedtest_results",
+            "api_keys",
+            "users",
+            "devices_fts",
+            "device_scan_schedules",
+            "router_profiles",
+            "webhooks",
+            "webhook_deliveries",
         ];
 
         for table in &expected_tables {
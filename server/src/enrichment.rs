@@ -19,6 +19,8 @@ pub struct EnrichmentResult {
     pub device_type: Option<String>,
     pub device_model: Option<String>,
     pub device_brand: Option<String>,
+    /// Coarse OS/platform guess from mDNS service types (e.g. "Apple device").
+    pub os_hint: Option<String>,
     /// Which source provided the primary identification.
     pub source: String,
 }
@@ -54,6 +56,7 @@ pub fn enrich(input: &EnrichmentInput) -> EnrichmentResult {
     // Layer 3: mDNS service analysis
     if let Some(ref services) = input.mdns_services {
         apply_mdns_hints(services, &mut result);
+        result.os_hint = os_hint_from_mdns(services).map(str::to_string);
     }
 
     // Layer 4: Hostname pattern matching (more specific)
@@ -85,9 +88,24 @@ pub fn enrich(input: &EnrichmentInput) -> EnrichmentResult {
     result
 }
 
+/// Map advertised mDNS service types to a coarse OS hint.
+pub fn os_hint_from_mdns(services: &str) -> Option<&'static str> {
+    let lower = services.to_lowercase();
+    if lower.contains("_airplay._tcp") {
+        Some("Apple device")
+    } else if lower.contains("_googlecast._tcp") {
+        Some("Chromecast")
+    } else if lower.contains("_workstation._tcp") {
+        Some("Linux workstation")
+    } else {
+        None
+    }
+}
+
 /// Persist enrichment results to the database for a device.
 ///
 /// Only updates fields that are non-None and respects `enrichment_corrected` flag.
+/// `os_hint` is only filled when empty so nmap OS matches are not overwritten.
 pub async fn persist_enrichment(
     db: &SqlitePool,
     device_id: &str,
@@ -117,6 +135,7 @@ pub async fn persist_enrichment(
             device_type = COALESCE(?, device_type),
             device_model = COALESCE(?, device_model),
             device_brand = COALESCE(?, device_brand),
+            os_hint = COALESCE(os_hint, ?),
             enrichment_source = COALESCE(?, enrichment_source),
             updated_at = datetime('now')
         WHERE id = ?"#,
@@ -126,6 +145,7 @@ pub async fn persist_enrichment(
     .bind(&result.device_type)
     .bind(&result.device_model)
     .bind(&result.device_brand)
+    .bind(&result.os_hint)
     .bind(if result.source.is_empty() {
         None
     } else {
@@ -274,12 +294,10 @@ fn apply_ttl_hints(ttl: u8, result: &mut EnrichmentResult) {
 
     match ttl {
         // TTL around 64 (within 1 hop)
-        57..=64 => {
-            // Could be Linux, macOS, iOS, Android — too ambiguous for os_family alone
-            // but we can note it's a Unix-like system
-            if result.source.is_empty() {
-                result.source = "ttl".to_string();
-            }
+        // Could be Linux, macOS, iOS, Android — too ambiguous for os_family alone
+        // but we can note it's a Unix-like system
+        57..=64 if result.source.is_empty() => {
+            result.source = "ttl".to_string();
         }
         // TTL around 128 (within 1 hop)
         121..=128 => {
@@ -689,6 +707,7 @@ pub async fn enrich_device(
         || result.device_type.is_some()
         || result.device_model.is_some()
         || result.device_brand.is_some()
+        || result.os_hint.is_some()
     {
         if let Err(e) = persist_enrichment(db, device_id, &result).await {
             warn!(device_id, error = %e, "Failed to persist enrichment");
@@ -1035,4 +1054,53 @@ mod tests {
             .unwrap();
         assert_eq!(os.as_deref(), Some("Windows"));
     }
+
+    #[test]
+    fn test_os_hint_from_mdns() {
+        assert_eq!(
+            os_hint_from_mdns("_raop._tcp,_airplay._tcp"),
+            Some("Apple device")
+        );
+        assert_eq!(os_hint_from_mdns("_googlecast._tcp"), Some("Chromecast"));
+        assert_eq!(
+            os_hint_from_mdns("_workstation._tcp,_ssh._tcp"),
+            Some("Linux workstation")
+        );
+        assert_eq!(os_hint_from_mdns("_ipp._tcp"), None);
+    }
+
+    #[tokio::test]
+    async fn test_persist_enrichment_keeps_existing_os_hint() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at, os_hint) VALUES (?, 'aa:bb:cc:dd:ee:fe', ?, ?, 'Linux 5.X')",
+        )
+        .bind(&id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let result = enrich(&EnrichmentInput {
+            mdns_services: Some("_workstation._tcp".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(result.os_hint.as_deref(), Some("Linux workstation"));
+        persist_enrichment(&pool, &id, &result).await.unwrap();
+
+        let hint: Option<String> = sqlx::query_scalar("SELECT os_hint FROM devices WHERE id = ?")
+            .bind(&id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            hint.as_deref(),
+            Some("Linux 5.X"),
+            "nmap hint must not be replaced"
+        );
+    }
 }
@@ -0,0 +1,196 @@
+//! LDAP / Active Directory login.
+//!
+//! Settings come from the `settings` table (`ldap_url`, `ldap_bind_dn`,
+//! `ldap_bind_password`, `ldap_user_base_dn`, `ldap_user_filter`), falling
+//! back to the same keys in the config file's `[auth]` section. The bind
+//! password is stored encrypted. A login looks
+//! the user up with the bind DN (or anonymously), then binds as the entry
+//! found with the supplied password.
+
+use std::time::Duration;
+
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use crate::config::AuthConfig;
+use crate::crypto;
+
+/// Filter used when `ldap_user_filter` is unset; `{username}` is replaced by
+/// the escaped login name. Active Directory typically wants
+/// `(sAMAccountName={username})`.
+pub const DEFAULT_USER_FILTER: &str = "(uid={username})";
+
+/// Connect budget for the directory server.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// LDAP result code for a rejected bind.
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// Directory connection settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdapSettings {
+    pub url: String,
+    pub bind_dn: Option<String>,
+    pub bind_password: Option<String>,
+    pub user_base_dn: String,
+    pub user_filter: String,
+}
+
+/// Read the LDAP settings. Returns `None` unless a URL and user base DN are set.
+pub async fn load_settings(db: &SqlitePool, auth: &AuthConfig) -> Option<LdapSettings> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'ldap\\_%' ESCAPE '\\'")
+            .fetch_all(db)
+            .await
+            .unwrap_or_default();
+    let get = |key: &str, fallback: &Option<String>| {
+        rows.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim().to_string())
+            .or_else(|| fallback.clone())
+            .filter(|v| !v.is_empty())
+    };
+
+    Some(LdapSettings {
+        url: get("ldap_url", &auth.ldap_url)?,
+        bind_dn: get("ldap_bind_dn", &auth.ldap_bind_dn),
+        bind_password: match rows.iter().find(|(k, _)| k == "ldap_bind_password") {
+            Some((key, sealed)) if !sealed.is_empty() => crypto::unseal_setting(key, sealed),
+            Some(_) => None,
+            None => auth.ldap_bind_password.clone().filter(|v| !v.is_empty()),
+        },
+        user_base_dn: get("ldap_user_base_dn", &auth.ldap_user_base_dn)?,
+        user_filter: get("ldap_user_filter", &auth.ldap_user_filter)
+            .unwrap_or_else(|| DEFAULT_USER_FILTER.to_string()),
+    })
+}
+
+/// The search filter for `username`, escaped so it cannot alter the filter.
+pub fn user_filter(template: &str, username: &str) -> String {
+    template.replace("{username}", &ldap_escape(username))
+}
+
+/// Connect and bind with the configured bind DN, or stay anonymous.
+async fn connect(settings: &LdapSettings) -> Result<Ldap, String> {
+    let conn_settings = LdapConnSettings::new().set_conn_timeout(CONNECT_TIMEOUT);
+    let (conn, mut ldap) = LdapConnAsync::with_settings(conn_settings, &settings.url)
+        .await
+        .map_err(|e| format!("Cannot connect to {}: {e}", settings.url))?;
+    ldap3::drive!(conn);
+
+    if let Some(ref bind_dn) = settings.bind_dn {
+        ldap.simple_bind(bind_dn, settings.bind_password.as_deref().unwrap_or(""))
+            .await
+            .and_then(|r| r.success())
+            .map_err(|e| format!("Bind as {bind_dn} failed: {e}"))?;
+    }
+    Ok(ldap)
+}
+
+/// Check `username`/`password` against the directory.
+///
+/// `Ok(false)` means the user was not found (or not uniquely) or the password
+/// was rejected; `Err` means the directory could not be queried.
+pub async fn authenticate(
+    settings: &LdapSettings,
+    username: &str,
+    password: &str,
+) -> Result<bool, String> {
+    // An empty password would be an unauthenticated bind, which servers accept.
+    if password.is_empty() {
+        return Ok(false);
+    }
+    let mut ldap = connect(settings).await?;
+    let filter = user_filter(&settings.user_filter, username);
+    let (entries, _) = ldap
+        .search(&settings.user_base_dn, Scope::Subtree, &filter, vec!["1.1"])
+        .await
+        .and_then(|r| r.success())
+        .map_err(|e| format!("User search failed: {e}"))?;
+    if entries.len() != 1 {
+        if entries.len() > 1 {
+            warn!(
+                username,
+                count = entries.len(),
+                "LDAP filter matched several entries"
+            );
+        }
+        let _ = ldap.unbind().await;
+        return Ok(false);
+    }
+    let user_dn = SearchEntry::construct(entries.into_iter().next().unwrap()).dn;
+
+    let result = ldap
+        .simple_bind(&user_dn, password)
+        .await
+        .map_err(|e| format!("Bind as {user_dn} failed: {e}"))?;
+    let _ = ldap.unbind().await;
+    match result.rc {
+        0 => Ok(true),
+        INVALID_CREDENTIALS => Ok(false),
+        _ => Err(format!("Bind as {user_dn} failed: {result}")),
+    }
+}
+
+/// Count the user entries under the base DN that the filter matches.
+pub async fn count_users(settings: &LdapSettings) -> Result<usize, String> {
+    let mut ldap = connect(settings).await?;
+    let filter = settings.user_filter.replace("{username}", "*");
+    let (entries, _) = ldap
+        .search(&settings.user_base_dn, Scope::Subtree, &filter, vec!["1.1"])
+        .await
+        .and_then(|r| r.success())
+        .map_err(|e| format!("User search failed: {e}"))?;
+    let _ = ldap.unbind().await;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_filter_escapes_username() {
+        assert_eq!(user_filter(DEFAULT_USER_FILTER, "alice"), "(uid=alice)");
+        assert_eq!(
+            user_filter("(sAMAccountName={username})", "*)(uid=*"),
+            "(sAMAccountName=\\2a\\29\\28uid=\\2a)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_settings_prefers_database() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let mut auth = AuthConfig::default();
+        assert!(load_settings(&pool, &auth).await.is_none());
+
+        auth.ldap_url = Some("ldap://config.example".to_string());
+        auth.ldap_user_base_dn = Some("ou=people,dc=example,dc=com".to_string());
+        let settings = load_settings(&pool, &auth).await.unwrap();
+        assert_eq!(settings.url, "ldap://config.example");
+        assert_eq!(settings.user_filter, DEFAULT_USER_FILTER);
+
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('ldap_url', 'ldaps://db.example')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let settings = load_settings(&pool, &auth).await.unwrap();
+        assert_eq!(settings.url, "ldaps://db.example");
+        assert_eq!(settings.user_base_dn, "ou=people,dc=example,dc=com");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_an_error() {
+        let settings = LdapSettings {
+            url: "ldap://127.0.0.1:1".to_string(),
+            bind_dn: None,
+            bind_password: None,
+            user_base_dn: "dc=example,dc=com".to_string(),
+            user_filter: DEFAULT_USER_FILTER.to_string(),
+        };
+        assert!(authenticate(&settings, "alice", "secret").await.is_err());
+        assert_eq!(authenticate(&settings, "alice", "").await, Ok(false));
+        assert!(count_users(&settings).await.is_err());
+    }
+}
@@ -1,13 +1,21 @@
 pub mod api;
 pub mod config;
+pub mod crypto;
 pub mod db;
 pub mod enrichment;
+pub mod ldap;
 pub mod mdns;
 pub mod netflow;
+pub mod notification;
 pub mod oui;
 pub mod retention;
 pub mod scanner;
+pub mod security;
+pub mod snmp;
+pub mod ssdp;
 pub mod static_files;
+pub mod tls;
 pub mod vyos;
 pub mod webhook;
+pub mod whois;
 pub mod ws;
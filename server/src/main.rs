@@ -1,8 +1,12 @@
 use anyhow::Result;
 use clap::Parser;
-use panoptikon_server::{api, config, db, mdns, netflow, retention, scanner};
+use panoptikon_server::{
+    api, config, crypto, db, mdns, netflow, notification, oui, retention, scanner, ssdp, tls, vyos,
+    webhook,
+};
 use std::net::SocketAddr;
-use tracing::info;
+use std::path::PathBuf;
+use tracing::{info, warn};
 
 /// Panoptikon — VyOS router management & network monitoring server.
 #[derive(Parser, Debug)]
@@ -19,6 +23,18 @@ struct Cli {
     /// Path to a TOML configuration file (optional).
     #[arg(short, long)]
     config: Option<String>,
+
+    /// TLS certificate (PEM chain or DER). With --tls-key, serves HTTPS on --listen.
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// TLS private key (PEM or DER).
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// With TLS enabled, also serve plain HTTP on this address.
+    #[arg(long, requires = "tls_cert")]
+    http_listen: Option<String>,
 }
 
 const BANNER: &str = r#"
@@ -55,17 +71,43 @@ async fn main() -> Result<()> {
         config::AppConfig::default()
     };
 
+    // Load the TLS certificate up front: a bad certificate must abort startup
+    // rather than fall back to plain HTTP.
+    let tls_config = match (&cli.tls_cert, &cli.tls_key) {
+        (Some(cert), Some(key)) => Some(tls::server_config(cert, key)?),
+        _ => None,
+    };
+
     // Initialize database and run migrations.
     let pool = db::init(&cli.db).await?;
     info!(path = %cli.db, "Database initialized");
 
+    // Forward audit log entries to syslog (no-op unless syslog_host is set).
+    notification::syslog::init(&app_config);
+
+    // Prefer a previously downloaded OUI database over the embedded one.
+    oui::init(&app_config);
+
+    // Load (or create) the key that encrypts router credentials in the database.
+    crypto::init(
+        crypto::key_path(&app_config, &cli.db).as_deref(),
+        crypto::secret(&app_config).as_deref(),
+    )?;
+    crypto::seal_plaintext_settings(&pool).await?;
+
     // Build shared application state (contains WsHub, session store, etc.).
-    let state = api::AppState::new(pool, app_config.clone());
+    let state = api::AppState {
+        config_path: cli.config.clone(),
+        ..api::AppState::new(pool, app_config.clone())
+    };
+    *state.active_profile_id.write().await =
+        api::router_profiles::load_active_profile_id(&state.db).await?;
 
     // Start periodic maintenance task (every hour): purge expired sessions + stale rate-limit entries.
     {
         let cleanup_pool = state.db.clone();
         let rate_limiter = state.rate_limiter.clone();
+        let endpoint_limiter = state.endpoint_limiter.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
             interval.tick().await; // skip the immediate first tick
@@ -86,6 +128,7 @@ async fn main() -> Result<()> {
                     }
                 }
                 rate_limiter.cleanup_stale();
+                endpoint_limiter.cleanup_stale();
             }
         });
     }
@@ -93,11 +136,57 @@ async fn main() -> Result<()> {
     // Start data retention background task (hourly cleanup + weekly VACUUM).
     retention::start_retention_task(state.db.clone(), app_config.retention.clone());
 
-    // Start the periodic ARP scanner in the background.
+    // Retry failed webhook deliveries with exponential backoff.
+    webhook::start_retry_task(state.db.clone());
+
+    // Start the daily VyOS config archive scheduler (no-op until enabled in settings).
+    vyos::config_archive::start_config_archive_task(
+        state.db.clone(),
+        state.config.clone(),
+        state.active_profile_id.clone(),
+    );
+
+    // Start the hourly DHCP lease expiry check (no-op until VyOS is configured).
+    vyos::dhcp_expiry::start_dhcp_expiry_task(
+        state.db.clone(),
+        state.config.clone(),
+        state.active_profile_id.clone(),
+        state.ws_hub.clone(),
+        state.severity_overrides.clone(),
+    );
+
+    // Sample firewall rule counters for the hit top-N endpoint (no-op until VyOS is configured).
+    vyos::firewall_stats::start_firewall_stats_task(
+        state.db.clone(),
+        state.config.clone(),
+        state.active_profile_id.clone(),
+    );
+
+    // Start scheduled WAN speed tests (no-op unless speedtest_interval_hours is set).
+    vyos::speedtest_schedule::start_speedtest_task(
+        state.db.clone(),
+        state.last_speedtest.clone(),
+        state.ws_hub.clone(),
+        &app_config,
+    );
+
+    // Start recurring per-device port scans (no-op until a schedule is set).
+    scanner::port_schedule::start_port_scan_schedule_task(
+        state.db.clone(),
+        state.ws_hub.clone(),
+        state.severity_overrides.clone(),
+    );
+
+    // Start the periodic ARP scanner in the background; config reloads restart its schedule.
+    let (scanner_config_tx, scanner_config_rx) =
+        tokio::sync::watch::channel(app_config.scanner.clone());
     scanner::start_scanner_task(
         state.db.clone(),
-        app_config.scanner.clone(),
+        scanner_config_rx,
         state.ws_hub.clone(),
+        state.severity_overrides.clone(),
+        state.telegram_limiter.clone(),
+        state.metrics.clone(),
     );
 
     // Start the passive mDNS/Bonjour discovery if enabled.
@@ -112,27 +201,74 @@ async fn main() -> Result<()> {
         info!("mDNS discovery disabled (set mdns_enabled = true in [scanner])");
     }
 
+    // Start the passive SSDP/UPnP listener if enabled.
+    if app_config.scanner.ssdp_enabled {
+        tokio::spawn(ssdp::start_ssdp_listener(state.db.clone()));
+    }
+
     // Start the NetFlow v5 UDP collector if enabled.
     if app_config.scanner.netflow_enabled {
         let port = app_config.scanner.netflow_port;
         info!(port, "Starting NetFlow v5 collector");
-        netflow::start_collector(state.db.clone(), port);
+        netflow::start_collector(
+            state.db.clone(),
+            port,
+            netflow::SpikeMonitor {
+                stats: state.traffic_stats.clone(),
+                config: state.config.clone(),
+                ws_hub: state.ws_hub.clone(),
+                severities: state.severity_overrides.clone(),
+            },
+        );
     } else {
         info!("NetFlow collector disabled (set netflow_enabled = true in [scanner])");
     }
 
+    // Re-read the config file on SIGHUP without dropping connections.
+    #[cfg(unix)]
+    if let Some(path) = state.config_path.clone() {
+        let shared_config = state.config.clone();
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                info!(path = %path, "SIGHUP received, reloading configuration");
+                if let Err(e) = config::reload(&shared_config, &path, &scanner_config_tx) {
+                    warn!("Config reload failed, keeping the current configuration: {e:#}");
+                }
+            }
+        });
+    }
+
     // Build the application router.
-    let app = api::router(state);
+    let app = api::router(state).into_make_service_with_connect_info::<SocketAddr>();
 
     // Start listening.
-    let listener = tokio::net::TcpListener::bind(&cli.listen).await?;
-    info!(addr = %cli.listen, "Listening");
-
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
-    )
-    .await?;
+    let Some(tls_config) = tls_config else {
+        let listener = tokio::net::TcpListener::bind(&cli.listen).await?;
+        info!(addr = %cli.listen, "Listening");
+        axum::serve(listener, app).await?;
+        return Ok(());
+    };
+
+    if let Some(ref http_listen) = cli.http_listen {
+        let listener = tokio::net::TcpListener::bind(http_listen).await?;
+        info!(addr = %http_listen, "Listening (HTTP)");
+        let http_app = app.clone();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, http_app).await {
+                tracing::error!("HTTP listener failed: {e}");
+            }
+        });
+    }
+
+    let addr: SocketAddr = cli
+        .listen
+        .parse()
+        .map_err(|e| anyhow::anyhow!("--listen must be an IP:port when TLS is enabled ({e})"))?;
+    info!(addr = %addr, "Listening (HTTPS)");
+    axum_server::bind_rustls(addr, tls_config)
+        .serve(app)
+        .await?;
 
     Ok(())
 }
@@ -18,7 +18,7 @@ const META_SERVICE: &str = "_services._dns-sd._udp.local.";
 /// Browses for all mDNS services, and for each resolved service:
 /// - Updates the device hostname (if not already set) by matching on IP
 /// - Stores discovered service types in the `mdns_services` column
-pub async fn start_mdns_discovery(pool: SqlitePool, _config: AppConfig) {
+pub async fn start_mdns_discovery(pool: SqlitePool, config: AppConfig) {
     info!("Starting mDNS/Bonjour passive discovery");
 
     let daemon = match ServiceDaemon::new() {
@@ -65,6 +65,13 @@ pub async fn start_mdns_discovery(pool: SqlitePool, _config: AppConfig) {
                         "mDNS resolved: hostname={hostname} type={service_type} IPs={addresses:?}"
                     );
 
+                    // Read per event so filter changes apply without a restart.
+                    let filter = load_service_filter(&pool, &config).await;
+                    if !filter.allows(&service_type) {
+                        debug!("mDNS: ignoring filtered service type {service_type}");
+                        continue;
+                    }
+
                     for addr in addresses {
                         let ip_str = addr.to_ip_addr().to_string();
                         if let Err(e) =
@@ -115,6 +122,78 @@ fn extract_service_type(full_name: &str) -> String {
     }
 }
 
+/// Settings key overriding `scanner.mdns_ignore_service_types`.
+pub const IGNORE_SERVICE_TYPES_KEY: &str = "mdns_ignore_service_types";
+/// Settings key overriding `scanner.mdns_include_service_types`.
+pub const INCLUDE_SERVICE_TYPES_KEY: &str = "mdns_include_service_types";
+
+/// Normalize a service type for comparison and storage: lowercase, without
+/// the trailing dot or `.local` domain (`_sleep-proxy._udp`).
+pub fn normalize_service_type(service_type: &str) -> String {
+    service_type
+        .trim()
+        .trim_end_matches('.')
+        .trim_end_matches(".local")
+        .to_lowercase()
+}
+
+/// Parse a comma-separated list of service types, normalized and without blanks.
+pub fn parse_service_types(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(normalize_service_type)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Which mDNS service types are recorded.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceTypeFilter {
+    pub ignore: Vec<String>,
+    /// When non-empty, only these types are recorded.
+    pub include: Vec<String>,
+}
+
+impl ServiceTypeFilter {
+    pub fn allows(&self, service_type: &str) -> bool {
+        let service_type = normalize_service_type(service_type);
+        !self.ignore.contains(&service_type)
+            && (self.include.is_empty() || self.include.contains(&service_type))
+    }
+}
+
+/// Read a service type list from the settings table, falling back to
+/// `fallback` (from the config file) when the key is unset.
+async fn load_service_types(pool: &SqlitePool, key: &str, fallback: &[String]) -> Vec<String> {
+    match sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(value)) => parse_service_types(&value),
+        _ => parse_service_types(&fallback.join(",")),
+    }
+}
+
+/// Load the service type filter, preferring the settings table over the
+/// config file for each list.
+pub async fn load_service_filter(pool: &SqlitePool, config: &AppConfig) -> ServiceTypeFilter {
+    ServiceTypeFilter {
+        ignore: load_service_types(
+            pool,
+            IGNORE_SERVICE_TYPES_KEY,
+            &config.scanner.mdns_ignore_service_types,
+        )
+        .await,
+        include: load_service_types(
+            pool,
+            INCLUDE_SERVICE_TYPES_KEY,
+            &config.scanner.mdns_include_service_types,
+        )
+        .await,
+    }
+}
+
 /// Upsert mDNS-discovered hostname and service type into the devices table.
 ///
 /// - Sets hostname only if the device currently has no hostname (doesn't overwrite).
@@ -350,6 +429,44 @@ mod tests {
         assert!(result.is_ok(), "Unknown IP should be silently ignored");
     }
 
+    #[test]
+    fn test_service_type_filter() {
+        let filter = ServiceTypeFilter {
+            ignore: parse_service_types("_sleep-proxy._udp, _companion-link._tcp.local."),
+            include: Vec::new(),
+        };
+        assert!(!filter.allows("_sleep-proxy._udp.local."));
+        assert!(!filter.allows("_Companion-Link._tcp.local."));
+        assert!(filter.allows("_airplay._tcp.local."));
+
+        let filter = ServiceTypeFilter {
+            ignore: vec!["_ipp._tcp".to_string()],
+            include: parse_service_types("_ipp._tcp,_printer._tcp"),
+        };
+        assert!(filter.allows("_printer._tcp.local."));
+        assert!(!filter.allows("_ipp._tcp.local."));
+        assert!(!filter.allows("_airplay._tcp.local."));
+    }
+
+    #[tokio::test]
+    async fn test_load_service_filter_prefers_settings() {
+        let pool = test_db().await;
+        let mut config = AppConfig::default();
+        config.scanner.mdns_ignore_service_types = vec!["_sleep-proxy._udp".to_string()];
+
+        let filter = load_service_filter(&pool, &config).await;
+        assert_eq!(filter.ignore, vec!["_sleep-proxy._udp"]);
+        assert!(filter.include.is_empty());
+
+        sqlx::query("INSERT INTO settings (key, value) VALUES (?, '_raop._tcp,_airplay._tcp')")
+            .bind(IGNORE_SERVICE_TYPES_KEY)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let filter = load_service_filter(&pool, &config).await;
+        assert_eq!(filter.ignore, vec!["_raop._tcp", "_airplay._tcp"]);
+    }
+
     #[test]
     fn test_extract_service_type() {
         assert_eq!(
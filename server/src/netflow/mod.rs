@@ -1,18 +1,49 @@
-//! NetFlow v5 UDP collector.
+//! NetFlow UDP collector.
 //!
-//! Listens on a configurable UDP port (default 9995), parses NetFlow v5 packets
-//! from pfSense/VyOS/softflowd, aggregates per-device bytes over 60-second
-//! windows, and batch-inserts into `traffic_samples` with `source = 'netflow'`.
+//! Listens on a configurable UDP port (default 9995), parses NetFlow v5, v9
+//! and IPFIX packets from pfSense/VyOS/softflowd/pmacct, aggregates per-device
+//! bytes over 60-second windows, and batch-inserts into `traffic_samples`
+//! with `source = 'netflow'`. Each flush is checked for traffic spikes
+//! (see [`spike`]).
+
+pub mod spike;
+pub mod v9;
 
 use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+use chrono::{DateTime, TimeZone, Utc};
 use sqlx::SqlitePool;
 use tokio::net::UdpSocket;
 use tracing::{debug, error, info, warn};
 
+use crate::api::alerts::SeverityOverrideCache;
+use crate::config::SharedConfig;
+use crate::ws::hub::WsHub;
+use v9::TemplateCache;
+
+// ---------------------------------------------------------------------------
+// Unified flow record
+// ---------------------------------------------------------------------------
+
+/// A flow decoded from any supported export format (v5, v9 or IPFIX).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowRecord {
+    pub src_ip: IpAddr,
+    pub dst_ip: IpAddr,
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub protocol: u8,
+    pub bytes: u64,
+    pub packets: u64,
+    /// Flow start, if the exporter reported it.
+    pub start: Option<DateTime<Utc>>,
+    /// Flow end, if the exporter reported it.
+    pub end: Option<DateTime<Utc>>,
+}
+
 // ---------------------------------------------------------------------------
 // NetFlow v5 wire format
 // ---------------------------------------------------------------------------
@@ -128,6 +159,58 @@ pub fn parse_v5_packet(buf: &[u8]) -> Option<(NetflowV5Header, Vec<NetflowV5Reco
     Some((header, records))
 }
 
+/// Convert a v5 uptime-relative timestamp to wall-clock time using the header.
+fn v5_timestamp(header: &NetflowV5Header, uptime_ms: u32) -> Option<DateTime<Utc>> {
+    let export_ms = i64::from(header.unix_secs) * 1000 + i64::from(header.unix_nsecs / 1_000_000);
+    let age_ms = i64::from(header.sys_uptime.checked_sub(uptime_ms)?);
+    Utc.timestamp_millis_opt(export_ms - age_ms).single()
+}
+
+impl FlowRecord {
+    /// Build a unified record from a v5 record and its packet header.
+    pub fn from_v5(header: &NetflowV5Header, rec: &NetflowV5Record) -> Self {
+        Self {
+            src_ip: IpAddr::V4(rec.src_addr),
+            dst_ip: IpAddr::V4(rec.dst_addr),
+            src_port: rec.src_port,
+            dst_port: rec.dst_port,
+            protocol: rec.protocol,
+            bytes: u64::from(rec.octets),
+            packets: u64::from(rec.packets),
+            start: v5_timestamp(header, rec.first),
+            end: v5_timestamp(header, rec.last),
+        }
+    }
+}
+
+/// Decode a datagram of any supported version into flow records.
+///
+/// v9 and IPFIX templates are stored in `templates` keyed by exporter.
+/// Returns `None` for unsupported or malformed packets.
+pub fn parse_packet(
+    buf: &[u8],
+    source: SocketAddr,
+    templates: &mut TemplateCache,
+) -> Option<Vec<FlowRecord>> {
+    if buf.len() < 2 {
+        return None;
+    }
+    match u16::from_be_bytes([buf[0], buf[1]]) {
+        5 => {
+            let (header, records) = parse_v5_packet(buf)?;
+            Some(
+                records
+                    .iter()
+                    .map(|rec| FlowRecord::from_v5(&header, rec))
+                    .collect(),
+            )
+        }
+        9 => v9::parse_v9_packet(buf, source, templates),
+        10 => v9::parse_ipfix_packet(buf, source, templates),
+        _ => None,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Aggregation
 // ---------------------------------------------------------------------------
@@ -153,6 +236,20 @@ pub fn aggregate_flows(
     entry.rx_bytes += rx_bytes;
 }
 
+/// Bytes and packets exchanged by one (src, dst) IP pair within a window.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FlowTotals {
+    pub bytes: u64,
+    pub packets: u64,
+}
+
+/// Accumulate a flow record into its (src, dst) IP pair totals.
+pub fn aggregate_pair(existing: &mut HashMap<(IpAddr, IpAddr), FlowTotals>, rec: &FlowRecord) {
+    let entry = existing.entry((rec.src_ip, rec.dst_ip)).or_default();
+    entry.bytes += rec.bytes;
+    entry.packets += rec.packets;
+}
+
 // ---------------------------------------------------------------------------
 // IP → device_id lookup
 // ---------------------------------------------------------------------------
@@ -175,10 +272,14 @@ async fn lookup_device_by_ip(pool: &SqlitePool, ip: &str) -> Option<String> {
 
 /// Insert aggregated traffic into traffic_samples.
 /// Converts total bytes in the window to bits-per-second (bps) assuming
-/// a 60-second aggregation window.
-async fn flush_traffic(pool: &SqlitePool, aggregated: HashMap<String, DeviceTraffic>) {
+/// a 60-second aggregation window. Returns the inserted `(device_id, rx + tx bps)`.
+async fn flush_traffic(
+    pool: &SqlitePool,
+    aggregated: HashMap<String, DeviceTraffic>,
+) -> Vec<(String, u64)> {
+    let mut inserted = Vec::new();
     if aggregated.is_empty() {
-        return;
+        return inserted;
     }
     let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
     for (device_id, traffic) in &aggregated {
@@ -202,12 +303,76 @@ async fn flush_traffic(pool: &SqlitePool, aggregated: HashMap<String, DeviceTraf
         .await
         {
             error!(device_id, "Failed to insert netflow traffic sample: {e}");
+            continue;
         }
+        inserted.push((device_id.clone(), (rx_bps + tx_bps) as u64));
     }
     info!(
         devices = aggregated.len(),
         "Flushed netflow traffic samples"
     );
+    inserted
+}
+
+/// Feed freshly flushed samples through spike detection and alert on spikes.
+async fn check_traffic_spikes(
+    pool: &SqlitePool,
+    samples: Vec<(String, u64)>,
+    monitor: &SpikeMonitor,
+) {
+    let (factor, min_samples) = {
+        let config = crate::config::read(&monitor.config);
+        (
+            config.alerts.traffic_spike_factor,
+            config.alerts.traffic_spike_min_samples,
+        )
+    };
+    let spikes: Vec<spike::TrafficSpike> = {
+        let mut stats = monitor.stats.lock().await;
+        samples
+            .iter()
+            .filter_map(|(device_id, bps)| stats.record(device_id, *bps, factor, min_samples))
+            .collect()
+    };
+    spike::create_spike_alerts(pool, &spikes, &monitor.ws_hub, &monitor.severities).await;
+}
+
+/// Shared state the collector needs to raise `traffic_spike` alerts.
+pub struct SpikeMonitor {
+    pub stats: spike::SharedTrafficStats,
+    pub config: SharedConfig,
+    pub ws_hub: Arc<WsHub>,
+    pub severities: SeverityOverrideCache,
+}
+
+/// Insert per IP-pair totals into netflow_flows in a single transaction.
+async fn flush_flows(pool: &SqlitePool, pairs: HashMap<(IpAddr, IpAddr), FlowTotals>) {
+    if pairs.is_empty() {
+        return;
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let result: Result<(), sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        for ((src, dst), totals) in &pairs {
+            sqlx::query(
+                r#"INSERT INTO netflow_flows (src_ip, dst_ip, bytes, packets, recorded_at)
+                   VALUES (?, ?, ?, ?, ?)"#,
+            )
+            .bind(src.to_string())
+            .bind(dst.to_string())
+            .bind(totals.bytes as i64)
+            .bind(totals.packets as i64)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+    .await;
+
+    if let Err(e) = result {
+        error!("Failed to insert netflow flow totals: {e}");
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -226,20 +391,23 @@ pub fn flows_received() -> u64 {
 // Main collector task
 // ---------------------------------------------------------------------------
 
-/// Start the NetFlow v5 UDP collector.
+/// Start the NetFlow UDP collector.
 ///
 /// Spawns a background tokio task that:
 /// 1. Binds to `0.0.0.0:<port>` UDP.
-/// 2. Receives datagrams, parses NetFlow v5 packets.
+/// 2. Receives datagrams, parses NetFlow v5/v9 and IPFIX packets.
 /// 3. Maps src/dst IP → device_id via device_ips table.
 /// 4. Aggregates bytes per device over 60-second windows.
-/// 5. Flushes aggregated data to traffic_samples.
-pub fn start_collector(pool: SqlitePool, port: u16) {
+/// 5. Flushes aggregated data to traffic_samples, and per IP-pair totals to
+///    netflow_flows.
+/// 6. Checks each device's new sample against its moving average and raises
+///    `traffic_spike` alerts.
+pub fn start_collector(pool: SqlitePool, port: u16, monitor: SpikeMonitor) {
     tokio::spawn(async move {
         let bind_addr: SocketAddr = ([0, 0, 0, 0], port).into();
         let socket = match UdpSocket::bind(bind_addr).await {
             Ok(s) => {
-                info!(port, "NetFlow v5 collector listening on UDP port");
+                info!(port, "NetFlow collector listening on UDP port");
                 Arc::new(s)
             }
             Err(e) => {
@@ -250,6 +418,8 @@ pub fn start_collector(pool: SqlitePool, port: u16) {
 
         let mut buf = [0u8; 65535];
         let mut aggregated: HashMap<String, DeviceTraffic> = HashMap::new();
+        let mut pairs: HashMap<(IpAddr, IpAddr), FlowTotals> = HashMap::new();
+        let mut templates = TemplateCache::new();
         let mut last_flush = tokio::time::Instant::now();
         let flush_interval = std::time::Duration::from_secs(60);
 
@@ -262,33 +432,30 @@ pub fn start_collector(pool: SqlitePool, port: u16) {
             .await;
 
             match recv_result {
-                Ok(Ok((len, _peer))) => {
-                    if let Some((header, records)) = parse_v5_packet(&buf[..len]) {
-                        debug!(
-                            count = header.count,
-                            seq = header.flow_sequence,
-                            "Received NetFlow v5 packet"
-                        );
+                Ok(Ok((len, peer))) => {
+                    if let Some(records) = parse_packet(&buf[..len], peer, &mut templates) {
+                        debug!(%peer, count = records.len(), "Received NetFlow packet");
 
                         FLOWS_RECEIVED.fetch_add(records.len() as u64, Ordering::Relaxed);
 
                         for rec in &records {
-                            let src_ip = rec.src_addr.to_string();
-                            let dst_ip = rec.dst_addr.to_string();
-                            let octets = rec.octets as u64;
+                            aggregate_pair(&mut pairs, rec);
+
+                            let src_ip = rec.src_ip.to_string();
+                            let dst_ip = rec.dst_ip.to_string();
 
                             // Source device: this device sent traffic (tx).
                             if let Some(device_id) = lookup_device_by_ip(&pool, &src_ip).await {
-                                aggregate_flows(&mut aggregated, &device_id, octets, 0);
+                                aggregate_flows(&mut aggregated, &device_id, rec.bytes, 0);
                             }
 
                             // Destination device: this device received traffic (rx).
                             if let Some(device_id) = lookup_device_by_ip(&pool, &dst_ip).await {
-                                aggregate_flows(&mut aggregated, &device_id, 0, octets);
+                                aggregate_flows(&mut aggregated, &device_id, 0, rec.bytes);
                             }
                         }
                     } else {
-                        debug!(len, "Received non-NetFlow-v5 packet, ignoring");
+                        debug!(len, "Received unsupported NetFlow packet, ignoring");
                     }
                 }
                 Ok(Err(e)) => {
@@ -302,7 +469,9 @@ pub fn start_collector(pool: SqlitePool, port: u16) {
             // Flush every 60 seconds.
             if last_flush.elapsed() >= flush_interval {
                 let to_flush = std::mem::take(&mut aggregated);
-                flush_traffic(&pool, to_flush).await;
+                let samples = flush_traffic(&pool, to_flush).await;
+                check_traffic_spikes(&pool, samples, &monitor).await;
+                flush_flows(&pool, std::mem::take(&mut pairs)).await;
                 last_flush = tokio::time::Instant::now();
             }
         }
@@ -455,6 +624,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_packet_v5_to_flow_record() {
+        let pkt = build_test_v5_packet(
+            Ipv4Addr::new(10, 10, 0, 100),
+            Ipv4Addr::new(8, 8, 8, 8),
+            1500,
+            10,
+        );
+        let peer: SocketAddr = "10.10.0.1:2055".parse().unwrap();
+        let records = parse_packet(&pkt, peer, &mut TemplateCache::new()).unwrap();
+        assert_eq!(records.len(), 1);
+        let rec = &records[0];
+        assert_eq!(rec.src_ip, IpAddr::V4(Ipv4Addr::new(10, 10, 0, 100)));
+        assert_eq!((rec.bytes, rec.packets, rec.dst_port), (1500, 10, 80));
+        // first = 100ms uptime, exported at uptime 1000ms → 900ms before unix_secs.
+        assert_eq!(
+            rec.start.unwrap().timestamp_millis(),
+            1_700_000_000_000 - 900
+        );
+    }
+
+    #[test]
+    fn test_parse_packet_unknown_version() {
+        let peer: SocketAddr = "10.10.0.1:2055".parse().unwrap();
+        assert!(parse_packet(&[0, 7, 0, 0], peer, &mut TemplateCache::new()).is_none());
+        assert!(parse_packet(&[0], peer, &mut TemplateCache::new()).is_none());
+    }
+
     #[test]
     fn test_aggregate_flows() {
         let mut map: HashMap<String, DeviceTraffic> = HashMap::new();
@@ -516,7 +713,8 @@ mod tests {
             },
         );
 
-        flush_traffic(&pool, aggregated).await;
+        let samples = flush_traffic(&pool, aggregated).await;
+        assert_eq!(samples, vec![(device_id.clone(), 24_000)]);
 
         // Verify the traffic sample was inserted.
         let row: Option<(i64, i64, String)> = sqlx::query_as(
@@ -565,4 +763,42 @@ mod tests {
         let not_found = lookup_device_by_ip(&pool, "10.10.0.99").await;
         assert!(not_found.is_none());
     }
+
+    #[tokio::test]
+    async fn test_flush_flows_aggregates_pairs() {
+        let pool = crate::db::init(":memory:").await.expect("DB init failed");
+        let rec = |dst: [u8; 4], bytes: u64| FlowRecord {
+            src_ip: IpAddr::V4(Ipv4Addr::new(10, 10, 0, 5)),
+            dst_ip: IpAddr::V4(Ipv4Addr::from(dst)),
+            src_port: 40000,
+            dst_port: 443,
+            protocol: 6,
+            bytes,
+            packets: 2,
+            start: None,
+            end: None,
+        };
+
+        let mut pairs = HashMap::new();
+        aggregate_pair(&mut pairs, &rec([1, 1, 1, 1], 100));
+        aggregate_pair(&mut pairs, &rec([1, 1, 1, 1], 50));
+        aggregate_pair(&mut pairs, &rec([8, 8, 8, 8], 10));
+        assert_eq!(pairs.len(), 2);
+
+        flush_flows(&pool, pairs).await;
+
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            "SELECT dst_ip, bytes, packets FROM netflow_flows WHERE src_ip = '10.10.0.5' ORDER BY dst_ip",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("1.1.1.1".to_string(), 150, 4),
+                ("8.8.8.8".to_string(), 10, 2)
+            ]
+        );
+    }
 }
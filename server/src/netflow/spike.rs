@@ -0,0 +1,232 @@
+//! Traffic spike detection on NetFlow samples.
+//!
+//! Each 60-second flush yields one total (rx + tx) bps sample per device.
+//! The last [`WINDOW`] samples per device form its baseline; a sample more
+//! than `[alerts] traffic_spike_factor` times the baseline average raises a
+//! `traffic_spike` alert.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use tracing::{error, warn};
+
+use crate::api::alerts::{is_device_muted, severity_for_alert_type, SeverityOverrideCache};
+use crate::webhook;
+use crate::ws::hub::WsHub;
+
+/// Samples kept per device for the moving average.
+pub const WINDOW: usize = 30;
+
+/// A device already alerted within this many seconds is not alerted again,
+/// so a sustained spike raises one alert while the average catches up.
+const REALERT_SECS: i64 = 15 * 60;
+
+/// Recent per-device bps samples, shared through `AppState`.
+pub type SharedTrafficStats = Arc<Mutex<TrafficStats>>;
+
+/// Circular buffers of the last [`WINDOW`] samples, keyed by device ID.
+#[derive(Debug, Default)]
+pub struct TrafficStats {
+    samples: HashMap<String, VecDeque<u64>>,
+}
+
+/// A sample that exceeded its device's moving average.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficSpike {
+    pub device_id: String,
+    pub current_bps: u64,
+    pub average_bps: u64,
+    pub factor: f64,
+}
+
+impl TrafficStats {
+    /// Average of the device's buffered samples, if it has any.
+    pub fn average(&self, device_id: &str) -> Option<u64> {
+        let samples = self.samples.get(device_id).filter(|s| !s.is_empty())?;
+        Some(samples.iter().sum::<u64>() / samples.len() as u64)
+    }
+
+    /// Add a sample, returning a spike if it exceeds `factor` times the
+    /// average of the preceding samples and at least `min_samples` precede it.
+    pub fn record(
+        &mut self,
+        device_id: &str,
+        bps: u64,
+        factor: f64,
+        min_samples: usize,
+    ) -> Option<TrafficSpike> {
+        let history_len = self.samples.get(device_id).map_or(0, VecDeque::len);
+        let spike = match self.average(device_id) {
+            Some(average) if history_len >= min_samples && average > 0 => {
+                let ratio = bps as f64 / average as f64;
+                (ratio > factor).then(|| TrafficSpike {
+                    device_id: device_id.to_string(),
+                    current_bps: bps,
+                    average_bps: average,
+                    factor: (ratio * 10.0).round() / 10.0,
+                })
+            }
+            _ => None,
+        };
+
+        let samples = self.samples.entry(device_id.to_string()).or_default();
+        samples.push_back(bps);
+        if samples.len() > WINDOW {
+            samples.pop_front();
+        }
+        spike
+    }
+}
+
+/// Store a `traffic_spike` alert for each spike and notify UI clients and
+/// webhooks. Muted devices and devices alerted recently are skipped.
+pub async fn create_spike_alerts(
+    db: &SqlitePool,
+    spikes: &[TrafficSpike],
+    ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+) {
+    if spikes.is_empty() {
+        return;
+    }
+    let now = Utc::now();
+    let recent_cutoff = (now - chrono::Duration::seconds(REALERT_SECS)).to_rfc3339();
+    let severity = severity_for_alert_type("traffic_spike", db, severities).await;
+
+    for spike in spikes {
+        if is_device_muted(db, &spike.device_id).await {
+            continue;
+        }
+        let already_alerted = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM alerts WHERE type = 'traffic_spike' AND device_id = ? \
+             AND created_at >= ?",
+        )
+        .bind(&spike.device_id)
+        .bind(&recent_cutoff)
+        .fetch_one(db)
+        .await
+        .unwrap_or(0)
+            > 0;
+        if already_alerted {
+            continue;
+        }
+
+        let details = json!({
+            "device_id": &spike.device_id,
+            "current_bps": spike.current_bps,
+            "average_bps": spike.average_bps,
+            "factor": spike.factor,
+        });
+        let result = sqlx::query(
+            r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+               VALUES (?, 'traffic_spike', ?, ?, ?, ?, ?)"#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(&spike.device_id)
+        .bind(format!(
+            "Traffic spike: {} bps is {}x the recent average of {} bps",
+            spike.current_bps, spike.factor, spike.average_bps
+        ))
+        .bind(details.to_string())
+        .bind(&severity)
+        .bind(now.to_rfc3339())
+        .execute(db)
+        .await;
+        if let Err(e) = result {
+            error!(device_id = %spike.device_id, "Failed to store traffic spike alert: {e}");
+            continue;
+        }
+        crate::api::metrics::record_alert_created("traffic_spike");
+        warn!(
+            device_id = %spike.device_id,
+            current_bps = spike.current_bps,
+            average_bps = spike.average_bps,
+            "Traffic spike detected"
+        );
+
+        ws_hub.broadcast("traffic_spike", details.clone());
+        webhook::dispatch_webhook(db, "traffic_spike", details);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_flags_spike_after_min_samples() {
+        let mut stats = TrafficStats::default();
+        // Not enough history yet, however large the sample.
+        for bps in [1000, 1200, 800, 1000] {
+            assert!(stats.record("dev", bps, 3.0, 5).is_none());
+        }
+        assert!(stats.record("dev", 50_000, 3.0, 5).is_none());
+
+        let mut stats = TrafficStats::default();
+        for _ in 0..5 {
+            stats.record("dev", 1000, 3.0, 5);
+        }
+        assert!(stats.record("dev", 2999, 3.0, 5).is_none());
+        let spike = stats.record("dev", 10_000, 3.0, 5).unwrap();
+        assert_eq!(spike.average_bps, 1333);
+        assert_eq!(spike.current_bps, 10_000);
+        assert_eq!(spike.factor, 7.5);
+        // Other devices keep their own baseline.
+        assert!(stats.record("other", 10_000, 3.0, 5).is_none());
+    }
+
+    #[test]
+    fn test_record_keeps_last_window_samples() {
+        let mut stats = TrafficStats::default();
+        for _ in 0..WINDOW {
+            stats.record("dev", 100, 3.0, 5);
+        }
+        for _ in 0..WINDOW {
+            stats.record("dev", 400, 3.0, 5);
+        }
+        assert_eq!(stats.average("dev"), Some(400));
+    }
+
+    #[tokio::test]
+    async fn test_create_spike_alerts_stores_once_per_cooldown() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) \
+             VALUES ('dev-1', 'aa:bb:cc:dd:ee:01', datetime('now'), datetime('now'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let ws_hub = WsHub::new();
+        let mut events = ws_hub.subscribe_ui();
+        let spike = TrafficSpike {
+            device_id: "dev-1".to_string(),
+            current_bps: 90_000,
+            average_bps: 10_000,
+            factor: 9.0,
+        };
+        let cache = SeverityOverrideCache::new();
+
+        for _ in 0..2 {
+            create_spike_alerts(&pool, std::slice::from_ref(&spike), &ws_hub, &cache).await;
+        }
+
+        let alerts: Vec<(String, String)> =
+            sqlx::query_as("SELECT details, severity FROM alerts WHERE type = 'traffic_spike'")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(alerts.len(), 1);
+        let details: serde_json::Value = serde_json::from_str(&alerts[0].0).unwrap();
+        assert_eq!(details["device_id"], "dev-1");
+        assert_eq!(details["current_bps"], 90_000);
+        assert_eq!(details["average_bps"], 10_000);
+        assert_eq!(details["factor"], 9.0);
+        assert_eq!(alerts[0].1, "WARNING");
+        assert_eq!(events.try_recv().unwrap().event, "traffic_spike");
+    }
+}
@@ -0,0 +1,582 @@
+//! NetFlow v9 and IPFIX (v10) template-based decoding.
+//!
+//! Both formats describe their records with templates sent ahead of the data.
+//! Templates are cached per exporter so later data flowsets can be decoded
+//! against them; data that arrives before its template is dropped until the
+//! exporter re-sends the template (typically every few seconds).
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use chrono::{DateTime, TimeZone, Utc};
+
+use super::FlowRecord;
+
+pub const V9_HEADER_LEN: usize = 20;
+pub const IPFIX_HEADER_LEN: usize = 16;
+
+/// Flowset/set IDs that carry templates rather than data.
+const V9_TEMPLATE_FLOWSET_ID: u16 = 0;
+const V9_OPTIONS_TEMPLATE_FLOWSET_ID: u16 = 1;
+const IPFIX_TEMPLATE_SET_ID: u16 = 2;
+const IPFIX_OPTIONS_TEMPLATE_SET_ID: u16 = 3;
+/// Data flowsets use the template ID, which is always >= 256.
+const MIN_DATA_SET_ID: u16 = 256;
+
+/// IPFIX field length marking a variable-length field.
+const VARIABLE_LENGTH: u16 = 65535;
+
+// Information element IDs shared by NetFlow v9 and IPFIX.
+const IE_IN_BYTES: u16 = 1;
+const IE_IN_PKTS: u16 = 2;
+const IE_PROTOCOL: u16 = 4;
+const IE_L4_SRC_PORT: u16 = 7;
+const IE_IPV4_SRC_ADDR: u16 = 8;
+const IE_L4_DST_PORT: u16 = 11;
+const IE_IPV4_DST_ADDR: u16 = 12;
+const IE_LAST_SWITCHED: u16 = 21;
+const IE_FIRST_SWITCHED: u16 = 22;
+const IE_IPV6_SRC_ADDR: u16 = 27;
+const IE_IPV6_DST_ADDR: u16 = 28;
+const IE_OCTET_TOTAL_COUNT: u16 = 85;
+const IE_PACKET_TOTAL_COUNT: u16 = 86;
+const IE_FLOW_START_SECONDS: u16 = 150;
+const IE_FLOW_END_SECONDS: u16 = 151;
+const IE_FLOW_START_MILLISECONDS: u16 = 152;
+const IE_FLOW_END_MILLISECONDS: u16 = 153;
+
+/// One field of a template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateField {
+    pub field_type: u16,
+    /// Field length in bytes, or [`VARIABLE_LENGTH`] (IPFIX only).
+    pub length: u16,
+    /// IPFIX private enterprise number; such fields are skipped when decoding.
+    pub enterprise: Option<u32>,
+}
+
+/// A flow template as announced by an exporter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub fields: Vec<TemplateField>,
+}
+
+impl Template {
+    /// Record length in bytes, or `None` if the template has variable-length fields.
+    fn fixed_len(&self) -> Option<usize> {
+        self.fields
+            .iter()
+            .map(|f| (f.length != VARIABLE_LENGTH).then_some(f.length as usize))
+            .sum()
+    }
+}
+
+/// Templates received so far, keyed by `(exporter address, template ID)`.
+#[derive(Debug, Default)]
+pub struct TemplateCache {
+    templates: HashMap<(SocketAddr, u16), Template>,
+}
+
+impl TemplateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, source: SocketAddr, template_id: u16) -> Option<&Template> {
+        self.templates.get(&(source, template_id))
+    }
+
+    pub fn insert(&mut self, source: SocketAddr, template_id: u16, template: Template) {
+        self.templates.insert((source, template_id), template);
+    }
+
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
+
+/// Export-time context needed to turn uptime-relative timestamps into wall-clock time.
+#[derive(Debug, Clone, Copy)]
+struct ExportClock {
+    unix_secs: u32,
+    /// Exporter uptime in milliseconds (NetFlow v9 only).
+    sys_uptime: Option<u32>,
+}
+
+impl ExportClock {
+    /// Convert an uptime-relative timestamp (milliseconds) to wall-clock time.
+    fn uptime_to_utc(&self, uptime_ms: u64) -> Option<DateTime<Utc>> {
+        let sys_uptime = u64::from(self.sys_uptime?);
+        let export_ms = i64::from(self.unix_secs) * 1000;
+        let age_ms = sys_uptime.checked_sub(uptime_ms)? as i64;
+        Utc.timestamp_millis_opt(export_ms - age_ms).single()
+    }
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> Option<u16> {
+    let bytes = buf.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    let bytes = buf.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Read a big-endian unsigned integer of 1–8 bytes (reduced-size encoding).
+fn read_uint(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() || bytes.len() > 8 {
+        return None;
+    }
+    Some(bytes.iter().fold(0u64, |acc, &b| (acc << 8) | u64::from(b)))
+}
+
+/// Parse the templates in a template flowset/set body.
+fn parse_templates(body: &[u8], ipfix: bool) -> Vec<(u16, Template)> {
+    let mut templates = Vec::new();
+    let mut offset = 0;
+    // Each template needs at least its 4-byte header; anything shorter is padding.
+    while offset + 4 <= body.len() {
+        let (Some(template_id), Some(field_count)) =
+            (read_u16(body, offset), read_u16(body, offset + 2))
+        else {
+            break;
+        };
+        offset += 4;
+
+        let mut fields = Vec::with_capacity(field_count as usize);
+        for _ in 0..field_count {
+            let (Some(raw_type), Some(length)) =
+                (read_u16(body, offset), read_u16(body, offset + 2))
+            else {
+                return templates;
+            };
+            offset += 4;
+            let enterprise = if ipfix && raw_type & 0x8000 != 0 {
+                let pen = match read_u32(body, offset) {
+                    Some(pen) => pen,
+                    None => return templates,
+                };
+                offset += 4;
+                Some(pen)
+            } else {
+                None
+            };
+            fields.push(TemplateField {
+                field_type: if ipfix { raw_type & 0x7FFF } else { raw_type },
+                length,
+                enterprise,
+            });
+        }
+
+        if template_id >= MIN_DATA_SET_ID && !fields.is_empty() {
+            templates.push((template_id, Template { fields }));
+        }
+    }
+    templates
+}
+
+/// Decode a single data record, returning the record and the bytes consumed.
+fn decode_record(
+    buf: &[u8],
+    template: &Template,
+    clock: ExportClock,
+) -> Option<(Option<FlowRecord>, usize)> {
+    let mut offset = 0;
+    let mut src_ip: Option<IpAddr> = None;
+    let mut dst_ip: Option<IpAddr> = None;
+    let mut src_port = 0;
+    let mut dst_port = 0;
+    let mut protocol = 0;
+    let mut bytes: Option<u64> = None;
+    let mut packets: Option<u64> = None;
+    let mut start = None;
+    let mut end = None;
+
+    for field in &template.fields {
+        let len = if field.length == VARIABLE_LENGTH {
+            let first = *buf.get(offset)?;
+            offset += 1;
+            if first == 255 {
+                let long = read_u16(buf, offset)?;
+                offset += 2;
+                long as usize
+            } else {
+                first as usize
+            }
+        } else {
+            field.length as usize
+        };
+        let value = buf.get(offset..offset + len)?;
+        offset += len;
+
+        // Enterprise-specific and unknown fields are skipped.
+        if field.enterprise.is_some() {
+            continue;
+        }
+        match (field.field_type, len) {
+            (IE_IPV4_SRC_ADDR, 4) => {
+                src_ip = Some(IpAddr::V4(Ipv4Addr::new(
+                    value[0], value[1], value[2], value[3],
+                )))
+            }
+            (IE_IPV4_DST_ADDR, 4) => {
+                dst_ip = Some(IpAddr::V4(Ipv4Addr::new(
+                    value[0], value[1], value[2], value[3],
+                )))
+            }
+            (IE_IPV6_SRC_ADDR, 16) => {
+                let octets: [u8; 16] = value.try_into().ok()?;
+                src_ip = Some(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            (IE_IPV6_DST_ADDR, 16) => {
+                let octets: [u8; 16] = value.try_into().ok()?;
+                dst_ip = Some(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            (IE_L4_SRC_PORT, _) => src_port = read_uint(value).unwrap_or(0) as u16,
+            (IE_L4_DST_PORT, _) => dst_port = read_uint(value).unwrap_or(0) as u16,
+            (IE_PROTOCOL, _) => protocol = read_uint(value).unwrap_or(0) as u8,
+            (IE_IN_BYTES, _) => bytes = read_uint(value),
+            (IE_OCTET_TOTAL_COUNT, _) => bytes = bytes.or(read_uint(value)),
+            (IE_IN_PKTS, _) => packets = read_uint(value),
+            (IE_PACKET_TOTAL_COUNT, _) => packets = packets.or(read_uint(value)),
+            (IE_FIRST_SWITCHED, _) => start = read_uint(value).and_then(|v| clock.uptime_to_utc(v)),
+            (IE_LAST_SWITCHED, _) => end = read_uint(value).and_then(|v| clock.uptime_to_utc(v)),
+            (IE_FLOW_START_SECONDS, _) => {
+                start = read_uint(value).and_then(|v| Utc.timestamp_opt(v as i64, 0).single())
+            }
+            (IE_FLOW_END_SECONDS, _) => {
+                end = read_uint(value).and_then(|v| Utc.timestamp_opt(v as i64, 0).single())
+            }
+            (IE_FLOW_START_MILLISECONDS, _) => {
+                start = read_uint(value).and_then(|v| Utc.timestamp_millis_opt(v as i64).single())
+            }
+            (IE_FLOW_END_MILLISECONDS, _) => {
+                end = read_uint(value).and_then(|v| Utc.timestamp_millis_opt(v as i64).single())
+            }
+            _ => {}
+        }
+    }
+
+    let record = match (src_ip, dst_ip) {
+        (Some(src_ip), Some(dst_ip)) => Some(FlowRecord {
+            src_ip,
+            dst_ip,
+            src_port,
+            dst_port,
+            protocol,
+            bytes: bytes.unwrap_or(0),
+            packets: packets.unwrap_or(0),
+            start,
+            end,
+        }),
+        // Records without addresses (e.g. options data) carry no traffic.
+        _ => None,
+    };
+    Some((record, offset))
+}
+
+/// Decode every record in a data flowset/set body.
+fn decode_data_set(body: &[u8], template: &Template, clock: ExportClock) -> Vec<FlowRecord> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    let min_len = template.fixed_len();
+    while offset < body.len() {
+        // Trailing bytes shorter than a record are padding.
+        if min_len.is_some_and(|len| body.len() - offset < len) {
+            break;
+        }
+        match decode_record(&body[offset..], template, clock) {
+            Some((record, consumed)) if consumed > 0 => {
+                records.extend(record);
+                offset += consumed;
+            }
+            _ => break,
+        }
+    }
+    records
+}
+
+/// Walk the flowsets/sets after the packet header.
+fn decode_sets(
+    buf: &[u8],
+    source: SocketAddr,
+    cache: &mut TemplateCache,
+    clock: ExportClock,
+    ipfix: bool,
+) -> Vec<FlowRecord> {
+    let (template_id, options_id) = if ipfix {
+        (IPFIX_TEMPLATE_SET_ID, IPFIX_OPTIONS_TEMPLATE_SET_ID)
+    } else {
+        (V9_TEMPLATE_FLOWSET_ID, V9_OPTIONS_TEMPLATE_FLOWSET_ID)
+    };
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= buf.len() {
+        let (Some(set_id), Some(set_len)) = (read_u16(buf, offset), read_u16(buf, offset + 2))
+        else {
+            break;
+        };
+        let set_len = set_len as usize;
+        if set_len < 4 || offset + set_len > buf.len() {
+            break;
+        }
+        let body = &buf[offset + 4..offset + set_len];
+        offset += set_len;
+
+        if set_id == template_id {
+            for (id, template) in parse_templates(body, ipfix) {
+                cache.insert(source, id, template);
+            }
+        } else if set_id == options_id {
+            // Options templates describe exporter metadata, not flows.
+        } else if set_id >= MIN_DATA_SET_ID {
+            if let Some(template) = cache.get(source, set_id) {
+                records.extend(decode_data_set(body, template, clock));
+            } else {
+                tracing::debug!(%source, template_id = set_id, "No template yet, dropping flowset");
+            }
+        }
+    }
+    records
+}
+
+/// Parse a NetFlow v9 packet, updating `cache` with any templates it carries.
+///
+/// Returns `None` if the buffer is not a NetFlow v9 packet.
+pub fn parse_v9_packet(
+    buf: &[u8],
+    source: SocketAddr,
+    cache: &mut TemplateCache,
+) -> Option<Vec<FlowRecord>> {
+    if buf.len() < V9_HEADER_LEN || read_u16(buf, 0)? != 9 {
+        return None;
+    }
+    let clock = ExportClock {
+        sys_uptime: Some(read_u32(buf, 4)?),
+        unix_secs: read_u32(buf, 8)?,
+    };
+    Some(decode_sets(
+        &buf[V9_HEADER_LEN..],
+        source,
+        cache,
+        clock,
+        false,
+    ))
+}
+
+/// Parse an IPFIX packet, updating `cache` with any templates it carries.
+///
+/// Returns `None` if the buffer is not an IPFIX message.
+pub fn parse_ipfix_packet(
+    buf: &[u8],
+    source: SocketAddr,
+    cache: &mut TemplateCache,
+) -> Option<Vec<FlowRecord>> {
+    if buf.len() < IPFIX_HEADER_LEN || read_u16(buf, 0)? != 10 {
+        return None;
+    }
+    // The message length bounds the sets; ignore anything beyond it.
+    let msg_len = (read_u16(buf, 2)? as usize).min(buf.len());
+    if msg_len < IPFIX_HEADER_LEN {
+        return None;
+    }
+    let clock = ExportClock {
+        sys_uptime: None,
+        unix_secs: read_u32(buf, 4)?,
+    };
+    Some(decode_sets(
+        &buf[IPFIX_HEADER_LEN..msg_len],
+        source,
+        cache,
+        clock,
+        true,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exporter() -> SocketAddr {
+        "10.10.0.1:2055".parse().unwrap()
+    }
+
+    /// Template 256: src addr, dst addr, src port, dst port, protocol, bytes, packets,
+    /// first switched, last switched.
+    const V9_FIELDS: &[(u16, u16)] = &[
+        (IE_IPV4_SRC_ADDR, 4),
+        (IE_IPV4_DST_ADDR, 4),
+        (IE_L4_SRC_PORT, 2),
+        (IE_L4_DST_PORT, 2),
+        (IE_PROTOCOL, 1),
+        (IE_IN_BYTES, 4),
+        (IE_IN_PKTS, 4),
+        (IE_FIRST_SWITCHED, 4),
+        (IE_LAST_SWITCHED, 4),
+    ];
+
+    fn v9_header(count: u16) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&9u16.to_be_bytes());
+        buf.extend_from_slice(&count.to_be_bytes());
+        buf.extend_from_slice(&60_000u32.to_be_bytes()); // sys_uptime
+        buf.extend_from_slice(&1_700_000_000u32.to_be_bytes()); // unix_secs
+        buf.extend_from_slice(&1u32.to_be_bytes()); // sequence
+        buf.extend_from_slice(&0u32.to_be_bytes()); // source_id
+        buf
+    }
+
+    fn v9_template_flowset() -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&256u16.to_be_bytes());
+        body.extend_from_slice(&(V9_FIELDS.len() as u16).to_be_bytes());
+        for &(ty, len) in V9_FIELDS {
+            body.extend_from_slice(&ty.to_be_bytes());
+            body.extend_from_slice(&len.to_be_bytes());
+        }
+        let mut set = Vec::new();
+        set.extend_from_slice(&V9_TEMPLATE_FLOWSET_ID.to_be_bytes());
+        set.extend_from_slice(&((body.len() + 4) as u16).to_be_bytes());
+        set.extend(body);
+        set
+    }
+
+    fn v9_data_flowset(bytes: u32) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&[192, 168, 1, 10]);
+        body.extend_from_slice(&[1, 1, 1, 1]);
+        body.extend_from_slice(&51000u16.to_be_bytes());
+        body.extend_from_slice(&443u16.to_be_bytes());
+        body.push(6);
+        body.extend_from_slice(&bytes.to_be_bytes());
+        body.extend_from_slice(&12u32.to_be_bytes());
+        body.extend_from_slice(&50_000u32.to_be_bytes()); // first: 10s before export
+        body.extend_from_slice(&59_000u32.to_be_bytes()); // last: 1s before export
+        body.extend_from_slice(&[0, 0, 0]); // padding
+        let mut set = Vec::new();
+        set.extend_from_slice(&256u16.to_be_bytes());
+        set.extend_from_slice(&((body.len() + 4) as u16).to_be_bytes());
+        set.extend(body);
+        set
+    }
+
+    #[test]
+    fn test_v9_template_then_data_in_same_packet() {
+        let mut pkt = v9_header(2);
+        pkt.extend(v9_template_flowset());
+        pkt.extend(v9_data_flowset(4096));
+
+        let mut cache = TemplateCache::new();
+        let records = parse_v9_packet(&pkt, exporter(), &mut cache).expect("v9 should parse");
+        assert_eq!(cache.len(), 1);
+        assert_eq!(records.len(), 1, "padding must not produce a record");
+
+        let rec = &records[0];
+        assert_eq!(rec.src_ip, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)));
+        assert_eq!(rec.dst_ip, IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+        assert_eq!((rec.src_port, rec.dst_port, rec.protocol), (51000, 443, 6));
+        assert_eq!((rec.bytes, rec.packets), (4096, 12));
+        assert_eq!(rec.start.unwrap().timestamp(), 1_700_000_000 - 10);
+        assert_eq!(rec.end.unwrap().timestamp(), 1_700_000_000 - 1);
+    }
+
+    #[test]
+    fn test_v9_data_before_template_is_dropped() {
+        let mut cache = TemplateCache::new();
+
+        let mut data_only = v9_header(1);
+        data_only.extend(v9_data_flowset(100));
+        let records = parse_v9_packet(&data_only, exporter(), &mut cache).unwrap();
+        assert!(records.is_empty());
+
+        let mut template_only = v9_header(1);
+        template_only.extend(v9_template_flowset());
+        parse_v9_packet(&template_only, exporter(), &mut cache).unwrap();
+
+        let records = parse_v9_packet(&data_only, exporter(), &mut cache).unwrap();
+        assert_eq!(records.len(), 1);
+
+        // Templates are per exporter.
+        let other: SocketAddr = "10.10.0.2:2055".parse().unwrap();
+        assert!(parse_v9_packet(&data_only, other, &mut cache)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_v9_rejects_other_versions() {
+        let mut pkt = v9_header(0);
+        pkt[1] = 5;
+        assert!(parse_v9_packet(&pkt, exporter(), &mut TemplateCache::new()).is_none());
+    }
+
+    #[test]
+    fn test_ipfix_skips_enterprise_variable_and_unknown_fields() {
+        // Template 300: src v6, dst v6, enterprise field (variable length),
+        // unknown IE 999 (2 bytes), octetTotalCount (8), flowStartMilliseconds (8).
+        let mut tmpl = Vec::new();
+        tmpl.extend_from_slice(&300u16.to_be_bytes());
+        tmpl.extend_from_slice(&6u16.to_be_bytes());
+        for (ty, len) in [(IE_IPV6_SRC_ADDR, 16u16), (IE_IPV6_DST_ADDR, 16)] {
+            tmpl.extend_from_slice(&ty.to_be_bytes());
+            tmpl.extend_from_slice(&len.to_be_bytes());
+        }
+        tmpl.extend_from_slice(&(0x8000u16 | 42).to_be_bytes());
+        tmpl.extend_from_slice(&VARIABLE_LENGTH.to_be_bytes());
+        tmpl.extend_from_slice(&29305u32.to_be_bytes());
+        for (ty, len) in [
+            (999u16, 2u16),
+            (IE_OCTET_TOTAL_COUNT, 8),
+            (IE_FLOW_START_MILLISECONDS, 8),
+        ] {
+            tmpl.extend_from_slice(&ty.to_be_bytes());
+            tmpl.extend_from_slice(&len.to_be_bytes());
+        }
+
+        let src: Ipv6Addr = "2001:db8::10".parse().unwrap();
+        let dst: Ipv6Addr = "2001:db8::20".parse().unwrap();
+        let mut data = Vec::new();
+        data.extend_from_slice(&src.octets());
+        data.extend_from_slice(&dst.octets());
+        data.push(3);
+        data.extend_from_slice(b"abc");
+        data.extend_from_slice(&0xBEEFu16.to_be_bytes());
+        data.extend_from_slice(&123_456u64.to_be_bytes());
+        data.extend_from_slice(&1_700_000_000_500u64.to_be_bytes());
+
+        let mut sets = Vec::new();
+        for (id, body) in [(IPFIX_TEMPLATE_SET_ID, tmpl), (300, data)] {
+            sets.extend_from_slice(&id.to_be_bytes());
+            sets.extend_from_slice(&((body.len() + 4) as u16).to_be_bytes());
+            sets.extend(body);
+        }
+        let mut pkt = Vec::new();
+        pkt.extend_from_slice(&10u16.to_be_bytes());
+        pkt.extend_from_slice(&((IPFIX_HEADER_LEN + sets.len()) as u16).to_be_bytes());
+        pkt.extend_from_slice(&1_700_000_001u32.to_be_bytes());
+        pkt.extend_from_slice(&7u32.to_be_bytes());
+        pkt.extend_from_slice(&0u32.to_be_bytes());
+        pkt.extend(sets);
+
+        let mut cache = TemplateCache::new();
+        let records = parse_ipfix_packet(&pkt, exporter(), &mut cache).expect("ipfix should parse");
+        assert_eq!(records.len(), 1);
+        let rec = &records[0];
+        assert_eq!(rec.src_ip, IpAddr::V6(src));
+        assert_eq!(rec.dst_ip, IpAddr::V6(dst));
+        assert_eq!(rec.bytes, 123_456);
+        assert_eq!(rec.packets, 0);
+        assert_eq!(rec.start.unwrap().timestamp_millis(), 1_700_000_000_500);
+        assert!(rec.end.is_none());
+
+        let template = cache.get(exporter(), 300).unwrap();
+        assert_eq!(template.fields[2].enterprise, Some(29305));
+        assert_eq!(template.fields[2].field_type, 42);
+    }
+}
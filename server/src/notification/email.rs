@@ -0,0 +1,256 @@
+//! SMTP email notifications for device alerts.
+//!
+//! Settings live in the `settings` table (`smtp_host`, `smtp_port`,
+//! `smtp_username`, `smtp_password`, `smtp_from`, `smtp_to`); the password is
+//! stored encrypted. Email is only sent when host, sender and at least one
+//! recipient are configured.
+
+use std::time::Duration;
+
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+/// Connect + send budget for a single message.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Port used when `smtp_port` is unset (submission with STARTTLS).
+const DEFAULT_SMTP_PORT: u16 = 587;
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsMode {
+    /// No TLS (port 25).
+    Plain,
+    /// Plaintext connection upgraded with STARTTLS (port 587).
+    StartTls,
+    /// TLS from the first byte (port 465).
+    Implicit,
+}
+
+impl TlsMode {
+    /// Pick the TLS mode from the port: 465 → TLS, 25 → plain, anything
+    /// else (including 587) → STARTTLS.
+    pub fn for_port(port: u16) -> Self {
+        match port {
+            465 => TlsMode::Implicit,
+            25 => TlsMode::Plain,
+            _ => TlsMode::StartTls,
+        }
+    }
+}
+
+/// SMTP configuration read from the settings table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// An alert to be delivered by email.
+#[derive(Debug, Clone)]
+pub struct EmailAlert {
+    pub alert_type: String,
+    pub mac: String,
+    pub ip: Option<String>,
+    pub timestamp: String,
+}
+
+/// Split the comma-separated `smtp_to` setting into recipient addresses.
+pub fn parse_recipients(value: &str) -> Vec<String> {
+    value
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Read the SMTP settings. Returns `None` unless host, sender and at least
+/// one recipient are set.
+pub async fn load_settings(db: &SqlitePool) -> Option<SmtpSettings> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT key, value FROM settings WHERE key LIKE 'smtp\\_%' ESCAPE '\\'")
+            .fetch_all(db)
+            .await
+            .ok()?;
+    let get = |key: &str| {
+        rows.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let to = parse_recipients(&get("smtp_to")?);
+    if to.is_empty() {
+        return None;
+    }
+    Some(SmtpSettings {
+        host: get("smtp_host")?,
+        port: get("smtp_port")
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(DEFAULT_SMTP_PORT),
+        username: get("smtp_username"),
+        password: get("smtp_password")
+            .and_then(|sealed| crate::crypto::unseal_setting("smtp_password", &sealed)),
+        from: get("smtp_from")?,
+        to,
+    })
+}
+
+/// Subject line for an alert email.
+pub fn alert_subject(alert: &EmailAlert) -> String {
+    format!("[Panoptikon] {}: {}", alert.alert_type, alert.mac)
+}
+
+/// Plaintext body for an alert email.
+pub fn alert_body(alert: &EmailAlert) -> String {
+    format!(
+        "Alert type: {}\nDevice MAC: {}\nIP address: {}\nTimestamp: {}\n",
+        alert.alert_type,
+        alert.mac,
+        alert.ip.as_deref().unwrap_or("unknown"),
+        alert.timestamp,
+    )
+}
+
+/// Build the SMTP transport for the configured host, port and TLS mode.
+fn build_transport(settings: &SmtpSettings) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let builder = match TlsMode::for_port(settings.port) {
+        TlsMode::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host),
+        TlsMode::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host),
+        TlsMode::Plain => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &settings.host,
+        )),
+    }
+    .map_err(|e| format!("Invalid SMTP host: {e}"))?;
+
+    let mut builder = builder.port(settings.port).timeout(Some(SMTP_TIMEOUT));
+    if let Some(username) = &settings.username {
+        builder = builder.credentials(Credentials::new(
+            username.clone(),
+            settings.password.clone().unwrap_or_default(),
+        ));
+    }
+    Ok(builder.build())
+}
+
+/// Send a plaintext email to all configured recipients.
+pub async fn send_email(settings: &SmtpSettings, subject: &str, body: &str) -> Result<(), String> {
+    let from: Mailbox = settings
+        .from
+        .parse()
+        .map_err(|e| format!("Invalid sender address '{}': {e}", settings.from))?;
+
+    let mut message = Message::builder().from(from).subject(subject);
+    for to in &settings.to {
+        let mailbox: Mailbox = to
+            .parse()
+            .map_err(|e| format!("Invalid recipient address '{to}': {e}"))?;
+        message = message.to(mailbox);
+    }
+    let message = message
+        .body(body.to_string())
+        .map_err(|e| format!("Failed to build email: {e}"))?;
+
+    build_transport(settings)?
+        .send(message)
+        .await
+        .map_err(|e| format!("SMTP send failed: {e}"))?;
+    Ok(())
+}
+
+/// Email an alert if SMTP is configured. Logs a warning on error but never fails.
+pub async fn send_alert(db: &SqlitePool, alert: &EmailAlert) {
+    let Some(settings) = load_settings(db).await else {
+        debug!("SMTP not configured, skipping alert email");
+        return;
+    };
+    if let Err(e) = send_email(&settings, &alert_subject(alert), &alert_body(alert)).await {
+        warn!(alert_type = %alert.alert_type, error = %e, "Alert email failed");
+    }
+}
+
+/// Fire off [`send_alert`] in the background. This never blocks the caller.
+pub fn dispatch_alert(db: &SqlitePool, alert_type: &str, mac: &str, ip: Option<&str>) {
+    let db = db.clone();
+    let alert = EmailAlert {
+        alert_type: alert_type.to_string(),
+        mac: mac.to_string(),
+        ip: ip.map(str::to_string),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    tokio::spawn(async move { send_alert(&db, &alert).await });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_mode_for_port() {
+        assert_eq!(TlsMode::for_port(465), TlsMode::Implicit);
+        assert_eq!(TlsMode::for_port(587), TlsMode::StartTls);
+        assert_eq!(TlsMode::for_port(25), TlsMode::Plain);
+        assert_eq!(TlsMode::for_port(2525), TlsMode::StartTls);
+    }
+
+    #[test]
+    fn test_parse_recipients() {
+        assert_eq!(
+            parse_recipients(" ops@example.com, noc@example.com;;"),
+            vec!["ops@example.com", "noc@example.com"]
+        );
+        assert!(parse_recipients(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_alert_body() {
+        let alert = EmailAlert {
+            alert_type: "device_offline".to_string(),
+            mac: "aa:bb:cc:dd:ee:ff".to_string(),
+            ip: None,
+            timestamp: "2024-01-01T00:00:00+00:00".to_string(),
+        };
+        assert_eq!(
+            alert_subject(&alert),
+            "[Panoptikon] device_offline: aa:bb:cc:dd:ee:ff"
+        );
+        let body = alert_body(&alert);
+        assert!(body.contains("Device MAC: aa:bb:cc:dd:ee:ff"));
+        assert!(body.contains("IP address: unknown"));
+        assert!(body.contains("Timestamp: 2024-01-01T00:00:00+00:00"));
+    }
+
+    #[tokio::test]
+    async fn test_load_settings() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        assert!(load_settings(&pool).await.is_none());
+
+        for (key, value) in [
+            ("smtp_host", "mail.example.com"),
+            ("smtp_from", "panoptikon@example.com"),
+            ("smtp_to", "ops@example.com,noc@example.com"),
+            ("smtp_username", ""),
+        ] {
+            sqlx::query("INSERT INTO settings (key, value) VALUES (?, ?)")
+                .bind(key)
+                .bind(value)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let settings = load_settings(&pool).await.unwrap();
+        assert_eq!(settings.host, "mail.example.com");
+        assert_eq!(settings.port, DEFAULT_SMTP_PORT);
+        assert_eq!(settings.username, None);
+        assert_eq!(settings.to.len(), 2);
+    }
+}
@@ -0,0 +1,5 @@
+//! Alert notification channels beyond webhooks, plus audit log forwarding.
+
+pub mod email;
+pub mod syslog;
+pub mod telegram;
@@ -0,0 +1,283 @@
+//! Audit log forwarding to a remote syslog server (RFC 5424 over UDP).
+//!
+//! Enabled by `syslog_host` / `syslog_port` in the config file. Every audit
+//! entry is sent as `local0.info` (success) or `local0.err` (failure) with an
+//! `audit@32473` structured-data element carrying the action, actor and,
+//! where known, the device id. Sends happen on a spawned task; failures are
+//! logged and kept in a ring buffer served by `GET /audit-log/syslog-status`.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use tokio::net::UdpSocket;
+use tracing::{info, warn};
+
+use crate::config::AppConfig;
+
+/// Syslog facility `local0`.
+const FACILITY_LOCAL0: u8 = 16;
+/// Severity `err`.
+const SEVERITY_ERR: u8 = 3;
+/// Severity `info`.
+const SEVERITY_INFO: u8 = 6;
+
+const APP_NAME: &str = "panoptikon";
+
+/// SD-ID for audit fields (32473 is the documentation enterprise number, RFC 5612).
+const SD_ID: &str = "audit@32473";
+
+/// RFC 5424 limits MSGID to 32 printable ASCII characters.
+const MAX_MSGID_LEN: usize = 32;
+
+/// Number of failed transmissions kept for the status endpoint.
+pub const MAX_FAILURES: usize = 100;
+
+/// Forwarder configured at startup; unset when syslog export is disabled.
+static FORWARDER: OnceLock<SyslogForwarder> = OnceLock::new();
+
+/// An audit entry to forward.
+#[derive(Debug, Clone)]
+pub struct SyslogEvent {
+    pub success: bool,
+    pub action: String,
+    pub actor: String,
+    pub device_id: Option<String>,
+    pub description: String,
+}
+
+/// A transmission that could not be delivered.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyslogFailure {
+    pub occurred_at: String,
+    pub action: String,
+    pub error: String,
+}
+
+/// Response for `GET /api/v1/audit-log/syslog-status`.
+#[derive(Debug, Serialize)]
+pub struct SyslogStatus {
+    pub enabled: bool,
+    /// `host:port` of the syslog server, when enabled.
+    pub target: Option<String>,
+    /// Most recent failures first.
+    pub failures: Vec<SyslogFailure>,
+}
+
+/// Sends audit events to one syslog server and remembers recent failures.
+pub struct SyslogForwarder {
+    host: String,
+    port: u16,
+    hostname: String,
+    failures: Mutex<VecDeque<SyslogFailure>>,
+}
+
+impl SyslogForwarder {
+    pub fn new(host: String, port: u16) -> Self {
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|h| h.trim().to_string())
+            .ok()
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| "-".to_string());
+        Self {
+            host,
+            port,
+            hostname,
+            failures: Mutex::new(VecDeque::with_capacity(MAX_FAILURES)),
+        }
+    }
+
+    /// Send one event as a single UDP datagram.
+    pub async fn send(&self, event: &SyslogEvent) -> std::io::Result<()> {
+        let message = format_message(event, &self.hostname, &Utc::now());
+        let socket = UdpSocket::bind("0.0.0.0:0").await?;
+        socket
+            .send_to(message.as_bytes(), (self.host.as_str(), self.port))
+            .await?;
+        Ok(())
+    }
+
+    /// Remember a failed transmission, dropping the oldest beyond [`MAX_FAILURES`].
+    pub fn record_failure(&self, action: &str, error: String) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if failures.len() == MAX_FAILURES {
+            failures.pop_front();
+        }
+        failures.push_back(SyslogFailure {
+            occurred_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            action: action.to_string(),
+            error,
+        });
+    }
+
+    fn status(&self) -> SyslogStatus {
+        let failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        SyslogStatus {
+            enabled: true,
+            target: Some(format!("{}:{}", self.host, self.port)),
+            failures: failures.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+/// Enable forwarding if `syslog_host` is configured. Call once at startup.
+pub fn init(config: &AppConfig) {
+    let Some(host) = config.syslog_host.as_deref().map(str::trim) else {
+        return;
+    };
+    if host.is_empty() {
+        return;
+    }
+    let forwarder = SyslogForwarder::new(host.to_string(), config.syslog_port);
+    info!(
+        host,
+        port = config.syslog_port,
+        "Forwarding audit log to syslog"
+    );
+    let _ = FORWARDER.set(forwarder);
+}
+
+/// Forward an event in the background. No-op when syslog export is disabled.
+pub fn forward(event: SyslogEvent) {
+    let Some(forwarder) = FORWARDER.get() else {
+        return;
+    };
+    tokio::spawn(async move {
+        if let Err(e) = forwarder.send(&event).await {
+            warn!(action = %event.action, "Syslog delivery failed: {e}");
+            forwarder.record_failure(&event.action, e.to_string());
+        }
+    });
+}
+
+/// Current forwarding state and recent failures.
+pub fn status() -> SyslogStatus {
+    match FORWARDER.get() {
+        Some(forwarder) => forwarder.status(),
+        None => SyslogStatus {
+            enabled: false,
+            target: None,
+            failures: Vec::new(),
+        },
+    }
+}
+
+/// Escape a structured-data parameter value (`"`, `\` and `]`).
+fn escape_param(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format an RFC 5424 message:
+/// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG`.
+pub fn format_message(event: &SyslogEvent, hostname: &str, now: &chrono::DateTime<Utc>) -> String {
+    let severity = if event.success {
+        SEVERITY_INFO
+    } else {
+        SEVERITY_ERR
+    };
+    let pri = FACILITY_LOCAL0 * 8 + severity;
+
+    let msgid: String = event
+        .action
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(MAX_MSGID_LEN)
+        .collect();
+    let msgid = if msgid.is_empty() {
+        "-".to_string()
+    } else {
+        msgid
+    };
+
+    let mut sd = format!(
+        "[{SD_ID} action=\"{}\" actor=\"{}\"",
+        escape_param(&event.action),
+        escape_param(&event.actor)
+    );
+    if let Some(ref device_id) = event.device_id {
+        sd.push_str(&format!(" device_id=\"{}\"", escape_param(device_id)));
+    }
+    sd.push(']');
+
+    format!(
+        "<{pri}>1 {} {hostname} {APP_NAME} {} {msgid} {sd} {}",
+        now.to_rfc3339_opts(SecondsFormat::Millis, true),
+        std::process::id(),
+        event.description
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(success: bool) -> SyslogEvent {
+        SyslogEvent {
+            success,
+            action: "device_delete".to_string(),
+            actor: "noc".to_string(),
+            device_id: Some("abc-123".to_string()),
+            description: "Deleted device".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_message() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap();
+        let msg = format_message(&event(true), "gw", &now);
+        let expected_prefix = "<134>1 2026-01-02T03:04:05.000Z gw panoptikon ";
+        assert!(msg.starts_with(expected_prefix), "{msg}");
+        assert!(msg.ends_with(
+            " device_delete [audit@32473 action=\"device_delete\" actor=\"noc\" \
+             device_id=\"abc-123\"] Deleted device"
+        ));
+
+        let mut failed = event(false);
+        failed.device_id = None;
+        failed.actor = "a\"b]".to_string();
+        let msg = format_message(&failed, "gw", &now);
+        assert!(msg.starts_with("<131>1 "));
+        assert!(msg.contains("[audit@32473 action=\"device_delete\" actor=\"a\\\"b\\]\"]"));
+    }
+
+    #[test]
+    fn test_failures_ring_buffer() {
+        let forwarder = SyslogForwarder::new("localhost".to_string(), 514);
+        for i in 0..MAX_FAILURES + 5 {
+            forwarder.record_failure(&format!("action_{i}"), "unreachable".to_string());
+        }
+        let status = forwarder.status();
+        assert!(status.enabled);
+        assert_eq!(status.target.as_deref(), Some("localhost:514"));
+        assert_eq!(status.failures.len(), MAX_FAILURES);
+        assert_eq!(
+            status.failures[0].action,
+            format!("action_{}", MAX_FAILURES + 4)
+        );
+        assert_eq!(status.failures[MAX_FAILURES - 1].action, "action_5");
+    }
+
+    #[tokio::test]
+    async fn test_send_delivers_datagram() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = server.local_addr().unwrap().port();
+        let forwarder = SyslogForwarder::new("127.0.0.1".to_string(), port);
+
+        forwarder.send(&event(true)).await.unwrap();
+
+        let mut buf = [0u8; 1024];
+        let (len, _) = server.recv_from(&mut buf).await.unwrap();
+        let msg = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(msg.starts_with("<134>1 "));
+        assert!(msg.contains("actor=\"noc\""));
+    }
+}
@@ -0,0 +1,237 @@
+//! Telegram alert notifications via the Bot API.
+//!
+//! Settings live in the `settings` table (`telegram_bot_token`, stored
+//! encrypted, and `telegram_chat_id`). Messages use MarkdownV2 and are spaced at least
+//! [`TELEGRAM_MIN_INTERVAL`] apart to stay clear of Bot API flood limits.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::warn;
+
+const TELEGRAM_API_BASE: &str = "https://api.telegram.org";
+
+/// Minimum spacing between two messages.
+pub const TELEGRAM_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Shared rate limiter allowing one message per [`TELEGRAM_MIN_INTERVAL`].
+///
+/// Cloning is cheap; all clones share the same interval. Senders queue up
+/// behind the lock, so a burst of alerts is delivered in order, just slower.
+#[derive(Clone, Default)]
+pub struct TelegramRateLimiter {
+    inner: Arc<Mutex<Option<Interval>>>,
+}
+
+impl TelegramRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait until the next send slot is available.
+    pub async fn wait(&self) {
+        let mut interval = self.inner.lock().await;
+        // Created lazily so the limiter can be built outside a runtime.
+        let interval = interval.get_or_insert_with(|| {
+            let mut interval = tokio::time::interval(TELEGRAM_MIN_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+        interval.tick().await;
+    }
+}
+
+/// Read `(bot_token, chat_id)` from the settings table. Returns `None`
+/// unless both are set.
+pub async fn get_telegram_settings(db: &SqlitePool) -> Option<(String, String)> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM settings WHERE key IN ('telegram_bot_token', 'telegram_chat_id')",
+    )
+    .fetch_all(db)
+    .await
+    .ok()?;
+    let get = |key: &str| {
+        rows.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let token = get("telegram_bot_token")?;
+    let token = crate::crypto::unseal_setting("telegram_bot_token", &token)?;
+    Some((token, get("telegram_chat_id")?))
+}
+
+/// Escape text for a MarkdownV2 message outside of code spans.
+pub fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape text for use inside a MarkdownV2 inline code span.
+fn escape_code(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+/// Device alert message: bold alert type, monospace MAC, plain IP.
+pub fn format_device_alert(alert_type: &str, mac: &str, ip: Option<&str>) -> String {
+    let mut text = format!(
+        "*{}*\nMAC: `{}`",
+        escape_markdown(alert_type),
+        escape_code(mac)
+    );
+    if let Some(ip) = ip {
+        text.push_str(&format!("\nIP: {}", escape_markdown(ip)));
+    }
+    text
+}
+
+/// Message for a failed VyOS configuration change.
+pub fn format_vyos_failure(action: &str, description: &str, error: &str) -> String {
+    format!(
+        "*vyos\\_write\\_failed*\nAction: `{}`\n{}\n{}",
+        escape_code(action),
+        escape_markdown(description),
+        escape_markdown(error)
+    )
+}
+
+/// POST a MarkdownV2 message to a chat through the Bot API.
+///
+/// Times out after 5 seconds. Logs a warning on error but never panics.
+pub async fn send_telegram_alert(token: &str, chat_id: &str, text: &str) {
+    send_to(TELEGRAM_API_BASE, token, chat_id, text).await;
+}
+
+async fn send_to(base: &str, token: &str, chat_id: &str, text: &str) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            warn!(error = %e, "Failed to build reqwest client for Telegram");
+            return;
+        }
+    };
+
+    let payload = serde_json::json!({
+        "chat_id": chat_id,
+        "text": text,
+        "parse_mode": "MarkdownV2",
+    });
+    // The token is part of the URL, so never log the URL itself.
+    match client
+        .post(format!("{base}/bot{token}/sendMessage"))
+        .json(&payload)
+        .send()
+        .await
+    {
+        Ok(resp) if !resp.status().is_success() => {
+            warn!(status = %resp.status(), "Telegram sendMessage returned non-success status");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!(error = %e.without_url(), "Telegram sendMessage failed");
+        }
+    }
+}
+
+/// Send `text` in the background if Telegram is configured, waiting for
+/// the rate limiter first. This never blocks the caller.
+pub fn dispatch(db: &SqlitePool, limiter: &TelegramRateLimiter, text: String) {
+    let db = db.clone();
+    let limiter = limiter.clone();
+
+    tokio::spawn(async move {
+        if let Some((token, chat_id)) = get_telegram_settings(&db).await {
+            limiter.wait().await;
+            send_telegram_alert(&token, &chat_id, &text).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!(escape_markdown("192.168.1.10"), "192\\.168\\.1\\.10");
+        assert_eq!(escape_markdown("new_device"), "new\\_device");
+        assert_eq!(escape_markdown("a (b) [c]!"), "a \\(b\\) \\[c\\]\\!");
+    }
+
+    #[test]
+    fn test_format_device_alert() {
+        assert_eq!(
+            format_device_alert("device_online", "aa:bb:cc:dd:ee:ff", Some("10.0.0.5")),
+            "*device\\_online*\nMAC: `aa:bb:cc:dd:ee:ff`\nIP: 10\\.0\\.0\\.5"
+        );
+        assert_eq!(
+            format_device_alert("device_offline", "aa:bb:cc:dd:ee:ff", None),
+            "*device\\_offline*\nMAC: `aa:bb:cc:dd:ee:ff`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_telegram_settings_requires_both() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('telegram_bot_token', ?)")
+            .bind(crate::crypto::encrypt("123:abc"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(get_telegram_settings(&pool).await.is_none());
+
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('telegram_chat_id', '-1001')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_telegram_settings(&pool).await,
+            Some(("123:abc".to_string(), "-1001".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_to_posts_markdown_message() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            // Read until the JSON body has arrived.
+            while !request.ends_with(b"}") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n{\"ok\":true}")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        send_to(&base, "123:abc", "-1001", "*hi*").await;
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /bot123:abc/sendMessage "));
+        assert!(request.contains(r#""parse_mode":"MarkdownV2""#));
+        assert!(request.contains(r#""chat_id":"-1001""#));
+    }
+}
@@ -3,15 +3,48 @@
 /// Embeds the IEEE MA-L (Manufacturer Assignment - Large) database at compile time.
 /// The database is a trimmed TSV file with ~39k entries mapping 3-byte OUI prefixes
 /// to vendor names.
+///
+/// A newer copy can be downloaded from the IEEE at runtime
+/// (`POST /settings/update-oui-db`); it is saved in the same format to
+/// `oui_db_path` and preferred over the embedded data from then on,
+/// including after restarts.
 use std::collections::HashMap;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde::Serialize;
+
+use crate::config::AppConfig;
 
 /// Raw OUI database embedded at compile time.
 /// Format: one line per entry, `HEXPREFIX\tVendorName\n` (e.g., `001122\tAcme Corp\n`).
 static OUI_RAW: &str = include_str!("oui_db.csv");
 
+/// Official IEEE MA-L registry (CSV).
+pub const IEEE_OUI_URL: &str = "https://standards-oui.ieee.org/oui/oui.csv";
+
+/// A download with fewer entries than this is rejected as truncated or not
+/// the registry at all (e.g. an HTML error page).
+const MIN_DOWNLOAD_ENTRIES: usize = 10_000;
+
+/// Where the active OUI data came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OuiSource {
+    /// Compiled into the binary.
+    Embedded,
+    /// Loaded from `oui_db_path`.
+    File,
+}
+
 /// Parsed OUI database: maps 3-byte prefix to vendor name.
-static OUI_DB: OnceLock<HashMap<[u8; 3], String>> = OnceLock::new();
+struct OuiDb {
+    entries: HashMap<[u8; 3], String>,
+    source: OuiSource,
+}
+
+/// Active OUI database, replaced wholesale on reload.
+static OUI_DB: OnceLock<RwLock<OuiDb>> = OnceLock::new();
 
 /// Parse a hex character to its nibble value.
 fn hex_nibble(b: u8) -> Option<u8> {
@@ -36,10 +69,10 @@ fn parse_hex_prefix(s: &str) -> Option<[u8; 3]> {
     ])
 }
 
-/// Initialize the OUI database from the embedded data.
-fn init_db() -> HashMap<[u8; 3], String> {
-    let mut map = HashMap::with_capacity(OUI_RAW.lines().count());
-    for line in OUI_RAW.lines() {
+/// Parse `HEXPREFIX\tVendorName` lines.
+fn parse_tsv(raw: &str) -> HashMap<[u8; 3], String> {
+    let mut map = HashMap::with_capacity(raw.lines().count());
+    for line in raw.lines() {
         if let Some((hex, vendor)) = line.split_once('\t') {
             if let Some(prefix) = parse_hex_prefix(hex.trim()) {
                 let vendor = vendor.trim();
@@ -52,6 +85,157 @@ fn init_db() -> HashMap<[u8; 3], String> {
     map
 }
 
+/// The embedded database.
+fn embedded_db() -> OuiDb {
+    OuiDb {
+        entries: parse_tsv(OUI_RAW),
+        source: OuiSource::Embedded,
+    }
+}
+
+fn db() -> &'static RwLock<OuiDb> {
+    OUI_DB.get_or_init(|| RwLock::new(embedded_db()))
+}
+
+/// Path of the downloaded database: `oui_db_path`, or
+/// `~/.local/share/panoptikon/oui_db.csv`.
+pub fn db_path(config: &AppConfig) -> PathBuf {
+    if let Some(path) = config
+        .oui_db_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        return PathBuf::from(path);
+    }
+    let base = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".local/share"))
+        .unwrap_or_default();
+    base.join("panoptikon").join("oui_db.csv")
+}
+
+/// Load the on-disk database if present. Call once at startup.
+pub fn init(config: &AppConfig) {
+    let path = db_path(config);
+    let raw = match std::fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            tracing::warn!(path = %path.display(), "Failed to read OUI database, using embedded copy: {e}");
+            return;
+        }
+    };
+    let entries = parse_tsv(&raw);
+    if entries.is_empty() {
+        tracing::warn!(path = %path.display(), "OUI database file is empty, using embedded copy");
+        return;
+    }
+    tracing::info!(path = %path.display(), entries = entries.len(), "Loaded OUI database from file");
+    install(entries, OuiSource::File);
+}
+
+/// Replace the active database.
+fn install(entries: HashMap<[u8; 3], String>, source: OuiSource) {
+    *db().write().unwrap_or_else(|e| e.into_inner()) = OuiDb { entries, source };
+}
+
+/// Number of entries and source of the active database.
+pub fn info() -> (usize, OuiSource) {
+    let db = db().read().unwrap_or_else(|e| e.into_inner());
+    (db.entries.len(), db.source)
+}
+
+/// Split one CSV record into fields, honouring `"..."` quoting and `""` escapes.
+fn split_csv_record(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse the IEEE registry CSV
+/// (`Registry,Assignment,Organization Name,Organization Address`).
+pub fn parse_ieee_csv(csv: &str) -> HashMap<[u8; 3], String> {
+    let mut map = HashMap::new();
+    for line in csv.lines().skip(1) {
+        let fields = split_csv_record(line);
+        let (Some(assignment), Some(vendor)) = (fields.get(1), fields.get(2)) else {
+            continue;
+        };
+        let vendor = vendor.trim();
+        if let Some(prefix) = parse_hex_prefix(assignment.trim()) {
+            if !vendor.is_empty() {
+                map.insert(prefix, vendor.to_string());
+            }
+        }
+    }
+    map
+}
+
+/// Write entries in the embedded TSV format, sorted by prefix. The file is
+/// written next to `path` and renamed into place so readers never see a
+/// partial database.
+pub fn save(path: &Path, entries: &HashMap<[u8; 3], String>) -> std::io::Result<()> {
+    let mut sorted: Vec<_> = entries.iter().collect();
+    sorted.sort_by_key(|(prefix, _)| **prefix);
+    let mut out = String::with_capacity(sorted.len() * 32);
+    for ([a, b, c], vendor) in sorted {
+        out.push_str(&format!(
+            "{a:02X}{b:02X}{c:02X}\t{}\n",
+            vendor.replace(['\t', '\n'], " ")
+        ));
+    }
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("csv.tmp");
+    std::fs::write(&tmp, out)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Download the IEEE registry from `url`, save it to `path` and make it the
+/// active database. Returns the new entry count.
+pub async fn update_from(url: &str, path: &Path) -> anyhow::Result<usize> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()?;
+    let csv = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    let entries = parse_ieee_csv(&csv);
+    if entries.len() < MIN_DOWNLOAD_ENTRIES {
+        anyhow::bail!(
+            "downloaded registry has only {} entries (expected at least {MIN_DOWNLOAD_ENTRIES})",
+            entries.len()
+        );
+    }
+
+    let count = entries.len();
+    let save_path = path.to_path_buf();
+    let entries =
+        tokio::task::spawn_blocking(move || save(&save_path, &entries).map(|_| entries)).await??;
+    install(entries, OuiSource::File);
+    Ok(count)
+}
+
 /// Extract the 3-byte OUI prefix from a MAC address string.
 ///
 /// Supports formats:
@@ -86,10 +270,10 @@ fn extract_oui_bytes(mac: &str) -> Option<[u8; 3]> {
 ///
 /// Accepts common MAC formats (colon-separated, dash-separated, plain hex).
 /// Returns `None` if the OUI prefix is not in the database.
-pub fn lookup(mac: &str) -> Option<&'static str> {
-    let db = OUI_DB.get_or_init(init_db);
+pub fn lookup(mac: &str) -> Option<String> {
     let prefix = extract_oui_bytes(mac)?;
-    db.get(&prefix).map(|s| s.as_str())
+    let db = db().read().unwrap_or_else(|e| e.into_inner());
+    db.entries.get(&prefix).cloned()
 }
 
 #[cfg(test)]
@@ -127,7 +311,7 @@ mod tests {
         let result = lookup("28:6f:b9:12:34:56");
         assert!(result.is_some(), "Expected to find vendor for 28:6F:B9");
         assert!(
-            result.unwrap().contains("Nokia"),
+            result.as_deref().unwrap().contains("Nokia"),
             "Expected Nokia in vendor name, got: {}",
             result.unwrap()
         );
@@ -153,11 +337,82 @@ mod tests {
 
     #[test]
     fn test_db_has_entries() {
-        let db = OUI_DB.get_or_init(init_db);
+        let db = embedded_db();
         assert!(
-            db.len() > 30_000,
+            db.entries.len() > 30_000,
             "Expected >30k OUI entries, got {}",
-            db.len()
+            db.entries.len()
+        );
+    }
+
+    #[test]
+    fn test_parse_ieee_csv() {
+        let csv = "Registry,Assignment,Organization Name,Organization Address\r\n\
+                   MA-L,286FB9,\"Nokia Shanghai Bell Co., Ltd.\",\"No.388 Ning Qiao Road,Jin Qiao Pudong Shanghai  CN 201206 \"\r\n\
+                   MA-L,0050C2,IEEE Registration Authority,\"445 Hoes Lane Piscataway NJ US 08554 \"\r\n\
+                   MA-L,08EA44,\"The \"\"Quoted\"\" Co\",Somewhere\r\n\
+                   MA-L,ZZZZZZ,Broken,Nowhere\r\n";
+        let entries = parse_ieee_csv(csv);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[&[0x28, 0x6F, 0xB9]],
+            "Nokia Shanghai Bell Co., Ltd."
         );
+        assert_eq!(entries[&[0x00, 0x50, 0xC2]], "IEEE Registration Authority");
+        assert_eq!(entries[&[0x08, 0xEA, 0x44]], "The \"Quoted\" Co");
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let dir = std::env::temp_dir().join(format!("panoptikon-oui-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("oui_db.csv");
+        let mut entries = HashMap::new();
+        entries.insert([0x28, 0x6F, 0xB9], "Nokia".to_string());
+        entries.insert([0x00, 0x11, 0x22], "Acme\tCorp".to_string());
+
+        save(&path, &entries).unwrap();
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(raw, "001122\tAcme Corp\n286FB9\tNokia\n");
+        assert_eq!(parse_tsv(&raw).len(), 2);
+        assert!(!path.with_extension("csv.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_db_path() {
+        let mut config = AppConfig {
+            oui_db_path: Some("/var/lib/panoptikon/oui.csv".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            db_path(&config),
+            PathBuf::from("/var/lib/panoptikon/oui.csv")
+        );
+
+        config.oui_db_path = None;
+        assert!(db_path(&config).ends_with("panoptikon/oui_db.csv"));
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_truncated_registry() {
+        use axum::{routing::get, Router};
+
+        let app = Router::new().route(
+            "/oui.csv",
+            get(|| async { "Registry,Assignment,Organization Name\nMA-L,286FB9,Nokia\n" }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let path =
+            std::env::temp_dir().join(format!("panoptikon-oui-{}.csv", uuid::Uuid::new_v4()));
+        let err = update_from(&format!("http://{addr}/oui.csv"), &path)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("only 1 entries"), "{err}");
+        assert!(!path.exists());
+        assert_eq!(info().1, OuiSource::Embedded);
     }
 }
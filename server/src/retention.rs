@@ -77,6 +77,21 @@ async fn delete_old_alerts(pool: &SqlitePool, days: u64) -> u64 {
     }
 }
 
+async fn delete_old_netflow_flows(pool: &SqlitePool, days: u64) -> u64 {
+    let interval = format!("-{days} days");
+    match sqlx::query(r#"DELETE FROM netflow_flows WHERE recorded_at < datetime('now', ?)"#)
+        .bind(&interval)
+        .execute(pool)
+        .await
+    {
+        Ok(r) => r.rows_affected(),
+        Err(e) => {
+            error!("retention: failed to delete old netflow_flows: {e}");
+            0
+        }
+    }
+}
+
 /// Check if VACUUM is needed (>7 days since last) and run it if so.
 async fn maybe_vacuum(pool: &SqlitePool) {
     // Check last_vacuum_at from settings table.
@@ -149,9 +164,11 @@ pub fn start_retention_task(pool: SqlitePool, config: RetentionConfig) {
             interval.tick().await;
             info!("retention: starting hourly cleanup");
             let (traffic, reports, events, alerts) = run_cleanup(&pool, &config).await;
-            if traffic + reports + events + alerts > 0 {
+            let flows = delete_old_netflow_flows(&pool, config.netflow_flows_days).await;
+            if traffic + reports + events + alerts + flows > 0 {
                 info!(
                     traffic_samples = traffic,
+                    netflow_flows = flows,
                     agent_reports = reports,
                     device_events = events,
                     alerts = alerts,
@@ -367,4 +384,22 @@ mod tests {
             .unwrap();
         assert_eq!(count.0, 1, "Unacknowledged alert should remain");
     }
+
+    #[tokio::test]
+    async fn test_retention_deletes_old_netflow_flows() {
+        let pool = setup_test_db().await;
+        for age in ["-10 days", "-1 day"] {
+            sqlx::query(
+                r#"INSERT INTO netflow_flows (src_ip, dst_ip, bytes, packets, recorded_at)
+                   VALUES ('10.0.0.1', '1.1.1.1', 100, 1, datetime('now', ?))"#,
+            )
+            .bind(age)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let deleted = delete_old_netflow_flows(&pool, default_config().netflow_flows_days).await;
+        assert_eq!(deleted, 1, "Only the 10-day-old flow should be deleted");
+    }
 }
@@ -1,22 +1,24 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::Result;
 use ipnetwork::IpNetwork;
+use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
 use tracing::{debug, info, warn};
 
 use super::DiscoveredDevice;
 
-/// Maximum number of concurrent ping processes.
-const PING_CONCURRENCY: usize = 64;
-
 /// Send ICMP echo requests to every host IP in a CIDR subnet.
 ///
 /// The purpose is **not** to confirm reachability — it is purely to populate
 /// the kernel ARP table so that the subsequent `read_arp_table()` call
 /// discovers all active devices on the subnet.
 ///
-/// Each ping is launched as a child process (`ping -c 1 -W 1 <ip>`).
+/// Each ping is launched as a child process (`ping -c 1 -W <secs> <ip>`) once
+/// a permit from `permits` is available, and killed after `timeout`.
 /// Exit codes are intentionally ignored.
-pub async fn ping_sweep(subnet: &str) {
+pub async fn ping_sweep(subnet: &str, permits: Arc<Semaphore>, timeout: Duration) {
     let network: IpNetwork = match subnet.parse() {
         Ok(n) => n,
         Err(e) => {
@@ -64,24 +66,32 @@ pub async fn ping_sweep(subnet: &str) {
     );
 
     let mut join_set: JoinSet<()> = JoinSet::new();
+    // `-W` only takes whole seconds; the tokio timeout enforces the exact limit.
+    let wait_secs = timeout.as_millis().div_ceil(1000).max(1).to_string();
 
     for ip in host_iter {
-        // Limit concurrency: wait for one to finish before spawning another.
-        if join_set.len() >= PING_CONCURRENCY {
-            let _ = join_set.join_next().await;
-        }
+        // Limit concurrency: the permit is taken before spawning and released
+        // when the ping task finishes.
+        let Ok(permit) = permits.clone().acquire_owned().await else {
+            break;
+        };
+        // Reap finished tasks so the set stays bounded by the permit count.
+        while join_set.try_join_next().is_some() {}
 
         let ip_str = ip.to_string();
+        let wait_secs = wait_secs.clone();
         join_set.spawn(async move {
-            match tokio::process::Command::new("ping")
-                .args(["-c", "1", "-W", "1", &ip_str])
+            let _permit = permit;
+            let ping = tokio::process::Command::new("ping")
+                .args(["-c", "1", "-W", &wait_secs, &ip_str])
                 .stdout(std::process::Stdio::null())
                 .stderr(std::process::Stdio::null())
-                .output()
-                .await
-            {
-                Ok(_) => {} // exit code ignored intentionally — any response populates ARP
-                Err(e) => debug!(ip = %ip_str, error = %e, "ping process failed to spawn"),
+                .kill_on_drop(true)
+                .output();
+            match tokio::time::timeout(timeout, ping).await {
+                Ok(Ok(_)) => {} // exit code ignored intentionally — any response populates ARP
+                Ok(Err(e)) => debug!(ip = %ip_str, error = %e, "ping process failed to spawn"),
+                Err(_) => {} // timed out; dropping the future kills the process
             }
         });
     }
@@ -124,7 +134,11 @@ pub async fn read_arp_table() -> Result<Vec<DiscoveredDevice>> {
                 continue;
             }
 
-            devices.push(DiscoveredDevice { ip, mac });
+            devices.push(DiscoveredDevice {
+                ip,
+                mac,
+                ip_version: 4,
+            });
         }
     }
 
@@ -162,7 +176,11 @@ pub(crate) fn parse_arp_output(output: &str) -> Vec<DiscoveredDevice> {
                 let mac_raw = rest.split_whitespace().next().unwrap_or("");
                 if !mac_raw.is_empty() && mac_raw != "<incomplete>" {
                     let mac = normalize_mac(mac_raw);
-                    devices.push(DiscoveredDevice { ip, mac });
+                    devices.push(DiscoveredDevice {
+                        ip,
+                        mac,
+                        ip_version: 4,
+                    });
                 }
             }
         }
@@ -0,0 +1,346 @@
+//! TCP banner grabbing for open ports found by a port scan.
+//!
+//! Connects to each open TCP port and records the first bytes the service
+//! sends (SSH version string, FTP/SMTP greeting). HTTP ports are sent a
+//! `GET /` instead and store an [`HttpServiceInfo`] as JSON; if that fails
+//! they fall back to a raw `HEAD` banner.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+/// Maximum concurrent banner connections per scan.
+const BANNER_CONCURRENCY_LIMIT: usize = 8;
+
+/// Overall connect + read budget per port.
+const BANNER_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Maximum banner length in bytes.
+const BANNER_MAX_BYTES: usize = 256;
+
+/// Ports that only answer after a request, so they are sent a `HEAD`.
+const HTTP_PORTS: &[u16] = &[80, 443, 8080, 8443];
+
+const HTTP_HEAD_REQUEST: &[u8] = b"HEAD / HTTP/1.0\r\n\r\n";
+
+/// HTTP ports spoken over TLS.
+pub(crate) const HTTPS_PORTS: &[u16] = &[443, 8443];
+
+/// Body bytes read while looking for `<title>`.
+const HTTP_BODY_MAX_BYTES: usize = 16 * 1024;
+
+/// Maximum stored title length in bytes.
+const TITLE_MAX_BYTES: usize = 200;
+
+/// What an HTTP(S) service answered to `GET /`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HttpServiceInfo {
+    pub port: u16,
+    pub status_code: u16,
+    pub server_header: Option<String>,
+    pub powered_by: Option<String>,
+    pub content_type: Option<String>,
+    pub title: Option<String>,
+    pub tls: bool,
+}
+
+/// Text of the first `<title>` element, whitespace-collapsed and cut to
+/// [`TITLE_MAX_BYTES`].
+pub fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = lower[start..]
+        .find("</title")
+        .map_or(html.len(), |i| start + i);
+    let title = html[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut cut = title.len().min(TITLE_MAX_BYTES);
+    while !title.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let title = &title[..cut];
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Send `GET /` to `ip:port` (over TLS if `tls`, accepting any certificate)
+/// within [`BANNER_TIMEOUT`].
+pub async fn probe_http(ip: IpAddr, port: u16, tls: bool) -> Option<HttpServiceInfo> {
+    let client = reqwest::Client::builder()
+        .timeout(BANNER_TIMEOUT)
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let scheme = if tls { "https" } else { "http" };
+    let url = format!("{scheme}://{}/", SocketAddr::new(ip, port));
+
+    let result = tokio::time::timeout(BANNER_TIMEOUT, async {
+        let mut response = client
+            .get(&url)
+            .header(reqwest::header::CONNECTION, "close")
+            .send()
+            .await?;
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let server_header = header(reqwest::header::SERVER);
+        let powered_by = header(reqwest::header::HeaderName::from_static("x-powered-by"));
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let status_code = response.status().as_u16();
+
+        let mut body = Vec::new();
+        while body.len() < HTTP_BODY_MAX_BYTES {
+            match response.chunk().await? {
+                Some(chunk) => body.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        Ok::<_, reqwest::Error>(HttpServiceInfo {
+            port,
+            status_code,
+            server_header,
+            powered_by,
+            content_type,
+            title: extract_title(&String::from_utf8_lossy(&body)),
+            tls,
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(info)) => Some(info),
+        Ok(Err(e)) => {
+            debug!(%ip, port, error = %e, "HTTP service probe failed");
+            None
+        }
+        Err(_) => {
+            debug!(%ip, port, "HTTP service probe timed out");
+            None
+        }
+    }
+}
+
+/// Banner for `port`: HTTP service info as JSON for HTTP ports, else (or if
+/// that fails) the raw bytes the service sends.
+async fn port_banner(ip: IpAddr, port: u16) -> Option<String> {
+    if HTTP_PORTS.contains(&port) {
+        let probe = probe_http(ip, port, HTTPS_PORTS.contains(&port)).await;
+        if let Some(json) = probe.and_then(|info| serde_json::to_string(&info).ok()) {
+            return Some(json);
+        }
+    }
+    grab_banner(ip, port).await
+}
+
+/// Turn raw banner bytes into storable text: invalid UTF-8 is replaced,
+/// NUL bytes are dropped and surrounding whitespace trimmed.
+pub fn sanitize_banner(raw: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(raw).replace('\0', "");
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Read up to [`BANNER_MAX_BYTES`] from `ip:port` within [`BANNER_TIMEOUT`].
+///
+/// Whatever arrived before the timeout is kept, so slow services still
+/// yield a partial banner.
+pub async fn grab_banner(ip: IpAddr, port: u16) -> Option<String> {
+    let mut buf = Vec::with_capacity(BANNER_MAX_BYTES);
+
+    let _ = tokio::time::timeout(BANNER_TIMEOUT, async {
+        let mut stream = TcpStream::connect(SocketAddr::new(ip, port)).await?;
+        if HTTP_PORTS.contains(&port) {
+            stream.write_all(HTTP_HEAD_REQUEST).await?;
+        }
+        let mut chunk = [0u8; BANNER_MAX_BYTES];
+        while buf.len() < BANNER_MAX_BYTES {
+            let n = stream
+                .read(&mut chunk[..BANNER_MAX_BYTES - buf.len()])
+                .await?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    })
+    .await;
+
+    sanitize_banner(&buf)
+}
+
+/// Grab banners for `ports` on `ip` and store them against `scan_id`.
+pub async fn grab_and_store(db: &SqlitePool, scan_id: i64, ip: IpAddr, ports: Vec<u16>) {
+    let mut join_set: JoinSet<(u16, Option<String>)> = JoinSet::new();
+    let mut banners: Vec<(u16, String)> = Vec::new();
+
+    let mut collect = |result: Result<(u16, Option<String>), tokio::task::JoinError>| match result {
+        Ok((port, Some(banner))) => banners.push((port, banner)),
+        Ok((port, None)) => debug!(%ip, port, "No banner received"),
+        Err(e) => warn!(error = %e, "Banner grab task failed"),
+    };
+
+    for port in ports {
+        // Limit concurrency: when at the cap, wait for one to finish before spawning.
+        if join_set.len() >= BANNER_CONCURRENCY_LIMIT {
+            if let Some(result) = join_set.join_next().await {
+                collect(result);
+            }
+        }
+        join_set.spawn(async move { (port, port_banner(ip, port).await) });
+    }
+    while let Some(result) = join_set.join_next().await {
+        collect(result);
+    }
+
+    for (port, banner) in banners {
+        if let Err(e) = sqlx::query(
+            "INSERT OR REPLACE INTO port_scan_banners (scan_id, port, banner) VALUES (?, ?, ?)",
+        )
+        .bind(scan_id)
+        .bind(port)
+        .bind(&banner)
+        .execute(db)
+        .await
+        {
+            warn!(scan_id, port, error = %e, "Failed to store port banner");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_sanitize_banner() {
+        assert_eq!(
+            sanitize_banner(b"SSH-2.0-OpenSSH_9.2\r\n").as_deref(),
+            Some("SSH-2.0-OpenSSH_9.2")
+        );
+        assert_eq!(
+            sanitize_banner(b"220 ftp\0ready\xff\r\n").as_deref(),
+            Some("220 ftpready\u{fffd}")
+        );
+        assert_eq!(sanitize_banner(b"\0\0 \r\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_grab_banner_reads_greeting() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"SSH-2.0-Test_1.0\r\n").await.unwrap();
+        });
+
+        let banner = grab_banner(addr.ip(), addr.port()).await;
+        assert_eq!(banner.as_deref(), Some("SSH-2.0-Test_1.0"));
+    }
+
+    #[tokio::test]
+    async fn test_grab_banner_closed_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        assert!(grab_banner(addr.ip(), addr.port()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_grab_and_store() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) \
+             VALUES ('d1', 'aa:bb:cc:00:00:61', datetime('now'), datetime('now'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let scan_id: i64 = sqlx::query_scalar(
+            "INSERT INTO port_scans (device_id, result_json) VALUES ('d1', '[]') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"220 mail ESMTP\r\n").await.unwrap();
+        });
+
+        grab_and_store(&pool, scan_id, addr.ip(), vec![addr.port()]).await;
+
+        let stored: Vec<(i64, String)> =
+            sqlx::query_as("SELECT port, banner FROM port_scan_banners WHERE scan_id = ?")
+                .bind(scan_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            stored,
+            vec![(addr.port() as i64, "220 mail ESMTP".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(
+            extract_title("<html><head><TITLE class=\"x\">\n  Router   Login\n</title>").as_deref(),
+            Some("Router Login")
+        );
+        assert_eq!(extract_title("<title></title>"), None);
+        assert_eq!(extract_title("<h1>no title</h1>"), None);
+        let long = format!("<title>{}</title>", "é".repeat(150));
+        assert_eq!(extract_title(&long).unwrap().len(), TITLE_MAX_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_probe_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await.unwrap();
+            let body = "<html><title>NAS Admin</title></html>";
+            let response = format!(
+                "HTTP/1.1 401 Unauthorized\r\nServer: lighttpd/1.4\r\nX-Powered-By: PHP/8.2\r\n\
+                 Content-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let info = probe_http(addr.ip(), addr.port(), false).await.unwrap();
+        assert_eq!(
+            info,
+            HttpServiceInfo {
+                port: addr.port(),
+                status_code: 401,
+                server_header: Some("lighttpd/1.4".to_string()),
+                powered_by: Some("PHP/8.2".to_string()),
+                content_type: Some("text/html".to_string()),
+                title: Some("NAS Admin".to_string()),
+                tls: false,
+            }
+        );
+    }
+}
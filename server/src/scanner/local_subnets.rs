@@ -0,0 +1,115 @@
+//! Subnet auto-detection from the host's network interfaces, used when no
+//! `[scanner] subnets` are configured.
+
+use std::net::Ipv4Addr;
+
+use ipnetwork::Ipv4Network;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::config::ScannerConfig;
+
+/// Interface name prefixes of container and VM bridges, which are not scanned.
+const IGNORED_INTERFACE_PREFIXES: &[&str] = &["docker", "veth", "virbr"];
+
+/// An IPv4 subnet the host is directly attached to.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LocalSubnet {
+    pub interface: String,
+    /// Network address in CIDR notation, e.g. `192.168.1.0/24`.
+    pub subnet: String,
+}
+
+/// The subnet for one interface address, or `None` if it should be skipped.
+fn subnet_for(interface: &str, ip: Ipv4Addr, prefix: u8) -> Option<LocalSubnet> {
+    if ip.is_loopback() || ip.is_link_local() {
+        return None;
+    }
+    if IGNORED_INTERFACE_PREFIXES
+        .iter()
+        .any(|p| interface.starts_with(p))
+    {
+        return None;
+    }
+    let network = Ipv4Network::new(ip, prefix).ok()?;
+    Some(LocalSubnet {
+        interface: interface.to_string(),
+        subnet: format!("{}/{}", network.network(), prefix),
+    })
+}
+
+/// Non-loopback IPv4 subnets of the host's interfaces, one entry per subnet.
+pub fn detect() -> Vec<LocalSubnet> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(i) => i,
+        Err(e) => {
+            warn!(error = %e, "Cannot enumerate network interfaces");
+            return Vec::new();
+        }
+    };
+
+    let mut subnets: Vec<LocalSubnet> = Vec::new();
+    for iface in interfaces {
+        let if_addrs::IfAddr::V4(ref v4) = iface.addr else {
+            continue;
+        };
+        if let Some(subnet) = subnet_for(&iface.name, v4.ip, v4.prefixlen) {
+            if !subnets.iter().any(|s| s.subnet == subnet.subnet) {
+                subnets.push(subnet);
+            }
+        }
+    }
+    subnets
+}
+
+/// Fill in auto-detected subnets when none are configured. Returns `true`
+/// if detection was used.
+pub fn fill_if_empty(config: &mut ScannerConfig) -> bool {
+    if !config.subnets.is_empty() {
+        return false;
+    }
+    config.subnets = detect().into_iter().map(|s| s.subnet).collect();
+    debug!(subnets = ?config.subnets, "Auto-detected scan subnets");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_for_masks_host_address() {
+        assert_eq!(
+            subnet_for("eth0", Ipv4Addr::new(192, 168, 1, 23), 24),
+            Some(LocalSubnet {
+                interface: "eth0".to_string(),
+                subnet: "192.168.1.0/24".to_string(),
+            })
+        );
+        assert_eq!(
+            subnet_for("wlan0", Ipv4Addr::new(10, 20, 130, 5), 17)
+                .unwrap()
+                .subnet,
+            "10.20.128.0/17"
+        );
+    }
+
+    #[test]
+    fn test_subnet_for_skips_loopback_and_container_bridges() {
+        assert!(subnet_for("lo", Ipv4Addr::LOCALHOST, 8).is_none());
+        assert!(subnet_for("eth1", Ipv4Addr::new(169, 254, 3, 4), 16).is_none());
+        assert!(subnet_for("docker0", Ipv4Addr::new(172, 17, 0, 1), 16).is_none());
+        assert!(subnet_for("veth1a2b3c", Ipv4Addr::new(172, 18, 0, 1), 16).is_none());
+        assert!(subnet_for("virbr0", Ipv4Addr::new(192, 168, 122, 1), 24).is_none());
+    }
+
+    #[test]
+    fn test_fill_if_empty_keeps_configured_subnets() {
+        let mut config = ScannerConfig {
+            subnets: vec!["10.0.0.0/24".to_string()],
+            ..ScannerConfig::default()
+        };
+        assert!(!fill_if_empty(&mut config));
+        assert_eq!(config.subnets, vec!["10.0.0.0/24"]);
+    }
+}
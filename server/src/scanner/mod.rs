@@ -1,17 +1,27 @@
 pub mod arp;
+pub mod banner;
+pub mod local_subnets;
+pub mod ndp;
+pub mod port_schedule;
+pub mod tls_cert;
 
 use anyhow::Result;
 use chrono::Utc;
 use hickory_resolver::TokioAsyncResolver;
 use serde_json::json;
 use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{watch, Semaphore};
 use tokio::task::JoinSet;
 use tracing::{debug, error, info, warn};
 
-use crate::api::alerts::{is_device_muted, severity_for_alert_type};
+use crate::api::alert_rules::{self, AlertContext, AlertDecision, AlertRuleSet};
+use crate::api::alerts::{is_device_muted, severity_for_alert_type, SeverityOverrideCache};
+use crate::api::maintenance::{self, ActiveWindows};
+use crate::api::metrics::{self, SharedMetrics};
 use crate::config::ScannerConfig;
 
 /// Enrichment target tuple: (device_id, ip, mac, hostname, vendor, mdns_services).
@@ -23,40 +33,91 @@ type EnrichmentTarget = (
     Option<String>,
     Option<String>,
 );
+use crate::notification::{email, telegram};
 use crate::webhook;
 use crate::ws::hub::WsHub;
 
-/// Discovered device from an ARP scan.
+/// Discovered device from an ARP or NDP scan.
 #[derive(Debug, Clone)]
 pub struct DiscoveredDevice {
     pub ip: String,
     pub mac: String,
+    /// 4 for ARP entries, 6 for NDP entries.
+    pub ip_version: u8,
 }
 
-/// Run an ARP scan on the specified subnets.
+/// Run an ARP (and optionally NDP) scan on the configured subnets.
 ///
 /// First performs an active ping sweep on each configured subnet to populate
 /// the kernel ARP table with entries for all reachable hosts, then reads the
 /// ARP table. This discovers devices that would otherwise be invisible to
-/// passive ARP cache reading.
-pub async fn scan_subnets(
-    subnets: &[String],
-    arp_settle_millis: u64,
-) -> Result<Vec<DiscoveredDevice>> {
-    // Phase 0: Active ping sweep — populate the ARP table.
-    for subnet in subnets {
-        arp::ping_sweep(subnet).await;
+/// passive ARP cache reading. With NDP enabled, IPv6 neighbours are read too
+/// and merged by MAC so dual-stack devices carry both addresses.
+pub async fn scan_subnets(config: &ScannerConfig) -> Result<Vec<DiscoveredDevice>> {
+    // Phase 0: Active ping sweep — populate the ARP and neighbour tables.
+    // One semaphore bounds the pings in flight across all subnets.
+    let permits = Arc::new(Semaphore::new(config.ping_concurrency.max(1)));
+    let ping_timeout = Duration::from_millis(config.ping_timeout_ms);
+    for subnet in &config.subnets {
+        arp::ping_sweep(subnet, permits.clone(), ping_timeout).await;
+    }
+    if config.ndp_enabled {
+        for interface in &config.ndp_sweep_interfaces {
+            ndp::multicast_ping(interface).await;
+        }
     }
 
     // Wait for the kernel to finish updating ARP entries.
     // Duration is configurable via panoptikon.toml [scanner] arp_settle_millis.
-    if arp_settle_millis > 0 {
-        tokio::time::sleep(Duration::from_millis(arp_settle_millis)).await;
+    if config.arp_settle_millis > 0 {
+        tokio::time::sleep(Duration::from_millis(config.arp_settle_millis)).await;
     }
 
-    // Phase 1: Read the (now enriched) ARP cache.
-    let devices = arp::read_arp_table().await?;
-    Ok(devices)
+    // Phase 1: Read the (now enriched) ARP cache and neighbour table.
+    let mut devices = arp::read_arp_table().await?;
+    if config.ndp_enabled {
+        match ndp::read_ndp_table().await {
+            Ok(neighbours) => devices.extend(neighbours),
+            Err(e) => debug!(error = %e, "IPv6 neighbour table not available"),
+        }
+    }
+    Ok(merge_by_mac(devices))
+}
+
+/// Group discovered entries by MAC, dropping duplicate addresses.
+///
+/// Within each MAC, IPv4 entries come first so a new dual-stack device is
+/// announced and enriched using its IPv4 address.
+pub(crate) fn merge_by_mac(mut devices: Vec<DiscoveredDevice>) -> Vec<DiscoveredDevice> {
+    devices.sort_by(|a, b| {
+        (a.mac.to_lowercase(), a.ip_version, &a.ip).cmp(&(
+            b.mac.to_lowercase(),
+            b.ip_version,
+            &b.ip,
+        ))
+    });
+    devices.dedup_by(|a, b| a.ip == b.ip && a.mac.eq_ignore_ascii_case(&b.mac));
+    devices
+}
+
+/// Find IPs answered by more than one MAC in a single scan, as
+/// `(ip, first_mac, second_mac)` with one entry per IP.
+pub fn detect_ip_conflicts(discovered: &[DiscoveredDevice]) -> Vec<(String, String, String)> {
+    let mut first_mac: HashMap<&str, String> = HashMap::new();
+    let mut conflicts: Vec<(String, String, String)> = Vec::new();
+    for dev in discovered {
+        let mac = dev.mac.to_lowercase();
+        match first_mac.get(dev.ip.as_str()) {
+            None => {
+                first_mac.insert(&dev.ip, mac);
+            }
+            Some(first) if *first != mac && !conflicts.iter().any(|c| c.0 == dev.ip) => {
+                conflicts.push((dev.ip.clone(), first.clone(), mac));
+            }
+            Some(_) => {}
+        }
+    }
+    conflicts
 }
 
 /// Start the periodic ARP scanner as a background tokio task.
@@ -67,37 +128,77 @@ pub async fn scan_subnets(
 /// 3. Detects online/offline state changes
 /// 4. Creates alerts for new devices, devices going offline, and devices coming back
 /// 5. Broadcasts changes to connected UI clients via the WsHub
-pub fn start_scanner_task(db: SqlitePool, config: ScannerConfig, ws_hub: Arc<WsHub>) {
-    let interval = std::time::Duration::from_secs(config.interval_seconds);
-    let grace = config.offline_grace_seconds;
-    let subnets = config.subnets.clone();
-    let arp_settle_millis = config.arp_settle_millis;
-
+///
+/// A new value on `config_rx` (sent when the config file is reloaded)
+/// restarts the schedule with the new interval and subnets.
+pub fn start_scanner_task(
+    db: SqlitePool,
+    mut config_rx: watch::Receiver<ScannerConfig>,
+    ws_hub: Arc<WsHub>,
+    severities: SeverityOverrideCache,
+    telegram_limiter: telegram::TelegramRateLimiter,
+    metrics: SharedMetrics,
+) {
     tokio::spawn(async move {
-        info!(
-            interval_secs = config.interval_seconds,
-            subnets = ?subnets,
-            "ARP scanner started"
-        );
-
         // Small initial delay to let the server finish starting up.
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
 
-        let mut ticker = tokio::time::interval(interval);
-        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-
+        let mut watching = true;
         loop {
-            ticker.tick().await;
+            let mut config = config_rx.borrow_and_update().clone();
+            if local_subnets::fill_if_empty(&mut config) {
+                warn!("No subnets configured; auto-detected: {:?}", config.subnets);
+            }
+            let grace = config.offline_grace_seconds;
+            info!(
+                interval_secs = config.interval_seconds,
+                subnets = ?config.subnets,
+                "ARP scanner started"
+            );
 
-            match scan_subnets(&subnets, arp_settle_millis).await {
-                Ok(devices) => {
-                    info!(count = devices.len(), "ARP scan completed");
-                    if let Err(e) = process_scan_results(&db, &devices, grace, &ws_hub).await {
-                        error!("Failed to process scan results: {e}");
+            let mut ticker =
+                tokio::time::interval(std::time::Duration::from_secs(config.interval_seconds));
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                tokio::select! {
+                    changed = config_rx.changed(), if watching => {
+                        if changed.is_ok() {
+                            break;
+                        }
+                        // Reloading is unavailable; keep the current schedule.
+                        watching = false;
+                        continue;
                     }
+                    _ = ticker.tick() => {}
                 }
-                Err(e) => {
-                    warn!("ARP scan failed: {e}");
+                let started = std::time::Instant::now();
+
+                match scan_subnets(&config).await {
+                    Ok(devices) => {
+                        info!(count = devices.len(), "ARP scan completed");
+                        if let Err(e) = process_scan_results(
+                            &db,
+                            &devices,
+                            grace,
+                            config.gateway_ip.as_deref(),
+                            &ws_hub,
+                            &severities,
+                            &telegram_limiter,
+                        )
+                        .await
+                        {
+                            error!("Failed to process scan results: {e}");
+                        }
+                        let mut metrics = metrics.lock().await;
+                        metrics
+                            .scan_duration
+                            .observe(started.elapsed().as_secs_f64());
+                        metrics.scan_devices_found = Some(devices.len() as u64);
+                    }
+                    Err(e) => {
+                        warn!("ARP scan failed: {e}");
+                    }
                 }
             }
         }
@@ -175,6 +276,461 @@ async fn update_hostname(
     }
 }
 
+/// Device attributes that alert rule conditions are evaluated against.
+struct AlertFacts {
+    vendor: Option<String>,
+    hostname: Option<String>,
+    last_seen_at: String,
+}
+
+/// Settings key for the alert dedup window in seconds (0 disables dedup).
+const ALERT_DEDUP_WINDOW_KEY: &str = "alert_dedup_window_secs";
+
+/// Default alert dedup window.
+pub const DEFAULT_ALERT_DEDUP_WINDOW_SECS: u64 = 300;
+
+/// Read the alert dedup window from the settings table.
+async fn alert_dedup_window_secs(db: &SqlitePool) -> u64 {
+    sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(ALERT_DEDUP_WINDOW_KEY)
+        .fetch_optional(db)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_ALERT_DEDUP_WINDOW_SECS)
+}
+
+/// An alert ready to be written to the `alerts` table.
+struct NewAlert<'a> {
+    alert_type: &'a str,
+    device_id: &'a str,
+    message: String,
+    details: Option<String>,
+    severity: &'a str,
+}
+
+/// Alert rules, maintenance windows and dedup window applied to every alert
+/// raised in one scan.
+struct AlertPolicy<'a> {
+    rules: &'a AlertRuleSet,
+    /// Devices in a maintenance window get neither stored alerts nor notifications.
+    maintenance: ActiveWindows,
+    now: &'a str,
+    /// Alerts created at or after this instant absorb repeats; `None` disables dedup.
+    dedup_cutoff: Option<String>,
+}
+
+impl AlertPolicy<'_> {
+    /// Whether a maintenance window covers `device_id`.
+    fn in_maintenance(&self, device_id: &str) -> bool {
+        self.maintenance.covers(Some(device_id))
+    }
+
+    /// Insert `alert`, or fold it into an alert of the same `(device_id, type)`
+    /// created within the dedup window by bumping its `count` and `updated_at`.
+    /// Dropped while the device is in a maintenance window.
+    async fn store(&self, conn: &mut sqlx::SqliteConnection, alert: NewAlert<'_>) -> Result<()> {
+        if self.in_maintenance(alert.device_id) {
+            debug!(
+                alert_type = alert.alert_type,
+                device_id = alert.device_id,
+                "Alert suppressed by maintenance window"
+            );
+            return Ok(());
+        }
+        if let Some(ref cutoff) = self.dedup_cutoff {
+            let recent: Option<String> = sqlx::query_scalar(
+                "SELECT id FROM alerts WHERE device_id = ? AND type = ? AND created_at >= ? \
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(alert.device_id)
+            .bind(alert.alert_type)
+            .bind(cutoff)
+            .fetch_optional(&mut *conn)
+            .await?;
+            if let Some(id) = recent {
+                sqlx::query("UPDATE alerts SET count = count + 1, updated_at = ? WHERE id = ?")
+                    .bind(self.now)
+                    .bind(&id)
+                    .execute(&mut *conn)
+                    .await?;
+                debug!(
+                    alert_type = alert.alert_type,
+                    device_id = alert.device_id,
+                    "Deduplicated repeated alert"
+                );
+                return Ok(());
+            }
+        }
+
+        sqlx::query(
+            r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(alert.alert_type)
+        .bind(alert.device_id)
+        .bind(alert.message)
+        .bind(alert.details)
+        .bind(alert.severity)
+        .bind(self.now)
+        .execute(&mut *conn)
+        .await?;
+        metrics::record_alert_created(alert.alert_type);
+        Ok(())
+    }
+}
+
+/// Everything needed to raise a `device_offline` alert during Phase 2.
+struct OfflineAlert<'a> {
+    db: &'a SqlitePool,
+    telegram_limiter: &'a telegram::TelegramRateLimiter,
+    policy: &'a AlertPolicy<'a>,
+    severity: &'a str,
+    now_ts: chrono::DateTime<Utc>,
+}
+
+impl OfflineAlert<'_> {
+    /// Store and send a `device_offline` alert unless a rule suppresses it.
+    /// Muted devices are notified but get no stored alert, as before rules existed.
+    async fn raise(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+        device_id: &str,
+        mac: &str,
+        ip: Option<&str>,
+        facts: &AlertFacts,
+    ) -> Result<()> {
+        let decision = self.policy.rules.decide(
+            "device_offline",
+            &AlertContext {
+                mac,
+                ip,
+                vendor: facts.vendor.as_deref(),
+                hostname: facts.hostname.as_deref(),
+                offline_secs: secs_since(&facts.last_seen_at, self.now_ts),
+            },
+        );
+        let Some(severity) = decision.severity_or(self.severity) else {
+            return Ok(());
+        };
+
+        if !is_device_muted(&mut *conn, device_id).await {
+            let alert = NewAlert {
+                alert_type: "device_offline",
+                device_id,
+                message: format!("Device {} went offline", mac),
+                details: None,
+                severity,
+            };
+            self.policy.store(&mut *conn, alert).await?;
+        }
+
+        if self.policy.in_maintenance(device_id) {
+            return Ok(());
+        }
+        notify_alert(
+            self.db,
+            self.telegram_limiter,
+            "device_offline",
+            json!({
+                "device_id": device_id,
+                "mac": mac,
+            }),
+            mac,
+            ip,
+        );
+        Ok(())
+    }
+}
+
+/// Severities for the address-change alerts raised during Phase 1.
+struct AddressAlertSeverities {
+    mac_conflict: String,
+    arp_spoofing: String,
+    ip_conflict: String,
+    ip_change: String,
+}
+
+/// Insert an address-change alert for `device_id` unless the device is muted
+/// or an alert rule suppresses it.
+#[allow(clippy::too_many_arguments)]
+async fn insert_address_alert(
+    conn: &mut sqlx::SqliteConnection,
+    alert_type: &str,
+    device_id: &str,
+    ctx: &AlertContext<'_>,
+    message: String,
+    details: serde_json::Value,
+    severity: &str,
+    policy: &AlertPolicy<'_>,
+) -> Result<()> {
+    let decision = policy.rules.decide(alert_type, ctx);
+    let Some(severity) = decision.severity_or(severity) else {
+        return Ok(());
+    };
+    if is_device_muted(&mut *conn, device_id).await {
+        return Ok(());
+    }
+    let alert = NewAlert {
+        alert_type,
+        device_id,
+        message,
+        details: Some(details.to_string()),
+        severity,
+    };
+    policy.store(conn, alert).await
+}
+
+/// Detect an existing device moving to a new IPv4 address.
+///
+/// Must run before the device's `device_ips` upsert. Fires when `dev.ip` was not
+/// already a current address of the device and the most recently seen IPv4
+/// address from an earlier scan differs from it. The superseded addresses are
+/// marked not current. IPv6 is skipped because privacy addresses rotate on
+/// their own.
+async fn check_ip_change(
+    conn: &mut sqlx::SqliteConnection,
+    device_id: &str,
+    mac: &str,
+    dev: &DiscoveredDevice,
+    now: &str,
+    severity: &str,
+    policy: &AlertPolicy<'_>,
+) -> Result<()> {
+    if dev.ip_version != 4 {
+        return Ok(());
+    }
+
+    let already_current: Option<i64> = sqlx::query_scalar(
+        "SELECT 1 FROM device_ips WHERE device_id = ? AND ip = ? AND is_current = 1",
+    )
+    .bind(device_id)
+    .bind(&dev.ip)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if already_current.is_some() {
+        return Ok(());
+    }
+
+    // Addresses already refreshed in this scan cycle are not "previous".
+    let previous_ip: Option<String> = sqlx::query_scalar(
+        "SELECT ip FROM device_ips \
+         WHERE device_id = ? AND ip_version = 4 AND seen_at != ? \
+         ORDER BY seen_at DESC LIMIT 1",
+    )
+    .bind(device_id)
+    .bind(now)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(previous_ip) = previous_ip.filter(|ip| *ip != dev.ip) else {
+        return Ok(());
+    };
+
+    sqlx::query(
+        "UPDATE device_ips SET is_current = 0 \
+         WHERE device_id = ? AND ip_version = 4 AND seen_at != ?",
+    )
+    .bind(device_id)
+    .bind(now)
+    .execute(&mut *conn)
+    .await?;
+
+    let ctx = AlertContext {
+        mac,
+        ip: Some(&dev.ip),
+        ..Default::default()
+    };
+    insert_address_alert(
+        conn,
+        "ip_change",
+        device_id,
+        &ctx,
+        format!("Device {mac} moved from {previous_ip} to {}", dev.ip),
+        json!({"mac": mac, "previous_ip": &previous_ip, "new_ip": &dev.ip}),
+        severity,
+        policy,
+    )
+    .await?;
+    info!(mac = %mac, from = %previous_ip, to = %dev.ip, "Device IP changed");
+    Ok(())
+}
+
+/// Detect a different MAC taking over an IP that another device currently holds.
+///
+/// The other device's mapping is marked not current so the conflict is
+/// reported once rather than on every scan. When the IP is the configured
+/// gateway this is reported as possible ARP spoofing instead, and broadcast
+/// to WebSocket clients straight away.
+#[allow(clippy::too_many_arguments)]
+async fn check_mac_conflict(
+    conn: &mut sqlx::SqliteConnection,
+    device_id: &str,
+    mac: &str,
+    dev: &DiscoveredDevice,
+    now: &str,
+    gateway_ip: Option<&str>,
+    ws_hub: &WsHub,
+    severities: &AddressAlertSeverities,
+    policy: &AlertPolicy<'_>,
+) -> Result<()> {
+    let holders: Vec<(String, String)> = sqlx::query_as(
+        "SELECT d.id, d.mac FROM device_ips di JOIN devices d ON d.id = di.device_id \
+         WHERE di.ip = ? AND di.is_current = 1 AND di.device_id != ?",
+    )
+    .bind(&dev.ip)
+    .bind(device_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let is_gateway = gateway_ip == Some(dev.ip.as_str());
+    for (previous_device_id, previous_mac) in holders {
+        sqlx::query("UPDATE device_ips SET is_current = 0 WHERE device_id = ? AND ip = ?")
+            .bind(&previous_device_id)
+            .bind(&dev.ip)
+            .execute(&mut *conn)
+            .await?;
+
+        let ctx = AlertContext {
+            mac,
+            ip: Some(&dev.ip),
+            ..Default::default()
+        };
+        let details = json!({
+            "ip": &dev.ip,
+            "previous_mac": &previous_mac,
+            "previous_device_id": &previous_device_id,
+            "new_mac": mac,
+            "detected_at": now,
+        });
+
+        if is_gateway {
+            insert_address_alert(
+                conn,
+                "arp_spoofing",
+                device_id,
+                &ctx,
+                format!(
+                    "Possible ARP spoofing: gateway IP {} moved from MAC {previous_mac} to MAC {mac}",
+                    dev.ip
+                ),
+                details.clone(),
+                &severities.arp_spoofing,
+                policy,
+            )
+            .await?;
+            ws_hub.broadcast("arp_spoofing", details);
+            warn!(ip = %dev.ip, previous_mac = %previous_mac, new_mac = %mac, "Possible ARP spoofing of gateway");
+        } else {
+            insert_address_alert(
+                conn,
+                "mac_conflict",
+                device_id,
+                &ctx,
+                format!("IP {} moved from {previous_mac} to {mac}", dev.ip),
+                details,
+                &severities.mac_conflict,
+                policy,
+            )
+            .await?;
+            warn!(ip = %dev.ip, previous_mac = %previous_mac, new_mac = %mac, "MAC conflict detected");
+        }
+    }
+    Ok(())
+}
+
+/// Raise an `ip_conflict` alert for two MACs answering on `ip` in one scan.
+///
+/// The devices may not exist yet, so the alert has no `device_id`; repeats
+/// within the dedup window are folded by IP instead.
+async fn record_ip_conflict(
+    conn: &mut sqlx::SqliteConnection,
+    (ip, first_mac, second_mac): &(String, String, String),
+    severity: &str,
+    policy: &AlertPolicy<'_>,
+) -> Result<()> {
+    let ctx = AlertContext {
+        mac: second_mac,
+        ip: Some(ip),
+        ..Default::default()
+    };
+    let decision = policy.rules.decide("ip_conflict", &ctx);
+    let Some(severity) = decision.severity_or(severity) else {
+        return Ok(());
+    };
+    warn!(ip = %ip, first_mac = %first_mac, second_mac = %second_mac, "Duplicate IP detected");
+    if policy.maintenance.covers(None) {
+        return Ok(());
+    }
+
+    if let Some(ref cutoff) = policy.dedup_cutoff {
+        let recent: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM alerts WHERE type = 'ip_conflict' AND device_id IS NULL \
+             AND json_extract(details, '$.ip') = ? AND created_at >= ? \
+             ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(ip)
+        .bind(cutoff)
+        .fetch_optional(&mut *conn)
+        .await?;
+        if let Some(id) = recent {
+            sqlx::query("UPDATE alerts SET count = count + 1, updated_at = ? WHERE id = ?")
+                .bind(policy.now)
+                .bind(&id)
+                .execute(&mut *conn)
+                .await?;
+            return Ok(());
+        }
+    }
+
+    sqlx::query(
+        r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+         VALUES (?, 'ip_conflict', NULL, ?, ?, ?, ?)"#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(format!(
+        "IP {ip} is used by both {first_mac} and {second_mac}"
+    ))
+    .bind(json!({"ip": ip, "macs": [first_mac, second_mac]}).to_string())
+    .bind(severity)
+    .bind(policy.now)
+    .execute(&mut *conn)
+    .await?;
+    metrics::record_alert_created("ip_conflict");
+    Ok(())
+}
+
+/// Seconds elapsed since `timestamp` (RFC 3339 or SQLite `datetime()` format).
+fn secs_since(timestamp: &str, now: chrono::DateTime<Utc>) -> Option<u64> {
+    let then = chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                .map(|t| t.and_utc())
+        })
+        .ok()?;
+    u64::try_from((now - then).num_seconds()).ok()
+}
+
+/// Send an alert to the webhook, email and Telegram channels.
+fn notify_alert(
+    db: &SqlitePool,
+    telegram_limiter: &telegram::TelegramRateLimiter,
+    alert_type: &str,
+    payload: serde_json::Value,
+    mac: &str,
+    ip: Option<&str>,
+) {
+    webhook::dispatch_webhook(db, alert_type, payload);
+    email::dispatch_alert(db, alert_type, mac, ip);
+    telegram::dispatch(
+        db,
+        telegram_limiter,
+        telegram::format_device_alert(alert_type, mac, ip),
+    );
+}
+
 /// Process ARP scan results: upsert devices, detect state changes, create alerts.
 ///
 /// All database mutations (device upserts, state changes, alerts, offline detection)
@@ -187,13 +743,40 @@ async fn update_hostname(
 /// Reverse DNS lookups (best-effort hostname enrichment) run *after* the transaction
 /// commits, outside the transaction boundary, since they are non-critical and involve
 /// network I/O that would hold the transaction open unnecessarily.
+///
+/// Every alert is checked against the enabled [`alert_rules`] first; a
+/// suppressed alert is neither stored nor sent to notification channels.
 pub async fn process_scan_results(
     db: &SqlitePool,
     discovered: &[DiscoveredDevice],
     offline_grace_secs: u64,
+    gateway_ip: Option<&str>,
     ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+    telegram_limiter: &telegram::TelegramRateLimiter,
 ) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
+    let now_ts = Utc::now();
+    let now = now_ts.to_rfc3339();
+
+    // Resolve alert severities up front so no extra DB reads happen inside the transaction.
+    let new_device_severity = severity_for_alert_type("new_device", db, severities).await;
+    let online_severity = severity_for_alert_type("device_online", db, severities).await;
+    let offline_severity = severity_for_alert_type("device_offline", db, severities).await;
+    let address_severities = AddressAlertSeverities {
+        mac_conflict: severity_for_alert_type("mac_conflict", db, severities).await,
+        arp_spoofing: severity_for_alert_type("arp_spoofing", db, severities).await,
+        ip_conflict: severity_for_alert_type("ip_conflict", db, severities).await,
+        ip_change: severity_for_alert_type("ip_change", db, severities).await,
+    };
+    let rules = alert_rules::load_rule_set(db).await;
+    let dedup_window = alert_dedup_window_secs(db).await;
+    let policy = AlertPolicy {
+        rules: &rules,
+        maintenance: maintenance::load_active(db, &now).await,
+        now: &now,
+        dedup_cutoff: (dedup_window > 0)
+            .then(|| (now_ts - chrono::Duration::seconds(dedup_window as i64)).to_rfc3339()),
+    };
 
     // Pairs of (device_id, ip) collected during upsert for batch DNS resolution.
     let mut dns_targets: Vec<(String, String)> = Vec::new();
@@ -204,27 +787,39 @@ pub async fn process_scan_results(
     // Begin a single transaction for all DB mutations (Phase 1 + Phase 2).
     let mut tx = db.begin().await?;
 
+    // --- Duplicate IPs within this scan, checked before any state changes ---
+    for conflict in detect_ip_conflicts(discovered) {
+        record_ip_conflict(&mut tx, &conflict, &address_severities.ip_conflict, &policy).await?;
+    }
+
     // --- Phase 1: Upsert discovered devices ---
     for dev in discovered {
         let mac_normalized = dev.mac.to_lowercase();
 
         // Check if device already exists.
-        let existing: Option<(String, bool)> =
-            sqlx::query("SELECT id, is_online FROM devices WHERE mac = ?")
-                .bind(&mac_normalized)
-                .fetch_optional(&mut *tx)
-                .await?
-                .map(|row| {
-                    let id: String = sqlx::Row::get(&row, "id");
-                    let is_online: bool = sqlx::Row::get::<i32, _>(&row, "is_online") != 0;
-                    (id, is_online)
-                });
+        let existing: Option<(String, bool, AlertFacts)> = sqlx::query(
+            "SELECT id, is_online, vendor, hostname, last_seen_at FROM devices WHERE mac = ?",
+        )
+        .bind(&mac_normalized)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(|row| {
+            let id: String = sqlx::Row::get(&row, "id");
+            let is_online: bool = sqlx::Row::get::<i32, _>(&row, "is_online") != 0;
+            let facts = AlertFacts {
+                vendor: sqlx::Row::get(&row, "vendor"),
+                hostname: sqlx::Row::get(&row, "hostname"),
+                last_seen_at: sqlx::Row::get(&row, "last_seen_at"),
+            };
+            (id, is_online, facts)
+        });
 
         let device_id = match existing {
-            Some((device_id, was_online)) => {
+            Some((device_id, was_online, facts)) => {
                 // Update last_seen_at and mark online.
                 sqlx::query(
-                    "UPDATE devices SET last_seen_at = ?, is_online = 1, updated_at = ? WHERE id = ?",
+                    "UPDATE devices SET last_seen_at = ?, is_online = 1, is_deleted = 0, updated_at = ? \
+                     WHERE id = ?",
                 )
                 .bind(&now)
                 .bind(&now)
@@ -232,15 +827,27 @@ pub async fn process_scan_results(
                 .execute(&mut *tx)
                 .await?;
 
+                check_ip_change(
+                    &mut tx,
+                    &device_id,
+                    &mac_normalized,
+                    dev,
+                    &now,
+                    &address_severities.ip_change,
+                    &policy,
+                )
+                .await?;
+
                 // Upsert device_ips.
                 sqlx::query(
-                    "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
-                     VALUES (?, ?, ?, 1) \
+                    "INSERT INTO device_ips (device_id, ip, seen_at, is_current, ip_version) \
+                     VALUES (?, ?, ?, 1, ?) \
                      ON CONFLICT(device_id, ip) DO UPDATE SET seen_at = ?, is_current = 1",
                 )
                 .bind(&device_id)
                 .bind(&dev.ip)
                 .bind(&now)
+                .bind(dev.ip_version)
                 .bind(&now)
                 .execute(&mut *tx)
                 .await?;
@@ -265,24 +872,32 @@ pub async fn process_scan_results(
                     .execute(&mut *tx)
                     .await?;
 
-                    // Create alert (skip if device is muted).
-                    if !is_device_muted(&mut *tx, &device_id).await {
-                        let alert_id = uuid::Uuid::new_v4().to_string();
-                        let severity = severity_for_alert_type("device_online");
-                        sqlx::query(
-                            r#"INSERT INTO alerts (id, type, device_id, message, severity, created_at)
-                             VALUES (?, 'device_online', ?, ?, ?, ?)"#,
-                        )
-                        .bind(&alert_id)
-                        .bind(&device_id)
-                        .bind(format!(
-                            "Device {} ({}) came back online",
-                            mac_normalized, dev.ip
-                        ))
-                        .bind(severity)
-                        .bind(&now)
-                        .execute(&mut *tx)
-                        .await?;
+                    let decision = policy.rules.decide(
+                        "device_online",
+                        &AlertContext {
+                            mac: &mac_normalized,
+                            ip: Some(&dev.ip),
+                            vendor: facts.vendor.as_deref(),
+                            hostname: facts.hostname.as_deref(),
+                            offline_secs: secs_since(&facts.last_seen_at, now_ts),
+                        },
+                    );
+
+                    // Create alert (skip if device is muted or a rule suppresses it).
+                    if let Some(severity) = decision.severity_or(&online_severity) {
+                        if !is_device_muted(&mut *tx, &device_id).await {
+                            let alert = NewAlert {
+                                alert_type: "device_online",
+                                device_id: &device_id,
+                                message: format!(
+                                    "Device {} ({}) came back online",
+                                    mac_normalized, dev.ip
+                                ),
+                                details: None,
+                                severity,
+                            };
+                            policy.store(&mut tx, alert).await?;
+                        }
                     }
 
                     info!(mac = %mac_normalized, ip = %dev.ip, "Device came back online");
@@ -296,15 +911,20 @@ pub async fn process_scan_results(
                         }),
                     );
 
-                    webhook::dispatch_webhook(
-                        db,
-                        "device_online",
-                        json!({
-                            "device_id": &device_id,
-                            "mac": &mac_normalized,
-                            "ip": &dev.ip,
-                        }),
-                    );
+                    if decision != AlertDecision::Suppress && !policy.in_maintenance(&device_id) {
+                        notify_alert(
+                            db,
+                            telegram_limiter,
+                            "device_online",
+                            json!({
+                                "device_id": &device_id,
+                                "mac": &mac_normalized,
+                                "ip": &dev.ip,
+                            }),
+                            &mac_normalized,
+                            Some(&dev.ip),
+                        );
+                    }
                 }
 
                 device_id
@@ -312,7 +932,7 @@ pub async fn process_scan_results(
             None => {
                 // New device discovered.
                 let device_id = uuid::Uuid::new_v4().to_string();
-                let vendor = crate::oui::lookup(&mac_normalized).map(|v| v.to_string());
+                let vendor = crate::oui::lookup(&mac_normalized);
 
                 sqlx::query(
                     "INSERT INTO devices (id, mac, vendor, first_seen_at, last_seen_at, is_online) \
@@ -328,11 +948,13 @@ pub async fn process_scan_results(
 
                 // Insert IP mapping.
                 sqlx::query(
-                    "INSERT INTO device_ips (device_id, ip, seen_at, is_current) VALUES (?, ?, ?, 1)",
+                    "INSERT INTO device_ips (device_id, ip, seen_at, is_current, ip_version) \
+                     VALUES (?, ?, ?, 1, ?)",
                 )
                 .bind(&device_id)
                 .bind(&dev.ip)
                 .bind(&now)
+                .bind(dev.ip_version)
                 .execute(&mut *tx)
                 .await?;
 
@@ -354,28 +976,33 @@ pub async fn process_scan_results(
                 .execute(&mut *tx)
                 .await?;
 
-                // Create alert for new unknown device.
-                let alert_id = uuid::Uuid::new_v4().to_string();
+                // Create alert for new unknown device (unless a rule suppresses it).
+                let decision = policy.rules.decide(
+                    "new_device",
+                    &AlertContext {
+                        mac: &mac_normalized,
+                        ip: Some(&dev.ip),
+                        vendor: vendor.as_deref(),
+                        ..Default::default()
+                    },
+                );
                 let vendor_str = vendor.as_deref().unwrap_or("Unknown");
-                let severity = severity_for_alert_type("new_device");
-                sqlx::query(
-                    r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
-                     VALUES (?, 'new_device', ?, ?, ?, ?, ?)"#,
-                )
-                .bind(&alert_id)
-                .bind(&device_id)
-                .bind(format!(
-                    "New device discovered: {} ({}) — {}",
-                    mac_normalized, dev.ip, vendor_str
-                ))
-                .bind(
-                    json!({"mac": &mac_normalized, "ip": &dev.ip, "vendor": vendor_str})
-                        .to_string(),
-                )
-                .bind(severity)
-                .bind(&now)
-                .execute(&mut *tx)
-                .await?;
+                if let Some(severity) = decision.severity_or(&new_device_severity) {
+                    let alert = NewAlert {
+                        alert_type: "new_device",
+                        device_id: &device_id,
+                        message: format!(
+                            "New device discovered: {} ({}) — {}",
+                            mac_normalized, dev.ip, vendor_str
+                        ),
+                        details: Some(
+                            json!({"mac": &mac_normalized, "ip": &dev.ip, "vendor": vendor_str})
+                                .to_string(),
+                        ),
+                        severity,
+                    };
+                    policy.store(&mut tx, alert).await?;
+                }
 
                 info!(
                     mac = %mac_normalized,
@@ -394,23 +1021,46 @@ pub async fn process_scan_results(
                     }),
                 );
 
-                webhook::dispatch_webhook(
-                    db,
-                    "new_device",
-                    json!({
-                        "device_id": &device_id,
-                        "mac": &mac_normalized,
-                        "ip": &dev.ip,
-                        "vendor": vendor_str,
-                    }),
-                );
+                if decision != AlertDecision::Suppress && !policy.in_maintenance(&device_id) {
+                    notify_alert(
+                        db,
+                        telegram_limiter,
+                        "new_device",
+                        json!({
+                            "device_id": &device_id,
+                            "mac": &mac_normalized,
+                            "ip": &dev.ip,
+                            "vendor": vendor_str,
+                        }),
+                        &mac_normalized,
+                        Some(&dev.ip),
+                    );
+                }
 
                 device_id
             }
         };
 
+        check_mac_conflict(
+            &mut tx,
+            &device_id,
+            &mac_normalized,
+            dev,
+            &now,
+            gateway_ip,
+            ws_hub,
+            &address_severities,
+            &policy,
+        )
+        .await?;
+
         dns_targets.push((device_id.clone(), dev.ip.clone()));
 
+        // Dual-stack devices appear once per address; enrich them only once.
+        if enrichment_targets.iter().any(|t| t.0 == device_id) {
+            continue;
+        }
+
         // Collect enrichment target: (device_id, ip, mac, hostname, vendor, mdns_services)
         let hostname: Option<String> =
             sqlx::query_scalar("SELECT hostname FROM devices WHERE id = ?")
@@ -446,20 +1096,44 @@ pub async fn process_scan_results(
     let grace_cutoff =
         (Utc::now() - chrono::Duration::seconds(offline_grace_secs as i64)).to_rfc3339();
 
-    let stale_devices: Vec<(String, String)> =
-        sqlx::query("SELECT id, mac FROM devices WHERE is_online = 1 AND last_seen_at < ?")
-            .bind(&grace_cutoff)
-            .fetch_all(&mut *tx)
-            .await?
-            .into_iter()
-            .map(|row| {
-                let id: String = sqlx::Row::get(&row, "id");
-                let mac: String = sqlx::Row::get(&row, "mac");
-                (id, mac)
-            })
-            .collect();
-
-    for (device_id, mac) in &stale_devices {
+    let stale_devices: Vec<(String, String, AlertFacts)> = sqlx::query(
+        "SELECT id, mac, vendor, hostname, last_seen_at FROM devices \
+         WHERE is_online = 1 AND is_deleted = 0 AND last_seen_at < ?",
+    )
+    .bind(&grace_cutoff)
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let id: String = sqlx::Row::get(&row, "id");
+        let mac: String = sqlx::Row::get(&row, "mac");
+        let facts = AlertFacts {
+            vendor: sqlx::Row::get(&row, "vendor"),
+            hostname: sqlx::Row::get(&row, "hostname"),
+            last_seen_at: sqlx::Row::get(&row, "last_seen_at"),
+        };
+        (id, mac, facts)
+    })
+    .collect();
+
+    let offline_alert = OfflineAlert {
+        db,
+        telegram_limiter,
+        policy: &policy,
+        severity: &offline_severity,
+        now_ts,
+    };
+
+    for (device_id, mac, facts) in &stale_devices {
+        // Last known IP for the offline notification, read before it is cleared.
+        let last_ip: Option<String> = sqlx::query_scalar(
+            "SELECT ip FROM device_ips WHERE device_id = ? AND is_current = 1 \
+             ORDER BY seen_at DESC LIMIT 1",
+        )
+        .bind(device_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
         // Mark offline.
         sqlx::query("UPDATE devices SET is_online = 0, updated_at = ? WHERE id = ?")
             .bind(&now)
@@ -491,23 +1165,6 @@ pub async fn process_scan_results(
         .execute(&mut *tx)
         .await?;
 
-        // Create alert (skip if device is muted).
-        if !is_device_muted(&mut *tx, device_id).await {
-            let alert_id = uuid::Uuid::new_v4().to_string();
-            let severity = severity_for_alert_type("device_offline");
-            sqlx::query(
-                r#"INSERT INTO alerts (id, type, device_id, message, severity, created_at)
-                 VALUES (?, 'device_offline', ?, ?, ?, ?)"#,
-            )
-            .bind(&alert_id)
-            .bind(device_id)
-            .bind(format!("Device {} went offline", mac))
-            .bind(severity)
-            .bind(&now)
-            .execute(&mut *tx)
-            .await?;
-        }
-
         info!(mac = %mac, "Device went offline");
 
         ws_hub.broadcast(
@@ -518,14 +1175,55 @@ pub async fn process_scan_results(
             }),
         );
 
-        webhook::dispatch_webhook(
-            db,
-            "device_offline",
-            json!({
-                "device_id": device_id,
-                "mac": mac,
-            }),
-        );
+        offline_alert
+            .raise(&mut tx, device_id, mac, last_ip.as_deref(), facts)
+            .await?;
+    }
+
+    // Rules with `min_offline_secs` fire once an outage has lasted long enough,
+    // so devices that were already offline are re-checked on every scan.
+    if rules.has_delayed_rules("device_offline") {
+        let offline: Vec<(String, String, AlertFacts, Option<String>)> = sqlx::query(
+            "SELECT d.id, d.mac, d.vendor, d.hostname, d.last_seen_at, \
+                    (SELECT MAX(COALESCE(a.updated_at, a.created_at)) FROM alerts a \
+                     WHERE a.device_id = d.id AND a.type = 'device_offline') AS last_alert_at \
+             FROM devices d WHERE d.is_online = 0 AND d.is_deleted = 0",
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let facts = AlertFacts {
+                vendor: sqlx::Row::get(&row, "vendor"),
+                hostname: sqlx::Row::get(&row, "hostname"),
+                last_seen_at: sqlx::Row::get(&row, "last_seen_at"),
+            };
+            (
+                sqlx::Row::get(&row, "id"),
+                sqlx::Row::get(&row, "mac"),
+                facts,
+                sqlx::Row::get(&row, "last_alert_at"),
+            )
+        })
+        .collect();
+
+        for (device_id, mac, facts, last_alert_at) in &offline {
+            // Decided above for devices that went offline in this scan.
+            if stale_devices.iter().any(|(id, _, _)| id == device_id) {
+                continue;
+            }
+            // One alert per outage: skip if one was raised after the device was last seen.
+            let alerted_this_outage = last_alert_at.as_deref().is_some_and(|alerted| {
+                secs_since(alerted, now_ts) <= secs_since(&facts.last_seen_at, now_ts)
+            });
+            // Muted devices never get a stored alert, so they would be re-notified each scan.
+            if alerted_this_outage || is_device_muted(&mut *tx, device_id).await {
+                continue;
+            }
+            offline_alert
+                .raise(&mut tx, device_id, mac, None, facts)
+                .await?;
+        }
     }
 
     // Commit the transaction — all Phase 1 + Phase 2 mutations are now durable.
@@ -717,11 +1415,20 @@ mod tests {
         let devices = vec![DiscoveredDevice {
             ip: "10.0.0.1".to_string(),
             mac: "aa:bb:cc:dd:ee:03".to_string(),
+            ip_version: 4,
         }];
 
-        process_scan_results(&pool, &devices, 300, &ws_hub)
-            .await
-            .expect("process_scan_results should succeed");
+        process_scan_results(
+            &pool,
+            &devices,
+            300,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .expect("process_scan_results should succeed");
 
         // Verify device was inserted.
         let row: Option<(String, i32)> =
@@ -769,10 +1476,19 @@ mod tests {
         let devices = vec![DiscoveredDevice {
             ip: "10.0.0.2".to_string(),
             mac: mac.to_string(),
+            ip_version: 4,
         }];
-        process_scan_results(&pool, &devices, 300, &ws_hub)
-            .await
-            .expect("initial scan");
+        process_scan_results(
+            &pool,
+            &devices,
+            300,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .expect("initial scan");
 
         // Step 2: Force the device to look stale by backdating last_seen_at.
         sqlx::query("UPDATE devices SET last_seen_at = datetime('now', '-1 hour') WHERE mac = ?")
@@ -782,9 +1498,17 @@ mod tests {
             .expect("backdate last_seen_at");
 
         // Run scan with no devices (empty) → should mark device offline.
-        process_scan_results(&pool, &[], 60, &ws_hub)
-            .await
-            .expect("empty scan");
+        process_scan_results(
+            &pool,
+            &[],
+            60,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .expect("empty scan");
 
         let is_online: i32 = sqlx::query_scalar("SELECT is_online FROM devices WHERE mac = ?")
             .bind(mac)
@@ -794,9 +1518,17 @@ mod tests {
         assert_eq!(is_online, 0, "Device should be offline after grace period");
 
         // Step 3: Device reappears.
-        process_scan_results(&pool, &devices, 300, &ws_hub)
-            .await
-            .expect("re-discovery scan");
+        process_scan_results(
+            &pool,
+            &devices,
+            300,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .expect("re-discovery scan");
 
         let is_online: i32 = sqlx::query_scalar("SELECT is_online FROM devices WHERE mac = ?")
             .bind(mac)
@@ -817,4 +1549,592 @@ mod tests {
         .expect("query state log");
         assert_eq!(states, vec!["online", "offline", "online"]);
     }
+
+    #[test]
+    fn test_merge_by_mac_orders_ipv4_first_and_dedups() {
+        let entry = |ip: &str, mac: &str, ip_version: u8| DiscoveredDevice {
+            ip: ip.to_string(),
+            mac: mac.to_string(),
+            ip_version,
+        };
+        let merged = merge_by_mac(vec![
+            entry("2001:db8::5", "aa:bb:cc:dd:ee:05", 6),
+            entry("10.0.0.9", "aa:bb:cc:dd:ee:09", 4),
+            entry("10.0.0.5", "AA:BB:CC:DD:EE:05", 4),
+            entry("2001:db8::5", "aa:bb:cc:dd:ee:05", 6),
+        ]);
+        let ips: Vec<&str> = merged.iter().map(|d| d.ip.as_str()).collect();
+        assert_eq!(ips, vec!["10.0.0.5", "2001:db8::5", "10.0.0.9"]);
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_dual_stack_single_device() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let mac = "aa:bb:cc:dd:ee:06";
+
+        let devices = merge_by_mac(vec![
+            DiscoveredDevice {
+                ip: "2001:db8::6".to_string(),
+                mac: mac.to_string(),
+                ip_version: 6,
+            },
+            DiscoveredDevice {
+                ip: "10.0.0.6".to_string(),
+                mac: mac.to_string(),
+                ip_version: 4,
+            },
+        ]);
+        process_scan_results(
+            &pool,
+            &devices,
+            300,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .expect("dual-stack scan");
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM devices WHERE mac = ?")
+            .bind(mac)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1, "Dual-stack device must be stored once");
+
+        let ips: Vec<(String, i64)> = sqlx::query_as(
+            r#"SELECT ip, ip_version FROM device_ips
+               WHERE device_id = (SELECT id FROM devices WHERE mac = ?)
+               ORDER BY ip_version"#,
+        )
+        .bind(mac)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            ips,
+            vec![("10.0.0.6".to_string(), 4), ("2001:db8::6".to_string(), 6)]
+        );
+
+        let message: String = sqlx::query_scalar(
+            "SELECT message FROM alerts WHERE type = 'new_device' AND device_id = \
+             (SELECT id FROM devices WHERE mac = ?)",
+        )
+        .bind(mac)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(
+            message.contains("10.0.0.6"),
+            "alert should use the IPv4 address"
+        );
+    }
+
+    fn device(ip: &str, mac: &str) -> DiscoveredDevice {
+        DiscoveredDevice {
+            ip: ip.to_string(),
+            mac: mac.to_string(),
+            ip_version: 4,
+        }
+    }
+
+    async fn alerts_of_type(pool: &SqlitePool, alert_type: &str) -> Vec<(String, String)> {
+        sqlx::query_as("SELECT device_id, details FROM alerts WHERE type = ? ORDER BY created_at")
+            .bind(alert_type)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_mac_conflict() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.20", "aa:bb:cc:dd:ee:20")],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.20", "aa:bb:cc:dd:ee:21")],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+
+        let alerts = alerts_of_type(&pool, "mac_conflict").await;
+        assert_eq!(alerts.len(), 1);
+        let new_id: String =
+            sqlx::query_scalar("SELECT id FROM devices WHERE mac = 'aa:bb:cc:dd:ee:21'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            alerts[0].0, new_id,
+            "alert belongs to the device now holding the IP"
+        );
+        let details: serde_json::Value = serde_json::from_str(&alerts[0].1).unwrap();
+        assert_eq!(details["previous_mac"], "aa:bb:cc:dd:ee:20");
+        assert_eq!(details["new_mac"], "aa:bb:cc:dd:ee:21");
+        assert_eq!(details["ip"], "10.0.0.20");
+
+        // The old holder's mapping is no longer current, so a rescan does not re-alert.
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.20", "aa:bb:cc:dd:ee:21")],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(alerts_of_type(&pool, "mac_conflict").await.len(), 1);
+    }
+
+    #[test]
+    fn test_detect_ip_conflicts() {
+        let discovered = [
+            device("10.0.0.5", "aa:bb:cc:dd:ee:05"),
+            device("10.0.0.6", "aa:bb:cc:dd:ee:06"),
+            device("10.0.0.5", "AA:BB:CC:DD:EE:05"),
+            device("10.0.0.5", "aa:bb:cc:dd:ee:50"),
+            device("10.0.0.5", "aa:bb:cc:dd:ee:51"),
+        ];
+        assert_eq!(
+            detect_ip_conflicts(&discovered),
+            vec![(
+                "10.0.0.5".to_string(),
+                "aa:bb:cc:dd:ee:05".to_string(),
+                "aa:bb:cc:dd:ee:50".to_string(),
+            )]
+        );
+        assert!(detect_ip_conflicts(&discovered[..3]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_ip_conflict() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let limiter = telegram::TelegramRateLimiter::new();
+        let discovered = [
+            device("10.0.0.40", "aa:bb:cc:dd:ee:40"),
+            device("10.0.0.40", "aa:bb:cc:dd:ee:41"),
+        ];
+
+        for _ in 0..2 {
+            process_scan_results(&pool, &discovered, 300, None, &ws_hub, &cache, &limiter)
+                .await
+                .unwrap();
+        }
+
+        let alerts: Vec<(Option<String>, String, String, i64)> = sqlx::query_as(
+            "SELECT device_id, details, severity, count FROM alerts WHERE type = 'ip_conflict'",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(alerts.len(), 1, "repeat scans fold into one alert");
+        let (device_id, details, severity, count) = &alerts[0];
+        assert!(device_id.is_none());
+        assert_eq!(severity, "WARNING");
+        assert_eq!(*count, 2);
+        let details: serde_json::Value = serde_json::from_str(details).unwrap();
+        assert_eq!(details["ip"], "10.0.0.40");
+        assert_eq!(
+            details["macs"],
+            serde_json::json!(["aa:bb:cc:dd:ee:40", "aa:bb:cc:dd:ee:41"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_gateway_arp_spoofing() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let mut events = ws_hub.subscribe_ui();
+        let cache = SeverityOverrideCache::new();
+        let limiter = telegram::TelegramRateLimiter::new();
+
+        for mac in ["aa:bb:cc:dd:ee:01", "66:66:66:66:66:66"] {
+            process_scan_results(
+                &pool,
+                &[device("10.0.0.1", mac)],
+                300,
+                Some("10.0.0.1"),
+                &ws_hub,
+                &cache,
+                &limiter,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(alerts_of_type(&pool, "mac_conflict").await.is_empty());
+        let alerts = alerts_of_type(&pool, "arp_spoofing").await;
+        assert_eq!(alerts.len(), 1);
+        let details: serde_json::Value = serde_json::from_str(&alerts[0].1).unwrap();
+        assert_eq!(details["previous_mac"], "aa:bb:cc:dd:ee:01");
+        assert_eq!(details["new_mac"], "66:66:66:66:66:66");
+        assert!(details["detected_at"].is_string());
+
+        let (message, severity): (String, String) =
+            sqlx::query_as("SELECT message, severity FROM alerts WHERE type = 'arp_spoofing'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            message,
+            "Possible ARP spoofing: gateway IP 10.0.0.1 moved from MAC aa:bb:cc:dd:ee:01 \
+             to MAC 66:66:66:66:66:66"
+        );
+        assert_eq!(severity, "CRITICAL");
+
+        let mut spoofing_events = 0;
+        while let Ok(msg) = events.try_recv() {
+            if msg.event == "arp_spoofing" {
+                assert_eq!(msg.payload["ip"], "10.0.0.1");
+                spoofing_events += 1;
+            }
+        }
+        assert_eq!(spoofing_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_ip_change() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let mac = "aa:bb:cc:dd:ee:22";
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.22", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        // Same IP again: no change.
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.22", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        assert!(alerts_of_type(&pool, "ip_change").await.is_empty());
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.23", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        let alerts = alerts_of_type(&pool, "ip_change").await;
+        assert_eq!(alerts.len(), 1);
+        let details: serde_json::Value = serde_json::from_str(&alerts[0].1).unwrap();
+        assert_eq!(details["previous_ip"], "10.0.0.22");
+        assert_eq!(details["new_ip"], "10.0.0.23");
+
+        let current: Vec<String> = sqlx::query_scalar(
+            "SELECT ip FROM device_ips WHERE is_current = 1 AND device_id = \
+             (SELECT id FROM devices WHERE mac = ?)",
+        )
+        .bind(mac)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(current, vec!["10.0.0.23"]);
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_address_alerts_respect_mute() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let mac = "aa:bb:cc:dd:ee:24";
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.24", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE devices SET muted_until = datetime('now', '+1 hours') WHERE mac = ?")
+            .bind(mac)
+            .execute(&pool)
+            .await
+            .unwrap();
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.25", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(alerts_of_type(&pool, "ip_change").await.is_empty());
+    }
+
+    async fn insert_rule(pool: &SqlitePool, event_type: &str, condition: &str, action: &str) {
+        sqlx::query(
+            "INSERT INTO alert_rules (id, name, event_type, condition_json, action) \
+             VALUES (?, 'test', ?, ?, ?)",
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(event_type)
+        .bind(condition)
+        .bind(action)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_alert_rules_suppress() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        // Only alert on new devices whose vendor is unknown.
+        insert_rule(&pool, "new_device", r#"{"vendor_unknown": true}"#, "create").await;
+
+        process_scan_results(
+            &pool,
+            // dc:a6:32 is a Raspberry Pi OUI; 02:… is locally administered (no vendor).
+            &[
+                device("10.0.0.30", "dc:a6:32:00:00:30"),
+                device("10.0.0.31", "02:00:00:00:00:31"),
+            ],
+            300,
+            None,
+            &ws_hub,
+            &SeverityOverrideCache::new(),
+            &telegram::TelegramRateLimiter::new(),
+        )
+        .await
+        .unwrap();
+
+        let alerts = alerts_of_type(&pool, "new_device").await;
+        assert_eq!(alerts.len(), 1, "only the unknown-vendor device alerts");
+        assert!(alerts[0].1.contains("02:00:00:00:00:31"));
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_delayed_offline_rule() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let limiter = telegram::TelegramRateLimiter::new();
+        let mac = "aa:bb:cc:dd:ee:40";
+        insert_rule(
+            &pool,
+            "device_offline",
+            r#"{"min_offline_secs": 7200}"#,
+            "create",
+        )
+        .await;
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.40", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &limiter,
+        )
+        .await
+        .unwrap();
+
+        // Offline for 10 minutes: the device is marked offline but no alert yet.
+        sqlx::query("UPDATE devices SET last_seen_at = ? WHERE mac = ?")
+            .bind((Utc::now() - chrono::Duration::minutes(10)).to_rfc3339())
+            .bind(mac)
+            .execute(&pool)
+            .await
+            .unwrap();
+        process_scan_results(&pool, &[], 300, None, &ws_hub, &cache, &limiter)
+            .await
+            .unwrap();
+        assert!(alerts_of_type(&pool, "device_offline").await.is_empty());
+
+        // Offline for 3 hours: a later scan raises the alert exactly once.
+        sqlx::query("UPDATE devices SET last_seen_at = ? WHERE mac = ?")
+            .bind((Utc::now() - chrono::Duration::hours(3)).to_rfc3339())
+            .bind(mac)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            process_scan_results(&pool, &[], 300, None, &ws_hub, &cache, &limiter)
+                .await
+                .unwrap();
+        }
+        assert_eq!(alerts_of_type(&pool, "device_offline").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_dedups_flapping_alerts() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let limiter = telegram::TelegramRateLimiter::new();
+        let mac = "aa:bb:cc:dd:ee:50";
+
+        let flap = || async {
+            process_scan_results(
+                &pool,
+                &[device("10.0.0.50", mac)],
+                300,
+                None,
+                &ws_hub,
+                &cache,
+                &limiter,
+            )
+            .await
+            .unwrap();
+            sqlx::query("UPDATE devices SET last_seen_at = ? WHERE mac = ?")
+                .bind((Utc::now() - chrono::Duration::minutes(10)).to_rfc3339())
+                .bind(mac)
+                .execute(&pool)
+                .await
+                .unwrap();
+            process_scan_results(&pool, &[], 300, None, &ws_hub, &cache, &limiter)
+                .await
+                .unwrap();
+        };
+        let offline_alerts = || async {
+            sqlx::query_as::<_, (i64, Option<String>)>(
+                "SELECT count, updated_at FROM alerts WHERE type = 'device_offline' \
+                 ORDER BY created_at",
+            )
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+
+        // Two offline transitions within the default window share one alert.
+        flap().await;
+        flap().await;
+        let alerts = offline_alerts().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].0, 2);
+        assert!(alerts[0].1.is_some());
+
+        // A zero window disables dedup.
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('alert_dedup_window_secs', '0')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        flap().await;
+        assert_eq!(offline_alerts().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_process_scan_results_maintenance_window() {
+        let pool = test_pool().await;
+        let ws_hub = Arc::new(WsHub::new());
+        let cache = SeverityOverrideCache::new();
+        let limiter = telegram::TelegramRateLimiter::new();
+        let mac = "aa:bb:cc:dd:ee:60";
+
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.60", mac)],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &limiter,
+        )
+        .await
+        .unwrap();
+        let device_id: String = sqlx::query_scalar("SELECT id FROM devices WHERE mac = ?")
+            .bind(mac)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO maintenance_windows (id, name, start_at, end_at, device_ids) \
+             VALUES ('mw-1', 'Upgrade', ?, ?, ?)",
+        )
+        .bind((now - chrono::Duration::hours(1)).to_rfc3339())
+        .bind((now + chrono::Duration::hours(1)).to_rfc3339())
+        .bind(json!([&device_id]).to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // The device goes offline: state and UI events update, but no alert.
+        let mut events = ws_hub.subscribe_ui();
+        sqlx::query("UPDATE devices SET last_seen_at = ? WHERE mac = ?")
+            .bind((now - chrono::Duration::minutes(10)).to_rfc3339())
+            .bind(mac)
+            .execute(&pool)
+            .await
+            .unwrap();
+        process_scan_results(&pool, &[], 300, None, &ws_hub, &cache, &limiter)
+            .await
+            .unwrap();
+        let is_online: bool = sqlx::query_scalar("SELECT is_online FROM devices WHERE id = ?")
+            .bind(&device_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!is_online);
+        assert_eq!(events.try_recv().unwrap().event, "device_offline");
+        assert!(alerts_of_type(&pool, "device_offline").await.is_empty());
+
+        // Other devices are not covered.
+        process_scan_results(
+            &pool,
+            &[device("10.0.0.61", "aa:bb:cc:dd:ee:61")],
+            300,
+            None,
+            &ws_hub,
+            &cache,
+            &limiter,
+        )
+        .await
+        .unwrap();
+        assert_eq!(alerts_of_type(&pool, "new_device").await.len(), 2);
+    }
 }
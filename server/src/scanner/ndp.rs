@@ -0,0 +1,114 @@
+use anyhow::Result;
+use std::net::Ipv6Addr;
+use tracing::{debug, info};
+
+use super::arp::normalize_mac;
+use super::DiscoveredDevice;
+
+/// Ping the IPv6 all-nodes multicast group (`ff02::1`) on an interface.
+///
+/// Like the IPv4 ping sweep this is only meant to populate the kernel
+/// neighbour table: every IPv6 host on the link answers, so one multicast
+/// echo replaces sweeping an address space far too large to enumerate.
+pub async fn multicast_ping(interface: &str) {
+    info!(interface = %interface, "Starting IPv6 multicast ping");
+    if let Err(e) = tokio::process::Command::new("ping")
+        .args(["-6", "-c", "2", "-W", "1", "-I", interface, "ff02::1"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .await
+    {
+        debug!(interface = %interface, error = %e, "ping -6 process failed to spawn");
+    }
+}
+
+/// Read the IPv6 neighbour (NDP) table via `ip -6 neigh show` (Linux).
+pub async fn read_ndp_table() -> Result<Vec<DiscoveredDevice>> {
+    let output = tokio::process::Command::new("ip")
+        .args(["-6", "neigh", "show"])
+        .output()
+        .await?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_ip_neigh_output(&stdout))
+}
+
+/// Parse the text output of `ip -6 neigh show` into a list of discovered devices.
+///
+/// Expected format:
+/// ```text
+/// 2001:db8::10 dev eth0 lladdr bc:24:11:d6:6b:62 REACHABLE
+/// fe80::1 dev eth0 lladdr aa:bb:cc:dd:ee:ff router STALE
+/// 2001:db8::99 dev eth0 FAILED
+/// ```
+/// Entries without a link-layer address (`INCOMPLETE`/`FAILED`) are skipped,
+/// as are link-local and multicast addresses, which every host has and which
+/// cannot be used to reach the device from elsewhere.
+pub(crate) fn parse_ip_neigh_output(output: &str) -> Vec<DiscoveredDevice> {
+    let mut devices = Vec::new();
+
+    for line in output.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(addr) = parts.first().and_then(|p| p.parse::<Ipv6Addr>().ok()) else {
+            continue;
+        };
+        let is_link_local = (addr.segments()[0] & 0xffc0) == 0xfe80;
+        if is_link_local || addr.is_multicast() || addr.is_loopback() {
+            continue;
+        }
+        if matches!(parts.last(), Some(&"FAILED") | Some(&"INCOMPLETE")) {
+            continue;
+        }
+
+        let mac = parts
+            .iter()
+            .position(|p| *p == "lladdr")
+            .and_then(|i| parts.get(i + 1));
+        if let Some(mac) = mac {
+            devices.push(DiscoveredDevice {
+                ip: addr.to_string(),
+                mac: normalize_mac(mac),
+                ip_version: 6,
+            });
+        }
+    }
+
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ip_neigh_output_basic() {
+        let sample = "2001:db8::10 dev eth0 lladdr BC:24:11:D6:6B:62 REACHABLE\n\
+                      fd00::5 dev eth0 lladdr 60:be:b4:28:ec:64 STALE\n\
+                      2001:db8::99 dev eth0 FAILED\n\
+                      2001:db8::98 dev eth0 lladdr 60:be:b4:28:ec:65 INCOMPLETE";
+
+        let entries = parse_ip_neigh_output(sample);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].ip, "2001:db8::10");
+        assert_eq!(entries[0].mac, "bc:24:11:d6:6b:62");
+        assert_eq!(entries[0].ip_version, 6);
+        assert_eq!(entries[1].ip, "fd00::5");
+    }
+
+    #[test]
+    fn test_parse_ip_neigh_output_skips_link_local_and_multicast() {
+        let sample = "fe80::1 dev eth0 lladdr aa:bb:cc:dd:ee:ff router STALE\n\
+                      ff02::fb dev eth0 lladdr 33:33:00:00:00:fb NOARP\n\
+                      garbage line";
+        assert!(parse_ip_neigh_output(sample).is_empty());
+    }
+
+    #[test]
+    fn test_parse_ip_neigh_output_router_flag() {
+        let sample = "2001:db8::1 dev eth0 lladdr aa:bb:cc:dd:ee:ff router REACHABLE";
+        let entries = parse_ip_neigh_output(sample);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].mac, "aa:bb:cc:dd:ee:ff");
+    }
+}
@@ -0,0 +1,190 @@
+//! Recurring per-device port scans.
+//!
+//! Every [`CHECK_INTERVAL`] the scheduler looks for enabled rows in
+//! `device_scan_schedules` whose `last_scan_at + interval_hours` has passed
+//! and runs the same nmap scan as `POST /devices/:id/scan`, storing results
+//! in `port_scans`. Offline devices are skipped (and retried next check)
+//! rather than left for nmap to time out on.
+
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::api::alerts::SeverityOverrideCache;
+use crate::api::devices::{run_port_scan, scan_target_ip};
+use crate::ws::hub::WsHub;
+
+/// How often the scheduler checks for due scans.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// A schedule whose next scan is due.
+#[derive(Debug, sqlx::FromRow)]
+pub struct DueScan {
+    pub device_id: String,
+    pub is_online: bool,
+}
+
+/// Enabled schedules for non-deleted devices that have never run or whose
+/// interval has elapsed.
+pub async fn due_scans(db: &SqlitePool) -> Result<Vec<DueScan>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT s.device_id, COALESCE(d.is_online, 0) AS is_online \
+         FROM device_scan_schedules s \
+         JOIN devices d ON d.id = s.device_id \
+         WHERE s.enabled = 1 AND d.is_deleted = 0 \
+           AND (s.last_scan_at IS NULL \
+                OR datetime(s.last_scan_at, '+' || s.interval_hours || ' hours') <= datetime('now')) \
+         ORDER BY s.last_scan_at",
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Scan one due device and record the attempt. Failed scans still advance
+/// `last_scan_at` so a broken target isn't retried every check.
+async fn run_due_scan(
+    db: &SqlitePool,
+    ws_hub: &Arc<WsHub>,
+    severities: &SeverityOverrideCache,
+    device_id: &str,
+) {
+    match scan_target_ip(db, device_id).await {
+        Ok(ip) => match run_port_scan(db, ws_hub, severities, device_id, &ip).await {
+            Ok(result) => info!(
+                device_id,
+                open_ports = result.ports.len(),
+                "Scheduled port scan complete"
+            ),
+            Err(e) => error!(device_id, "Scheduled port scan failed: {e}"),
+        },
+        Err(e) => warn!(device_id, "Skipping scheduled port scan: {e}"),
+    }
+
+    if let Err(e) = sqlx::query(
+        "UPDATE device_scan_schedules SET last_scan_at = datetime('now') WHERE device_id = ?",
+    )
+    .bind(device_id)
+    .execute(db)
+    .await
+    {
+        error!(device_id, "Failed to update scan schedule: {e}");
+    }
+}
+
+/// Start the port scan scheduler.
+pub fn start_port_scan_schedule_task(
+    db: SqlitePool,
+    ws_hub: Arc<WsHub>,
+    severities: SeverityOverrideCache,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let due = match due_scans(&db).await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Failed to load due port scans: {e}");
+                    continue;
+                }
+            };
+
+            for scan in due {
+                if !scan.is_online {
+                    warn!(
+                        device_id = %scan.device_id,
+                        "Device offline, skipping scheduled port scan"
+                    );
+                    continue;
+                }
+                run_due_scan(&db, &ws_hub, &severities, &scan.device_id).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_device(db: &SqlitePool, id: &str, is_online: bool) {
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at, is_online) \
+             VALUES (?, ?, datetime('now'), datetime('now'), ?)",
+        )
+        .bind(id)
+        .bind(format!("AA:BB:CC:00:00:{}", &id[id.len() - 2..]))
+        .bind(is_online)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    async fn schedule(
+        db: &SqlitePool,
+        id: &str,
+        hours: i64,
+        last_scan: Option<&str>,
+        enabled: bool,
+    ) {
+        sqlx::query(
+            "INSERT INTO device_scan_schedules (device_id, interval_hours, last_scan_at, enabled) \
+             VALUES (?, ?, datetime('now', ?), ?)",
+        )
+        .bind(id)
+        .bind(hours)
+        .bind(last_scan)
+        .bind(enabled)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_due_scans() {
+        let db = crate::db::init(":memory:").await.unwrap();
+        for (id, online) in [
+            ("dev-01", true),
+            ("dev-02", true),
+            ("dev-03", false),
+            ("dev-04", true),
+            ("dev-05", true),
+        ] {
+            insert_device(&db, id, online).await;
+        }
+        // Never scanned.
+        schedule(&db, "dev-01", 6, None, true).await;
+        // Scanned 7 hours ago on a 6-hour interval.
+        schedule(&db, "dev-02", 6, Some("-7 hours"), true).await;
+        // Due but offline: still returned, the task decides to skip it.
+        schedule(&db, "dev-03", 1, Some("-2 hours"), true).await;
+        // Not due yet.
+        schedule(&db, "dev-04", 24, Some("-1 hours"), true).await;
+        // Disabled.
+        schedule(&db, "dev-05", 1, None, false).await;
+
+        let due = due_scans(&db).await.unwrap();
+        let mut ids: Vec<(&str, bool)> = due
+            .iter()
+            .map(|d| (d.device_id.as_str(), d.is_online))
+            .collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![("dev-01", true), ("dev-02", true), ("dev-03", false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_due_scan_advances_last_scan_at() {
+        let db = crate::db::init(":memory:").await.unwrap();
+        insert_device(&db, "dev-01", true).await;
+        schedule(&db, "dev-01", 6, None, true).await;
+
+        // No current IP: the scan is skipped but the attempt is recorded.
+        run_due_scan(&db, &WsHub::new(), &SeverityOverrideCache::new(), "dev-01").await;
+
+        assert!(due_scans(&db).await.unwrap().is_empty());
+    }
+}
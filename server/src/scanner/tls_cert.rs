@@ -0,0 +1,506 @@
+//! TLS certificate inspection for HTTPS services found by a port scan.
+//!
+//! Completes a TLS handshake (accepting any certificate, without sending a
+//! request), parses the leaf certificate and stores it in
+//! `tls_certificates`. Certificates that expire within
+//! [`EXPIRY_WARNING_DAYS`] raise `cert_expiring`; expired ones raise
+//! `cert_expired`.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tracing::{debug, warn};
+use x509_parser::extensions::GeneralName;
+use x509_parser::x509::X509Name;
+
+use super::banner::HTTPS_PORTS;
+use crate::api::alerts::{is_device_muted, severity_for_alert_type, SeverityOverrideCache};
+use crate::webhook;
+use crate::ws::hub::WsHub;
+
+/// Alert when a certificate expires within this many days.
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Connect + handshake budget per port.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Leaf certificate of a TLS service.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TlsCertificate {
+    pub port: u16,
+    pub subject_cn: Option<String>,
+    pub issuer_cn: Option<String>,
+    pub san_dns_names: Vec<String>,
+    /// RFC 3339, UTC.
+    pub not_before: String,
+    /// RFC 3339, UTC.
+    pub not_after: String,
+    pub is_self_signed: bool,
+    pub last_checked: String,
+}
+
+/// Whether an open port found by nmap is worth a TLS handshake.
+pub fn is_tls_port(port: u16, service: &str) -> bool {
+    HTTPS_PORTS.contains(&port) || service.contains("ssl") || service.contains("https")
+}
+
+/// Accepts every server certificate: we inspect certificates, we do not
+/// trust them. Handshake signatures are still checked.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// First common name of an X.509 name.
+fn common_name(name: &X509Name<'_>) -> Option<String> {
+    name.iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string)
+}
+
+/// Format an X.509 time as RFC 3339 UTC.
+fn rfc3339(time: x509_parser::time::ASN1Time) -> String {
+    chrono::DateTime::from_timestamp(time.timestamp(), 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Parse a DER certificate served on `port`.
+pub fn parse_certificate(port: u16, der: &[u8], checked_at: &str) -> Option<TlsCertificate> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+    let san_dns_names = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|san| {
+            san.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let validity = cert.validity();
+
+    Some(TlsCertificate {
+        port,
+        subject_cn: common_name(cert.subject()),
+        issuer_cn: common_name(cert.issuer()),
+        san_dns_names,
+        not_before: rfc3339(validity.not_before),
+        not_after: rfc3339(validity.not_after),
+        is_self_signed: cert.subject().as_raw() == cert.issuer().as_raw(),
+        last_checked: checked_at.to_string(),
+    })
+}
+
+/// Handshake with `ip:port` and return the leaf certificate's DER bytes.
+pub async fn fetch_leaf_certificate(ip: IpAddr, port: u16) -> Option<Vec<u8>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .ok()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let result = tokio::time::timeout(HANDSHAKE_TIMEOUT, async {
+        let tcp = TcpStream::connect(SocketAddr::new(ip, port)).await?;
+        let tls = connector
+            .connect(ServerName::IpAddress(ip.into()), tcp)
+            .await?;
+        let (_, session) = tls.get_ref();
+        Ok::<_, std::io::Error>(
+            session
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .map(|leaf| leaf.as_ref().to_vec()),
+        )
+    })
+    .await;
+
+    match result {
+        Ok(Ok(leaf)) => leaf,
+        Ok(Err(e)) => {
+            debug!(%ip, port, error = %e, "TLS handshake failed");
+            None
+        }
+        Err(_) => {
+            debug!(%ip, port, "TLS handshake timed out");
+            None
+        }
+    }
+}
+
+/// The alert a certificate expiring at `not_after` warrants at `now`.
+pub fn expiry_alert_type(
+    not_after: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<&'static str> {
+    if not_after <= now {
+        Some("cert_expired")
+    } else if not_after - now < chrono::Duration::days(EXPIRY_WARNING_DAYS) {
+        Some("cert_expiring")
+    } else {
+        None
+    }
+}
+
+/// Store `cert` for the device and alert if it expires soon or has expired.
+/// An unacknowledged alert of the same type for the same port is not repeated.
+pub async fn record_certificate(
+    db: &SqlitePool,
+    ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+    device_id: &str,
+    cert: &TlsCertificate,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO tls_certificates
+               (device_id, port, subject_cn, issuer_cn, san_dns_names, not_before, not_after,
+                is_self_signed, last_checked)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(device_id, port) DO UPDATE SET
+               subject_cn = excluded.subject_cn,
+               issuer_cn = excluded.issuer_cn,
+               san_dns_names = excluded.san_dns_names,
+               not_before = excluded.not_before,
+               not_after = excluded.not_after,
+               is_self_signed = excluded.is_self_signed,
+               last_checked = excluded.last_checked"#,
+    )
+    .bind(device_id)
+    .bind(cert.port)
+    .bind(&cert.subject_cn)
+    .bind(&cert.issuer_cn)
+    .bind(serde_json::to_string(&cert.san_dns_names).unwrap_or_else(|_| "[]".to_string()))
+    .bind(&cert.not_before)
+    .bind(&cert.not_after)
+    .bind(cert.is_self_signed)
+    .bind(&cert.last_checked)
+    .execute(db)
+    .await?;
+
+    let Ok(not_after) = chrono::DateTime::parse_from_rfc3339(&cert.not_after) else {
+        return Ok(());
+    };
+    let not_after = not_after.with_timezone(&chrono::Utc);
+    let Some(alert_type) = expiry_alert_type(not_after, now) else {
+        return Ok(());
+    };
+    if is_device_muted(db, device_id).await {
+        return Ok(());
+    }
+
+    let pending: Option<i64> = sqlx::query_scalar(
+        r#"SELECT 1 FROM alerts
+           WHERE device_id = ? AND type = ? AND acknowledged_at IS NULL
+             AND json_extract(details, '$.port') = ?
+           LIMIT 1"#,
+    )
+    .bind(device_id)
+    .bind(alert_type)
+    .bind(cert.port)
+    .fetch_optional(db)
+    .await?;
+    if pending.is_some() {
+        return Ok(());
+    }
+
+    let label: String =
+        sqlx::query_scalar(r#"SELECT COALESCE(name, hostname, mac) FROM devices WHERE id = ?"#)
+            .bind(device_id)
+            .fetch_optional(db)
+            .await?
+            .unwrap_or_else(|| device_id.to_string());
+    let subject = cert.subject_cn.as_deref().unwrap_or("certificate");
+    let message = if alert_type == "cert_expired" {
+        format!(
+            "TLS certificate {subject} on {label}:{} expired on {}",
+            cert.port, cert.not_after
+        )
+    } else {
+        let days = (not_after - now).num_days();
+        format!(
+            "TLS certificate {subject} on {label}:{} expires in {days} days",
+            cert.port
+        )
+    };
+    let details = json!({
+        "port": cert.port,
+        "subject_cn": &cert.subject_cn,
+        "not_after": &cert.not_after,
+    });
+    let severity = severity_for_alert_type(alert_type, db, severities).await;
+    sqlx::query(
+        r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+           VALUES (?, ?, ?, ?, ?, ?, datetime('now'))"#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(alert_type)
+    .bind(device_id)
+    .bind(&message)
+    .bind(details.to_string())
+    .bind(&severity)
+    .execute(db)
+    .await?;
+    crate::api::metrics::record_alert_created(alert_type);
+
+    let payload = json!({
+        "device_id": device_id,
+        "message": &message,
+        "port": cert.port,
+    });
+    ws_hub.broadcast(alert_type, payload.clone());
+    webhook::dispatch_webhook(db, alert_type, payload);
+    Ok(())
+}
+
+/// Inspect the certificates on `ports` of `ip` and record them for the device.
+pub async fn inspect_and_store(
+    db: &SqlitePool,
+    ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+    device_id: &str,
+    ip: IpAddr,
+    ports: Vec<u16>,
+) {
+    for port in ports {
+        let now = chrono::Utc::now();
+        let checked_at = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let Some(cert) = fetch_leaf_certificate(ip, port)
+            .await
+            .and_then(|der| parse_certificate(port, &der, &checked_at))
+        else {
+            continue;
+        };
+        if let Err(e) = record_certificate(db, ws_hub, severities, device_id, &cert, now).await {
+            warn!(device_id, port, error = %e, "Failed to store TLS certificate");
+        }
+    }
+}
+
+/// Certificates stored for a device, ordered by port.
+pub async fn load_certificates(
+    db: &SqlitePool,
+    device_id: &str,
+) -> Result<Vec<TlsCertificate>, sqlx::Error> {
+    type Row = (
+        i64,
+        Option<String>,
+        Option<String>,
+        String,
+        String,
+        String,
+        bool,
+        String,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        r#"SELECT port, subject_cn, issuer_cn, san_dns_names, not_before, not_after,
+                  is_self_signed, last_checked
+           FROM tls_certificates WHERE device_id = ? ORDER BY port"#,
+    )
+    .bind(device_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                port,
+                subject_cn,
+                issuer_cn,
+                san,
+                not_before,
+                not_after,
+                is_self_signed,
+                last_checked,
+            )| {
+                TlsCertificate {
+                    port: port as u16,
+                    subject_cn,
+                    issuer_cn,
+                    san_dns_names: serde_json::from_str(&san).unwrap_or_default(),
+                    not_before,
+                    not_after,
+                    is_self_signed,
+                    last_checked,
+                }
+            },
+        )
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    fn self_signed() -> rcgen::CertifiedKey<rcgen::KeyPair> {
+        rcgen::generate_simple_self_signed(vec!["nas.local".to_string()]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_and_parse_certificate() {
+        let key = self_signed();
+        let server_config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![key.cert.der().clone()],
+            rustls::pki_types::PrivateKeyDer::Pkcs8(key.signing_key.serialize_der().into()),
+        )
+        .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut tls = acceptor.accept(socket).await.unwrap();
+            let _ = tls.shutdown().await;
+        });
+
+        let der = fetch_leaf_certificate(addr.ip(), addr.port())
+            .await
+            .unwrap();
+        let cert = parse_certificate(443, &der, "2026-01-01T00:00:00Z").unwrap();
+        assert_eq!(cert.port, 443);
+        assert_eq!(cert.san_dns_names, vec!["nas.local".to_string()]);
+        assert!(cert.is_self_signed);
+        assert_eq!(cert.subject_cn, cert.issuer_cn);
+        assert!(cert.not_before < cert.not_after);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_certificate_from_plain_port_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"SSH-2.0-Test\r\n").await.unwrap();
+        });
+        assert!(fetch_leaf_certificate(addr.ip(), addr.port())
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_record_certificate_alerts_once() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, name, first_seen_at, last_seen_at) \
+             VALUES ('d1', 'aa:bb:cc:00:00:71', 'nas', datetime('now'), datetime('now'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let hub = WsHub::new();
+        let severities = SeverityOverrideCache::new();
+        let now = chrono::Utc::now();
+        let cert = |port: u16, days: i64| TlsCertificate {
+            port,
+            subject_cn: Some("nas.local".to_string()),
+            issuer_cn: Some("nas.local".to_string()),
+            san_dns_names: vec!["nas.local".to_string()],
+            not_before: "2025-01-01T00:00:00Z".to_string(),
+            not_after: (now + chrono::Duration::days(days))
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            is_self_signed: true,
+            last_checked: now.to_rfc3339(),
+        };
+
+        for _ in 0..2 {
+            record_certificate(&pool, &hub, &severities, "d1", &cert(443, 10), now)
+                .await
+                .unwrap();
+        }
+        record_certificate(&pool, &hub, &severities, "d1", &cert(8443, -1), now)
+            .await
+            .unwrap();
+        record_certificate(&pool, &hub, &severities, "d1", &cert(9443, 365), now)
+            .await
+            .unwrap();
+
+        let alerts: Vec<(String, String)> =
+            sqlx::query_as("SELECT type, severity FROM alerts ORDER BY type")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            alerts,
+            vec![
+                ("cert_expired".to_string(), "CRITICAL".to_string()),
+                ("cert_expiring".to_string(), "WARNING".to_string()),
+            ]
+        );
+
+        let stored = load_certificates(&pool, "d1").await.unwrap();
+        let ports: Vec<u16> = stored.iter().map(|c| c.port).collect();
+        assert_eq!(ports, vec![443, 8443, 9443]);
+        assert_eq!(stored[0], cert(443, 10));
+    }
+}
@@ -0,0 +1,3 @@
+//! Device security assessment.
+
+pub mod score;
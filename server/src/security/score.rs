@@ -0,0 +1,381 @@
+//! Per-device risk score.
+//!
+//! Combines open ports, MAC vendor, device age, alerts and agent coverage
+//! into a 0–100 score. Higher means riskier. Every contributing signal is
+//! returned as a named factor so the UI can explain the number.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+/// Ports considered sensitive when open, with the points they add.
+const SENSITIVE_PORTS: &[(u16, &str, i32)] = &[
+    (22, "ssh_port_open", 10),
+    (23, "telnet_port_open", 25),
+    (3389, "rdp_port_open", 20),
+    (8080, "http_alt_port_open", 5),
+];
+
+/// Devices first seen within this many days count as new discoveries.
+const NEW_DEVICE_DAYS: i64 = 7;
+
+/// Points per unacknowledged critical alert, and the cap for all of them.
+const CRITICAL_ALERT_IMPACT: i32 = 20;
+const CRITICAL_ALERT_CAP: i32 = 40;
+
+/// An agent report newer than this counts as recent.
+const RECENT_AGENT_REPORT_SECS: i64 = 3600;
+
+const MAX_SCORE: i32 = 100;
+
+/// A single signal contributing to the score.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScoreFactor {
+    pub name: &'static str,
+    pub impact: i32,
+}
+
+/// Response for `GET /api/v1/devices/:id/security-score`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SecurityScore {
+    pub score: i32,
+    pub level: &'static str,
+    pub factors: Vec<ScoreFactor>,
+}
+
+/// Everything the score is computed from, gathered from the database.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreInputs {
+    pub mac: String,
+    pub hostname: Option<String>,
+    pub first_seen_at: Option<DateTime<Utc>>,
+    /// Open ports from the latest port scan.
+    pub open_ports: Vec<u16>,
+    pub unacknowledged_critical_alerts: i64,
+    pub agent_last_report_at: Option<DateTime<Utc>>,
+}
+
+/// Whether the locally-administered bit is set in the MAC's first octet.
+///
+/// Phones and laptops set this bit when they randomise their MAC per network.
+fn is_locally_administered(mac: &str) -> bool {
+    let first: String = mac
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .take(2)
+        .collect();
+    u8::from_str_radix(&first, 16)
+        .map(|octet| octet & 0x02 != 0)
+        .unwrap_or(false)
+}
+
+/// Map a score to a coarse risk level.
+fn risk_level(score: i32) -> &'static str {
+    match score {
+        s if s >= 75 => "critical",
+        s if s >= 50 => "high",
+        s if s >= 25 => "medium",
+        _ => "low",
+    }
+}
+
+/// Compute the score for `inputs` as of `now`.
+pub fn compute_score(inputs: &ScoreInputs, now: DateTime<Utc>) -> SecurityScore {
+    let mut factors = Vec::new();
+
+    for &(port, name, impact) in SENSITIVE_PORTS {
+        if inputs.open_ports.contains(&port) {
+            factors.push(ScoreFactor { name, impact });
+        }
+    }
+
+    let vendor_known = crate::oui::lookup(&inputs.mac).is_some();
+    if !vendor_known {
+        factors.push(ScoreFactor {
+            name: "unknown_vendor",
+            impact: 15,
+        });
+        if is_locally_administered(&inputs.mac) {
+            factors.push(ScoreFactor {
+                name: "randomized_mac",
+                impact: 10,
+            });
+        }
+    }
+
+    if inputs
+        .first_seen_at
+        .is_some_and(|seen| now - seen < Duration::days(NEW_DEVICE_DAYS))
+    {
+        factors.push(ScoreFactor {
+            name: "new_device",
+            impact: 5,
+        });
+    }
+
+    if inputs.unacknowledged_critical_alerts > 0 {
+        let alerts = inputs
+            .unacknowledged_critical_alerts
+            .min(i64::from(i32::MAX)) as i32;
+        factors.push(ScoreFactor {
+            name: "unacknowledged_critical_alerts",
+            impact: alerts
+                .saturating_mul(CRITICAL_ALERT_IMPACT)
+                .min(CRITICAL_ALERT_CAP),
+        });
+    }
+
+    if inputs
+        .agent_last_report_at
+        .is_some_and(|at| now - at <= Duration::seconds(RECENT_AGENT_REPORT_SECS))
+    {
+        factors.push(ScoreFactor {
+            name: "recent_agent_report",
+            impact: -10,
+        });
+    }
+
+    if inputs
+        .hostname
+        .as_deref()
+        .is_some_and(|h| !h.trim().is_empty())
+    {
+        factors.push(ScoreFactor {
+            name: "known_hostname",
+            impact: -5,
+        });
+    }
+
+    let score = factors
+        .iter()
+        .map(|f| f.impact)
+        .sum::<i32>()
+        .clamp(0, MAX_SCORE);
+
+    SecurityScore {
+        score,
+        level: risk_level(score),
+        factors,
+    }
+}
+
+/// Parse a timestamp stored either as RFC 3339 or SQLite `datetime()` text.
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .map(|ndt| ndt.and_utc())
+        })
+        .ok()
+}
+
+/// Load the score inputs for a device. Returns `None` if the device does not exist.
+pub async fn load_inputs(
+    db: &SqlitePool,
+    device_id: &str,
+) -> Result<Option<ScoreInputs>, sqlx::Error> {
+    let Some(device) = sqlx::query("SELECT mac, hostname, first_seen_at FROM devices WHERE id = ?")
+        .bind(device_id)
+        .fetch_optional(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let scan_json: Option<String> = sqlx::query_scalar(
+        "SELECT result_json FROM port_scans WHERE device_id = ? ORDER BY scanned_at DESC LIMIT 1",
+    )
+    .bind(device_id)
+    .fetch_optional(db)
+    .await?;
+    let open_ports = scan_json
+        .and_then(|json| serde_json::from_str::<Vec<crate::api::devices::PortEntry>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| p.state == "open")
+        .map(|p| p.port)
+        .collect();
+
+    let unacknowledged_critical_alerts: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM alerts \
+         WHERE device_id = ? AND severity = 'CRITICAL' AND acknowledged_at IS NULL",
+    )
+    .bind(device_id)
+    .fetch_one(db)
+    .await?;
+
+    let agent_last_report_at: Option<String> =
+        sqlx::query_scalar("SELECT MAX(last_report_at) FROM agents WHERE device_id = ?")
+            .bind(device_id)
+            .fetch_one(db)
+            .await?;
+
+    let first_seen_at: String = device.try_get("first_seen_at")?;
+
+    Ok(Some(ScoreInputs {
+        mac: device.try_get("mac")?,
+        hostname: device.try_get("hostname")?,
+        first_seen_at: parse_timestamp(&first_seen_at),
+        open_ports,
+        unacknowledged_critical_alerts,
+        agent_last_report_at: agent_last_report_at.as_deref().and_then(parse_timestamp),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A MAC whose OUI is in the embedded database (Nokia).
+    const KNOWN_MAC: &str = "28:6f:b9:12:34:56";
+    /// Locally-administered MAC, as produced by MAC randomisation.
+    const RANDOM_MAC: &str = "da:a1:19:00:11:22";
+
+    fn names(score: &SecurityScore) -> Vec<&'static str> {
+        score.factors.iter().map(|f| f.name).collect()
+    }
+
+    fn established(mac: &str) -> ScoreInputs {
+        ScoreInputs {
+            mac: mac.to_string(),
+            first_seen_at: Some(Utc::now() - Duration::days(30)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_quiet_known_device_scores_zero() {
+        let score = compute_score(&established(KNOWN_MAC), Utc::now());
+        assert_eq!(score.score, 0);
+        assert_eq!(score.level, "low");
+        assert!(score.factors.is_empty());
+    }
+
+    #[test]
+    fn test_sensitive_ports_and_new_device() {
+        let mut inputs = established(KNOWN_MAC);
+        inputs.open_ports = vec![22, 23, 80, 8080];
+        inputs.first_seen_at = Some(Utc::now() - Duration::days(2));
+
+        let score = compute_score(&inputs, Utc::now());
+        assert_eq!(
+            names(&score),
+            vec![
+                "ssh_port_open",
+                "telnet_port_open",
+                "http_alt_port_open",
+                "new_device"
+            ]
+        );
+        assert_eq!(score.score, 45);
+        assert_eq!(score.level, "medium");
+    }
+
+    #[test]
+    fn test_randomized_mac_adds_to_unknown_vendor() {
+        let score = compute_score(&established(RANDOM_MAC), Utc::now());
+        assert_eq!(names(&score), vec!["unknown_vendor", "randomized_mac"]);
+        assert_eq!(score.score, 25);
+    }
+
+    #[test]
+    fn test_critical_alerts_capped_at_forty() {
+        let mut inputs = established(KNOWN_MAC);
+        inputs.unacknowledged_critical_alerts = 5;
+        let score = compute_score(&inputs, Utc::now());
+        assert_eq!(
+            score.factors,
+            vec![ScoreFactor {
+                name: "unacknowledged_critical_alerts",
+                impact: 40
+            }]
+        );
+    }
+
+    #[test]
+    fn test_positive_signals_subtract_and_floor_at_zero() {
+        let mut inputs = established(KNOWN_MAC);
+        inputs.open_ports = vec![22];
+        inputs.hostname = Some("nas".to_string());
+        inputs.agent_last_report_at = Some(Utc::now() - Duration::minutes(5));
+        let score = compute_score(&inputs, Utc::now());
+        assert_eq!(
+            names(&score),
+            vec!["ssh_port_open", "recent_agent_report", "known_hostname"]
+        );
+        assert_eq!(score.score, 0);
+
+        // A stale agent report earns no credit.
+        inputs.agent_last_report_at = Some(Utc::now() - Duration::days(2));
+        assert_eq!(compute_score(&inputs, Utc::now()).score, 5);
+    }
+
+    #[test]
+    fn test_score_capped_at_hundred() {
+        let mut inputs = established(RANDOM_MAC);
+        inputs.open_ports = vec![22, 23, 3389, 8080];
+        inputs.unacknowledged_critical_alerts = 3;
+        inputs.first_seen_at = Some(Utc::now());
+        let score = compute_score(&inputs, Utc::now());
+        // 60 (ports) + 15 + 10 + 5 + 40 = 130
+        assert_eq!(score.score, 100);
+        assert_eq!(score.level, "critical");
+    }
+
+    #[tokio::test]
+    async fn test_load_inputs_from_db() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, hostname, first_seen_at, last_seen_at) \
+             VALUES ('d1', ?, 'printer', datetime('now', '-1 day'), datetime('now'))",
+        )
+        .bind(KNOWN_MAC)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"INSERT INTO port_scans (device_id, result_json) VALUES ('d1',
+               '[{"port":23,"protocol":"tcp","state":"open","service":"telnet","version":""},
+                 {"port":22,"protocol":"tcp","state":"closed","service":"ssh","version":""}]')"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (id, severity, acked) in [
+            ("a1", "CRITICAL", None),
+            ("a2", "CRITICAL", Some("2026-01-01 00:00:00")),
+            ("a3", "WARNING", None),
+        ] {
+            sqlx::query(
+                "INSERT INTO alerts (id, type, device_id, message, severity, acknowledged_at) \
+                 VALUES (?, 'new_device', 'd1', 'test', ?, ?)",
+            )
+            .bind(id)
+            .bind(severity)
+            .bind(acked)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let inputs = load_inputs(&pool, "d1").await.unwrap().unwrap();
+        assert_eq!(inputs.open_ports, vec![23]);
+        assert_eq!(inputs.unacknowledged_critical_alerts, 1);
+        assert!(inputs.agent_last_report_at.is_none());
+
+        let score = compute_score(&inputs, Utc::now());
+        assert_eq!(
+            names(&score),
+            vec![
+                "telnet_port_open",
+                "new_device",
+                "unacknowledged_critical_alerts",
+                "known_hostname"
+            ]
+        );
+        assert_eq!(score.score, 45);
+
+        assert!(load_inputs(&pool, "missing").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,467 @@
+//! Minimal BER encoding/decoding for SNMPv2c messages.
+//!
+//! Covers exactly what polling needs: Get/GetNext requests and responses
+//! with the SMIv2 application types. Anything else decodes as
+//! [`SnmpValue::Other`] instead of failing the whole message.
+
+use serde::Serialize;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_COUNTER32: u8 = 0x41;
+const TAG_GAUGE32: u8 = 0x42;
+const TAG_TIMETICKS: u8 = 0x43;
+const TAG_COUNTER64: u8 = 0x46;
+const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+
+pub const PDU_GET: u8 = 0xA0;
+pub const PDU_GET_NEXT: u8 = 0xA1;
+pub const PDU_RESPONSE: u8 = 0xA2;
+
+/// SNMP version field value for v2c.
+pub const VERSION_2C: i64 = 1;
+
+/// An object identifier, e.g. `1.3.6.1.2.1.1.1.0`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Oid(pub Vec<u32>);
+
+impl Oid {
+    /// Parse dotted notation. The first arc must be 0, 1 or 2 and, below 2,
+    /// the second must be under 40 (X.690 8.19.4), so the first two arcs
+    /// always fit the single subidentifier they are encoded as.
+    pub fn parse(s: &str) -> Option<Self> {
+        let arcs = s
+            .trim_start_matches('.')
+            .split('.')
+            .map(|a| a.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        let (&first, &second) = (arcs.first()?, arcs.get(1)?);
+        let valid = match first {
+            0 | 1 => second < 40,
+            2 => second <= u32::MAX - 80,
+            _ => false,
+        };
+        valid.then_some(Self(arcs))
+    }
+
+    pub fn starts_with(&self, prefix: &Oid) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+}
+
+impl std::fmt::Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arcs: Vec<String> = self.0.iter().map(u32::to_string).collect();
+        f.write_str(&arcs.join("."))
+    }
+}
+
+impl Serialize for Oid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A decoded variable binding value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SnmpValue {
+    Integer(i64),
+    /// Octet strings, decoded lossily as UTF-8.
+    String(String),
+    Null,
+    Oid(Oid),
+    IpAddress(String),
+    Counter32(u64),
+    Gauge32(u64),
+    /// Hundredths of a second.
+    TimeTicks(u64),
+    Counter64(u64),
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+    /// Unsupported type, with its BER tag.
+    Other(u8),
+}
+
+/// One `oid = value` pair.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VarBind {
+    pub oid: Oid,
+    pub value: SnmpValue,
+}
+
+/// A decoded SNMP message (request or response).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub version: i64,
+    pub community: String,
+    pub pdu_type: u8,
+    pub request_id: i64,
+    pub error_status: i64,
+    pub error_index: i64,
+    pub varbinds: Vec<VarBind>,
+}
+
+// ── Encoding ────────────────────────────────────────────
+
+fn encode_length(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    encode_length(content.len(), &mut out);
+    out.extend_from_slice(content);
+    out
+}
+
+fn encode_integer(tag: u8, value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    // Drop redundant leading bytes while keeping the sign bit intact.
+    let mut start = 0;
+    while start < 7 {
+        let (b, next) = (bytes[start], bytes[start + 1]);
+        if (b == 0x00 && next & 0x80 == 0) || (b == 0xFF && next & 0x80 != 0) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+    tlv(tag, &bytes[start..])
+}
+
+fn encode_unsigned(tag: u8, value: u64) -> Vec<u8> {
+    let mut content = value.to_be_bytes().to_vec();
+    while content.len() > 1 && content[0] == 0 && content[1] & 0x80 == 0 {
+        content.remove(0);
+    }
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    tlv(tag, &content)
+}
+
+fn encode_oid(oid: &Oid) -> Vec<u8> {
+    let arcs = &oid.0;
+    let mut content = Vec::new();
+    let first = arcs.first().copied().unwrap_or(0) * 40 + arcs.get(1).copied().unwrap_or(0);
+    for &arc in std::iter::once(&first).chain(arcs.iter().skip(2)) {
+        let mut chunk = vec![(arc & 0x7F) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push(0x80 | (rest & 0x7F) as u8);
+            rest >>= 7;
+        }
+        chunk.reverse();
+        content.extend(chunk);
+    }
+    tlv(TAG_OID, &content)
+}
+
+fn encode_value(value: &SnmpValue) -> Vec<u8> {
+    match value {
+        SnmpValue::Integer(v) => encode_integer(TAG_INTEGER, *v),
+        SnmpValue::String(s) => tlv(TAG_OCTET_STRING, s.as_bytes()),
+        SnmpValue::Null | SnmpValue::Other(_) => tlv(TAG_NULL, &[]),
+        SnmpValue::Oid(oid) => encode_oid(oid),
+        SnmpValue::IpAddress(ip) => {
+            let octets = ip
+                .parse::<std::net::Ipv4Addr>()
+                .map(|a| a.octets())
+                .unwrap_or_default();
+            tlv(TAG_IP_ADDRESS, &octets)
+        }
+        SnmpValue::Counter32(v) => encode_unsigned(TAG_COUNTER32, *v),
+        SnmpValue::Gauge32(v) => encode_unsigned(TAG_GAUGE32, *v),
+        SnmpValue::TimeTicks(v) => encode_unsigned(TAG_TIMETICKS, *v),
+        SnmpValue::Counter64(v) => encode_unsigned(TAG_COUNTER64, *v),
+        SnmpValue::NoSuchObject => tlv(TAG_NO_SUCH_OBJECT, &[]),
+        SnmpValue::NoSuchInstance => tlv(TAG_NO_SUCH_INSTANCE, &[]),
+        SnmpValue::EndOfMibView => tlv(TAG_END_OF_MIB_VIEW, &[]),
+    }
+}
+
+/// Encode a complete SNMP message.
+pub fn encode_message(msg: &Message) -> Vec<u8> {
+    let mut varbinds = Vec::new();
+    for vb in &msg.varbinds {
+        let mut pair = encode_oid(&vb.oid);
+        pair.extend(encode_value(&vb.value));
+        varbinds.extend(tlv(TAG_SEQUENCE, &pair));
+    }
+
+    let mut pdu = encode_integer(TAG_INTEGER, msg.request_id);
+    pdu.extend(encode_integer(TAG_INTEGER, msg.error_status));
+    pdu.extend(encode_integer(TAG_INTEGER, msg.error_index));
+    pdu.extend(tlv(TAG_SEQUENCE, &varbinds));
+
+    let mut body = encode_integer(TAG_INTEGER, msg.version);
+    body.extend(tlv(TAG_OCTET_STRING, msg.community.as_bytes()));
+    body.extend(tlv(msg.pdu_type, &pdu));
+    tlv(TAG_SEQUENCE, &body)
+}
+
+/// Encode a Get or GetNext request for `oids`.
+pub fn encode_request(pdu_type: u8, community: &str, request_id: i64, oids: &[Oid]) -> Vec<u8> {
+    encode_message(&Message {
+        version: VERSION_2C,
+        community: community.to_string(),
+        pdu_type,
+        request_id,
+        error_status: 0,
+        error_index: 0,
+        varbinds: oids
+            .iter()
+            .map(|oid| VarBind {
+                oid: oid.clone(),
+                value: SnmpValue::Null,
+            })
+            .collect(),
+    })
+}
+
+// ── Decoding ────────────────────────────────────────────
+
+/// Split one TLV off the front of `buf`: `(tag, content, rest)`.
+fn read_tlv(buf: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *buf.first()?;
+    let first = *buf.get(1)?;
+    let (len, header) = if first & 0x80 == 0 {
+        (first as usize, 2)
+    } else {
+        let n = (first & 0x7F) as usize;
+        if n == 0 || n > 4 {
+            return None;
+        }
+        let len = buf
+            .get(2..2 + n)?
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, 2 + n)
+    };
+    let content = buf.get(header..header + len)?;
+    Some((tag, content, &buf[header + len..]))
+}
+
+fn expect_tlv(buf: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    let (t, content, rest) = read_tlv(buf)?;
+    (t == tag).then_some((content, rest))
+}
+
+fn decode_integer(content: &[u8]) -> Option<i64> {
+    if content.is_empty() || content.len() > 8 {
+        return None;
+    }
+    let init: i64 = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    Some(
+        content
+            .iter()
+            .fold(init, |acc, &b| (acc << 8) | i64::from(b)),
+    )
+}
+
+fn decode_unsigned(content: &[u8]) -> Option<u64> {
+    // A leading zero byte may pad the value to 9 bytes for Counter64.
+    let content = match content {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        other => other,
+    };
+    if content.is_empty() || content.len() > 8 {
+        return None;
+    }
+    Some(
+        content
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | u64::from(b)),
+    )
+}
+
+fn decode_oid(content: &[u8]) -> Option<Oid> {
+    let mut values = Vec::new();
+    let mut current: u32 = 0;
+    for &b in content {
+        current = current.checked_mul(128)? | u32::from(b & 0x7F);
+        if b & 0x80 == 0 {
+            values.push(current);
+            current = 0;
+        }
+    }
+    let first = *values.first()?;
+    let (a, b) = if first < 80 {
+        (first / 40, first % 40)
+    } else {
+        (2, first - 80)
+    };
+    let mut arcs = vec![a, b];
+    arcs.extend_from_slice(&values[1..]);
+    Some(Oid(arcs))
+}
+
+fn decode_value(tag: u8, content: &[u8]) -> SnmpValue {
+    let decoded = match tag {
+        TAG_INTEGER => decode_integer(content).map(SnmpValue::Integer),
+        TAG_OCTET_STRING => Some(SnmpValue::String(
+            String::from_utf8_lossy(content).into_owned(),
+        )),
+        TAG_NULL => Some(SnmpValue::Null),
+        TAG_OID => decode_oid(content).map(SnmpValue::Oid),
+        TAG_IP_ADDRESS if content.len() == 4 => Some(SnmpValue::IpAddress(
+            std::net::Ipv4Addr::new(content[0], content[1], content[2], content[3]).to_string(),
+        )),
+        TAG_COUNTER32 => decode_unsigned(content).map(SnmpValue::Counter32),
+        TAG_GAUGE32 => decode_unsigned(content).map(SnmpValue::Gauge32),
+        TAG_TIMETICKS => decode_unsigned(content).map(SnmpValue::TimeTicks),
+        TAG_COUNTER64 => decode_unsigned(content).map(SnmpValue::Counter64),
+        TAG_NO_SUCH_OBJECT => Some(SnmpValue::NoSuchObject),
+        TAG_NO_SUCH_INSTANCE => Some(SnmpValue::NoSuchInstance),
+        TAG_END_OF_MIB_VIEW => Some(SnmpValue::EndOfMibView),
+        _ => None,
+    };
+    decoded.unwrap_or(SnmpValue::Other(tag))
+}
+
+/// Decode a complete SNMP message. Returns `None` if it is malformed.
+pub fn decode_message(buf: &[u8]) -> Option<Message> {
+    let (body, _) = expect_tlv(buf, TAG_SEQUENCE)?;
+    let (version, rest) = expect_tlv(body, TAG_INTEGER)?;
+    let (community, rest) = expect_tlv(rest, TAG_OCTET_STRING)?;
+    let (pdu_type, pdu, _) = read_tlv(rest)?;
+
+    let (request_id, rest) = expect_tlv(pdu, TAG_INTEGER)?;
+    let (error_status, rest) = expect_tlv(rest, TAG_INTEGER)?;
+    let (error_index, rest) = expect_tlv(rest, TAG_INTEGER)?;
+    let (mut list, _) = expect_tlv(rest, TAG_SEQUENCE)?;
+
+    let mut varbinds = Vec::new();
+    while !list.is_empty() {
+        let (pair, rest) = expect_tlv(list, TAG_SEQUENCE)?;
+        list = rest;
+        let (oid, value_buf) = expect_tlv(pair, TAG_OID)?;
+        let (tag, content, _) = read_tlv(value_buf)?;
+        varbinds.push(VarBind {
+            oid: decode_oid(oid)?,
+            value: decode_value(tag, content),
+        });
+    }
+
+    Some(Message {
+        version: decode_integer(version)?,
+        community: String::from_utf8_lossy(community).into_owned(),
+        pdu_type,
+        request_id: decode_integer(request_id)?,
+        error_status: decode_integer(error_status)?,
+        error_index: decode_integer(error_index)?,
+        varbinds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_get_request_matches_reference_bytes() {
+        // snmpget -v2c -c public <host> 1.3.6.1.2.1.1.1.0 with request-id 1.
+        let oid = Oid::parse("1.3.6.1.2.1.1.1.0").unwrap();
+        let bytes = encode_request(PDU_GET, "public", 1, &[oid]);
+        let expected: &[u8] = &[
+            0x30, 0x26, 0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', 0xA0,
+            0x19, 0x02, 0x01, 0x01, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x0E, 0x30, 0x0C,
+            0x06, 0x08, 0x2B, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00,
+        ];
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_message_roundtrip_with_application_types() {
+        let msg = Message {
+            version: VERSION_2C,
+            community: "s3cret".to_string(),
+            pdu_type: PDU_RESPONSE,
+            request_id: 0x1234_5678,
+            error_status: 0,
+            error_index: 0,
+            varbinds: vec![
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.2.1.1.3.0").unwrap(),
+                    value: SnmpValue::TimeTicks(4_294_967_295),
+                },
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.2.1.2.2.1.5.1").unwrap(),
+                    value: SnmpValue::Gauge32(1_000_000_000),
+                },
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.2.1.31.1.1.1.6.1").unwrap(),
+                    value: SnmpValue::Counter64(u64::MAX),
+                },
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.4.1.99999.1").unwrap(),
+                    value: SnmpValue::Integer(-129),
+                },
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.2.1.4.20.1.1.10.0.0.1").unwrap(),
+                    value: SnmpValue::IpAddress("10.0.0.1".to_string()),
+                },
+                VarBind {
+                    oid: Oid::parse("1.3.6.1.2.1.1.9.9").unwrap(),
+                    value: SnmpValue::EndOfMibView,
+                },
+            ],
+        };
+        assert_eq!(decode_message(&encode_message(&msg)), Some(msg));
+    }
+
+    #[test]
+    fn test_long_form_length_and_large_arcs() {
+        let descr = "x".repeat(300);
+        let oid = Oid::parse("1.3.6.1.4.1.2636.3.1.13.1.5.9.1.0.0").unwrap();
+        let msg = Message {
+            version: VERSION_2C,
+            community: "public".to_string(),
+            pdu_type: PDU_RESPONSE,
+            request_id: 7,
+            error_status: 0,
+            error_index: 0,
+            varbinds: vec![VarBind {
+                oid: oid.clone(),
+                value: SnmpValue::String(descr.clone()),
+            }],
+        };
+        let decoded = decode_message(&encode_message(&msg)).unwrap();
+        assert_eq!(decoded.varbinds[0].oid, oid);
+        assert_eq!(decoded.varbinds[0].value, SnmpValue::String(descr));
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_message() {
+        let bytes = encode_request(PDU_GET, "public", 1, &[Oid::parse("1.3.6.1").unwrap()]);
+        assert!(decode_message(&bytes[..bytes.len() - 3]).is_none());
+    }
+
+    #[test]
+    fn test_oid_parse_and_display() {
+        let oid = Oid::parse(".1.3.6.1.2.1.1.1.0").unwrap();
+        assert_eq!(oid.to_string(), "1.3.6.1.2.1.1.1.0");
+        assert!(oid.starts_with(&Oid::parse("1.3.6.1.2.1.1").unwrap()));
+        assert!(Oid::parse("1.3.x").is_none());
+        assert!(Oid::parse("1").is_none());
+        assert!(Oid::parse("2.999.1").is_some());
+        assert!(Oid::parse("1.40.1").is_none());
+        assert!(Oid::parse("4294967295.1").is_none());
+        assert!(Oid::parse("2.4294967295").is_none());
+    }
+}
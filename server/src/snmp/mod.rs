@@ -0,0 +1,381 @@
+//! SNMP v2c polling for device enrichment.
+//!
+//! Polls the standard system group (sysDescr, sysUpTime) and the ifTable
+//! columns operators care about (ifDescr, ifSpeed, ifOperStatus) from a
+//! device using the community configured in `device_snmp`.
+
+pub mod ber;
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tokio::sync::Mutex;
+
+use ber::{Oid, SnmpValue, VarBind, PDU_GET, PDU_GET_NEXT, PDU_RESPONSE};
+
+pub const OID_SYS_DESCR: &str = "1.3.6.1.2.1.1.1.0";
+pub const OID_SYS_UPTIME: &str = "1.3.6.1.2.1.1.3.0";
+pub const OID_IF_DESCR: &str = "1.3.6.1.2.1.2.2.1.2";
+pub const OID_IF_SPEED: &str = "1.3.6.1.2.1.2.2.1.5";
+pub const OID_IF_OPER_STATUS: &str = "1.3.6.1.2.1.2.2.1.8";
+
+/// How long a poll result is served from cache.
+pub const POLL_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Per-request timeout; each request is retried once.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upper bound on rows fetched per table walk.
+const MAX_WALK_ROWS: usize = 1024;
+
+/// Per-device poll results with the instant they were fetched.
+pub type SnmpPollCache = Arc<Mutex<HashMap<String, (Instant, SnmpPollResult)>>>;
+
+/// SNMP settings stored per device in `device_snmp`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnmpConfig {
+    pub community: String,
+    /// Only `"2c"` is supported.
+    pub version: String,
+    pub port: u16,
+}
+
+/// One row of the ifTable.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnmpInterface {
+    pub index: u32,
+    pub descr: Option<String>,
+    /// Bits per second as reported by ifSpeed.
+    pub speed: Option<u64>,
+    /// ifOperStatus as text (`up`, `down`, ...).
+    pub oper_status: Option<String>,
+}
+
+/// Result of polling a device.
+#[derive(Debug, Clone, Serialize)]
+pub struct SnmpPollResult {
+    pub device_id: String,
+    pub target: String,
+    pub polled_at: String,
+    pub sys_descr: Option<String>,
+    /// sysUpTime in hundredths of a second.
+    pub sys_uptime_ticks: Option<u64>,
+    pub interfaces: Vec<SnmpInterface>,
+    /// Every variable binding returned, in request order.
+    pub varbinds: Vec<VarBind>,
+}
+
+/// Map an ifOperStatus value to its RFC 2863 name.
+fn oper_status_name(value: i64) -> String {
+    match value {
+        1 => "up",
+        2 => "down",
+        3 => "testing",
+        4 => "unknown",
+        5 => "dormant",
+        6 => "notPresent",
+        7 => "lowerLayerDown",
+        _ => return value.to_string(),
+    }
+    .to_string()
+}
+
+fn next_request_id() -> i64 {
+    static NEXT_ID: AtomicI32 = AtomicI32::new(1);
+    i64::from(NEXT_ID.fetch_add(1, Ordering::Relaxed) & 0x7FFF_FFFF)
+}
+
+/// A minimal SNMP v2c client over UDP.
+pub struct SnmpClient {
+    target: SocketAddr,
+    community: String,
+    socket: UdpSocket,
+}
+
+impl SnmpClient {
+    pub async fn connect(target: SocketAddr, community: &str) -> Result<Self> {
+        let bind: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(bind)
+            .await
+            .context("failed to bind SNMP socket")?;
+        socket
+            .connect(target)
+            .await
+            .context("failed to connect SNMP socket")?;
+        Ok(Self {
+            target,
+            community: community.to_string(),
+            socket,
+        })
+    }
+
+    /// Send one request and wait for the matching response, retrying once.
+    async fn request(&self, pdu_type: u8, oids: &[Oid]) -> Result<Vec<VarBind>> {
+        let request_id = next_request_id();
+        let packet = ber::encode_request(pdu_type, &self.community, request_id, oids);
+        let mut buf = vec![0u8; 65535];
+
+        for _attempt in 0..2 {
+            self.socket.send(&packet).await?;
+            let deadline = tokio::time::Instant::now() + REQUEST_TIMEOUT;
+            loop {
+                let len = match tokio::time::timeout_at(deadline, self.socket.recv(&mut buf)).await
+                {
+                    Ok(res) => res?,
+                    Err(_) => break,
+                };
+                let Some(msg) = ber::decode_message(&buf[..len]) else {
+                    continue;
+                };
+                // Ignore late replies to earlier requests.
+                if msg.pdu_type != PDU_RESPONSE || msg.request_id != request_id {
+                    continue;
+                }
+                if msg.error_status != 0 {
+                    bail!(
+                        "SNMP error status {} (index {}) from {}",
+                        msg.error_status,
+                        msg.error_index,
+                        self.target
+                    );
+                }
+                return Ok(msg.varbinds);
+            }
+        }
+        bail!("SNMP request to {} timed out", self.target)
+    }
+
+    /// GET the given scalar OIDs.
+    pub async fn get(&self, oids: &[Oid]) -> Result<Vec<VarBind>> {
+        self.request(PDU_GET, oids).await
+    }
+
+    /// Walk the subtree under `root` with GETNEXT.
+    pub async fn walk(&self, root: &Oid) -> Result<Vec<VarBind>> {
+        let mut rows = Vec::new();
+        let mut current = root.clone();
+        while rows.len() < MAX_WALK_ROWS {
+            let mut reply = self.request(PDU_GET_NEXT, &[current.clone()]).await?;
+            let Some(vb) = reply.pop() else { break };
+            if !vb.oid.starts_with(root)
+                || vb.oid <= current
+                || matches!(vb.value, SnmpValue::EndOfMibView)
+            {
+                break;
+            }
+            current = vb.oid.clone();
+            rows.push(vb);
+        }
+        Ok(rows)
+    }
+}
+
+/// The ifTable row for a column varbind, keyed by its last arc (ifIndex).
+fn interface_row<'a>(
+    rows: &'a mut BTreeMap<u32, SnmpInterface>,
+    vb: &VarBind,
+) -> &'a mut SnmpInterface {
+    let index = vb.oid.0.last().copied().unwrap_or(0);
+    rows.entry(index).or_insert_with(|| SnmpInterface {
+        index,
+        descr: None,
+        speed: None,
+        oper_status: None,
+    })
+}
+
+/// Build the ifTable from the three walked columns.
+fn build_interfaces(
+    descr: &[VarBind],
+    speed: &[VarBind],
+    status: &[VarBind],
+) -> Vec<SnmpInterface> {
+    let mut rows: BTreeMap<u32, SnmpInterface> = BTreeMap::new();
+    for vb in descr {
+        if let SnmpValue::String(s) = &vb.value {
+            interface_row(&mut rows, vb).descr = Some(s.clone());
+        }
+    }
+    for vb in speed {
+        if let SnmpValue::Gauge32(v) | SnmpValue::Counter32(v) = vb.value {
+            interface_row(&mut rows, vb).speed = Some(v);
+        }
+    }
+    for vb in status {
+        if let SnmpValue::Integer(v) = vb.value {
+            interface_row(&mut rows, vb).oper_status = Some(oper_status_name(v));
+        }
+    }
+    rows.into_values().collect()
+}
+
+/// Poll sysDescr, sysUpTime and the ifTable from `target`.
+pub async fn poll(device_id: &str, target: SocketAddr, community: &str) -> Result<SnmpPollResult> {
+    let client = SnmpClient::connect(target, community).await?;
+
+    let scalars = [OID_SYS_DESCR, OID_SYS_UPTIME]
+        .iter()
+        .filter_map(|s| Oid::parse(s))
+        .collect::<Vec<_>>();
+    let system = client.get(&scalars).await?;
+
+    let mut columns = Vec::new();
+    for column in [OID_IF_DESCR, OID_IF_SPEED, OID_IF_OPER_STATUS] {
+        let root = Oid::parse(column).expect("valid ifTable OID");
+        columns.push(client.walk(&root).await?);
+    }
+
+    let sys_descr = system.iter().find_map(|vb| match &vb.value {
+        SnmpValue::String(s) if vb.oid.to_string() == OID_SYS_DESCR => Some(s.clone()),
+        _ => None,
+    });
+    let sys_uptime_ticks = system.iter().find_map(|vb| match vb.value {
+        SnmpValue::TimeTicks(t) => Some(t),
+        _ => None,
+    });
+    let interfaces = build_interfaces(&columns[0], &columns[1], &columns[2]);
+
+    Ok(SnmpPollResult {
+        device_id: device_id.to_string(),
+        target: target.to_string(),
+        polled_at: chrono::Utc::now().to_rfc3339(),
+        sys_descr,
+        sys_uptime_ticks,
+        interfaces,
+        varbinds: system
+            .into_iter()
+            .chain(columns.into_iter().flatten())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ber::Message;
+
+    /// Spawn a fake SNMP agent on localhost answering GET/GETNEXT from `mib`.
+    async fn spawn_agent(community: &'static str, mib: Vec<(&str, SnmpValue)>) -> SocketAddr {
+        let mib: BTreeMap<Oid, SnmpValue> = mib
+            .into_iter()
+            .map(|(oid, value)| (Oid::parse(oid).unwrap(), value))
+            .collect();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 65535];
+            loop {
+                let Ok((len, peer)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let Some(req) = ber::decode_message(&buf[..len]) else {
+                    continue;
+                };
+                // Wrong community: real agents stay silent.
+                if req.community != community {
+                    continue;
+                }
+                let varbinds = req
+                    .varbinds
+                    .iter()
+                    .map(|vb| {
+                        let found = if req.pdu_type == PDU_GET_NEXT {
+                            mib.range((
+                                std::ops::Bound::Excluded(vb.oid.clone()),
+                                std::ops::Bound::Unbounded,
+                            ))
+                            .next()
+                            .map(|(o, v)| (o.clone(), v.clone()))
+                        } else {
+                            mib.get(&vb.oid).map(|v| (vb.oid.clone(), v.clone()))
+                        };
+                        match found {
+                            Some((oid, value)) => VarBind { oid, value },
+                            None if req.pdu_type == PDU_GET_NEXT => VarBind {
+                                oid: vb.oid.clone(),
+                                value: SnmpValue::EndOfMibView,
+                            },
+                            None => VarBind {
+                                oid: vb.oid.clone(),
+                                value: SnmpValue::NoSuchObject,
+                            },
+                        }
+                    })
+                    .collect();
+                let resp = ber::encode_message(&Message {
+                    pdu_type: PDU_RESPONSE,
+                    varbinds,
+                    ..req
+                });
+                let _ = socket.send_to(&resp, peer).await;
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_poll_fake_agent() {
+        let addr = spawn_agent(
+            "lab",
+            vec![
+                (OID_SYS_DESCR, SnmpValue::String("EdgeSwitch 24".into())),
+                ("1.3.6.1.2.1.1.3.0", SnmpValue::TimeTicks(123_456)),
+                ("1.3.6.1.2.1.1.5.0", SnmpValue::String("sw1".into())),
+                ("1.3.6.1.2.1.2.2.1.2.1", SnmpValue::String("0/1".into())),
+                ("1.3.6.1.2.1.2.2.1.2.2", SnmpValue::String("0/2".into())),
+                ("1.3.6.1.2.1.2.2.1.5.1", SnmpValue::Gauge32(1_000_000_000)),
+                ("1.3.6.1.2.1.2.2.1.5.2", SnmpValue::Gauge32(100_000_000)),
+                ("1.3.6.1.2.1.2.2.1.8.1", SnmpValue::Integer(1)),
+                ("1.3.6.1.2.1.2.2.1.8.2", SnmpValue::Integer(2)),
+                ("1.3.6.1.2.1.2.2.1.9.1", SnmpValue::TimeTicks(0)),
+            ],
+        )
+        .await;
+
+        let result = poll("dev-1", addr, "lab")
+            .await
+            .expect("poll should succeed");
+        assert_eq!(result.sys_descr.as_deref(), Some("EdgeSwitch 24"));
+        assert_eq!(result.sys_uptime_ticks, Some(123_456));
+        assert_eq!(
+            result.interfaces,
+            vec![
+                SnmpInterface {
+                    index: 1,
+                    descr: Some("0/1".into()),
+                    speed: Some(1_000_000_000),
+                    oper_status: Some("up".into()),
+                },
+                SnmpInterface {
+                    index: 2,
+                    descr: Some("0/2".into()),
+                    speed: Some(100_000_000),
+                    oper_status: Some("down".into()),
+                },
+            ]
+        );
+        // 2 scalars + 3 columns × 2 rows; the walk stops at the column boundary.
+        assert_eq!(result.varbinds.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_get_times_out_on_wrong_community() {
+        let addr = spawn_agent("lab", vec![(OID_SYS_DESCR, SnmpValue::Null)]).await;
+        let client = SnmpClient::connect(addr, "public").await.unwrap();
+        let err = client
+            .get(&[Oid::parse(OID_SYS_DESCR).unwrap()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
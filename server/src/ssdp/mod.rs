@@ -0,0 +1,386 @@
+//! Passive SSDP/UPnP listener for devices that announce themselves but are
+//! missed by ARP scans (smart TVs, Hue bridges, Sonos speakers, ...).
+//!
+//! Joins the SSDP multicast group and, for each `NOTIFY ssdp:alive`, fetches
+//! the device description from the `LOCATION` URL. The friendly name,
+//! manufacturer and model are stored as JSON in `devices.upnp_info` for the
+//! device currently holding the sender's IP, and `upnp` is added to its
+//! `mdns_services` list.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use sqlx::SqlitePool;
+use tokio::net::UdpSocket;
+use tracing::{debug, info, warn};
+
+use crate::mdns::upsert_mdns_info;
+
+/// SSDP multicast group.
+pub const SSDP_MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+/// SSDP port.
+pub const SSDP_PORT: u16 = 1900;
+
+/// Service type recorded in `mdns_services` for UPnP devices.
+const UPNP_SERVICE: &str = "upnp";
+
+/// Timeout for fetching a device description.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Device descriptions larger than this are ignored.
+const MAX_DESCRIPTION_BYTES: usize = 64 * 1024;
+/// Devices re-announce every few minutes; refetch a description at most this often.
+const REFETCH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Headers of an `ssdp:alive` announcement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SsdpNotify {
+    pub usn: String,
+    pub nt: String,
+    pub location: String,
+}
+
+/// UPnP device description fields stored in `devices.upnp_info`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpnpInfo {
+    /// Always `"upnp"`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub friendly_name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model_name: Option<String>,
+}
+
+impl UpnpInfo {
+    fn is_empty(&self) -> bool {
+        self.friendly_name.is_none() && self.manufacturer.is_none() && self.model_name.is_none()
+    }
+}
+
+/// Parse an SSDP datagram, returning its headers if it is a
+/// `NOTIFY * HTTP/1.1` with `NTS: ssdp:alive` and all of `USN`, `NT` and
+/// `LOCATION` present. Header names are case-insensitive.
+pub fn parse_notify(datagram: &str) -> Option<SsdpNotify> {
+    let mut lines = datagram.lines();
+    if !lines
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("NOTIFY * HTTP/1.1")
+    {
+        return None;
+    }
+
+    let (mut usn, mut nt, mut nts, mut location) = (None, None, None, None);
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "usn" => usn = Some(value),
+            "nt" => nt = Some(value),
+            "nts" => nts = Some(value),
+            "location" => location = Some(value),
+            _ => {}
+        }
+    }
+
+    if !nts?.eq_ignore_ascii_case("ssdp:alive") {
+        return None;
+    }
+    Some(SsdpNotify {
+        usn: usn?,
+        nt: nt?,
+        location: location.filter(|l| !l.is_empty())?,
+    })
+}
+
+/// Text content of the first `<tag>` element, with XML entities decoded.
+fn xml_element(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let len = xml[start..].find(&format!("</{tag}>"))?;
+    let value = xml[start..start + len]
+        .trim()
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    (!value.is_empty()).then_some(value)
+}
+
+/// Extract the root device's `friendlyName`, `manufacturer` and `modelName`
+/// from a UPnP device description.
+pub fn parse_device_description(xml: &str) -> UpnpInfo {
+    UpnpInfo {
+        kind: UPNP_SERVICE.to_string(),
+        friendly_name: xml_element(xml, "friendlyName"),
+        manufacturer: xml_element(xml, "manufacturer"),
+        model_name: xml_element(xml, "modelName"),
+    }
+}
+
+/// Whether `location` is an http URL served by `sender`. Announcements
+/// pointing elsewhere are ignored so a spoofed NOTIFY can't make the server
+/// fetch arbitrary URLs.
+pub fn location_matches_sender(location: &str, sender: IpAddr) -> bool {
+    let Ok(url) = reqwest::Url::parse(location) else {
+        return false;
+    };
+    let host_ip = url
+        .host_str()
+        .map(|h| h.trim_start_matches('[').trim_end_matches(']'))
+        .and_then(|h| h.parse::<IpAddr>().ok());
+    url.scheme() == "http" && host_ip == Some(sender)
+}
+
+/// Download and parse the device description at `location`.
+async fn fetch_description(client: &reqwest::Client, location: &str) -> Result<UpnpInfo, String> {
+    let resp = client
+        .get(location)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    if resp
+        .content_length()
+        .is_some_and(|len| len > MAX_DESCRIPTION_BYTES as u64)
+    {
+        return Err("device description too large".to_string());
+    }
+    let body = resp.bytes().await.map_err(|e| e.to_string())?;
+    if body.len() > MAX_DESCRIPTION_BYTES {
+        return Err("device description too large".to_string());
+    }
+    Ok(parse_device_description(&String::from_utf8_lossy(&body)))
+}
+
+/// Store `info` on the device currently holding `ip` and record `upnp` in its
+/// service list. Unknown IPs are skipped until the ARP scanner has seen them.
+pub async fn upsert_upnp_info(
+    pool: &SqlitePool,
+    ip: &str,
+    info: &UpnpInfo,
+) -> Result<(), sqlx::Error> {
+    let json = serde_json::to_string(info).unwrap_or_default();
+    let updated = sqlx::query(
+        r#"UPDATE devices SET upnp_info = ?
+           WHERE id = (SELECT device_id FROM device_ips WHERE ip = ? AND is_current = 1 LIMIT 1)"#,
+    )
+    .bind(&json)
+    .bind(ip)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        debug!("SSDP: no device found for IP {ip}, skipping");
+        return Ok(());
+    }
+
+    upsert_mdns_info(pool, ip, "", UPNP_SERVICE).await
+}
+
+/// Bind `0.0.0.0:1900` with `SO_REUSEADDR` (so other SSDP listeners on the
+/// host keep working) and join the SSDP multicast group.
+fn bind_multicast_socket() -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, SSDP_PORT).into())?;
+    socket.join_multicast_v4(&SSDP_MULTICAST_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// Start the passive SSDP listener. Runs until the socket fails to bind.
+pub async fn start_ssdp_listener(pool: SqlitePool) {
+    let socket = match bind_multicast_socket() {
+        Ok(s) => {
+            info!(port = SSDP_PORT, "SSDP/UPnP passive discovery listening");
+            s
+        }
+        Err(e) => {
+            warn!("Failed to join SSDP multicast group (multicast may not be supported on this interface): {e}");
+            return;
+        }
+    };
+
+    let client = match reqwest::Client::builder().timeout(FETCH_TIMEOUT).build() {
+        Ok(c) => c,
+        Err(e) => {
+            warn!(error = %e, "Failed to build reqwest client for SSDP");
+            return;
+        }
+    };
+
+    // Last fetch time per LOCATION URL.
+    let mut fetched: HashMap<String, Instant> = HashMap::new();
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let (len, sender) = match socket.recv_from(&mut buf).await {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("SSDP receive error: {e}");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        let Some(notify) = parse_notify(&String::from_utf8_lossy(&buf[..len])) else {
+            continue;
+        };
+        let sender_ip = sender.ip();
+        if !location_matches_sender(&notify.location, sender_ip) {
+            debug!(
+                "SSDP: ignoring {} from {sender_ip} with foreign LOCATION",
+                notify.usn
+            );
+            continue;
+        }
+        if fetched
+            .get(&notify.location)
+            .is_some_and(|at| at.elapsed() < REFETCH_INTERVAL)
+        {
+            continue;
+        }
+        fetched.retain(|_, at| at.elapsed() < REFETCH_INTERVAL);
+        fetched.insert(notify.location.clone(), Instant::now());
+
+        debug!(
+            "SSDP alive: usn={} nt={} from {sender_ip}",
+            notify.usn, notify.nt
+        );
+
+        match fetch_description(&client, &notify.location).await {
+            Ok(info) if info.is_empty() => {
+                debug!("SSDP: empty device description at {}", notify.location);
+            }
+            Ok(info) => {
+                let ip = sender_ip.to_string();
+                if let Err(e) = upsert_upnp_info(&pool, &ip, &info).await {
+                    warn!("Failed to upsert UPnP info for {ip}: {e}");
+                }
+            }
+            Err(e) => debug!("SSDP: failed to fetch {}: {e}", notify.location),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALIVE: &str = "NOTIFY * HTTP/1.1\r\n\
+        HOST: 239.255.255.250:1900\r\n\
+        CACHE-CONTROL: max-age=1800\r\n\
+        location: http://192.168.1.20:80/description.xml\r\n\
+        NT: upnp:rootdevice\r\n\
+        NTS: ssdp:alive\r\n\
+        USN: uuid:2f402f80-da50-11e1-9b23-001788255acc::upnp:rootdevice\r\n\r\n";
+
+    #[test]
+    fn test_parse_notify() {
+        let notify = parse_notify(ALIVE).unwrap();
+        assert_eq!(notify.location, "http://192.168.1.20:80/description.xml");
+        assert_eq!(notify.nt, "upnp:rootdevice");
+        assert!(notify.usn.starts_with("uuid:2f402f80"));
+
+        assert!(parse_notify(&ALIVE.replace("ssdp:alive", "ssdp:byebye")).is_none());
+        assert!(parse_notify(&ALIVE.replace("NOTIFY *", "M-SEARCH *")).is_none());
+        assert!(parse_notify(&ALIVE.replace("location:", "X-Other:")).is_none());
+    }
+
+    #[test]
+    fn test_parse_device_description() {
+        let xml = r#"<?xml version="1.0"?>
+            <root xmlns="urn:schemas-upnp-org:device-1-0">
+              <device>
+                <friendlyName>Hue Bridge (192.168.1.20)</friendlyName>
+                <manufacturer>Signify</manufacturer>
+                <modelName>Philips hue bridge 2015</modelName>
+                <deviceList>
+                  <device><friendlyName>Embedded</friendlyName></device>
+                </deviceList>
+              </device>
+            </root>"#;
+        let info = parse_device_description(xml);
+        assert_eq!(
+            info.friendly_name.as_deref(),
+            Some("Hue Bridge (192.168.1.20)")
+        );
+        assert_eq!(info.manufacturer.as_deref(), Some("Signify"));
+        assert_eq!(info.model_name.as_deref(), Some("Philips hue bridge 2015"));
+
+        let info = parse_device_description("<root><modelName>A &amp; B</modelName></root>");
+        assert_eq!(info.model_name.as_deref(), Some("A & B"));
+        assert!(info.friendly_name.is_none());
+    }
+
+    #[test]
+    fn test_location_matches_sender() {
+        let sender: IpAddr = "192.168.1.20".parse().unwrap();
+        assert!(location_matches_sender(
+            "http://192.168.1.20:80/desc.xml",
+            sender
+        ));
+        assert!(!location_matches_sender(
+            "http://192.168.1.21/desc.xml",
+            sender
+        ));
+        assert!(!location_matches_sender(
+            "http://router.local/desc.xml",
+            sender
+        ));
+        assert!(!location_matches_sender("file:///etc/passwd", sender));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_upnp_info() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at, mdns_services) \
+             VALUES ('dev-1', 'AA:BB:CC:DD:EE:01', datetime('now'), datetime('now'), '_hue._tcp')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
+             VALUES ('dev-1', '192.168.1.20', datetime('now'), 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let info = UpnpInfo {
+            kind: UPNP_SERVICE.to_string(),
+            friendly_name: Some("Hue Bridge".to_string()),
+            manufacturer: Some("Signify".to_string()),
+            model_name: None,
+        };
+        upsert_upnp_info(&pool, "192.168.1.20", &info)
+            .await
+            .unwrap();
+        // Unknown IPs are ignored.
+        upsert_upnp_info(&pool, "192.168.1.99", &info)
+            .await
+            .unwrap();
+
+        let (json, services, hostname): (String, String, Option<String>) = sqlx::query_as(
+            "SELECT upnp_info, mdns_services, hostname FROM devices WHERE id = 'dev-1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let stored: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(stored["type"], "upnp");
+        assert_eq!(stored["friendly_name"], "Hue Bridge");
+        assert_eq!(stored["model_name"], serde_json::Value::Null);
+        assert_eq!(services, "_hue._tcp,upnp");
+        assert_eq!(hostname, None);
+    }
+}
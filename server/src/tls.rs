@@ -0,0 +1,194 @@
+//! Certificates for the HTTPS listener.
+//!
+//! Certificates and keys may be PEM or DER. An unreadable, mismatched or
+//! expired certificate aborts startup instead of silently falling back to
+//! plain HTTP.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tracing::{info, warn};
+
+/// Warn at startup when the certificate expires within this many days.
+const EXPIRY_WARNING_DAYS: i64 = 14;
+
+fn read_file(path: &Path, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).with_context(|| format!("cannot read TLS {what} {}", path.display()))
+}
+
+fn is_pem(bytes: &[u8]) -> bool {
+    bytes.trim_ascii_start().starts_with(b"-----BEGIN")
+}
+
+/// Read a PEM certificate chain, or a single DER certificate.
+pub fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let bytes = read_file(path, "certificate")?;
+    let certs = if is_pem(&bytes) {
+        rustls_pemfile::certs(&mut bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("invalid PEM certificate {}", path.display()))?
+    } else {
+        vec![CertificateDer::from(bytes)]
+    };
+    if certs.is_empty() {
+        bail!("no certificate found in {}", path.display());
+    }
+    Ok(certs)
+}
+
+/// Read a PEM private key (PKCS#8, PKCS#1 or SEC1), or a DER key.
+pub fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    let bytes = read_file(path, "private key")?;
+    if is_pem(&bytes) {
+        rustls_pemfile::private_key(&mut bytes.as_slice())
+            .with_context(|| format!("invalid PEM private key {}", path.display()))?
+            .ok_or_else(|| anyhow!("no private key found in {}", path.display()))
+    } else {
+        PrivateKeyDer::try_from(bytes)
+            .map_err(|e| anyhow!("invalid DER private key {}: {e}", path.display()))
+    }
+}
+
+/// Return the certificate's `not_after` (Unix seconds), failing if it is
+/// already past at `now`.
+pub fn check_not_expired(cert: &CertificateDer<'_>, now: i64) -> Result<i64> {
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref())
+        .map_err(|e| anyhow!("cannot parse certificate: {e}"))?;
+    let not_after = parsed.validity().not_after;
+    if not_after.timestamp() <= now {
+        bail!("certificate expired on {not_after}");
+    }
+    Ok(not_after.timestamp())
+}
+
+/// Build the rustls configuration for the HTTPS listener.
+pub fn server_config(cert_path: &Path, key_path: &Path) -> Result<RustlsConfig> {
+    let certs = load_certs(cert_path)?;
+    let key = load_key(key_path)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let not_after = check_not_expired(&certs[0], now)
+        .with_context(|| format!("TLS certificate {}", cert_path.display()))?;
+    let days_left = (not_after - now) / 86400;
+    if days_left < EXPIRY_WARNING_DAYS {
+        warn!(days_left, cert = %cert_path.display(), "TLS certificate expires soon");
+    } else {
+        info!(days_left, cert = %cert_path.display(), "TLS certificate loaded");
+    }
+
+    // Pin the provider: both ring and aws-lc-rs are compiled into rustls via
+    // other dependencies, so there is no unambiguous process default.
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .context("TLS certificate and private key do not match")?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(RustlsConfig::from_config(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("panoptikon-tls-{}-{name}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn self_signed() -> rcgen::CertifiedKey<rcgen::KeyPair> {
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap()
+    }
+
+    #[test]
+    fn test_server_config_pem_and_der() {
+        let generated = self_signed();
+        let pem_cert = temp_file("cert.pem", generated.cert.pem().as_bytes());
+        let pem_key = temp_file("key.pem", generated.signing_key.serialize_pem().as_bytes());
+        let der_cert = temp_file("cert.der", generated.cert.der());
+        let der_key = temp_file("key.der", &generated.signing_key.serialize_der());
+
+        assert!(server_config(&pem_cert, &pem_key).is_ok());
+        assert!(server_config(&der_cert, &der_key).is_ok());
+        assert!(server_config(&pem_cert, &der_key).is_ok());
+
+        let other = self_signed();
+        let other_key = temp_file("other.pem", other.signing_key.serialize_pem().as_bytes());
+        let err = server_config(&pem_cert, &other_key).unwrap_err();
+        assert!(format!("{err:#}").contains("do not match"), "{err:#}");
+
+        for path in [pem_cert, pem_key, der_cert, der_key, other_key] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_expired_or_missing_certificate_is_rejected() {
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        params.not_before = rcgen::date_time_ymd(2020, 1, 1);
+        params.not_after = rcgen::date_time_ymd(2021, 1, 1);
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+
+        let cert_path = temp_file("expired.pem", cert.pem().as_bytes());
+        let key_path = temp_file("expired-key.pem", key.serialize_pem().as_bytes());
+        let err = server_config(&cert_path, &key_path).unwrap_err();
+        assert!(format!("{err:#}").contains("expired"), "{err:#}");
+
+        let err = server_config(Path::new("/nonexistent/cert.pem"), &key_path).unwrap_err();
+        assert!(format!("{err:#}").contains("cannot read TLS certificate"));
+
+        let empty = temp_file(
+            "empty.pem",
+            b"-----BEGIN NOTHING-----\n-----END NOTHING-----\n",
+        );
+        assert!(load_certs(&empty).is_err());
+
+        for path in [cert_path, key_path, empty] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_https_listener_serves_router() {
+        let generated = self_signed();
+        let cert = temp_file("cert.pem", generated.cert.pem().as_bytes());
+        let key = temp_file("key.pem", generated.signing_key.serialize_pem().as_bytes());
+        let tls = server_config(&cert, &key).unwrap();
+
+        let app = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+        let handle = axum_server::Handle::new();
+        let server = axum_server::bind_rustls("127.0.0.1:0".parse().unwrap(), tls)
+            .handle(handle.clone())
+            .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>());
+        tokio::spawn(server);
+        let addr = handle.listening().await.unwrap();
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap();
+        let body = client
+            .get(format!("https://localhost:{}/health", addr.port()))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "ok");
+
+        handle.shutdown();
+        std::fs::remove_file(cert).unwrap();
+        std::fs::remove_file(key).unwrap();
+    }
+}
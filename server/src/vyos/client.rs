@@ -42,6 +42,11 @@ impl VyosClient {
         }
     }
 
+    /// Router URL this client talks to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     /// POST /retrieve — read running configuration at `path`.
     pub async fn retrieve(&self, path: &[&str]) -> Result<Value> {
         let data = serde_json::json!({
@@ -84,6 +89,18 @@ impl VyosClient {
         self.post_form("/configure", &data).await
     }
 
+    /// POST /configure — set several paths in a single commit.
+    ///
+    /// VyOS applies a list of operations atomically, so either every path is
+    /// set or none are.
+    pub async fn configure_set_many(&self, paths: &[Vec<&str>]) -> Result<Value> {
+        let data: Vec<Value> = paths
+            .iter()
+            .map(|path| serde_json::json!({ "op": "set", "path": path }))
+            .collect();
+        self.post_form("/configure", &Value::Array(data)).await
+    }
+
     /// Run an nmap scan against a target IP via VyOS.
     ///
     /// Uses the VyOS `/show` endpoint with `path: ["nmap", "-sV", "<ip>"]`.
@@ -150,8 +167,34 @@ impl VyosClient {
         anyhow::bail!("VyOS API does not support 'show iperf3'. Use run_speedtest_ookla() instead.")
     }
 
+    /// Run a traceroute from the router to `target` out of `interface`.
+    ///
+    /// Traceroutes to distant hosts can take much longer than the default
+    /// request timeout, so this call allows up to 30 seconds.
+    /// Returns the raw traceroute text output.
+    pub async fn run_traceroute(&self, target: &str, interface: &str) -> Result<String> {
+        let data = serde_json::json!({
+            "op": "show",
+            "path": ["traceroute", target, "interface", interface],
+        });
+        let value = self
+            .post_form_with_timeout("/show", &data, Some(Duration::from_secs(30)))
+            .await?;
+        Ok(value.as_str().unwrap_or_default().to_string())
+    }
+
     /// Low-level helper: send a multipart form POST to the VyOS API.
     async fn post_form(&self, endpoint: &str, data: &Value) -> Result<Value> {
+        self.post_form_with_timeout(endpoint, data, None).await
+    }
+
+    /// Like [`post_form`](Self::post_form), optionally overriding the client timeout.
+    async fn post_form_with_timeout(
+        &self,
+        endpoint: &str,
+        data: &Value,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
         let url = format!("{}{endpoint}", self.base_url);
         let data_str = serde_json::to_string(data)?;
 
@@ -159,13 +202,11 @@ impl VyosClient {
             .text("data", data_str)
             .text("key", self.api_key.clone());
 
-        let resp = self
-            .http
-            .post(&url)
-            .multipart(form)
-            .send()
-            .await
-            .context("VyOS API request failed")?;
+        let mut request = self.http.post(&url).multipart(form);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        let resp = request.send().await.context("VyOS API request failed")?;
 
         let status = resp.status();
         let body = resp
@@ -0,0 +1,473 @@
+//! VyOS config archiving to a git repository.
+//!
+//! Retrieves the running configuration, writes it as pretty-printed JSON to
+//! `vyos-config.json` and pushes a commit to the configured repository.
+//! Settings (`git_archive_repo_url`, `git_archive_branch`,
+//! `git_archive_auth_token`) live in the settings table; the token is stored
+//! encrypted.
+
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::api::audit;
+use crate::api::router_profiles::ActiveRouterProfile;
+use crate::config::{AppConfig, SharedConfig};
+
+/// File name of the archived config inside the repository.
+pub const ARCHIVE_FILE_NAME: &str = "vyos-config.json";
+
+/// Branch used when `git_archive_branch` is not set.
+const DEFAULT_BRANCH: &str = "main";
+
+/// How often the scheduler checks whether a daily archive is due.
+const SCHEDULE_CHECK_SECS: u64 = 3600;
+
+/// Git repository settings for config archiving.
+#[derive(Debug, Clone)]
+pub struct ArchiveSettings {
+    pub repo_url: String,
+    pub branch: String,
+    pub auth_token: Option<String>,
+}
+
+/// Result of a successful archive run.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveResult {
+    /// SHA of the branch tip after the run.
+    pub commit_sha: String,
+    /// `false` when the config was unchanged and no commit was created.
+    pub changed: bool,
+    pub branch: String,
+}
+
+/// Errors that can occur while archiving.
+#[derive(Debug)]
+pub enum ArchiveError {
+    /// No repository URL configured.
+    NotConfigured,
+    /// VyOS is not configured or the config could not be retrieved.
+    Vyos(String),
+    /// The remote rejected our credentials.
+    Auth(String),
+    /// Any other git failure (network, bad URL, rejected push).
+    Git(String),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::NotConfigured => write!(f, "Git archive repository is not configured"),
+            ArchiveError::Vyos(msg) => write!(f, "VyOS error: {msg}"),
+            ArchiveError::Auth(msg) => write!(f, "Git authentication failed: {msg}"),
+            ArchiveError::Git(msg) => write!(f, "Git error: {msg}"),
+        }
+    }
+}
+
+impl From<git2::Error> for ArchiveError {
+    fn from(e: git2::Error) -> Self {
+        if e.code() == git2::ErrorCode::Auth
+            || (e.class() == git2::ErrorClass::Http && e.message().contains("401"))
+        {
+            ArchiveError::Auth(e.message().to_string())
+        } else {
+            ArchiveError::Git(e.message().to_string())
+        }
+    }
+}
+
+/// Read a non-empty setting value.
+async fn get_setting(db: &SqlitePool, key: &str) -> Option<String> {
+    sqlx::query_scalar::<_, String>("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(db)
+        .await
+        .ok()
+        .flatten()
+        .filter(|v| !v.is_empty())
+}
+
+/// Load archive settings, or `None` if no repository URL is configured.
+pub async fn load_settings(db: &SqlitePool) -> Option<ArchiveSettings> {
+    let repo_url = get_setting(db, "git_archive_repo_url").await?;
+    let branch = get_setting(db, "git_archive_branch")
+        .await
+        .unwrap_or_else(|| DEFAULT_BRANCH.to_string());
+    let auth_token = crate::crypto::get_sealed_setting(db, "git_archive_auth_token").await;
+    Some(ArchiveSettings {
+        repo_url,
+        branch,
+        auth_token,
+    })
+}
+
+/// Credentials callback offering `token` once. libgit2 calls it again when
+/// the remote rejects the credential; answering with the same token would
+/// end in a generic "authentication replays" error, so the second call
+/// fails with [`git2::ErrorCode::Auth`] instead.
+fn token_credentials(
+    token: &str,
+) -> impl FnMut(&str, Option<&str>, git2::CredentialType) -> Result<git2::Cred, git2::Error> + '_ {
+    let mut offered = false;
+    move |_url, _username, _allowed| {
+        if std::mem::replace(&mut offered, true) {
+            return Err(git2::Error::new(
+                git2::ErrorCode::Auth,
+                git2::ErrorClass::Http,
+                "remote rejected the auth token",
+            ));
+        }
+        // GitHub/Gitea accept any username with a token as password;
+        // GitLab expects "oauth2".
+        git2::Cred::userpass_plaintext("oauth2", token)
+    }
+}
+
+/// Build fetch/push callbacks that authenticate with the configured token.
+fn remote_callbacks(token: Option<&str>) -> git2::RemoteCallbacks<'_> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    if let Some(token) = token {
+        callbacks.credentials(token_credentials(token));
+    }
+    callbacks
+}
+
+/// Commit `content` as [`ARCHIVE_FILE_NAME`] on the configured branch and push it.
+///
+/// Blocking — call from `spawn_blocking`. Uses a throwaway bare clone in the
+/// system temp directory. If the file is unchanged no commit is created and
+/// the current branch tip is returned.
+pub fn push_config(
+    settings: &ArchiveSettings,
+    content: &str,
+    message: &str,
+) -> Result<ArchiveResult, ArchiveError> {
+    let workdir = std::env::temp_dir().join(format!("panoptikon-archive-{}", uuid::Uuid::new_v4()));
+    let result = push_config_in(&workdir, settings, content, message);
+    let _ = std::fs::remove_dir_all(&workdir);
+    result
+}
+
+fn push_config_in(
+    workdir: &Path,
+    settings: &ArchiveSettings,
+    content: &str,
+    message: &str,
+) -> Result<ArchiveResult, ArchiveError> {
+    let token = settings.auth_token.as_deref();
+
+    let mut fetch_opts = git2::FetchOptions::new();
+    fetch_opts.remote_callbacks(remote_callbacks(token));
+    let repo = git2::build::RepoBuilder::new()
+        .bare(true)
+        .fetch_options(fetch_opts)
+        .clone(&settings.repo_url, workdir)?;
+
+    // Only the remote's default branch gets a local ref on clone; other
+    // branches are reachable through their remote-tracking ref.
+    let branch_ref = format!("refs/heads/{}", settings.branch);
+    let tracking_ref = format!("refs/remotes/origin/{}", settings.branch);
+    let parent = [&branch_ref, &tracking_ref]
+        .iter()
+        .find_map(|name| repo.find_reference(name).ok())
+        .and_then(|r| r.peel_to_commit().ok());
+
+    let blob = repo.blob(content.as_bytes())?;
+    let parent_tree = parent.as_ref().map(|c| c.tree()).transpose()?;
+    let mut builder = repo.treebuilder(parent_tree.as_ref())?;
+    builder.insert(ARCHIVE_FILE_NAME, blob, 0o100644)?;
+    let tree_id = builder.write()?;
+
+    if let (Some(parent), Some(parent_tree)) = (&parent, &parent_tree) {
+        if parent_tree.id() == tree_id {
+            return Ok(ArchiveResult {
+                commit_sha: parent.id().to_string(),
+                changed: false,
+                branch: settings.branch.clone(),
+            });
+        }
+    }
+
+    let tree = repo.find_tree(tree_id)?;
+    let signature = git2::Signature::now("Panoptikon", "panoptikon@localhost")?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let commit_id = repo.commit(
+        Some(&branch_ref),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )?;
+
+    let rejection = std::cell::RefCell::new(None::<String>);
+    let mut push_opts = git2::PushOptions::new();
+    let mut callbacks = remote_callbacks(token);
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(msg) = status {
+            *rejection.borrow_mut() = Some(format!("{refname}: {msg}"));
+        }
+        Ok(())
+    });
+    push_opts.remote_callbacks(callbacks);
+
+    let mut remote = repo.find_remote("origin")?;
+    remote.push(
+        &[format!("{branch_ref}:{branch_ref}")],
+        Some(&mut push_opts),
+    )?;
+    drop(push_opts);
+
+    if let Some(msg) = rejection.into_inner() {
+        return Err(ArchiveError::Git(format!("push rejected: {msg}")));
+    }
+
+    Ok(ArchiveResult {
+        commit_sha: commit_id.to_string(),
+        changed: true,
+        branch: settings.branch.clone(),
+    })
+}
+
+/// Retrieve the running VyOS config and archive it, recording the outcome
+/// in the audit log.
+pub async fn run_archive(
+    db: &SqlitePool,
+    config: &AppConfig,
+    active_profile: &ActiveRouterProfile,
+    username: &str,
+) -> Result<ArchiveResult, ArchiveError> {
+    let settings = load_settings(db).await.ok_or(ArchiveError::NotConfigured)?;
+
+    let description = format!(
+        "Archive VyOS config to {} ({})",
+        settings.repo_url, settings.branch
+    );
+    let commands = vec!["showConfig".to_string()];
+
+    let result = archive_with_settings(db, config, active_profile, &settings, username).await;
+    match &result {
+        Ok(r) => {
+            let description = format!("{description} — commit {}", r.commit_sha);
+            audit::log_success(
+                db,
+                audit::SYSTEM_ACTOR,
+                "config_archive",
+                &description,
+                &commands,
+            )
+            .await;
+        }
+        Err(e) => {
+            audit::log_failure(
+                db,
+                audit::SYSTEM_ACTOR,
+                "config_archive",
+                &description,
+                &commands,
+                &e.to_string(),
+            )
+            .await;
+        }
+    }
+    result
+}
+
+async fn archive_with_settings(
+    db: &SqlitePool,
+    config: &AppConfig,
+    active_profile: &ActiveRouterProfile,
+    settings: &ArchiveSettings,
+    username: &str,
+) -> Result<ArchiveResult, ArchiveError> {
+    let client = crate::api::vyos::get_vyos_client_from_db(db, config, active_profile)
+        .await
+        .ok_or_else(|| ArchiveError::Vyos("Router not configured".to_string()))?;
+    let value = client
+        .retrieve(&[])
+        .await
+        .map_err(|e| ArchiveError::Vyos(e.to_string()))?;
+    let content =
+        serde_json::to_string_pretty(&value).map_err(|e| ArchiveError::Vyos(e.to_string()))? + "\n";
+
+    let timestamp = Utc::now().to_rfc3339();
+    let message = format!("Panoptikon auto-backup: {timestamp} by {username}");
+
+    let settings = settings.clone();
+    tokio::task::spawn_blocking(move || push_config(&settings, &content, &message))
+        .await
+        .map_err(|e| ArchiveError::Git(format!("archive task failed: {e}")))?
+}
+
+/// Whether the daily archive schedule is enabled.
+pub async fn schedule_enabled(db: &SqlitePool) -> bool {
+    get_setting(db, "git_archive_schedule_enabled")
+        .await
+        .as_deref()
+        == Some("true")
+}
+
+/// Whether more than a day has passed since the last scheduled archive.
+async fn archive_due(db: &SqlitePool) -> bool {
+    let Some(last_run) = get_setting(db, "git_archive_last_run_at").await else {
+        return true;
+    };
+    let row: Option<(i64,)> =
+        sqlx::query_as(r#"SELECT 1 WHERE datetime(?, '+1 day') <= datetime('now')"#)
+            .bind(&last_run)
+            .fetch_optional(db)
+            .await
+            .unwrap_or(None);
+    row.is_some()
+}
+
+/// Start the background task that runs the daily config archive when enabled.
+pub fn start_config_archive_task(
+    db: SqlitePool,
+    config: SharedConfig,
+    active_profile: ActiveRouterProfile,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULE_CHECK_SECS));
+        interval.tick().await; // skip the immediate first tick
+        loop {
+            interval.tick().await;
+            if !schedule_enabled(&db).await || !archive_due(&db).await {
+                continue;
+            }
+
+            // Record the attempt up front so a failing remote is retried daily, not hourly.
+            let _ = sqlx::query(
+                r#"INSERT INTO settings (key, value) VALUES ('git_archive_last_run_at', datetime('now'))
+                   ON CONFLICT(key) DO UPDATE SET value = datetime('now')"#,
+            )
+            .execute(&db)
+            .await;
+
+            let current = crate::config::read(&config).clone();
+            match run_archive(&db, &current, &active_profile, "scheduler").await {
+                Ok(r) => info!(
+                    commit = %r.commit_sha,
+                    changed = r.changed,
+                    "Scheduled VyOS config archive completed"
+                ),
+                Err(e) => error!("Scheduled VyOS config archive failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty bare repository to act as the remote.
+    fn bare_remote() -> (std::path::PathBuf, ArchiveSettings) {
+        let path = std::env::temp_dir().join(format!("panoptikon-remote-{}", uuid::Uuid::new_v4()));
+        git2::Repository::init_bare(&path).expect("init bare remote");
+        let settings = ArchiveSettings {
+            repo_url: path.to_string_lossy().to_string(),
+            branch: "main".to_string(),
+            auth_token: None,
+        };
+        (path, settings)
+    }
+
+    #[test]
+    fn test_push_config_creates_commit() {
+        let (path, settings) = bare_remote();
+
+        let first = push_config(&settings, "{\"a\": 1}\n", "first").expect("first push");
+        assert!(first.changed);
+
+        let remote = git2::Repository::open_bare(&path).unwrap();
+        let tip = remote
+            .find_reference("refs/heads/main")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        assert_eq!(tip.id().to_string(), first.commit_sha);
+        assert_eq!(tip.message(), Some("first"));
+        let entry = tip.tree().unwrap().get_name(ARCHIVE_FILE_NAME).is_some();
+        assert!(entry, "archive file should be committed");
+
+        // Same content: no new commit.
+        let same = push_config(&settings, "{\"a\": 1}\n", "again").expect("second push");
+        assert!(!same.changed);
+        assert_eq!(same.commit_sha, first.commit_sha);
+
+        // Changed content: new commit on top of the previous one.
+        let second = push_config(&settings, "{\"a\": 2}\n", "second").expect("third push");
+        assert!(second.changed);
+        let tip = remote
+            .find_reference("refs/heads/main")
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        assert_eq!(tip.id().to_string(), second.commit_sha);
+        assert_eq!(tip.parent_id(0).unwrap().to_string(), first.commit_sha);
+
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn test_push_config_bad_remote() {
+        let settings = ArchiveSettings {
+            repo_url: "/nonexistent/panoptikon/repo.git".to_string(),
+            branch: "main".to_string(),
+            auth_token: None,
+        };
+        let err = push_config(&settings, "{}", "msg").unwrap_err();
+        assert!(matches!(err, ArchiveError::Git(_)));
+    }
+
+    #[test]
+    fn test_rejected_token_is_auth_error() {
+        let mut credentials = token_credentials("bad-token");
+        assert!(credentials(
+            "https://git.example/repo.git",
+            None,
+            git2::CredentialType::USER_PASS_PLAINTEXT
+        )
+        .is_ok());
+        let err = credentials(
+            "https://git.example/repo.git",
+            None,
+            git2::CredentialType::USER_PASS_PLAINTEXT,
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(ArchiveError::from(err), ArchiveError::Auth(_)));
+    }
+
+    #[test]
+    fn test_push_config_rejected_token() {
+        use std::io::{Read, Write};
+
+        // HTTP remote that answers every request with 401.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"git\"\r\n\
+                      Content-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+
+        let settings = ArchiveSettings {
+            repo_url: format!("http://{addr}/repo.git"),
+            branch: "main".to_string(),
+            auth_token: Some("bad-token".to_string()),
+        };
+        let err = push_config(&settings, "{}", "msg").unwrap_err();
+        assert!(matches!(err, ArchiveError::Auth(_)), "got {err}");
+    }
+}
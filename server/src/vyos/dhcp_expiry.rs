@@ -0,0 +1,238 @@
+//! Hourly check for DHCP leases about to expire on online devices.
+//!
+//! Fetches `show dhcp server leases` from VyOS and raises a
+//! `dhcp_lease_expiring` alert for every active lease that expires within
+//! two hours and belongs to a device Panoptikon currently sees online.
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::api::alerts::{is_device_muted, severity_for_alert_type, SeverityOverrideCache};
+use crate::api::router_profiles::ActiveRouterProfile;
+use crate::api::vyos::{
+    filter_expiring_leases, get_vyos_client_from_db, parse_dhcp_leases_text, ExpiringDhcpLease,
+};
+use crate::config::SharedConfig;
+use crate::webhook;
+use crate::ws::hub::WsHub;
+
+/// How often leases are checked.
+const CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Leases expiring within this many seconds raise an alert.
+const ALERT_WITHIN_SECS: i64 = 2 * 3600;
+
+/// Create `dhcp_lease_expiring` alerts for active leases of online devices.
+///
+/// A device already alerted within the last [`ALERT_WITHIN_SECS`] is skipped
+/// so the hourly check doesn't repeat itself for the same lease.
+/// Returns the number of alerts created.
+pub async fn create_expiry_alerts(
+    db: &SqlitePool,
+    leases: &[ExpiringDhcpLease],
+    ws_hub: &WsHub,
+    severities: &SeverityOverrideCache,
+) -> anyhow::Result<usize> {
+    let now = Utc::now();
+    let recent_cutoff = (now - chrono::Duration::seconds(ALERT_WITHIN_SECS)).to_rfc3339();
+    let severity = severity_for_alert_type("dhcp_lease_expiring", db, severities).await;
+    let mut created = 0;
+
+    for expiring in leases {
+        let lease = &expiring.lease;
+        if lease.state != "active" || expiring.remaining_secs > ALERT_WITHIN_SECS {
+            continue;
+        }
+
+        let device_id: Option<String> = sqlx::query_scalar(
+            r#"SELECT d.id FROM device_ips di
+               JOIN devices d ON d.id = di.device_id
+               WHERE di.ip = ? AND di.is_current = 1 AND d.is_online = 1
+               LIMIT 1"#,
+        )
+        .bind(&lease.ip)
+        .fetch_optional(db)
+        .await?;
+        let Some(device_id) = device_id else {
+            continue;
+        };
+
+        if is_device_muted(db, &device_id).await {
+            continue;
+        }
+
+        let already_alerted: bool = sqlx::query_scalar::<_, i64>(
+            r#"SELECT COUNT(*) FROM alerts
+               WHERE type = 'dhcp_lease_expiring' AND device_id = ? AND created_at >= ?"#,
+        )
+        .bind(&device_id)
+        .bind(&recent_cutoff)
+        .fetch_one(db)
+        .await?
+            > 0;
+        if already_alerted {
+            continue;
+        }
+
+        let minutes = expiring.remaining_secs / 60;
+        let details = json!({
+            "ip": &lease.ip,
+            "mac": &lease.mac,
+            "remaining_secs": expiring.remaining_secs,
+            "lease_expiry": &lease.lease_expiry,
+        });
+        sqlx::query(
+            r#"INSERT INTO alerts (id, type, device_id, message, details, severity, created_at)
+               VALUES (?, 'dhcp_lease_expiring', ?, ?, ?, ?, ?)"#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(&device_id)
+        .bind(format!(
+            "DHCP lease for {} ({}) expires in {minutes} min",
+            lease.ip, lease.mac
+        ))
+        .bind(details.to_string())
+        .bind(&severity)
+        .bind(now.to_rfc3339())
+        .execute(db)
+        .await?;
+        created += 1;
+        crate::api::metrics::record_alert_created("dhcp_lease_expiring");
+
+        let payload = json!({"device_id": &device_id, "ip": &lease.ip, "mac": &lease.mac});
+        ws_hub.broadcast("dhcp_lease_expiring", payload.clone());
+        webhook::dispatch_webhook(db, "dhcp_lease_expiring", payload);
+    }
+
+    Ok(created)
+}
+
+/// Start the hourly DHCP lease expiry check (no-op while VyOS is not configured).
+pub fn start_dhcp_expiry_task(
+    db: SqlitePool,
+    config: SharedConfig,
+    active_profile: ActiveRouterProfile,
+    ws_hub: Arc<WsHub>,
+    severities: SeverityOverrideCache,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+        interval.tick().await; // skip the immediate first tick
+        loop {
+            interval.tick().await;
+            let current = crate::config::read(&config).clone();
+            let Some(client) = get_vyos_client_from_db(&db, &current, &active_profile).await else {
+                continue;
+            };
+            let text = match client.show(&["dhcp", "server", "leases"]).await {
+                Ok(value) => value.as_str().unwrap_or("").to_string(),
+                Err(e) => {
+                    warn!("DHCP lease expiry check: VyOS query failed: {e}");
+                    continue;
+                }
+            };
+
+            let leases = filter_expiring_leases(
+                parse_dhcp_leases_text(&text),
+                ALERT_WITHIN_SECS,
+                Utc::now(),
+            );
+            match create_expiry_alerts(&db, &leases, &ws_hub, &severities).await {
+                Ok(0) => {}
+                Ok(n) => info!(alerts = n, "Created DHCP lease expiry alerts"),
+                Err(e) => error!("DHCP lease expiry check failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::vyos::VyosDhcpLease;
+
+    fn expiring(ip: &str, remaining_secs: i64) -> ExpiringDhcpLease {
+        ExpiringDhcpLease {
+            lease: VyosDhcpLease {
+                ip: ip.to_string(),
+                mac: "aa:bb:cc:dd:ee:ff".to_string(),
+                hostname: None,
+                state: "active".to_string(),
+                lease_start: None,
+                lease_expiry: None,
+                remaining: None,
+                pool: None,
+            },
+            remaining_secs,
+        }
+    }
+
+    async fn insert_device(pool: &SqlitePool, ip: &str, online: bool) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO devices (id, mac, is_online, first_seen_at, last_seen_at) \
+             VALUES (?, ?, ?, datetime('now'), datetime('now'))",
+        )
+        .bind(&id)
+        .bind(&id)
+        .bind(online as i64)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO device_ips (device_id, ip, seen_at, is_current) \
+             VALUES (?, ?, datetime('now'), 1)",
+        )
+        .bind(&id)
+        .bind(ip)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    #[tokio::test]
+    async fn test_expiry_alerts_only_for_online_devices() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        let hub = WsHub::new();
+        let severities = SeverityOverrideCache::new();
+        insert_device(&pool, "10.0.0.5", true).await;
+        insert_device(&pool, "10.0.0.66", false).await;
+
+        let leases = vec![
+            expiring("10.0.0.5", 1800),
+            expiring("10.0.0.66", 1800),
+            expiring("10.0.0.99", 1800),
+        ];
+        let created = create_expiry_alerts(&pool, &leases, &hub, &severities)
+            .await
+            .unwrap();
+        assert_eq!(created, 1);
+
+        // A second run within the window doesn't duplicate the alert.
+        let created = create_expiry_alerts(&pool, &leases, &hub, &severities)
+            .await
+            .unwrap();
+        assert_eq!(created, 0);
+    }
+
+    #[tokio::test]
+    async fn test_expiry_alerts_skip_leases_beyond_two_hours() {
+        let pool = crate::db::init(":memory:").await.unwrap();
+        insert_device(&pool, "10.0.0.5", true).await;
+
+        let created = create_expiry_alerts(
+            &pool,
+            &[expiring("10.0.0.5", 3 * 3600)],
+            &WsHub::new(),
+            &SeverityOverrideCache::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created, 0);
+    }
+}
@@ -0,0 +1,165 @@
+//! Periodic sampling of firewall rule counters into `firewall_rule_stats`.
+//!
+//! Every few minutes the live packet/byte counters of every configured chain
+//! are stored with the previous sample's values, so the hit top-N endpoint
+//! can sum per-rule deltas over a period. A counter lower than the previous
+//! sample means the router rebooted or the rule was recreated; the delta
+//! then counts from zero.
+
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::api::router_profiles::ActiveRouterProfile;
+use crate::api::vyos::{
+    get_vyos_client_from_db, parse_firewall_config, parse_firewall_rule_counters,
+    FirewallRuleCounters,
+};
+use crate::config::SharedConfig;
+
+/// How often counters are sampled.
+const SAMPLE_INTERVAL_SECS: u64 = 300;
+
+/// Samples older than this are pruned (the longest hit top-N period).
+const RETENTION_DAYS: i64 = 30;
+
+/// Store one sample per rule of `chain` (VyOS path, e.g. "ipv4.forward.filter").
+///
+/// The first sample of a rule records no delta, since the counters may have
+/// accumulated long before Panoptikon started watching.
+pub async fn record_samples(
+    db: &SqlitePool,
+    chain: &str,
+    counters: &[FirewallRuleCounters],
+) -> Result<(), sqlx::Error> {
+    for counter in counters {
+        let packets = counter.packet_count as i64;
+        let bytes = counter.byte_count as i64;
+        let last: Option<(i64, i64)> = sqlx::query_as(
+            r#"SELECT packets, bytes FROM firewall_rule_stats
+               WHERE chain = ? AND rule_number = ?
+               ORDER BY id DESC LIMIT 1"#,
+        )
+        .bind(chain)
+        .bind(counter.rule_number)
+        .fetch_optional(db)
+        .await?;
+
+        let (prev_packets, prev_bytes) = match last {
+            None => (packets, bytes),
+            Some((last_packets, last_bytes)) if packets < last_packets || bytes < last_bytes => {
+                (0, 0)
+            }
+            Some(last) => last,
+        };
+
+        sqlx::query(
+            r#"INSERT INTO firewall_rule_stats
+                   (chain, rule_number, packets, prev_packets, bytes, prev_bytes)
+               VALUES (?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(chain)
+        .bind(counter.rule_number)
+        .bind(packets)
+        .bind(prev_packets)
+        .bind(bytes)
+        .bind(prev_bytes)
+        .execute(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Start sampling firewall counters (no-op while VyOS is not configured).
+pub fn start_firewall_stats_task(
+    db: SqlitePool,
+    config: SharedConfig,
+    active_profile: ActiveRouterProfile,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(SAMPLE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let current = crate::config::read(&config).clone();
+            let Some(client) = get_vyos_client_from_db(&db, &current, &active_profile).await else {
+                continue;
+            };
+            let firewall = match client.retrieve(&["firewall"]).await {
+                Ok(data) => parse_firewall_config(&data),
+                Err(e) => {
+                    warn!("Firewall counter sampling: VyOS config query failed: {e}");
+                    continue;
+                }
+            };
+
+            for chain in &firewall.chains {
+                let [version, direction, filter] = chain.path.as_slice() else {
+                    continue;
+                };
+                let path = ["firewall", version, direction, filter];
+                let text = match client.show(&path).await {
+                    Ok(value) => value.as_str().unwrap_or("").to_string(),
+                    Err(e) => {
+                        warn!(chain = %chain.path.join("."), "Firewall counter query failed: {e}");
+                        continue;
+                    }
+                };
+                let counters = parse_firewall_rule_counters(&text);
+                if let Err(e) = record_samples(&db, &chain.path.join("."), &counters).await {
+                    error!("Failed to store firewall rule counters: {e}");
+                }
+            }
+
+            if let Err(e) =
+                sqlx::query("DELETE FROM firewall_rule_stats WHERE sampled_at < datetime('now', ?)")
+                    .bind(format!("-{RETENTION_DAYS} days"))
+                    .execute(&db)
+                    .await
+            {
+                error!("Failed to prune firewall rule counters: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counters(rules: &[(u32, u64)]) -> Vec<FirewallRuleCounters> {
+        rules
+            .iter()
+            .map(|&(rule_number, packets)| FirewallRuleCounters {
+                rule_number,
+                packet_count: packets,
+                byte_count: packets * 100,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_record_samples_tracks_deltas() {
+        let db = crate::db::init(":memory:").await.unwrap();
+        let chain = "ipv4.forward.filter";
+
+        record_samples(&db, chain, &counters(&[(10, 1000), (20, 50)]))
+            .await
+            .unwrap();
+        record_samples(&db, chain, &counters(&[(10, 1500), (20, 80)]))
+            .await
+            .unwrap();
+        // Rule 20 was recreated: its counter restarted from zero.
+        record_samples(&db, chain, &counters(&[(10, 1600), (20, 5)]))
+            .await
+            .unwrap();
+
+        let deltas: Vec<(i64, i64)> = sqlx::query_as(
+            "SELECT rule_number, SUM(packets - prev_packets) FROM firewall_rule_stats \
+             GROUP BY rule_number ORDER BY rule_number",
+        )
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(deltas, vec![(10, 600), (20, 35)]);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod client;
+pub mod config_archive;
+pub mod dhcp_expiry;
+pub mod firewall_stats;
 pub mod speedtest_ookla;
+pub mod speedtest_schedule;
@@ -0,0 +1,89 @@
+//! Scheduled WAN speed tests.
+//!
+//! When `speedtest_interval_hours` is configured, runs the Ookla Speedtest
+//! CLI on that interval, stores the result, refreshes the cached latest
+//! result and broadcasts a `speedtest_complete` WebSocket event.
+
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::api::vyos::{run_speedtest, SpeedTestResult};
+use crate::config::AppConfig;
+use crate::ws::hub::WsHub;
+
+/// Whether a result's download speed is below the warning threshold.
+pub fn below_threshold(result: &SpeedTestResult, threshold_mbps: Option<f64>) -> bool {
+    threshold_mbps.is_some_and(|t| result.download_mbps < t)
+}
+
+/// Start the speed test scheduler. Does nothing unless
+/// `speedtest_interval_hours` is set to a non-zero value.
+pub fn start_speedtest_task(
+    db: SqlitePool,
+    last_speedtest: Arc<Mutex<Option<SpeedTestResult>>>,
+    ws_hub: Arc<WsHub>,
+    config: &AppConfig,
+) {
+    let Some(hours) = config.speedtest_interval_hours.filter(|h| *h > 0) else {
+        return;
+    };
+    let threshold = config.speedtest_warn_threshold_mbps;
+    info!(interval_hours = hours, "Scheduled speed tests enabled");
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(hours * 3600));
+        interval.tick().await; // skip the immediate first tick
+        loop {
+            interval.tick().await;
+            let result = match run_speedtest(&db).await {
+                Ok(result) => result,
+                Err(e) => {
+                    error!("Scheduled speed test failed: {e}");
+                    continue;
+                }
+            };
+
+            if below_threshold(&result, threshold) {
+                warn!(
+                    download_mbps = result.download_mbps,
+                    threshold_mbps = threshold,
+                    "Scheduled speed test download below threshold"
+                );
+            }
+
+            *last_speedtest.lock().await = Some(result.clone());
+            ws_hub.broadcast(
+                "speedtest_complete",
+                serde_json::to_value(&result).unwrap_or_default(),
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_below_threshold() {
+        let result = SpeedTestResult {
+            download_mbps: 80.0,
+            upload_mbps: 20.0,
+            ping_ms: 5.0,
+            jitter_ms: 0.5,
+            packet_loss: 0.0,
+            isp: "Test ISP".to_string(),
+            server: "Test Server".to_string(),
+            result_url: None,
+            tested_at: Utc::now(),
+            error: None,
+        };
+        assert!(below_threshold(&result, Some(100.0)));
+        assert!(!below_threshold(&result, Some(50.0)));
+        assert!(!below_threshold(&result, None));
+    }
+}
@@ -1,9 +1,52 @@
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
 use serde_json::Value;
+use sha2::Sha256;
 use sqlx::SqlitePool;
+use std::fmt;
 use std::time::Duration;
-use tracing::warn;
+use tracing::{info, warn};
 
-/// Read the webhook_url from the settings table. Returns `None` if not set or empty.
+/// Header carrying `sha256=<hex HMAC of the body>` for webhooks with a secret.
+pub const SIGNATURE_HEADER: &str = "X-Panoptikon-Signature";
+
+/// Event filter value matching every event type.
+pub const ALL_EVENTS: &str = "*";
+
+/// Delay before each retry of a failed delivery, indexed by the number of
+/// attempts made so far minus one. After the last retry fails the delivery
+/// is marked `failed`.
+pub const RETRY_BACKOFF_SECS: [i64; 5] = [60, 300, 1800, 7200, 86400];
+
+/// How often the retry task looks for due deliveries.
+const RETRY_INTERVAL_SECS: u64 = 60;
+
+/// A destination for one webhook delivery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookTarget {
+    /// `None` for the legacy settings URL, whose failures are not retried.
+    pub webhook_id: Option<String>,
+    pub url: String,
+    pub secret: Option<String>,
+}
+
+/// Why a webhook POST failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryError {
+    /// HTTP status of a non-2xx response; `None` for timeouts and connection errors.
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Read the legacy webhook_url from the settings table. Returns `None` if not set or empty.
+///
+/// Deprecated in favour of the `webhooks` table; still receives every event.
 pub async fn get_webhook_url(db: &SqlitePool) -> Option<String> {
     let row: Option<(String,)> =
         sqlx::query_as(r#"SELECT value FROM settings WHERE key = 'webhook_url'"#)
@@ -14,51 +57,423 @@ pub async fn get_webhook_url(db: &SqlitePool) -> Option<String> {
     row.and_then(|(v,)| if v.is_empty() { None } else { Some(v) })
 }
 
-/// POST a JSON payload to the given webhook URL.
+/// Whether a comma-separated `events` filter includes `event`.
+pub fn event_matches(events: &str, event: &str) -> bool {
+    events
+        .split(',')
+        .map(str::trim)
+        .any(|e| e == ALL_EVENTS || e == event)
+}
+
+/// Enabled webhooks subscribed to `event`, plus the legacy settings URL.
+pub async fn webhook_targets(db: &SqlitePool, event: &str) -> Vec<WebhookTarget> {
+    let rows: Vec<(String, String, String, Option<String>)> =
+        sqlx::query_as("SELECT id, url, events, secret FROM webhooks WHERE enabled = 1")
+            .fetch_all(db)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to load webhooks");
+                Vec::new()
+            });
+
+    let mut targets: Vec<WebhookTarget> = rows
+        .into_iter()
+        .filter(|(_, _, events, _)| event_matches(events, event))
+        .map(|(id, url, _, secret)| WebhookTarget {
+            webhook_id: Some(id),
+            url,
+            secret: secret.filter(|s| !s.is_empty()),
+        })
+        .collect();
+    if let Some(url) = get_webhook_url(db).await {
+        targets.push(WebhookTarget {
+            webhook_id: None,
+            url,
+            secret: None,
+        });
+    }
+    targets
+}
+
+/// `sha256=<hex>` HMAC-SHA256 of `body` keyed with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    format!("sha256={hex}")
+}
+
+/// POST a JSON payload to the given webhook URL, signing it when `secret` is set.
+/// Returns the response status code on success.
 ///
-/// Times out after 5 seconds. Logs a warning on error but never panics.
-pub async fn send_webhook(url: &str, payload: Value) {
-    let client = match reqwest::Client::builder()
+/// Times out after 5 seconds. Logs a warning and returns the reason on error
+/// but never panics.
+pub async fn send_webhook(
+    url: &str,
+    secret: Option<&str>,
+    payload: &Value,
+) -> Result<u16, DeliveryError> {
+    let failed = |message: String| DeliveryError {
+        status: None,
+        message,
+    };
+    let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(5))
         .build()
-    {
-        Ok(c) => c,
-        Err(e) => {
+        .map_err(|e| {
             warn!(error = %e, "Failed to build reqwest client for webhook");
-            return;
-        }
-    };
+            failed(e.to_string())
+        })?;
 
-    match client.post(url).json(&payload).send().await {
+    let body = serde_json::to_vec(payload).map_err(|e| failed(e.to_string()))?;
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret {
+        request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+    }
+
+    match request.body(body).send().await {
+        Ok(resp) if resp.status().is_success() => Ok(resp.status().as_u16()),
         Ok(resp) => {
-            if !resp.status().is_success() {
-                warn!(
-                    url = %url,
-                    status = %resp.status(),
-                    "Webhook POST returned non-success status"
-                );
-            }
+            warn!(
+                url = %url,
+                status = %resp.status(),
+                "Webhook POST returned non-success status"
+            );
+            Err(DeliveryError {
+                status: Some(resp.status().as_u16()),
+                message: format!("webhook returned HTTP {}", resp.status()),
+            })
         }
         Err(e) => {
             warn!(url = %url, error = %e, "Webhook POST failed");
+            Err(failed(e.to_string()))
         }
     }
 }
 
-/// Check the settings table for a webhook URL and, if set, fire off a non-blocking
-/// POST with the given alert payload. This never blocks the caller.
+/// The JSON body sent for an event.
+pub fn webhook_payload(alert_type: &str, payload: Value) -> Value {
+    serde_json::json!({
+        "type": alert_type,
+        "data": payload,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Fire a non-blocking POST with the given alert payload to every enabled
+/// webhook subscribed to `alert_type`, concurrently. This never blocks the caller.
 pub fn dispatch_webhook(db: &SqlitePool, alert_type: &str, payload: Value) {
     let db = db.clone();
     let alert_type = alert_type.to_string();
 
     tokio::spawn(async move {
-        if let Some(url) = get_webhook_url(&db).await {
-            let webhook_payload = serde_json::json!({
-                "type": alert_type,
-                "data": payload,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
+        let targets = webhook_targets(&db, &alert_type).await;
+        if targets.is_empty() {
+            return;
+        }
+        let webhook_payload = webhook_payload(&alert_type, payload);
+
+        let mut deliveries = tokio::task::JoinSet::new();
+        for target in targets {
+            let db = db.clone();
+            let alert_type = alert_type.clone();
+            let body = webhook_payload.clone();
+            deliveries.spawn(async move {
+                // Failures are logged by send_webhook; named webhooks get retried.
+                let Err(e) = send_webhook(&target.url, target.secret.as_deref(), &body).await
+                else {
+                    return;
+                };
+                if let Some(webhook_id) = target.webhook_id {
+                    record_failed_delivery(&db, &webhook_id, &alert_type, &body, &e).await;
+                }
             });
-            send_webhook(&url, webhook_payload).await;
         }
+        while deliveries.join_next().await.is_some() {}
     });
 }
+
+/// Store a failed first delivery as `pending` so the retry task picks it up.
+async fn record_failed_delivery(
+    db: &SqlitePool,
+    webhook_id: &str,
+    event_type: &str,
+    payload: &Value,
+    error: &DeliveryError,
+) {
+    let result = sqlx::query(
+        r#"INSERT INTO webhook_deliveries
+               (id, webhook_id, event_type, payload_json, response_code, error)
+           VALUES (?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(webhook_id)
+    .bind(event_type)
+    .bind(payload.to_string())
+    .bind(error.status)
+    .bind(&error.message)
+    .execute(db)
+    .await;
+    if let Err(e) = result {
+        warn!(error = %e, webhook_id, "Failed to record webhook delivery");
+    }
+}
+
+/// Whether a pending delivery with `attempt_count` attempts, the last at
+/// `last_attempt_at`, is due for a retry at `now`.
+pub fn retry_due(attempt_count: i64, last_attempt_at: NaiveDateTime, now: NaiveDateTime) -> bool {
+    let Some(delay) = usize::try_from(attempt_count - 1)
+        .ok()
+        .and_then(|i| RETRY_BACKOFF_SECS.get(i))
+    else {
+        return false;
+    };
+    now >= last_attempt_at + chrono::Duration::seconds(*delay)
+}
+
+/// Retry every pending delivery that is due at `now`, returning how many
+/// were attempted. Deliveries for disabled webhooks wait until re-enabled.
+pub async fn retry_pending_deliveries(
+    db: &SqlitePool,
+    now: NaiveDateTime,
+) -> Result<usize, sqlx::Error> {
+    let pending: Vec<(String, String, i64, String, String, Option<String>)> = sqlx::query_as(
+        r#"SELECT d.id, d.payload_json, d.attempt_count, d.last_attempt_at, w.url, w.secret
+           FROM webhook_deliveries d
+           JOIN webhooks w ON w.id = d.webhook_id
+           WHERE d.status = 'pending' AND w.enabled = 1"#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut attempted = 0;
+    for (id, payload_json, attempt_count, last_attempt_at, url, secret) in pending {
+        let Ok(last_attempt_at) =
+            NaiveDateTime::parse_from_str(&last_attempt_at, "%Y-%m-%d %H:%M:%S")
+        else {
+            continue;
+        };
+        if !retry_due(attempt_count, last_attempt_at, now) {
+            continue;
+        }
+        let Ok(payload) = serde_json::from_str::<Value>(&payload_json) else {
+            continue;
+        };
+        attempted += 1;
+
+        let secret = secret.filter(|s| !s.is_empty());
+        let attempts = attempt_count + 1;
+        let (status, response_code, error) =
+            match send_webhook(&url, secret.as_deref(), &payload).await {
+                Ok(code) => ("delivered", Some(code), None),
+                Err(e) if attempts > RETRY_BACKOFF_SECS.len() as i64 => {
+                    warn!(delivery_id = %id, attempts, "Webhook delivery failed permanently");
+                    ("failed", e.status, Some(e.message))
+                }
+                Err(e) => ("pending", e.status, Some(e.message)),
+            };
+        sqlx::query(
+            r#"UPDATE webhook_deliveries
+               SET attempt_count = ?, last_attempt_at = ?, status = ?,
+                   response_code = ?, error = ?
+               WHERE id = ?"#,
+        )
+        .bind(attempts)
+        .bind(now.format("%Y-%m-%d %H:%M:%S").to_string())
+        .bind(status)
+        .bind(response_code)
+        .bind(error)
+        .bind(&id)
+        .execute(db)
+        .await?;
+    }
+    Ok(attempted)
+}
+
+/// Spawn the background task retrying failed webhook deliveries every minute.
+pub fn start_retry_task(db: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(RETRY_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match retry_pending_deliveries(&db, chrono::Utc::now().naive_utc()).await {
+                Ok(0) => {}
+                Ok(n) => info!(retried = n, "Retried pending webhook deliveries"),
+                Err(e) => warn!(error = %e, "Webhook delivery retry failed"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_matches() {
+        assert!(event_matches(
+            "new_device, device_offline",
+            "device_offline"
+        ));
+        assert!(!event_matches("new_device", "device_offline"));
+        assert!(event_matches("*", "agent_offline"));
+        assert!(!event_matches("", "agent_offline"));
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_targets() {
+        let db = crate::db::init(":memory:").await.unwrap();
+        for (id, url, events, enabled, secret) in [
+            ("1", "http://sec", "new_device", 1, Some("s3cret")),
+            ("2", "http://ops", "device_offline,agent_offline", 1, None),
+            ("3", "http://all", "*", 1, Some("")),
+            ("4", "http://off", "*", 0, None),
+        ] {
+            sqlx::query(
+                "INSERT INTO webhooks (id, name, url, events, enabled, secret) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(url)
+            .bind(url)
+            .bind(events)
+            .bind(enabled)
+            .bind(secret)
+            .execute(&db)
+            .await
+            .unwrap();
+        }
+
+        let mut urls: Vec<String> = webhook_targets(&db, "new_device")
+            .await
+            .into_iter()
+            .map(|t| t.url)
+            .collect();
+        urls.sort();
+        assert_eq!(urls, vec!["http://all", "http://sec"]);
+
+        let targets = webhook_targets(&db, "device_offline").await;
+        assert_eq!(targets.len(), 2);
+        assert!(targets.iter().all(|t| t.secret.is_none()));
+
+        // The legacy settings URL receives everything.
+        sqlx::query("INSERT INTO settings (key, value) VALUES ('webhook_url', 'http://legacy')")
+            .execute(&db)
+            .await
+            .unwrap();
+        let targets = webhook_targets(&db, "dhcp_lease_expiring").await;
+        assert!(targets.contains(&WebhookTarget {
+            webhook_id: None,
+            url: "http://legacy".to_string(),
+            secret: None,
+        }));
+    }
+
+    #[test]
+    fn test_retry_due() {
+        let last =
+            NaiveDateTime::parse_from_str("2026-03-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let after = |secs| last + chrono::Duration::seconds(secs);
+        assert!(!retry_due(1, last, after(59)));
+        assert!(retry_due(1, last, after(60)));
+        assert!(!retry_due(2, last, after(299)));
+        assert!(retry_due(2, last, after(300)));
+        assert!(retry_due(5, last, after(86400)));
+        assert!(!retry_due(6, last, after(10 * 86400)));
+        assert!(!retry_due(0, last, after(60)));
+    }
+
+    async fn insert_webhook(db: &SqlitePool, id: &str, url: &str) {
+        sqlx::query("INSERT INTO webhooks (id, name, url, events) VALUES (?, ?, ?, '*')")
+            .bind(id)
+            .bind(id)
+            .bind(url)
+            .execute(db)
+            .await
+            .unwrap();
+    }
+
+    async fn delivery_state(db: &SqlitePool, webhook_id: &str) -> (i64, String, String) {
+        sqlx::query_as(
+            "SELECT attempt_count, status, last_attempt_at FROM webhook_deliveries \
+             WHERE webhook_id = ?",
+        )
+        .bind(webhook_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retry_backoff_until_failed_or_delivered() {
+        let db = crate::db::init(":memory:").await.unwrap();
+        let payload = webhook_payload("new_device", serde_json::json!({"mac": "aa"}));
+        let refused = DeliveryError {
+            status: None,
+            message: "connection refused".to_string(),
+        };
+
+        // An address nothing listens on.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dead_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        drop(listener);
+        insert_webhook(&db, "dead", &dead_url).await;
+        record_failed_delivery(&db, "dead", "new_device", &payload, &refused).await;
+
+        let (_, _, last) = delivery_state(&db, "dead").await;
+        let mut last = NaiveDateTime::parse_from_str(&last, "%Y-%m-%d %H:%M:%S").unwrap();
+        let early = last + chrono::Duration::seconds(59);
+        assert_eq!(retry_pending_deliveries(&db, early).await.unwrap(), 0);
+
+        for (i, delay) in RETRY_BACKOFF_SECS.iter().enumerate() {
+            last += chrono::Duration::seconds(*delay);
+            assert_eq!(retry_pending_deliveries(&db, last).await.unwrap(), 1);
+            let (attempts, status, _) = delivery_state(&db, "dead").await;
+            assert_eq!(attempts, i as i64 + 2);
+            let expected = if i + 1 == RETRY_BACKOFF_SECS.len() {
+                "failed"
+            } else {
+                "pending"
+            };
+            assert_eq!(status, expected);
+        }
+        let much_later = last + chrono::Duration::days(30);
+        assert_eq!(retry_pending_deliveries(&db, much_later).await.unwrap(), 0);
+
+        // A receiver that has recovered gets the stored payload.
+        let app = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(|| async { axum::http::StatusCode::NO_CONTENT }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        insert_webhook(&db, "live", &live_url).await;
+        record_failed_delivery(&db, "live", "new_device", &payload, &refused).await;
+
+        let retry_at = chrono::Utc::now().naive_utc() + chrono::Duration::seconds(61);
+        assert_eq!(retry_pending_deliveries(&db, retry_at).await.unwrap(), 1);
+        let (attempts, status, _) = delivery_state(&db, "live").await;
+        assert_eq!((attempts, status.as_str()), (2, "delivered"));
+        let code: Option<i64> = sqlx::query_scalar(
+            "SELECT response_code FROM webhook_deliveries WHERE webhook_id = 'live'",
+        )
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(code, Some(204));
+    }
+}
@@ -0,0 +1,231 @@
+//! ASN lookup for external IPs via the Team Cymru IP-to-ASN DNS service.
+//!
+//! `<reversed ip>.origin.asn.cymru.com` (`origin6` for IPv6, one label per
+//! nibble) has a TXT record such as
+//! `"15169 | 8.8.8.0/24 | US | arin | 2023-12-28"`. Results are cached for a
+//! day since allocations rarely change, and lookups are limited to 10 per
+//! second so a top-talkers page cannot flood the resolver.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use hickory_resolver::TokioAsyncResolver;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::debug;
+
+/// How long a lookup result is served from the cache.
+pub const ASN_CACHE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Minimum spacing between DNS queries (10 per second).
+const LOOKUP_MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Budget for one TXT query.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Origin AS of an IP as published by Team Cymru.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AsnInfo {
+    pub asn: u32,
+    pub bgp_prefix: String,
+    /// ISO country code of the allocation.
+    pub cc: String,
+    /// Regional registry (`arin`, `ripencc`, …).
+    pub registry: String,
+    /// Allocation date (`YYYY-MM-DD`), when published.
+    pub allocated: Option<String>,
+}
+
+/// Cached lookups keyed by IP, with the instant each was fetched.
+pub type AsnCache = Arc<Mutex<HashMap<IpAddr, (Instant, AsnInfo)>>>;
+
+/// Whether `ip` is publicly routed, i.e. worth an ASN lookup.
+pub fn is_external(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_unspecified()
+                || v4.is_documentation()
+                // Carrier-grade NAT, 100.64.0.0/10.
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10.
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// The Cymru origin query name for `ip`.
+pub fn origin_query_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{d}.{c}.{b}.{a}.origin.asn.cymru.com.")
+        }
+        IpAddr::V6(v6) => {
+            let nibbles: Vec<String> = v6
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|byte| [byte & 0x0f, byte >> 4])
+                .map(|nibble| format!("{nibble:x}"))
+                .collect();
+            format!("{}.origin6.asn.cymru.com.", nibbles.join("."))
+        }
+    }
+}
+
+/// Parse an origin TXT record. When several ASNs announce the prefix
+/// (`"13335 209242 | …"`), the first is used.
+pub fn parse_origin_txt(txt: &str) -> Option<AsnInfo> {
+    let fields: Vec<&str> = txt.trim_matches('"').split('|').map(str::trim).collect();
+    let [asns, prefix, cc, registry, rest @ ..] = fields.as_slice() else {
+        return None;
+    };
+    Some(AsnInfo {
+        asn: asns.split_whitespace().next()?.parse().ok()?,
+        bgp_prefix: prefix.to_string(),
+        cc: cc.to_string(),
+        registry: registry.to_string(),
+        allocated: rest
+            .first()
+            .filter(|date| !date.is_empty())
+            .map(|date| date.to_string()),
+    })
+}
+
+/// Shared resolver, built from the system configuration on first use.
+fn resolver() -> Option<&'static TokioAsyncResolver> {
+    static RESOLVER: OnceLock<Option<TokioAsyncResolver>> = OnceLock::new();
+    RESOLVER
+        .get_or_init(|| match TokioAsyncResolver::tokio_from_system_conf() {
+            Ok(resolver) => Some(resolver),
+            Err(e) => {
+                debug!(error = %e, "Cannot create DNS resolver for ASN lookups");
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Wait for the next lookup slot.
+async fn rate_limit() {
+    static LIMITER: Mutex<Option<Interval>> = Mutex::const_new(None);
+    let mut interval = LIMITER.lock().await;
+    let interval = interval.get_or_insert_with(|| {
+        let mut interval = tokio::time::interval(LOOKUP_MIN_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    });
+    interval.tick().await;
+}
+
+/// Look up the origin AS of `ip`. Returns `None` for non-public addresses and
+/// when the lookup fails.
+pub async fn lookup_asn(ip: IpAddr) -> Option<AsnInfo> {
+    if !is_external(ip) {
+        return None;
+    }
+    let resolver = resolver()?;
+    rate_limit().await;
+
+    let name = origin_query_name(ip);
+    let lookup =
+        match tokio::time::timeout(LOOKUP_TIMEOUT, resolver.txt_lookup(name.as_str())).await {
+            Ok(Ok(lookup)) => lookup,
+            Ok(Err(e)) => {
+                debug!(%ip, error = %e, "ASN lookup failed");
+                return None;
+            }
+            Err(_) => {
+                debug!(%ip, "ASN lookup timed out");
+                return None;
+            }
+        };
+    lookup.iter().find_map(|txt| {
+        let text: String = txt
+            .iter()
+            .map(|part| String::from_utf8_lossy(part))
+            .collect();
+        parse_origin_txt(&text)
+    })
+}
+
+/// [`lookup_asn`] through `cache`; failed lookups are not cached.
+pub async fn cached_lookup(cache: &AsnCache, ip: IpAddr) -> Option<AsnInfo> {
+    if let Some((fetched_at, info)) = cache.lock().await.get(&ip) {
+        if fetched_at.elapsed() < ASN_CACHE_TTL {
+            return Some(info.clone());
+        }
+    }
+    let info = lookup_asn(ip).await?;
+    let mut cache = cache.lock().await;
+    cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ASN_CACHE_TTL);
+    cache.insert(ip, (Instant::now(), info.clone()));
+    Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_origin_txt() {
+        assert_eq!(
+            parse_origin_txt("\"15169 | 8.8.8.0/24 | US | arin | 2023-12-28\""),
+            Some(AsnInfo {
+                asn: 15169,
+                bgp_prefix: "8.8.8.0/24".to_string(),
+                cc: "US".to_string(),
+                registry: "arin".to_string(),
+                allocated: Some("2023-12-28".to_string()),
+            })
+        );
+        let multi = parse_origin_txt("13335 209242 | 1.1.1.0/24 | AU | apnic | ").unwrap();
+        assert_eq!(multi.asn, 13335);
+        assert_eq!(multi.allocated, None);
+        assert_eq!(parse_origin_txt("not a record"), None);
+    }
+
+    #[test]
+    fn test_origin_query_name() {
+        assert_eq!(
+            origin_query_name("8.8.4.1".parse().unwrap()),
+            "1.4.8.8.origin.asn.cymru.com."
+        );
+        let v6 = origin_query_name("2001:db8::1".parse().unwrap());
+        assert!(v6.starts_with("1.0.0.0.0.0.0.0."));
+        assert!(v6.ends_with(".8.b.d.0.1.0.0.2.origin6.asn.cymru.com."));
+    }
+
+    #[tokio::test]
+    async fn test_private_ips_are_not_looked_up() {
+        for ip in [
+            "10.0.0.1",
+            "192.168.1.5",
+            "100.64.1.1",
+            "fe80::1",
+            "fd00::1",
+        ] {
+            let ip: IpAddr = ip.parse().unwrap();
+            assert!(!is_external(ip));
+            assert_eq!(lookup_asn(ip).await, None);
+        }
+        assert!(is_external("1.1.1.1".parse().unwrap()));
+        assert!(is_external("2606:4700::1111".parse().unwrap()));
+    }
+}
@@ -260,12 +260,17 @@ async fn test_setup_with_vyos_settings() {
             .expect("query failed");
     assert_eq!(vyos_url.as_deref(), Some("https://192.168.1.1"));
 
-    let vyos_key: Option<String> =
+    // The API key is stored encrypted.
+    let vyos_key: String =
         sqlx::query_scalar("SELECT value FROM settings WHERE key = 'vyos_api_key'")
-            .fetch_optional(&pool)
+            .fetch_one(&pool)
             .await
             .expect("query failed");
-    assert_eq!(vyos_key.as_deref(), Some("secret_key_123"));
+    assert!(!vyos_key.contains("secret_key_123"));
+    assert_eq!(
+        panoptikon_server::crypto::decrypt(&vyos_key).as_deref(),
+        Some("secret_key_123")
+    );
 
     // Verify setup_complete was set.
     let setup_complete: Option<String> =
@@ -444,3 +449,451 @@ async fn test_connect_info_configured() {
         }
     }
 }
+
+// ── Test 13: Bulk device operations roll back on partial failure ────
+
+#[tokio::test]
+async fn test_bulk_devices_all_or_nothing() {
+    let (base_url, pool) = spawn_test_server().await;
+    let client = http_client();
+    client
+        .post(format!("{base_url}/api/v1/setup"))
+        .json(&serde_json::json!({"password": "testpassword123"}))
+        .send()
+        .await
+        .expect("setup request failed");
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for (id, mac) in [
+        ("dev-1", "aa:bb:cc:00:00:01"),
+        ("dev-2", "aa:bb:cc:00:00:02"),
+    ] {
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(mac)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let bulk = |body: Value| {
+        client
+            .post(format!("{base_url}/api/v1/devices/bulk"))
+            .json(&body)
+            .send()
+    };
+    let tag_count = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM device_tags")
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+
+    // One unknown ID: nothing is tagged and the unknown ID is reported.
+    let resp = bulk(serde_json::json!({
+        "device_ids": ["dev-1", "missing", "dev-2"],
+        "operation": "tag",
+        "value": "iot",
+    }))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["succeeded"], 0);
+    assert_eq!(body["failed"], serde_json::json!(["missing"]));
+    assert_eq!(tag_count().await, 0);
+
+    let resp = bulk(serde_json::json!({
+        "device_ids": ["dev-1", "dev-2"],
+        "operation": "tag",
+        "value": "iot",
+    }))
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["succeeded"], 2);
+    assert_eq!(tag_count().await, 2);
+
+    // Soft-deleted devices disappear from the device list.
+    let resp = bulk(serde_json::json!({"device_ids": ["dev-1"], "operation": "delete"}))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let devices: Value = client
+        .get(format!("{base_url}/api/v1/devices"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ids: Vec<&str> = devices
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["dev-2"]);
+
+    // Requests over the size limit are rejected outright.
+    let too_many: Vec<String> = (0..=500).map(|i| format!("dev-{i}")).collect();
+    let resp = bulk(serde_json::json!({"device_ids": too_many, "operation": "unmute"}))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+// ── Test 14: Device scans are rate limited per device ───────────────
+
+#[tokio::test]
+async fn test_device_scan_rate_limited() {
+    let (base_url, _pool) = spawn_test_server().await;
+    let client = http_client();
+    client
+        .post(format!("{base_url}/api/v1/setup"))
+        .json(&serde_json::json!({"password": "testpassword123"}))
+        .send()
+        .await
+        .expect("setup request failed");
+
+    // The first request reaches the handler (unknown device → 404).
+    let resp = client
+        .post(format!("{base_url}/api/v1/devices/missing/scan"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // The second one within the window is rejected before the handler runs.
+    let resp = client
+        .post(format!("{base_url}/api/v1/devices/missing/scan"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("retry-after"));
+
+    // Other devices have their own budget.
+    let resp = client
+        .post(format!("{base_url}/api/v1/devices/other/scan"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+// ── Test 15: API keys authenticate headless clients by scope ────────
+
+#[tokio::test]
+async fn test_api_key_bearer_auth() {
+    let (base_url, _pool) = spawn_test_server().await;
+    let client = http_client();
+    client
+        .post(format!("{base_url}/api/v1/setup"))
+        .json(&serde_json::json!({"password": "testpassword123"}))
+        .send()
+        .await
+        .expect("setup request failed");
+
+    let resp = client
+        .post(format!("{base_url}/api/v1/api-keys"))
+        .json(&serde_json::json!({"name": "monitoring", "scopes": ["read"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: Value = resp.json().await.unwrap();
+    let key = created["key"].as_str().unwrap().to_string();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    // A cookie-less client authenticates with the key.
+    let headless = reqwest::Client::new();
+    let resp = headless
+        .get(format!("{base_url}/api/v1/devices"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Read-only keys cannot write or manage keys.
+    let resp = headless
+        .post(format!("{base_url}/api/v1/devices/bulk"))
+        .bearer_auth(&key)
+        .json(&serde_json::json!({"device_ids": [], "operation": "mute"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = headless
+        .get(format!("{base_url}/api/v1/api-keys"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Listing never exposes the key or its hash.
+    let keys: Value = client
+        .get(format!("{base_url}/api/v1/api-keys"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(keys[0]["id"], id.as_str());
+    assert!(keys[0].get("key").is_none());
+    assert!(keys[0].get("key_hash").is_none());
+
+    // Revoked keys stop working.
+    let resp = client
+        .delete(format!("{base_url}/api/v1/api-keys/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    let resp = headless
+        .get(format!("{base_url}/api/v1/devices"))
+        .bearer_auth(&key)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+// ── Test 16: Viewer accounts are read-only ──────────────────────────
+
+#[tokio::test]
+async fn test_viewer_role_is_read_only() {
+    let (base_url, _pool) = spawn_test_server().await;
+    let admin = http_client();
+    admin
+        .post(format!("{base_url}/api/v1/setup"))
+        .json(&serde_json::json!({"password": "testpassword123"}))
+        .send()
+        .await
+        .expect("setup request failed");
+
+    let me: Value = admin
+        .get(format!("{base_url}/api/v1/auth/me"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        me,
+        serde_json::json!({"username": "admin", "role": "admin"})
+    );
+
+    let resp = admin
+        .post(format!("{base_url}/api/v1/users"))
+        .json(&serde_json::json!({"username": "noc", "password": "viewerpass"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let viewer = http_client();
+    let resp = viewer
+        .post(format!("{base_url}/api/v1/auth/login"))
+        .json(&serde_json::json!({"username": "noc", "password": "wrongpass"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = viewer
+        .post(format!("{base_url}/api/v1/auth/login"))
+        .json(&serde_json::json!({"username": "noc", "password": "viewerpass"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let me: Value = viewer
+        .get(format!("{base_url}/api/v1/auth/me"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me, serde_json::json!({"username": "noc", "role": "viewer"}));
+
+    let resp = viewer
+        .get(format!("{base_url}/api/v1/devices"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for path in ["/api/v1/devices/bulk", "/api/v1/users"] {
+        let resp = viewer
+            .post(format!("{base_url}{path}"))
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{path}");
+    }
+
+    // Rejected writes are audited with the viewer as the actor.
+    let log: Value = admin
+        .get(format!("{base_url}/api/v1/audit-log?action=api_request"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = log["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["path"] == "/users" && e["actor"] == "noc")
+        .expect("viewer's POST /users should be audited");
+    assert_eq!(entry["method"], "POST");
+    assert_eq!(entry["status_code"], 403);
+    assert_eq!(entry["success"], false);
+    assert_eq!(entry["ip_address"], "127.0.0.1");
+
+    // The admin's own account creation is audited too, without the password.
+    let created = log["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["path"] == "/users" && e["actor"] == "admin")
+        .expect("admin's POST /users should be audited");
+    assert_eq!(created["status_code"], 201);
+    let summary = created["request_body_summary"].as_str().unwrap();
+    assert!(summary.contains("\"noc\""));
+    assert!(!summary.contains("viewerpass"));
+
+    let resp = viewer
+        .post(format!("{base_url}/api/v1/auth/logout"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+}
+
+// ── Test 17: Large responses are compressed, WebSockets are not ─────
+
+#[tokio::test]
+async fn test_response_compression() {
+    let (base_url, pool) = spawn_test_server().await;
+    let client = http_client();
+    let resp = client
+        .post(format!("{base_url}/api/v1/setup"))
+        .json(&serde_json::json!({"password": "testpassword123"}))
+        .send()
+        .await
+        .expect("setup request failed");
+    let session_cookie = resp
+        .headers()
+        .get(reqwest::header::SET_COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .expect("setup sets a session cookie")
+        .to_string();
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for i in 0..200 {
+        sqlx::query(
+            "INSERT INTO devices (id, mac, first_seen_at, last_seen_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(format!("dev-{i}"))
+        .bind(format!("aa:bb:cc:00:{:02x}:{:02x}", i / 256, i % 256))
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    for encoding in ["gzip", "br"] {
+        let resp = client
+            .get(format!("{base_url}/api/v1/devices"))
+            .header(reqwest::header::ACCEPT_ENCODING, encoding)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()
+                .get(reqwest::header::CONTENT_ENCODING)
+                .and_then(|v| v.to_str().ok()),
+            Some(encoding)
+        );
+    }
+
+    // Clients that don't ask for compression get plain JSON.
+    let resp = client
+        .get(format!("{base_url}/api/v1/devices"))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .is_none());
+    let devices: Value = resp.json().await.unwrap();
+    assert_eq!(devices.as_array().map(Vec::len), Some(200));
+
+    // The WebSocket handshake completes even when the client accepts gzip.
+    let ws_url = format!("{}/api/v1/ws", base_url.replace("http://", "ws://"));
+    let mut request =
+        tokio_tungstenite::tungstenite::client::IntoClientRequest::into_client_request(ws_url)
+            .unwrap();
+    request
+        .headers_mut()
+        .insert("Cookie", session_cookie.parse().unwrap());
+    request
+        .headers_mut()
+        .insert("Accept-Encoding", "gzip, br".parse().unwrap());
+    let (_socket, response) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("WebSocket handshake failed");
+    assert_eq!(response.status(), 101);
+    assert!(response.headers().get("content-encoding").is_none());
+}
+
+// ── Test 18: Handler errors are RFC 7807 problem documents ──────────
+
+#[tokio::test]
+async fn test_handler_errors_are_problem_json() {
+    let (client, base_url) = setup_fresh("integration_test_pw").await;
+
+    let resp = client
+        .delete(format!("{base_url}/api/v1/alerts/missing"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        resp.headers()[reqwest::header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["type"],
+        "https://panoptikon.dev/errors/alert-not-found"
+    );
+    assert_eq!(body["instance"], "/api/v1/alerts/missing");
+
+    // No router is configured, so VyOS reads report 503 the same way.
+    let resp = client
+        .get(format!("{base_url}/api/v1/vyos/config-interfaces"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["code"], "service_unavailable");
+}
@@ -136,9 +136,21 @@ export default function AgentDetailContent() {
         </div>
         <p className="text-sm text-slate-500 mt-1">
           Last seen: {agent.last_report_at ? timeAgo(agent.last_report_at) : "Never"}
-          {agent.hostname && <> · {agent.hostname}</>}
+          {(agent.display_name ?? agent.hostname) && (
+            <> · {agent.display_name ?? agent.hostname}</>
+          )}
           {agent.os_name && <> · {agent.os_name} {agent.os_version ?? ""}</>}
         </p>
+        {agent.is_container && (
+          <p className="text-xs text-slate-500 mt-1">
+            Container: {agent.container_runtime ?? "unknown runtime"}
+            {agent.namespace && <> · namespace {agent.namespace}</>}
+            {agent.node_name && <> · node {agent.node_name}</>}
+            {agent.hostname && agent.hostname !== agent.display_name && (
+              <> · hostname {agent.hostname}</>
+            )}
+          </p>
+        )}
       </div>
 
       {/* Charts */}
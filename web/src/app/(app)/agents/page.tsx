@@ -249,7 +249,7 @@ export default function AgentsPage() {
                     )}
                   </TableCell>
                   <TableCell className="font-mono tabular-nums text-slate-400">
-                    {agent.hostname ?? "—"}
+                    {agent.display_name ?? agent.hostname ?? "—"}
                   </TableCell>
                   <TableCell className="text-slate-400">
                     {agent.os_name ? `${agent.os_name} ${agent.os_version ?? ""}` : "—"}
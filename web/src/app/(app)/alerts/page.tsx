@@ -53,7 +53,26 @@ function alertIcon(type: Alert["type"]) {
     case "agent_offline":
       return <Activity className="h-5 w-5 text-rose-400" />;
     case "high_bandwidth":
+    case "traffic_spike":
       return <AlertTriangle className="h-5 w-5 text-amber-400" />;
+    case "dhcp_lease_expiring":
+      return <AlertTriangle className="h-5 w-5 text-amber-400" />;
+    case "mac_conflict":
+      return <AlertTriangle className="h-5 w-5 text-amber-400" />;
+    case "arp_spoofing":
+      return <AlertTriangle className="h-5 w-5 text-rose-400" />;
+    case "ip_conflict":
+      return <AlertTriangle className="h-5 w-5 text-amber-400" />;
+    case "ip_change":
+      return <Activity className="h-5 w-5 text-sky-400" />;
+    case "new_port_open":
+      return <Shield className="h-5 w-5 text-amber-400" />;
+    case "port_closed":
+      return <Shield className="h-5 w-5 text-sky-400" />;
+    case "cert_expiring":
+      return <Shield className="h-5 w-5 text-amber-400" />;
+    case "cert_expired":
+      return <Shield className="h-5 w-5 text-rose-400" />;
     default:
       return <Shield className="h-5 w-5 text-slate-400" />;
   }
@@ -71,6 +90,26 @@ function alertTypeLabel(type: Alert["type"]): string {
       return "Agent Offline";
     case "high_bandwidth":
       return "High Bandwidth";
+    case "traffic_spike":
+      return "Traffic Spike";
+    case "dhcp_lease_expiring":
+      return "DHCP Lease Expiring";
+    case "mac_conflict":
+      return "MAC Conflict";
+    case "arp_spoofing":
+      return "ARP Spoofing";
+    case "ip_conflict":
+      return "Duplicate IP";
+    case "ip_change":
+      return "IP Changed";
+    case "new_port_open":
+      return "New Open Port";
+    case "port_closed":
+      return "Port Closed";
+    case "cert_expiring":
+      return "Certificate Expiring";
+    case "cert_expired":
+      return "Certificate Expired";
     default:
       return "Alert";
   }
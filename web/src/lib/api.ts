@@ -9,30 +9,52 @@ import type {
   AgentCreateResponse,
   AgentReport,
   Alert,
+  ApiKey,
+  ApiKeyScope,
+  AsnInfo,
   AuditLogListResponse,
   AuthStatus,
+  BackupInfo,
   ConfigBackup,
   ConfigBackupListResponse,
   ConfigDiffResponse,
+  CreatedApiKey,
+  CreatedWebhook,
+  CurrentUser,
   DashboardStats,
   DbSizeData,
   Device,
+  DhcpPoolStats,
   DhcpStaticMapping,
   FirewallConfig,
   FirewallRuleRequest,
   FirewallGroups,
+  LocalSubnet,
   LoginResponse,
+  MaintenanceWindow,
+  MaintenanceWindowInput,
   NetflowStatus,
+  OuiDbInfo,
+  OutdatedAgent,
+  RouterProfile,
   RouterStatus,
   SearchResponse,
   SettingsData,
   SpeedTestResult,
+  SyslogStatus,
+  TestLdapResult,
   TopDevice,
+  TotpSetup,
+  TrafficConversation,
   TrafficHistoryPoint,
+  User,
+  UserRole,
   VyosDhcpLease,
   VyosInterface,
   VyosRoute,
   VyosWriteResponse,
+  Webhook,
+  WebhookDelivery,
 } from "./types";
 
 const API_BASE = process.env.NEXT_PUBLIC_API_URL || "";
@@ -55,11 +77,12 @@ async function request<T>(path: string, init?: RequestInit): Promise<T> {
     throw new Error("Unauthorized");
   }
   if (!res.ok) {
-    // Try to extract server error message from JSON body
+    // Try to extract server error message from the problem+json body
     let detail = res.statusText;
     try {
       const body = await res.json();
-      if (body?.error) detail = body.error;
+      if (body?.detail) detail = body.detail;
+      else if (body?.error) detail = body.error;
     } catch {
       // body wasn't JSON — keep statusText
     }
@@ -152,6 +175,27 @@ export function muteDevice(id: string, hours: number): Promise<void> {
   return apiPost<void>(`/api/v1/devices/${id}/mute?hours=${hours}`);
 }
 
+export function fetchMaintenanceWindows(): Promise<MaintenanceWindow[]> {
+  return apiGet<MaintenanceWindow[]>("/api/v1/maintenance");
+}
+
+export function createMaintenanceWindow(
+  body: MaintenanceWindowInput
+): Promise<MaintenanceWindow> {
+  return apiPost<MaintenanceWindow>("/api/v1/maintenance", body);
+}
+
+export function updateMaintenanceWindow(
+  id: string,
+  body: MaintenanceWindowInput
+): Promise<MaintenanceWindow> {
+  return apiPatch<MaintenanceWindow>(`/api/v1/maintenance/${id}`, body);
+}
+
+export function deleteMaintenanceWindow(id: string): Promise<void> {
+  return apiDelete(`/api/v1/maintenance/${id}`);
+}
+
 // ─── Devices ────────────────────────────────────────────
 
 export function fetchDevices(): Promise<Device[]> {
@@ -182,8 +226,47 @@ export function fetchDeviceUptime(id: string, days = 7): Promise<UptimeStats> {
   return apiGet<UptimeStats>(`/api/v1/devices/${id}/uptime?days=${days}`);
 }
 
-export function wakeDevice(id: string): Promise<void> {
-  return apiPost<void>(`/api/v1/devices/${id}/wake`);
+export interface DeviceAvailability {
+  device_id: string;
+  window_days: number;
+  /** Null when the state log has no entries in the window. */
+  uptime_pct: number | null;
+  total_online_secs: number;
+  total_offline_secs: number;
+  transition_count: number;
+}
+
+export function fetchDeviceAvailability(id: string, windowDays = 30): Promise<DeviceAvailability> {
+  return apiGet<DeviceAvailability>(`/api/v1/devices/${id}/availability?window=${windowDays}d`);
+}
+
+export interface SlaReportPeriod {
+  period_start: string;
+  /** Exclusive: the start of the next period. */
+  period_end: string;
+  /** Null when nothing is known about the device's state in the period. */
+  uptime_pct: number | null;
+  uptime_secs: number;
+  downtime_secs: number;
+  outage_count: number;
+  longest_outage_secs: number;
+}
+
+export function fetchDeviceSlaReport(
+  id: string,
+  period: "weekly" | "monthly" = "weekly",
+  count = 4
+): Promise<SlaReportPeriod[]> {
+  return apiGet<SlaReportPeriod[]>(
+    `/api/v1/devices/${id}/sla-report?period=${period}&count=${count}`
+  );
+}
+
+export function wakeDevice(
+  id: string,
+  options?: { interface?: string; broadcast?: string },
+): Promise<void> {
+  return apiPost<void>(`/api/v1/devices/${id}/wake`, options);
 }
 
 export interface PortEntry {
@@ -208,6 +291,47 @@ export function fetchPortScan(id: string): Promise<PortScanResult> {
   return apiGet<PortScanResult>(`/api/v1/devices/${id}/scan`);
 }
 
+export interface TlsCertificate {
+  port: number;
+  subject_cn: string | null;
+  issuer_cn: string | null;
+  san_dns_names: string[];
+  not_before: string;
+  not_after: string;
+  is_self_signed: boolean;
+  last_checked: string;
+}
+
+export function fetchDeviceCertificates(id: string): Promise<TlsCertificate[]> {
+  return apiGet<TlsCertificate[]>(`/api/v1/devices/${id}/certificates`);
+}
+
+export interface ScanSchedule {
+  device_id: string;
+  interval_hours: number;
+  last_scan_at: string | null;
+  enabled: boolean;
+}
+
+export function fetchScanSchedule(id: string): Promise<ScanSchedule> {
+  return apiGet<ScanSchedule>(`/api/v1/devices/${id}/scan/schedule`);
+}
+
+export function setScanSchedule(
+  id: string,
+  intervalHours: number,
+  enabled = true
+): Promise<ScanSchedule> {
+  return apiPost<ScanSchedule>(`/api/v1/devices/${id}/scan/schedule`, {
+    interval_hours: intervalHours,
+    enabled,
+  });
+}
+
+export function deleteScanSchedule(id: string): Promise<void> {
+  return apiDelete(`/api/v1/devices/${id}/scan/schedule`);
+}
+
 export interface EnrichmentCorrection {
   os_family?: string;
   os_version?: string;
@@ -233,6 +357,10 @@ export function createAgent(name: string): Promise<AgentCreateResponse> {
   return apiPost<AgentCreateResponse>("/api/v1/agents", { name });
 }
 
+export function fetchOutdatedAgents(): Promise<OutdatedAgent[]> {
+  return apiGet<OutdatedAgent[]>("/api/v1/agents/outdated");
+}
+
 export function fetchAgent(id: string): Promise<Agent> {
   return apiGet<Agent>(`/api/v1/agents/${id}`);
 }
@@ -247,14 +375,153 @@ export function fetchTrafficHistory(minutes = 60): Promise<TrafficHistoryPoint[]
   return apiGet<TrafficHistoryPoint[]>(`/api/v1/traffic/history?minutes=${minutes}`);
 }
 
+export function fetchTrafficConversations(
+  window: "1h" | "24h" | "7d" = "1h",
+  limit = 20,
+): Promise<TrafficConversation[]> {
+  return apiGet<TrafficConversation[]>(
+    `/api/v1/traffic/conversations?window=${window}&limit=${limit}`,
+  );
+}
+
+export function fetchIpAsn(ip: string): Promise<AsnInfo> {
+  return apiGet<AsnInfo>(`/api/v1/traffic/enrich?ip=${encodeURIComponent(ip)}`);
+}
+
 // ─── Auth ───────────────────────────────────────────────
 
 export function fetchAuthStatus(): Promise<AuthStatus> {
   return apiGet<AuthStatus>("/api/v1/auth/status");
 }
 
-export function login(password: string): Promise<LoginResponse> {
-  return apiPost<LoginResponse>("/api/v1/auth/login", { password });
+export function login(
+  password: string,
+  totpCode?: string,
+  username?: string,
+): Promise<LoginResponse> {
+  return apiPost<LoginResponse>("/api/v1/auth/login", {
+    username,
+    password,
+    totp_code: totpCode,
+  });
+}
+
+export function fetchCurrentUser(): Promise<CurrentUser> {
+  return apiGet<CurrentUser>("/api/v1/auth/me");
+}
+
+export function fetchUsers(): Promise<User[]> {
+  return apiGet<User[]>("/api/v1/users");
+}
+
+export function createUser(body: {
+  username: string;
+  password: string;
+  role?: UserRole;
+}): Promise<User> {
+  return apiPost<User>("/api/v1/users", body);
+}
+
+export function setupTotp(): Promise<TotpSetup> {
+  return apiPost<TotpSetup>("/api/v1/auth/totp/setup");
+}
+
+export function verifyTotp(code: string): Promise<void> {
+  return apiPost<void>("/api/v1/auth/totp/verify", { code });
+}
+
+export function disableTotp(code: string): Promise<void> {
+  return apiPost<void>("/api/v1/auth/totp/disable", { code });
+}
+
+// ─── API keys ───────────────────────────────────────────
+
+export function fetchApiKeys(): Promise<ApiKey[]> {
+  return apiGet<ApiKey[]>("/api/v1/api-keys");
+}
+
+export function createApiKey(body: {
+  name: string;
+  scopes: ApiKeyScope[];
+  expires_in_days?: number;
+}): Promise<CreatedApiKey> {
+  return apiPost<CreatedApiKey>("/api/v1/api-keys", body);
+}
+
+export function deleteApiKey(id: string): Promise<void> {
+  return apiDelete(`/api/v1/api-keys/${id}`);
+}
+
+// ─── Router profiles ────────────────────────────────────
+
+export function fetchRouterProfiles(): Promise<RouterProfile[]> {
+  return apiGet<RouterProfile[]>("/api/v1/router-profiles");
+}
+
+export function createRouterProfile(body: {
+  name: string;
+  url: string;
+  api_key: string;
+}): Promise<RouterProfile> {
+  return apiPost<RouterProfile>("/api/v1/router-profiles", body);
+}
+
+export function updateRouterProfile(
+  id: string,
+  body: { name?: string; url?: string; api_key?: string },
+): Promise<RouterProfile> {
+  return apiPatch<RouterProfile>(`/api/v1/router-profiles/${id}`, body);
+}
+
+export function deleteRouterProfile(id: string): Promise<void> {
+  return apiDelete(`/api/v1/router-profiles/${id}`);
+}
+
+export function activateRouterProfile(id: string): Promise<RouterProfile> {
+  return apiPost<RouterProfile>(`/api/v1/router-profiles/${id}/activate`);
+}
+
+export function fetchWebhooks(): Promise<Webhook[]> {
+  return apiGet<Webhook[]>("/api/v1/webhooks");
+}
+
+export function createWebhook(body: {
+  name: string;
+  url: string;
+  events: string[];
+  enabled?: boolean;
+  secret?: string;
+}): Promise<CreatedWebhook> {
+  return apiPost<CreatedWebhook>("/api/v1/webhooks", body);
+}
+
+export function updateWebhook(
+  id: string,
+  body: {
+    name?: string;
+    url?: string;
+    events?: string[];
+    enabled?: boolean;
+    secret?: string;
+  },
+): Promise<Webhook> {
+  return apiPatch<Webhook>(`/api/v1/webhooks/${id}`, body);
+}
+
+export function deleteWebhook(id: string): Promise<void> {
+  return apiDelete(`/api/v1/webhooks/${id}`);
+}
+
+export function testWebhook(id: string): Promise<void> {
+  return apiPost<void>(`/api/v1/webhooks/${id}/test`);
+}
+
+export function fetchWebhookDeliveries(id: string): Promise<WebhookDelivery[]> {
+  return apiGet<WebhookDelivery[]>(`/api/v1/webhooks/${id}/deliveries`);
+}
+
+export function fetchLocalSubnets(): Promise<LocalSubnet[]> {
+  return apiGet<LocalSubnet[]>("/api/v1/scanner/local-subnets");
 }
 
 export function logout(): Promise<void> {
@@ -295,10 +562,23 @@ export function fetchRouterFirewall(): Promise<FirewallConfig> {
   return apiGet<FirewallConfig>("/api/v1/vyos/firewall");
 }
 
+/** Flush cached router reads so the next fetch queries VyOS. */
+export function invalidateRouterCache(): Promise<{ invalidated: number }> {
+  return apiPost<{ invalidated: number }>("/api/v1/vyos/cache/invalidate", {});
+}
+
 export function runSpeedTest(): Promise<SpeedTestResult> {
   return apiPost<SpeedTestResult>("/api/v1/router/speedtest");
 }
 
+export function fetchSpeedTestHistory(limit = 50): Promise<SpeedTestResult[]> {
+  return apiGet<SpeedTestResult[]>(`/api/v1/router/speedtest/history?limit=${limit}`);
+}
+
+export function fetchLatestSpeedTest(): Promise<SpeedTestResult> {
+  return apiGet<SpeedTestResult>("/api/v1/router/speedtest/latest");
+}
+
 // ─── Firewall Groups ─────────────────────────────────────
 
 export function fetchFirewallGroups(): Promise<FirewallGroups> {
@@ -413,6 +693,10 @@ export function toggleInterface(
   });
 }
 
+export function fetchDhcpPools(): Promise<DhcpPoolStats[]> {
+  return apiGet<DhcpPoolStats[]>("/api/v1/vyos/dhcp/pools");
+}
+
 export function fetchDhcpStaticMappings(): Promise<DhcpStaticMapping[]> {
   return apiGet<DhcpStaticMapping[]>("/api/v1/vyos/dhcp/static-mappings");
 }
@@ -427,6 +711,16 @@ export function createDhcpStaticMapping(body: {
   return apiPost<VyosWriteResponse>("/api/v1/vyos/dhcp/static-mappings", body);
 }
 
+export function makeDhcpLeaseStatic(
+  ip: string,
+  body: { network: string; subnet: string; name: string }
+): Promise<DhcpStaticMapping> {
+  return apiPost<DhcpStaticMapping>(
+    `/api/v1/vyos/dhcp-leases/${encodeURIComponent(ip)}/make-static`,
+    body
+  );
+}
+
 export function deleteDhcpStaticMapping(
   network: string,
   subnet: string,
@@ -505,6 +799,28 @@ export function toggleFirewallRule(
   );
 }
 
+export function moveFirewallRule(
+  chain: { path: string[] },
+  number: number,
+  newNumber: number
+): Promise<VyosWriteResponse> {
+  return apiPost<VyosWriteResponse>(
+    `/api/v1/vyos/firewall/${encodeURIComponent(chainPath(chain))}/rules/${number}/move`,
+    { new_number: newNumber }
+  );
+}
+
+export function cloneFirewallRule(
+  chain: { path: string[] },
+  number: number,
+  newNumber: number
+): Promise<VyosWriteResponse> {
+  return apiPost<VyosWriteResponse>(
+    `/api/v1/vyos/firewall/${encodeURIComponent(chainPath(chain))}/rules/${number}/clone`,
+    { new_number: newNumber }
+  );
+}
+
 // ─── NetFlow ────────────────────────────────────────────
 
 export function fetchNetflowStatus(): Promise<NetflowStatus> {
@@ -530,21 +846,45 @@ export function updateSettings(body: {
   scan_interval_seconds?: number;
   scan_subnets?: string;
   ping_sweep_enabled?: boolean;
+  alert_dedup_window_secs?: number;
+  mdns_ignore_service_types?: string[];
+  mdns_include_service_types?: string[];
   retention_traffic_hours?: number;
   retention_alerts_days?: number;
   retention_agent_reports_days?: number;
+  ldap_url?: string;
+  ldap_bind_dn?: string;
+  ldap_bind_password?: string;
+  ldap_user_base_dn?: string;
+  ldap_user_filter?: string;
 }): Promise<SettingsData> {
   return apiPatch<SettingsData>("/api/v1/settings", body);
 }
 
+export function testLdap(): Promise<TestLdapResult> {
+  return apiPost<TestLdapResult>("/api/v1/settings/test-ldap");
+}
+
 export function fetchDbSize(): Promise<DbSizeData> {
   return apiGet<DbSizeData>("/api/v1/settings/db-size");
 }
 
+export function fetchBackupInfo(): Promise<BackupInfo> {
+  return apiGet<BackupInfo>("/api/v1/settings/backup/info");
+}
+
 export function triggerVacuum(): Promise<void> {
   return apiPost<void>("/api/v1/settings/vacuum");
 }
 
+export function fetchOuiDbInfo(): Promise<OuiDbInfo> {
+  return apiGet<OuiDbInfo>("/api/v1/settings/oui-db-info");
+}
+
+export function updateOuiDb(): Promise<OuiDbInfo> {
+  return apiPost<OuiDbInfo>("/api/v1/settings/update-oui-db");
+}
+
 // ─── Audit Log ──────────────────────────────────────────
 
 export function fetchAuditLog(
@@ -561,6 +901,10 @@ export function fetchAuditLogActions(): Promise<string[]> {
   return apiGet<string[]>("/api/v1/audit-log/actions");
 }
 
+export function fetchSyslogStatus(): Promise<SyslogStatus> {
+  return apiGet<SyslogStatus>("/api/v1/audit-log/syslog-status");
+}
+
 // ─── Topology Positions ──────────────────────────────────
 
 export interface NodePosition {
@@ -582,6 +926,17 @@ export function deleteTopologyPositions(): Promise<void> {
   return apiDelete("/api/v1/topology/positions");
 }
 
+/** Compute a force-directed layout server-side (not persisted). */
+export function fetchTopologyAutoLayout(): Promise<NodePosition[]> {
+  return apiPost<NodePosition[]>("/api/v1/topology/auto-layout");
+}
+
+/** URL of the server-rendered topology diagram (`json` for a D3 node-link graph). */
+export function topologyExportUrl(format: "svg" | "json" = "svg"): string {
+  const query = format === "json" ? "?format=json" : "";
+  return `${API_BASE}/api/v1/topology/export.svg${query}`;
+}
+
 // ─── Config Backups ─────────────────────────────────────
 
 export function fetchConfigBackups(
@@ -20,6 +20,7 @@ export interface Device {
   is_online: boolean;
   /** Current IP addresses — backend returns plain strings. */
   ips: string[];
+  tags: string[];
   /** mDNS/Bonjour discovered service types (comma-separated). */
   mdns_services?: string | null;
   agent?: AgentSummary | null;
@@ -35,10 +36,32 @@ export interface Device {
   device_model?: string | null;
   /** Device brand (e.g. "Apple", "Samsung"). */
   device_brand?: string | null;
+  /** Best OS guess from mDNS service types or nmap OS detection. */
+  os_hint?: string | null;
   /** Which enrichment source provided the identification. */
   enrichment_source?: string | null;
   /** Whether user has manually corrected the enrichment. */
   enrichment_corrected?: boolean | null;
+  /** UPnP device description learned from SSDP announcements. */
+  upnp_info?: UpnpInfo | null;
+  /** First 100 characters of the most recent note (single-device responses only). */
+  latest_note?: string | null;
+}
+
+export interface UpnpInfo {
+  type: "upnp";
+  friendly_name: string | null;
+  manufacturer: string | null;
+  model_name: string | null;
+}
+
+export interface DeviceNote {
+  id: string;
+  device_id: string;
+  author: string;
+  body: string;
+  created_at: string;
+  updated_at: string;
 }
 
 export interface AgentSummary {
@@ -51,6 +74,12 @@ export interface AgentSummary {
 
 // ─── Agents ─────────────────────────────────────────────
 
+export interface OutdatedAgent {
+  agent_id: string;
+  current_version: string;
+  latest_version: string;
+}
+
 export interface Agent {
   id: string;
   name: string | null;
@@ -62,6 +91,13 @@ export interface Agent {
   is_online: boolean;
   last_report_at: string | null;
   created_at: string;
+  is_container: boolean;
+  container_runtime: string | null;
+  pod_name: string | null;
+  namespace: string | null;
+  node_name: string | null;
+  /** Pod name for containerized agents, otherwise the hostname. */
+  display_name: string | null;
 }
 
 export interface AgentCreateResponse {
@@ -81,7 +117,7 @@ export interface AgentReport {
 
 export interface Alert {
   id: string;
-  type: "device_online" | "device_offline" | "new_device" | "high_bandwidth" | "agent_offline";
+  type: "device_online" | "device_offline" | "new_device" | "high_bandwidth" | "agent_offline" | "dhcp_lease_expiring" | "mac_conflict" | "arp_spoofing" | "ip_conflict" | "ip_change" | "traffic_spike" | "new_port_open" | "port_closed" | "cert_expiring" | "cert_expired";
   device_id: string | null;
   agent_id: string | null;
   message: string;
@@ -91,6 +127,28 @@ export interface Alert {
   acknowledged_at: string | null;
   acknowledged_by: string | null;
   created_at: string;
+  /** Number of occurrences folded into this alert by deduplication. */
+  count: number;
+  updated_at: string | null;
+}
+
+export interface MaintenanceWindow {
+  id: string;
+  name: string;
+  start_at: string;
+  end_at: string;
+  /** Covered devices; null covers every device. */
+  device_ids: string[] | null;
+  enabled: boolean;
+  created_at: string;
+}
+
+export interface MaintenanceWindowInput {
+  name?: string;
+  start_at?: string;
+  end_at?: string;
+  device_ids?: string[] | null;
+  enabled?: boolean;
 }
 
 // ─── Dashboard / Stats ──────────────────────────────────
@@ -103,6 +161,7 @@ export interface DashboardStats {
   alerts_unread: number;
   wan_rx_bps: number;
   wan_tx_bps: number;
+  latest_agent_version: string;
 }
 
 export interface TopDevice {
@@ -123,6 +182,29 @@ export interface TrafficHistoryPoint {
   tx_bps: number;
 }
 
+/** A (source, destination) NetFlow pair with totals over the window. */
+export interface TrafficConversation {
+  src_ip: string;
+  dst_ip: string;
+  src_device_id?: string;
+  dst_device_id?: string;
+  src_alias?: string;
+  dst_alias?: string;
+  bytes: number;
+  packets: number;
+  flow_count: number;
+  direction: "intra" | "inbound" | "outbound" | "transit";
+}
+
+/** Origin AS of a public IP (Team Cymru). */
+export interface AsnInfo {
+  asn: number;
+  bgp_prefix: string;
+  cc: string;
+  registry: string;
+  allocated: string | null;
+}
+
 // ─── NetFlow ────────────────────────────────────────────
 
 export interface NetflowStatus {
@@ -196,6 +278,16 @@ export interface DhcpStaticMapping {
   ip: string;
 }
 
+export interface DhcpPoolStats {
+  network: string;
+  subnet: string;
+  total_addresses: number;
+  active_leases: number;
+  static_mappings: number;
+  available: number;
+  utilization_pct: number;
+}
+
 export interface VyosWriteResponse {
   success: boolean;
   message: string;
@@ -283,16 +375,41 @@ export interface SettingsData {
   scan_interval_seconds: number | null;
   scan_subnets: string | null;
   ping_sweep_enabled: boolean | null;
+  alert_dedup_window_secs: number | null;
+  mdns_ignore_service_types: string[];
+  mdns_include_service_types: string[];
   // Data Retention
   retention_traffic_hours: number | null;
   retention_alerts_days: number | null;
   retention_agent_reports_days: number | null;
+  // LDAP Authentication
+  ldap_url: string | null;
+  ldap_bind_dn: string | null;
+  ldap_bind_password_set: boolean;
+  ldap_user_base_dn: string | null;
+  ldap_user_filter: string | null;
+  ldap_required: boolean;
+}
+
+export interface TestLdapResult {
+  users_found: number;
 }
 
 export interface DbSizeData {
   size_bytes: number;
 }
 
+export interface BackupInfo {
+  last_backup_at: string | null;
+  size_bytes: number;
+  migration_version: number;
+}
+
+export interface OuiDbInfo {
+  entries: number;
+  source: "embedded" | "file";
+}
+
 // ─── Search ─────────────────────────────────────────────
 
 export interface SearchDevice {
@@ -302,6 +419,7 @@ export interface SearchDevice {
   mac_address: string;
   vendor: string | null;
   is_online: boolean;
+  match_type: "exact" | "fuzzy";
 }
 
 export interface SearchAgent {
@@ -334,6 +452,12 @@ export interface AuditLogEntry {
   vyos_commands: string;
   success: boolean;
   error_msg: string | null;
+  actor: string | null;
+  method: string | null;
+  path: string | null;
+  status_code: number | null;
+  request_body_summary: string | null;
+  ip_address: string | null;
 }
 
 export interface AuditLogListResponse {
@@ -343,6 +467,18 @@ export interface AuditLogListResponse {
   per_page: number;
 }
 
+export interface SyslogFailure {
+  occurred_at: string;
+  action: string;
+  error: string;
+}
+
+export interface SyslogStatus {
+  enabled: boolean;
+  target: string | null;
+  failures: SyslogFailure[];
+}
+
 // ─── Config Backups ─────────────────────────────────────
 
 export interface ConfigBackupSummary {
@@ -374,8 +510,88 @@ export interface ConfigDiffResponse {
 export interface AuthStatus {
   authenticated: boolean;
   needs_setup: boolean;
+  totp_enabled: boolean;
 }
 
 export interface LoginResponse {
   message: string;
 }
+
+export type UserRole = "admin" | "viewer";
+
+export interface CurrentUser {
+  username: string;
+  role: UserRole;
+}
+
+export interface User extends CurrentUser {
+  id: string;
+  created_at: string;
+}
+
+export interface TotpSetup {
+  secret: string;
+  uri: string;
+}
+
+export type ApiKeyScope = "read" | "write" | "agent";
+
+export interface ApiKey {
+  id: string;
+  name: string;
+  scopes: ApiKeyScope[];
+  created_at: string;
+  expires_at: string | null;
+  last_used_at: string | null;
+}
+
+export interface CreatedApiKey extends ApiKey {
+  /** Plaintext key; only returned once, at creation. */
+  key: string;
+}
+
+export interface RouterProfile {
+  id: string;
+  name: string;
+  url: string;
+  is_active: boolean;
+  created_at: string;
+}
+
+export interface Webhook {
+  id: string;
+  name: string;
+  url: string;
+  /** Event types delivered to this webhook; `"*"` means all. */
+  events: string[];
+  enabled: boolean;
+  has_secret: boolean;
+  created_at: string;
+}
+
+/** Returned once on creation; `secret` is `null` for unsigned webhooks. */
+export interface CreatedWebhook extends Webhook {
+  secret: string | null;
+  signature: {
+    header: string;
+    algorithm: string;
+    format: string;
+  };
+}
+
+export interface WebhookDelivery {
+  id: string;
+  event_type: string;
+  payload: Record<string, unknown>;
+  attempt_count: number;
+  last_attempt_at: string;
+  status: "pending" | "delivered" | "failed";
+  response_code: number | null;
+  error: string | null;
+}
+
+/** A subnet the scanner would auto-detect from the host's interfaces. */
+export interface LocalSubnet {
+  interface: string;
+  subnet: string;
+}